@@ -7,6 +7,7 @@
 #ifndef WASM_TOOLS_H
 #define WASM_TOOLS_H
 
+#include <stddef.h>
 #include <stdint.h>
 
 #ifdef __cplusplus
@@ -47,6 +48,7 @@ enum wasm_tools_error {
 	WASM_TOOLS_SUCCESS              = 0,  ///< success
 	WASM_TOOLS_ERROR                = -1, ///< generic error
 	WASM_TOOLS_INSUFFICIENT_ENTROPY = -2, ///< wasm-smith seed too short
+	WASM_TOOLS_INVALID_FEATURES     = -3, ///< unknown name in a list of features
 };
 
 /**
@@ -54,6 +56,9 @@ enum wasm_tools_error {
  *
  * \param bytes this is the input pointer for the wasm_tools_byte_vec_t
  *   instance to be deleted
+ *
+ * Deleting an empty vector, whose `data` is NULL, does nothing, and the
+ * vector is left empty afterwards.
  */
 void wasm_tools_byte_vec_delete(struct wasm_tools_byte_vec_t* bytes);
 
@@ -67,10 +72,73 @@ void wasm_tools_byte_vec_delete(struct wasm_tools_byte_vec_t* bytes);
  * \return WASM_TOOLS_SUCCESS if generation is successful,
  *   WASM_TOOLS_INSUFFICIENT_ENTROPY if seed has too little entropy
  *
+ * `bytes` is set to an empty vector on entry, so it may always be deleted.
  * This function does not take ownership of `seed`
  */
 enum wasm_tools_error wasm_smith_create(const char* seed, size_t seed_len, struct wasm_tools_byte_vec_t* bytes);
 
+/**
+ * \brief Validates a wasm module or component.
+ *
+ * \param wasm the input pointer to the binary
+ * \param wasm_len the length of `wasm`, in bytes
+ * \param features a NUL-terminated, comma-separated list of WebAssembly
+ *   features to enable, as accepted by `wasm-tools validate --features`, or
+ *   NULL for the default features
+ * \param error wasm_tools_byte_vec_t instance where the UTF-8 error message
+ *   is written on failure, or NULL to discard it
+ *
+ * \return WASM_TOOLS_SUCCESS if the binary is valid,
+ *   WASM_TOOLS_INVALID_FEATURES if `features` names an unknown feature, or
+ *   WASM_TOOLS_ERROR if the binary is invalid
+ *
+ * The error message isn't NUL-terminated, and must be deleted with
+ * #wasm_tools_byte_vec_delete. It's set to an empty vector on entry, so it
+ * may always be deleted. This function does not take ownership of `wasm` or
+ * `features`.
+ */
+enum wasm_tools_error wasm_tools_validate(const uint8_t* wasm, size_t wasm_len, const char* features, struct wasm_tools_byte_vec_t* error);
+
+/**
+ * \brief Prints a wasm module or component in the text format.
+ *
+ * \param wasm the input pointer to the binary
+ * \param wasm_len the length of `wasm`, in bytes
+ * \param wat wasm_tools_byte_vec_t instance where the UTF-8 text is written
+ * \param error wasm_tools_byte_vec_t instance where the UTF-8 error message
+ *   is written on failure, or NULL to discard it
+ *
+ * \return WASM_TOOLS_SUCCESS if printing is successful, or WASM_TOOLS_ERROR
+ *   if the binary couldn't be parsed
+ *
+ * Neither output is NUL-terminated, and both must be deleted with
+ * #wasm_tools_byte_vec_delete. Both are set to empty vectors on entry, so they
+ * may always be deleted. This function does not take ownership of `wasm`.
+ */
+enum wasm_tools_error wasm_tools_print(const uint8_t* wasm, size_t wasm_len, struct wasm_tools_byte_vec_t* wat, struct wasm_tools_byte_vec_t* error);
+
+/**
+ * \brief Parses the text format into a wasm binary.
+ *
+ * \param wat the input pointer to the UTF-8 text
+ * \param wat_len the length of `wat`, in bytes
+ * \param features a NUL-terminated, comma-separated list of WebAssembly
+ *   features to enable, or NULL for every feature that the text format
+ *   supports
+ * \param wasm wasm_tools_byte_vec_t instance where the binary is written
+ * \param error wasm_tools_byte_vec_t instance where the UTF-8 error message
+ *   is written on failure, or NULL to discard it
+ *
+ * \return WASM_TOOLS_SUCCESS if parsing is successful,
+ *   WASM_TOOLS_INVALID_FEATURES if `features` names an unknown feature, or
+ *   WASM_TOOLS_ERROR if the text couldn't be parsed
+ *
+ * Both outputs must be deleted with #wasm_tools_byte_vec_delete. Both are set
+ * to empty vectors on entry, so they may always be deleted. This function
+ * does not take ownership of `wat` or `features`.
+ */
+enum wasm_tools_error wasm_tools_parse(const uint8_t* wat, size_t wat_len, const char* features, struct wasm_tools_byte_vec_t* wasm, struct wasm_tools_byte_vec_t* error);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif
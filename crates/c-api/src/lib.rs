@@ -7,7 +7,10 @@
 #![allow(non_snake_case, non_camel_case_types, non_upper_case_globals)]
 
 use arbitrary::{Error, Unstructured};
+use std::ffi::CStr;
+use std::os::raw::c_char;
 use wasm_smith::{DefaultConfig, Module};
+use wasmparser::{Validator, WasmFeatures};
 
 #[repr(C)]
 pub struct wasm_tools_byte_vec_t {
@@ -20,40 +23,333 @@ pub enum wasm_tools_error {
     WASM_TOOLS_SUCCESS = 0,
     WASM_TOOLS_ERROR = -1,
     WASM_TOOLS_INSUFFICIENT_ENTROPY = -2,
+    WASM_TOOLS_INVALID_FEATURES = -3,
 }
 use wasm_tools_error::*;
 
+impl wasm_tools_byte_vec_t {
+    fn set(&mut self, bytes: Vec<u8>) {
+        let mut buffer = bytes.into_boxed_slice();
+        self.data = buffer.as_mut_ptr();
+        self.size = buffer.len();
+        std::mem::forget(buffer);
+    }
+
+    fn clear(&mut self) {
+        self.data = std::ptr::null_mut();
+        self.size = 0;
+    }
+}
+
+/// Reads the `len` bytes at `data`, which may be NULL if `len` is zero.
+unsafe fn slice<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data, len)
+    }
+}
+
+/// Parses the NUL-terminated list of features at `features`, returning
+/// `default` if it's NULL.
+unsafe fn parse_features(
+    features: *const c_char,
+    default: WasmFeatures,
+) -> Result<WasmFeatures, String> {
+    if features.is_null() {
+        return Ok(default);
+    }
+    CStr::from_ptr(features)
+        .to_str()
+        .map_err(|e| e.to_string())?
+        .parse::<WasmFeatures>()
+        .map_err(|e| e.to_string())
+}
+
+/// Writes `message` to `error`, unless it's NULL, and returns `code`.
+fn fail(
+    code: wasm_tools_error,
+    message: String,
+    error: Option<&mut wasm_tools_byte_vec_t>,
+) -> wasm_tools_error {
+    if let Some(error) = error {
+        error.set(message.into_bytes());
+    }
+    code
+}
+
+/// # Safety
+///
+/// `bytes` must be empty or have been written by one of the functions below,
+/// and not deleted since.
 #[no_mangle]
-pub extern "C" fn wasm_tools_byte_vec_delete(bytes: &mut wasm_tools_byte_vec_t) {
-    unsafe {
-        Vec::from_raw_parts(bytes.data, bytes.size, bytes.size);
+pub unsafe extern "C" fn wasm_tools_byte_vec_delete(bytes: &mut wasm_tools_byte_vec_t) {
+    if bytes.data.is_null() {
+        return;
     }
+    drop(Vec::from_raw_parts(bytes.data, bytes.size, bytes.size));
+    bytes.clear();
 }
 
+/// # Safety
+///
+/// `seed` must point to `seed_len` bytes, unless `seed_len` is zero.
 #[no_mangle]
-pub extern "C" fn wasm_smith_create(
+pub unsafe extern "C" fn wasm_smith_create(
     seed: *const u8,
     seed_len: usize,
     bytes: &mut wasm_tools_byte_vec_t,
 ) -> wasm_tools_error {
+    bytes.clear();
     // seed == NULL is acceptable as long as seed_len is zero
-    let seed_bytes = if seed_len == 0 {
-        &[]
-    } else {
-        unsafe { std::slice::from_raw_parts(seed, seed_len) }
-    };
-
-    bytes.data = std::ptr::null_mut();
-    let mut u = Unstructured::new(seed_bytes);
+    let mut u = Unstructured::new(slice(seed, seed_len));
     match Module::new(DefaultConfig::default(), &mut u) {
         Ok(module) => {
-            let mut wasm_buffer = module.to_bytes().into_boxed_slice();
-            bytes.data = wasm_buffer.as_mut_ptr();
-            bytes.size = wasm_buffer.len();
-            std::mem::forget(wasm_buffer);
+            bytes.set(module.to_bytes());
             WASM_TOOLS_SUCCESS
         }
         Err(Error::NotEnoughData) => WASM_TOOLS_INSUFFICIENT_ENTROPY,
         Err(_e) => WASM_TOOLS_ERROR,
     }
 }
+
+/// # Safety
+///
+/// `wasm` must point to `wasm_len` bytes, unless `wasm_len` is zero, and
+/// `features` must be NULL or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_tools_validate(
+    wasm: *const u8,
+    wasm_len: usize,
+    features: *const c_char,
+    mut error: Option<&mut wasm_tools_byte_vec_t>,
+) -> wasm_tools_error {
+    if let Some(error) = &mut error {
+        error.clear();
+    }
+    let features = match parse_features(features, WasmFeatures::default()) {
+        Ok(features) => features,
+        Err(e) => return fail(WASM_TOOLS_INVALID_FEATURES, e, error),
+    };
+    match Validator::new_with_features(features).validate_all(slice(wasm, wasm_len)) {
+        Ok(_) => WASM_TOOLS_SUCCESS,
+        Err(e) => fail(WASM_TOOLS_ERROR, e.to_string(), error),
+    }
+}
+
+/// # Safety
+///
+/// `wasm` must point to `wasm_len` bytes, unless `wasm_len` is zero.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_tools_print(
+    wasm: *const u8,
+    wasm_len: usize,
+    wat: &mut wasm_tools_byte_vec_t,
+    mut error: Option<&mut wasm_tools_byte_vec_t>,
+) -> wasm_tools_error {
+    wat.clear();
+    if let Some(error) = &mut error {
+        error.clear();
+    }
+    match wasmprinter::print_bytes(slice(wasm, wasm_len)) {
+        Ok(text) => {
+            wat.set(text.into_bytes());
+            WASM_TOOLS_SUCCESS
+        }
+        Err(e) => fail(WASM_TOOLS_ERROR, e.to_string(), error),
+    }
+}
+
+/// # Safety
+///
+/// `wat` must point to `wat_len` bytes, unless `wat_len` is zero, and
+/// `features` must be NULL or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_tools_parse(
+    wat: *const u8,
+    wat_len: usize,
+    features: *const c_char,
+    wasm: &mut wasm_tools_byte_vec_t,
+    mut error: Option<&mut wasm_tools_byte_vec_t>,
+) -> wasm_tools_error {
+    wasm.clear();
+    if let Some(error) = &mut error {
+        error.clear();
+    }
+    let features = match parse_features(features, WasmFeatures::all()) {
+        Ok(features) => features,
+        Err(e) => return fail(WASM_TOOLS_INVALID_FEATURES, e, error),
+    };
+    let text = match std::str::from_utf8(slice(wat, wat_len)) {
+        Ok(text) => text,
+        Err(e) => return fail(WASM_TOOLS_ERROR, e.to_string(), error),
+    };
+    match wat::parse_str_with_features(text, features) {
+        Ok(binary) => {
+            wasm.set(binary);
+            WASM_TOOLS_SUCCESS
+        }
+        Err(e) => fail(WASM_TOOLS_ERROR, e.to_string(), error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A vector holding something other than an output, which every function
+    /// should overwrite.
+    fn garbage() -> wasm_tools_byte_vec_t {
+        wasm_tools_byte_vec_t {
+            data: std::ptr::NonNull::dangling().as_ptr(),
+            size: 3,
+        }
+    }
+
+    /// Copies the contents of `bytes` and deletes it.
+    fn take(bytes: &mut wasm_tools_byte_vec_t) -> String {
+        let contents = unsafe { slice(bytes.data, bytes.size) }.to_vec();
+        unsafe { wasm_tools_byte_vec_delete(bytes) };
+        assert!(bytes.data.is_null());
+        String::from_utf8_lossy(&contents).into_owned()
+    }
+
+    const EMPTY_MODULE: &[u8] = b"\0asm\x01\0\0\0";
+
+    #[test]
+    fn delete_empty() {
+        let mut bytes = garbage();
+        bytes.clear();
+        unsafe { wasm_tools_byte_vec_delete(&mut bytes) };
+        assert!(bytes.data.is_null());
+    }
+
+    #[test]
+    fn smith() {
+        let seed = (0..4096).map(|i| i as u8).collect::<Vec<_>>();
+        let mut bytes = garbage();
+        let ret = unsafe { wasm_smith_create(seed.as_ptr(), seed.len(), &mut bytes) };
+        assert!(matches!(ret, WASM_TOOLS_SUCCESS));
+        assert!(take(&mut bytes).starts_with("\0asm"));
+
+        // An empty seed may be passed as NULL.
+        let mut bytes = garbage();
+        let ret = unsafe { wasm_smith_create(std::ptr::null(), 0, &mut bytes) };
+        match ret {
+            WASM_TOOLS_SUCCESS => assert!(!take(&mut bytes).is_empty()),
+            _ => assert!(bytes.data.is_null()),
+        }
+    }
+
+    #[test]
+    fn validate() {
+        let mut error = garbage();
+        let ret = unsafe {
+            wasm_tools_validate(
+                EMPTY_MODULE.as_ptr(),
+                EMPTY_MODULE.len(),
+                std::ptr::null(),
+                Some(&mut error),
+            )
+        };
+        assert!(matches!(ret, WASM_TOOLS_SUCCESS));
+        assert!(error.data.is_null());
+
+        let ret = unsafe { wasm_tools_validate(b"nope".as_ptr(), 4, std::ptr::null(), None) };
+        assert!(matches!(ret, WASM_TOOLS_ERROR));
+        let ret =
+            unsafe { wasm_tools_validate(b"nope".as_ptr(), 4, std::ptr::null(), Some(&mut error)) };
+        assert!(matches!(ret, WASM_TOOLS_ERROR));
+        assert!(take(&mut error).contains("magic header"));
+
+        let features = c"simd,nope";
+        let mut error = garbage();
+        let ret = unsafe {
+            wasm_tools_validate(
+                EMPTY_MODULE.as_ptr(),
+                EMPTY_MODULE.len(),
+                features.as_ptr(),
+                Some(&mut error),
+            )
+        };
+        assert!(matches!(ret, WASM_TOOLS_INVALID_FEATURES));
+        assert_eq!(take(&mut error), "unknown feature `nope`");
+    }
+
+    #[test]
+    fn print() {
+        let mut wat = garbage();
+        let mut error = garbage();
+        let ret = unsafe {
+            wasm_tools_print(
+                EMPTY_MODULE.as_ptr(),
+                EMPTY_MODULE.len(),
+                &mut wat,
+                Some(&mut error),
+            )
+        };
+        assert!(matches!(ret, WASM_TOOLS_SUCCESS));
+        assert!(error.data.is_null());
+        assert_eq!(take(&mut wat), "(module)");
+
+        let mut wat = garbage();
+        let ret = unsafe { wasm_tools_print(b"nope".as_ptr(), 4, &mut wat, Some(&mut error)) };
+        assert!(matches!(ret, WASM_TOOLS_ERROR));
+        assert!(wat.data.is_null());
+        let message = take(&mut error);
+        assert!(message.contains("magic header"), "{message}");
+        assert!(!message.contains("Error {"), "{message}");
+    }
+
+    #[test]
+    fn parse() {
+        let text = b"(module)";
+        let mut wasm = garbage();
+        let mut error = garbage();
+        let ret = unsafe {
+            wasm_tools_parse(
+                text.as_ptr(),
+                text.len(),
+                std::ptr::null(),
+                &mut wasm,
+                Some(&mut error),
+            )
+        };
+        assert!(matches!(ret, WASM_TOOLS_SUCCESS));
+        assert!(error.data.is_null());
+        let binary = unsafe { slice(wasm.data, wasm.size) }.to_vec();
+        assert_eq!(binary, EMPTY_MODULE);
+        take(&mut wasm);
+
+        for text in [&b"(module"[..], b"\xff"] {
+            let mut wasm = garbage();
+            let mut error = garbage();
+            let ret = unsafe {
+                wasm_tools_parse(
+                    text.as_ptr(),
+                    text.len(),
+                    std::ptr::null(),
+                    &mut wasm,
+                    Some(&mut error),
+                )
+            };
+            assert!(matches!(ret, WASM_TOOLS_ERROR));
+            assert!(wasm.data.is_null());
+            assert!(!take(&mut error).is_empty());
+        }
+
+        let features = c"nope";
+        let mut wasm = garbage();
+        let ret = unsafe {
+            wasm_tools_parse(
+                text.as_ptr(),
+                text.len(),
+                features.as_ptr(),
+                &mut wasm,
+                None,
+            )
+        };
+        assert!(matches!(ret, WASM_TOOLS_INVALID_FEATURES));
+        assert!(wasm.data.is_null());
+    }
+}
@@ -369,7 +369,18 @@ impl<'a> Dump<'a> {
                     self.section(s, "canonical function", |me, end, f| {
                         let (name, col) = match &f {
                             CanonicalFunction::Lift { .. } => ("func", &mut i.funcs),
-                            CanonicalFunction::Lower { .. } => ("core func", &mut i.core_funcs),
+                            CanonicalFunction::Lower { .. }
+                            | CanonicalFunction::TaskReturn { .. }
+                            | CanonicalFunction::TaskWait { .. }
+                            | CanonicalFunction::TaskPoll { .. }
+                            | CanonicalFunction::TaskYield { .. }
+                            | CanonicalFunction::StreamNew { .. }
+                            | CanonicalFunction::StreamReceive { .. }
+                            | CanonicalFunction::StreamSend { .. }
+                            | CanonicalFunction::FutureNew { .. }
+                            | CanonicalFunction::FutureReceive { .. }
+                            | CanonicalFunction::FutureSend { .. }
+                            | CanonicalFunction::ErrorDrop => ("core func", &mut i.core_funcs),
                         };
 
                         write!(me.state, "[{} {}] {:?}", name, inc(col), f)?;
@@ -384,6 +395,13 @@ impl<'a> Dump<'a> {
                     })?
                 }
 
+                Payload::ComponentValueSection(s) => {
+                    self.section(s, "component value", |me, end, v| {
+                        write!(me.state, "[value {}] {:?}", inc(&mut i.values), v.ty)?;
+                        me.print(end)
+                    })?
+                }
+
                 Payload::ComponentStartSection(mut s) => {
                     write!(self.state, "start section")?;
                     self.print(s.range().start)?;
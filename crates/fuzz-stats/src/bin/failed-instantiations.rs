@@ -1,5 +1,6 @@
 use arbitrary::{Arbitrary, Error, Unstructured};
 use rand::RngCore;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering::SeqCst};
 use std::sync::Arc;
 use wasm_smith::SwarmConfig;
@@ -8,6 +9,8 @@ use wasmtime::*;
 struct State {
     engine: Engine,
     print: bool,
+    /// Where modules which fail to instantiate are written.
+    output_dir: PathBuf,
     remaining: AtomicIsize,
     total: AtomicUsize,
     instantiate_trap: AtomicUsize,
@@ -25,6 +28,8 @@ fn main() {
 fn under_10_percent() {
     let mut state = State::new();
     state.print = false;
+    let dir = tempfile::tempdir().unwrap();
+    state.output_dir = dir.path().to_path_buf();
     state.remaining.store(1000, SeqCst);
     let state = Arc::new(state);
     state.run();
@@ -47,6 +52,7 @@ impl State {
         State {
             engine: Engine::new(&config).unwrap(),
             print: true,
+            output_dir: PathBuf::from("."),
             total: AtomicUsize::new(0),
             remaining: AtomicIsize::new(isize::max_value()),
             instantiate_trap: AtomicUsize::new(0),
@@ -140,19 +146,19 @@ impl State {
                 // Traps are ok if they happen during instantiation. This is an
                 // expected occurrence we want to account for.
                 if e.downcast_ref::<Trap>().is_some() {
-                    std::fs::write("trap.wasm", &wasm).unwrap();
+                    std::fs::write(self.output_dir.join("trap.wasm"), &wasm).unwrap();
                     self.instantiate_trap.fetch_add(1, SeqCst);
 
                 // Ooms, like traps, are normal during instantiations. This
                 // can happen, for example, if a defined memory is very large.
                 } else if store.data().oom {
-                    std::fs::write("oom.wasm", &wasm).unwrap();
+                    std::fs::write(self.output_dir.join("oom.wasm"), &wasm).unwrap();
                     self.instantiate_oom.fetch_add(1, SeqCst);
 
                 // In theory nothing else fails to instantiate. If it does, then
                 // panic.
                 } else {
-                    std::fs::write("panic.wasm", &wasm).unwrap();
+                    std::fs::write(self.output_dir.join("panic.wasm"), &wasm).unwrap();
                     panic!("unknown: {}", e);
                 }
             }
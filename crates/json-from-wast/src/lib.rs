@@ -0,0 +1,613 @@
+//! A library to compile `*.wast` scripts into a set of WebAssembly binaries
+//! and a JSON manifest describing the commands of the script.
+//!
+//! The manifest format matches the one produced by wabt's `wast2json` tool,
+//! which many engines already have a test harness for. Each module defined in
+//! the script is written to its own file and referenced by name from the
+//! manifest.
+//!
+//! ```
+//! let output = json_from_wast::compile(
+//!     "add.wast",
+//!     r#"
+//!         (module (func (export "add") (param i32 i32) (result i32)
+//!             local.get 0
+//!             local.get 1
+//!             i32.add))
+//!         (assert_return (invoke "add" (i32.const 1) (i32.const 2)) (i32.const 3))
+//!     "#,
+//! )?;
+//! assert_eq!(output.files.len(), 1);
+//! assert_eq!(output.files[0].0, "add.0.wasm");
+//! assert_eq!(output.manifest.commands.len(), 2);
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+#![deny(missing_docs)]
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::path::Path;
+use wast::core::{HeapType, NanPattern, V128Const, V128Pattern, WastArgCore, WastRetCore};
+use wast::lexer::Lexer;
+use wast::parser::{self, ParseBuffer};
+use wast::token::{Float32, Float64, Id, Span};
+use wast::{QuoteWat, Wast, WastArg, WastDirective, WastExecute, WastInvoke, WastRet, Wat};
+
+/// The result of compiling a `*.wast` script.
+#[derive(Debug)]
+pub struct Output {
+    /// The JSON manifest of commands in the script.
+    pub manifest: Manifest,
+    /// The files referenced by the manifest, as pairs of file name and file
+    /// contents.
+    ///
+    /// Files are either WebAssembly binaries (`*.wasm`) or, for modules that
+    /// are expected to fail to parse, the original text (`*.wat`).
+    pub files: Vec<(String, Vec<u8>)>,
+}
+
+/// The top-level JSON manifest for a `*.wast` script.
+#[derive(Debug, Serialize)]
+pub struct Manifest {
+    /// The name of the script this manifest was compiled from.
+    pub source_filename: String,
+    /// The commands of the script, in order.
+    pub commands: Vec<Command>,
+}
+
+/// A single command in a `*.wast` script.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum Command {
+    /// Instantiate a module, making it the current module.
+    Module {
+        line: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        filename: String,
+    },
+    /// Make the exports of a module available for import under `as`.
+    Register {
+        line: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[serde(rename = "as")]
+        as_: String,
+    },
+    /// Perform an action whose result is ignored.
+    Action { line: usize, action: Action },
+    /// Perform an action and compare its results to `expected`.
+    AssertReturn {
+        line: usize,
+        action: Action,
+        expected: Vec<Const>,
+    },
+    /// Perform an action which is expected to trap.
+    AssertTrap {
+        line: usize,
+        action: Action,
+        text: String,
+    },
+    /// Perform an action which is expected to exhaust the call stack.
+    AssertExhaustion {
+        line: usize,
+        action: Action,
+        text: String,
+    },
+    /// Perform an action which is expected to throw an exception.
+    AssertException { line: usize, action: Action },
+    /// The module is expected to fail to decode or parse.
+    AssertMalformed {
+        line: usize,
+        filename: String,
+        text: String,
+        module_type: ModuleType,
+    },
+    /// The module is expected to fail validation.
+    AssertInvalid {
+        line: usize,
+        filename: String,
+        text: String,
+        module_type: ModuleType,
+    },
+    /// The module is expected to fail to link.
+    AssertUnlinkable {
+        line: usize,
+        filename: String,
+        text: String,
+        module_type: ModuleType,
+    },
+    /// The module is expected to trap during instantiation.
+    AssertUninstantiable {
+        line: usize,
+        filename: String,
+        text: String,
+        module_type: ModuleType,
+    },
+}
+
+/// The format of a file referenced from the manifest.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleType {
+    /// A WebAssembly binary.
+    Binary,
+    /// WebAssembly text.
+    Text,
+}
+
+/// An action to perform on a module.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[allow(missing_docs)]
+pub enum Action {
+    /// Invoke the exported function `field` with `args`.
+    Invoke {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        module: Option<String>,
+        field: String,
+        args: Vec<Const>,
+    },
+    /// Read the value of the exported global `field`.
+    Get {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        module: Option<String>,
+        field: String,
+    },
+}
+
+/// A constant used as an argument or expected result.
+///
+/// Scalar values are encoded as the unsigned decimal representation of their
+/// bits, with the exception of NaN patterns which are encoded as
+/// `nan:canonical` or `nan:arithmetic`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+#[allow(missing_docs)]
+pub enum Const {
+    I32 {
+        value: String,
+    },
+    I64 {
+        value: String,
+    },
+    F32 {
+        value: String,
+    },
+    F64 {
+        value: String,
+    },
+    V128 {
+        lane_type: LaneType,
+        value: Vec<String>,
+    },
+    /// A function reference; `value` is `"null"` for a null reference and
+    /// absent if any non-null reference is accepted.
+    Funcref {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value: Option<String>,
+    },
+    /// An external reference; `value` is `"null"` for a null reference.
+    Externref {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value: Option<String>,
+    },
+    Anyref {
+        value: String,
+    },
+    Eqref {
+        value: String,
+    },
+    Dataref {
+        value: String,
+    },
+    Arrayref {
+        value: String,
+    },
+    I31ref {
+        value: String,
+    },
+}
+
+/// The interpretation of the lanes of a `v128` constant.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[allow(missing_docs)]
+pub enum LaneType {
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+/// Compiles the `*.wast` script `contents` into a manifest and the set of
+/// files it references.
+///
+/// The `source_filename` is recorded in the manifest, is used to render
+/// errors, and has its file stem used as the prefix of each generated file
+/// name.
+pub fn compile(source_filename: &str, contents: &str) -> Result<Output> {
+    let path = Path::new(source_filename);
+    let adjust = |mut err: wast::Error| {
+        err.set_path(path);
+        err.set_text(contents);
+        err
+    };
+    let mut lexer = Lexer::new(contents);
+    lexer.allow_confusing_unicode(true);
+    let buf = ParseBuffer::new_with_lexer(lexer).map_err(adjust)?;
+    let ast = parser::parse::<Wast>(&buf).map_err(adjust)?;
+
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("module")
+        .to_string();
+    let mut compiler = Compiler {
+        contents,
+        stem,
+        files: Vec::new(),
+    };
+    let mut commands = Vec::new();
+    for directive in ast.directives {
+        let span = directive.span();
+        let command = compiler.directive(directive).with_context(|| {
+            let (line, col) = span.linecol_in(contents);
+            format!(
+                "failed to compile directive at {}:{}:{}",
+                source_filename,
+                line + 1,
+                col + 1
+            )
+        })?;
+        commands.push(command);
+    }
+
+    Ok(Output {
+        manifest: Manifest {
+            source_filename: source_filename.to_string(),
+            commands,
+        },
+        files: compiler.files,
+    })
+}
+
+struct Compiler<'a> {
+    contents: &'a str,
+    stem: String,
+    files: Vec<(String, Vec<u8>)>,
+}
+
+impl Compiler<'_> {
+    fn directive(&mut self, directive: WastDirective<'_>) -> Result<Command> {
+        let line = self.line(directive.span());
+        Ok(match directive {
+            WastDirective::Wat(mut module) => {
+                let name = quote_wat_id(&module).map(id_name);
+                let (filename, _) = self.module(&mut module, false)?;
+                Command::Module {
+                    line,
+                    name,
+                    filename,
+                }
+            }
+            WastDirective::Register { name, module, .. } => Command::Register {
+                line,
+                name: module.map(id_name),
+                as_: name.to_string(),
+            },
+            WastDirective::Invoke(invoke) => Command::Action {
+                line,
+                action: invoke_action(invoke)?,
+            },
+            WastDirective::AssertReturn { exec, results, .. } => Command::AssertReturn {
+                line,
+                action: self.action(exec)?,
+                expected: results.iter().map(ret).collect::<Result<_>>()?,
+            },
+            WastDirective::AssertTrap {
+                exec: WastExecute::Wat(module),
+                message,
+                ..
+            } => {
+                let (filename, module_type) = self.module(&mut QuoteWat::Wat(module), false)?;
+                Command::AssertUninstantiable {
+                    line,
+                    filename,
+                    text: message.to_string(),
+                    module_type,
+                }
+            }
+            WastDirective::AssertTrap { exec, message, .. } => Command::AssertTrap {
+                line,
+                action: self.action(exec)?,
+                text: message.to_string(),
+            },
+            WastDirective::AssertExhaustion { call, message, .. } => Command::AssertExhaustion {
+                line,
+                action: invoke_action(call)?,
+                text: message.to_string(),
+            },
+            WastDirective::AssertException { exec, .. } => Command::AssertException {
+                line,
+                action: self.action(exec)?,
+            },
+            WastDirective::AssertMalformed {
+                mut module,
+                message,
+                ..
+            } => {
+                let (filename, module_type) = self.module(&mut module, true)?;
+                Command::AssertMalformed {
+                    line,
+                    filename,
+                    text: message.to_string(),
+                    module_type,
+                }
+            }
+            WastDirective::AssertInvalid {
+                mut module,
+                message,
+                ..
+            } => {
+                let (filename, module_type) = self.module(&mut module, true)?;
+                Command::AssertInvalid {
+                    line,
+                    filename,
+                    text: message.to_string(),
+                    module_type,
+                }
+            }
+            WastDirective::AssertUnlinkable {
+                module, message, ..
+            } => {
+                let (filename, module_type) = self.module(&mut QuoteWat::Wat(module), false)?;
+                Command::AssertUnlinkable {
+                    line,
+                    filename,
+                    text: message.to_string(),
+                    module_type,
+                }
+            }
+            WastDirective::ModuleDefinition(_) | WastDirective::ModuleInstance { .. } => {
+                bail!("module definitions and instances are not supported")
+            }
+            WastDirective::Thread(_) | WastDirective::Wait { .. } => {
+                bail!("`thread` and `wait` directives are not supported")
+            }
+        })
+    }
+
+    fn action(&mut self, exec: WastExecute<'_>) -> Result<Action> {
+        match exec {
+            WastExecute::Invoke(invoke) => invoke_action(invoke),
+            WastExecute::Get { module, global } => Ok(Action::Get {
+                module: module.map(id_name),
+                field: global.to_string(),
+            }),
+            WastExecute::Wat(_) => bail!("module instantiation is not supported as an action"),
+        }
+    }
+
+    /// Writes out `module` to a new file, returning the name of the file and
+    /// its format.
+    ///
+    /// Quoted modules are written out as text when `allow_text` is set, since
+    /// they're expected to fail to parse; otherwise they're encoded as a
+    /// binary like all other modules.
+    fn module(
+        &mut self,
+        module: &mut QuoteWat<'_>,
+        allow_text: bool,
+    ) -> Result<(String, ModuleType)> {
+        let (module_type, contents) = match module {
+            QuoteWat::QuoteModule(_, source) if allow_text => {
+                (ModuleType::Text, quoted_text(source, false))
+            }
+            QuoteWat::QuoteComponent(_, source) if allow_text => {
+                (ModuleType::Text, quoted_text(source, true))
+            }
+            _ => {
+                let binary = module.encode().map_err(|mut e| {
+                    e.set_text(self.contents);
+                    e
+                })?;
+                (ModuleType::Binary, binary)
+            }
+        };
+        let extension = match module_type {
+            ModuleType::Binary => "wasm",
+            ModuleType::Text => "wat",
+        };
+        let filename = format!("{}.{}.{}", self.stem, self.files.len(), extension);
+        self.files.push((filename.clone(), contents));
+        Ok((filename, module_type))
+    }
+
+    fn line(&self, span: Span) -> usize {
+        span.linecol_in(self.contents).0 + 1
+    }
+}
+
+/// Reassembles the text of a quoted module the same way that
+/// `QuoteWat::encode` does before parsing it.
+fn quoted_text(source: &[(Span, &[u8])], component: bool) -> Vec<u8> {
+    let mut text = Vec::new();
+    if component {
+        text.extend_from_slice(b"(component ");
+    }
+    for (_, src) in source {
+        text.extend_from_slice(src);
+        text.push(b' ');
+    }
+    if component {
+        text.push(b')');
+    }
+    text
+}
+
+fn quote_wat_id<'a>(module: &QuoteWat<'a>) -> Option<Id<'a>> {
+    match module {
+        QuoteWat::Wat(Wat::Module(m)) => m.id,
+        QuoteWat::Wat(Wat::Component(c)) => c.id,
+        QuoteWat::QuoteModule(..) | QuoteWat::QuoteComponent(..) => None,
+    }
+}
+
+fn id_name(id: Id<'_>) -> String {
+    format!("${}", id.name())
+}
+
+fn invoke_action(invoke: WastInvoke<'_>) -> Result<Action> {
+    Ok(Action::Invoke {
+        module: invoke.module.map(id_name),
+        field: invoke.name.to_string(),
+        args: invoke.args.iter().map(arg).collect::<Result<_>>()?,
+    })
+}
+
+fn arg(arg: &WastArg<'_>) -> Result<Const> {
+    let arg = match arg {
+        WastArg::Core(arg) => arg,
+        WastArg::Component(_) => bail!("component model values are not supported"),
+    };
+    Ok(match arg {
+        WastArgCore::I32(i) => Const::I32 {
+            value: (*i as u32).to_string(),
+        },
+        WastArgCore::I64(i) => Const::I64 {
+            value: (*i as u64).to_string(),
+        },
+        WastArgCore::F32(f) => Const::F32 {
+            value: f.bits.to_string(),
+        },
+        WastArgCore::F64(f) => Const::F64 {
+            value: f.bits.to_string(),
+        },
+        WastArgCore::V128(v) => v128(v),
+        WastArgCore::RefNull(ty) => ref_null(ty)?,
+        WastArgCore::RefExtern(i) => Const::Externref {
+            value: Some(i.to_string()),
+        },
+    })
+}
+
+fn ret(ret: &WastRet<'_>) -> Result<Const> {
+    let ret = match ret {
+        WastRet::Core(ret) => ret,
+        WastRet::Component(_) => bail!("component model values are not supported"),
+    };
+    Ok(match ret {
+        WastRetCore::I32(i) => Const::I32 {
+            value: (*i as u32).to_string(),
+        },
+        WastRetCore::I64(i) => Const::I64 {
+            value: (*i as u64).to_string(),
+        },
+        WastRetCore::F32(f) => Const::F32 {
+            value: f32_pattern(f),
+        },
+        WastRetCore::F64(f) => Const::F64 {
+            value: f64_pattern(f),
+        },
+        WastRetCore::V128(v) => v128_pattern(v),
+        WastRetCore::RefNull(None) => bail!("untyped `ref.null` results are not supported"),
+        WastRetCore::RefNull(Some(ty)) => ref_null(ty)?,
+        WastRetCore::RefExtern(i) => Const::Externref {
+            value: Some(i.to_string()),
+        },
+        WastRetCore::RefFunc(None) => Const::Funcref { value: None },
+        WastRetCore::RefFunc(Some(_)) => {
+            bail!("`ref.func` results with an index are not supported")
+        }
+        WastRetCore::Either(_) => bail!("`either` results are not supported"),
+    })
+}
+
+fn ref_null(ty: &HeapType<'_>) -> Result<Const> {
+    let value = "null".to_string();
+    Ok(match ty {
+        HeapType::Func => Const::Funcref { value: Some(value) },
+        HeapType::Extern => Const::Externref { value: Some(value) },
+        HeapType::Any => Const::Anyref { value },
+        HeapType::Eq => Const::Eqref { value },
+        HeapType::Data => Const::Dataref { value },
+        HeapType::Array => Const::Arrayref { value },
+        HeapType::I31 => Const::I31ref { value },
+        HeapType::Index(_) => bail!("`ref.null` of a concrete type is not supported"),
+    })
+}
+
+fn v128(v: &V128Const) -> Const {
+    let (lane_type, value) = match v {
+        V128Const::I8x16(lanes) => (
+            LaneType::I8,
+            lanes.iter().map(|i| (*i as u8).to_string()).collect(),
+        ),
+        V128Const::I16x8(lanes) => (
+            LaneType::I16,
+            lanes.iter().map(|i| (*i as u16).to_string()).collect(),
+        ),
+        V128Const::I32x4(lanes) => (
+            LaneType::I32,
+            lanes.iter().map(|i| (*i as u32).to_string()).collect(),
+        ),
+        V128Const::I64x2(lanes) => (
+            LaneType::I64,
+            lanes.iter().map(|i| (*i as u64).to_string()).collect(),
+        ),
+        V128Const::F32x4(lanes) => (
+            LaneType::F32,
+            lanes.iter().map(|f| f.bits.to_string()).collect(),
+        ),
+        V128Const::F64x2(lanes) => (
+            LaneType::F64,
+            lanes.iter().map(|f| f.bits.to_string()).collect(),
+        ),
+    };
+    Const::V128 { lane_type, value }
+}
+
+fn v128_pattern(v: &V128Pattern) -> Const {
+    let (lane_type, value) = match v {
+        V128Pattern::I8x16(lanes) => (
+            LaneType::I8,
+            lanes.iter().map(|i| (*i as u8).to_string()).collect(),
+        ),
+        V128Pattern::I16x8(lanes) => (
+            LaneType::I16,
+            lanes.iter().map(|i| (*i as u16).to_string()).collect(),
+        ),
+        V128Pattern::I32x4(lanes) => (
+            LaneType::I32,
+            lanes.iter().map(|i| (*i as u32).to_string()).collect(),
+        ),
+        V128Pattern::I64x2(lanes) => (
+            LaneType::I64,
+            lanes.iter().map(|i| (*i as u64).to_string()).collect(),
+        ),
+        V128Pattern::F32x4(lanes) => (LaneType::F32, lanes.iter().map(f32_pattern).collect()),
+        V128Pattern::F64x2(lanes) => (LaneType::F64, lanes.iter().map(f64_pattern).collect()),
+    };
+    Const::V128 { lane_type, value }
+}
+
+fn f32_pattern(pattern: &NanPattern<Float32>) -> String {
+    match pattern {
+        NanPattern::CanonicalNan => "nan:canonical".to_string(),
+        NanPattern::ArithmeticNan => "nan:arithmetic".to_string(),
+        NanPattern::Value(f) => f.bits.to_string(),
+    }
+}
+
+fn f64_pattern(pattern: &NanPattern<Float64>) -> String {
+    match pattern {
+        NanPattern::CanonicalNan => "nan:canonical".to_string(),
+        NanPattern::ArithmeticNan => "nan:arithmetic".to_string(),
+        NanPattern::Value(f) => f.bits.to_string(),
+    }
+}
@@ -0,0 +1,160 @@
+use serde_json::json;
+
+#[test]
+fn commands() -> anyhow::Result<()> {
+    let output = json_from_wast::compile(
+        "dir/test.wast",
+        r#"
+(module $m
+  (global (export "g") i32 (i32.const 7))
+  (func (export "id") (param f32) (result f32) local.get 0)
+  (func (export "trap") unreachable))
+(register "m" $m)
+(invoke "trap")
+(assert_return (invoke $m "id" (f32.const 1.5)) (f32.const 1.5))
+(assert_return (invoke "id" (f32.const nan)) (f32.const nan:canonical))
+(assert_return (get "g") (i32.const -1))
+(assert_trap (invoke "trap") "unreachable")
+(assert_malformed (module quote "(func") "unexpected end")
+(assert_invalid (module (func (result i32))) "type mismatch")
+(assert_trap (module (func unreachable) (start 0)) "unreachable")
+"#,
+    )?;
+
+    let manifest = serde_json::to_value(&output.manifest)?;
+    assert_eq!(
+        manifest,
+        json!({
+            "source_filename": "dir/test.wast",
+            "commands": [
+                {"type": "module", "line": 2, "name": "$m", "filename": "test.0.wasm"},
+                {"type": "register", "line": 6, "name": "$m", "as": "m"},
+                {"type": "action", "line": 7, "action": {"type": "invoke", "field": "trap", "args": []}},
+                {
+                    "type": "assert_return",
+                    "line": 8,
+                    "action": {
+                        "type": "invoke",
+                        "module": "$m",
+                        "field": "id",
+                        "args": [{"type": "f32", "value": "1069547520"}],
+                    },
+                    "expected": [{"type": "f32", "value": "1069547520"}],
+                },
+                {
+                    "type": "assert_return",
+                    "line": 9,
+                    "action": {
+                        "type": "invoke",
+                        "field": "id",
+                        "args": [{"type": "f32", "value": "2143289344"}],
+                    },
+                    "expected": [{"type": "f32", "value": "nan:canonical"}],
+                },
+                {
+                    "type": "assert_return",
+                    "line": 10,
+                    "action": {"type": "get", "field": "g"},
+                    "expected": [{"type": "i32", "value": "4294967295"}],
+                },
+                {
+                    "type": "assert_trap",
+                    "line": 11,
+                    "action": {"type": "invoke", "field": "trap", "args": []},
+                    "text": "unreachable",
+                },
+                {
+                    "type": "assert_malformed",
+                    "line": 12,
+                    "filename": "test.1.wat",
+                    "text": "unexpected end",
+                    "module_type": "text",
+                },
+                {
+                    "type": "assert_invalid",
+                    "line": 13,
+                    "filename": "test.2.wasm",
+                    "text": "type mismatch",
+                    "module_type": "binary",
+                },
+                {
+                    "type": "assert_uninstantiable",
+                    "line": 14,
+                    "filename": "test.3.wasm",
+                    "text": "unreachable",
+                    "module_type": "binary",
+                },
+            ],
+        })
+    );
+
+    let names = output
+        .files
+        .iter()
+        .map(|(n, _)| n.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        ["test.0.wasm", "test.1.wat", "test.2.wasm", "test.3.wasm"]
+    );
+    assert_eq!(output.files[1].1, b"(func ");
+    wasmparser::validate(&output.files[0].1)?;
+    assert!(wasmparser::validate(&output.files[2].1).is_err());
+    Ok(())
+}
+
+#[test]
+fn v128_and_refs() -> anyhow::Result<()> {
+    let output = json_from_wast::compile(
+        "simd.wast",
+        r#"
+(module (func (export "f") (param v128 externref) (result v128 funcref)
+  local.get 0
+  ref.null func))
+(assert_return
+  (invoke "f" (v128.const i16x8 -1 0 1 2 3 4 5 6) (ref.extern 3))
+  (v128.const f64x2 nan:arithmetic 0)
+  (ref.null func))
+"#,
+    )?;
+
+    let manifest = serde_json::to_value(&output.manifest)?;
+    assert_eq!(
+        manifest["commands"][1]["action"]["args"],
+        json!([
+            {
+                "type": "v128",
+                "lane_type": "i16",
+                "value": ["65535", "0", "1", "2", "3", "4", "5", "6"],
+            },
+            {"type": "externref", "value": "3"},
+        ])
+    );
+    assert_eq!(
+        manifest["commands"][1]["expected"],
+        json!([
+            {"type": "v128", "lane_type": "f64", "value": ["nan:arithmetic", "0"]},
+            {"type": "funcref", "value": "null"},
+        ])
+    );
+    Ok(())
+}
+
+#[test]
+fn errors() {
+    let err = json_from_wast::compile("bad.wast", "(module (func $f (i32.const)))").unwrap_err();
+    assert!(format!("{:?}", err).contains("bad.wast:1:"), "{:?}", err);
+
+    let err = json_from_wast::compile(
+        "component.wast",
+        "(component)\n(assert_return (invoke \"f\" (u32.const 1)))",
+    )
+    .unwrap_err();
+    let err = format!("{:?}", err);
+    assert!(err.contains("component.wast:2:"), "{}", err);
+    assert!(
+        err.contains("component model values are not supported"),
+        "{}",
+        err
+    );
+}
@@ -1,6 +1,6 @@
 //! Module for CLI parsing.
 
-use crate::{composer::ComponentComposer, config::Config};
+use crate::{composer::ComponentComposer, config::Config, source::ContentStore};
 use anyhow::{Context, Result};
 use clap::Parser;
 use std::path::{Path, PathBuf};
@@ -13,8 +13,13 @@ use wasmparser::{Validator, WasmFeatures};
 #[clap(name = "component-encoder", version = env!("CARGO_PKG_VERSION"))]
 pub struct WasmComposeCommand {
     /// The path of the output composed WebAssembly component.
-    #[clap(long, short = 'o', value_name = "OUTPUT")]
-    pub output: PathBuf,
+    #[clap(
+        long,
+        short = 'o',
+        value_name = "OUTPUT",
+        required_unless_present = "dry-run"
+    )]
+    pub output: Option<PathBuf>,
 
     /// The path to the configuration file to use.
     #[clap(long, short = 'c', value_name = "CONFIG")]
@@ -24,6 +29,18 @@ pub struct WasmComposeCommand {
     #[clap(long = "search-path", short = 'p', value_name = "PATH")]
     pub paths: Vec<PathBuf>,
 
+    /// A content-addressed store to fetch dependencies from.
+    ///
+    /// Dependencies with a path of the form `sha256:<hex digest>` are read
+    /// from `<STORE>/sha256/<hex digest>` and verified against the digest.
+    #[clap(long, value_name = "STORE")]
+    pub content_store: Option<PathBuf>,
+
+    /// The name of an instance to import in the composed output component
+    /// rather than instantiate.
+    #[clap(long = "import", value_name = "NAME")]
+    pub imports: Vec<String>,
+
     /// Skip validation of the composed output component.
     #[clap(long)]
     pub skip_validation: bool,
@@ -32,6 +49,30 @@ pub struct WasmComposeCommand {
     #[clap(long = "no-imports")]
     pub disallow_imports: bool,
 
+    /// Share dependencies with identical contents.
+    ///
+    /// Identical components are defined once and instances of them with
+    /// identical arguments are shared, unless the component has state.
+    #[clap(long)]
+    pub share_dependencies: bool,
+
+    /// Analyze the composition without encoding it.
+    ///
+    /// Prints which instantiation arguments are satisfied by which instances
+    /// and which remain as imports of the composed component.
+    #[clap(long)]
+    pub dry_run: bool,
+
+    /// Print the instantiation graph in the given format instead of the
+    /// analysis of a dry run.
+    #[clap(
+        long,
+        value_name = "FORMAT",
+        requires = "dry-run",
+        possible_values = ["dot", "mermaid"]
+    )]
+    pub graph_format: Option<String>,
+
     /// The path to the root component to compose.
     #[clap(value_name = "COMPONENT")]
     pub component: PathBuf,
@@ -43,12 +84,22 @@ impl WasmComposeCommand {
         let config = self.create_config()?;
         log::debug!("configuration:\n{:#?}", config);
 
-        let bytes = ComponentComposer::new(&self.component, &config).compose()?;
+        let mut composer = ComponentComposer::new(&self.component, &config);
+        if let Some(store) = &self.content_store {
+            composer = composer.with_source(ContentStore::new(store));
+        }
+
+        let output = match &self.output {
+            Some(output) if !self.dry_run => output,
+            _ => return self.dry_run(&composer),
+        };
+
+        let bytes = composer.compose()?;
 
-        std::fs::write(&self.output, &bytes).with_context(|| {
+        std::fs::write(output, &bytes).with_context(|| {
             format!(
                 "failed to write composed component `{output}`",
-                output = self.output.display()
+                output = output.display()
             )
         })?;
 
@@ -57,23 +108,76 @@ impl WasmComposeCommand {
         } else {
             Validator::new_with_features(WasmFeatures {
                 component_model: true,
+                component_model_async: true,
                 ..Default::default()
             })
             .validate_all(&bytes)
             .with_context(|| {
                 format!(
                     "failed to validate output component `{output}`",
-                    output = self.output.display()
+                    output = output.display()
                 )
             })?;
 
             log::debug!("output component validated successfully");
         }
 
-        println!(
-            "composed component `{output}`",
-            output = self.output.display()
-        );
+        println!("composed component `{output}`", output = output.display());
+
+        Ok(())
+    }
+
+    fn dry_run(&self, composer: &ComponentComposer) -> Result<()> {
+        let graph = composer.graph()?;
+
+        match self.graph_format.as_deref() {
+            Some("dot") => print!("{}", graph.to_dot()),
+            Some("mermaid") => print!("{}", graph.to_mermaid()),
+            Some(format) => unreachable!("unsupported graph format `{format}`"),
+            None => {
+                let imported = graph.imported_instances();
+                let satisfied = graph
+                    .connections()
+                    .into_iter()
+                    .filter(|c| !imported.contains(&c.source))
+                    .collect::<Vec<_>>();
+
+                println!("satisfied arguments:");
+                if satisfied.is_empty() {
+                    println!("  none");
+                }
+                for c in satisfied {
+                    match c.export {
+                        Some(export) => println!(
+                            "  `{import}` of instance `{target}` <- export `{export}` of instance `{source}`",
+                            import = c.import,
+                            target = c.target,
+                            source = c.source,
+                        ),
+                        None => println!(
+                            "  `{import}` of instance `{target}` <- instance `{source}`",
+                            import = c.import,
+                            target = c.target,
+                            source = c.source,
+                        ),
+                    }
+                }
+
+                println!("imported instances:");
+                if imported.is_empty() {
+                    println!("  none");
+                }
+                for name in imported {
+                    let users = graph
+                        .connections()
+                        .into_iter()
+                        .filter(|c| c.source == name)
+                        .map(|c| format!("`{}` of instance `{}`", c.import, c.target))
+                        .collect::<Vec<_>>();
+                    println!("  `{name}` for {users}", users = users.join(", "));
+                }
+            }
+        }
 
         Ok(())
     }
@@ -94,8 +198,10 @@ impl WasmComposeCommand {
         };
 
         config.search_paths.extend(self.paths.iter().cloned());
+        config.imports.extend(self.imports.iter().cloned());
         config.skip_validation |= self.skip_validation;
         config.disallow_imports |= self.disallow_imports;
+        config.share_dependencies |= self.share_dependencies;
         Ok(config)
     }
 }
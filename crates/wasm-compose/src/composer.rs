@@ -3,19 +3,22 @@
 use crate::{
     config::Config,
     encoding::{InstantiationGraphEncoder, TypeEncoder},
+    source::{verify_digest, ComponentSource},
 };
 use anyhow::{anyhow, bail, Context, Result};
 use indexmap::{IndexMap, IndexSet};
 use petgraph::{algo::toposort, dot::Dot, graph::NodeIndex, visit::EdgeRef, EdgeDirection, Graph};
+use semver::{Version, VersionReq};
 use std::{
-    collections::VecDeque,
+    collections::{HashSet, VecDeque},
+    fmt::Write,
     path::{Path, PathBuf},
 };
 use wasm_encoder::ComponentExportKind;
 use wasmparser::{
     types::{ComponentEntityType, ComponentInstanceType, Types, TypesRef},
     Chunk, ComponentExport, ComponentExternalKind, ComponentImport, ComponentTypeRef, Encoding,
-    Parser, Payload, ValidPayload, Validator, WasmFeatures,
+    Parser, Payload, ProducersSectionReader, ValidPayload, Validator, WasmFeatures,
 };
 
 /// The root component name used in configuration.
@@ -37,6 +40,10 @@ pub(crate) struct Component {
     imports: IndexMap<String, ComponentTypeRef>,
     /// The export map of the component.
     exports: IndexMap<String, (ComponentExternalKind, u32)>,
+    /// The fields of the component's `producers` section.
+    ///
+    /// Maps each field name to the names and versions of its values.
+    producers: IndexMap<String, IndexMap<String, String>>,
 }
 
 impl Component {
@@ -52,14 +59,42 @@ impl Component {
             format!("failed to parse component `{path}`", path = path.display())
         })?;
 
+        Self::from_bytes(index, path, bytes, import_name)
+    }
+
+    /// Creates a component from bytes fetched from a component source.
+    ///
+    /// The bytes may be either a binary or text format component.
+    fn from_source(
+        index: ComponentIndex,
+        reference: &str,
+        bytes: &[u8],
+        import_name: Option<String>,
+    ) -> Result<Self> {
+        log::info!("parsing WebAssembly component `{reference}`");
+
+        let bytes = wat::parse_bytes(bytes)
+            .with_context(|| format!("failed to parse component `{reference}`"))?;
+
+        Self::from_bytes(index, reference.into(), bytes.into_owned(), import_name)
+    }
+
+    fn from_bytes(
+        index: ComponentIndex,
+        path: PathBuf,
+        bytes: Vec<u8>,
+        import_name: Option<String>,
+    ) -> Result<Self> {
         let mut parser = Parser::new(0);
         let mut parsers = Vec::new();
         let mut validator = Validator::new_with_features(WasmFeatures {
             component_model: true,
+            component_model_async: true,
             ..Default::default()
         });
         let mut imports = IndexMap::new();
         let mut exports = IndexMap::new();
+        let mut producers = IndexMap::new();
 
         let mut cur = bytes.as_slice();
         loop {
@@ -105,6 +140,22 @@ impl Component {
                                         );
                                     }
                                 }
+                                Payload::CustomSection(s) if s.name() == "producers" => {
+                                    match read_producers(s.data(), s.data_offset()) {
+                                        Ok(fields) => {
+                                            for (field, values) in fields {
+                                                producers
+                                                    .entry(field)
+                                                    .or_insert_with(IndexMap::new)
+                                                    .extend(values);
+                                            }
+                                        }
+                                        Err(e) => log::warn!(
+                                            "ignoring malformed `producers` section in component `{path}`: {e:#}",
+                                            path = path.display()
+                                        ),
+                                    }
+                                }
                                 _ => {}
                             }
                         }
@@ -124,6 +175,7 @@ impl Component {
                                     import_name,
                                     imports,
                                     exports,
+                                    producers,
                                 };
                                 log::debug!(
                                     "WebAssembly component `{path}` parsed:\n{component:#?}",
@@ -155,6 +207,10 @@ impl Component {
         self.types.as_ref()
     }
 
+    pub(crate) fn producers(&self) -> &IndexMap<String, IndexMap<String, String>> {
+        &self.producers
+    }
+
     pub(crate) fn import_name(&self) -> Option<&str> {
         self.import_name.as_deref()
     }
@@ -206,24 +262,67 @@ impl Component {
     }
 
     /// Gets an exported instance index and type with the given export name.
+    ///
+    /// If the name is of the form `<name>@<version>` and there is no export
+    /// of that exact name, the instance export with the highest
+    /// semver-compatible version is returned instead.
     fn export_instance(&self, name: &str) -> Option<(ExportIndex, &ComponentInstanceType)> {
-        self.exports
-            .get_full(name)
-            .and_then(|(i, _, (kind, index))| match kind {
-                ComponentExternalKind::Instance => Some((
-                    ExportIndex(i),
-                    self.types.component_instance_at(*index).unwrap(),
-                )),
-                _ => None,
+        let index = self
+            .exports
+            .get_index_of(name)
+            .or_else(|| self.find_compatible_version(name))?;
+
+        match self.exports[index] {
+            (ComponentExternalKind::Instance, ty) => Some((
+                ExportIndex(index),
+                self.types.component_instance_at(ty).unwrap(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Finds the index of the instance export with the highest version that is
+    /// semver-compatible with the version of the given name.
+    fn find_compatible_version(&self, name: &str) -> Option<usize> {
+        let (base, version) = parse_versioned_name(name)?;
+        let req = VersionReq::parse(&format!("^{version}")).ok()?;
+
+        let (index, export_version) = self
+            .exports
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, (kind, _)))| *kind == ComponentExternalKind::Instance)
+            .filter_map(|(i, (export, _))| {
+                let (export_base, export_version) = parse_versioned_name(export)?;
+                (export_base == base && req.matches(&export_version)).then_some((i, export_version))
             })
+            .max_by(|(_, a), (_, b)| a.cmp(b))?;
+
+        log::debug!(
+            "using export `{export}` of component `{path}` for `{name}` (version {export_version} is compatible with {version})",
+            export = self.exports.get_index(index).unwrap().0,
+            path = self.path.display(),
+        );
+
+        Some(index)
     }
 
     /// Finds a compatible instance export on the component for the given instance type.
+    ///
+    /// An export with the given name (or a semver-compatible version of it) is
+    /// preferred; otherwise, the first compatible instance export is used.
     fn find_compatible_export(
         &self,
+        name: &str,
         ty: &ComponentInstanceType,
         types: TypesRef,
     ) -> Option<ExportIndex> {
+        if let Some((index, export_ty)) = self.export_instance(name) {
+            if ComponentInstanceType::is_subtype_of(export_ty, self.types.as_ref(), ty, types) {
+                return Some(index);
+            }
+        }
+
         self.exports
             .iter()
             .position(|(_, (kind, index))| {
@@ -267,6 +366,58 @@ impl Component {
 
         true
     }
+
+    /// Determines if instances of this component have state.
+    ///
+    /// Returns a description of the state if the component (or any module
+    /// or component nested in it) defines a memory, a table, or a mutable
+    /// global.
+    fn state(&self) -> Result<Option<&'static str>> {
+        for payload in Parser::new(0).parse_all(&self.bytes) {
+            match payload? {
+                Payload::MemorySection(s) if s.get_count() > 0 => {
+                    return Ok(Some("defines a memory"))
+                }
+                Payload::TableSection(s) if s.get_count() > 0 => {
+                    return Ok(Some("defines a table"))
+                }
+                Payload::GlobalSection(s) => {
+                    for global in s {
+                        if global?.ty.mutable {
+                            return Ok(Some("defines a mutable global"));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Reads the fields of a `producers` section.
+fn read_producers(
+    data: &[u8],
+    offset: usize,
+) -> Result<IndexMap<String, IndexMap<String, String>>> {
+    let mut fields = IndexMap::new();
+    for field in ProducersSectionReader::new(data, offset)? {
+        let field = field?;
+        let values: &mut IndexMap<_, _> = fields.entry(field.name.to_string()).or_default();
+        for value in field.get_producer_field_values_reader()? {
+            let value = value?;
+            values.insert(value.name.to_string(), value.version.to_string());
+        }
+    }
+    Ok(fields)
+}
+
+/// Splits a name of the form `<name>@<version>` into the name and its
+/// semantic version.
+fn parse_versioned_name(name: &str) -> Option<(&str, Version)> {
+    let (base, version) = name.rsplit_once('@')?;
+    Some((base, Version::parse(version).ok()?))
 }
 
 impl std::fmt::Debug for Component {
@@ -328,6 +479,7 @@ struct InstantiationArg {
     export: Option<ExportIndex>,
 }
 
+#[derive(Default)]
 pub(crate) struct InstantiationGraph {
     /// The parsed components in the graph.
     components: IndexMap<String, Component>,
@@ -354,6 +506,43 @@ impl InstantiationGraph {
         }
     }
 
+    /// Gets the name of the component with the given index.
+    pub(crate) fn component_name(&self, index: ComponentIndex) -> &str {
+        self.components
+            .get_index(index.0)
+            .map(|(n, _)| n.as_str())
+            .expect("invalid component index")
+    }
+
+    /// Gets the root instance of the graph.
+    ///
+    /// The root instance is the first instantiation in the graph; its exports
+    /// are exported from the composed component.
+    pub(crate) fn root(&self) -> Option<InstanceIndex> {
+        self.instances
+            .node_indices()
+            .find(|i| matches!(self.instances[*i], Instance::Instantiation { .. }))
+    }
+
+    /// Gets the instances reachable from the root instance.
+    ///
+    /// An instance is reachable if it is the root instance or if it is an
+    /// argument of a reachable instance; only reachable instances contribute
+    /// to the exports of the composed component.
+    pub(crate) fn reachable_instances(&self) -> HashSet<InstanceIndex> {
+        let mut reachable = HashSet::new();
+        let mut stack = self.root().into_iter().collect::<Vec<_>>();
+        while let Some(instance) = stack.pop() {
+            if reachable.insert(instance) {
+                stack.extend(
+                    self.instances
+                        .neighbors_directed(instance, EdgeDirection::Incoming),
+                );
+            }
+        }
+        reachable
+    }
+
     /// Gets the set of import references for an imported instance.
     ///
     /// Returns `None` for instantiated instances.
@@ -414,6 +603,99 @@ impl InstantiationGraph {
         }
     }
 
+    /// Finds a compatible instance for the given instance type.
+    ///
+    /// Returns `Ok(None)` if the given instance itself is compatible.
+    /// Returns `Ok(Some(index))` if a compatible instance export from the instance was found.
+    /// Returns `Err(_)` if no compatible instance was found.
+    pub(crate) fn find_compatible_instance(
+        &self,
+        instance: InstanceIndex,
+        dependent: InstanceIndex,
+        arg_name: &str,
+        ty: &ComponentInstanceType,
+        types: TypesRef,
+    ) -> Result<Option<ExportIndex>> {
+        match self.component(instance) {
+            Some(component) => {
+                let instance_name = self.instance_name(instance);
+                let dependent_name = self.instance_name(dependent);
+
+                // Check if the instance or one of its exports is compatible with the expected import type
+                if component.is_subtype_of(ty, types) {
+                    // The instance itself can be used
+                    log::debug!(
+                        "instance `{instance_name}` can be used for argument `{arg_name}` of instance `{dependent_name}`",
+                    );
+                    return Ok(None);
+                }
+
+                log::debug!(
+                    "searching for compatible export from instance `{instance_name}` for argument `{arg_name}` of instance `{dependent_name}`",
+                );
+
+                let export = component.find_compatible_export(arg_name, ty, types) .ok_or_else(|| {
+                    anyhow!(
+                        "component `{path}` is not compatible with import `{arg_name}` of component `{dependent_path}`",
+                        path = component.path.display(),
+                        dependent_path = self.component(dependent).unwrap().path.display(),
+                    )
+                })?;
+
+                log::debug!(
+                    "export `{export_name}` (export index {export}) from instance `{instance_name}` can be used for argument `{arg_name}` of instance `{dependent_name}`",
+                    export = export.0,
+                    export_name = component.exports.get_index(export.0).unwrap().0,
+                );
+
+                Ok(Some(export))
+            }
+            None => {
+                // An imported instance should be directly compatible
+                Ok(None)
+            }
+        }
+    }
+
+    /// Resolves an explicitly specified export to its index.
+    ///
+    /// Returns an error if the export is not found or if it is not compatible with the given type.
+    pub(crate) fn resolve_export_index(
+        &self,
+        export: &str,
+        instance: InstanceIndex,
+        dependent: InstanceIndex,
+        arg_name: &str,
+        ty: &ComponentInstanceType,
+        types: TypesRef,
+    ) -> Result<ExportIndex> {
+        let instance_name = self.instance_name(instance);
+
+        match self.component(instance) {
+            Some(component) => match component.export_instance(export) {
+                Some((index, export_ty)) => {
+                    if !ComponentInstanceType::is_subtype_of(
+                        export_ty,
+                        component.types.as_ref(),
+                        ty,
+                        types,
+                    ) {
+                        bail!("component `{path}` exports an instance named `{export}` but it is not compatible with import `{arg_name}` of component `{dependent_path}`",
+                            path = component.path.display(),
+                            dependent_path = self.component(dependent).unwrap().path.display(),
+                        )
+                    }
+
+                    Ok(index)
+                }
+                None => bail!("component `{path}` does not export an instance named `{export}`",
+                    path = component.path.display(),
+                ),
+            },
+            None => bail!("an explicit export `{export}` cannot be specified for imported instance `{instance_name}`"),
+        }
+    }
+
     /// Resolves an import reference to its originating component, import name, and instance type.
     pub(crate) fn resolve_import(
         &self,
@@ -435,6 +717,142 @@ impl InstantiationGraph {
             _ => unreachable!("should not have an import ref to a non-instance import"),
         }
     }
+
+    /// Shares dependencies with identical contents in the graph.
+    ///
+    /// Components with identical bytes are defined only once, and instances
+    /// of such components that are instantiated with identical arguments are
+    /// merged into a single instance.
+    ///
+    /// Instances of components with state (e.g. a memory) are never merged
+    /// as their users would observe each other's changes; a warning is
+    /// emitted instead.
+    pub(crate) fn share_dependencies(&mut self) -> Result<()> {
+        // Map each component to the first component with identical contents
+        let canonical = self
+            .components
+            .iter()
+            .enumerate()
+            .map(|(i, (name, c))| {
+                let (index, (canonical, _)) = self
+                    .components
+                    .iter()
+                    .enumerate()
+                    .find(|(_, (_, o))| o.bytes == c.bytes && o.import_name == c.import_name)
+                    .unwrap();
+                if index != i {
+                    log::info!(
+                        "component `{name}` is identical to component `{canonical}` and will be shared"
+                    );
+                }
+                ComponentIndex(index)
+            })
+            .collect::<Vec<_>>();
+
+        for instance in self.instances.node_weights_mut() {
+            match instance {
+                Instance::Import(refs) => {
+                    *refs = refs
+                        .iter()
+                        .map(|r| ImportRef {
+                            component: canonical[r.component.0],
+                            import: r.import,
+                        })
+                        .collect();
+                }
+                Instance::Instantiation { component } => {
+                    *component = canonical[component.0];
+                }
+            }
+        }
+
+        // Merge instances until no more instances can be shared; merging
+        // instances may cause the arguments of their dependents to become
+        // identical
+        let root = self.root();
+        let mut warned = HashSet::new();
+        while let Some((keep, remove)) = self.find_shareable_instances(root, &mut warned)? {
+            log::info!(
+                "instance `{remove}` is identical to instance `{keep}` and will be shared",
+                keep = self.instance_name(keep),
+                remove = self.instance_name(remove),
+            );
+
+            let edges = self
+                .instances
+                .edges_directed(remove, EdgeDirection::Outgoing)
+                .map(|e| (e.target(), *e.weight()))
+                .collect::<Vec<_>>();
+            for (target, arg) in edges {
+                self.instances.add_edge(keep, target, arg);
+            }
+
+            // Removing a node moves the last node into its place, which
+            // keeps the names map in sync when using a swap removal; the
+            // index of the moved name must then be updated to match
+            self.instances.remove_node(remove);
+            self.names.swap_remove_index(remove.index());
+            if let Some((_, index)) = self.names.get_index_mut(remove.index()) {
+                *index = remove;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finds a pair of instances of the same component with identical
+    /// arguments, returning the instance to keep and the instance to remove.
+    fn find_shareable_instances(
+        &self,
+        root: Option<InstanceIndex>,
+        warned: &mut HashSet<(String, String)>,
+    ) -> Result<Option<(InstanceIndex, InstanceIndex)>> {
+        let candidates = self
+            .instances
+            .node_indices()
+            .filter(|i| Some(*i) != root)
+            .filter_map(|i| match self.instances[i] {
+                Instance::Import(_) => None,
+                Instance::Instantiation { component } => Some((i, component)),
+            })
+            .collect::<Vec<_>>();
+
+        for (n, (a, component)) in candidates.iter().enumerate() {
+            for (b, other) in &candidates[n + 1..] {
+                if component != other || self.arguments(*a) != self.arguments(*b) {
+                    continue;
+                }
+
+                let component = &self.components[component.0];
+                if let Some(reason) = component.state()? {
+                    let a = self.instance_name(*a).to_string();
+                    let b = self.instance_name(*b).to_string();
+                    if warned.insert((a.clone(), b.clone())) {
+                        log::warn!(
+                            "instances `{a}` and `{b}` of component `{path}` cannot be shared because the component {reason}",
+                            path = component.path.display(),
+                        );
+                    }
+                    continue;
+                }
+
+                return Ok(Some((*a, *b)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Gets the set of instantiation arguments of the given instance.
+    fn arguments(
+        &self,
+        instance: InstanceIndex,
+    ) -> HashSet<(InstanceIndex, ImportIndex, Option<ExportIndex>)> {
+        self.instances
+            .edges_directed(instance, EdgeDirection::Incoming)
+            .map(|e| (e.source(), e.weight().import, e.weight().export))
+            .collect()
+    }
 }
 
 /// An instance dependency to process in the instantiation graph.
@@ -452,12 +870,18 @@ struct Dependency {
 struct InstantiationGraphBuilder<'a> {
     /// The associated composition configuration.
     config: &'a Config,
+    /// The sources to fetch explicit dependencies from.
+    sources: &'a [Box<dyn ComponentSource + 'a>],
     /// The graph being built.
     graph: InstantiationGraph,
 }
 
 impl<'a> InstantiationGraphBuilder<'a> {
-    fn new(component: &Path, config: &'a Config) -> Result<Self> {
+    fn new(
+        component: &Path,
+        config: &'a Config,
+        sources: &'a [Box<dyn ComponentSource + 'a>],
+    ) -> Result<Self> {
         // The root component is always first in the map
         let mut components = IndexMap::new();
         components.insert(
@@ -467,6 +891,7 @@ impl<'a> InstantiationGraphBuilder<'a> {
 
         Ok(Self {
             config,
+            sources,
             graph: InstantiationGraph {
                 components,
                 instances: Default::default(),
@@ -510,11 +935,30 @@ impl<'a> InstantiationGraphBuilder<'a> {
                 "component with name `{name}` has an explicit path of `{path}`",
                 path = dep.path.display()
             );
-            return Ok(Some(Component::new(
-                index,
-                self.config.dir.join(&dep.path),
-                dep.import.clone(),
-            )?));
+            let reference = dep.path.to_string_lossy();
+            let mut component = None;
+            for source in self.sources {
+                if let Some(bytes) = source.fetch(&reference)? {
+                    component = Some(Component::from_source(
+                        index,
+                        &reference,
+                        &bytes,
+                        dep.import.clone(),
+                    )?);
+                    break;
+                }
+            }
+
+            let component = match component {
+                Some(component) => component,
+                None => Component::new(index, self.config.dir.join(&dep.path), dep.import.clone())?,
+            };
+
+            if let Some(digest) = &dep.digest {
+                verify_digest(name, &component.bytes, digest)?;
+            }
+
+            return Ok(Some(component));
         }
 
         // Otherwise, search the paths for a valid component with the same name
@@ -563,24 +1007,20 @@ impl<'a> InstantiationGraphBuilder<'a> {
             return Ok((*index, true));
         }
 
-        let instance = match self.add_component(component_name)? {
-            Some(component) => {
-                // If a dependency component was instantiated, mark it in the graph
-                if component.0 != 0 {
-                    self.graph.instantiated = true;
-                }
-                Instance::Instantiation { component }
-            }
-            None => {
-                if self.config.disallow_imports {
-                    bail!(
-                        "a dependency named `{component_name}` could not be found and instance imports are not allowed",
-                    );
-                }
+        let instance = if self.config.imports.iter().any(|i| i == name) {
+            let import = match import {
+                Some(import) => import,
+                None => bail!("the root instance cannot be imported"),
+            };
 
-                log::warn!("instance `{name}` will be imported because a dependency named `{component_name}` could not be found");
-                Instance::Import([import.unwrap()].into())
+            if self.config.disallow_imports {
+                bail!("instance `{name}` is configured to be imported but instance imports are not allowed");
             }
+
+            log::debug!("instance `{name}` will be imported as configured");
+            Instance::Import([import].into())
+        } else {
+            self.instantiate_dependency(name, component_name, import)?
         };
 
         let index = self.graph.instances.add_node(instance);
@@ -593,97 +1033,34 @@ impl<'a> InstantiationGraphBuilder<'a> {
         Ok((index, false))
     }
 
-    /// Finds a compatible instance for the given instance type.
+    /// Creates an instance of the dependency with the given name.
     ///
-    /// Returns `Ok(None)` if the given instance itself is compatible.
-    /// Returns `Ok(Some(index))` if a compatible instance export from the instance was found.
-    /// Returns `Err(_)` if no compatible instance was found.
-    fn find_compatible_instance(
-        &self,
-        instance: InstanceIndex,
-        dependent: InstanceIndex,
-        arg_name: &str,
-        ty: &ComponentInstanceType,
-        types: TypesRef,
-    ) -> Result<Option<ExportIndex>> {
-        match self.graph.component(instance) {
+    /// If the dependency cannot be located, the instance will be imported.
+    fn instantiate_dependency(
+        &mut self,
+        name: &str,
+        component_name: &str,
+        import: Option<ImportRef>,
+    ) -> Result<Instance> {
+        Ok(match self.add_component(component_name)? {
             Some(component) => {
-                let instance_name = self.graph.instance_name(instance);
-                let dependent_name = self.graph.instance_name(dependent);
-
-                // Check if the instance or one of its exports is compatible with the expected import type
-                if component.is_subtype_of(ty, types) {
-                    // The instance itself can be used
-                    log::debug!(
-                        "instance `{instance_name}` can be used for argument `{arg_name}` of instance `{dependent_name}`",
-                    );
-                    return Ok(None);
+                // If a dependency component was instantiated, mark it in the graph
+                if component.0 != 0 {
+                    self.graph.instantiated = true;
                 }
-
-                log::debug!(
-                    "searching for compatible export from instance `{instance_name}` for argument `{arg_name}` of instance `{dependent_name}`",
-                );
-
-                let export = component.find_compatible_export(ty, types) .ok_or_else(|| {
-                    anyhow!(
-                        "component `{path}` is not compatible with import `{arg_name}` of component `{dependent_path}`",
-                        path = component.path.display(),
-                        dependent_path = self.graph.component(dependent).unwrap().path.display(),
-                    )
-                })?;
-
-                log::debug!(
-                    "export `{export_name}` (export index {export}) from instance `{instance_name}` can be used for argument `{arg_name}` of instance `{dependent_name}`",
-                    export = export.0,
-                    export_name = component.exports.get_index(export.0).unwrap().0,
-                );
-
-                Ok(Some(export))
+                Instance::Instantiation { component }
             }
             None => {
-                // An imported instance should be directly compatible
-                Ok(None)
-            }
-        }
-    }
-
-    /// Resolves an explicitly specified export to its index.
-    ///
-    /// Returns an error if the export is not found or if it is not compatible with the given type.
-    fn resolve_export_index(
-        &self,
-        export: &str,
-        instance: InstanceIndex,
-        dependent: InstanceIndex,
-        arg_name: &str,
-        ty: &ComponentInstanceType,
-        types: TypesRef,
-    ) -> Result<ExportIndex> {
-        let instance_name = self.graph.instance_name(instance);
-
-        match self.graph.component(instance) {
-            Some(component) => match component.export_instance(export) {
-                Some((index, export_ty)) => {
-                    if !ComponentInstanceType::is_subtype_of(
-                        export_ty,
-                        component.types.as_ref(),
-                        ty,
-                        types,
-                    ) {
-                        bail!("component `{path}` exports an instance named `{export}` but it is not compatible with import `{arg_name}` of component `{dependent_path}`",
-                            path = component.path.display(),
-                            dependent_path = self.graph.component(dependent).unwrap().path.display(),
-                        )
-                    }
-
-                    Ok(index)
+                if self.config.disallow_imports {
+                    bail!(
+                        "a dependency named `{component_name}` could not be found and instance imports are not allowed",
+                    );
                 }
-                None => bail!("component `{path}` does not export an instance named `{export}`",
-                    path = component.path.display(),
-                ),
-            },
-            None => bail!("an explicit export `{export}` cannot be specified for imported instance `{instance_name}`"),
-        }
+
+                log::warn!("instance `{name}` will be imported because a dependency named `{component_name}` could not be found");
+                Instance::Import([import.unwrap()].into())
+            }
+        })
     }
 
     /// Processes a dependency in the graph.
@@ -707,7 +1084,7 @@ impl<'a> InstantiationGraphBuilder<'a> {
         let (dependent, import_name, import_type) = self.graph.resolve_import(dependency.import);
 
         let export = match &dependency.export {
-            Some(export) => Some(self.resolve_export_index(
+            Some(export) => Some(self.graph.resolve_export_index(
                 export,
                 instance,
                 dependency.dependent,
@@ -715,7 +1092,7 @@ impl<'a> InstantiationGraphBuilder<'a> {
                 import_type,
                 dependent.types.as_ref(),
             )?),
-            None => self.find_compatible_instance(
+            None => self.graph.find_compatible_instance(
                 instance,
                 dependency.dependent,
                 import_name,
@@ -820,6 +1197,13 @@ impl<'a> InstantiationGraphBuilder<'a> {
             }
         }
 
+        // Ensure every configured import is an instance in the graph
+        for name in &self.config.imports {
+            if !self.graph.names.contains_key(name) {
+                bail!("instance `{name}` is configured to be imported but it is not an argument of any instantiation");
+            }
+        }
+
         Ok(self.graph)
     }
 }
@@ -828,6 +1212,7 @@ impl<'a> InstantiationGraphBuilder<'a> {
 pub struct ComponentComposer<'a> {
     component: &'a Path,
     config: &'a Config,
+    sources: Vec<Box<dyn ComponentSource + 'a>>,
 }
 
 impl<'a> ComponentComposer<'a> {
@@ -837,21 +1222,30 @@ impl<'a> ComponentComposer<'a> {
     /// * `component` - The path to the component to compose.
     /// * `config` - The configuration to use for the composition.
     pub fn new(component: &'a Path, config: &'a Config) -> Self {
-        Self { component, config }
+        Self {
+            component,
+            config,
+            sources: Vec::new(),
+        }
     }
 
-    /// Composes a WebAssembly component based on the composer's configuration.
+    /// Adds a source to fetch explicit dependencies from.
     ///
-    /// ## Returns
-    /// Returns the bytes of the composed component.
-    pub fn compose(&self) -> Result<Vec<u8>> {
-        let graph = InstantiationGraphBuilder::new(self.component, self.config)?.build()?;
+    /// Sources are consulted in the order they are added; a dependency
+    /// that no source resolves is read from a local file.
+    pub fn with_source(mut self, source: impl ComponentSource + 'a) -> Self {
+        self.sources.push(Box::new(source));
+        self
+    }
 
-        log::debug!(
-            "components:\n{components:#?}\ninstantiation graph:\n{graph:?}",
-            components = graph.components,
-            graph = Dot::new(&graph.instances)
-        );
+    /// Builds the composition graph based on the composer's configuration
+    /// without encoding it.
+    ///
+    /// This can be used to inspect how the dependencies of the component
+    /// would be wired together.
+    pub fn graph(&self) -> Result<CompositionGraph> {
+        let mut graph =
+            InstantiationGraphBuilder::new(self.component, self.config, &self.sources)?.build()?;
 
         // If not a single dependency was instantiated, error out
         if !graph.instantiated {
@@ -861,6 +1255,483 @@ impl<'a> ComponentComposer<'a> {
             );
         }
 
-        InstantiationGraphEncoder::new(self.config, &graph).encode()
+        if self.config.share_dependencies {
+            graph.share_dependencies()?;
+        }
+
+        Ok(CompositionGraph { graph })
+    }
+
+    /// Composes a WebAssembly component based on the composer's configuration.
+    ///
+    /// ## Returns
+    /// Returns the bytes of the composed component.
+    pub fn compose(&self) -> Result<Vec<u8>> {
+        self.graph()?.encode()
+    }
+}
+
+/// Identifies a component added to a [`CompositionGraph`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ComponentId(ComponentIndex);
+
+/// Identifies an instance in a [`CompositionGraph`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct InstanceId(InstanceIndex);
+
+/// A graph of component instantiations that can be encoded as a composed
+/// component.
+///
+/// Unlike [`ComponentComposer`], which discovers dependencies on disk based on
+/// a [`Config`], the graph is built explicitly: components are added from
+/// bytes or files, instantiated, and the instantiation arguments connected.
+///
+/// The first instantiation in the graph is the root instance; its exports
+/// become the exports of the composed component.
+///
+/// # Examples
+///
+/// ```
+/// use wasm_compose::composer::CompositionGraph;
+///
+/// let mut graph = CompositionGraph::new();
+/// let root = graph.add_component(
+///     "root",
+///     r#"(component
+///         (import "a" (instance (export "f" (func))))
+///         (export "a" (instance 0))
+///     )"#,
+/// )?;
+/// let a = graph.add_component(
+///     "a",
+///     r#"(component
+///         (core module $m (func (export "f")))
+///         (core instance $i (instantiate $m))
+///         (func (export "f") (canon lift (core func $i "f")))
+///     )"#,
+/// )?;
+///
+/// let root = graph.instantiate("root", root)?;
+/// let a = graph.instantiate("a", a)?;
+/// graph.connect(a, None, root, "a")?;
+///
+/// let bytes = graph.encode()?;
+/// # let _ = bytes;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Default)]
+pub struct CompositionGraph {
+    graph: InstantiationGraph,
+}
+
+impl CompositionGraph {
+    /// Constructs a new, empty composition graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a component with the given name to the graph.
+    ///
+    /// The bytes may be either a binary component or its text format.
+    ///
+    /// Returns an error if the component is not valid or if a component with
+    /// the same name was already added.
+    pub fn add_component(&mut self, name: &str, bytes: impl AsRef<[u8]>) -> Result<ComponentId> {
+        let bytes = wat::parse_bytes(bytes.as_ref())
+            .with_context(|| format!("failed to parse component `{name}`"))?
+            .into_owned();
+        self.insert_component(name, |index| {
+            Component::from_bytes(index, name.into(), bytes, None)
+        })
+    }
+
+    /// Adds a component with the given name to the graph from a file.
+    ///
+    /// Returns an error if the component cannot be read, is not valid, or if
+    /// a component with the same name was already added.
+    pub fn add_component_file(
+        &mut self,
+        name: &str,
+        path: impl Into<PathBuf>,
+    ) -> Result<ComponentId> {
+        self.insert_component(name, |index| Component::new(index, path, None))
+    }
+
+    fn insert_component(
+        &mut self,
+        name: &str,
+        component: impl FnOnce(ComponentIndex) -> Result<Component>,
+    ) -> Result<ComponentId> {
+        if self.graph.components.contains_key(name) {
+            bail!("a component named `{name}` already exists in the graph");
+        }
+
+        let index = ComponentIndex(self.graph.components.len());
+        let component = component(index)?;
+        self.graph.components.insert(name.to_string(), component);
+        log::debug!(
+            "adding component `{name}` (component index {index})",
+            index = index.0
+        );
+        Ok(ComponentId(index))
+    }
+
+    /// Adds an instantiation of the given component to the graph.
+    ///
+    /// Every instance import of the component must be connected with
+    /// [`CompositionGraph::connect`] before the graph is encoded.
+    pub fn instantiate(&mut self, name: &str, component: ComponentId) -> Result<InstanceId> {
+        let comp = &self.graph.components[component.0 .0];
+        for (import, ty) in &comp.imports {
+            if !matches!(ty, ComponentTypeRef::Instance(_)) {
+                bail!(
+                    "component `{path}` has a non-instance import named `{import}`",
+                    path = comp.path.display()
+                );
+            }
+        }
+
+        self.add_instance(
+            name,
+            Instance::Instantiation {
+                component: component.0,
+            },
+        )
+    }
+
+    /// Gets the instance with the given name.
+    pub fn instance(&self, name: &str) -> Option<InstanceId> {
+        self.graph.names.get(name).map(|index| InstanceId(*index))
+    }
+
+    /// Gets the name of the given instance.
+    pub fn instance_name(&self, instance: InstanceId) -> &str {
+        self.graph.instance_name(instance.0)
+    }
+
+    /// Adds an instance to the graph that will be imported by the composed
+    /// component.
+    ///
+    /// The type of the imported instance is determined by the instantiation
+    /// arguments it is connected to.
+    pub fn import_instance(&mut self, name: &str) -> Result<InstanceId> {
+        self.add_instance(name, Instance::Import(IndexSet::new()))
+    }
+
+    fn add_instance(&mut self, name: &str, instance: Instance) -> Result<InstanceId> {
+        if self.graph.names.contains_key(name) {
+            bail!("an instance named `{name}` already exists in the graph");
+        }
+
+        let index = self.graph.instances.add_node(instance);
+        log::debug!(
+            "adding instance `{name}` to the graph (instance index {index})",
+            index = index.index()
+        );
+        assert_eq!(index.index(), self.graph.names.len());
+        self.graph.names.insert(name.to_string(), index);
+        Ok(InstanceId(index))
+    }
+
+    /// Connects an instance to an instance import of an instantiation.
+    ///
+    /// If `export` is `None`, either the `source` instance itself or the first
+    /// of its instance exports compatible with the import is used as the
+    /// argument; otherwise, the named instance export of `source` is used.
+    ///
+    /// Returns an error if the argument is not compatible with the import or
+    /// if the import is already connected.
+    pub fn connect(
+        &mut self,
+        source: InstanceId,
+        export: Option<&str>,
+        target: InstanceId,
+        import: &str,
+    ) -> Result<()> {
+        let target_name = self.graph.instance_name(target.0);
+        let component = match self.graph.instances[target.0] {
+            Instance::Import(_) => {
+                bail!("imported instance `{target_name}` cannot be instantiated with arguments")
+            }
+            Instance::Instantiation { component } => component,
+        };
+
+        let comp = &self.graph.components[component.0];
+        let import = match comp.imports.get_index_of(import) {
+            Some(index) => ImportRef {
+                component,
+                import: ImportIndex(index),
+            },
+            None => bail!(
+                "component `{path}` has no import named `{import}`",
+                path = comp.path.display()
+            ),
+        };
+
+        if self
+            .graph
+            .instances
+            .edges_directed(target.0, EdgeDirection::Incoming)
+            .any(|e| e.weight().import == import.import)
+        {
+            let (_, import_name, _) = self.graph.resolve_import(import);
+            bail!("argument `{import_name}` of instance `{target_name}` is already connected");
+        }
+
+        let (dependent, import_name, import_type) = self.graph.resolve_import(import);
+        let export = match export {
+            Some(export) => Some(self.graph.resolve_export_index(
+                export,
+                source.0,
+                target.0,
+                import_name,
+                import_type,
+                dependent.types.as_ref(),
+            )?),
+            None => self.graph.find_compatible_instance(
+                source.0,
+                target.0,
+                import_name,
+                import_type,
+                dependent.types.as_ref(),
+            )?,
+        };
+
+        if let Instance::Import(refs) = &mut self.graph.instances[source.0] {
+            refs.insert(import);
+        }
+
+        self.graph.instances.add_edge(
+            source.0,
+            target.0,
+            InstantiationArg {
+                import: import.import,
+                export,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Gets the connections between instances and instantiation arguments in
+    /// the graph.
+    pub fn connections(&self) -> Vec<Connection<'_>> {
+        self.graph
+            .instances
+            .edge_references()
+            .map(|e| {
+                let arg = e.weight();
+                let component = self.graph.component(e.target()).unwrap();
+                Connection {
+                    source: self.graph.instance_name(e.source()),
+                    export: self
+                        .graph
+                        .component(e.source())
+                        .zip(arg.export)
+                        .map(|(c, export)| c.export(export).0),
+                    target: self.graph.instance_name(e.target()),
+                    import: component.imports.get_index(arg.import.0).unwrap().0,
+                }
+            })
+            .collect()
+    }
+
+    /// Gets the names of the instances that will be imported by the composed
+    /// component.
+    pub fn imported_instances(&self) -> Vec<&str> {
+        self.graph
+            .instances
+            .node_indices()
+            .filter(|i| self.graph.import_refs(*i).is_some())
+            .map(|i| self.graph.instance_name(i))
+            .collect()
+    }
+
+    /// Gets the instantiation arguments that are not connected to an
+    /// instance, as pairs of instance name and import name.
+    pub fn unconnected_arguments(&self) -> Vec<(&str, &str)> {
+        let mut unconnected = Vec::new();
+        for instance in self.graph.instances.node_indices() {
+            if let Instance::Instantiation { component } = self.graph.instances[instance] {
+                let comp = &self.graph.components[component.0];
+                for (index, name) in comp.imports.keys().enumerate() {
+                    if !self
+                        .graph
+                        .instances
+                        .edges_directed(instance, EdgeDirection::Incoming)
+                        .any(|e| e.weight().import.0 == index)
+                    {
+                        unconnected.push((self.graph.instance_name(instance), name.as_str()));
+                    }
+                }
+            }
+        }
+        unconnected
+    }
+
+    /// Gets the names of the instances that are unreachable from the root
+    /// instance.
+    ///
+    /// The exports of unreachable instances are never used by the composed
+    /// component, so they are omitted when the graph is encoded, along with
+    /// any component definitions, imported instances, and instance types used
+    /// only by them. The definitions of the components that are kept are
+    /// encoded unchanged.
+    pub fn unreachable_instances(&self) -> Vec<&str> {
+        let reachable = self.graph.reachable_instances();
+        self.graph
+            .instances
+            .node_indices()
+            .filter(|i| !reachable.contains(i))
+            .map(|i| self.graph.instance_name(i))
+            .collect()
+    }
+
+    /// Renders the graph in the Graphviz DOT format.
+    ///
+    /// Instantiations are drawn as boxes and imported instances as dashed
+    /// ellipses; each edge is labeled with the argument it satisfies.
+    pub fn to_dot(&self) -> String {
+        fn escape(s: &str) -> String {
+            s.replace('\\', "\\\\").replace('"', "\\\"")
+        }
+
+        let mut dot = String::from("digraph {\n");
+        for instance in self.graph.instances.node_indices() {
+            let name = escape(self.graph.instance_name(instance));
+            match self.graph.component(instance) {
+                Some(_) => writeln!(
+                    dot,
+                    "    {index} [label=\"{name}\", shape=box];",
+                    index = instance.index()
+                ),
+                None => writeln!(
+                    dot,
+                    "    {index} [label=\"{name}\", style=dashed];",
+                    index = instance.index()
+                ),
+            }
+            .unwrap();
+        }
+        for (e, connection) in self
+            .graph
+            .instances
+            .edge_references()
+            .zip(self.connections())
+        {
+            writeln!(
+                dot,
+                "    {source} -> {target} [label=\"{label}\"];",
+                source = e.source().index(),
+                target = e.target().index(),
+                label = escape(&connection.label()),
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the graph as a Mermaid flowchart.
+    ///
+    /// Instantiations are drawn as rectangles and imported instances as
+    /// stadium shapes; each edge is labeled with the argument it satisfies.
+    pub fn to_mermaid(&self) -> String {
+        fn escape(s: &str) -> String {
+            s.replace('"', "#quot;")
+        }
+
+        let mut mermaid = String::from("flowchart LR\n");
+        for instance in self.graph.instances.node_indices() {
+            let name = escape(self.graph.instance_name(instance));
+            match self.graph.component(instance) {
+                Some(_) => writeln!(
+                    mermaid,
+                    "    i{index}[\"{name}\"]",
+                    index = instance.index()
+                ),
+                None => writeln!(
+                    mermaid,
+                    "    i{index}([\"{name}\"])",
+                    index = instance.index()
+                ),
+            }
+            .unwrap();
+        }
+        for (e, connection) in self
+            .graph
+            .instances
+            .edge_references()
+            .zip(self.connections())
+        {
+            writeln!(
+                mermaid,
+                "    i{source} -- \"{label}\" --> i{target}",
+                source = e.source().index(),
+                target = e.target().index(),
+                label = escape(&connection.label()),
+            )
+            .unwrap();
+        }
+        mermaid
+    }
+
+    /// Encodes the graph as a composed component.
+    ///
+    /// Returns an error if the graph has no instantiations, if an instance
+    /// import of an instantiation is not connected, or if the instantiations
+    /// form a cycle.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        if self.graph.root().is_none() {
+            bail!("the composition graph does not contain any instantiations");
+        }
+
+        if let Some((instance, import)) = self.unconnected_arguments().first() {
+            bail!("argument `{import}` of instance `{instance}` is not connected");
+        }
+
+        for instance in self.graph.instances.node_indices() {
+            if let Some(refs) = self.graph.import_refs(instance) {
+                if refs.is_empty() {
+                    bail!(
+                        "imported instance `{name}` is not connected to any instantiation",
+                        name = self.graph.instance_name(instance)
+                    );
+                }
+            }
+        }
+
+        log::debug!(
+            "components:\n{components:#?}\ninstantiation graph:\n{graph:?}",
+            components = self.graph.components,
+            graph = Dot::new(&self.graph.instances)
+        );
+
+        InstantiationGraphEncoder::new(&self.graph).encode()
+    }
+}
+
+/// A connection in a [`CompositionGraph`] from an instance, or one of its
+/// exports, to an instantiation argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Connection<'a> {
+    /// The name of the instance providing the argument.
+    pub source: &'a str,
+    /// The name of the instance export used as the argument.
+    ///
+    /// This is `None` if the source instance itself is the argument.
+    pub export: Option<&'a str>,
+    /// The name of the instance being instantiated.
+    pub target: &'a str,
+    /// The name of the import satisfied by the argument.
+    pub import: &'a str,
+}
+
+impl Connection<'_> {
+    fn label(&self) -> String {
+        match self.export {
+            Some(export) => format!("{export} -> {import}", import = self.import),
+            None => self.import.to_string(),
+        }
     }
 }
@@ -14,8 +14,18 @@ use std::{
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct Dependency {
     /// The path to the dependency's component file.
+    ///
+    /// If a component source of the composer resolves the path as a
+    /// reference (e.g. a URL), the component is fetched from that source.
     pub path: PathBuf,
 
+    /// The expected digest of the binary encoding of the dependency's
+    /// component (e.g. `sha256:<hex digest>`).
+    ///
+    /// If present, the component is verified to match the digest.
+    #[serde(default)]
+    pub digest: Option<String>,
+
     /// The name to import the component with.
     ///
     /// By default, components are defined (embedded) in the composed component.
@@ -29,6 +39,7 @@ impl FromStr for Dependency {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(Self {
             path: s.into(),
+            digest: None,
             import: None,
         })
     }
@@ -103,6 +114,21 @@ pub struct Config {
     #[serde(default)]
     pub disallow_imports: bool,
 
+    /// Whether or not to share dependencies with identical contents.
+    ///
+    /// Identical components are defined only once in the output component
+    /// and their instances are shared when instantiated with identical
+    /// arguments, unless the component has state.
+    #[serde(default)]
+    pub share_dependencies: bool,
+
+    /// The names of instances to import in the output component.
+    ///
+    /// These instances are imported rather than instantiated, even if a
+    /// dependency of the same name can be located.
+    #[serde(default)]
+    pub imports: Vec<String>,
+
     /// The explicit, transitive dependencies of the root component.
     #[serde(default, deserialize_with = "de::index_map")]
     pub dependencies: IndexMap<String, Dependency>,
@@ -1,8 +1,5 @@
-use crate::{
-    composer::{
-        Component, ComponentIndex, ExportIndex, ImportRef, InstanceIndex, InstantiationGraph,
-    },
-    config::Config,
+use crate::composer::{
+    Component, ComponentIndex, ExportIndex, ImportRef, InstanceIndex, InstantiationGraph,
 };
 use anyhow::{bail, Result};
 use indexmap::{IndexMap, IndexSet};
@@ -208,6 +205,7 @@ impl<'a> TypeEncoder<'a> {
     fn table_type(ty: wasmparser::TableType) -> TableType {
         TableType {
             element_type: Self::val_type(ty.element_type),
+            table64: ty.table64,
             minimum: ty.initial,
             maximum: ty.maximum,
         }
@@ -631,8 +629,6 @@ impl<'a> TypeEncoder<'a> {
 
 /// Used to encode an instantiation graph.
 pub(crate) struct InstantiationGraphEncoder<'a> {
-    /// The associated composition configuration.
-    config: &'a Config,
     /// The graph being encoded.
     graph: &'a InstantiationGraph,
     /// Map from graph component index to encoded component index.
@@ -659,9 +655,8 @@ pub(crate) struct InstantiationGraphEncoder<'a> {
 
 impl<'a> InstantiationGraphEncoder<'a> {
     /// Create a new encoder for the given graph.
-    pub(crate) fn new(config: &'a Config, graph: &'a InstantiationGraph) -> Self {
+    pub(crate) fn new(graph: &'a InstantiationGraph) -> Self {
         Self {
-            config,
             graph,
             component_indexes: Default::default(),
             instance_indexes: Default::default(),
@@ -680,8 +675,18 @@ impl<'a> InstantiationGraphEncoder<'a> {
     pub(crate) fn encode(mut self) -> Result<Vec<u8>> {
         let mut encoded = wasm_encoder::Component::new();
 
-        // Encode the instances from the graph
+        // Encode the instances from the graph, skipping any instance that
+        // does not contribute to the exports of the root instance
+        let reachable = self.graph.reachable_instances();
         for instance in self.graph.instantiation_order()? {
+            if !reachable.contains(&instance) {
+                log::info!(
+                    "instance `{name}` is unreachable from the root instance and will not be encoded",
+                    name = self.graph.instance_name(instance)
+                );
+                continue;
+            }
+
             if let Some(component) = self.graph.component(instance) {
                 self.encode_instantiation(instance, component, &mut encoded)?;
                 continue;
@@ -698,6 +703,9 @@ impl<'a> InstantiationGraphEncoder<'a> {
         // Encode the exports from the root component
         self.encode_exports(&mut encoded)?;
 
+        // Encode the producers of the composed component
+        self.encode_producers(&mut encoded);
+
         Ok(encoded.finish())
     }
 
@@ -797,7 +805,7 @@ impl<'a> InstantiationGraphEncoder<'a> {
         encoded: &mut wasm_encoder::Component,
     ) -> Result<()> {
         let instance_name = self.graph.instance_name(instance);
-        let dependency = self.config.dependency_name(instance_name);
+        let dependency = self.graph.component_name(component.index());
 
         // Encode the instance's component if it hasn't been encoded already
         let component_index = match self.component_indexes.entry(component.index()) {
@@ -897,14 +905,55 @@ impl<'a> InstantiationGraphEncoder<'a> {
         Ok(())
     }
 
+    /// Encode the `producers` section of the composed component.
+    ///
+    /// The producers of the root component are propagated to the composed
+    /// component, with `wasm-compose` added to the `processed-by` field; the
+    /// sections of every composed component are otherwise preserved as-is
+    /// in their definitions.
+    fn encode_producers(&self, encoded: &mut wasm_encoder::Component) {
+        let mut producers = self
+            .graph
+            .root()
+            .and_then(|root| self.graph.component(root))
+            .map(|root| root.producers().clone())
+            .unwrap_or_default();
+
+        producers
+            .entry("processed-by".to_string())
+            .or_default()
+            .insert(
+                env!("CARGO_PKG_NAME").to_string(),
+                env!("CARGO_PKG_VERSION").to_string(),
+            );
+
+        let mut data = Vec::new();
+        producers.len().encode(&mut data);
+        for (field, values) in &producers {
+            field.as_str().encode(&mut data);
+            values.len().encode(&mut data);
+            for (name, version) in values {
+                name.as_str().encode(&mut data);
+                version.as_str().encode(&mut data);
+            }
+        }
+
+        encoded.section(&CustomSection {
+            name: "producers",
+            data: &data,
+        });
+    }
+
     /// Encode the exports of the composed component.
     ///
-    /// This always exports everything from the root (index 0) component.
+    /// This always exports everything from the root instance.
     fn encode_exports(&mut self, encoded: &mut wasm_encoder::Component) -> Result<()> {
         let mut exports = ComponentExportSection::new();
 
-        // The root instance is always the first node in the graph
-        let instance = InstanceIndex::new(0);
+        let instance = self
+            .graph
+            .root()
+            .expect("graph should have a root instance");
         let component = self.graph.component(instance).unwrap();
         let instance_index = self.instance_indexes[&instance];
 
@@ -7,3 +7,4 @@ pub mod cli;
 pub mod composer;
 pub mod config;
 pub(crate) mod encoding;
+pub mod source;
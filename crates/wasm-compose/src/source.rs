@@ -0,0 +1,104 @@
+//! Module for fetching the components used in a composition.
+
+use anyhow::{bail, Context, Result};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// A source of components for a composition.
+///
+/// Component sources resolve the references of explicit dependencies in the
+/// composition configuration (e.g. an OCI reference, an HTTP URL, or a
+/// content digest) to the bytes of the component.
+///
+/// Sources are consulted in the order they are added to the composer; if no
+/// source resolves a reference, it is treated as a path to a local file.
+pub trait ComponentSource {
+    /// Fetches the bytes of the component with the given reference.
+    ///
+    /// Returns `Ok(None)` if the source does not handle the reference.
+    ///
+    /// The bytes may be either a binary or text format component.
+    fn fetch(&self, reference: &str) -> Result<Option<Vec<u8>>>;
+}
+
+/// A content-addressed component store on the local file system.
+///
+/// The store resolves references of the form `sha256:<hex digest>` to the
+/// file `<dir>/sha256/<hex digest>` and verifies the component in the file
+/// matches the digest.
+///
+/// As with the `digest` of a dependency in the configuration, the digest is
+/// of the binary format of the component, so a file in the text format is
+/// stored under the digest of its binary.
+#[derive(Debug, Clone)]
+pub struct ContentStore {
+    dir: PathBuf,
+}
+
+impl ContentStore {
+    /// Creates a new content store rooted at the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Gets the root directory of the store.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl ComponentSource for ContentStore {
+    fn fetch(&self, reference: &str) -> Result<Option<Vec<u8>>> {
+        let hex = match reference.strip_prefix("sha256:") {
+            Some(hex) => hex,
+            None => return Ok(None),
+        };
+
+        if hex.is_empty() || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            bail!("invalid content digest `{reference}`");
+        }
+
+        let path = self.dir.join("sha256").join(hex.to_ascii_lowercase());
+        log::info!(
+            "fetching component `{reference}` from content store `{path}`",
+            path = path.display()
+        );
+
+        let bytes = std::fs::read(&path).with_context(|| {
+            format!(
+                "failed to read component `{reference}` from file `{path}`",
+                path = path.display()
+            )
+        })?;
+        let bytes = wat::parse_bytes(&bytes)
+            .with_context(|| {
+                format!(
+                    "failed to parse component `{reference}` from file `{path}`",
+                    path = path.display()
+                )
+            })?
+            .into_owned();
+
+        verify_digest(reference, &bytes, reference)?;
+        Ok(Some(bytes))
+    }
+}
+
+/// Verifies the given bytes of a component match the expected digest.
+///
+/// The bytes are expected to be in the binary format, which is the form that
+/// digests are computed over, and the digest is expected to be of the form
+/// `sha256:<hex digest>`.
+pub fn verify_digest(name: &str, bytes: &[u8], digest: &str) -> Result<()> {
+    let expected = match digest.strip_prefix("sha256:") {
+        Some(hex) => hex.to_ascii_lowercase(),
+        None => bail!("unsupported digest `{digest}` for component `{name}`: only `sha256` digests are supported"),
+    };
+
+    let actual = format!("{:x}", Sha256::digest(bytes));
+    if actual != expected {
+        bail!("component `{name}` has digest `sha256:{actual}` but digest `{digest}` was expected");
+    }
+
+    Ok(())
+}
@@ -65,6 +65,7 @@ fn component_composing() -> Result<()> {
 
             Validator::new_with_features(WasmFeatures {
                 component_model: true,
+                component_model_async: true,
                 ..Default::default()
             })
             .validate_all(&bytes)
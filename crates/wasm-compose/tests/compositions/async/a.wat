@@ -0,0 +1,17 @@
+(component
+  (type $t (func (result u32)))
+  (core func $return (canon task.return $t))
+  (core module $m
+    (import "" "return" (func (param i32)))
+    (func (export "f") (param i32) (result i32) unreachable)
+    (func (export "cb") (param i32 i32 i32) (result i32) unreachable)
+  )
+  (core instance $i (instantiate $m
+    (with "" (instance (export "return" (func $return))))
+  ))
+  (func $f (param "x" u32) (result u32)
+    (canon lift (core func $i "f") async (callback (func $i "cb")))
+  )
+  (instance (export "f" (func $f)))
+  (export "a" (instance 0))
+)
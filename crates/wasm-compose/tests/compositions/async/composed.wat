@@ -0,0 +1,74 @@
+(component
+  (component (;0;)
+    (type (;0;) (func (result u32)))
+    (core func (;0;) (canon task.return 0))
+    (core module (;0;) $m
+      (type (;0;) (func (param i32)))
+      (type (;1;) (func (param i32) (result i32)))
+      (type (;2;) (func (param i32 i32 i32) (result i32)))
+      (import "" "return" (func (;0;) (type 0)))
+      (func (;1;) (type 1) (param i32) (result i32)
+        unreachable
+      )
+      (func (;2;) (type 2) (param i32 i32 i32) (result i32)
+        unreachable
+      )
+      (export "f" (func 1))
+      (export "cb" (func 2))
+    )
+    (core instance (;0;) 
+      (export "return" (func 0))
+    )
+    (core instance (;1;) (instantiate 0
+        (with "" (instance 0))
+      )
+    )
+    (type (;1;) (func (param "x" u32) (result u32)))
+    (alias core export 1 "f" (core func (;1;)))
+    (alias core export 1 "cb" (core func (;2;)))
+    (func (;0;) (type 1) (canon lift (core func 1) async (callback 2)))
+    (instance (;0;)
+      (export "f" (func 0))
+    )
+    (export "a" (instance 0))
+  )
+  (instance (;0;) (instantiate 0))
+  (component (;1;)
+    (type (;0;) 
+      (instance
+        (type (;0;) (func (param "x" u32) (result u32)))
+        (export "f" (func (type 0)))
+      )
+    )
+    (import "a" (instance (;0;) (type 0)))
+    (alias export 0 "f" (func (;0;)))
+    (core module (;0;) $libc
+      (memory (;0;) 1)
+      (export "memory" (memory 0))
+    )
+    (core instance (;0;) (instantiate 0))
+    (alias core export 0 "memory" (core memory (;0;)))
+    (core func (;0;) (canon lower (func 0) async (memory 0)))
+    (alias core export 0 "memory" (core memory (;1;)))
+    (core func (;1;) (canon task.wait async (memory 1)))
+    (core module (;1;) $m
+      (type (;0;) (func (param i32 i32) (result i32)))
+      (type (;1;) (func (param i32) (result i32)))
+      (import "" "f" (func (;0;) (type 0)))
+      (import "" "wait" (func (;1;) (type 1)))
+    )
+    (core instance (;1;) 
+      (export "f" (func 0))
+      (export "wait" (func 1))
+    )
+    (core instance (;2;) (instantiate 1
+        (with "" (instance 1))
+      )
+    )
+  )
+  (alias export 0 "a" (instance (;1;)))
+  (instance (;2;) (instantiate 1
+      (with "a" (instance 1))
+    )
+  )
+)
@@ -0,0 +1,15 @@
+(component
+  (import "a" (instance $a (export "f" (func (param "x" u32) (result u32)))))
+  (alias export $a "f" (func $f))
+  (core module $libc (memory (export "memory") 1))
+  (core instance $libc (instantiate $libc))
+  (core func $f (canon lower (func $f) async (memory $libc "memory")))
+  (core func $wait (canon task.wait async (memory $libc "memory")))
+  (core module $m
+    (import "" "f" (func (param i32 i32) (result i32)))
+    (import "" "wait" (func (param i32) (result i32)))
+  )
+  (core instance (instantiate $m
+    (with "" (instance (export "f" (func $f)) (export "wait" (func $wait))))
+  ))
+)
@@ -0,0 +1,6 @@
+(component
+  (import "b" (instance (export "g" (func))))
+  (core module $m (func (export "f")))
+  (core instance $i (instantiate $m))
+  (func (export "f") (canon lift (core func $i "f")))
+)
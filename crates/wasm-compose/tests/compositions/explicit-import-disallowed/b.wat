@@ -0,0 +1,5 @@
+(component
+  (core module $m (func (export "g")))
+  (core instance $i (instantiate $m))
+  (func (export "g") (canon lift (core func $i "g")))
+)
@@ -0,0 +1,4 @@
+(component
+  (import "a" (instance (export "f" (func))))
+  (import "b" (instance (export "g" (func))))
+)
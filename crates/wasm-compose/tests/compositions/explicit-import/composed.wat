@@ -0,0 +1,53 @@
+(component
+  (type (;0;) 
+    (instance
+      (type (;0;) (func))
+      (export "g" (func (type 0)))
+    )
+  )
+  (import "b" (instance (;0;) (type 0)))
+  (component (;0;)
+    (type (;0;) 
+      (instance
+        (type (;0;) (func))
+        (export "g" (func (type 0)))
+      )
+    )
+    (import "b" (instance (;0;) (type 0)))
+    (core module (;0;) $m
+      (type (;0;) (func))
+      (func (;0;) (type 0))
+      (export "f" (func 0))
+    )
+    (core instance (;0;) (instantiate 0))
+    (type (;1;) (func))
+    (alias core export 0 "f" (core func (;0;)))
+    (func (;0;) (type 1) (canon lift (core func 0)))
+    (export "f" (func 0))
+  )
+  (instance (;1;) (instantiate 0
+      (with "b" (instance 0))
+    )
+  )
+  (component (;1;)
+    (type (;0;) 
+      (instance
+        (type (;0;) (func))
+        (export "f" (func (type 0)))
+      )
+    )
+    (import "a" (instance (;0;) (type 0)))
+    (type (;1;) 
+      (instance
+        (type (;0;) (func))
+        (export "g" (func (type 0)))
+      )
+    )
+    (import "b" (instance (;1;) (type 1)))
+  )
+  (instance (;2;) (instantiate 1
+      (with "b" (instance 0))
+      (with "a" (instance 1))
+    )
+  )
+)
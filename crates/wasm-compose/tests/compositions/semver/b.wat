@@ -0,0 +1,10 @@
+(component
+  (type (instance (export "f" (func)) (export "g" (func))))
+  (import "i" (instance (type 0)))
+  (export "unversioned" (instance 0))
+  (export "pkg:iface@0.9.0" (instance 0))
+  (export "pkg:iface@1.0.3" (instance 0))
+  (export "pkg:iface@1.0.2" (instance 0))
+  (export "pkg:other@0.3.0" (instance 0))
+  (export "pkg:other@0.2.5" (instance 0))
+)
@@ -0,0 +1,55 @@
+(component
+  (type (;0;) 
+    (instance
+      (type (;0;) (func))
+      (export "f" (func (type 0)))
+      (type (;1;) (func))
+      (export "g" (func (type 1)))
+    )
+  )
+  (import "i" (instance (;0;) (type 0)))
+  (component (;0;)
+    (type (;0;) 
+      (instance
+        (type (;0;) (func))
+        (export "f" (func (type 0)))
+        (type (;1;) (func))
+        (export "g" (func (type 1)))
+      )
+    )
+    (import "i" (instance (;0;) (type 0)))
+    (export "unversioned" (instance 0))
+    (export "pkg:iface@0.9.0" (instance 0))
+    (export "pkg:iface@1.0.3" (instance 0))
+    (export "pkg:iface@1.0.2" (instance 0))
+    (export "pkg:other@0.3.0" (instance 0))
+    (export "pkg:other@0.2.5" (instance 0))
+  )
+  (instance (;1;) (instantiate 0
+      (with "i" (instance 0))
+    )
+  )
+  (component (;1;)
+    (type (;0;) 
+      (instance
+        (type (;0;) (func))
+        (export "f" (func (type 0)))
+      )
+    )
+    (import "pkg:iface@1.0.1" (instance (;0;) (type 0)))
+    (type (;1;) 
+      (instance
+        (type (;0;) (func))
+        (export "g" (func (type 0)))
+      )
+    )
+    (import "pkg:other@0.2.0" (instance (;1;) (type 1)))
+  )
+  (alias export 1 "pkg:other@0.2.5" (instance (;2;)))
+  (alias export 1 "pkg:iface@1.0.3" (instance (;3;)))
+  (instance (;4;) (instantiate 1
+      (with "pkg:other@0.2.0" (instance 2))
+      (with "pkg:iface@1.0.1" (instance 3))
+    )
+  )
+)
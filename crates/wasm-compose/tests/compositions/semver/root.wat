@@ -0,0 +1,4 @@
+(component
+  (import "pkg:iface@1.0.1" (instance (export "f" (func))))
+  (import "pkg:other@0.2.0" (instance (export "g" (func))))
+)
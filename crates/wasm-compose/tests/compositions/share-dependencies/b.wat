@@ -0,0 +1,5 @@
+(component
+  (import "a" (instance $a (export "f" (func))))
+  (alias export $a "f" (func $f))
+  (export "f" (func $f))
+)
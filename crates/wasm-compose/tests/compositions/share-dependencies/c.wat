@@ -0,0 +1,9 @@
+(component
+  (core module $m
+    (memory 1)
+    (func (export "g"))
+  )
+  (core instance $i (instantiate $m))
+  (func $g (canon lift (core func $i "g")))
+  (export "g" (func $g))
+)
@@ -0,0 +1,82 @@
+(component
+  (component (;0;)
+    (core module (;0;) $m
+      (type (;0;) (func))
+      (func (;0;) (type 0))
+      (memory (;0;) 1)
+      (export "g" (func 0))
+    )
+    (core instance (;0;) (instantiate 0))
+    (type (;0;) (func))
+    (alias core export 0 "g" (core func (;0;)))
+    (func (;0;) (type 0) (canon lift (core func 0)))
+    (export "g" (func 0))
+  )
+  (instance (;0;) (instantiate 0))
+  (instance (;1;) (instantiate 0))
+  (component (;1;)
+    (core module (;0;) $m
+      (type (;0;) (func))
+      (func (;0;) (type 0))
+      (export "f" (func 0))
+    )
+    (core instance (;0;) (instantiate 0))
+    (type (;0;) (func))
+    (alias core export 0 "f" (core func (;0;)))
+    (func (;0;) (type 0) (canon lift (core func 0)))
+    (export "f" (func 0))
+  )
+  (instance (;2;) (instantiate 1))
+  (component (;2;)
+    (type (;0;) 
+      (instance
+        (type (;0;) (func))
+        (export "f" (func (type 0)))
+      )
+    )
+    (import "a" (instance (;0;) (type 0)))
+    (alias export 0 "f" (func (;0;)))
+    (export "f" (func 0))
+  )
+  (instance (;3;) (instantiate 2
+      (with "a" (instance 2))
+    )
+  )
+  (component (;3;)
+    (type (;0;) 
+      (instance
+        (type (;0;) (func))
+        (export "f" (func (type 0)))
+      )
+    )
+    (import "b1" (instance (;0;) (type 0)))
+    (type (;1;) 
+      (instance
+        (type (;0;) (func))
+        (export "f" (func (type 0)))
+      )
+    )
+    (import "b2" (instance (;1;) (type 1)))
+    (type (;2;) 
+      (instance
+        (type (;0;) (func))
+        (export "g" (func (type 0)))
+      )
+    )
+    (import "c1" (instance (;2;) (type 2)))
+    (type (;3;) 
+      (instance
+        (type (;0;) (func))
+        (export "g" (func (type 0)))
+      )
+    )
+    (import "c2" (instance (;3;) (type 3)))
+  )
+  (instance (;4;) (instantiate 3
+      (with "b2" (instance 3))
+      (with "c2" (instance 0))
+      (with "c1" (instance 1))
+      (with "b1" (instance 3))
+    )
+  )
+)
@@ -0,0 +1,6 @@
+(component
+  (import "b1" (instance (export "f" (func))))
+  (import "b2" (instance (export "f" (func))))
+  (import "c1" (instance (export "g" (func))))
+  (import "c2" (instance (export "g" (func))))
+)
@@ -0,0 +1,416 @@
+use anyhow::Result;
+use pretty_assertions::assert_eq;
+use std::fs;
+use std::path::Path;
+use wasm_compose::{
+    composer::{ComponentComposer, CompositionGraph, Connection},
+    config::Config,
+};
+use wasmparser::{Validator, WasmFeatures};
+
+fn validate(bytes: &[u8]) -> Result<()> {
+    Validator::new_with_features(WasmFeatures {
+        component_model: true,
+        component_model_async: true,
+        ..Default::default()
+    })
+    .validate_all(bytes)?;
+    Ok(())
+}
+
+/// Builds the same composition as the `complex` configuration-based test case
+/// and expects the same output.
+#[test]
+fn matches_configured_composition() -> Result<()> {
+    let dir = "tests/compositions/complex";
+    let mut graph = CompositionGraph::new();
+    let root = graph.add_component_file("$component", format!("{dir}/root.wat"))?;
+    let a = graph.add_component_file("a", format!("{dir}/a.wat"))?;
+    let b = graph.add_component_file("b", format!("{dir}/b.wat"))?;
+
+    let root = graph.instantiate("$component", root)?;
+    let b1 = graph.instantiate("b1", b)?;
+    let b2 = graph.instantiate("b2", b)?;
+    let a = graph.instantiate("a", a)?;
+    graph.connect(b1, None, root, "b1")?;
+    graph.connect(b2, Some("x"), root, "b2")?;
+    graph.connect(a, None, b1, "a")?;
+    graph.connect(a, None, b2, "a")?;
+
+    let bytes = graph.encode()?;
+    validate(&bytes)?;
+    assert_eq!(
+        fs::read_to_string(format!("{dir}/composed.wat"))?
+            .replace("\r\n", "\n")
+            .trim(),
+        wasmprinter::print_bytes(&bytes)?,
+    );
+    Ok(())
+}
+
+#[test]
+fn imported_instances() -> Result<()> {
+    let mut graph = CompositionGraph::new();
+    let root = graph.add_component(
+        "root",
+        r#"(component
+            (import "a" (instance (export "f" (func))))
+            (import "b" (instance (export "f" (func))))
+        )"#,
+    )?;
+    let root = graph.instantiate("root", root)?;
+    let imported = graph.import_instance("imported")?;
+    graph.connect(imported, None, root, "a")?;
+    graph.connect(imported, None, root, "b")?;
+
+    let bytes = graph.encode()?;
+    validate(&bytes)?;
+    let text = wasmprinter::print_bytes(&bytes)?;
+    assert!(text.contains("(import \"imported\" (instance"), "{text}");
+    assert_eq!(text.matches("\n  (import ").count(), 1, "{text}");
+    Ok(())
+}
+
+#[test]
+fn errors() -> Result<()> {
+    let mut graph = CompositionGraph::new();
+    assert_eq!(
+        graph.encode().unwrap_err().to_string(),
+        "the composition graph does not contain any instantiations"
+    );
+
+    let root = graph.add_component(
+        "root",
+        r#"(component
+            (import "a" (instance (export "f" (func))))
+        )"#,
+    )?;
+    let empty_component = graph.add_component("empty", "(component)")?;
+    assert_eq!(
+        graph
+            .add_component("empty", "(component)")
+            .unwrap_err()
+            .to_string(),
+        "a component named `empty` already exists in the graph"
+    );
+    assert!(graph.add_component("bad", "(module)").is_err());
+
+    let root = graph.instantiate("root", root)?;
+    let empty = graph.instantiate("empty", empty_component)?;
+    assert_eq!(
+        graph
+            .instantiate("root", empty_component)
+            .unwrap_err()
+            .to_string(),
+        "an instance named `root` already exists in the graph"
+    );
+
+    assert_eq!(graph.unconnected_arguments(), [("root", "a")]);
+    assert_eq!(
+        graph.encode().unwrap_err().to_string(),
+        "argument `a` of instance `root` is not connected"
+    );
+    assert_eq!(
+        graph
+            .connect(empty, None, root, "b")
+            .unwrap_err()
+            .to_string(),
+        "component `root` has no import named `b`"
+    );
+    assert_eq!(
+        graph
+            .connect(empty, None, root, "a")
+            .unwrap_err()
+            .to_string(),
+        "component `empty` is not compatible with import `a` of component `root`"
+    );
+
+    let imported = graph.import_instance("imported")?;
+    assert_eq!(
+        graph
+            .connect(root, None, imported, "a")
+            .unwrap_err()
+            .to_string(),
+        "imported instance `imported` cannot be instantiated with arguments"
+    );
+    graph.connect(imported, None, root, "a")?;
+    assert_eq!(
+        graph
+            .connect(imported, None, root, "a")
+            .unwrap_err()
+            .to_string(),
+        "argument `a` of instance `root` is already connected"
+    );
+
+    graph.import_instance("unused")?;
+    assert_eq!(
+        graph.encode().unwrap_err().to_string(),
+        "imported instance `unused` is not connected to any instantiation"
+    );
+    Ok(())
+}
+
+#[test]
+fn cycle() -> Result<()> {
+    let mut graph = CompositionGraph::new();
+    let c = graph.add_component(
+        "c",
+        r#"(component
+            (import "in" (instance (export "f" (func))))
+            (alias export 0 "f" (func))
+            (export "f" (func 0))
+        )"#,
+    )?;
+    let x = graph.instantiate("x", c)?;
+    let y = graph.instantiate("y", c)?;
+    graph.connect(x, None, y, "in")?;
+    graph.connect(y, None, x, "in")?;
+    assert!(graph
+        .encode()
+        .unwrap_err()
+        .to_string()
+        .contains("form a cycle in the instantiation graph"));
+    Ok(())
+}
+
+#[test]
+fn analysis() -> Result<()> {
+    let dir = "tests/compositions/merged-import";
+    let config = Config {
+        dir: dir.into(),
+        ..Default::default()
+    };
+    let root = format!("{dir}/root.wat");
+    let graph = ComponentComposer::new(root.as_ref(), &config).graph()?;
+
+    let connection = |source, target, import| Connection {
+        source,
+        export: None,
+        target,
+        import,
+    };
+    assert_eq!(
+        graph.connections(),
+        [
+            connection("a", "$component", "a"),
+            connection("b", "$component", "b"),
+            connection("a", "b", "a"),
+        ]
+    );
+    assert_eq!(graph.imported_instances(), ["a"]);
+    assert!(graph.unconnected_arguments().is_empty());
+
+    assert_eq!(
+        graph.to_dot(),
+        r#"digraph {
+    0 [label="$component", shape=box];
+    1 [label="a", style=dashed];
+    2 [label="b", shape=box];
+    1 -> 0 [label="a"];
+    2 -> 0 [label="b"];
+    1 -> 2 [label="a"];
+}
+"#
+    );
+    assert_eq!(
+        graph.to_mermaid(),
+        r#"flowchart LR
+    i0["$component"]
+    i1(["a"])
+    i2["b"]
+    i1 -- "a" --> i0
+    i2 -- "b" --> i0
+    i1 -- "a" --> i2
+"#
+    );
+    Ok(())
+}
+
+/// Shares the dependencies of the `share-dependencies` test case, where the
+/// merged instances aren't the last in the graph, so other instances are moved
+/// into their places.
+#[test]
+fn shared_dependencies() -> Result<()> {
+    let dir = "tests/compositions/share-dependencies";
+    let config = Config::from_file(format!("{dir}/config.yml"))?;
+    let root = Path::new(dir).join("root.wat");
+    let graph = ComponentComposer::new(&root, &config).graph()?;
+
+    assert!(graph.instance("a2").is_none());
+    assert!(graph.instance("b2").is_none());
+    for name in ["$component", "a1", "b1", "c1", "c2"] {
+        let instance = graph.instance(name).unwrap();
+        assert_eq!(graph.instance_name(instance), name);
+    }
+
+    let bytes = graph.encode()?;
+    validate(&bytes)?;
+    Ok(())
+}
+
+#[test]
+fn unreachable_instances() -> Result<()> {
+    let mut graph = CompositionGraph::new();
+    let root = graph.add_component(
+        "root",
+        r#"(component
+            (import "a" (instance (export "f" (func))))
+        )"#,
+    )?;
+    let a = graph.add_component(
+        "a",
+        r#"(component
+            (import "in" (instance (export "f" (func))))
+            (alias export 0 "f" (func))
+            (export "f" (func 0))
+        )"#,
+    )?;
+    let unused = graph.add_component(
+        "unused",
+        r#"(component
+            (import "in" (instance (export "g" (func))))
+        )"#,
+    )?;
+
+    let root = graph.instantiate("root", root)?;
+    let a = graph.instantiate("a", a)?;
+    let dead = graph.instantiate("dead", unused)?;
+    let f = graph.import_instance("f")?;
+    let g = graph.import_instance("g")?;
+    graph.connect(a, None, root, "a")?;
+    graph.connect(f, None, a, "in")?;
+    graph.connect(g, None, dead, "in")?;
+
+    assert_eq!(graph.unreachable_instances(), ["dead", "g"]);
+
+    let bytes = graph.encode()?;
+    validate(&bytes)?;
+    let text = wasmprinter::print_bytes(&bytes)?;
+    assert!(text.contains("(import \"f\" (instance"), "{text}");
+    assert!(!text.contains("(import \"g\""), "{text}");
+    assert_eq!(text.matches("\n  (component ").count(), 2, "{text}");
+    assert_eq!(text.matches("\n  (instance ").count(), 2, "{text}");
+    assert_eq!(text.matches("\n  (type ").count(), 1, "{text}");
+    Ok(())
+}
+
+#[test]
+fn producers() -> Result<()> {
+    use wasm_encoder::{CustomSection, Encode};
+
+    // Append a `producers` section to the root component
+    let mut root = wat::parse_str(
+        r#"(component
+            (import "a" (instance (export "f" (func))))
+        )"#,
+    )?;
+    let mut data = Vec::new();
+    1u32.encode(&mut data);
+    "language".encode(&mut data);
+    1u32.encode(&mut data);
+    "Rust".encode(&mut data);
+    "1.63.0".encode(&mut data);
+    root.push(0);
+    CustomSection {
+        name: "producers",
+        data: &data,
+    }
+    .encode(&mut root);
+
+    let mut graph = CompositionGraph::new();
+    let root = graph.add_component("root", root)?;
+    let a = graph.add_component(
+        "a",
+        r#"(component
+            (core module $m (func (export "f")))
+            (core instance $i (instantiate $m))
+            (func $f (canon lift (core func $i "f")))
+            (export "f" (func $f))
+        )"#,
+    )?;
+    let root = graph.instantiate("root", root)?;
+    let a = graph.instantiate("a", a)?;
+    graph.connect(a, None, root, "a")?;
+    let bytes = graph.encode()?;
+    validate(&bytes)?;
+
+    let fields = top_level_producers(&bytes)?;
+    assert_eq!(
+        fields,
+        [
+            "language Rust 1.63.0".to_string(),
+            format!("processed-by wasm-compose {}", env!("CARGO_PKG_VERSION")),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn malformed_producers() -> Result<()> {
+    use wasm_encoder::{CustomSection, Encode};
+
+    // Append a `producers` section claiming more fields than it holds
+    let mut root = wat::parse_str(
+        r#"(component
+            (import "a" (instance (export "f" (func))))
+        )"#,
+    )?;
+    root.push(0);
+    CustomSection {
+        name: "producers",
+        data: &[2, 1, b'x'],
+    }
+    .encode(&mut root);
+
+    let mut graph = CompositionGraph::new();
+    let root = graph.add_component("root", root)?;
+    let a = graph.add_component(
+        "a",
+        r#"(component
+            (core module $m (func (export "f")))
+            (core instance $i (instantiate $m))
+            (func $f (canon lift (core func $i "f")))
+            (export "f" (func $f))
+        )"#,
+    )?;
+    let root = graph.instantiate("root", root)?;
+    let a = graph.instantiate("a", a)?;
+    graph.connect(a, None, root, "a")?;
+    let bytes = graph.encode()?;
+    validate(&bytes)?;
+
+    // The malformed section is skipped rather than failing the composition
+    assert_eq!(
+        top_level_producers(&bytes)?,
+        [format!(
+            "processed-by wasm-compose {}",
+            env!("CARGO_PKG_VERSION")
+        )]
+    );
+    Ok(())
+}
+
+/// Collects the fields of the top-level `producers` section.
+fn top_level_producers(bytes: &[u8]) -> Result<Vec<String>> {
+    use wasmparser::{Parser, Payload, ProducersSectionReader};
+
+    let mut depth = 0;
+    let mut fields = Vec::new();
+    for payload in Parser::new(0).parse_all(bytes) {
+        match payload? {
+            Payload::Version { .. } => depth += 1,
+            Payload::End(_) => depth -= 1,
+            Payload::CustomSection(s) if depth == 1 && s.name() == "producers" => {
+                for field in ProducersSectionReader::new(s.data(), s.data_offset())? {
+                    let field = field?;
+                    for value in field.get_producer_field_values_reader()? {
+                        let value = value?;
+                        fields.push(format!("{} {} {}", field.name, value.name, value.version));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(fields)
+}
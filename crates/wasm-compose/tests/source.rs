@@ -0,0 +1,139 @@
+use anyhow::Result;
+use std::{collections::HashMap, path::Path};
+use wasm_compose::{
+    composer::ComponentComposer,
+    config::{Config, Dependency},
+    source::{ComponentSource, ContentStore},
+};
+use wasmparser::{Validator, WasmFeatures};
+
+const DIR: &str = "tests/sources";
+const DIGEST: &str = "sha256:533199b5ca08c9de8449a9b871e5fcfe7b038c0eda9a0624de2433f03305e6a5";
+
+/// A component source that serves components from memory.
+struct MemorySource(HashMap<&'static str, Vec<u8>>);
+
+impl ComponentSource for MemorySource {
+    fn fetch(&self, reference: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.0.get(reference).cloned())
+    }
+}
+
+fn config(path: &str, digest: Option<&str>) -> Config {
+    Config {
+        dir: DIR.into(),
+        dependencies: [(
+            "a".to_string(),
+            Dependency {
+                path: path.into(),
+                digest: digest.map(Into::into),
+                import: None,
+            },
+        )]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    }
+}
+
+fn validate(bytes: &[u8]) -> Result<()> {
+    Validator::new_with_features(WasmFeatures {
+        component_model: true,
+        ..Default::default()
+    })
+    .validate_all(bytes)?;
+    Ok(())
+}
+
+#[test]
+fn custom_source() -> Result<()> {
+    let root = Path::new(DIR).join("root.wat");
+    let config = config("https://example.com/a.wasm", None);
+    let source = MemorySource(
+        [(
+            "https://example.com/a.wasm",
+            std::fs::read(Path::new(DIR).join("a.wat"))?,
+        )]
+        .into_iter()
+        .collect(),
+    );
+
+    let bytes = ComponentComposer::new(&root, &config)
+        .with_source(source)
+        .compose()?;
+    validate(&bytes)?;
+
+    // Without the source, the reference is treated as a file path
+    let err = ComponentComposer::new(&root, &config)
+        .compose()
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("failed to parse component"),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn content_store() -> Result<()> {
+    let root = Path::new(DIR).join("root.wat");
+    let config = config(DIGEST, None);
+    let bytes = ComponentComposer::new(&root, &config)
+        .with_source(ContentStore::new(Path::new(DIR).join("store")))
+        .compose()?;
+    validate(&bytes)?;
+
+    let config = self::config(&DIGEST.replace("5331", "0000"), None);
+    let err = ComponentComposer::new(&root, &config)
+        .with_source(ContentStore::new(Path::new(DIR).join("store")))
+        .compose()
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("failed to read component `sha256:0000"),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+/// The store holds the text format of `a.wat` under the digest of its binary,
+/// which is the same digest that a local dependency is checked against.
+#[test]
+fn digest_of_binary() -> Result<()> {
+    let root = Path::new(DIR).join("root.wat");
+    let store = ContentStore::new(Path::new(DIR).join("store"));
+    let fetched = store.fetch(DIGEST)?.unwrap();
+    assert_eq!(fetched, wat::parse_file(Path::new(DIR).join("a.wat"))?);
+
+    let config = config("a.wat", Some(DIGEST));
+    let bytes = ComponentComposer::new(&root, &config).compose()?;
+    validate(&bytes)?;
+    Ok(())
+}
+
+#[test]
+fn digest_mismatch() -> Result<()> {
+    let root = Path::new(DIR).join("root.wat");
+    let config = config("a.wat", Some("sha256:00"));
+    let err = ComponentComposer::new(&root, &config)
+        .compose()
+        .unwrap_err();
+    let message = err.to_string();
+    assert!(
+        message.starts_with("component `a` has digest `sha256:"),
+        "{message}"
+    );
+    assert!(
+        message.ends_with("but digest `sha256:00` was expected"),
+        "{message}"
+    );
+
+    let config = self::config("a.wat", Some("md5:00"));
+    let err = ComponentComposer::new(&root, &config)
+        .compose()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unsupported digest `md5:00` for component `a`: only `sha256` digests are supported"
+    );
+    Ok(())
+}
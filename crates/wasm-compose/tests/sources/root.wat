@@ -0,0 +1,3 @@
+(component
+  (import "a" (instance (export "f" (func))))
+)
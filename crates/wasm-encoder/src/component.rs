@@ -7,6 +7,7 @@ mod instances;
 mod modules;
 mod start;
 mod types;
+mod values;
 
 pub use self::aliases::*;
 pub use self::canonicals::*;
@@ -17,6 +18,7 @@ pub use self::instances::*;
 pub use self::modules::*;
 pub use self::start::*;
 pub use self::types::*;
+pub use self::values::*;
 
 use crate::{CustomSection, Encode};
 
@@ -72,6 +74,8 @@ pub enum ComponentSectionId {
     Import = 10,
     /// The section is an export section.
     Export = 11,
+    /// The section is a value section.
+    Value = 12,
 }
 
 impl From<ComponentSectionId> for u8 {
@@ -21,6 +21,11 @@ pub enum CanonicalOption {
     /// The post-return function to use if the lifting of a function requires
     /// cleanup after the function returns.
     PostReturn(u32),
+    /// Use the async ABI for lifting or lowering.
+    Async,
+    /// Use the specified function to deliver async events to a lifted
+    /// function which uses the stackless async ABI.
+    Callback(u32),
 }
 
 impl Encode for CanonicalOption {
@@ -41,6 +46,11 @@ impl Encode for CanonicalOption {
                 sink.push(0x05);
                 idx.encode(sink);
             }
+            Self::Async => sink.push(0x06),
+            Self::Callback(idx) => {
+                sink.push(0x07);
+                idx.encode(sink);
+            }
         }
     }
 }
@@ -118,6 +128,137 @@ impl CanonicalFunctionSection {
         self.num_added += 1;
         self
     }
+
+    /// Define a function which returns a result to the caller of a lifted
+    /// async function.
+    pub fn task_return(&mut self, type_index: u32) -> &mut Self {
+        self.bytes.push(0x09);
+        type_index.encode(&mut self.bytes);
+        self.num_added += 1;
+        self
+    }
+
+    /// Define a function which waits for at least one outstanding async task,
+    /// stream, or future to make progress, returning the first such event.
+    pub fn task_wait(&mut self, async_: bool, memory: u32) -> &mut Self {
+        self.bytes.push(0x0a);
+        self.bytes.push(if async_ { 1 } else { 0 });
+        memory.encode(&mut self.bytes);
+        self.num_added += 1;
+        self
+    }
+
+    /// Define a function which checks whether any outstanding async task,
+    /// stream, or future has made progress without blocking.
+    pub fn task_poll(&mut self, async_: bool, memory: u32) -> &mut Self {
+        self.bytes.push(0x0b);
+        self.bytes.push(if async_ { 1 } else { 0 });
+        memory.encode(&mut self.bytes);
+        self.num_added += 1;
+        self
+    }
+
+    /// Define a function which yields control to the host so that other
+    /// tasks are able to make progress, if any.
+    pub fn task_yield(&mut self, async_: bool) -> &mut Self {
+        self.bytes.push(0x0c);
+        self.bytes.push(if async_ { 1 } else { 0 });
+        self.num_added += 1;
+        self
+    }
+
+    /// Define a function which creates a new `stream` handle of the specified
+    /// type.
+    pub fn stream_new(&mut self, type_index: u32) -> &mut Self {
+        self.bytes.push(0x0e);
+        type_index.encode(&mut self.bytes);
+        self.num_added += 1;
+        self
+    }
+
+    /// Define a function which receives values from a `stream` of the
+    /// specified type.
+    pub fn stream_receive<O>(&mut self, type_index: u32, options: O) -> &mut Self
+    where
+        O: IntoIterator<Item = CanonicalOption>,
+        O::IntoIter: ExactSizeIterator,
+    {
+        self.bytes.push(0x0f);
+        type_index.encode(&mut self.bytes);
+        self.encode_options(options);
+        self.num_added += 1;
+        self
+    }
+
+    /// Define a function which sends values to a `stream` of the specified
+    /// type.
+    pub fn stream_send<O>(&mut self, type_index: u32, options: O) -> &mut Self
+    where
+        O: IntoIterator<Item = CanonicalOption>,
+        O::IntoIter: ExactSizeIterator,
+    {
+        self.bytes.push(0x10);
+        type_index.encode(&mut self.bytes);
+        self.encode_options(options);
+        self.num_added += 1;
+        self
+    }
+
+    /// Define a function which creates a new `future` handle of the specified
+    /// type.
+    pub fn future_new(&mut self, type_index: u32) -> &mut Self {
+        self.bytes.push(0x15);
+        type_index.encode(&mut self.bytes);
+        self.num_added += 1;
+        self
+    }
+
+    /// Define a function which receives the value of a `future` of the
+    /// specified type.
+    pub fn future_receive<O>(&mut self, type_index: u32, options: O) -> &mut Self
+    where
+        O: IntoIterator<Item = CanonicalOption>,
+        O::IntoIter: ExactSizeIterator,
+    {
+        self.bytes.push(0x16);
+        type_index.encode(&mut self.bytes);
+        self.encode_options(options);
+        self.num_added += 1;
+        self
+    }
+
+    /// Define a function which sends the value of a `future` of the specified
+    /// type.
+    pub fn future_send<O>(&mut self, type_index: u32, options: O) -> &mut Self
+    where
+        O: IntoIterator<Item = CanonicalOption>,
+        O::IntoIter: ExactSizeIterator,
+    {
+        self.bytes.push(0x17);
+        type_index.encode(&mut self.bytes);
+        self.encode_options(options);
+        self.num_added += 1;
+        self
+    }
+
+    /// Define a function which drops an `error` handle.
+    pub fn error_drop(&mut self) -> &mut Self {
+        self.bytes.push(0x1e);
+        self.num_added += 1;
+        self
+    }
+
+    fn encode_options<O>(&mut self, options: O)
+    where
+        O: IntoIterator<Item = CanonicalOption>,
+        O::IntoIter: ExactSizeIterator,
+    {
+        let options = options.into_iter();
+        options.len().encode(&mut self.bytes);
+        for option in options {
+            option.encode(&mut self.bytes);
+        }
+    }
 }
 
 impl Encode for CanonicalFunctionSection {
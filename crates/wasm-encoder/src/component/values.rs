@@ -0,0 +1,142 @@
+use crate::{
+    encode_section, ComponentSection, ComponentSectionId, ComponentValType, Encode,
+    PrimitiveValType,
+};
+
+/// Represents a value of a primitive component value type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrimitiveValue<'a> {
+    /// A boolean value.
+    Bool(bool),
+    /// A signed 8-bit integer.
+    S8(i8),
+    /// An unsigned 8-bit integer.
+    U8(u8),
+    /// A signed 16-bit integer.
+    S16(i16),
+    /// An unsigned 16-bit integer.
+    U16(u16),
+    /// A signed 32-bit integer.
+    S32(i32),
+    /// An unsigned 32-bit integer.
+    U32(u32),
+    /// A signed 64-bit integer.
+    S64(i64),
+    /// An unsigned 64-bit integer.
+    U64(u64),
+    /// A 32-bit floating point number, given as its raw bits.
+    Float32(u32),
+    /// A 64-bit floating point number, given as its raw bits.
+    Float64(u64),
+    /// A Unicode character.
+    Char(char),
+    /// A string.
+    String(&'a str),
+}
+
+impl PrimitiveValue<'_> {
+    /// Gets the type of this value.
+    pub fn ty(&self) -> PrimitiveValType {
+        match self {
+            Self::Bool(_) => PrimitiveValType::Bool,
+            Self::S8(_) => PrimitiveValType::S8,
+            Self::U8(_) => PrimitiveValType::U8,
+            Self::S16(_) => PrimitiveValType::S16,
+            Self::U16(_) => PrimitiveValType::U16,
+            Self::S32(_) => PrimitiveValType::S32,
+            Self::U32(_) => PrimitiveValType::U32,
+            Self::S64(_) => PrimitiveValType::S64,
+            Self::U64(_) => PrimitiveValType::U64,
+            Self::Float32(_) => PrimitiveValType::Float32,
+            Self::Float64(_) => PrimitiveValType::Float64,
+            Self::Char(_) => PrimitiveValType::Char,
+            Self::String(_) => PrimitiveValType::String,
+        }
+    }
+}
+
+impl Encode for PrimitiveValue<'_> {
+    fn encode(&self, sink: &mut Vec<u8>) {
+        match *self {
+            Self::Bool(b) => sink.push(b as u8),
+            Self::S8(i) => sink.push(i as u8),
+            Self::U8(i) => sink.push(i),
+            Self::S16(i) => i32::from(i).encode(sink),
+            Self::U16(i) => u32::from(i).encode(sink),
+            Self::S32(i) => i.encode(sink),
+            Self::U32(i) => i.encode(sink),
+            Self::S64(i) => i.encode(sink),
+            Self::U64(i) => i.encode(sink),
+            Self::Float32(bits) => sink.extend(bits.to_le_bytes()),
+            Self::Float64(bits) => sink.extend(bits.to_le_bytes()),
+            Self::Char(c) => u32::from(c).encode(sink),
+            Self::String(s) => s.encode(sink),
+        }
+    }
+}
+
+/// An encoder for the value section of WebAssembly components.
+///
+/// # Example
+///
+/// ```
+/// use wasm_encoder::{Component, ComponentValueSection, PrimitiveValue};
+///
+/// let mut values = ComponentValueSection::new();
+/// values.primitive(PrimitiveValue::U32(42));
+/// values.primitive(PrimitiveValue::String("hello"));
+///
+/// let mut component = Component::new();
+/// component.section(&values);
+///
+/// let bytes = component.finish();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ComponentValueSection {
+    bytes: Vec<u8>,
+    num_added: u32,
+}
+
+impl ComponentValueSection {
+    /// Create a new component value section encoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of values in the section.
+    pub fn len(&self) -> u32 {
+        self.num_added
+    }
+
+    /// Determines if the section is empty.
+    pub fn is_empty(&self) -> bool {
+        self.num_added == 0
+    }
+
+    /// Define a value of a primitive type.
+    pub fn primitive(&mut self, value: PrimitiveValue<'_>) -> &mut Self {
+        let mut data = Vec::new();
+        value.encode(&mut data);
+        self.raw(value.ty().into(), &data)
+    }
+
+    /// Define a value of the given type from its already-encoded bytes.
+    pub fn raw(&mut self, ty: ComponentValType, data: &[u8]) -> &mut Self {
+        ty.encode(&mut self.bytes);
+        data.encode(&mut self.bytes);
+        self.num_added += 1;
+        self
+    }
+}
+
+impl Encode for ComponentValueSection {
+    fn encode(&self, sink: &mut Vec<u8>) {
+        encode_section(sink, self.num_added, &self.bytes);
+    }
+}
+
+impl ComponentSection for ComponentValueSection {
+    fn id(&self) -> u8 {
+        ComponentSectionId::Value.into()
+    }
+}
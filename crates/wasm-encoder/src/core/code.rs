@@ -321,6 +321,8 @@ pub enum Instruction<'a> {
     Return,
     Call(u32),
     CallIndirect { ty: u32, table: u32 },
+    ReturnCall(u32),
+    ReturnCallIndirect { ty: u32, table: u32 },
     Throw(u32),
     Rethrow(u32),
 
@@ -908,6 +910,15 @@ impl Encode for Instruction<'_> {
                 ty.encode(sink);
                 table.encode(sink);
             }
+            Instruction::ReturnCall(f) => {
+                sink.push(0x12);
+                f.encode(sink);
+            }
+            Instruction::ReturnCallIndirect { ty, table } => {
+                sink.push(0x13);
+                ty.encode(sink);
+                table.encode(sink);
+            }
             Instruction::Delegate(l) => {
                 sink.push(0x18);
                 l.encode(sink);
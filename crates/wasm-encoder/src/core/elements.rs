@@ -15,6 +15,7 @@ use crate::{encode_section, ConstExpr, Encode, Section, SectionId, ValType};
 /// let mut tables = TableSection::new();
 /// tables.table(TableType {
 ///     element_type: ValType::FuncRef,
+///     table64: false,
 ///     minimum: 128,
 ///     maximum: None,
 /// });
@@ -12,6 +12,7 @@ use crate::{encode_section, Encode, Section, SectionId, ValType};
 /// let mut tables = TableSection::new();
 /// tables.table(TableType {
 ///     element_type: ValType::FuncRef,
+///     table64: false,
 ///     minimum: 128,
 ///     maximum: None,
 /// });
@@ -68,10 +69,12 @@ impl Section for TableSection {
 pub struct TableType {
     /// The table's element type.
     pub element_type: ValType,
+    /// Whether or not this is a 64-bit table.
+    pub table64: bool,
     /// Minimum size, in elements, of this table
-    pub minimum: u32,
+    pub minimum: u64,
     /// Maximum size, in elements, of this table
-    pub maximum: Option<u32>,
+    pub maximum: Option<u64>,
 }
 
 impl Encode for TableType {
@@ -80,6 +83,9 @@ impl Encode for TableType {
         if self.maximum.is_some() {
             flags |= 0b001;
         }
+        if self.table64 {
+            flags |= 0b100;
+        }
 
         self.element_type.encode(sink);
         sink.push(flags);
@@ -0,0 +1,344 @@
+//! The set of WebAssembly proposals enabled for an operation.
+//!
+//! [`WasmFeatures`] is shared by the crates of wasm-tools: it's the type of
+//! `wasmparser::WasmFeatures` for validation and of `wast::parser::Features`
+//! for parsing the text format, `wasm_smith::Config::features` describes the
+//! modules that a configuration generates, and `WasmMutate::features` limits
+//! what mutated modules may use. Configuring every step of a pipeline with the
+//! same value keeps them from disagreeing about what's allowed.
+//!
+//! `wasm-encoder` doesn't take features: it encodes whatever it's given, and
+//! it's up to the other steps to only give it what's allowed.
+//!
+//! Features can also be parsed from the comma-separated lists accepted by the
+//! `--features` flag of the `wasm-tools` CLI:
+//!
+//! ```
+//! use wasm_features::WasmFeatures;
+//!
+//! let features: WasmFeatures = "mvp,simd".parse().unwrap();
+//! assert!(features.simd);
+//! assert!(!features.bulk_memory);
+//!
+//! let features: WasmFeatures = "all,-threads".parse().unwrap();
+//! assert!(features.memory64);
+//! assert!(!features.threads);
+//! ```
+
+#![deny(missing_docs)]
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Flags for the WebAssembly proposals which are enabled.
+#[derive(Hash, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, rename_all = "kebab-case")
+)]
+pub struct WasmFeatures {
+    /// The WebAssembly `mutable-global` proposal (enabled by default)
+    pub mutable_global: bool,
+    /// The WebAssembly `nontrapping-float-to-int-conversions` proposal (enabled by default)
+    pub saturating_float_to_int: bool,
+    /// The WebAssembly `sign-extension-ops` proposal (enabled by default)
+    pub sign_extension: bool,
+    /// The WebAssembly reference types proposal (enabled by default)
+    pub reference_types: bool,
+    /// The WebAssembly multi-value proposal (enabled by default)
+    pub multi_value: bool,
+    /// The WebAssembly bulk memory operations proposal (enabled by default)
+    pub bulk_memory: bool,
+    /// The WebAssembly SIMD proposal (enabled by default)
+    pub simd: bool,
+    /// The WebAssembly Relaxed SIMD proposal
+    pub relaxed_simd: bool,
+    /// The WebAssembly threads proposal
+    pub threads: bool,
+    /// The WebAssembly tail-call proposal
+    pub tail_call: bool,
+    /// Whether or not only deterministic instructions are allowed
+    #[cfg_attr(feature = "serde", serde(rename = "deterministic"))]
+    pub deterministic_only: bool,
+    /// The WebAssembly multi memory proposal
+    pub multi_memory: bool,
+    /// The WebAssembly exception handling proposal
+    #[cfg_attr(feature = "serde", serde(rename = "exception-handling"))]
+    pub exceptions: bool,
+    /// The WebAssembly memory64 proposal
+    pub memory64: bool,
+    /// The WebAssembly extended_const proposal
+    pub extended_const: bool,
+    /// The WebAssembly component model proposal.
+    pub component_model: bool,
+    /// The async extensions to the WebAssembly component model proposal:
+    /// async lifts and lowers, and the task, stream, and future built-ins.
+    pub component_model_async: bool,
+    /// The WebAssembly typed function references proposal.
+    ///
+    /// This is only recognized by the text format parser.
+    pub function_references: bool,
+    /// The WebAssembly GC proposal.
+    ///
+    /// This is only recognized by the text format parser.
+    pub gc: bool,
+}
+
+type Field = fn(&mut WasmFeatures) -> &mut bool;
+
+/// The name of each feature in lists parsed with [`FromStr`], along with the
+/// field that it sets.
+const FEATURES: &[(&str, Field)] = &[
+    ("reference-types", |f| &mut f.reference_types),
+    ("simd", |f| &mut f.simd),
+    ("threads", |f| &mut f.threads),
+    ("bulk-memory", |f| &mut f.bulk_memory),
+    ("multi-value", |f| &mut f.multi_value),
+    ("tail-call", |f| &mut f.tail_call),
+    ("component-model", |f| &mut f.component_model),
+    ("component-model-async", |f| &mut f.component_model_async),
+    ("multi-memory", |f| &mut f.multi_memory),
+    ("exception-handling", |f| &mut f.exceptions),
+    ("memory64", |f| &mut f.memory64),
+    ("extended-const", |f| &mut f.extended_const),
+    ("deterministic", |f| &mut f.deterministic_only),
+    ("saturating-float-to-int", |f| {
+        &mut f.saturating_float_to_int
+    }),
+    ("sign-extension", |f| &mut f.sign_extension),
+    ("mutable-global", |f| &mut f.mutable_global),
+    ("relaxed-simd", |f| &mut f.relaxed_simd),
+    ("function-references", |f| &mut f.function_references),
+    ("gc", |f| &mut f.gc),
+];
+
+impl WasmFeatures {
+    /// The names of the presets accepted by [`WasmFeatures::preset`].
+    pub const PRESETS: &'static [&'static str] = &["mvp", "default", "wasi-preview2", "all"];
+
+    /// Returns a set of features with every proposal disabled, which only
+    /// accepts the WebAssembly 1.0 MVP.
+    ///
+    /// As with [`WasmFeatures::all`], `deterministic_only` is left as it is
+    /// by default.
+    pub fn mvp() -> WasmFeatures {
+        let mut ret = WasmFeatures::default();
+        ret.set_all(false);
+        ret
+    }
+
+    /// Returns a set of features with every proposal enabled.
+    ///
+    /// This doesn't restrict WebAssembly to deterministic instructions, so
+    /// `deterministic_only` is left as it is by default.
+    pub fn all() -> WasmFeatures {
+        let mut ret = WasmFeatures::default();
+        ret.set_all(true);
+        ret
+    }
+
+    /// Returns the preset named `name`, which is one of:
+    ///
+    /// * `"mvp"`: no features beyond the original WebAssembly specification,
+    ///   as with [`WasmFeatures::mvp`]
+    ///
+    /// * `"default"`: the features that are enabled by default
+    ///
+    /// * `"wasi-preview2"`: the default features plus the component model
+    ///
+    /// * `"all"`: every proposal, as with [`WasmFeatures::all`]
+    pub fn preset(name: &str) -> Option<WasmFeatures> {
+        Some(match name {
+            "mvp" => WasmFeatures::mvp(),
+            "default" => WasmFeatures::default(),
+            "wasi-preview2" => WasmFeatures {
+                component_model: true,
+                ..WasmFeatures::default()
+            },
+            "all" | "all-proposals" => WasmFeatures::all(),
+            _ => return None,
+        })
+    }
+
+    /// Returns the name of each feature, as accepted by [`FromStr`], along
+    /// with whether it's enabled.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, bool)> + '_ {
+        let mut copy = *self;
+        FEATURES
+            .iter()
+            .map(move |(name, field)| (*name, *field(&mut copy)))
+    }
+
+    /// Enables or disables the feature called `name`, returning `false` if
+    /// there's no such feature.
+    pub fn set(&mut self, name: &str, enabled: bool) -> bool {
+        match FEATURES.iter().find(|(n, _)| *n == name) {
+            Some((_, field)) => {
+                *field(self) = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn set_all(&mut self, enabled: bool) {
+        for (name, field) in FEATURES {
+            // Determinism is a restriction rather than a proposal, so it
+            // doesn't count as one of "all" of them.
+            if *name != "deterministic" {
+                *field(self) = enabled;
+            }
+        }
+    }
+}
+
+impl Default for WasmFeatures {
+    fn default() -> WasmFeatures {
+        WasmFeatures {
+            // off-by-default features
+            relaxed_simd: false,
+            threads: false,
+            tail_call: false,
+            multi_memory: false,
+            exceptions: false,
+            memory64: false,
+            extended_const: false,
+            component_model: false,
+            component_model_async: false,
+            function_references: false,
+            gc: false,
+            deterministic_only: cfg!(feature = "deterministic"),
+
+            // on-by-default features
+            mutable_global: true,
+            saturating_float_to_int: true,
+            sign_extension: true,
+            bulk_memory: true,
+            multi_value: true,
+            reference_types: true,
+            simd: true,
+        }
+    }
+}
+
+/// Parses a comma-separated list of features to enable, starting from the
+/// default features.
+///
+/// A feature prefixed with `-` is disabled instead. `"all"` enables every
+/// proposal, or disables them as `"-all"`, and the other names of
+/// [`WasmFeatures::PRESETS`] replace the features so far with that preset, so
+/// `"mvp,simd"` is the MVP plus SIMD.
+impl FromStr for WasmFeatures {
+    type Err = UnknownFeature;
+
+    fn from_str(s: &str) -> Result<WasmFeatures, UnknownFeature> {
+        let mut ret = WasmFeatures::default();
+        for part in s.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let (enable, name) = match part.strip_prefix('-') {
+                Some(name) => (false, name),
+                None => (true, part),
+            };
+            match name {
+                "all" | "all-proposals" => ret.set_all(enable),
+                _ if enable && WasmFeatures::preset(name).is_some() => {
+                    ret = WasmFeatures::preset(name).unwrap();
+                }
+                _ => {
+                    if !ret.set(name, enable) {
+                        return Err(UnknownFeature(name.to_string()));
+                    }
+                }
+            }
+        }
+        Ok(ret)
+    }
+}
+
+/// The error returned when parsing [`WasmFeatures`] names a feature which
+/// doesn't exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFeature(String);
+
+impl fmt::Display for UnknownFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown feature `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownFeature {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!("".parse(), Ok(WasmFeatures::default()));
+        assert_eq!("default".parse(), Ok(WasmFeatures::default()));
+        assert_eq!("mvp".parse(), Ok(WasmFeatures::mvp()));
+        assert_eq!("all".parse(), Ok(WasmFeatures::all()));
+        assert_eq!("all-proposals".parse(), Ok(WasmFeatures::all()));
+
+        assert_eq!(
+            "mvp, simd".parse(),
+            Ok(WasmFeatures {
+                simd: true,
+                ..WasmFeatures::mvp()
+            })
+        );
+
+        let features: WasmFeatures = "wasi-preview2,-simd".parse().unwrap();
+        assert!(features.component_model);
+        assert!(!features.simd);
+
+        let features: WasmFeatures = "-all,deterministic".parse().unwrap();
+        assert_eq!(
+            features.iter().filter(|(_, on)| *on).collect::<Vec<_>>(),
+            [("deterministic", true)]
+        );
+
+        let err = "simd,nope".parse::<WasmFeatures>().unwrap_err();
+        assert_eq!(err.to_string(), "unknown feature `nope`");
+        assert!("-mvp".parse::<WasmFeatures>().is_err());
+    }
+
+    #[test]
+    fn presets() {
+        for name in WasmFeatures::PRESETS {
+            assert!(WasmFeatures::preset(name).is_some(), "{}", name);
+        }
+        let all = WasmFeatures::all();
+        assert!(all.iter().all(|(name, on)| on || name == "deterministic"));
+        let mvp = WasmFeatures::mvp();
+        assert!(mvp.iter().all(|(name, on)| !on || name == "deterministic"));
+    }
+
+    #[test]
+    fn mvp() {
+        assert_eq!(
+            WasmFeatures::mvp(),
+            WasmFeatures {
+                mutable_global: false,
+                saturating_float_to_int: false,
+                sign_extension: false,
+                reference_types: false,
+                multi_value: false,
+                bulk_memory: false,
+                simd: false,
+                relaxed_simd: false,
+                threads: false,
+                tail_call: false,
+                deterministic_only: cfg!(feature = "deterministic"),
+                multi_memory: false,
+                exceptions: false,
+                memory64: false,
+                extended_const: false,
+                component_model: false,
+                component_model_async: false,
+                function_references: false,
+                gc: false,
+            }
+        );
+    }
+}
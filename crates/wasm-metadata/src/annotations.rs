@@ -0,0 +1,254 @@
+//! Descriptive annotations, mirroring the [OCI annotation keys] for images.
+//!
+//! Each annotation is stored as UTF-8 text in its own custom section, named
+//! after the annotation, at the top level of a module or component. Fields
+//! which aren't set have no section, and sections of other annotations are
+//! left alone when one is updated.
+//!
+//! [OCI annotation keys]: https://github.com/opencontainers/image-spec/blob/main/annotations.md
+
+use crate::sections::{self, Binary, Kind, NestingPath};
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// A kind of annotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Annotation {
+    /// The SPDX license expression of the code, such as `Apache-2.0 OR MIT`.
+    License,
+    /// A URL of the source code that the binary was built from.
+    Source,
+    /// A human-readable description of the binary.
+    Description,
+    /// A URL with more information about the binary.
+    Homepage,
+    /// The version control revision that the binary was built from.
+    Revision,
+}
+
+impl Annotation {
+    /// Every kind of annotation, in the order they're listed in reports.
+    pub const ALL: [Annotation; 5] = [
+        Annotation::License,
+        Annotation::Source,
+        Annotation::Description,
+        Annotation::Homepage,
+        Annotation::Revision,
+    ];
+
+    /// The name of the custom section holding this annotation.
+    pub fn section_name(&self) -> &'static str {
+        match self {
+            Annotation::License => "license",
+            Annotation::Source => "source",
+            Annotation::Description => "description",
+            Annotation::Homepage => "homepage",
+            Annotation::Revision => "revision",
+        }
+    }
+
+    /// The OCI annotation key corresponding to this annotation.
+    pub fn oci_key(&self) -> &'static str {
+        match self {
+            Annotation::License => "org.opencontainers.image.licenses",
+            Annotation::Source => "org.opencontainers.image.source",
+            Annotation::Description => "org.opencontainers.image.description",
+            Annotation::Homepage => "org.opencontainers.image.url",
+            Annotation::Revision => "org.opencontainers.image.revision",
+        }
+    }
+
+    pub(crate) fn from_section_name(name: &str) -> Option<Annotation> {
+        Annotation::ALL
+            .into_iter()
+            .find(|a| a.section_name() == name)
+    }
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.section_name())
+    }
+}
+
+impl FromStr for Annotation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Annotation> {
+        match Annotation::from_section_name(s) {
+            Some(annotation) => Ok(annotation),
+            None => bail!(
+                "unknown annotation `{s}`, expected one of: license, source, description, \
+                 homepage, revision"
+            ),
+        }
+    }
+}
+
+/// The annotations of a module or component.
+///
+/// Fields which are `None` aren't set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Annotations {
+    /// See [`Annotation::License`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// See [`Annotation::Source`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// See [`Annotation::Description`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// See [`Annotation::Homepage`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    /// See [`Annotation::Revision`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+}
+
+impl Annotations {
+    /// Creates a set of annotations with no fields set.
+    pub fn new() -> Annotations {
+        Annotations::default()
+    }
+
+    /// Reads the annotations at the top level of `wasm`.
+    ///
+    /// Annotations of nested modules and components aren't read; see
+    /// [`extract_annotations`] for those. If there are several sections for
+    /// an annotation, the last one wins.
+    pub fn from_wasm(wasm: &[u8]) -> Result<Annotations> {
+        Self::from_binary(&Binary::parse(wasm)?)
+    }
+
+    pub(crate) fn from_binary(binary: &Binary<'_>) -> Result<Annotations> {
+        let mut annotations = Annotations::new();
+        for section in &binary.sections {
+            let (name, data, _) = match section.custom()? {
+                Some(custom) => custom,
+                None => continue,
+            };
+            if let Some(annotation) = Annotation::from_section_name(name) {
+                let value = std::str::from_utf8(data)
+                    .with_context(|| format!("annotation `{annotation}` is not valid UTF-8"))?;
+                annotations.set(annotation, Some(value.to_string()));
+            }
+        }
+        Ok(annotations)
+    }
+
+    /// Returns the value of `annotation`, if it's set.
+    pub fn get(&self, annotation: Annotation) -> Option<&str> {
+        self.field(annotation).as_deref()
+    }
+
+    /// Sets `annotation` to `value`, or unsets it if that's `None`.
+    pub fn set(&mut self, annotation: Annotation, value: Option<String>) {
+        *self.field_mut(annotation) = value;
+    }
+
+    /// Returns each annotation which is set, along with its value.
+    pub fn iter(&self) -> impl Iterator<Item = (Annotation, &str)> {
+        Annotation::ALL
+            .into_iter()
+            .filter_map(|a| Some((a, self.get(a)?)))
+    }
+
+    /// Returns whether no annotations are set.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Returns the annotations which are set keyed by their OCI annotation
+    /// keys, as used in the manifests of OCI images.
+    pub fn to_oci(&self) -> IndexMap<&'static str, String> {
+        self.iter()
+            .map(|(a, value)| (a.oci_key(), value.to_string()))
+            .collect()
+    }
+
+    /// Returns `wasm` with the annotations which are set stored at its top
+    /// level.
+    ///
+    /// Existing sections of these annotations are replaced in place, and
+    /// annotations which aren't set here are kept as they are.
+    pub fn add_to(&self, wasm: &[u8]) -> Result<Vec<u8>> {
+        let mut wasm = wasm.to_vec();
+        for (annotation, value) in self.iter() {
+            wasm = sections::replace_custom_section(
+                &wasm,
+                annotation.section_name(),
+                Some(value.as_bytes()),
+            )?;
+        }
+        Ok(wasm)
+    }
+
+    fn field(&self, annotation: Annotation) -> &Option<String> {
+        match annotation {
+            Annotation::License => &self.license,
+            Annotation::Source => &self.source,
+            Annotation::Description => &self.description,
+            Annotation::Homepage => &self.homepage,
+            Annotation::Revision => &self.revision,
+        }
+    }
+
+    fn field_mut(&mut self, annotation: Annotation) -> &mut Option<String> {
+        match annotation {
+            Annotation::License => &mut self.license,
+            Annotation::Source => &mut self.source,
+            Annotation::Description => &mut self.description,
+            Annotation::Homepage => &mut self.homepage,
+            Annotation::Revision => &mut self.revision,
+        }
+    }
+}
+
+/// Returns `wasm` without the top-level section of `annotation`, along with
+/// whether it had one.
+pub fn remove_annotation(wasm: &[u8], annotation: Annotation) -> Result<(Vec<u8>, bool)> {
+    let name = annotation.section_name();
+    let mut had_annotation = false;
+    for section in &Binary::parse(wasm)?.sections {
+        had_annotation |= section.custom_named(name)?.is_some();
+    }
+    let wasm = sections::replace_custom_section(wasm, name, None)?;
+    Ok((wasm, had_annotation))
+}
+
+/// The annotations of one module or component.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnnotationsEntry {
+    /// Where the module or component is nested.
+    pub path: NestingPath,
+    /// Whether this is a module or a component.
+    pub kind: Kind,
+    /// Its annotations.
+    pub annotations: Annotations,
+}
+
+/// Returns the annotations of `wasm` and of every module and component nested
+/// within it which has any, outermost first and in the order they're
+/// defined.
+pub fn extract_annotations(wasm: &[u8]) -> Result<Vec<AnnotationsEntry>> {
+    let mut entries = Vec::new();
+    sections::walk(wasm, &mut |path, binary, _| {
+        let annotations = Annotations::from_binary(binary)
+            .with_context(|| format!("invalid annotations in `{path}`"))?;
+        if !annotations.is_empty() {
+            entries.push(AnnotationsEntry {
+                path: path.clone(),
+                kind: binary.kind,
+                annotations,
+            });
+        }
+        Ok(())
+    })?;
+    Ok(entries)
+}
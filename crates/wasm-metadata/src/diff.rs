@@ -0,0 +1,157 @@
+//! Differences between the metadata of two binaries.
+
+use crate::dump::read_names;
+use crate::sections::{self, NestingPath};
+use crate::{entries, Annotations, Producers};
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// A metadata item which was added, removed, or changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Change {
+    /// Where the module or component with the item is nested.
+    pub path: NestingPath,
+    /// The item, such as `processed-by wasm-tools` or `function 3`.
+    pub item: String,
+    /// The value in the old binary, or `None` if the item was added.
+    pub old: Option<String>,
+    /// The value in the new binary, or `None` if the item was removed.
+    pub new: Option<String>,
+}
+
+/// The differences between the metadata of two modules or components.
+///
+/// Each list holds the changed items of one kind of metadata, in the order
+/// they appear in the old binary followed by the items only in the new one.
+/// The metadata of every nested module and component is compared too.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MetadataDiff {
+    /// Tools in `producers` sections, with their versions.
+    pub producers: Vec<Change>,
+    /// Names in `name` sections, such as `module`, `function 3`, or
+    /// `local 3 0` for the first local of function 3.
+    pub names: Vec<Change>,
+    /// Annotations, such as `license`, at the top level of each binary.
+    pub annotations: Vec<Change>,
+    /// Custom sections of any kind, with their sizes and digests.
+    ///
+    /// A section whose name appears more than once is numbered by its
+    /// occurrence, such as `.debug_info (2)`.
+    pub custom_sections: Vec<Change>,
+    /// Named metadata entries at the top level.
+    pub entries: Vec<Change>,
+}
+
+impl MetadataDiff {
+    /// Compares the metadata of `old` and `new`.
+    pub fn new(old: &[u8], new: &[u8]) -> Result<MetadataDiff> {
+        let old = Snapshot::new(old)?;
+        let new = Snapshot::new(new)?;
+        Ok(MetadataDiff {
+            producers: changes(&old.producers, &new.producers),
+            names: changes(&old.names, &new.names),
+            annotations: changes(&old.annotations, &new.annotations),
+            custom_sections: changes(&old.custom_sections, &new.custom_sections),
+            entries: changes(&old.entries, &new.entries),
+        })
+    }
+
+    /// Returns whether the metadata is the same.
+    pub fn is_empty(&self) -> bool {
+        self.producers.is_empty()
+            && self.names.is_empty()
+            && self.annotations.is_empty()
+            && self.custom_sections.is_empty()
+            && self.entries.is_empty()
+    }
+}
+
+type Items = IndexMap<(NestingPath, String), String>;
+
+/// The metadata of a binary, flattened to items and their values.
+#[derive(Default)]
+struct Snapshot {
+    producers: Items,
+    names: Items,
+    annotations: Items,
+    custom_sections: Items,
+    entries: Items,
+}
+
+impl Snapshot {
+    fn new(wasm: &[u8]) -> Result<Snapshot> {
+        let mut snapshot = Snapshot::default();
+        sections::walk(wasm, &mut |path, binary, _| {
+            if let Some(producers) = Producers::from_binary(binary)? {
+                for (field, values) in producers.fields() {
+                    for (name, version) in values {
+                        let item = format!("{field} {name}");
+                        snapshot
+                            .producers
+                            .insert((path.clone(), item), version.clone());
+                    }
+                }
+            }
+
+            for (annotation, value) in Annotations::from_binary(binary)?.iter() {
+                let key = (path.clone(), annotation.to_string());
+                snapshot.annotations.insert(key, value.to_string());
+            }
+
+            let mut occurrences = IndexMap::<&str, usize>::new();
+            for section in &binary.sections {
+                let (name, data, offset) = match section.custom()? {
+                    Some(custom) => custom,
+                    None => continue,
+                };
+                let count = occurrences.entry(name).or_default();
+                *count += 1;
+                let item = match *count {
+                    1 => name.to_string(),
+                    n => format!("{name} ({n})"),
+                };
+                let digest = format!("{:x}", Sha256::digest(data));
+                let value = format!("{} bytes, sha256 {}", data.len(), &digest[..16]);
+                snapshot.custom_sections.insert((path.clone(), item), value);
+
+                if name == "name" {
+                    read_names(data, offset, &mut |item, name| {
+                        let key = (path.clone(), item);
+                        snapshot.names.insert(key, name.to_string());
+                    })?;
+                }
+            }
+            Ok(())
+        })?;
+        for (key, value) in entries(wasm)? {
+            snapshot
+                .entries
+                .insert((NestingPath::default(), key), value.to_string());
+        }
+        Ok(snapshot)
+    }
+}
+
+fn changes(old: &Items, new: &Items) -> Vec<Change> {
+    let change = |(path, item): &(NestingPath, String), old: Option<&String>, new| Change {
+        path: path.clone(),
+        item: item.clone(),
+        old: old.cloned(),
+        new,
+    };
+    let mut changes = Vec::new();
+    for (key, value) in old {
+        match new.get(key) {
+            Some(v) if v == value => {}
+            v => changes.push(change(key, Some(value), v.cloned())),
+        }
+    }
+    for (key, value) in new {
+        if !old.contains_key(key) {
+            changes.push(change(key, None, Some(value.clone())));
+        }
+    }
+    changes
+}
@@ -0,0 +1,205 @@
+//! Every metadata item of a binary, located by its byte range.
+//!
+//! Tools which strip, sign, or splice metadata need to know where each item
+//! is as well as what it holds. [`dump`] reports both from a single parse, so
+//! the ranges can be used to edit the original bytes directly.
+
+use crate::sections::{self, Kind, NestingPath};
+use crate::{Annotation, Producers, Sbom, SignatureSection, Value};
+use crate::{ENTRY_SECTION_PREFIX, PRODUCERS_SECTION, SBOM_SECTION, SIGNATURE_SECTION};
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::ops::Range;
+use wasmparser::{IndirectNameMap, Name, NameMap, NameSectionReader};
+
+/// The parsed contents of a metadata item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case", tag = "kind")]
+pub enum MetadataValue {
+    /// A `producers` section.
+    Producers {
+        /// The tools it records.
+        producers: Producers,
+    },
+    /// A `name` section.
+    Names {
+        /// Each named item, such as `function 3` or `local 3 0`, and its
+        /// name.
+        names: IndexMap<String, String>,
+    },
+    /// A named metadata entry.
+    Entry {
+        /// The key of the entry.
+        key: String,
+        /// The value of the entry.
+        value: Value,
+    },
+    /// An annotation, such as the license of the code.
+    Annotation {
+        /// The kind of annotation.
+        annotation: Annotation,
+        /// The value of the annotation.
+        value: String,
+    },
+    /// An embedded SBOM.
+    Sbom {
+        /// The SBOM.
+        sbom: Sbom,
+    },
+    /// A `signature` section.
+    Signature {
+        /// The digest and signatures it holds.
+        signature: SignatureSection,
+    },
+    /// Any other custom section, whose contents aren't parsed.
+    Custom,
+}
+
+/// A metadata item of a module or component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MetadataItem {
+    /// Where the module or component with the item is nested.
+    pub path: NestingPath,
+    /// Whether the item belongs to a module or a component.
+    pub kind: Kind,
+    /// The name of the custom section holding the item.
+    pub section: String,
+    /// The range of the whole custom section, including its id, size, and
+    /// name, within the outermost binary.
+    ///
+    /// Removing these bytes, and updating the size of each section that the
+    /// item is nested within, removes the item.
+    pub range: Range<usize>,
+    /// The range of the data of the custom section, following its name,
+    /// within the outermost binary.
+    pub data_range: Range<usize>,
+    /// The parsed contents of the item.
+    pub value: MetadataValue,
+}
+
+/// Returns every custom section of `wasm` and of every module and component
+/// nested within it, outermost first and in the order they appear, along
+/// with their byte ranges and parsed contents.
+pub fn dump(wasm: &[u8]) -> Result<Vec<MetadataItem>> {
+    let mut items = Vec::new();
+    sections::walk(wasm, &mut |path, binary, offset| {
+        for section in &binary.sections {
+            let (name, data, data_offset) = match section.custom()? {
+                Some(custom) => custom,
+                None => continue,
+            };
+            let value = parse(name, data, offset + data_offset)
+                .with_context(|| format!("invalid `{name}` section in `{path}`"))?;
+            let start = offset + data_offset;
+            items.push(MetadataItem {
+                path: path.clone(),
+                kind: binary.kind,
+                section: name.to_string(),
+                range: offset + section.range.start..offset + section.range.end,
+                data_range: start..start + data.len(),
+                value,
+            });
+        }
+        Ok(())
+    })?;
+    Ok(items)
+}
+
+fn parse(name: &str, data: &[u8], offset: usize) -> Result<MetadataValue> {
+    if let Some(key) = name.strip_prefix(ENTRY_SECTION_PREFIX) {
+        return Ok(MetadataValue::Entry {
+            key: key.to_string(),
+            value: Value::decode(key, data)?,
+        });
+    }
+    if let Some(annotation) = Annotation::from_section_name(name) {
+        let value = std::str::from_utf8(data).context("annotation is not valid UTF-8")?;
+        return Ok(MetadataValue::Annotation {
+            annotation,
+            value: value.to_string(),
+        });
+    }
+    Ok(match name {
+        PRODUCERS_SECTION => MetadataValue::Producers {
+            producers: Producers::from_section(data, offset)?,
+        },
+        "name" => {
+            let mut names = IndexMap::new();
+            read_names(data, offset, &mut |item, name| {
+                names.insert(item, name.to_string());
+            })?;
+            MetadataValue::Names { names }
+        }
+        SBOM_SECTION => MetadataValue::Sbom {
+            sbom: Sbom::parse(data)?,
+        },
+        SIGNATURE_SECTION => MetadataValue::Signature {
+            signature: SignatureSection::parse(data, offset)?,
+        },
+        _ => MetadataValue::Custom,
+    })
+}
+
+/// Calls `insert` with each item of a `name` section, such as `function 3`,
+/// and its name.
+pub(crate) fn read_names(
+    data: &[u8],
+    offset: usize,
+    insert: &mut dyn FnMut(String, &str),
+) -> Result<()> {
+    let mut reader = NameSectionReader::new(data, offset)?;
+    while !reader.eof() {
+        let (kind, map) = match reader.read()? {
+            Name::Module(name) => {
+                insert("module".to_string(), name.get_name()?);
+                continue;
+            }
+            Name::Local(map) => {
+                read_indirect_name_map("local", map, insert)?;
+                continue;
+            }
+            Name::Label(map) => {
+                read_indirect_name_map("label", map, insert)?;
+                continue;
+            }
+            Name::Function(map) => ("function", map),
+            Name::Type(map) => ("type", map),
+            Name::Table(map) => ("table", map),
+            Name::Memory(map) => ("memory", map),
+            Name::Global(map) => ("global", map),
+            Name::Element(map) => ("element", map),
+            Name::Data(map) => ("data", map),
+            Name::Unknown { .. } => continue,
+        };
+        read_name_map(kind, map, insert)?;
+    }
+    Ok(())
+}
+
+fn read_name_map(kind: &str, map: NameMap<'_>, insert: &mut dyn FnMut(String, &str)) -> Result<()> {
+    let mut map = map.get_map()?;
+    for _ in 0..map.get_count() {
+        let naming = map.read()?;
+        insert(format!("{kind} {}", naming.index), naming.name);
+    }
+    Ok(())
+}
+
+fn read_indirect_name_map(
+    kind: &str,
+    map: IndirectNameMap<'_>,
+    insert: &mut dyn FnMut(String, &str),
+) -> Result<()> {
+    let mut map = map.get_indirect_map()?;
+    for _ in 0..map.get_indirect_count() {
+        let indirect = map.read()?;
+        let mut names = indirect.get_map()?;
+        for _ in 0..names.get_count() {
+            let naming = names.read()?;
+            let item = format!("{kind} {} {}", indirect.indirect_index, naming.index);
+            insert(item, naming.name);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,131 @@
+//! Arbitrary named metadata entries, such as build ids and git commits.
+//!
+//! Each entry is stored in its own custom section named
+//! `metadata:<key>` at the top level of a module or component. The data of
+//! the section is a single byte for the kind of value, `0` for a string or
+//! `1` for JSON, followed by the value encoded as UTF-8.
+
+use crate::sections::{self, Binary};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::fmt;
+
+/// The prefix of the names of the custom sections holding entries.
+pub const ENTRY_SECTION_PREFIX: &str = "metadata:";
+
+const STRING_VALUE: u8 = 0;
+const JSON_VALUE: u8 = 1;
+
+/// The value of a metadata entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Value {
+    /// A plain string.
+    String(String),
+    /// A JSON document.
+    Json(serde_json::Value),
+}
+
+impl Value {
+    fn encode(&self) -> Vec<u8> {
+        let (kind, text) = match self {
+            Value::String(s) => (STRING_VALUE, s.clone()),
+            Value::Json(json) => (JSON_VALUE, json.to_string()),
+        };
+        let mut data = vec![kind];
+        data.extend_from_slice(text.as_bytes());
+        data
+    }
+
+    pub(crate) fn decode(key: &str, data: &[u8]) -> Result<Value> {
+        let (kind, text) = match data.split_first() {
+            Some((kind, text)) => (*kind, text),
+            None => bail!("metadata entry `{key}` is empty"),
+        };
+        let text = std::str::from_utf8(text)
+            .with_context(|| format!("metadata entry `{key}` is not valid UTF-8"))?;
+        match kind {
+            STRING_VALUE => Ok(Value::String(text.to_string())),
+            JSON_VALUE => Ok(Value::Json(
+                serde_json::from_str(text)
+                    .with_context(|| format!("metadata entry `{key}` is not valid JSON"))?,
+            )),
+            _ => bail!("metadata entry `{key}` has unknown value kind {kind}"),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(s) => f.write_str(s),
+            Value::Json(json) => write!(f, "{json}"),
+        }
+    }
+}
+
+fn section_name(key: &str) -> Result<String> {
+    if key.is_empty() {
+        bail!("metadata entry keys must not be empty");
+    }
+    Ok(format!("{ENTRY_SECTION_PREFIX}{key}"))
+}
+
+/// Returns the value of the entry `key` in `wasm`, if there is one.
+///
+/// If there are several sections for `key`, the last one wins.
+pub fn get_entry(wasm: &[u8], key: &str) -> Result<Option<Value>> {
+    let name = section_name(key)?;
+    let binary = Binary::parse(wasm)?;
+    let mut value = None;
+    for section in &binary.sections {
+        if let Some((data, _)) = section.custom_named(&name)? {
+            value = Some(Value::decode(key, data)?);
+        }
+    }
+    Ok(value)
+}
+
+/// Returns every entry in `wasm`, in the order their sections appear.
+pub fn entries(wasm: &[u8]) -> Result<Vec<(String, Value)>> {
+    let binary = Binary::parse(wasm)?;
+    let mut entries = Vec::<(String, Value)>::new();
+    for section in &binary.sections {
+        let (name, data, _) = match section.custom()? {
+            Some(custom) => custom,
+            None => continue,
+        };
+        let key = match name.strip_prefix(ENTRY_SECTION_PREFIX) {
+            Some(key) => key,
+            None => continue,
+        };
+        let value = Value::decode(key, data)?;
+        match entries.iter_mut().find(|(k, _)| k == key) {
+            Some(entry) => entry.1 = value,
+            None => entries.push((key.to_string(), value)),
+        }
+    }
+    Ok(entries)
+}
+
+/// Returns `wasm` with the entry `key` set to `value`.
+///
+/// An existing entry is replaced in place; otherwise the entry is appended
+/// to the end of the module or component.
+pub fn set_entry(wasm: &[u8], key: &str, value: &Value) -> Result<Vec<u8>> {
+    sections::replace_custom_section(wasm, &section_name(key)?, Some(&value.encode()))
+}
+
+/// Returns `wasm` without the entry `key`, along with whether it had one.
+///
+/// The value of the entry isn't decoded, so malformed entries can be
+/// removed too.
+pub fn remove_entry(wasm: &[u8], key: &str) -> Result<(Vec<u8>, bool)> {
+    let name = section_name(key)?;
+    let mut had_entry = false;
+    for section in &Binary::parse(wasm)?.sections {
+        had_entry |= section.custom_named(&name)?.is_some();
+    }
+    let wasm = sections::replace_custom_section(wasm, &name, None)?;
+    Ok((wasm, had_entry))
+}
@@ -0,0 +1,77 @@
+//! Read and manipulate the metadata of WebAssembly modules and components.
+//!
+//! Metadata is stored in custom sections, which this crate reads and
+//! rewrites without touching the rest of a binary. Components are walked
+//! recursively, so the metadata of every module and component nested within
+//! one can be collected in a single call:
+//!
+//! ```
+//! use wasm_metadata::{Producers, ProducersReport};
+//!
+//! let mut producers = Producers::new();
+//! producers.add("language", "Rust", "");
+//! let wasm = producers.add_to(&wat::parse_str("(module)").unwrap()).unwrap();
+//!
+//! let report = ProducersReport::from_wasm(&wasm).unwrap();
+//! assert_eq!(report.tools()[0].name, "Rust");
+//! ```
+//!
+//! Build systems can also stamp arbitrary named entries, such as a build id
+//! or git commit, into a module or component:
+//!
+//! ```
+//! use wasm_metadata::{get_entry, set_entry, Value};
+//!
+//! let wasm = wat::parse_str("(component)").unwrap();
+//! let value = Value::String("3a10b20".to_string());
+//! let wasm = set_entry(&wasm, "git-sha", &value).unwrap();
+//! assert_eq!(get_entry(&wasm, "git-sha").unwrap(), Some(value));
+//! ```
+//!
+//! Descriptive annotations, such as the license and source of the code, can
+//! be added to or updated in place:
+//!
+//! ```
+//! use wasm_metadata::{Annotation, Annotations};
+//!
+//! let mut annotations = Annotations::new();
+//! annotations.license = Some("Apache-2.0 WITH LLVM-exception".to_string());
+//! let wasm = annotations.add_to(&wat::parse_str("(module)").unwrap()).unwrap();
+//!
+//! let annotations = Annotations::from_wasm(&wasm).unwrap();
+//! assert_eq!(annotations.get(Annotation::Source), None);
+//! ```
+//!
+//! Artifacts can be signed and verified too, with Ed25519 keys or any other
+//! [`Signer`] and [`Verifier`]:
+//!
+//! ```
+//! use wasm_metadata::{sign, verify, Ed25519Keys, Ed25519Signer};
+//!
+//! let signer = Ed25519Signer::from_seed([7; 32]);
+//! let wasm = sign(&wat::parse_str("(module)").unwrap(), &signer).unwrap();
+//!
+//! let mut keys = Ed25519Keys::new();
+//! keys.add_hex(&signer.public_key_hex()).unwrap();
+//! assert!(verify(&wasm, &keys).unwrap().is_verified());
+//! ```
+
+#![deny(missing_docs)]
+
+mod annotations;
+mod diff;
+mod dump;
+mod entries;
+mod producers;
+mod sbom;
+mod sections;
+mod signature;
+
+pub use annotations::*;
+pub use diff::*;
+pub use dump::*;
+pub use entries::*;
+pub use producers::*;
+pub use sbom::*;
+pub use sections::{Kind, NestingPath};
+pub use signature::*;
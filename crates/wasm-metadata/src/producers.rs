@@ -0,0 +1,220 @@
+//! The `producers` custom section and reports aggregated over nested
+//! binaries.
+//!
+//! See the [tool conventions] for the format of the section.
+//!
+//! [tool conventions]: https://github.com/WebAssembly/tool-conventions/blob/main/ProducersSection.md
+
+use crate::sections::{self, Binary, Kind, NestingPath};
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::Serialize;
+use wasm_encoder::Encode;
+use wasmparser::ProducersSectionReader;
+
+/// The name of the custom section holding producers.
+pub const PRODUCERS_SECTION: &str = "producers";
+
+/// The contents of a `producers` custom section.
+///
+/// Fields (such as `language`, `processed-by`, and `sdk`) map the names of
+/// tools to their versions, both in the order they were added.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Producers(IndexMap<String, IndexMap<String, String>>);
+
+impl Producers {
+    /// Creates an empty set of producers.
+    pub fn new() -> Producers {
+        Producers::default()
+    }
+
+    /// Reads the `producers` section at the top level of `wasm`.
+    ///
+    /// Sections of nested modules and components aren't read; see
+    /// [`ProducersReport`] for those. Returns `None` if there's no
+    /// `producers` section.
+    pub fn from_wasm(wasm: &[u8]) -> Result<Option<Producers>> {
+        Self::from_binary(&Binary::parse(wasm)?)
+    }
+
+    pub(crate) fn from_binary(binary: &Binary<'_>) -> Result<Option<Producers>> {
+        let mut result = None;
+        for section in &binary.sections {
+            if let Some((data, offset)) = section.custom_named(PRODUCERS_SECTION)? {
+                let producers = result.get_or_insert_with(Producers::new);
+                producers.merge(&Producers::from_section(data, offset)?);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Parses the data of a `producers` custom section found at `offset`.
+    pub fn from_section(data: &[u8], offset: usize) -> Result<Producers> {
+        let mut producers = Producers::new();
+        let mut reader = ProducersSectionReader::new(data, offset)?;
+        for _ in 0..reader.get_count() {
+            let field = reader.read()?;
+            for value in field.get_producer_field_values_reader()? {
+                let value = value?;
+                producers.add(field.name, value.name, value.version);
+            }
+        }
+        Ok(producers)
+    }
+
+    /// Adds the tool `name` at `version` to `field`, replacing any version
+    /// already recorded for it.
+    pub fn add(&mut self, field: &str, name: &str, version: &str) {
+        self.0
+            .entry(field.to_string())
+            .or_default()
+            .insert(name.to_string(), version.to_string());
+    }
+
+    /// Adds every tool in `other`, whose versions take precedence.
+    pub fn merge(&mut self, other: &Producers) {
+        for (field, values) in other.fields() {
+            for (name, version) in values {
+                self.add(field, name, version);
+            }
+        }
+    }
+
+    /// Returns the tools recorded for `field`.
+    pub fn get(&self, field: &str) -> Option<&IndexMap<String, String>> {
+        self.0.get(field)
+    }
+
+    /// Returns every field and the tools recorded for it.
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &IndexMap<String, String>)> {
+        self.0
+            .iter()
+            .map(|(field, values)| (field.as_str(), values))
+    }
+
+    /// Returns whether no tools are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.0.values().all(|values| values.is_empty())
+    }
+
+    /// Encodes the data of a `producers` custom section.
+    pub fn section_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        self.0.len().encode(&mut data);
+        for (field, values) in &self.0 {
+            field.as_str().encode(&mut data);
+            values.len().encode(&mut data);
+            for (name, version) in values {
+                name.as_str().encode(&mut data);
+                version.as_str().encode(&mut data);
+            }
+        }
+        data
+    }
+
+    /// Returns `wasm` with these producers merged into its top-level
+    /// `producers` section.
+    ///
+    /// The tools already recorded are kept, and the existing sections are
+    /// replaced by a single one.
+    pub fn add_to(&self, wasm: &[u8]) -> Result<Vec<u8>> {
+        let mut producers = Producers::from_wasm(wasm)?.unwrap_or_default();
+        producers.merge(self);
+        sections::replace_custom_section(wasm, PRODUCERS_SECTION, Some(&producers.section_data()))
+    }
+}
+
+/// The `producers` section of one module or component.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProducersEntry {
+    /// Where the module or component is nested.
+    pub path: NestingPath,
+    /// Whether this is a module or a component.
+    pub kind: Kind,
+    /// The contents of its `producers` section.
+    pub producers: Producers,
+}
+
+/// The number of modules and components which record a tool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ToolCount {
+    /// The field the tool is recorded in, such as `processed-by`.
+    pub field: String,
+    /// The name of the tool.
+    pub name: String,
+    /// The version of the tool.
+    pub version: String,
+    /// How many `producers` sections record this tool at this version.
+    pub count: usize,
+}
+
+/// The `producers` sections of a module or component and of every module and
+/// component nested within it.
+///
+/// This answers which toolchains touched an artifact: [`tools`] totals each
+/// tool and version across all of them.
+///
+/// [`tools`]: ProducersReport::tools
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProducersReport {
+    /// The binaries which have a `producers` section, outermost first and in
+    /// the order they're defined.
+    pub entries: Vec<ProducersEntry>,
+}
+
+impl ProducersReport {
+    /// Collects the `producers` sections of `wasm` and everything nested
+    /// within it.
+    pub fn from_wasm(wasm: &[u8]) -> Result<ProducersReport> {
+        let mut entries = Vec::new();
+        sections::walk(wasm, &mut |path, binary, _| {
+            if let Some(producers) = Producers::from_binary(binary)? {
+                entries.push(ProducersEntry {
+                    path: path.clone(),
+                    kind: binary.kind,
+                    producers,
+                });
+            }
+            Ok(())
+        })?;
+        Ok(ProducersReport { entries })
+    }
+
+    /// Returns the `producers` section of the binary at `path`, if it has
+    /// one.
+    pub fn get(&self, path: &NestingPath) -> Option<&Producers> {
+        self.entries
+            .iter()
+            .find(|e| e.path == *path)
+            .map(|e| &e.producers)
+    }
+
+    /// Counts the sections recording each tool at each version.
+    ///
+    /// Tools are grouped by field and otherwise listed in the order they're
+    /// first seen.
+    pub fn tools(&self) -> Vec<ToolCount> {
+        let mut counts = IndexMap::<_, IndexMap<_, usize>>::new();
+        for entry in &self.entries {
+            for (field, values) in entry.producers.fields() {
+                let field = counts.entry(field).or_default();
+                for (name, version) in values {
+                    *field.entry((name.as_str(), version.as_str())).or_default() += 1;
+                }
+            }
+        }
+        counts
+            .into_iter()
+            .flat_map(|(field, tools)| {
+                tools
+                    .into_iter()
+                    .map(move |((name, version), count)| ToolCount {
+                        field: field.to_string(),
+                        name: name.to_string(),
+                        version: version.to_string(),
+                        count,
+                    })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,167 @@
+//! Software bills of materials.
+//!
+//! An SBOM is embedded as a JSON document in an `sbom` custom section at the
+//! top level of a module or component. Both [SPDX] and [CycloneDX] documents
+//! are supported, and are told apart by their `spdxVersion` and `bomFormat`
+//! fields respectively.
+//!
+//! [SPDX]: https://spdx.dev/
+//! [CycloneDX]: https://cyclonedx.org/
+
+use crate::sections::{self, Kind, NestingPath};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+/// The name of the custom section holding an SBOM.
+pub const SBOM_SECTION: &str = "sbom";
+
+/// The format of an SBOM document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SbomFormat {
+    /// An SPDX JSON document.
+    Spdx,
+    /// A CycloneDX JSON document.
+    #[serde(rename = "cyclonedx")]
+    CycloneDx,
+}
+
+/// An SBOM document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Sbom {
+    /// The format of the document.
+    pub format: SbomFormat,
+    /// The document.
+    pub document: Value,
+}
+
+impl Sbom {
+    /// Parses an SPDX or CycloneDX JSON document.
+    pub fn parse(json: &[u8]) -> Result<Sbom> {
+        let document: Value = serde_json::from_slice(json).context("SBOM is not valid JSON")?;
+        Sbom::new(document)
+    }
+
+    /// Detects the format of `document`.
+    pub fn new(document: Value) -> Result<Sbom> {
+        let format = if document.get("spdxVersion").is_some() {
+            SbomFormat::Spdx
+        } else if document.get("bomFormat").and_then(Value::as_str) == Some("CycloneDX") {
+            SbomFormat::CycloneDx
+        } else {
+            bail!("SBOM is neither an SPDX nor a CycloneDX JSON document");
+        };
+        Ok(Sbom { format, document })
+    }
+
+    /// Reads the SBOM at the top level of `wasm`, if it has one.
+    ///
+    /// SBOMs of nested modules and components aren't read; see
+    /// [`extract_sboms`] for those.
+    pub fn from_wasm(wasm: &[u8]) -> Result<Option<Sbom>> {
+        let mut result = None;
+        for section in &sections::Binary::parse(wasm)?.sections {
+            if let Some((data, _)) = section.custom_named(SBOM_SECTION)? {
+                result = Some(Sbom::parse(data)?);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns `wasm` with this SBOM embedded at its top level, replacing any
+    /// SBOM already there.
+    pub fn add_to(&self, wasm: &[u8]) -> Result<Vec<u8>> {
+        let json = serde_json::to_vec(&self.document)?;
+        sections::replace_custom_section(wasm, SBOM_SECTION, Some(&json))
+    }
+}
+
+/// The SBOM of one module or component.
+#[derive(Debug, Clone, Serialize)]
+pub struct SbomEntry {
+    /// Where the module or component is nested.
+    pub path: NestingPath,
+    /// Whether this is a module or a component.
+    pub kind: Kind,
+    /// The SBOM.
+    pub sbom: Sbom,
+}
+
+/// Returns the SBOMs of `wasm` and of every module and component nested
+/// within it, outermost first and in the order they're defined.
+pub fn extract_sboms(wasm: &[u8]) -> Result<Vec<SbomEntry>> {
+    let mut entries = Vec::new();
+    sections::walk(wasm, &mut |path, binary, _| {
+        for section in &binary.sections {
+            if let Some((data, _)) = section.custom_named(SBOM_SECTION)? {
+                let sbom =
+                    Sbom::parse(data).with_context(|| format!("invalid SBOM in `{path}`"))?;
+                entries.push(SbomEntry {
+                    path: path.clone(),
+                    kind: binary.kind,
+                    sbom,
+                });
+            }
+        }
+        Ok(())
+    })?;
+    Ok(entries)
+}
+
+/// Merges SBOMs of the same format into one.
+///
+/// The first SBOM is the base of the result, so its document-level fields,
+/// such as its name and creation info, are kept. The SPDX `packages`,
+/// `files`, and `relationships`, or the CycloneDX `components` and
+/// `dependencies`, of the others are appended to it, skipping any already
+/// present. Elements are identified by their `SPDXID`, `bom-ref`, or `ref`
+/// if they have one and are otherwise compared in full.
+pub fn merge_sboms(sboms: &[Sbom]) -> Result<Sbom> {
+    let (first, rest) = match sboms.split_first() {
+        Some(split) => split,
+        None => bail!("no SBOMs to merge"),
+    };
+    let mut merged = first.clone();
+    // The arrays to merge and the field identifying their elements.
+    let arrays: &[(&str, &str)] = match first.format {
+        SbomFormat::Spdx => &[
+            ("packages", "SPDXID"),
+            ("files", "SPDXID"),
+            ("relationships", ""),
+        ],
+        SbomFormat::CycloneDx => &[("components", "bom-ref"), ("dependencies", "ref")],
+    };
+    for sbom in rest {
+        if sbom.format != first.format {
+            bail!("cannot merge SBOMs of different formats");
+        }
+        for (name, id) in arrays {
+            let items = match sbom.document.get(name).and_then(Value::as_array) {
+                Some(items) => items,
+                None => continue,
+            };
+            let target = merged
+                .document
+                .as_object_mut()
+                .context("SBOM document is not a JSON object")?
+                .entry(name.to_string())
+                .or_insert_with(|| Value::Array(Vec::new()));
+            let target = match target.as_array_mut() {
+                Some(target) => target,
+                None => bail!("SBOM field `{name}` is not an array"),
+            };
+            for item in items {
+                let key = |v: &Value| v.get(*id).cloned();
+                let present = target.iter().any(|t| match (key(t), key(item)) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => t == item,
+                });
+                if !present {
+                    target.push(item.clone());
+                }
+            }
+        }
+    }
+    Ok(merged)
+}
@@ -0,0 +1,245 @@
+//! A shallow view of the top-level sections of a module or component.
+//!
+//! Metadata lives in custom sections, so nothing here needs the rest of a
+//! binary to be parsed: sections are split by their id and size only, and
+//! sections which aren't rewritten are copied through byte-for-byte.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::fmt;
+use std::ops::Range;
+use wasm_encoder::{CustomSection, Encode};
+use wasmparser::BinaryReader;
+
+/// The id of the component section containing a nested core module.
+const COMPONENT_CORE_MODULE_SECTION: u8 = 1;
+
+/// The id of the component section containing a nested component.
+const COMPONENT_COMPONENT_SECTION: u8 = 4;
+
+/// Whether a binary is a core module or a component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Kind {
+    /// A core WebAssembly module.
+    Module,
+    /// A component.
+    Component,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Module => f.write_str("module"),
+            Kind::Component => f.write_str("component"),
+        }
+    }
+}
+
+/// The location of a module or component nested within a component.
+///
+/// Each step is the kind of the nested binary and its index among the
+/// modules or components defined directly in its parent. The path of the
+/// outermost binary is empty and is displayed as `/`; the second core module
+/// of the first nested component is displayed as `/component[0]/module[1]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NestingPath(Vec<(Kind, u32)>);
+
+impl NestingPath {
+    /// Returns whether this is the path of the outermost binary.
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the steps from the outermost binary to this one.
+    pub fn steps(&self) -> &[(Kind, u32)] {
+        &self.0
+    }
+
+    fn join(&self, kind: Kind, index: u32) -> NestingPath {
+        let mut steps = self.0.clone();
+        steps.push((kind, index));
+        NestingPath(steps)
+    }
+}
+
+impl fmt::Display for NestingPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("/");
+        }
+        for (kind, index) in &self.0 {
+            write!(f, "/{kind}[{index}]")?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for NestingPath {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A module or component split into its header and top-level sections.
+pub(crate) struct Binary<'a> {
+    pub(crate) kind: Kind,
+    pub(crate) header: &'a [u8],
+    pub(crate) sections: Vec<Section<'a>>,
+}
+
+/// A top-level section of a [`Binary`].
+pub(crate) struct Section<'a> {
+    pub(crate) id: u8,
+    /// The range of the whole section, including its id and size, within
+    /// the binary.
+    pub(crate) range: Range<usize>,
+    /// The bytes of the whole section, including its id and size.
+    pub(crate) raw: &'a [u8],
+    /// The contents of the section following its size.
+    pub(crate) contents: &'a [u8],
+    /// The offset of `contents` within the binary.
+    pub(crate) contents_offset: usize,
+}
+
+impl<'a> Section<'a> {
+    /// Returns the name, data, and offset of the data of a custom section.
+    pub(crate) fn custom(&self) -> Result<Option<(&'a str, &'a [u8], usize)>> {
+        if self.id != 0 {
+            return Ok(None);
+        }
+        let mut reader = BinaryReader::new_with_offset(self.contents, self.contents_offset);
+        let name = reader.read_string()?;
+        let start = reader.current_position();
+        Ok(Some((
+            name,
+            &self.contents[start..],
+            self.contents_offset + start,
+        )))
+    }
+
+    /// Returns the data of this section if it's a custom section named
+    /// `name`.
+    pub(crate) fn custom_named(&self, name: &str) -> Result<Option<(&'a [u8], usize)>> {
+        Ok(match self.custom()? {
+            Some((n, data, offset)) if n == name => Some((data, offset)),
+            _ => None,
+        })
+    }
+}
+
+impl<'a> Binary<'a> {
+    /// Splits `wasm` into its header and top-level sections.
+    pub(crate) fn parse(wasm: &'a [u8]) -> Result<Binary<'a>> {
+        if wasm.len() < 8 || &wasm[..4] != b"\0asm" {
+            bail!("input is not a WebAssembly module or component");
+        }
+        let kind = match wasm[6..8] {
+            [0, 0] => Kind::Module,
+            [1, 0] => Kind::Component,
+            _ => bail!("unknown WebAssembly binary layer"),
+        };
+        let mut reader = BinaryReader::new_with_offset(&wasm[8..], 8);
+        let mut sections = Vec::new();
+        while !reader.eof() {
+            let start = reader.original_position();
+            let id = reader.read_u8()?;
+            let size = reader.read_var_u32()? as usize;
+            let contents_offset = reader.original_position();
+            let contents = reader.read_bytes(size)?;
+            let end = reader.original_position();
+            sections.push(Section {
+                id,
+                range: start..end,
+                raw: &wasm[start..end],
+                contents,
+                contents_offset,
+            });
+        }
+        Ok(Binary {
+            kind,
+            header: &wasm[..8],
+            sections,
+        })
+    }
+
+    /// Returns the modules and components defined directly in this binary,
+    /// along with the offset of each within it.
+    pub(crate) fn nested(&self) -> impl Iterator<Item = (Kind, &'a [u8], usize)> + '_ {
+        let is_component = self.kind == Kind::Component;
+        self.sections.iter().filter_map(move |s| {
+            let kind = match s.id {
+                COMPONENT_CORE_MODULE_SECTION if is_component => Kind::Module,
+                COMPONENT_COMPONENT_SECTION if is_component => Kind::Component,
+                _ => return None,
+            };
+            Some((kind, s.contents, s.contents_offset))
+        })
+    }
+}
+
+/// Calls `f` with `wasm` and then with every module and component nested
+/// within it, outermost first and in the order they're defined.
+///
+/// The offset passed to `f` is that of each binary within `wasm`.
+pub(crate) fn walk(
+    wasm: &[u8],
+    f: &mut dyn FnMut(&NestingPath, &Binary<'_>, usize) -> Result<()>,
+) -> Result<()> {
+    walk_at(wasm, &NestingPath::default(), 0, f)
+}
+
+fn walk_at(
+    wasm: &[u8],
+    path: &NestingPath,
+    offset: usize,
+    f: &mut dyn FnMut(&NestingPath, &Binary<'_>, usize) -> Result<()>,
+) -> Result<()> {
+    let binary = Binary::parse(wasm)?;
+    f(path, &binary, offset)?;
+    let mut modules = 0;
+    let mut components = 0;
+    for (kind, nested, nested_offset) in binary.nested() {
+        let count = match kind {
+            Kind::Module => &mut modules,
+            Kind::Component => &mut components,
+        };
+        walk_at(nested, &path.join(kind, *count), offset + nested_offset, f)?;
+        *count += 1;
+    }
+    Ok(())
+}
+
+/// Encodes a custom section, including its id and size.
+pub(crate) fn custom_section(name: &str, data: &[u8]) -> Vec<u8> {
+    let mut section = vec![0];
+    CustomSection { name, data }.encode(&mut section);
+    section
+}
+
+/// Rebuilds `wasm` with every top-level custom section named `name` removed
+/// and, if `data` is given, a single custom section named `name` holding it
+/// in place of the first one removed, or at the end if there were none.
+pub(crate) fn replace_custom_section(
+    wasm: &[u8],
+    name: &str,
+    data: Option<&[u8]>,
+) -> Result<Vec<u8>> {
+    let binary = Binary::parse(wasm)?;
+    let mut result = binary.header.to_vec();
+    let mut replaced = false;
+    for section in &binary.sections {
+        if section.custom_named(name)?.is_none() {
+            result.extend_from_slice(section.raw);
+            continue;
+        }
+        if let (Some(data), false) = (data, replaced) {
+            result.extend(custom_section(name, data));
+        }
+        replaced = true;
+    }
+    if let (Some(data), false) = (data, replaced) {
+        result.extend(custom_section(name, data));
+    }
+    Ok(result)
+}
@@ -0,0 +1,437 @@
+//! Embedded digests and signatures.
+//!
+//! A module or component is signed over its canonical view: its header
+//! followed by every top-level section except `signature` custom sections,
+//! in order. The SHA-256 digest of that view is stored in a `signature`
+//! custom section along with any number of signatures over the digest, so
+//! signing never changes the bytes being signed and several parties can sign
+//! the same artifact.
+//!
+//! Keys are handled by implementations of [`Signer`] and [`Verifier`]; Ed25519
+//! keys are supported out of the box by [`Ed25519Signer`] and
+//! [`Ed25519Keys`].
+//!
+//! See [`SignatureSection`] for the format of the section.
+
+use crate::sections::{self, Binary};
+use anyhow::{bail, Context, Result};
+use ed25519_compact::{KeyPair, PublicKey, Seed};
+use serde::Serialize;
+use sha2::{Digest as _, Sha256};
+use std::collections::HashMap;
+use std::ops::Range;
+use wasm_encoder::Encode;
+use wasmparser::BinaryReader;
+
+/// The name of the custom section holding the digest and signatures.
+pub const SIGNATURE_SECTION: &str = "signature";
+
+/// The name of the only supported digest algorithm.
+pub const SHA256: &str = "sha256";
+
+/// The name of the Ed25519 signature algorithm.
+pub const ED25519: &str = "ed25519";
+
+const FORMAT_VERSION: u8 = 1;
+
+fn serialize_hex<S: serde::Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex::encode(bytes))
+}
+
+/// The digest of the canonical view of a module or component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Digest {
+    /// The digest algorithm, which is always [`SHA256`].
+    pub algorithm: String,
+    /// The digest.
+    #[serde(serialize_with = "serialize_hex")]
+    pub bytes: Vec<u8>,
+    /// The ranges of the binary which the digest covers, in order.
+    ///
+    /// Everything outside of these ranges is a `signature` section.
+    pub covered: Vec<Range<usize>>,
+}
+
+impl Digest {
+    /// Computes the digest of the canonical view of `wasm`.
+    pub fn of(wasm: &[u8]) -> Result<Digest> {
+        let binary = Binary::parse(wasm)?;
+        let mut hasher = Sha256::new();
+        let mut covered: Vec<Range<usize>> = Vec::new();
+        let mut cover = |range: Range<usize>| {
+            hasher.update(&wasm[range.clone()]);
+            match covered.last_mut() {
+                Some(last) if last.end == range.start => last.end = range.end,
+                _ => covered.push(range),
+            }
+        };
+        cover(0..binary.header.len());
+        for section in &binary.sections {
+            if section.custom_named(SIGNATURE_SECTION)?.is_none() {
+                cover(section.range.clone());
+            }
+        }
+        Ok(Digest {
+            algorithm: SHA256.to_string(),
+            bytes: hasher.finalize().to_vec(),
+            covered,
+        })
+    }
+}
+
+/// A signature over the digest of a module or component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Signature {
+    /// The signature algorithm, such as [`ED25519`].
+    pub algorithm: String,
+    /// Identifies the key which made the signature.
+    pub key_id: String,
+    /// The signature.
+    #[serde(serialize_with = "serialize_hex")]
+    pub bytes: Vec<u8>,
+}
+
+/// The contents of a `signature` custom section.
+///
+/// The format of the section is defined by this crate and isn't shared with
+/// other WebAssembly signing tools. Its data is laid out as follows, where
+/// strings are encoded like WebAssembly names, byte strings are prefixed with
+/// their LEB128 length, and vectors with their LEB128 count:
+///
+/// ```text
+/// section   ::= 0x01                  ;; format version
+///               algorithm:string      ;; digest algorithm, always "sha256"
+///               digest:bytes
+///               signatures:vec(signature)
+/// signature ::= algorithm:string      ;; such as "ed25519"
+///               key-id:string
+///               signature:bytes
+/// ```
+///
+/// Signatures are made over the raw bytes of the digest. The key id of an
+/// Ed25519 key is the hex encoding of its public key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SignatureSection {
+    /// The digest algorithm, which is always [`SHA256`].
+    pub digest_algorithm: String,
+    /// The digest of the canonical view when the section was written.
+    #[serde(serialize_with = "serialize_hex")]
+    pub digest: Vec<u8>,
+    /// The signatures over the digest.
+    pub signatures: Vec<Signature>,
+}
+
+impl SignatureSection {
+    /// Reads the `signature` section at the top level of `wasm`, if it has
+    /// one.
+    ///
+    /// It's an error for there to be more than one.
+    pub fn from_wasm(wasm: &[u8]) -> Result<Option<SignatureSection>> {
+        let mut result = None;
+        for section in &Binary::parse(wasm)?.sections {
+            if let Some((data, offset)) = section.custom_named(SIGNATURE_SECTION)? {
+                if result.is_some() {
+                    bail!("found more than one `{SIGNATURE_SECTION}` section");
+                }
+                result = Some(SignatureSection::parse(data, offset)?);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Parses the data of a `signature` custom section found at `offset`.
+    pub fn parse(data: &[u8], offset: usize) -> Result<SignatureSection> {
+        let mut reader = BinaryReader::new_with_offset(data, offset);
+        let version = reader.read_u8()?;
+        if version != FORMAT_VERSION {
+            bail!("unsupported `{SIGNATURE_SECTION}` section version {version}");
+        }
+        let digest_algorithm = reader.read_string()?.to_string();
+        let digest = read_byte_string(&mut reader)?;
+        let mut signatures = Vec::new();
+        for _ in 0..reader.read_var_u32()? {
+            signatures.push(Signature {
+                algorithm: reader.read_string()?.to_string(),
+                key_id: reader.read_string()?.to_string(),
+                bytes: read_byte_string(&mut reader)?,
+            });
+        }
+        if !reader.eof() {
+            bail!(
+                "unexpected data at the end of the `{SIGNATURE_SECTION}` section (at offset {:#x})",
+                reader.original_position()
+            );
+        }
+        Ok(SignatureSection {
+            digest_algorithm,
+            digest,
+            signatures,
+        })
+    }
+
+    /// Encodes the data of a `signature` custom section.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = vec![FORMAT_VERSION];
+        self.digest_algorithm.as_str().encode(&mut data);
+        self.digest.as_slice().encode(&mut data);
+        self.signatures.len().encode(&mut data);
+        for signature in &self.signatures {
+            signature.algorithm.as_str().encode(&mut data);
+            signature.key_id.as_str().encode(&mut data);
+            signature.bytes.as_slice().encode(&mut data);
+        }
+        data
+    }
+}
+
+fn read_byte_string(reader: &mut BinaryReader<'_>) -> Result<Vec<u8>> {
+    let len = reader.read_var_u32()?;
+    Ok(reader.read_bytes(len as usize)?.to_vec())
+}
+
+/// Makes signatures with a private key.
+pub trait Signer {
+    /// The name of the signature algorithm, such as [`ED25519`].
+    fn algorithm(&self) -> String;
+
+    /// Identifies the key, so that verifiers can find the matching public
+    /// key.
+    fn key_id(&self) -> String;
+
+    /// Signs `digest`, the raw bytes of the digest of the canonical view.
+    fn sign(&self, digest: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// The result of checking one signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SignatureStatus {
+    /// The signature was made over the digest by the key.
+    Valid,
+    /// The key is known, but it didn't make this signature over the digest.
+    Invalid,
+    /// The verifier doesn't know the key or its algorithm.
+    UnknownKey,
+}
+
+/// Checks signatures with public keys.
+pub trait Verifier {
+    /// Checks that `signature` was made over `digest`, the raw bytes of the
+    /// digest of the canonical view.
+    fn verify(&self, signature: &Signature, digest: &[u8]) -> Result<SignatureStatus>;
+}
+
+/// Returns `wasm` with a `signature` section holding the digest of its
+/// canonical view and no signatures.
+///
+/// This is enough to detect accidental changes with [`verify`].
+pub fn embed_digest(wasm: &[u8]) -> Result<Vec<u8>> {
+    let digest = Digest::of(wasm)?;
+    let section = SignatureSection {
+        digest_algorithm: digest.algorithm,
+        digest: digest.bytes,
+        signatures: Vec::new(),
+    };
+    sections::replace_custom_section(wasm, SIGNATURE_SECTION, Some(&section.encode()))
+}
+
+/// Returns `wasm` signed by `signer`.
+///
+/// Existing signatures are kept if the digest they were made over is still
+/// the digest of the canonical view, except for one made by the same key,
+/// which is replaced. Otherwise the binary has changed since it was signed
+/// and they're dropped.
+pub fn sign(wasm: &[u8], signer: &dyn Signer) -> Result<Vec<u8>> {
+    let digest = Digest::of(wasm)?;
+    let mut signatures = match SignatureSection::from_wasm(wasm)? {
+        Some(section) if section.digest == digest.bytes => section.signatures,
+        _ => Vec::new(),
+    };
+    let signature = Signature {
+        algorithm: signer.algorithm(),
+        key_id: signer.key_id(),
+        bytes: signer.sign(&digest.bytes)?,
+    };
+    signatures.retain(|s| s.algorithm != signature.algorithm || s.key_id != signature.key_id);
+    signatures.push(signature);
+    let section = SignatureSection {
+        digest_algorithm: digest.algorithm,
+        digest: digest.bytes,
+        signatures,
+    };
+    sections::replace_custom_section(wasm, SIGNATURE_SECTION, Some(&section.encode()))
+}
+
+/// The result of checking one signature of a module or component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SignatureCheck {
+    /// The signature algorithm.
+    pub algorithm: String,
+    /// Identifies the key which made the signature.
+    pub key_id: String,
+    /// Whether the signature is valid.
+    pub status: SignatureStatus,
+}
+
+/// The result of verifying a module or component.
+#[derive(Debug, Clone, Serialize)]
+pub struct Verification {
+    /// The digest of the canonical view, including the ranges it covers.
+    pub digest: Digest,
+    /// Whether the embedded digest is the digest of the canonical view, i.e.
+    /// whether the covered bytes are unchanged since the section was
+    /// written.
+    pub digest_matches: bool,
+    /// The result of checking each embedded signature, in order.
+    ///
+    /// Signatures are only checked if the digest matches.
+    pub signatures: Vec<SignatureCheck>,
+}
+
+impl Verification {
+    /// Returns whether the digest matches, at least one signature is valid,
+    /// and no signature made by a known key is invalid.
+    pub fn is_verified(&self) -> bool {
+        self.digest_matches
+            && self
+                .signatures
+                .iter()
+                .any(|s| s.status == SignatureStatus::Valid)
+            && self
+                .signatures
+                .iter()
+                .all(|s| s.status != SignatureStatus::Invalid)
+    }
+}
+
+/// Verifies the embedded digest and signatures of `wasm` with `verifier`.
+///
+/// It's an error for `wasm` not to have a `signature` section.
+pub fn verify(wasm: &[u8], verifier: &dyn Verifier) -> Result<Verification> {
+    let section = match SignatureSection::from_wasm(wasm)? {
+        Some(section) => section,
+        None => bail!("no `{SIGNATURE_SECTION}` section found"),
+    };
+    if section.digest_algorithm != SHA256 {
+        bail!(
+            "unsupported digest algorithm `{}`",
+            section.digest_algorithm
+        );
+    }
+    let digest = Digest::of(wasm)?;
+    let digest_matches = section.digest == digest.bytes;
+    let mut signatures = Vec::new();
+    if digest_matches {
+        for signature in &section.signatures {
+            signatures.push(SignatureCheck {
+                algorithm: signature.algorithm.clone(),
+                key_id: signature.key_id.clone(),
+                status: verifier.verify(signature, &digest.bytes)?,
+            });
+        }
+    }
+    Ok(Verification {
+        digest,
+        digest_matches,
+        signatures,
+    })
+}
+
+/// Signs with an Ed25519 key.
+///
+/// The key id is the hex encoding of the public key.
+pub struct Ed25519Signer {
+    key_pair: KeyPair,
+}
+
+impl Ed25519Signer {
+    /// Creates a signer from the 32-byte seed of a key pair.
+    pub fn from_seed(seed: [u8; 32]) -> Ed25519Signer {
+        Ed25519Signer {
+            key_pair: KeyPair::from_seed(Seed::new(seed)),
+        }
+    }
+
+    /// Creates a signer from the hex encoding of the 32-byte seed of a key
+    /// pair, such as the contents of a key file.
+    ///
+    /// Surrounding whitespace is ignored.
+    pub fn from_hex(seed: &str) -> Result<Ed25519Signer> {
+        let seed = hex::decode(seed.trim()).context("Ed25519 seed is not valid hex")?;
+        match <[u8; 32]>::try_from(seed) {
+            Ok(seed) => Ok(Ed25519Signer::from_seed(seed)),
+            Err(seed) => bail!("Ed25519 seed is {} bytes, not 32", seed.len()),
+        }
+    }
+
+    /// Returns the hex encoding of the public key.
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(*self.key_pair.pk)
+    }
+}
+
+impl Signer for Ed25519Signer {
+    fn algorithm(&self) -> String {
+        ED25519.to_string()
+    }
+
+    fn key_id(&self) -> String {
+        self.public_key_hex()
+    }
+
+    fn sign(&self, digest: &[u8]) -> Result<Vec<u8>> {
+        Ok(self.key_pair.sk.sign(digest, None).to_vec())
+    }
+}
+
+/// A set of trusted Ed25519 public keys.
+#[derive(Default)]
+pub struct Ed25519Keys {
+    keys: HashMap<String, PublicKey>,
+}
+
+impl Ed25519Keys {
+    /// Creates an empty set of keys.
+    pub fn new() -> Ed25519Keys {
+        Ed25519Keys::default()
+    }
+
+    /// Trusts `public_key` and returns its key id.
+    pub fn add(&mut self, public_key: [u8; 32]) -> String {
+        let key_id = hex::encode(public_key);
+        self.keys.insert(key_id.clone(), PublicKey::new(public_key));
+        key_id
+    }
+
+    /// Trusts the public key with the given hex encoding, such as the
+    /// contents of a public key file, and returns its key id.
+    ///
+    /// Surrounding whitespace is ignored.
+    pub fn add_hex(&mut self, public_key: &str) -> Result<String> {
+        let key = hex::decode(public_key.trim()).context("Ed25519 public key is not valid hex")?;
+        match <[u8; 32]>::try_from(key) {
+            Ok(key) => Ok(self.add(key)),
+            Err(key) => bail!("Ed25519 public key is {} bytes, not 32", key.len()),
+        }
+    }
+}
+
+impl Verifier for Ed25519Keys {
+    fn verify(&self, signature: &Signature, digest: &[u8]) -> Result<SignatureStatus> {
+        if signature.algorithm != ED25519 {
+            return Ok(SignatureStatus::UnknownKey);
+        }
+        let key = match self.keys.get(&signature.key_id) {
+            Some(key) => key,
+            None => return Ok(SignatureStatus::UnknownKey),
+        };
+        let valid = ed25519_compact::Signature::from_slice(&signature.bytes)
+            .and_then(|s| key.verify(digest, &s))
+            .is_ok();
+        Ok(if valid {
+            SignatureStatus::Valid
+        } else {
+            SignatureStatus::Invalid
+        })
+    }
+}
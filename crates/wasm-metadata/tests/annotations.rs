@@ -0,0 +1,141 @@
+use anyhow::Result;
+use wasm_metadata::{extract_annotations, remove_annotation, Annotation, Annotations};
+use wasmparser::{Validator, WasmFeatures};
+
+fn validate(wasm: &[u8]) -> Result<()> {
+    let features = WasmFeatures {
+        component_model: true,
+        ..Default::default()
+    };
+    Validator::new_with_features(features).validate_all(wasm)?;
+    Ok(())
+}
+
+/// Builds a component with `inner` nested in it as a core module.
+fn nest(outer: &[u8], inner: &[u8]) -> Vec<u8> {
+    let mut wasm = outer.to_vec();
+    wasm.push(1);
+    wasm_encoder::Encode::encode(&inner.len(), &mut wasm);
+    wasm.extend_from_slice(inner);
+    wasm
+}
+
+#[test]
+fn add_read_and_update() -> Result<()> {
+    for wat in [
+        "(module (func (export \"f\")))",
+        "(component (core module))",
+    ] {
+        let wasm = wat::parse_str(wat)?;
+        assert!(Annotations::from_wasm(&wasm)?.is_empty());
+
+        let mut annotations = Annotations::new();
+        annotations.license = Some("Apache-2.0 OR MIT".to_string());
+        annotations.source = Some("https://example.com/repo".to_string());
+        annotations.revision = Some("3a10b20".to_string());
+        let wasm = annotations.add_to(&wasm)?;
+        validate(&wasm)?;
+        assert_eq!(Annotations::from_wasm(&wasm)?, annotations);
+
+        // Updating one annotation keeps the others.
+        let mut update = Annotations::new();
+        update.set(Annotation::Revision, Some("5c4d1e0".to_string()));
+        update.set(Annotation::Description, Some("An example".to_string()));
+        let wasm = update.add_to(&wasm)?;
+        let read = Annotations::from_wasm(&wasm)?;
+        assert_eq!(read.get(Annotation::License), Some("Apache-2.0 OR MIT"));
+        assert_eq!(read.get(Annotation::Revision), Some("5c4d1e0"));
+        assert_eq!(read.get(Annotation::Homepage), None);
+        assert_eq!(
+            read.iter().map(|(a, _)| a).collect::<Vec<_>>(),
+            [
+                Annotation::License,
+                Annotation::Source,
+                Annotation::Description,
+                Annotation::Revision,
+            ]
+        );
+
+        // Rewriting the same annotations leaves the binary as it was.
+        assert_eq!(read.add_to(&wasm)?, wasm);
+
+        let (wasm, removed) = remove_annotation(&wasm, Annotation::Source)?;
+        assert!(removed);
+        validate(&wasm)?;
+        assert_eq!(Annotations::from_wasm(&wasm)?.source, None);
+        let (_, removed) = remove_annotation(&wasm, Annotation::Source)?;
+        assert!(!removed);
+    }
+    Ok(())
+}
+
+#[test]
+fn oci_keys() -> Result<()> {
+    let mut annotations = Annotations::new();
+    annotations.homepage = Some("https://example.com".to_string());
+    annotations.license = Some("MIT".to_string());
+    let oci = annotations.to_oci();
+    assert_eq!(
+        oci.iter()
+            .map(|(k, v)| (*k, v.as_str()))
+            .collect::<Vec<_>>(),
+        [
+            ("org.opencontainers.image.licenses", "MIT"),
+            ("org.opencontainers.image.url", "https://example.com"),
+        ]
+    );
+
+    assert_eq!("revision".parse::<Annotation>()?, Annotation::Revision);
+    assert!("licenses".parse::<Annotation>().is_err());
+    Ok(())
+}
+
+#[test]
+fn other_sections_are_preserved() -> Result<()> {
+    let wasm = wat::parse_str(r#"(module (@custom "other" "abc") (func))"#)?;
+    let mut annotations = Annotations::new();
+    annotations.description = Some("described".to_string());
+    let added = annotations.add_to(&wasm)?;
+    let (removed, _) = remove_annotation(&added, Annotation::Description)?;
+    assert_eq!(removed, wasm);
+    Ok(())
+}
+
+#[test]
+fn extract_nested() -> Result<()> {
+    let mut annotations = Annotations::new();
+    annotations.license = Some("MIT".to_string());
+    let module = annotations.add_to(&wat::parse_str("(module)")?)?;
+    let wasm = nest(&wat::parse_str("(component)")?, &module);
+    let wasm = nest(&wasm, &wat::parse_str("(module)")?);
+    validate(&wasm)?;
+
+    // Only the outermost binary's annotations are read directly.
+    assert!(Annotations::from_wasm(&wasm)?.is_empty());
+
+    let entries = extract_annotations(&wasm)?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].path.to_string(), "/module[0]");
+    assert_eq!(entries[0].annotations, annotations);
+    Ok(())
+}
+
+#[test]
+fn malformed_annotations() -> Result<()> {
+    let wasm = wat::parse_str(r#"(module (@custom "license" "\ff"))"#)?;
+    let err = Annotations::from_wasm(&wasm).unwrap_err();
+    assert!(format!("{err:#}").contains("not valid UTF-8"), "{err:#}");
+
+    // Malformed annotations can still be replaced or removed.
+    let mut annotations = Annotations::new();
+    annotations.license = Some("MIT".to_string());
+    let fixed = annotations.add_to(&wasm)?;
+    assert_eq!(Annotations::from_wasm(&fixed)?, annotations);
+    let (removed, _) = remove_annotation(&wasm, Annotation::License)?;
+    assert!(Annotations::from_wasm(&removed)?.is_empty());
+
+    let wasm = nest(&wat::parse_str("(component)")?, &wasm);
+    let err = extract_annotations(&wasm).unwrap_err();
+    assert!(format!("{err:#}").contains("/module[0]"), "{err:#}");
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use anyhow::Result;
+use wasm_metadata::{
+    set_entry, sign, Annotations, Change, Ed25519Signer, MetadataDiff, Producers, Value,
+};
+
+fn change(path: &str, item: &str, old: Option<&str>, new: Option<&str>) -> (String, Change) {
+    let change = Change {
+        path: Default::default(),
+        item: item.to_string(),
+        old: old.map(str::to_string),
+        new: new.map(str::to_string),
+    };
+    (path.to_string(), change)
+}
+
+fn with_paths(changes: &[Change]) -> Vec<(String, Change)> {
+    changes
+        .iter()
+        .map(|c| {
+            let path = c.path.to_string();
+            let mut c = c.clone();
+            c.path = Default::default();
+            (path, c)
+        })
+        .collect()
+}
+
+#[test]
+fn identical_binaries() -> Result<()> {
+    let wasm = wat::parse_str(r#"(module $m (func $f) (@custom "a" "b"))"#)?;
+    let diff = MetadataDiff::new(&wasm, &wasm)?;
+    assert!(diff.is_empty());
+    Ok(())
+}
+
+#[test]
+fn producers_and_entries() -> Result<()> {
+    let mut producers = Producers::new();
+    producers.add("processed-by", "rustc", "1.63.0");
+    producers.add("language", "Rust", "");
+    let old = producers.add_to(&wat::parse_str("(module)")?)?;
+    let old = set_entry(&old, "git-sha", &Value::String("abc".to_string()))?;
+
+    let mut producers = Producers::new();
+    producers.add("processed-by", "rustc", "1.64.0");
+    producers.add("sdk", "wasi-sdk", "16");
+    let new = producers.add_to(&wat::parse_str("(module)")?)?;
+    let new = set_entry(&new, "git-sha", &Value::String("def".to_string()))?;
+
+    let diff = MetadataDiff::new(&old, &new)?;
+    assert_eq!(
+        with_paths(&diff.producers),
+        [
+            change("/", "processed-by rustc", Some("1.63.0"), Some("1.64.0")),
+            change("/", "language Rust", Some(""), None),
+            change("/", "sdk wasi-sdk", None, Some("16")),
+        ]
+    );
+    assert_eq!(
+        with_paths(&diff.entries),
+        [change("/", "git-sha", Some("abc"), Some("def"))]
+    );
+    let sections = diff
+        .custom_sections
+        .iter()
+        .map(|c| c.item.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(sections, ["producers", "metadata:git-sha"]);
+    Ok(())
+}
+
+#[test]
+fn names_of_nested_modules() -> Result<()> {
+    let old = wat::parse_str(
+        r#"(component (core module $a (func $f (param $x i32))) (core module $b))"#,
+    )?;
+    let new = wat::parse_str(
+        r#"(component (core module $a (func $g (param $x i32))) (core module $c))"#,
+    )?;
+    let diff = MetadataDiff::new(&old, &new)?;
+    assert_eq!(
+        with_paths(&diff.names),
+        [
+            change("/module[0]", "function 0", Some("f"), Some("g")),
+            change("/module[1]", "module", Some("b"), Some("c")),
+        ]
+    );
+    assert!(diff.producers.is_empty());
+    assert!(diff.entries.is_empty());
+    Ok(())
+}
+
+#[test]
+fn annotations() -> Result<()> {
+    let mut annotations = Annotations::new();
+    annotations.license = Some("MIT".to_string());
+    annotations.revision = Some("abc".to_string());
+    let old = annotations.add_to(&wat::parse_str("(component (core module))")?)?;
+
+    annotations.license = Some("Apache-2.0".to_string());
+    annotations.revision = None;
+    let new = annotations.add_to(&wat::parse_str("(component (core module))")?)?;
+
+    let diff = MetadataDiff::new(&old, &new)?;
+    assert_eq!(
+        with_paths(&diff.annotations),
+        [
+            change("/", "license", Some("MIT"), Some("Apache-2.0")),
+            change("/", "revision", Some("abc"), None),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn repeated_custom_sections() -> Result<()> {
+    let old = wat::parse_str(r#"(module (@custom "x" "1") (@custom "x" "2"))"#)?;
+    let new = wat::parse_str(r#"(module (@custom "x" "1") (@custom "x" "3"))"#)?;
+    let diff = MetadataDiff::new(&old, &new)?;
+    let items = diff
+        .custom_sections
+        .iter()
+        .map(|c| c.item.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(items, ["x (2)"]);
+    assert!(diff.custom_sections[0]
+        .old
+        .as_ref()
+        .unwrap()
+        .starts_with("1 bytes, sha256 "));
+    Ok(())
+}
+
+#[test]
+fn signing_only_adds_a_signature_section() -> Result<()> {
+    let old = wat::parse_str("(module $m (func $f))")?;
+    let new = sign(&old, &Ed25519Signer::from_seed([1; 32]))?;
+    let diff = MetadataDiff::new(&old, &new)?;
+    assert!(diff.names.is_empty());
+    assert_eq!(diff.custom_sections.len(), 1);
+    assert_eq!(diff.custom_sections[0].item, "signature");
+    assert_eq!(diff.custom_sections[0].old, None);
+
+    let json = serde_json::to_value(&diff)?;
+    assert_eq!(json["custom_sections"][0]["path"], "/");
+    Ok(())
+}
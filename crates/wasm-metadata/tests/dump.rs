@@ -0,0 +1,109 @@
+use anyhow::Result;
+use wasm_metadata::{dump, set_entry, Annotation, Kind, MetadataValue, Producers, Value};
+
+/// Builds a component with `inner` nested in it as a core module.
+fn nest(outer: &[u8], inner: &[u8]) -> Vec<u8> {
+    let mut wasm = outer.to_vec();
+    wasm.push(1);
+    wasm_encoder::Encode::encode(&inner.len(), &mut wasm);
+    wasm.extend_from_slice(inner);
+    wasm
+}
+
+#[test]
+fn items_and_ranges() -> Result<()> {
+    let mut producers = Producers::new();
+    producers.add("language", "Rust", "");
+    let module = wat::parse_str(
+        r#"(module $m
+            (@custom "license" "MIT")
+            (@custom "other" "abc")
+            (func $f)
+        )"#,
+    )?;
+    let module = producers.add_to(&module)?;
+    let component = set_entry(
+        &wat::parse_str("(component)")?,
+        "build-id",
+        &Value::String("1234".to_string()),
+    )?;
+    let wasm = nest(&component, &module);
+
+    let items = dump(&wasm)?;
+    let sections = items
+        .iter()
+        .map(|i| (i.path.to_string(), i.kind, i.section.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        sections,
+        [
+            ("/".to_string(), Kind::Component, "metadata:build-id"),
+            ("/module[0]".to_string(), Kind::Module, "name"),
+            ("/module[0]".to_string(), Kind::Module, "license"),
+            ("/module[0]".to_string(), Kind::Module, "other"),
+            ("/module[0]".to_string(), Kind::Module, "producers"),
+        ]
+    );
+
+    assert_eq!(
+        items[0].value,
+        MetadataValue::Entry {
+            key: "build-id".to_string(),
+            value: Value::String("1234".to_string()),
+        }
+    );
+    match &items[1].value {
+        MetadataValue::Names { names } => {
+            assert_eq!(names["module"], "m");
+            assert_eq!(names["function 0"], "f");
+        }
+        value => panic!("unexpected value {value:?}"),
+    }
+    assert_eq!(
+        items[2].value,
+        MetadataValue::Annotation {
+            annotation: Annotation::License,
+            value: "MIT".to_string(),
+        }
+    );
+    assert_eq!(items[3].value, MetadataValue::Custom);
+    assert_eq!(items[4].value, MetadataValue::Producers { producers });
+
+    // The ranges locate each item within the outermost binary.
+    for item in &items {
+        assert_eq!(wasm[item.range.start], 0, "{}", item.section);
+        assert_eq!(item.range.end, item.data_range.end);
+        assert!(item.range.start < item.data_range.start);
+    }
+    assert_eq!(&wasm[items[2].data_range.clone()], b"MIT");
+    assert_eq!(&wasm[items[3].data_range.clone()], b"abc");
+    let entry = &wasm[items[0].range.clone()];
+    assert!(entry.ends_with(b"metadata:build-id\x001234"));
+    Ok(())
+}
+
+#[test]
+fn ranges_splice_out_items() -> Result<()> {
+    let wasm = wat::parse_str(r#"(module (@custom "a" "1") (func) (@custom "b" "22"))"#)?;
+    let items = dump(&wasm)?;
+    assert_eq!(items.len(), 2);
+
+    let mut stripped = wasm[..items[0].range.start].to_vec();
+    stripped.extend_from_slice(&wasm[items[0].range.end..items[1].range.start]);
+    stripped.extend_from_slice(&wasm[items[1].range.end..]);
+    assert_eq!(stripped, wat::parse_str("(module (func))")?);
+    Ok(())
+}
+
+#[test]
+fn malformed_items_name_their_path() -> Result<()> {
+    let module = wat::parse_str(r#"(module (@custom "producers" "\01"))"#)?;
+    let wasm = nest(&wat::parse_str("(component)")?, &module);
+    let err = dump(&wasm).unwrap_err();
+    let err = format!("{err:#}");
+    assert!(
+        err.contains("invalid `producers` section in `/module[0]`"),
+        "{err}"
+    );
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use anyhow::Result;
+use wasm_metadata::{entries, get_entry, remove_entry, set_entry, Value};
+use wasmparser::{Validator, WasmFeatures};
+
+fn validate(wasm: &[u8]) -> Result<()> {
+    let features = WasmFeatures {
+        component_model: true,
+        ..Default::default()
+    };
+    Validator::new_with_features(features).validate_all(wasm)?;
+    Ok(())
+}
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+#[test]
+fn set_get_and_remove() -> Result<()> {
+    for wat in [
+        "(module (func (export \"f\")))",
+        "(component (core module))",
+    ] {
+        let wasm = wat::parse_str(wat)?;
+        assert_eq!(get_entry(&wasm, "build-id")?, None);
+
+        let wasm = set_entry(&wasm, "build-id", &string("1234"))?;
+        let json = Value::Json(serde_json::json!({ "pipeline": 7, "branch": "main" }));
+        let wasm = set_entry(&wasm, "ci", &json)?;
+        validate(&wasm)?;
+        assert_eq!(get_entry(&wasm, "build-id")?, Some(string("1234")));
+        assert_eq!(get_entry(&wasm, "ci")?, Some(json.clone()));
+        assert_eq!(
+            entries(&wasm)?,
+            [
+                ("build-id".to_string(), string("1234")),
+                ("ci".to_string(), json.clone()),
+            ]
+        );
+
+        // Setting an entry again replaces it in place.
+        let wasm = set_entry(&wasm, "build-id", &string("5678"))?;
+        assert_eq!(
+            entries(&wasm)?,
+            [
+                ("build-id".to_string(), string("5678")),
+                ("ci".to_string(), json.clone()),
+            ]
+        );
+
+        let (wasm, removed) = remove_entry(&wasm, "build-id")?;
+        assert!(removed);
+        validate(&wasm)?;
+        assert_eq!(get_entry(&wasm, "build-id")?, None);
+        assert_eq!(entries(&wasm)?, [("ci".to_string(), json)]);
+
+        let (_, removed) = remove_entry(&wasm, "build-id")?;
+        assert!(!removed);
+    }
+    Ok(())
+}
+
+#[test]
+fn nested_entries_are_not_top_level() -> Result<()> {
+    let wasm =
+        wat::parse_str(r#"(component (core module (@custom "metadata:build-id" "\00nested")))"#)?;
+    assert_eq!(get_entry(&wasm, "build-id")?, None);
+    assert!(entries(&wasm)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn other_sections_are_preserved() -> Result<()> {
+    let wasm = wat::parse_str(r#"(module (@custom "other" "abc") (func))"#)?;
+    let set = set_entry(&wasm, "key", &string("value"))?;
+    let (removed, _) = remove_entry(&set, "key")?;
+    assert_eq!(removed, wasm);
+    Ok(())
+}
+
+#[test]
+fn malformed_entries() -> Result<()> {
+    let wasm = wat::parse_str(r#"(module (@custom "metadata:bad" "\01{"))"#)?;
+    let err = get_entry(&wasm, "bad").unwrap_err();
+    assert!(format!("{err:#}").contains("not valid JSON"), "{err:#}");
+
+    // Malformed entries can still be removed.
+    let (wasm, removed) = remove_entry(&wasm, "bad")?;
+    assert!(removed);
+    assert!(entries(&wasm)?.is_empty());
+
+    assert!(set_entry(&wasm, "", &string("x")).is_err());
+    Ok(())
+}
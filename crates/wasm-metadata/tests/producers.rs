@@ -0,0 +1,111 @@
+use anyhow::Result;
+use wasm_metadata::{Kind, Producers, ProducersReport, ToolCount};
+use wasmparser::{Validator, WasmFeatures};
+
+const COMPONENT: &str = r#"
+(component
+  (core module (@custom "producers" "\01\08language\01\04Rust\00"))
+  (component
+    (core module)
+    (core module (@custom "producers" "\01\08language\01\04Rust\00"))
+  )
+  (@custom "producers" "\01\0cprocessed-by\01\0awasm-tools\031.0")
+)
+"#;
+
+fn validate(wasm: &[u8]) -> Result<()> {
+    let features = WasmFeatures {
+        component_model: true,
+        ..Default::default()
+    };
+    Validator::new_with_features(features).validate_all(wasm)?;
+    Ok(())
+}
+
+fn tool(field: &str, name: &str, version: &str, count: usize) -> ToolCount {
+    ToolCount {
+        field: field.to_string(),
+        name: name.to_string(),
+        version: version.to_string(),
+        count,
+    }
+}
+
+#[test]
+fn aggregates_nested_producers() -> Result<()> {
+    let wasm = wat::parse_str(COMPONENT)?;
+    let report = ProducersReport::from_wasm(&wasm)?;
+
+    let paths = report
+        .entries
+        .iter()
+        .map(|e| (e.path.to_string(), e.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        [
+            ("/".to_string(), Kind::Component),
+            ("/module[0]".to_string(), Kind::Module),
+            ("/component[0]/module[1]".to_string(), Kind::Module),
+        ]
+    );
+
+    assert_eq!(
+        report.tools(),
+        [
+            tool("processed-by", "wasm-tools", "1.0", 1),
+            tool("language", "Rust", "", 2),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn report_serializes_paths_as_strings() -> Result<()> {
+    let wasm = wat::parse_str(COMPONENT)?;
+    let report = ProducersReport::from_wasm(&wasm)?;
+    let json = serde_json::to_value(&report)?;
+    assert_eq!(json["entries"][2]["path"], "/component[0]/module[1]");
+    assert_eq!(json["entries"][2]["kind"], "module");
+    assert_eq!(json["entries"][2]["producers"]["language"]["Rust"], "");
+    Ok(())
+}
+
+#[test]
+fn add_to_merges_with_existing_section() -> Result<()> {
+    let wasm = wat::parse_str(COMPONENT)?;
+
+    let mut producers = Producers::new();
+    producers.add("processed-by", "wasm-tools", "1.1");
+    producers.add("sdk", "wasi-sdk", "16");
+    let wasm = producers.add_to(&wasm)?;
+    validate(&wasm)?;
+
+    let top = Producers::from_wasm(&wasm)?.unwrap();
+    assert_eq!(top.get("processed-by").unwrap()["wasm-tools"], "1.1");
+    assert_eq!(top.get("sdk").unwrap()["wasi-sdk"], "16");
+
+    // Nested sections are untouched.
+    let report = ProducersReport::from_wasm(&wasm)?;
+    assert_eq!(report.entries.len(), 3);
+    assert_eq!(
+        report.entries[1].producers.get("language").unwrap().len(),
+        1
+    );
+    Ok(())
+}
+
+#[test]
+fn missing_producers() -> Result<()> {
+    let wasm = wat::parse_str("(module)")?;
+    assert!(Producers::from_wasm(&wasm)?.is_none());
+    assert!(ProducersReport::from_wasm(&wasm)?.entries.is_empty());
+    Ok(())
+}
+
+#[test]
+fn malformed_producers_is_an_error() -> Result<()> {
+    let wasm = wat::parse_str(r#"(module (@custom "producers" "\05\01"))"#)?;
+    assert!(ProducersReport::from_wasm(&wasm).is_err());
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use serde_json::json;
+use wasm_metadata::{extract_sboms, merge_sboms, Sbom, SbomFormat};
+
+fn spdx(name: &str, packages: &[&str]) -> Sbom {
+    let packages = packages
+        .iter()
+        .map(|p| json!({ "SPDXID": format!("SPDXRef-{p}"), "name": p }))
+        .collect::<Vec<_>>();
+    let relationships = packages
+        .iter()
+        .map(|p| {
+            json!({
+                "spdxElementId": "SPDXRef-DOCUMENT",
+                "relationshipType": "DESCRIBES",
+                "relatedSpdxElement": p["SPDXID"],
+            })
+        })
+        .collect::<Vec<_>>();
+    Sbom::new(json!({
+        "spdxVersion": "SPDX-2.3",
+        "name": name,
+        "packages": packages,
+        "relationships": relationships,
+    }))
+    .unwrap()
+}
+
+fn cyclonedx(components: &[&str]) -> Sbom {
+    let components = components
+        .iter()
+        .map(|c| json!({ "bom-ref": c, "name": c, "type": "library" }))
+        .collect::<Vec<_>>();
+    Sbom::new(json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "components": components,
+    }))
+    .unwrap()
+}
+
+fn names(sbom: &Sbom, array: &str) -> Vec<String> {
+    sbom.document[array]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+/// Builds a component with `inner` nested in it as a core module.
+fn nest(outer: &[u8], inner: &[u8]) -> Vec<u8> {
+    let mut wasm = outer.to_vec();
+    wasm.push(1);
+    wasm_encoder::Encode::encode(&inner.len(), &mut wasm);
+    wasm.extend_from_slice(inner);
+    wasm
+}
+
+#[test]
+fn detects_formats() -> Result<()> {
+    assert_eq!(spdx("a", &[]).format, SbomFormat::Spdx);
+    assert_eq!(cyclonedx(&[]).format, SbomFormat::CycloneDx);
+    assert!(Sbom::parse(b"{\"name\": \"x\"}").is_err());
+    assert!(Sbom::parse(b"not json").is_err());
+    Ok(())
+}
+
+#[test]
+fn embed_and_extract_nested() -> Result<()> {
+    let module =
+        spdx("module", &["libc", "dlmalloc"]).add_to(&wat::parse_str("(module (func))")?)?;
+    let component = spdx("component", &["app"]).add_to(&wat::parse_str("(component)")?)?;
+    let wasm = nest(&component, &module);
+    wasmparser::Validator::new_with_features(wasmparser::WasmFeatures {
+        component_model: true,
+        ..Default::default()
+    })
+    .validate_all(&wasm)?;
+
+    assert_eq!(
+        Sbom::from_wasm(&wasm)?.unwrap().document["name"],
+        "component"
+    );
+
+    let entries = extract_sboms(&wasm)?;
+    let paths = entries
+        .iter()
+        .map(|e| e.path.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(paths, ["/", "/module[0]"]);
+    assert_eq!(entries[1].sbom.document["name"], "module");
+
+    let sboms = entries.into_iter().map(|e| e.sbom).collect::<Vec<_>>();
+    let merged = merge_sboms(&sboms)?;
+    assert_eq!(merged.document["name"], "component");
+    assert_eq!(names(&merged, "packages"), ["app", "libc", "dlmalloc"]);
+    assert_eq!(
+        merged.document["relationships"].as_array().unwrap().len(),
+        3
+    );
+    Ok(())
+}
+
+#[test]
+fn replaces_existing_sbom() -> Result<()> {
+    let wasm = spdx("old", &[]).add_to(&wat::parse_str("(module)")?)?;
+    let wasm = spdx("new", &[]).add_to(&wasm)?;
+    let entries = extract_sboms(&wasm)?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].sbom.document["name"], "new");
+    Ok(())
+}
+
+#[test]
+fn merge_deduplicates() -> Result<()> {
+    let merged = merge_sboms(&[spdx("a", &["x", "y"]), spdx("b", &["y", "z"])])?;
+    assert_eq!(names(&merged, "packages"), ["x", "y", "z"]);
+    assert_eq!(
+        merged.document["relationships"].as_array().unwrap().len(),
+        3
+    );
+
+    let merged = merge_sboms(&[cyclonedx(&["serde"]), cyclonedx(&["serde", "anyhow"])])?;
+    assert_eq!(names(&merged, "components"), ["serde", "anyhow"]);
+
+    // Arrays missing from the first document are created.
+    let empty = Sbom::new(json!({ "bomFormat": "CycloneDX", "specVersion": "1.5" }))?;
+    let merged = merge_sboms(&[empty, cyclonedx(&["serde"])])?;
+    assert_eq!(names(&merged, "components"), ["serde"]);
+    Ok(())
+}
+
+#[test]
+fn merge_errors() {
+    assert!(merge_sboms(&[]).is_err());
+    assert!(merge_sboms(&[spdx("a", &[]), cyclonedx(&[])]).is_err());
+
+    let bad = Sbom {
+        format: SbomFormat::Spdx,
+        document: json!({ "spdxVersion": "SPDX-2.3", "packages": 1 }),
+    };
+    assert!(merge_sboms(&[bad, spdx("b", &["x"])]).is_err());
+}
+
+#[test]
+fn invalid_nested_sbom_names_its_path() -> Result<()> {
+    let module = wat::parse_str(r#"(module (@custom "sbom" "{}"))"#)?;
+    let wasm = nest(&wat::parse_str("(component)")?, &module);
+    let err = extract_sboms(&wasm).unwrap_err();
+    assert!(format!("{err:#}").contains("/module[0]"), "{err:#}");
+    Ok(())
+}
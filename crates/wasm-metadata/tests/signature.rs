@@ -0,0 +1,222 @@
+use anyhow::Result;
+use wasm_metadata::{
+    embed_digest, set_entry, sign, verify, Digest, Ed25519Keys, Ed25519Signer, Signature,
+    SignatureSection, SignatureStatus, Signer, Value, Verifier,
+};
+use wasmparser::{Validator, WasmFeatures};
+
+const COMPONENT: &str = r#"
+(component
+  (core module (func (export "f")) (@custom "signature" "\01"))
+  (@custom "before" "abc")
+)
+"#;
+
+fn validate(wasm: &[u8]) -> Result<()> {
+    let features = WasmFeatures {
+        component_model: true,
+        ..Default::default()
+    };
+    Validator::new_with_features(features).validate_all(wasm)?;
+    Ok(())
+}
+
+fn signer(seed: u8) -> Ed25519Signer {
+    Ed25519Signer::from_seed([seed; 32])
+}
+
+fn keys(signers: &[&Ed25519Signer]) -> Ed25519Keys {
+    let mut keys = Ed25519Keys::new();
+    for signer in signers {
+        keys.add_hex(&signer.public_key_hex()).unwrap();
+    }
+    keys
+}
+
+#[test]
+fn sign_and_verify() -> Result<()> {
+    let wasm = wat::parse_str(COMPONENT)?;
+    let alice = signer(1);
+    let signed = sign(&wasm, &alice)?;
+    validate(&signed)?;
+
+    // The digest covers everything but the appended section, which is the
+    // same as the digest of the unsigned binary.
+    let digest = Digest::of(&signed)?;
+    assert_eq!(digest.covered, vec![0..wasm.len()]);
+    assert_eq!(digest.bytes, Digest::of(&wasm)?.bytes);
+
+    let verification = verify(&signed, &keys(&[&alice]))?;
+    assert!(verification.digest_matches);
+    assert!(verification.is_verified());
+    assert_eq!(verification.signatures.len(), 1);
+    assert_eq!(verification.signatures[0].key_id, alice.public_key_hex());
+    assert_eq!(verification.signatures[0].status, SignatureStatus::Valid);
+
+    // Unknown keys don't verify anything.
+    let verification = verify(&signed, &keys(&[&signer(2)]))?;
+    assert!(verification.digest_matches);
+    assert!(!verification.is_verified());
+    assert_eq!(
+        verification.signatures[0].status,
+        SignatureStatus::UnknownKey
+    );
+    Ok(())
+}
+
+#[test]
+fn multiple_signers() -> Result<()> {
+    let wasm = wat::parse_str(COMPONENT)?;
+    let (alice, bob) = (signer(1), signer(2));
+    let signed = sign(&sign(&wasm, &alice)?, &bob)?;
+    // Signing again with the same key replaces its signature.
+    let signed = sign(&signed, &alice)?;
+
+    let section = SignatureSection::from_wasm(&signed)?.unwrap();
+    let ids = section
+        .signatures
+        .iter()
+        .map(|s| s.key_id.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, [bob.public_key_hex(), alice.public_key_hex()]);
+
+    let verification = verify(&signed, &keys(&[&alice, &bob]))?;
+    assert!(verification.is_verified());
+    assert!(verification
+        .signatures
+        .iter()
+        .all(|s| s.status == SignatureStatus::Valid));
+    Ok(())
+}
+
+#[test]
+fn tampering_is_detected() -> Result<()> {
+    let wasm = wat::parse_str(COMPONENT)?;
+    let alice = signer(1);
+    let signed = sign(&wasm, &alice)?;
+
+    let tampered = set_entry(&signed, "build-id", &Value::String("1".to_string()))?;
+    let verification = verify(&tampered, &keys(&[&alice]))?;
+    assert!(!verification.digest_matches);
+    assert!(verification.signatures.is_empty());
+    assert!(!verification.is_verified());
+
+    // Re-signing the changed binary drops the stale signatures.
+    let bob = signer(2);
+    let resigned = sign(&tampered, &bob)?;
+    let section = SignatureSection::from_wasm(&resigned)?.unwrap();
+    assert_eq!(section.signatures.len(), 1);
+    assert_eq!(section.signatures[0].key_id, bob.public_key_hex());
+    Ok(())
+}
+
+#[test]
+fn forged_signature_is_invalid() -> Result<()> {
+    struct Forger(String);
+
+    impl Signer for Forger {
+        fn algorithm(&self) -> String {
+            "ed25519".to_string()
+        }
+        fn key_id(&self) -> String {
+            self.0.clone()
+        }
+        fn sign(&self, _digest: &[u8]) -> Result<Vec<u8>> {
+            Ok(vec![0; 64])
+        }
+    }
+
+    let wasm = wat::parse_str(COMPONENT)?;
+    let alice = signer(1);
+    let signed = sign(&sign(&wasm, &alice)?, &Forger(signer(2).public_key_hex()))?;
+    let verification = verify(&signed, &keys(&[&alice, &signer(2)]))?;
+    assert!(verification.digest_matches);
+    assert_eq!(verification.signatures[0].status, SignatureStatus::Valid);
+    assert_eq!(verification.signatures[1].status, SignatureStatus::Invalid);
+    assert!(!verification.is_verified());
+    Ok(())
+}
+
+#[test]
+fn custom_verifier() -> Result<()> {
+    struct Checksum;
+
+    impl Signer for Checksum {
+        fn algorithm(&self) -> String {
+            "checksum".to_string()
+        }
+        fn key_id(&self) -> String {
+            "builder".to_string()
+        }
+        fn sign(&self, digest: &[u8]) -> Result<Vec<u8>> {
+            Ok(vec![digest.iter().fold(0, |a, b| a ^ b)])
+        }
+    }
+
+    impl Verifier for Checksum {
+        fn verify(&self, signature: &Signature, digest: &[u8]) -> Result<SignatureStatus> {
+            Ok(if signature.bytes == self.sign(digest)? {
+                SignatureStatus::Valid
+            } else {
+                SignatureStatus::Invalid
+            })
+        }
+    }
+
+    let wasm = wat::parse_str("(module)")?;
+    let verification = verify(&sign(&wasm, &Checksum)?, &Checksum)?;
+    assert!(verification.is_verified());
+    assert_eq!(verification.signatures[0].key_id, "builder");
+    Ok(())
+}
+
+#[test]
+fn digest_only() -> Result<()> {
+    let wasm = wat::parse_str("(module (func))")?;
+    let embedded = embed_digest(&wasm)?;
+    let verification = verify(&embedded, &Ed25519Keys::new())?;
+    assert!(verification.digest_matches);
+    assert!(verification.signatures.is_empty());
+    assert!(!verification.is_verified());
+
+    assert!(verify(&wasm, &Ed25519Keys::new()).is_err());
+    Ok(())
+}
+
+#[test]
+fn signature_section_in_the_middle() -> Result<()> {
+    let wasm = wat::parse_str("(module (func))")?;
+    let signed = sign(&wasm, &signer(1))?;
+    // Move the signature section before the function sections by rebuilding
+    // the binary around it.
+    let section_len = signed.len() - wasm.len();
+    let mut moved = wasm[..8].to_vec();
+    moved.extend_from_slice(&signed[wasm.len()..]);
+    moved.extend_from_slice(&wasm[8..]);
+
+    let verification = verify(&moved, &keys(&[&signer(1)]))?;
+    assert!(verification.is_verified());
+    assert_eq!(
+        verification.digest.covered,
+        [0..8, 8 + section_len..moved.len()]
+    );
+    Ok(())
+}
+
+#[test]
+fn malformed_sections() -> Result<()> {
+    let two = wat::parse_str(
+        r#"(module (@custom "signature" "\01\06sha256\00\00") (@custom "signature" "\01\06sha256\00\00"))"#,
+    )?;
+    let err = SignatureSection::from_wasm(&two).unwrap_err();
+    assert!(err.to_string().contains("more than one"), "{err}");
+
+    let version = wat::parse_str(r#"(module (@custom "signature" "\02"))"#)?;
+    let err = SignatureSection::from_wasm(&version).unwrap_err();
+    assert!(err.to_string().contains("version 2"), "{err}");
+
+    assert!(Ed25519Signer::from_hex("zz").is_err());
+    assert!(Ed25519Signer::from_hex("0102").is_err());
+    assert!(Ed25519Keys::new().add_hex("0102").is_err());
+    Ok(())
+}
@@ -0,0 +1,727 @@
+//! Mutations of WebAssembly components.
+//!
+//! Components are mutated section by section: the sections that a mutation
+//! does not touch are copied over as raw bytes, and within a rewritten
+//! section only the affected entries are re-encoded. Nested core modules and
+//! components are mutated with the regular mutators, recursively.
+
+use crate::{Error, ErrorKind, Result, WasmMutate};
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
+use wasm_encoder::{
+    Component, ComponentExportKind, ComponentSectionId, Encode, ModuleArg, RawSection,
+};
+use wasmparser::{
+    types::{ComponentEntityType, Types},
+    BinaryReader, CanonicalFunction, CanonicalOption, Chunk, ComponentAliasSectionReader,
+    ComponentCanonicalSectionReader, ComponentExportSectionReader, ComponentExternalKind,
+    ComponentImportSectionReader, ComponentInstance, ComponentInstanceSectionReader,
+    ComponentTypeSectionReader, CoreTypeSectionReader, Encoding, Instance, InstanceSectionReader,
+    Parser, Payload, SectionReader, SectionWithLimitedItems, Validator,
+};
+
+/// The number of seeds tried when looking for a mutation of a nested module or
+/// component that keeps the enclosing component valid.
+const NESTED_MUTATION_ATTEMPTS: usize = 10;
+
+/// The number of items tried when looking for one whose removal keeps the
+/// component valid.
+const REMOVAL_ATTEMPTS: usize = 10;
+
+#[derive(Clone, Copy, Debug)]
+enum ComponentMutation {
+    /// Mutate a nested core module or component in place.
+    Nested,
+    /// Duplicate an existing alias at the end of the component.
+    AddAlias,
+    /// Remove the last alias of the component when nothing can refer to it.
+    RemoveAlias,
+    /// Change the string encoding of a lifted or lowered function.
+    CanonicalOptions,
+    /// Swap two of the named arguments of an instantiation.
+    ShuffleInstanceArgs,
+    /// Remove an item, such as an instance, alias, or type, or a whole
+    /// section, such as a nested module, when the component stays valid.
+    RemoveItem,
+}
+
+const MUTATIONS: &[ComponentMutation] = &[
+    ComponentMutation::Nested,
+    ComponentMutation::AddAlias,
+    ComponentMutation::RemoveAlias,
+    ComponentMutation::CanonicalOptions,
+    ComponentMutation::ShuffleInstanceArgs,
+    ComponentMutation::RemoveItem,
+];
+
+/// Is the given Wasm binary a component rather than a core module?
+pub(crate) fn is_component(wasm: &[u8]) -> bool {
+    matches!(
+        Parser::new(0).parse(wasm, true),
+        Ok(Chunk::Parsed {
+            payload: Payload::Version {
+                encoding: Encoding::Component,
+                ..
+            },
+            ..
+        })
+    )
+}
+
+impl<'wasm> WasmMutate<'wasm> {
+    /// Apply one mutation to the given component, starting with a random
+    /// mutation and moving on to the next one until one is applicable.
+    pub(crate) fn mutate_component(&mut self, input_wasm: &'wasm [u8]) -> Result<Vec<u8>> {
+        self.rng = Some(SmallRng::seed_from_u64(self.seed));
+        let sections = parse_sections(input_wasm)?;
+
+        let start = self.rng().gen_range(0..MUTATIONS.len());
+        for i in 0..MUTATIONS.len() {
+            let mutation = MUTATIONS[(start + i) % MUTATIONS.len()];
+            log::trace!("attempting to mutate component with `{:?}`", mutation);
+            let mutated = match mutation {
+                ComponentMutation::Nested => self.mutate_nested(input_wasm, &sections)?,
+                ComponentMutation::AddAlias => self.add_alias(&sections)?,
+                ComponentMutation::RemoveAlias => self.remove_alias(&sections)?,
+                ComponentMutation::CanonicalOptions => self.mutate_canonical_options(&sections)?,
+                ComponentMutation::ShuffleInstanceArgs => self.shuffle_instance_args(&sections)?,
+                ComponentMutation::RemoveItem => self.remove_item(input_wasm, &sections)?,
+            };
+            if let Some(component) = mutated {
+                if self.preserve_component_type && !self.same_external_type(input_wasm, &component)
+                {
+                    log::debug!("component mutation `{:?}` changed its type", mutation);
+                    continue;
+                }
+                log::debug!("component mutation `{:?}` succeeded", mutation);
+                return Ok(component);
+            }
+        }
+
+        Err(Error::no_mutations_applicable())
+    }
+
+    fn mutate_nested(
+        &mut self,
+        input_wasm: &[u8],
+        sections: &[RawSection<'wasm>],
+    ) -> Result<Option<Vec<u8>>> {
+        let nested = sections
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| {
+                s.id == ComponentSectionId::CoreModule as u8
+                    || s.id == ComponentSectionId::Component as u8
+            })
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let index = match nested.choose(self.rng()) {
+            Some(index) => *index,
+            None => return Ok(None),
+        };
+
+        // The mutators keep a core module itself valid, but not necessarily
+        // its imports and exports, which the rest of the component depends
+        // on. Only keep mutations that leave a valid component valid.
+        let valid = self.is_valid(input_wasm);
+        for _ in 0..NESTED_MUTATION_ATTEMPTS {
+            let mut mutate = self.clone();
+            mutate.seed = self.rng().gen();
+            mutate.rng = None;
+            mutate.info = None;
+            let result = mutate.run(sections[index].data).and_then(|mut iter| {
+                iter.next()
+                    .unwrap_or_else(|| Err(Error::no_mutations_applicable()))
+            });
+            self.fuel.set(mutate.fuel.get());
+
+            let data = match result {
+                Ok(data) => data,
+                Err(e) if matches!(e.kind(), ErrorKind::NoMutationsApplicable) => continue,
+                Err(e) => return Err(e),
+            };
+            let component = encode_replacing(sections, index, &data);
+            if (!valid || self.is_valid(&component))
+                && (!self.preserve_component_type
+                    || self.same_external_type(input_wasm, &component))
+            {
+                return Ok(Some(component));
+            }
+        }
+        Ok(None)
+    }
+
+    fn add_alias(&mut self, sections: &[RawSection<'wasm>]) -> Result<Option<Vec<u8>>> {
+        if self.reduce {
+            return Ok(None);
+        }
+
+        let mut aliases = Vec::new();
+        for section in sections {
+            if section.id == ComponentSectionId::Alias as u8 {
+                let reader = ComponentAliasSectionReader::new(section.data, 0)?;
+                aliases.extend(
+                    entries(section.data, reader)?
+                        .into_iter()
+                        .map(|(_, raw)| raw),
+                );
+            }
+        }
+        let alias = match aliases.choose(self.rng()) {
+            Some(alias) => *alias,
+            None => return Ok(None),
+        };
+
+        // Appending the alias creates a new item at the end of its index
+        // space, which nothing refers to.
+        let data = section_data(&[alias]);
+        let mut sections = sections.to_vec();
+        sections.push(RawSection {
+            id: ComponentSectionId::Alias as u8,
+            data: &data,
+        });
+        Ok(Some(encode(&sections)))
+    }
+
+    fn remove_alias(&mut self, sections: &[RawSection<'wasm>]) -> Result<Option<Vec<u8>>> {
+        // Only the last alias of an alias section that is followed by custom
+        // sections alone is known to be unused.
+        let index = match sections
+            .iter()
+            .rposition(|s| s.id != ComponentSectionId::CoreCustom as u8)
+        {
+            Some(index) if sections[index].id == ComponentSectionId::Alias as u8 => index,
+            _ => return Ok(None),
+        };
+        let data = sections[index].data;
+        let aliases = entries(data, ComponentAliasSectionReader::new(data, 0)?)?;
+        let remaining = aliases[..aliases.len().saturating_sub(1)]
+            .iter()
+            .map(|(_, raw)| *raw)
+            .collect::<Vec<_>>();
+
+        if remaining.is_empty() {
+            let mut sections = sections.to_vec();
+            sections.remove(index);
+            return Ok(Some(encode(&sections)));
+        }
+        Ok(Some(encode_replacing(
+            sections,
+            index,
+            &section_data(&remaining),
+        )))
+    }
+
+    fn remove_item(
+        &mut self,
+        input_wasm: &[u8],
+        sections: &[RawSection<'wasm>],
+    ) -> Result<Option<Vec<u8>>> {
+        if self.preserve_semantics || sections.is_empty() {
+            return Ok(None);
+        }
+
+        // Removing an item renumbers the items after it in its index space,
+        // so rather than rewriting every reference, only keep removals that
+        // leave a valid component valid.
+        let valid = self.is_valid(input_wasm);
+        for _ in 0..REMOVAL_ATTEMPTS {
+            let index = self.rng().gen_range(0..sections.len());
+            let items = items(&sections[index])?;
+            if items.is_empty() {
+                continue;
+            }
+            let removed = self.rng().gen_range(0..items.len());
+            let component = if items.len() == 1 {
+                let mut sections = sections.to_vec();
+                sections.remove(index);
+                encode(&sections)
+            } else {
+                let remaining = items
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != removed)
+                    .map(|(_, raw)| *raw)
+                    .collect::<Vec<_>>();
+                encode_replacing(sections, index, &section_data(&remaining))
+            };
+            if !valid || self.is_valid(&component) {
+                return Ok(Some(component));
+            }
+        }
+        Ok(None)
+    }
+
+    fn mutate_canonical_options(
+        &mut self,
+        sections: &[RawSection<'wasm>],
+    ) -> Result<Option<Vec<u8>>> {
+        if self.preserve_semantics {
+            return Ok(None);
+        }
+
+        let mut candidates = Vec::new();
+        for (i, section) in sections.iter().enumerate() {
+            if section.id != ComponentSectionId::CanonicalFunction as u8 {
+                continue;
+            }
+            let reader = ComponentCanonicalSectionReader::new(section.data, 0)?;
+            let funcs = entries(section.data, reader)?;
+            for (j, (func, _)) in funcs.iter().enumerate() {
+                let options = match func {
+                    CanonicalFunction::Lift { options, .. }
+                    | CanonicalFunction::Lower { options, .. } => options,
+                    _ => continue,
+                };
+                // When reducing, the string encoding can only be removed.
+                if !self.reduce || options.iter().any(is_string_encoding) {
+                    candidates.push((i, j));
+                }
+            }
+        }
+        let (section, entry) = match candidates.choose(self.rng()) {
+            Some(candidate) => *candidate,
+            None => return Ok(None),
+        };
+
+        let data = sections[section].data;
+        let funcs = entries(data, ComponentCanonicalSectionReader::new(data, 0)?)?;
+        let options = match &funcs[entry].0 {
+            CanonicalFunction::Lift { options, .. } | CanonicalFunction::Lower { options, .. } => {
+                options
+            }
+            _ => unreachable!(),
+        };
+
+        // Replace the string encoding with a different one, or with none at
+        // all, which means UTF-8.
+        let encoding = options.iter().copied().find(is_string_encoding);
+        let choices = [
+            None,
+            Some(CanonicalOption::UTF8),
+            Some(CanonicalOption::UTF16),
+            Some(CanonicalOption::CompactUTF16),
+        ];
+        let choices = choices
+            .iter()
+            .filter(|c| **c != encoding && (!self.reduce || c.is_none()))
+            .collect::<Vec<_>>();
+        let new_encoding = **choices.choose(self.rng()).unwrap();
+        let options = options
+            .iter()
+            .copied()
+            .filter(|o| !is_string_encoding(o))
+            .chain(new_encoding)
+            .map(translate_canonical_option)
+            .collect::<Vec<_>>();
+
+        let mut func = Vec::new();
+        match &funcs[entry].0 {
+            CanonicalFunction::Lift {
+                core_func_index,
+                type_index,
+                ..
+            } => {
+                func.extend_from_slice(&[0x00, 0x00]);
+                core_func_index.encode(&mut func);
+                options.encode(&mut func);
+                type_index.encode(&mut func);
+            }
+            CanonicalFunction::Lower { func_index, .. } => {
+                func.extend_from_slice(&[0x01, 0x00]);
+                func_index.encode(&mut func);
+                options.encode(&mut func);
+            }
+            _ => unreachable!(),
+        }
+
+        let mut raw = funcs.iter().map(|(_, raw)| *raw).collect::<Vec<_>>();
+        raw[entry] = &func;
+        Ok(Some(encode_replacing(
+            sections,
+            section,
+            &section_data(&raw),
+        )))
+    }
+
+    fn shuffle_instance_args(&mut self, sections: &[RawSection<'wasm>]) -> Result<Option<Vec<u8>>> {
+        if self.reduce {
+            return Ok(None);
+        }
+
+        let mut candidates = Vec::new();
+        for (i, section) in sections.iter().enumerate() {
+            if section.id == ComponentSectionId::CoreInstance as u8 {
+                let reader = InstanceSectionReader::new(section.data, 0)?;
+                for (j, (instance, _)) in entries(section.data, reader)?.iter().enumerate() {
+                    if let Instance::Instantiate { args, .. } = instance {
+                        if args.len() >= 2 {
+                            candidates.push((i, j));
+                        }
+                    }
+                }
+            } else if section.id == ComponentSectionId::Instance as u8 {
+                let reader = ComponentInstanceSectionReader::new(section.data, 0)?;
+                for (j, (instance, _)) in entries(section.data, reader)?.iter().enumerate() {
+                    if let ComponentInstance::Instantiate { args, .. } = instance {
+                        if args.len() >= 2 {
+                            candidates.push((i, j));
+                        }
+                    }
+                }
+            }
+        }
+        let (section, entry) = match candidates.choose(self.rng()) {
+            Some(candidate) => *candidate,
+            None => return Ok(None),
+        };
+
+        // Arguments are matched up with imports by name, so their order does
+        // not matter.
+        let data = sections[section].data;
+        let mut instance = vec![0x00];
+        let raw = if sections[section].id == ComponentSectionId::CoreInstance as u8 {
+            let instances = entries(data, InstanceSectionReader::new(data, 0)?)?;
+            let (module_index, mut args) = match &instances[entry].0 {
+                Instance::Instantiate { module_index, args } => (*module_index, args.to_vec()),
+                _ => unreachable!(),
+            };
+            self.swap_two(&mut args);
+            module_index.encode(&mut instance);
+            args.len().encode(&mut instance);
+            for arg in args {
+                arg.name.encode(&mut instance);
+                ModuleArg::Instance(arg.index).encode(&mut instance);
+            }
+            instances.iter().map(|(_, raw)| *raw).collect::<Vec<_>>()
+        } else {
+            let instances = entries(data, ComponentInstanceSectionReader::new(data, 0)?)?;
+            let (component_index, mut args) = match &instances[entry].0 {
+                ComponentInstance::Instantiate {
+                    component_index,
+                    args,
+                } => (*component_index, args.to_vec()),
+                _ => unreachable!(),
+            };
+            self.swap_two(&mut args);
+            component_index.encode(&mut instance);
+            args.len().encode(&mut instance);
+            for arg in args {
+                arg.name.encode(&mut instance);
+                translate_kind(arg.kind).encode(&mut instance);
+                arg.index.encode(&mut instance);
+            }
+            instances.iter().map(|(_, raw)| *raw).collect::<Vec<_>>()
+        };
+
+        let mut raw = raw;
+        raw[entry] = &instance;
+        Ok(Some(encode_replacing(
+            sections,
+            section,
+            &section_data(&raw),
+        )))
+    }
+
+    fn swap_two<T>(&mut self, items: &mut [T]) {
+        let a = self.rng().gen_range(0..items.len());
+        let b = (a + self.rng().gen_range(1..items.len())) % items.len();
+        items.swap(a, b);
+    }
+}
+
+/// Split a component into its sections.
+fn parse_sections(wasm: &[u8]) -> Result<Vec<RawSection<'_>>> {
+    let mut reader = BinaryReader::new(wasm);
+    // Skip the magic number and version, which were checked by the parser.
+    reader.read_bytes(8)?;
+    let mut sections = Vec::new();
+    while !reader.eof() {
+        let id = reader.read_u8()?;
+        let len = reader.read_var_u32()?;
+        let data = reader.read_bytes(len as usize)?;
+        sections.push(RawSection { id, data });
+    }
+    Ok(sections)
+}
+
+/// The types of a component, and the names and types of its imports and
+/// exports.
+struct ExternalType<'a> {
+    types: Types,
+    imports: Vec<(&'a str, ComponentEntityType)>,
+    exports: Vec<(&'a str, ComponentEntityType)>,
+}
+
+impl<'a> ExternalType<'a> {
+    /// Get the external type of the given component, if `validator` accepts
+    /// it.
+    fn new(mut validator: Validator, wasm: &'a [u8]) -> Option<ExternalType<'a>> {
+        let types = validator.validate_all(wasm).ok()?;
+        let mut imports = Vec::new();
+        let mut exports = Vec::new();
+        for section in parse_sections(wasm).ok()? {
+            if section.id == ComponentSectionId::Import as u8 {
+                for import in ComponentImportSectionReader::new(section.data, 0).ok()? {
+                    let import = import.ok()?;
+                    let ty = types.component_entity_type_from_import(&import)?;
+                    imports.push((import.name, ty));
+                }
+            } else if section.id == ComponentSectionId::Export as u8 {
+                for export in ComponentExportSectionReader::new(section.data, 0).ok()? {
+                    let export = export.ok()?;
+                    let ty = types.component_entity_type_from_export(&export)?;
+                    exports.push((export.name, ty));
+                }
+            }
+        }
+        Some(ExternalType {
+            types,
+            imports,
+            exports,
+        })
+    }
+
+    /// Is this the same type as `other`, with the same imports and exports in
+    /// the same order, each of an equivalent type?
+    fn same_as(&self, other: &ExternalType) -> bool {
+        let same = |a: &[(&str, ComponentEntityType)], b: &[(&str, ComponentEntityType)]| {
+            let (at, bt) = (self.types.as_ref(), other.types.as_ref());
+            a.len() == b.len()
+                && a.iter().zip(b).all(|((a_name, a), (b_name, b))| {
+                    a_name == b_name
+                        && ComponentEntityType::is_subtype_of(a, at, b, bt)
+                        && ComponentEntityType::is_subtype_of(b, bt, a, at)
+                })
+        };
+        same(&self.imports, &other.imports) && same(&self.exports, &other.exports)
+    }
+}
+
+impl WasmMutate<'_> {
+    /// Does `mutated` have the same imports and exports, of the same types, as
+    /// `original`?
+    ///
+    /// An invalid original component has no type to preserve, so any mutation
+    /// of it is accepted.
+    fn same_external_type(&self, original: &[u8], mutated: &[u8]) -> bool {
+        match ExternalType::new(self.validator(), original) {
+            Some(original) => {
+                ExternalType::new(self.validator(), mutated).is_some_and(|m| original.same_as(&m))
+            }
+            None => true,
+        }
+    }
+}
+
+/// Get the raw bytes of each of the items defined by a section.
+///
+/// Sections that aren't a list of items, such as nested modules, are treated
+/// as a single item.
+fn items<'a>(section: &RawSection<'a>) -> Result<Vec<&'a [u8]>> {
+    fn raw<R>(data: &[u8], reader: R) -> Result<Vec<&[u8]>>
+    where
+        R: SectionReader + SectionWithLimitedItems,
+    {
+        Ok(entries(data, reader)?
+            .into_iter()
+            .map(|(_, raw)| raw)
+            .collect())
+    }
+
+    let data = section.data;
+    let id = section.id;
+    if id == ComponentSectionId::CoreInstance as u8 {
+        raw(data, InstanceSectionReader::new(data, 0)?)
+    } else if id == ComponentSectionId::CoreType as u8 {
+        raw(data, CoreTypeSectionReader::new(data, 0)?)
+    } else if id == ComponentSectionId::Instance as u8 {
+        raw(data, ComponentInstanceSectionReader::new(data, 0)?)
+    } else if id == ComponentSectionId::Alias as u8 {
+        raw(data, ComponentAliasSectionReader::new(data, 0)?)
+    } else if id == ComponentSectionId::Type as u8 {
+        raw(data, ComponentTypeSectionReader::new(data, 0)?)
+    } else if id == ComponentSectionId::CanonicalFunction as u8 {
+        raw(data, ComponentCanonicalSectionReader::new(data, 0)?)
+    } else if id == ComponentSectionId::Import as u8 {
+        raw(data, ComponentImportSectionReader::new(data, 0)?)
+    } else if id == ComponentSectionId::Export as u8 {
+        raw(data, ComponentExportSectionReader::new(data, 0)?)
+    } else {
+        Ok(vec![data])
+    }
+}
+
+/// Read all the entries of a section along with their raw bytes.
+fn entries<R>(data: &[u8], mut reader: R) -> Result<Vec<(R::Item, &[u8])>>
+where
+    R: SectionReader + SectionWithLimitedItems,
+{
+    let mut entries = Vec::new();
+    for _ in 0..reader.get_count() {
+        let start = reader.original_position();
+        let item = reader.read()?;
+        entries.push((item, &data[start..reader.original_position()]));
+    }
+    Ok(entries)
+}
+
+/// Build the contents of a section from the raw bytes of its entries.
+fn section_data(entries: &[&[u8]]) -> Vec<u8> {
+    let mut data = Vec::new();
+    entries.len().encode(&mut data);
+    for entry in entries {
+        data.extend_from_slice(entry);
+    }
+    data
+}
+
+fn encode(sections: &[RawSection]) -> Vec<u8> {
+    let mut component = Component::new();
+    for section in sections {
+        component.section(section);
+    }
+    component.finish()
+}
+
+fn encode_replacing(sections: &[RawSection], index: usize, data: &[u8]) -> Vec<u8> {
+    let mut sections = sections.to_vec();
+    sections[index].data = data;
+    encode(&sections)
+}
+
+fn is_string_encoding(option: &CanonicalOption) -> bool {
+    matches!(
+        option,
+        CanonicalOption::UTF8 | CanonicalOption::UTF16 | CanonicalOption::CompactUTF16
+    )
+}
+
+fn translate_canonical_option(option: CanonicalOption) -> wasm_encoder::CanonicalOption {
+    match option {
+        CanonicalOption::UTF8 => wasm_encoder::CanonicalOption::UTF8,
+        CanonicalOption::UTF16 => wasm_encoder::CanonicalOption::UTF16,
+        CanonicalOption::CompactUTF16 => wasm_encoder::CanonicalOption::CompactUTF16,
+        CanonicalOption::Memory(i) => wasm_encoder::CanonicalOption::Memory(i),
+        CanonicalOption::Realloc(i) => wasm_encoder::CanonicalOption::Realloc(i),
+        CanonicalOption::PostReturn(i) => wasm_encoder::CanonicalOption::PostReturn(i),
+        CanonicalOption::Async => wasm_encoder::CanonicalOption::Async,
+        CanonicalOption::Callback(i) => wasm_encoder::CanonicalOption::Callback(i),
+    }
+}
+
+fn translate_kind(kind: ComponentExternalKind) -> ComponentExportKind {
+    match kind {
+        ComponentExternalKind::Module => ComponentExportKind::Module,
+        ComponentExternalKind::Func => ComponentExportKind::Func,
+        ComponentExternalKind::Value => ComponentExportKind::Value,
+        ComponentExternalKind::Type => ComponentExportKind::Type,
+        ComponentExternalKind::Instance => ComponentExportKind::Instance,
+        ComponentExternalKind::Component => ComponentExportKind::Component,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPONENT: &str = r#"
+        (component
+          (import "log" (func $log (param string)))
+          (core module $libc
+            (memory (export "memory") 1)
+            (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+              i32.const 0)
+          )
+          (core module $main
+            (import "libc" "memory" (memory 1))
+            (import "env" "log" (func $log (param i32 i32)))
+            (func (export "run") (param i32 i32)
+              local.get 0
+              local.get 1
+              call $log)
+            (func (export "add") (param i32 i32) (result i32)
+              local.get 0
+              local.get 1
+              i32.add)
+          )
+          (core instance $libc (instantiate $libc))
+          (alias core export $libc "memory" (core memory $memory))
+          (alias core export $libc "realloc" (core func $realloc))
+          (core func $log_lower
+            (canon lower (func $log) string-encoding=utf16 (memory $memory) (realloc $realloc)))
+          (core instance $main (instantiate $main
+            (with "libc" (instance $libc))
+            (with "env" (instance (export "log" (func $log_lower))))
+          ))
+          (func (export "run") (param string)
+            (canon lift (core func $main "run") (memory $memory) (realloc $realloc)))
+          (alias core export $main "add" (core func $add))
+        )
+    "#;
+
+    // Without nested modules, every mutation that applies when reducing is
+    // one of the component-level ones.
+    const REDUCIBLE_COMPONENT: &str = r#"
+        (component
+          (import "i" (instance $i (export "f" (func)) (export "g" (func))))
+          (alias export $i "f" (func $f))
+          (core func (canon lower (func $f) string-encoding=utf16))
+          (alias export $i "g" (func $g))
+        )
+    "#;
+
+    fn mutate(wasm: &[u8], configure: impl Fn(&mut WasmMutate)) -> Vec<Vec<u8>> {
+        assert!(is_component(wasm));
+        let mut outputs = Vec::new();
+        for seed in 0..100 {
+            let mut wasm_mutate = WasmMutate::default();
+            wasm_mutate.seed(seed);
+            configure(&mut wasm_mutate);
+            match wasm_mutate.run(wasm) {
+                Ok(mut iter) => outputs.push(iter.next().unwrap().unwrap()),
+                Err(e) if matches!(e.kind(), ErrorKind::NoMutationsApplicable) => {}
+                Err(e) => panic!("failed to mutate component: {}", e),
+            };
+        }
+        assert!(!outputs.is_empty());
+        outputs
+    }
+
+    #[test]
+    fn mutated_components_are_valid() {
+        let wasm = wat::parse_str(COMPONENT).unwrap();
+        let mut changed = false;
+        for output in mutate(&wasm, |_| {}) {
+            crate::validate(&output);
+            changed |= output != wasm;
+        }
+        assert!(changed);
+    }
+
+    #[test]
+    fn reduced_components_are_smaller() {
+        let wasm = wat::parse_str(REDUCIBLE_COMPONENT).unwrap();
+        for output in mutate(&wasm, |m| {
+            m.reduce(true);
+        }) {
+            crate::validate(&output);
+            assert!(output.len() < wasm.len());
+        }
+    }
+
+    #[test]
+    fn component_type_is_preserved() {
+        let wasm = wat::parse_str(COMPONENT).unwrap();
+        for output in mutate(&wasm, |m| {
+            m.preserve_component_type(true);
+        }) {
+            crate::validate(&output);
+            assert!(WasmMutate::default().same_external_type(&wasm, &output));
+        }
+
+        let other =
+            wat::parse_str(r#"(component (import "log" (func (param string) (result u32))))"#)
+                .unwrap();
+        assert!(!WasmMutate::default().same_external_type(&wasm, &other));
+    }
+}
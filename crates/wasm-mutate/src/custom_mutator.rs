@@ -0,0 +1,123 @@
+//! Support for mutators defined outside of this crate.
+
+use crate::{Result, WasmMutate};
+use rand::Rng;
+use std::borrow::Cow;
+
+/// A mutation defined outside of `wasm-mutate`, registered with
+/// [`WasmMutate::add_mutator`].
+///
+/// Custom mutators let domain-specific fuzzers add their own mutations, for
+/// example ones that introduce particular patterns of WASI calls, without
+/// forking `wasm-mutate`. A registered custom mutator is as likely to be tried
+/// first as each of the built-in mutators, and like them it is skipped when
+/// its [`can_mutate`][CustomMutator::can_mutate] returns `false`.
+///
+/// Custom mutators work on the raw bytes of a core Wasm module. When the input
+/// module is valid, a mutated module that is not valid is discarded and the
+/// next mutator is tried instead.
+///
+/// # Example
+///
+/// ```
+/// # fn _foo() -> anyhow::Result<()> {
+/// use std::sync::Arc;
+/// use wasm_mutate::{CustomMutator, WasmMutate};
+///
+/// struct AddCustomSection;
+///
+/// impl CustomMutator for AddCustomSection {
+///     fn can_mutate(&self, _wasm: &[u8], config: &WasmMutate) -> bool {
+///         !config.reduces()
+///     }
+///
+///     fn mutate(
+///         &self,
+///         wasm: &[u8],
+///         _seed: u64,
+///         _config: &WasmMutate,
+///     ) -> wasm_mutate::Result<Vec<u8>> {
+///         // Append an empty custom section named "foo".
+///         let mut wasm = wasm.to_vec();
+///         wasm.extend_from_slice(&[0, 4, 3, b'f', b'o', b'o']);
+///         Ok(wasm)
+///     }
+/// }
+///
+/// let input_wasm = wat::parse_str("(module)")?;
+/// let mut mutate = WasmMutate::default();
+/// mutate.add_mutator(Arc::new(AddCustomSection));
+/// for mutated_wasm in mutate.run(&input_wasm)? {
+///     let mutated_wasm = mutated_wasm?;
+///     // Feed `mutated_wasm` into your tests...
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub trait CustomMutator {
+    /// Can this `CustomMutator` *probably* be applied to the given Wasm and
+    /// configuration?
+    ///
+    /// As with the built-in mutators, this should be a quick, incomplete
+    /// check, and it should take the configuration into account: a mutator
+    /// that grows the Wasm should return `false` when
+    /// [`WasmMutate::reduces`] is set, and one that changes its behavior should
+    /// return `false` when [`WasmMutate::preserves_semantics`] is set.
+    fn can_mutate(&self, wasm: &[u8], config: &WasmMutate) -> bool;
+
+    /// Run this mutation, returning the mutated Wasm.
+    ///
+    /// The `seed` should be the only source of randomness, so that given the
+    /// same input Wasm and configuration `wasm-mutate` always generates the
+    /// same output Wasm.
+    fn mutate(&self, wasm: &[u8], seed: u64, config: &WasmMutate) -> Result<Vec<u8>>;
+
+    /// What is this mutator's name?
+    ///
+    /// This is only used for debugging and logging purposes.
+    fn name(&self) -> Cow<'static, str> {
+        std::any::type_name::<Self>().into()
+    }
+}
+
+impl WasmMutate<'_> {
+    /// Try the custom mutators in order, starting from the given one, and
+    /// return the output of the first one that can mutate the input Wasm.
+    pub(crate) fn apply_custom_mutators(&mut self, start: usize) -> Result<Option<Vec<u8>>> {
+        let mutators = self.custom_mutators.clone();
+        let input_wasm = self.info().input_wasm;
+        let mut valid = None;
+
+        for i in 0..mutators.len() {
+            let m = &mutators[(start + i) % mutators.len()];
+
+            let can_mutate = m.can_mutate(input_wasm, self);
+            log::trace!("Can custom `{}` mutate? {}", m.name(), can_mutate);
+            if !can_mutate {
+                continue;
+            }
+
+            log::debug!("attempting to mutate with custom `{}`", m.name());
+            let seed = self.rng().gen();
+            let wasm = match m.mutate(input_wasm, seed, self) {
+                Ok(wasm) => wasm,
+                Err(e) => {
+                    log::debug!("custom mutator `{}` failed: {}", m.name(), e);
+                    return Err(e);
+                }
+            };
+            if *valid.get_or_insert_with(|| self.is_valid(input_wasm)) && !self.is_valid(&wasm) {
+                log::debug!("custom mutator `{}` produced invalid Wasm", m.name());
+                continue;
+            }
+            log::debug!("custom mutator `{}` succeeded", m.name());
+            self.last_mutation = self.last_start.map(|start| (start, m.name()));
+            if self.preserve_debug_info {
+                return crate::debug_info::preserve_debug_info(input_wasm, &wasm).map(Some);
+            }
+            return Ok(Some(wasm));
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,327 @@
+//! Keeping the debug information of a module usable across mutations.
+//!
+//! When debug info preservation is enabled, the `name` section and the DWARF
+//! `.debug_*` custom sections of the input module are carried over to every
+//! mutated module, in place of whatever the mutation did to them:
+//!
+//! * Names are kept for the items that still exist. For functions whose body
+//!   changed, label names and the names of locals that no longer exist are
+//!   dropped.
+//!
+//! * DWARF refers to code by its offset within the code section, so every
+//!   function body that changed is padded with `nop`s to its original size,
+//!   which keeps every function at its original offset. A mutation that grows
+//!   a function body can't be reconciled with the DWARF and is rejected.
+//!
+//! Mutators that renumber existing items, such as removing a function, are
+//! disabled in this mode since the names would no longer line up.
+
+use crate::{info::ModuleInfo, Error, Result, WasmMutate};
+use wasm_encoder::{Encode, IndirectNameMap, Module, NameMap, NameSection, RawSection, SectionId};
+use wasmparser::{
+    BinaryReader, CustomSectionReader, FunctionBody, Name, NameSectionReader, NamingReader, Parser,
+    Payload, Type, TypeRef,
+};
+
+/// Is the custom section with the given name one that debug info preservation
+/// keeps intact?
+pub(crate) fn is_debug_section(name: &str) -> bool {
+    name == "name" || name.starts_with(".debug_")
+}
+
+/// The parts of a module that its debug information refers to.
+#[derive(Default)]
+struct Layout<'a> {
+    /// The number of parameters of each type.
+    type_params: Vec<u32>,
+    /// The type of each function, including imported ones.
+    func_types: Vec<u32>,
+    num_imported_funcs: u32,
+    num_tables: u32,
+    num_memories: u32,
+    num_globals: u32,
+    num_elements: u32,
+    num_data: u32,
+    /// The encoded length of the code section's function count.
+    code_count_len: usize,
+    bodies: Vec<Body<'a>>,
+    /// The name and DWARF sections, in order.
+    debug_sections: Vec<(&'a str, &'a [u8])>,
+}
+
+struct Body<'a> {
+    /// The encoded body, including its size.
+    raw: &'a [u8],
+    /// The encoded length of the body's size.
+    size_len: usize,
+    /// The number of locals, excluding parameters.
+    num_locals: u32,
+}
+
+impl<'a> Layout<'a> {
+    fn new(wasm: &'a [u8]) -> Result<Layout<'a>> {
+        let mut layout = Layout::default();
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload? {
+                Payload::TypeSection(reader) => {
+                    for ty in reader {
+                        match ty? {
+                            Type::Func(ty) => layout.type_params.push(ty.params().len() as u32),
+                        }
+                    }
+                }
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        match import?.ty {
+                            TypeRef::Func(ty) => {
+                                layout.func_types.push(ty);
+                                layout.num_imported_funcs += 1;
+                            }
+                            TypeRef::Table(_) => layout.num_tables += 1,
+                            TypeRef::Memory(_) => layout.num_memories += 1,
+                            TypeRef::Global(_) => layout.num_globals += 1,
+                            TypeRef::Tag(_) => {}
+                        }
+                    }
+                }
+                Payload::FunctionSection(reader) => {
+                    for ty in reader {
+                        layout.func_types.push(ty?);
+                    }
+                }
+                Payload::TableSection(reader) => layout.num_tables += reader.get_count(),
+                Payload::MemorySection(reader) => layout.num_memories += reader.get_count(),
+                Payload::GlobalSection(reader) => layout.num_globals += reader.get_count(),
+                Payload::ElementSection(reader) => layout.num_elements = reader.get_count(),
+                Payload::DataSection(reader) => layout.num_data = reader.get_count(),
+                Payload::CodeSectionStart { range, .. } => {
+                    let data = &wasm[range];
+                    let mut reader = BinaryReader::new(data);
+                    let count = reader.read_var_u32()?;
+                    layout.code_count_len = reader.current_position();
+                    for _ in 0..count {
+                        let start = reader.current_position();
+                        let size = reader.read_var_u32()?;
+                        let size_len = reader.current_position() - start;
+                        let body = reader.read_bytes(size as usize)?;
+                        let mut num_locals = 0;
+                        for local in FunctionBody::new(0, body).get_locals_reader()? {
+                            num_locals += local?.0;
+                        }
+                        layout.bodies.push(Body {
+                            raw: &data[start..reader.current_position()],
+                            size_len,
+                            num_locals,
+                        });
+                    }
+                }
+                Payload::CustomSection(reader) if is_debug_section(reader.name()) => {
+                    layout
+                        .debug_sections
+                        .push((reader.name(), &wasm[reader.range()]));
+                }
+                _ => {}
+            }
+        }
+        Ok(layout)
+    }
+
+    /// The number of locals of the given function, including parameters.
+    fn num_locals(&self, func: u32) -> Option<u32> {
+        let ty = *self.func_types.get(func as usize)?;
+        let params = *self.type_params.get(ty as usize)?;
+        match func.checked_sub(self.num_imported_funcs) {
+            Some(defined) => Some(params + self.bodies.get(defined as usize)?.num_locals),
+            None => Some(params),
+        }
+    }
+
+    /// Has the body of the given function changed in `other`?
+    fn body_changed(&self, other: &Layout, func: u32) -> bool {
+        fn body<'b>(layout: &Layout<'b>, func: u32) -> Option<&'b [u8]> {
+            let defined = func.checked_sub(layout.num_imported_funcs)?;
+            Some(layout.bodies.get(defined as usize)?.raw)
+        }
+        body(self, func) != body(other, func)
+    }
+}
+
+/// Carry the debug information of `original` over to `mutated`, a mutation of
+/// it.
+pub(crate) fn preserve_debug_info(original: &[u8], mutated: &[u8]) -> Result<Vec<u8>> {
+    let before = Layout::new(original)?;
+    if before.debug_sections.is_empty() {
+        return Ok(mutated.to_vec());
+    }
+    let after = Layout::new(mutated)?;
+    let info = ModuleInfo::new(mutated)?;
+
+    let has_dwarf = before
+        .debug_sections
+        .iter()
+        .any(|(name, _)| *name != "name");
+    let code = match info.code {
+        Some(_) if has_dwarf => Some(pad_code(&before, &after)?),
+        _ => None,
+    };
+
+    let mut module = Module::new();
+    for (i, section) in info.raw_sections.iter().enumerate() {
+        if section.id == SectionId::Custom as u8
+            && is_debug_section(CustomSectionReader::new(section.data, 0)?.name())
+        {
+            continue;
+        }
+        match &code {
+            Some(code) if Some(i) == info.code => {
+                module.section(&RawSection {
+                    id: SectionId::Code as u8,
+                    data: code,
+                });
+            }
+            _ => {
+                module.section(section);
+            }
+        }
+    }
+    for (name, data) in &before.debug_sections {
+        if *name == "name" {
+            module.section(&names(&before, &after, data)?);
+        } else {
+            module.section(&RawSection {
+                id: SectionId::Custom as u8,
+                data,
+            });
+        }
+    }
+    Ok(module.finish())
+}
+
+/// Re-encode the code section of `after` so that each function body is at the
+/// same offset as in `before`.
+fn pad_code(before: &Layout, after: &Layout) -> Result<Vec<u8>> {
+    let mut code = Vec::new();
+    after.bodies.len().encode(&mut code);
+    if code.len() != before.code_count_len {
+        // Re-encode the count with the original width, if it fits.
+        code.clear();
+        let mut count = after.bodies.len();
+        for i in 0..before.code_count_len {
+            let more = i + 1 < before.code_count_len;
+            code.push((count & 0x7f) as u8 | if more { 0x80 } else { 0 });
+            count >>= 7;
+        }
+        if count != 0 {
+            return Err(Error::no_mutations_applicable());
+        }
+    }
+
+    for (i, body) in after.bodies.iter().enumerate() {
+        let original = match before.bodies.get(i) {
+            Some(original) if original.raw != body.raw => original,
+            // Unchanged and new bodies are kept as they are; new ones come
+            // after all of the original ones.
+            _ => {
+                code.extend_from_slice(body.raw);
+                continue;
+            }
+        };
+        let contents = &body.raw[body.size_len..];
+        let size = original.raw.len() - original.size_len;
+        if contents.len() > size {
+            return Err(Error::no_mutations_applicable());
+        }
+        // Keep the original size, and fill the body up with `nop`s before its
+        // final `end`.
+        code.extend_from_slice(&original.raw[..original.size_len]);
+        code.extend_from_slice(&contents[..contents.len() - 1]);
+        code.extend(std::iter::repeat_n(0x01, size - contents.len()));
+        code.push(0x0b);
+    }
+    Ok(code)
+}
+
+/// Rebuild the name section `data` of `before` for `after`.
+fn names(before: &Layout, after: &Layout, data: &[u8]) -> Result<NameSection> {
+    /// Keep the names of the indices below `count`.
+    fn name_map(mut names: NamingReader, count: u32) -> Result<NameMap> {
+        let mut map = NameMap::new();
+        for _ in 0..names.get_count() {
+            let naming = names.read()?;
+            if naming.index < count {
+                map.append(naming.index, naming.name);
+            }
+        }
+        Ok(map)
+    }
+
+    let custom = CustomSectionReader::new(data, 0)?;
+    let mut reader = NameSectionReader::new(custom.data(), custom.data_offset())?;
+    let mut section = NameSection::new();
+    while !reader.eof() {
+        match reader.read()? {
+            Name::Module(name) => section.module(name.get_name()?),
+            Name::Function(names) => {
+                section.functions(&name_map(names.get_map()?, after.func_types.len() as u32)?)
+            }
+            Name::Local(names) => {
+                let mut indirect = IndirectNameMap::new();
+                let mut reader = names.get_indirect_map()?;
+                for _ in 0..reader.get_indirect_count() {
+                    let naming = reader.read()?;
+                    let func = naming.indirect_index;
+                    let count = match after.num_locals(func) {
+                        Some(count) => count,
+                        None => continue,
+                    };
+                    indirect.append(func, &name_map(naming.get_map()?, count)?);
+                }
+                section.locals(&indirect);
+            }
+            Name::Label(names) => {
+                let mut indirect = IndirectNameMap::new();
+                let mut reader = names.get_indirect_map()?;
+                for _ in 0..reader.get_indirect_count() {
+                    let naming = reader.read()?;
+                    let func = naming.indirect_index;
+                    if before.body_changed(after, func) {
+                        continue;
+                    }
+                    indirect.append(func, &name_map(naming.get_map()?, u32::MAX)?);
+                }
+                section.labels(&indirect);
+            }
+            Name::Type(names) => {
+                section.types(&name_map(names.get_map()?, after.type_params.len() as u32)?)
+            }
+            Name::Table(names) => section.tables(&name_map(names.get_map()?, after.num_tables)?),
+            Name::Memory(names) => {
+                section.memories(&name_map(names.get_map()?, after.num_memories)?)
+            }
+            Name::Global(names) => section.globals(&name_map(names.get_map()?, after.num_globals)?),
+            Name::Element(names) => {
+                section.elements(&name_map(names.get_map()?, after.num_elements)?)
+            }
+            Name::Data(names) => section.data(&name_map(names.get_map()?, after.num_data)?),
+            // Subsections that aren't understood can't be kept consistent.
+            Name::Unknown { .. } => {}
+        }
+    }
+    Ok(section)
+}
+
+impl<'wasm> WasmMutate<'wasm> {
+    /// Get the function that finishes encoding each module mutated from the
+    /// input Wasm, carrying the input's debug information over to it if that
+    /// is configured.
+    pub(crate) fn finisher(&self) -> impl Fn(Result<Module>) -> Result<Vec<u8>> + 'wasm {
+        let original = self.preserve_debug_info.then(|| self.info().input_wasm);
+        move |module| {
+            let wasm = module?.finish();
+            match original {
+                Some(original) => preserve_debug_info(original, &wasm),
+                None => Ok(wasm),
+            }
+        }
+    }
+}
@@ -9,24 +9,40 @@
 
 #![cfg_attr(not(feature = "clap"), deny(missing_docs))]
 
+mod component;
+mod custom_mutator;
+mod debug_info;
 mod error;
 mod info;
 mod module;
 mod mutators;
+mod replay;
 
+pub use custom_mutator::CustomMutator;
 pub use error::*;
+pub use replay::MutationRecord;
+pub use wasm_features::WasmFeatures;
 
 use crate::mutators::{
-    add_function::AddFunctionMutator, add_type::AddTypeMutator, codemotion::CodemotionMutator,
-    function_body_unreachable::FunctionBodyUnreachable, modify_const_exprs::ConstExpressionMutator,
-    modify_data::ModifyDataMutator, peephole::PeepholeMutator, remove_export::RemoveExportMutator,
-    remove_item::RemoveItemMutator, remove_section::RemoveSection,
-    rename_export::RenameExportMutator, snip_function::SnipMutator, Item,
+    add_function::AddFunctionMutator,
+    add_type::AddTypeMutator,
+    codemotion::CodemotionMutator,
+    function_body_unreachable::FunctionBodyUnreachable,
+    modify_const_exprs::ConstExpressionMutator,
+    modify_data::{ModifyDataMutator, StructuredDataMutator},
+    peephole::PeepholeMutator,
+    remove_export::RemoveExportMutator,
+    remove_item::{RemoveDeadFunctionsMutator, RemoveItemMutator},
+    remove_section::RemoveSection,
+    rename_export::RenameExportMutator,
+    simd::SimdMutator,
+    snip_function::SnipMutator,
+    Item,
 };
 use info::ModuleInfo;
 use mutators::Mutator;
 use rand::{rngs::SmallRng, Rng, SeedableRng};
-use std::{cell::Cell, sync::Arc};
+use std::{borrow::Cow, cell::Cell, sync::Arc};
 
 #[cfg(feature = "clap")]
 use clap::Parser;
@@ -46,10 +62,12 @@ macro_rules! define_mutators {
             log::trace!("Can `{}` mutate? {}", m.name(), can_mutate);
             if can_mutate {
                 log::debug!("attempting to mutate with `{}`", m.name());
+                let finish = $self.finisher();
+                $self.last_mutation = $self.last_start.map(|start| (start, m.name()));
                 match m.clone().mutate($self) {
                     Ok(iter) => {
                         log::debug!("mutator `{}` succeeded", m.name());
-                        return Ok(Box::new(iter.into_iter().map(|r| r.map(|m| m.finish()))))
+                        return Ok(Box::new(iter.into_iter().map(finish)))
                     }
                     Err(e) => {
                         log::debug!("mutator `{}` failed: {}", m.name(), e);
@@ -65,10 +83,12 @@ macro_rules! define_mutators {
                 log::trace!("Can `{}` mutate? {}", m.name(), can_mutate);
                 if can_mutate {
                     log::debug!("attempting to mutate with `{}`", m.name());
+                    let finish = $self.finisher();
+                    $self.last_mutation = $self.last_start.map(|start| (start, m.name()));
                     match m.clone().mutate($self) {
                         Ok(iter) => {
                             log::debug!("mutator `{}` succeeded", m.name());
-                            return Ok(Box::new(iter.into_iter().map(|r| r.map(|m| m.finish()))))
+                            return Ok(Box::new(iter.into_iter().map(finish)))
                         }
                         Err(e) => {
                             log::debug!("mutator {} failed: {}", m.name(), e);
@@ -82,9 +102,11 @@ macro_rules! define_mutators {
                 let m = $head;
 
                 if m.can_mutate($self) {
+                    let finish = $self.finisher();
+                    $self.last_mutation = $self.last_start.map(|start| (start, m.name()));
                     match m.clone().mutate($self) {
                         Ok(iter) => {
-                            return Ok(Box::new(iter.into_iter().map(|r| r.map(|m| m.finish()))))
+                            return Ok(Box::new(iter.into_iter().map(finish)))
                         }
                         Err(e) => {
                             log::debug!("mutator {} failed: {}; will try again", m.name(), e);
@@ -100,12 +122,31 @@ macro_rules! define_mutators {
     ( $self: ident , ($first: expr , $( $tail: expr ,)* ) ) => {
         {
             let count = define_mutators!(@count $first , $($tail ,)*);
-            let discriminator:u32 = $self.rng().gen_range(0..count);
+            // Custom mutators take part in choosing the first mutator to try.
+            // When one of them is chosen but none of them can mutate, the
+            // built-in mutators are tried from the first one.
+            let total = count + $self.custom_mutators.len() as u32;
+            let mut discriminator:u32 = $self.choose_start(total);
+            let custom_first = discriminator >= count;
+            if custom_first {
+                if let Some(wasm) = $self.apply_custom_mutators((discriminator - count) as usize)? {
+                    return Ok(Box::new(std::iter::once(Ok(wasm))));
+                }
+                discriminator = 0;
+            }
             define_mutators!(@expand $self, discriminator , 0 , $first , $($tail ,)*  . , );
+            custom_first
         }
     };
 }
 
+/// The weight of a starting mutator that no feedback has been reported for.
+const INITIAL_START_WEIGHT: u32 = 16;
+
+/// The largest weight that feedback can give a starting mutator, which keeps
+/// the others from being starved.
+const MAX_START_WEIGHT: u32 = 1024;
+
 // NB: only add this doc comment if we are not building the CLI, since otherwise
 // it will override the main CLI's about text.
 #[cfg_attr(
@@ -184,11 +225,53 @@ pub struct WasmMutate<'wasm> {
     #[cfg_attr(feature = "clap", clap(long))]
     reduce: bool,
 
+    /// Keep the name section and DWARF custom sections of the Wasm module
+    /// consistent with the mutated module, so that it can still be
+    /// symbolicated.
+    #[cfg_attr(feature = "clap", clap(long))]
+    preserve_debug_info: bool,
+
+    /// Only keep mutations of a component that leave the names and types of
+    /// its imports and exports unchanged, so that every mutated component can
+    /// be instantiated the same way as the original.
+    #[cfg_attr(feature = "clap", clap(long))]
+    preserve_component_type: bool,
+
+    /// Comma-separated list of WebAssembly features which the input and the
+    /// mutated Wasm may use, as with `wasm-tools validate --features`.
+    ///
+    /// Defaults to the features that `wasm-mutate` supports: the default
+    /// features plus multi-memory, memory64, and the component model.
+    #[cfg_attr(feature = "clap", clap(long))]
+    features: Option<WasmFeatures>,
+
     // Note: this is only exposed via the programmatic interface, not via the
     // CLI.
     #[cfg_attr(feature = "clap", clap(skip = None))]
     raw_mutate_func: Option<Arc<dyn Fn(&mut Vec<u8>, usize) -> Result<()>>>,
 
+    // Note: this is only exposed via the programmatic interface, not via the
+    // CLI.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    custom_mutators: Vec<Arc<dyn CustomMutator>>,
+
+    // Note: the feedback used to weigh mutators is only exposed via the
+    // programmatic interface, not via the CLI.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    start_weights: Vec<u32>,
+
+    #[cfg_attr(feature = "clap", clap(skip = None))]
+    last_start: Option<u32>,
+
+    // The starting mutator and the name of the mutator used by the last run,
+    // for recording it.
+    #[cfg_attr(feature = "clap", clap(skip = None))]
+    last_mutation: Option<(u32, Cow<'static, str>)>,
+
+    // The starting mutator to use instead of a random one, when replaying.
+    #[cfg_attr(feature = "clap", clap(skip = None))]
+    forced_start: Option<u32>,
+
     #[cfg_attr(feature = "clap", clap(skip = None))]
     rng: Option<SmallRng>,
 
@@ -208,7 +291,15 @@ impl Default for WasmMutate<'_> {
             seed,
             preserve_semantics: false,
             reduce: false,
+            preserve_debug_info: false,
+            preserve_component_type: false,
+            features: None,
             raw_mutate_func: None,
+            custom_mutators: Vec::new(),
+            start_weights: Vec::new(),
+            last_start: None,
+            last_mutation: None,
+            forced_start: None,
             fuel: Cell::new(u64::MAX),
             rng: None,
             info: None,
@@ -249,6 +340,49 @@ impl<'wasm> WasmMutate<'wasm> {
         self
     }
 
+    /// Configure whether the debug information of the Wasm module is kept
+    /// usable in the mutated module.
+    ///
+    /// When set, the name section and DWARF `.debug_*` custom sections of the
+    /// input are carried over to every mutated module. Names of items that no
+    /// longer exist are dropped, mutators that would renumber items are
+    /// disabled, and mutated function bodies are padded so that every
+    /// function keeps its original offset in the code section. Mutations that
+    /// would grow a function body of a module with DWARF are rejected.
+    ///
+    /// This keeps crash reproducers symbolicated, which eases triage.
+    pub fn preserve_debug_info(&mut self, preserve_debug_info: bool) -> &mut Self {
+        self.preserve_debug_info = preserve_debug_info;
+        self
+    }
+
+    /// Configure whether mutations of a component must keep its external type
+    /// unchanged.
+    ///
+    /// When set, every mutated component has the same imports and exports,
+    /// with the same types, as the input component: the interface that its
+    /// WIT describes stays the same. This lets host integration fuzzing mutate
+    /// the internals of a component while every output remains instantiable
+    /// by the same harness. This has no effect on core modules.
+    pub fn preserve_component_type(&mut self, preserve_component_type: bool) -> &mut Self {
+        self.preserve_component_type = preserve_component_type;
+        self
+    }
+
+    /// Set the WebAssembly features which the input and the mutated Wasm may
+    /// use.
+    ///
+    /// Mutations which only validate with features beyond these are
+    /// rejected. Passing the same [`WasmFeatures`] that generated or
+    /// validates the input, such as `wasm_smith::Config::features`, keeps
+    /// every step of a fuzzing pipeline in agreement.
+    ///
+    /// Defaults to [`supported_features`].
+    pub fn features(&mut self, features: WasmFeatures) -> &mut Self {
+        self.features = Some(features);
+        self
+    }
+
     /// Set a custom raw mutation function.
     ///
     /// This is used when we need some underlying raw bytes, for example when
@@ -269,6 +403,50 @@ impl<'wasm> WasmMutate<'wasm> {
         self
     }
 
+    /// Register a custom mutator to be used alongside the built-in ones.
+    ///
+    /// Custom mutators are only applied to core modules. See
+    /// [`CustomMutator`] for details.
+    pub fn add_mutator(&mut self, mutator: Arc<dyn CustomMutator>) -> &mut Self {
+        self.custom_mutators.push(mutator);
+        self
+    }
+
+    /// Report whether the mutation produced by the last call to
+    /// [`run`][crate::WasmMutate::run] was interesting, for example because it
+    /// covered new code in the system under test.
+    ///
+    /// Each run starts trying mutators from a randomly chosen one. Without any
+    /// feedback, that choice is uniform. Once feedback has been reported,
+    /// starting points that led to interesting mutations are chosen more
+    /// often, and the others less often, for the rest of the session.
+    pub fn report_interesting(&mut self, interesting: bool) -> &mut Self {
+        let start = match self.last_start.take() {
+            Some(start) => start as usize,
+            None => return self,
+        };
+        if self.start_weights.len() <= start {
+            self.start_weights.resize(start + 1, INITIAL_START_WEIGHT);
+        }
+        let weight = &mut self.start_weights[start];
+        *weight = if interesting {
+            (*weight * 2).min(MAX_START_WEIGHT)
+        } else {
+            (*weight - 1).max(1)
+        };
+        self
+    }
+
+    /// Are only semantics-preserving transformations performed?
+    pub fn preserves_semantics(&self) -> bool {
+        self.preserve_semantics
+    }
+
+    /// Are only size-reducing transformations performed?
+    pub fn reduces(&self) -> bool {
+        self.reduce
+    }
+
     pub(crate) fn consume_fuel(&self, qt: u64) -> Result<()> {
         if qt > self.fuel.get() {
             log::info!("Out of fuel");
@@ -279,10 +457,22 @@ impl<'wasm> WasmMutate<'wasm> {
     }
 
     /// Run this configured `WasmMutate` on the given input Wasm.
+    ///
+    /// The input may be either a core module or a component. Components are
+    /// mutated by mutating their nested core modules and components in place,
+    /// adding or removing aliases, changing canonical options, and shuffling
+    /// instantiation arguments, always producing a single mutated component.
     pub fn run<'a>(
         &'a mut self,
         input_wasm: &'wasm [u8],
     ) -> Result<Box<dyn Iterator<Item = Result<Vec<u8>>> + 'a>> {
+        self.last_start = None;
+        self.last_mutation = None;
+        if component::is_component(input_wasm) {
+            let component = self.mutate_component(input_wasm)?;
+            return Ok(Box::new(std::iter::once(Ok(component))));
+        }
+
         self.setup(input_wasm)?;
 
         // This macro just expands the logic to return an iterator form the
@@ -292,7 +482,7 @@ impl<'wasm> WasmMutate<'wasm> {
         // All possible start indexes are calculated at compilation time, if N
         // is the number of mutataros, N possible starting indexes are injected
         // and compiled to the final code
-        define_mutators!(
+        let custom_tried = define_mutators!(
             self,
             (
                 PeepholeMutator::new(2),
@@ -319,15 +509,55 @@ impl<'wasm> WasmMutate<'wasm> {
                 RemoveItemMutator(Item::Data),
                 RemoveItemMutator(Item::Element),
                 RemoveItemMutator(Item::Tag),
+                RemoveDeadFunctionsMutator,
                 ModifyDataMutator {
                     max_data_size: 10 << 20, // 10MB
                 },
+                StructuredDataMutator::TruncateString,
+                StructuredDataMutator::PermuteRecords,
+                StructuredDataMutator::FlipString,
+                SimdMutator::LaneIndex,
+                SimdMutator::Derelax,
+                SimdMutator::Shuffle,
+                SimdMutator::SameWidth,
             )
         );
 
+        if !custom_tried {
+            if let Some(wasm) = self.apply_custom_mutators(0)? {
+                return Ok(Box::new(std::iter::once(Ok(wasm))));
+            }
+        }
+
         Err(Error::no_mutations_applicable())
     }
 
+    /// Choose the first of `count` mutators to try, by the weights learned
+    /// from feedback if there is any.
+    ///
+    /// When replaying, the recorded start is used instead, but the random
+    /// choice is still drawn so that the rest of the run consumes the RNG
+    /// exactly as the recorded run did.
+    fn choose_start(&mut self, count: u32) -> u32 {
+        let start = if self.start_weights.is_empty() {
+            self.rng().gen_range(0..count)
+        } else {
+            self.start_weights
+                .resize(count as usize, INITIAL_START_WEIGHT);
+            let total = self.start_weights.iter().sum::<u32>();
+            let mut choice = self.rng().gen_range(0..total);
+            let mut start = 0;
+            while choice >= self.start_weights[start] {
+                choice -= self.start_weights[start];
+                start += 1;
+            }
+            start as u32
+        };
+        let start = self.forced_start.take().unwrap_or(start);
+        self.last_start = Some(start);
+        start
+    }
+
     fn setup(&mut self, input_wasm: &'wasm [u8]) -> Result<()> {
         self.info = Some(ModuleInfo::new(input_wasm)?);
         self.rng = Some(SmallRng::seed_from_u64(self.seed));
@@ -383,21 +613,48 @@ impl<'wasm> WasmMutate<'wasm> {
     }
 }
 
+/// The WebAssembly features that `wasm-mutate` supports, which are used
+/// unless others are configured with [`WasmMutate::features`].
+pub fn supported_features() -> WasmFeatures {
+    WasmFeatures {
+        multi_memory: true,
+        memory64: true,
+        component_model: true,
+        component_model_async: true,
+        ..WasmFeatures::default()
+    }
+}
+
+impl WasmMutate<'_> {
+    /// Does the given module or component validate with the configured
+    /// features?
+    pub(crate) fn is_valid(&self, wasm: &[u8]) -> bool {
+        self.validator().validate_all(wasm).is_ok()
+    }
+
+    /// A validator with the configured features.
+    pub(crate) fn validator(&self) -> wasmparser::Validator {
+        wasmparser::Validator::new_with_features(self.features.unwrap_or_else(supported_features))
+    }
+}
+
 #[cfg(test)]
 pub(crate) fn validate(bytes: &[u8]) {
-    let mut validator = wasmparser::Validator::new_with_features(wasmparser::WasmFeatures {
-        memory64: true,
-        multi_memory: true,
-        ..Default::default()
-    });
+    let mut validator = WasmMutate::default().validator();
     let err = match validator.validate_all(bytes) {
         Ok(_) => return,
         Err(e) => e,
     };
-    drop(std::fs::write("test.wasm", &bytes));
+    // The invalid module is kept outside of the source tree for debugging.
+    let dir = tempfile::tempdir().unwrap().keep();
+    drop(std::fs::write(dir.join("test.wasm"), bytes));
     if let Ok(text) = wasmprinter::print_bytes(bytes) {
-        drop(std::fs::write("test.wat", &text));
+        drop(std::fs::write(dir.join("test.wat"), &text));
     }
 
-    panic!("wasm failed to validate: {} (written to test.wasm)", err);
+    panic!(
+        "wasm failed to validate: {} (written to {})",
+        err,
+        dir.join("test.wasm").display()
+    );
 }
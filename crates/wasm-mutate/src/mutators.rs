@@ -32,6 +32,7 @@ pub mod remove_export;
 pub mod remove_item;
 pub mod remove_section;
 pub mod rename_export;
+pub mod simd;
 pub mod snip_function;
 pub mod start;
 
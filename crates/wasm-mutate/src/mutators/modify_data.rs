@@ -1,8 +1,9 @@
 use super::translate::ConstExprKind;
 use super::Mutator;
 use crate::mutators::{DefaultTranslator, Translator};
-use crate::{Result, WasmMutate};
-use rand::Rng;
+use crate::{Error, Result, WasmMutate};
+use rand::{rngs::SmallRng, seq::SliceRandom, Rng};
+use std::ops::Range;
 use wasm_encoder::{DataSection, DataSegment, DataSegmentMode, Module};
 use wasmparser::{DataKind, DataSectionReader};
 
@@ -17,62 +18,215 @@ impl Mutator for ModifyDataMutator {
         self,
         config: &'a mut WasmMutate,
     ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
-        let mut new_section = DataSection::new();
-        let mut reader = DataSectionReader::new(config.info().get_data_section().data, 0)?;
+        let reader = DataSectionReader::new(config.info().get_data_section().data, 0)?;
 
         // Select an arbitrary data segment to modify.
         let data_to_modify = config.rng().gen_range(0..reader.get_count());
 
-        // Iterate over all data segments in the old data section and re-add
-        // them to the `new_section` one-by-one.
-        for i in 0..reader.get_count() {
-            let data = reader.read()?;
-            let offset;
-            // Preserve the mode of the data segment
-            let mode = match &data.kind {
-                DataKind::Active {
-                    memory_index,
-                    offset_expr,
-                } => {
-                    offset = DefaultTranslator.translate_const_expr(
-                        offset_expr,
-                        &wasmparser::ValType::I32,
-                        ConstExprKind::DataOffset,
-                    )?;
-                    DataSegmentMode::Active {
-                        memory_index: *memory_index,
-                        offset: &offset,
-                    }
+        let module = replace_segment(config, data_to_modify, |config, data| {
+            config.raw_mutate(data, self.max_data_size)
+        })?;
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        // Modifying a data segment doesn't preserve the semantics of the
+        // original module and also only works if there's actually some data.
+        !config.preserve_semantics && config.info().num_data() > 0
+    }
+}
+
+/// Mutator that modifies a data segment by treating its contents as
+/// structured data, such as a table of strings or an array of records, rather
+/// than as raw bytes.
+///
+/// These mutations never change the size of a data segment, so that the
+/// addresses that code uses to refer into it stay meaningful.
+#[derive(Clone, Copy)]
+pub enum StructuredDataMutator {
+    /// Truncate a NUL-terminated string by moving its terminator earlier.
+    TruncateString,
+    /// Swap two of the records of a segment that looks like an array of
+    /// fixed-size records.
+    PermuteRecords,
+    /// Change the characters of a NUL-terminated string.
+    FlipString,
+}
+
+/// The record sizes considered when treating a data segment as an array of
+/// records.
+const RECORD_SIZES: &[usize] = &[4, 8, 16];
+
+/// Find the NUL-terminated strings of printable ASCII characters in `data`,
+/// returning the range of each one, excluding its terminator.
+fn strings(data: &[u8]) -> Vec<Range<usize>> {
+    let mut strings = Vec::new();
+    let mut start = 0;
+    for (i, byte) in data.iter().enumerate() {
+        if *byte == 0 {
+            if i > start && data[start..i].iter().all(|b| (0x20..=0x7e).contains(b)) {
+                strings.push(start..i);
+            }
+            start = i + 1;
+        }
+    }
+    strings
+}
+
+/// Find the ways to see `data` as an array of at least two records, returning
+/// the record sizes for which some two records differ.
+fn record_sizes(data: &[u8]) -> Vec<usize> {
+    RECORD_SIZES
+        .iter()
+        .copied()
+        .filter(|size| data.len().is_multiple_of(*size) && data.len() / size >= 2)
+        .filter(|size| {
+            let mut records = data.chunks(*size);
+            let first = records.next().unwrap();
+            records.any(|record| record != first)
+        })
+        .collect()
+}
+
+impl StructuredDataMutator {
+    /// Can this mutation be applied to the given data segment contents?
+    fn applies_to(&self, data: &[u8]) -> bool {
+        match self {
+            Self::TruncateString | Self::FlipString => !strings(data).is_empty(),
+            Self::PermuteRecords => !record_sizes(data).is_empty(),
+        }
+    }
+
+    fn apply(&self, rng: &mut SmallRng, data: &mut [u8]) {
+        match self {
+            Self::TruncateString => {
+                let string = strings(data).choose(rng).unwrap().clone();
+                let cut = rng.gen_range(string.clone());
+                data[cut] = 0;
+            }
+            Self::FlipString => {
+                let string = strings(data).choose(rng).unwrap().clone();
+                let original = data[string.clone()].to_vec();
+                let chars = &mut data[string];
+                match rng.gen_range(0..3) {
+                    0 => chars.reverse(),
+                    1 => chars.iter_mut().for_each(|c| {
+                        if c.is_ascii_lowercase() {
+                            c.make_ascii_uppercase()
+                        } else {
+                            c.make_ascii_lowercase()
+                        }
+                    }),
+                    _ => {}
                 }
-                DataKind::Passive => DataSegmentMode::Passive,
-            };
-            // If this is the correct data segment apply the mutation,
-            // otherwise preserve the data.
-            let mut data = data.data.to_vec();
-            if i == data_to_modify {
-                config.raw_mutate(&mut data, self.max_data_size)?;
+                // Make sure that the string actually changes by replacing one
+                // of its characters with another printable one.
+                if chars == &original[..] {
+                    let i = rng.gen_range(0..chars.len());
+                    let c = rng.gen_range(0x20..0x7e);
+                    chars[i] = if c >= chars[i] { c + 1 } else { c };
+                }
+            }
+            Self::PermuteRecords => {
+                let size = *record_sizes(data).choose(rng).unwrap();
+                let count = data.len() / size;
+                let i = rng.gen_range(0..count);
+                // Not all records are the same, so there is always some record
+                // that differs from the chosen one.
+                let differing = (0..count)
+                    .filter(|j| data[j * size..][..size] != data[i * size..][..size])
+                    .collect::<Vec<_>>();
+                let j = *differing.choose(rng).unwrap();
+                let (i, j) = (i.min(j), i.max(j));
+                let (head, tail) = data.split_at_mut(j * size);
+                head[i * size..][..size].swap_with_slice(&mut tail[..size]);
             }
-            new_section.segment(DataSegment { mode, data });
         }
+    }
+}
 
-        Ok(Box::new(std::iter::once(Ok(config
-            .info()
-            .replace_section(
-                config.info().data.unwrap(),
-                &new_section,
-            )))))
+impl Mutator for StructuredDataMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let mut candidates = Vec::new();
+        let reader = DataSectionReader::new(config.info().get_data_section().data, 0)?;
+        for (i, data) in reader.into_iter().enumerate() {
+            if self.applies_to(data?.data) {
+                candidates.push(i as u32);
+            }
+        }
+        let data_to_modify = match candidates.choose(config.rng()) {
+            Some(i) => *i,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+
+        let module = replace_segment(config, data_to_modify, |config, data| {
+            self.apply(config.rng(), data);
+            Ok(())
+        })?;
+        Ok(Box::new(std::iter::once(Ok(module))))
     }
 
     fn can_mutate<'a>(&self, config: &'a WasmMutate) -> bool {
-        // Modifying a data segment doesn't preserve the semantics of the
-        // original module and also only works if there's actually some data.
-        !config.preserve_semantics && config.info().num_data() > 0
+        // These mutations neither preserve semantics nor reduce the size of
+        // the module.
+        !config.preserve_semantics && !config.reduce && config.info().num_data() > 0
+    }
+}
+
+/// Rebuild the data section, replacing the contents of the `data_to_modify`th
+/// data segment with the result of `mutate`.
+fn replace_segment(
+    config: &mut WasmMutate,
+    data_to_modify: u32,
+    mutate: impl FnOnce(&mut WasmMutate, &mut Vec<u8>) -> Result<()>,
+) -> Result<Module> {
+    let mut new_section = DataSection::new();
+    let mut reader = DataSectionReader::new(config.info().get_data_section().data, 0)?;
+    let mut mutate = Some(mutate);
+
+    // Iterate over all data segments in the old data section and re-add
+    // them to the `new_section` one-by-one.
+    for i in 0..reader.get_count() {
+        let data = reader.read()?;
+        let offset;
+        // Preserve the mode of the data segment
+        let mode = match &data.kind {
+            DataKind::Active {
+                memory_index,
+                offset_expr,
+            } => {
+                offset = DefaultTranslator.translate_const_expr(
+                    offset_expr,
+                    &wasmparser::ValType::I32,
+                    ConstExprKind::DataOffset,
+                )?;
+                DataSegmentMode::Active {
+                    memory_index: *memory_index,
+                    offset: &offset,
+                }
+            }
+            DataKind::Passive => DataSegmentMode::Passive,
+        };
+        // If this is the correct data segment apply the mutation,
+        // otherwise preserve the data.
+        let mut data = data.data.to_vec();
+        if i == data_to_modify {
+            (mutate.take().unwrap())(config, &mut data)?;
+        }
+        new_section.segment(DataSegment { mode, data });
     }
+
+    Ok(config
+        .info()
+        .replace_section(config.info().data.unwrap(), &new_section))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::ModifyDataMutator;
+    use super::{ModifyDataMutator, StructuredDataMutator};
     use crate::WasmMutate;
     use std::sync::Arc;
 
@@ -90,4 +244,36 @@ mod tests {
             r#"(module (data "y"))"#,
         );
     }
+
+    #[test]
+    fn test_truncate_string() {
+        crate::mutators::match_mutation(
+            r#"(module (memory 1) (data (i32.const 0) "\00ab\00"))"#,
+            StructuredDataMutator::TruncateString,
+            r#"(module (memory 1) (data (i32.const 0) "\00a\00\00"))"#,
+        );
+    }
+
+    #[test]
+    fn test_flip_string() {
+        crate::mutators::match_mutation(
+            r#"(module (memory 1) (data (i32.const 0) "abc\00xyz"))"#,
+            StructuredDataMutator::FlipString,
+            r#"(module (memory 1) (data (i32.const 0) "cba\00xyz"))"#,
+        );
+        crate::mutators::match_mutation(
+            r#"(module (memory 1) (data (i32.const 0) "abc\00xyz"))"#,
+            StructuredDataMutator::FlipString,
+            r#"(module (memory 1) (data (i32.const 0) "ABC\00xyz"))"#,
+        );
+    }
+
+    #[test]
+    fn test_permute_records() {
+        crate::mutators::match_mutation(
+            r#"(module (data "aaaabbbbaaaa"))"#,
+            StructuredDataMutator::PermuteRecords,
+            r#"(module (data "bbbbaaaaaaaa"))"#,
+        );
+    }
 }
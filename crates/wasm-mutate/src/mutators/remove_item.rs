@@ -11,13 +11,13 @@ use crate::mutators::{translate, Item, Mutator, Translator};
 use crate::Error;
 use crate::{ModuleInfo, Result, WasmMutate};
 use rand::Rng;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use wasm_encoder::*;
 use wasmparser::{
-    BinaryReader, CodeSectionReader, DataSectionReader, ElementSectionReader, ExportSectionReader,
-    ExternalKind, FunctionSectionReader, GlobalSectionReader, ImportSectionReader,
-    MemorySectionReader, Operator, SectionReader, TableSectionReader, TagSectionReader,
-    TypeSectionReader,
+    BinaryReader, CodeSectionReader, DataSectionReader, ElementItem, ElementSectionReader,
+    ExportSectionReader, ExternalKind, FunctionSectionReader, GlobalSectionReader,
+    ImportSectionReader, MemorySectionReader, Operator, OperatorsReader, Parser, Payload,
+    SectionReader, TableSectionReader, TagSectionReader, TypeSectionReader,
 };
 
 /// Mutator that removes a random item in a wasm module (function, global,
@@ -27,7 +27,9 @@ pub struct RemoveItemMutator(pub Item);
 
 impl Mutator for RemoveItemMutator {
     fn can_mutate(&self, config: &WasmMutate) -> bool {
-        self.0.can_mutate(config)
+        // Removing an item renumbers the ones after it, which would leave
+        // debug information referring to the wrong items.
+        !config.preserve_debug_info && self.0.can_mutate(config)
     }
 
     fn mutate<'a>(
@@ -42,7 +44,7 @@ impl Mutator for RemoveItemMutator {
 
         let result = RemoveItem {
             item: self.0,
-            idx,
+            removed: [idx].into_iter().collect(),
             referenced_functions: HashSet::new(),
             function_reference_action: Funcref::Save,
         }
@@ -60,6 +62,109 @@ impl Mutator for RemoveItemMutator {
     }
 }
 
+/// Mutator that removes all of the functions that can't be reached from the
+/// module's exports, element segments, globals, or start function.
+///
+/// This only shrinks modules, and does so much faster than removing one
+/// function at a time, so it's only used when reducing.
+#[derive(Copy, Clone)]
+pub struct RemoveDeadFunctionsMutator;
+
+impl Mutator for RemoveDeadFunctionsMutator {
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        config.reduce && !config.preserve_debug_info && config.info().num_functions() > 0
+    }
+
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<wasm_encoder::Module>> + 'a>>
+    where
+        Self: Copy,
+    {
+        let dead = dead_functions(config.info())?;
+        if dead.is_empty() {
+            return Err(Error::no_mutations_applicable());
+        }
+        log::trace!("attempting to remove dead functions {:?}", dead);
+
+        let module = RemoveItem {
+            item: Item::Function,
+            removed: dead,
+            referenced_functions: HashSet::new(),
+            function_reference_action: Funcref::Save,
+        }
+        .remove(config.info())?;
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+}
+
+/// Find the functions that aren't transitively referenced by any of the
+/// module's exports, element segments, globals, or its start function.
+fn dead_functions(info: &ModuleInfo) -> Result<BTreeSet<u32>> {
+    fn referenced(ops: OperatorsReader, worklist: &mut Vec<u32>) -> Result<()> {
+        for op in ops {
+            match op? {
+                Operator::Call { function_index }
+                | Operator::ReturnCall { function_index }
+                | Operator::RefFunc { function_index } => worklist.push(function_index),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    let mut worklist = Vec::new();
+    let mut bodies = Vec::new();
+    for payload in Parser::new(0).parse_all(info.input_wasm) {
+        match payload? {
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export?;
+                    if export.kind == ExternalKind::Func {
+                        worklist.push(export.index);
+                    }
+                }
+            }
+            Payload::StartSection { func, .. } => worklist.push(func),
+            Payload::ElementSection(reader) => {
+                for element in reader {
+                    for item in element?.items.get_items_reader()? {
+                        match item? {
+                            ElementItem::Func(idx) => worklist.push(idx),
+                            ElementItem::Expr(expr) => {
+                                referenced(expr.get_operators_reader(), &mut worklist)?
+                            }
+                        }
+                    }
+                }
+            }
+            Payload::GlobalSection(reader) => {
+                for global in reader {
+                    referenced(global?.init_expr.get_operators_reader(), &mut worklist)?;
+                }
+            }
+            Payload::CodeSectionEntry(body) => bodies.push(body),
+            _ => {}
+        }
+    }
+
+    let mut live = HashSet::new();
+    while let Some(idx) = worklist.pop() {
+        if !live.insert(idx) {
+            continue;
+        }
+        if let Some(defined) = idx.checked_sub(info.num_imported_functions()) {
+            if let Some(body) = bodies.get(defined as usize) {
+                referenced(body.get_operators_reader()?, &mut worklist)?;
+            }
+        }
+    }
+    Ok((0..info.num_functions())
+        .filter(|idx| !live.contains(idx))
+        .collect())
+}
+
 impl Item {
     fn can_mutate(&self, config: &WasmMutate) -> bool {
         // This heuristic is a bit of a lie in that just because an item is
@@ -103,7 +208,8 @@ impl Item {
 
 struct RemoveItem {
     item: Item,
-    idx: u32,
+    /// The indices of the items being removed, in the `item` index space.
+    removed: BTreeSet<u32>,
     function_reference_action: Funcref,
     referenced_functions: HashSet<u32>,
 }
@@ -157,35 +263,35 @@ impl RemoveItem {
                         let item = item?;
                         match &item.ty {
                             wasmparser::TypeRef::Func(ty) => {
-                                if self.item != Item::Function || self.idx != function {
+                                if !self.removes(Item::Function, function) {
                                     let ty = self.remap(Item::Type, *ty)?;
                                     result.import(item.module, item.name, EntityType::Function(ty));
                                 }
                                 function += 1;
                             }
                             wasmparser::TypeRef::Table(ty) => {
-                                if self.item != Item::Table || self.idx != table {
+                                if !self.removes(Item::Table, table) {
                                     let ty = self.translate_table_type(ty)?;
                                     result.import(item.module, item.name, ty);
                                 }
                                 table += 1;
                             }
                             wasmparser::TypeRef::Memory(ty) => {
-                                if self.item != Item::Memory || self.idx != memory {
+                                if !self.removes(Item::Memory, memory) {
                                     let ty = self.translate_memory_type(ty)?;
                                     result.import(item.module, item.name, ty);
                                 }
                                 memory += 1;
                             }
                             wasmparser::TypeRef::Global(ty) => {
-                                if self.item != Item::Global || self.idx != global {
+                                if !self.removes(Item::Global, global) {
                                     let ty = self.translate_global_type(ty)?;
                                     result.import(item.module, item.name, ty);
                                 }
                                 global += 1;
                             }
                             wasmparser::TypeRef::Tag(ty) => {
-                                if self.item != Item::Tag || self.idx != tag {
+                                if !self.removes(Item::Tag, tag) {
                                     let ty = self.translate_tag_type(ty)?;
                                     result.import(item.module, item.name, ty);
                                 }
@@ -320,7 +426,7 @@ impl RemoveItem {
                     // we're removing a data item, otherwise it's preserved
                     // as-is.
                     let count = if self.item == Item::Data {
-                        count - 1
+                        count - self.removed.len() as u32
                     } else {
                         count
                     };
@@ -347,6 +453,11 @@ impl RemoveItem {
         Ok(module)
     }
 
+    /// Is the `idx`th item in the `item` index space being removed?
+    fn removes(&self, item: Item, idx: u32) -> bool {
+        item == self.item && self.removed.contains(&idx)
+    }
+
     /// This is a helper function to filter out the items of the `section`
     /// provided.
     ///
@@ -375,7 +486,7 @@ impl RemoveItem {
         let mut index = offset;
         while !section.eof() {
             let item = section.read()?;
-            if index != self.idx || section_item != self.item {
+            if !self.removes(section_item, index) {
                 encode(self, item, &mut result)?;
             }
             index += 1;
@@ -393,20 +504,18 @@ impl Translator for RemoveItem {
     /// This is "the point" of this type. This function remaps an `idx`
     /// provided, in the `item` index space, to a new index.
     ///
-    /// This `RemoveItem` structure will only remove at most one item which
-    /// means that the index given is in one of four cases:
+    /// This `RemoveItem` structure only removes items from one index space
+    /// which means that the index given is in one of three cases:
     ///
-    /// * If the `item` doesn't match the index space of the item we're
+    /// * If the `item` doesn't match the index space of the items we're
     ///   removing, then `idx` is guaranteed to not need modification.
-    /// * Otherwise if `idx` is less than the index being removed, it's entirely
-    ///   unmodified since we're only modifying later items.
-    /// * Otherwise if `idx` matches the index that's being removed then this
+    /// * Otherwise if `idx` matches an index that's being removed then this
     ///   means that the item was actually uses. In this situation we simply say
     ///   that the mutation is not applicable. This will bail out this entire
-    ///   attempt to remove the `idx`th item and loops like `wasm-shrink` will
-    ///   try something else.
-    /// * Finally our index is larger than the one being removed which means we
-    ///   now decrement it by one to account for the removed item.
+    ///   attempt to remove the items and loops like `wasm-shrink` will try
+    ///   something else.
+    /// * Finally our index is decremented by the number of removed items
+    ///   before it, if any.
     fn remap(&mut self, item: Item, idx: u32) -> Result<u32> {
         // If we're before the code section then all function references, no
         // matter where they are, are considered "referencing functions" so we
@@ -420,17 +529,14 @@ impl Translator for RemoveItem {
         if item != self.item {
             // Different kind of item, no change
             Ok(idx)
-        } else if idx < self.idx {
-            // A later item was removed, so this index doesn't change
-            Ok(idx)
-        } else if idx == self.idx {
+        } else if self.removed.contains(&idx) {
             // If we're removing a referenced item then that means that this
             // mutation fails.
             Err(Error::no_mutations_applicable())
         } else {
-            // Otherwise this item comes after the item being removed, so
-            // this item's index has decreased by one.
-            Ok(idx - 1)
+            // Otherwise this item's index has decreased by the number of items
+            // removed before it.
+            Ok(idx - self.removed.range(..idx).count() as u32)
         }
     }
 
@@ -460,7 +566,7 @@ impl Translator for RemoveItem {
 
 #[cfg(test)]
 mod tests {
-    use super::{Item, RemoveItemMutator};
+    use super::{Item, RemoveDeadFunctionsMutator, RemoveItemMutator};
 
     #[test]
     fn remove_type() {
@@ -717,6 +823,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn remove_dead_functions() {
+        crate::WasmMutate::default().reduce(true).match_mutation(
+            r#"(module
+                    (import "" "dead" (func))
+                    (import "" "live" (func))
+                    (func call 0)
+                    (func (export "a") call 1 call 5)
+                    (func)
+                    (func call 4)
+                    (func)
+                    (func)
+                    (table 1 funcref)
+                    (elem (i32.const 0) func 6)
+                    (global funcref (ref.func 7))
+            )"#,
+            RemoveDeadFunctionsMutator,
+            r#"(module
+                    (import "" "live" (func))
+                    (func (export "a") call 0 call 3)
+                    (func)
+                    (func call 2)
+                    (func)
+                    (func)
+                    (table 1 funcref)
+                    (elem (i32.const 0) func 4)
+                    (global funcref (ref.func 5))
+            )"#,
+        );
+    }
+
     #[test]
     fn remove_empty_element() {
         crate::mutators::match_mutation(
@@ -37,10 +37,26 @@ fn is_empty_section(section: &wasm_encoder::RawSection) -> bool {
     .unwrap_or(false)
 }
 
+/// Custom sections holding debug information aren't removed when it is being
+/// preserved.
+fn is_removable_custom_section(config: &WasmMutate, section: &wasm_encoder::RawSection) -> bool {
+    section.id == SectionId::Custom as u8
+        && !(config.preserve_debug_info
+            && wasmparser::CustomSectionReader::new(section.data, 0)
+                .is_ok_and(|c| crate::debug_info::is_debug_section(c.name())))
+}
+
 impl Mutator for RemoveSection {
     fn can_mutate(&self, config: &WasmMutate) -> bool {
         match self {
-            &Self::Custom => config.info().has_custom_section(),
+            &Self::Custom => {
+                config.info().has_custom_section()
+                    && config
+                        .info()
+                        .raw_sections
+                        .iter()
+                        .any(|s| is_removable_custom_section(config, s))
+            }
             &Self::Empty => config
                 .info()
                 .raw_sections
@@ -63,7 +79,7 @@ impl Mutator for RemoveSection {
             .enumerate()
             .filter_map(|(i, s)| match self {
                 Self::Empty if is_empty_section(s) => Some(i),
-                Self::Custom if s.id == wasm_encoder::SectionId::Custom as u8 => Some(i),
+                Self::Custom if is_removable_custom_section(config, s) => Some(i),
                 _ => None,
             })
             .collect::<Vec<_>>();
@@ -0,0 +1,598 @@
+//! Mutators that target the `v128` operations of the SIMD proposals.
+//!
+//! Each of these picks one SIMD instruction in the code section and replaces
+//! it with a similar one, to exercise the SIMD code generation of engines.
+
+use super::translate::{self, DefaultTranslator, Translator};
+use super::Mutator;
+use crate::{Error, Result, WasmMutate};
+use rand::{seq::SliceRandom, Rng};
+use std::mem::discriminant;
+use wasm_encoder::{CodeSection, Instruction, Module};
+use wasmparser::{CodeSectionReader, FunctionBody, Operator};
+
+/// Replaces a single SIMD instruction with a similar one.
+#[derive(Clone, Copy)]
+pub enum SimdMutator {
+    /// Change the lane index of a lane access, e.g. an `extract_lane`.
+    LaneIndex,
+    /// Replace a relaxed SIMD instruction with its non-relaxed counterpart.
+    ///
+    /// The fused multiply-add instructions have no such counterpart and are
+    /// left alone.
+    Derelax,
+    /// Permute the lanes of an `i8x16.shuffle`.
+    Shuffle,
+    /// Replace an instruction with another one that has the same signature
+    /// and the same lane shapes, e.g. `f32x4.ceil` with `f32x4.sqrt`.
+    SameWidth,
+}
+
+/// Groups of interchangeable instructions.
+///
+/// The instructions of a group have the same signature, and the lanes of
+/// their operands and results have the same shapes, e.g. all of
+/// `i16x8.extend_low_i8x16_s` and friends take `i8x16` lanes and produce
+/// `i16x8` lanes.
+const SAME_WIDTH_GROUPS: &[&[Instruction<'static>]] = {
+    use Instruction::*;
+    &[
+        // [v128 v128] -> [v128]
+        &[V128And, V128AndNot, V128Or, V128Xor],
+        &[
+            I8x16Eq, I8x16Ne, I8x16LtS, I8x16LtU, I8x16GtS, I8x16GtU, I8x16LeS, I8x16LeU, I8x16GeS,
+            I8x16GeU,
+        ],
+        &[
+            I8x16Swizzle,
+            I8x16Add,
+            I8x16AddSatS,
+            I8x16AddSatU,
+            I8x16Sub,
+            I8x16SubSatS,
+            I8x16SubSatU,
+            I8x16MinS,
+            I8x16MinU,
+            I8x16MaxS,
+            I8x16MaxU,
+            I8x16RoundingAverageU,
+        ],
+        &[I8x16NarrowI16x8S, I8x16NarrowI16x8U],
+        &[
+            I16x8Eq, I16x8Ne, I16x8LtS, I16x8LtU, I16x8GtS, I16x8GtU, I16x8LeS, I16x8LeU, I16x8GeS,
+            I16x8GeU,
+        ],
+        &[
+            I16x8Q15MulrSatS,
+            I16x8Add,
+            I16x8AddSatS,
+            I16x8AddSatU,
+            I16x8Sub,
+            I16x8SubSatS,
+            I16x8SubSatU,
+            I16x8Mul,
+            I16x8MinS,
+            I16x8MinU,
+            I16x8MaxS,
+            I16x8MaxU,
+            I16x8RoundingAverageU,
+        ],
+        &[I16x8NarrowI32x4S, I16x8NarrowI32x4U],
+        &[
+            I16x8ExtMulLowI8x16S,
+            I16x8ExtMulHighI8x16S,
+            I16x8ExtMulLowI8x16U,
+            I16x8ExtMulHighI8x16U,
+        ],
+        &[
+            I32x4Eq, I32x4Ne, I32x4LtS, I32x4LtU, I32x4GtS, I32x4GtU, I32x4LeS, I32x4LeU, I32x4GeS,
+            I32x4GeU,
+        ],
+        &[
+            I32x4Add, I32x4Sub, I32x4Mul, I32x4MinS, I32x4MinU, I32x4MaxS, I32x4MaxU,
+        ],
+        &[
+            I32x4DotI16x8S,
+            I32x4ExtMulLowI16x8S,
+            I32x4ExtMulHighI16x8S,
+            I32x4ExtMulLowI16x8U,
+            I32x4ExtMulHighI16x8U,
+        ],
+        &[F32x4Eq, F32x4Ne, F32x4Lt, F32x4Gt, F32x4Le, F32x4Ge],
+        &[
+            F32x4Add, F32x4Sub, F32x4Mul, F32x4Div, F32x4Min, F32x4Max, F32x4PMin, F32x4PMax,
+        ],
+        &[I64x2Eq, I64x2Ne, I64x2LtS, I64x2GtS, I64x2LeS, I64x2GeS],
+        &[I64x2Add, I64x2Sub, I64x2Mul],
+        &[
+            I64x2ExtMulLowI32x4S,
+            I64x2ExtMulHighI32x4S,
+            I64x2ExtMulLowI32x4U,
+            I64x2ExtMulHighI32x4U,
+        ],
+        &[F64x2Eq, F64x2Ne, F64x2Lt, F64x2Gt, F64x2Le, F64x2Ge],
+        &[
+            F64x2Add, F64x2Sub, F64x2Mul, F64x2Div, F64x2Min, F64x2Max, F64x2PMin, F64x2PMax,
+        ],
+        // [v128] -> [v128]
+        &[I8x16Abs, I8x16Neg, I8x16Popcnt],
+        &[I16x8Abs, I16x8Neg],
+        &[
+            I16x8ExtAddPairwiseI8x16S,
+            I16x8ExtAddPairwiseI8x16U,
+            I16x8ExtendLowI8x16S,
+            I16x8ExtendHighI8x16S,
+            I16x8ExtendLowI8x16U,
+            I16x8ExtendHighI8x16U,
+        ],
+        &[I32x4Abs, I32x4Neg],
+        &[
+            I32x4ExtAddPairwiseI16x8S,
+            I32x4ExtAddPairwiseI16x8U,
+            I32x4ExtendLowI16x8S,
+            I32x4ExtendHighI16x8S,
+            I32x4ExtendLowI16x8U,
+            I32x4ExtendHighI16x8U,
+        ],
+        &[I32x4TruncSatF32x4S, I32x4TruncSatF32x4U],
+        &[I32x4TruncSatF64x2SZero, I32x4TruncSatF64x2UZero],
+        &[
+            F32x4Ceil,
+            F32x4Floor,
+            F32x4Trunc,
+            F32x4Nearest,
+            F32x4Abs,
+            F32x4Neg,
+            F32x4Sqrt,
+        ],
+        &[F32x4ConvertI32x4S, F32x4ConvertI32x4U],
+        &[I64x2Abs, I64x2Neg],
+        &[
+            I64x2ExtendLowI32x4S,
+            I64x2ExtendHighI32x4S,
+            I64x2ExtendLowI32x4U,
+            I64x2ExtendHighI32x4U,
+        ],
+        &[
+            F64x2Ceil,
+            F64x2Floor,
+            F64x2Trunc,
+            F64x2Nearest,
+            F64x2Abs,
+            F64x2Neg,
+            F64x2Sqrt,
+        ],
+        &[F64x2ConvertLowI32x4S, F64x2ConvertLowI32x4U],
+        // [v128 i32] -> [v128]
+        &[I8x16Shl, I8x16ShrS, I8x16ShrU],
+        &[I16x8Shl, I16x8ShrS, I16x8ShrU],
+        &[I32x4Shl, I32x4ShrS, I32x4ShrU],
+        &[I64x2Shl, I64x2ShrS, I64x2ShrU],
+        // [v128] -> [i32]
+        &[I8x16AllTrue, I8x16Bitmask],
+        &[I16x8AllTrue, I16x8Bitmask],
+        &[I32x4AllTrue, I32x4Bitmask],
+        &[I64x2AllTrue, I64x2Bitmask],
+    ]
+};
+
+impl SimdMutator {
+    fn same_width_group(inst: &Instruction) -> Option<&'static [Instruction<'static>]> {
+        SAME_WIDTH_GROUPS
+            .iter()
+            .copied()
+            .find(|group| group.iter().any(|i| discriminant(i) == discriminant(inst)))
+    }
+
+    /// The number of lanes that the lane index of `inst` selects from, if it
+    /// has one.
+    fn lane_count(inst: &Instruction) -> Option<u8> {
+        use Instruction::*;
+        match inst {
+            I8x16ExtractLaneS { .. }
+            | I8x16ExtractLaneU { .. }
+            | I8x16ReplaceLane { .. }
+            | V128Load8Lane { .. }
+            | V128Store8Lane { .. } => Some(16),
+            I16x8ExtractLaneS { .. }
+            | I16x8ExtractLaneU { .. }
+            | I16x8ReplaceLane { .. }
+            | V128Load16Lane { .. }
+            | V128Store16Lane { .. } => Some(8),
+            I32x4ExtractLane { .. }
+            | I32x4ReplaceLane { .. }
+            | F32x4ExtractLane { .. }
+            | F32x4ReplaceLane { .. }
+            | V128Load32Lane { .. }
+            | V128Store32Lane { .. } => Some(4),
+            I64x2ExtractLane { .. }
+            | I64x2ReplaceLane { .. }
+            | F64x2ExtractLane { .. }
+            | F64x2ReplaceLane { .. }
+            | V128Load64Lane { .. }
+            | V128Store64Lane { .. } => Some(2),
+            _ => None,
+        }
+    }
+
+    fn derelaxed(inst: &Instruction) -> Option<Instruction<'static>> {
+        use Instruction::*;
+        Some(match inst {
+            I8x16RelaxedSwizzle => I8x16Swizzle,
+            I32x4RelaxedTruncSatF32x4S => I32x4TruncSatF32x4S,
+            I32x4RelaxedTruncSatF32x4U => I32x4TruncSatF32x4U,
+            I32x4RelaxedTruncSatF64x2SZero => I32x4TruncSatF64x2SZero,
+            I32x4RelaxedTruncSatF64x2UZero => I32x4TruncSatF64x2UZero,
+            I8x16LaneSelect | I16x8LaneSelect | I32x4LaneSelect | I64x2LaneSelect => V128Bitselect,
+            F32x4RelaxedMin => F32x4Min,
+            F32x4RelaxedMax => F32x4Max,
+            F64x2RelaxedMin => F64x2Min,
+            F64x2RelaxedMax => F64x2Max,
+            _ => return None,
+        })
+    }
+
+    fn applies_to(&self, inst: &Instruction) -> bool {
+        match self {
+            SimdMutator::LaneIndex => Self::lane_count(inst).is_some(),
+            SimdMutator::Derelax => Self::derelaxed(inst).is_some(),
+            SimdMutator::Shuffle => matches!(inst, Instruction::I8x16Shuffle { .. }),
+            SimdMutator::SameWidth => Self::same_width_group(inst).is_some(),
+        }
+    }
+
+    /// Produce the replacement for `inst`, to which this mutator applies.
+    fn replace(&self, inst: &Instruction<'static>, rng: &mut impl Rng) -> Instruction<'static> {
+        match self {
+            SimdMutator::LaneIndex => {
+                let count = Self::lane_count(inst).unwrap();
+                let mut inst = inst.clone();
+                match &mut inst {
+                    Instruction::I8x16ExtractLaneS { lane }
+                    | Instruction::I8x16ExtractLaneU { lane }
+                    | Instruction::I8x16ReplaceLane { lane }
+                    | Instruction::I16x8ExtractLaneS { lane }
+                    | Instruction::I16x8ExtractLaneU { lane }
+                    | Instruction::I16x8ReplaceLane { lane }
+                    | Instruction::I32x4ExtractLane { lane }
+                    | Instruction::I32x4ReplaceLane { lane }
+                    | Instruction::I64x2ExtractLane { lane }
+                    | Instruction::I64x2ReplaceLane { lane }
+                    | Instruction::F32x4ExtractLane { lane }
+                    | Instruction::F32x4ReplaceLane { lane }
+                    | Instruction::F64x2ExtractLane { lane }
+                    | Instruction::F64x2ReplaceLane { lane }
+                    | Instruction::V128Load8Lane { lane, .. }
+                    | Instruction::V128Load16Lane { lane, .. }
+                    | Instruction::V128Load32Lane { lane, .. }
+                    | Instruction::V128Load64Lane { lane, .. }
+                    | Instruction::V128Store8Lane { lane, .. }
+                    | Instruction::V128Store16Lane { lane, .. }
+                    | Instruction::V128Store32Lane { lane, .. }
+                    | Instruction::V128Store64Lane { lane, .. } => {
+                        *lane = (*lane + rng.gen_range(1..count)) % count;
+                    }
+                    _ => unreachable!(),
+                }
+                inst
+            }
+            SimdMutator::Derelax => Self::derelaxed(inst).unwrap(),
+            SimdMutator::Shuffle => {
+                let mut lanes = match inst {
+                    Instruction::I8x16Shuffle { lanes } => *lanes,
+                    _ => unreachable!(),
+                };
+                let original = lanes;
+                lanes.shuffle(rng);
+                // A shuffle that selects the same lane everywhere can't be
+                // permuted, so select another lane in one place instead.
+                if lanes == original {
+                    let i = rng.gen_range(0..lanes.len());
+                    lanes[i] = (lanes[i] + rng.gen_range(1..32)) % 32;
+                }
+                Instruction::I8x16Shuffle { lanes }
+            }
+            SimdMutator::SameWidth => {
+                let group = Self::same_width_group(inst).unwrap();
+                let others = group
+                    .iter()
+                    .filter(|i| discriminant(*i) != discriminant(inst))
+                    .collect::<Vec<_>>();
+                (*others.choose(rng).unwrap()).clone()
+            }
+        }
+    }
+}
+
+impl Mutator for SimdMutator {
+    fn mutate<'a>(
+        self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let code_section = config.info().get_code_section();
+        let bodies = CodeSectionReader::new(code_section.data, 0)?
+            .into_iter()
+            .collect::<wasmparser::Result<Vec<_>>>()?;
+
+        // Find all of the instructions in functions that can be re-encoded
+        // that this mutator applies to.
+        let mut candidates = Vec::new();
+        for (i, body) in bodies.iter().enumerate() {
+            config.consume_fuel(1)?;
+            let mut matches = Vec::new();
+            let translated =
+                operators(body)?.iter().enumerate().all(|(j, op)| {
+                    match translate::op(&mut DefaultTranslator, op) {
+                        Ok(inst) => {
+                            if self.applies_to(&inst) {
+                                matches.push((i, j));
+                            }
+                            true
+                        }
+                        Err(_) => false,
+                    }
+                });
+            if translated {
+                candidates.extend(matches);
+            }
+        }
+        let (func, index) = match candidates.choose(config.rng()) {
+            Some(candidate) => *candidate,
+            None => return Err(Error::no_mutations_applicable()),
+        };
+
+        let ops = operators(&bodies[func])?;
+        let inst = translate::op(&mut DefaultTranslator, &ops[index])?;
+        let replacement = self.replace(&inst, config.rng());
+        log::trace!(
+            "Replacing {:?} with {:?} in function {}",
+            inst,
+            replacement,
+            func
+        );
+
+        let mut codes = CodeSection::new();
+        for (i, body) in bodies.into_iter().enumerate() {
+            if i == func {
+                let mut translator = ReplaceOperator {
+                    index,
+                    seen: 0,
+                    replacement: Some(replacement.clone()),
+                };
+                translator.translate_code(body, &mut codes)?;
+            } else {
+                codes.raw(&code_section.data[body.range().start..body.range().end]);
+            }
+        }
+
+        Ok(Box::new(std::iter::once(Ok(config
+            .info()
+            .replace_section(config.info().code.unwrap(), &codes)))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        // Only removing the relaxed prefix of an opcode makes a module
+        // smaller.
+        !config.preserve_semantics
+            && (!config.reduce || matches!(self, SimdMutator::Derelax))
+            && config.info().has_nonempty_code()
+    }
+}
+
+fn operators<'a>(body: &FunctionBody<'a>) -> Result<Vec<Operator<'a>>> {
+    let mut reader = body.get_operators_reader()?;
+    reader.allow_memarg64(true);
+    Ok(reader.into_iter().collect::<wasmparser::Result<Vec<_>>>()?)
+}
+
+/// Translates a function body, replacing the operator at `index`.
+struct ReplaceOperator {
+    index: usize,
+    seen: usize,
+    replacement: Option<Instruction<'static>>,
+}
+
+impl Translator for ReplaceOperator {
+    fn as_obj(&mut self) -> &mut dyn Translator {
+        self
+    }
+
+    fn translate_op(&mut self, op: &Operator<'_>) -> Result<Instruction<'static>> {
+        let index = self.seen;
+        self.seen += 1;
+        match self.replacement.take() {
+            Some(replacement) if index == self.index => Ok(replacement),
+            replacement => {
+                self.replacement = replacement;
+                translate::op(self.as_obj(), op)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mutator, SimdMutator, SAME_WIDTH_GROUPS};
+    use crate::{ErrorKind, WasmMutate};
+    use std::mem::discriminant;
+    use wasm_encoder::Instruction;
+
+    #[test]
+    fn test_lane_index() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (param v128) (result i64)
+                    local.get 0
+                    i64x2.extract_lane 0
+                )
+            )
+            "#,
+            SimdMutator::LaneIndex,
+            r#"
+            (module
+                (func (param v128) (result i64)
+                    local.get 0
+                    i64x2.extract_lane 1
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_derelax() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (param v128 v128 v128) (result v128)
+                    local.get 0
+                    local.get 1
+                    local.get 2
+                    i32x4.laneselect
+                )
+            )
+            "#,
+            SimdMutator::Derelax,
+            r#"
+            (module
+                (func (param v128 v128 v128) (result v128)
+                    local.get 0
+                    local.get 1
+                    local.get 2
+                    v128.bitselect
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_shuffle() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (param v128 v128) (result v128)
+                    local.get 0
+                    local.get 1
+                    i8x16.shuffle 1 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0
+                )
+            )
+            "#,
+            SimdMutator::Shuffle,
+            r#"
+            (module
+                (func (param v128 v128) (result v128)
+                    local.get 0
+                    local.get 1
+                    i8x16.shuffle 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 1
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_same_width() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (param v128 i32) (result v128)
+                    local.get 0
+                    local.get 1
+                    i32x4.shl
+                )
+            )
+            "#,
+            SimdMutator::SameWidth,
+            r#"
+            (module
+                (func (param v128 i32) (result v128)
+                    local.get 0
+                    local.get 1
+                    i32x4.shr_u
+                )
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn test_same_width_groups() {
+        let mut seen: Vec<&Instruction> = Vec::new();
+        for group in SAME_WIDTH_GROUPS {
+            assert!(group.len() > 1, "{:?} has no alternatives", group);
+            for inst in group.iter() {
+                assert!(
+                    !seen.iter().any(|i| discriminant(*i) == discriminant(inst)),
+                    "{:?} is in more than one group",
+                    inst
+                );
+                seen.push(inst);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mutations_are_valid() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory 1)
+                (func (export "binary") (param v128 v128) (result v128)
+                    (i8x16.narrow_i16x8_s
+                        (i16x8.extmul_low_i8x16_u
+                            (i8x16.add_sat_s (local.get 0) (local.get 1))
+                            (i8x16.eq (local.get 0) (local.get 1)))
+                        (i32x4.dot_i16x8_s
+                            (f32x4.pmin
+                                (f32x4.lt (local.get 0) (local.get 1))
+                                (v128.and (local.get 0) (local.get 1)))
+                            (i64x2.extmul_high_i32x4_s
+                                (i64x2.ge_s (local.get 0) (local.get 1))
+                                (f64x2.div (local.get 0) (local.get 1)))))
+                )
+                (func (export "unary") (param v128) (result v128)
+                    (f64x2.convert_low_i32x4_u
+                        (i32x4.trunc_sat_f64x2_s_zero
+                            (f32x4.convert_i32x4_s
+                                (i32x4.trunc_sat_f32x4_u
+                                    (f32x4.nearest
+                                        (i64x2.extend_high_i32x4_s
+                                            (i32x4.extadd_pairwise_i16x8_u
+                                                (i16x8.extend_low_i8x16_s
+                                                    (i8x16.popcnt
+                                                        (f64x2.sqrt (local.get 0)))))))))))
+                )
+                (func (export "lanes") (param v128 i32) (result i32)
+                    (i8x16.shuffle 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+                        (i64x2.shr_u (local.get 0) (local.get 1))
+                        (v128.load32_lane 2 (i32.const 0) (local.get 0)))
+                    i16x8.bitmask
+                    (i32x4.extract_lane 3 (local.get 0))
+                    i32.add
+                )
+            )
+            "#,
+        )
+        .unwrap();
+
+        for mutator in [
+            SimdMutator::LaneIndex,
+            SimdMutator::Shuffle,
+            SimdMutator::SameWidth,
+        ] {
+            for seed in 0..256 {
+                let mut config = WasmMutate::default();
+                config.seed(seed);
+                config.setup(&wasm).unwrap();
+                let mutation = match mutator.mutate(&mut config) {
+                    Ok(mut mutations) => mutations.next().unwrap().unwrap(),
+                    Err(e) if matches!(e.kind(), ErrorKind::NoMutationsApplicable) => continue,
+                    Err(e) => panic!("mutation error: {}", e),
+                };
+                crate::validate(&mutation.finish());
+            }
+        }
+    }
+}
@@ -137,6 +137,7 @@ pub fn table_type(
 ) -> Result<wasm_encoder::TableType> {
     Ok(wasm_encoder::TableType {
         element_type: t.translate_ty(&ty.element_type)?,
+        table64: ty.table64,
         minimum: ty.initial,
         maximum: ty.maximum,
     })
@@ -316,6 +317,13 @@ pub fn op(t: &mut dyn Translator, op: &Operator<'_>) -> Result<Instruction<'stat
             ty: t.remap(Item::Type, *index)?,
             table: t.remap(Item::Table, *table_index)?,
         },
+        O::ReturnCall { function_index } => {
+            I::ReturnCall(t.remap(Item::Function, *function_index)?)
+        }
+        O::ReturnCallIndirect { index, table_index } => I::ReturnCallIndirect {
+            ty: t.remap(Item::Type, *index)?,
+            table: t.remap(Item::Table, *table_index)?,
+        },
         O::Delegate { relative_depth } => I::Delegate(*relative_depth),
         O::CatchAll => I::CatchAll,
         O::Drop => I::Drop,
@@ -923,8 +931,6 @@ pub fn op(t: &mut dyn Translator, op: &Operator<'_>) -> Result<Instruction<'stat
         | O::I64AtomicRmw8CmpxchgU { .. }
         | O::I64AtomicRmw16CmpxchgU { .. }
         | O::I64AtomicRmw32CmpxchgU { .. }
-        | O::ReturnCall { .. }
-        | O::ReturnCallIndirect { .. }
         | O::AtomicFence { .. } => return Err(Error::no_mutations_applicable()),
     })
 }
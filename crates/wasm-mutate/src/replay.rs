@@ -0,0 +1,137 @@
+//! Recording the mutations that `wasm-mutate` applies, and replaying them.
+
+use crate::{Error, ErrorKind, Result, WasmMutate};
+use std::fmt;
+use std::str::FromStr;
+
+/// A record of one mutation applied by [`WasmMutate::run`], as returned by
+/// [`WasmMutate::last_mutation`].
+///
+/// A sequence of these, each applied to the output of the one before, can be
+/// replayed onto the original input with [`WasmMutate::replay`]. Replaying
+/// just a prefix of the sequence makes it possible to bisect which mutation
+/// introduced a crash.
+///
+/// Records are displayed, and parsed, in a compact one-line format:
+///
+/// ```text
+/// <seed> <start> <output> <mutator>
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MutationRecord {
+    /// The RNG seed of the run.
+    pub seed: u64,
+    /// The index of the mutator that the run started trying mutators from.
+    pub start: u32,
+    /// The index of the mutated module among those produced by the run.
+    pub output: usize,
+    /// The name of the mutator that applied the mutation.
+    pub mutator: String,
+}
+
+impl fmt::Display for MutationRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.seed, self.start, self.output, self.mutator
+        )
+    }
+}
+
+impl FromStr for MutationRecord {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::other(format!("invalid mutation record: `{}`", s));
+        let mut parts = s.trim().splitn(4, ' ');
+        let mut next = || parts.next().ok_or_else(invalid);
+        Ok(MutationRecord {
+            seed: next()?.parse().map_err(|_| invalid())?,
+            start: next()?.parse().map_err(|_| invalid())?,
+            output: next()?.parse().map_err(|_| invalid())?,
+            mutator: next()?.to_string(),
+        })
+    }
+}
+
+impl<'wasm> WasmMutate<'wasm> {
+    /// Get the record of the mutation that produced the `output`th mutated
+    /// module of the last call to [`run`][crate::WasmMutate::run].
+    ///
+    /// Returns `None` if the last run didn't mutate a core module.
+    pub fn last_mutation(&self, output: usize) -> Option<MutationRecord> {
+        let (start, mutator) = self.last_mutation.as_ref()?;
+        Some(MutationRecord {
+            seed: self.seed,
+            start: *start,
+            output,
+            mutator: mutator.to_string(),
+        })
+    }
+
+    /// Replay the recorded mutations onto the given input Wasm, each onto the
+    /// output of the one before, and return the final mutated Wasm.
+    ///
+    /// The mutations are only reproduced faithfully when this `WasmMutate`
+    /// is configured the same way as the one that they were recorded with,
+    /// including its custom mutators. If a replayed run applies a different
+    /// mutator than the recorded one, an error is returned.
+    pub fn replay(&self, input_wasm: &[u8], records: &[MutationRecord]) -> Result<Vec<u8>> {
+        let mut wasm = input_wasm.to_vec();
+        for record in records {
+            let mut mutate = WasmMutate {
+                seed: record.seed,
+                preserve_semantics: self.preserve_semantics,
+                fuel: self.fuel.clone(),
+                reduce: self.reduce,
+                preserve_debug_info: self.preserve_debug_info,
+                preserve_component_type: self.preserve_component_type,
+                features: self.features,
+                raw_mutate_func: self.raw_mutate_func.clone(),
+                custom_mutators: self.custom_mutators.clone(),
+                forced_start: Some(record.start),
+                ..WasmMutate::default()
+            };
+            let mutated = mutate.run(&wasm)?.nth(record.output);
+            let applied = mutate.last_mutation(record.output);
+            let mutated = match mutated {
+                Some(Ok(mutated)) if applied.as_ref() == Some(record) => mutated,
+                Some(Err(e)) if !matches!(e.kind(), ErrorKind::NoMutationsApplicable) => {
+                    return Err(e)
+                }
+                _ => {
+                    return Err(Error::other(format!(
+                        "replaying mutation `{}` diverged from the recording",
+                        record
+                    )))
+                }
+            };
+            self.fuel.set(mutate.fuel.get());
+            wasm = mutated;
+        }
+        Ok(wasm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MutationRecord;
+
+    #[test]
+    fn records_round_trip() {
+        let record = MutationRecord {
+            seed: 42,
+            start: 3,
+            output: 1,
+            mutator: "custom mutator".to_string(),
+        };
+        assert_eq!(record.to_string(), "42 3 1 custom mutator");
+        assert_eq!(
+            record.to_string().parse::<MutationRecord>().unwrap(),
+            record
+        );
+        assert!("42 3".parse::<MutationRecord>().is_err());
+        assert!("42 x 1 foo".parse::<MutationRecord>().is_err());
+    }
+}
@@ -1,4 +1,5 @@
-use wasm_mutate::{ErrorKind, WasmMutate};
+use std::sync::Arc;
+use wasm_mutate::{CustomMutator, ErrorKind, MutationRecord, WasmFeatures, WasmMutate};
 use wasmparser::Validator;
 
 fn validate(validator: &mut Validator, bytes: &[u8]) {
@@ -6,12 +7,17 @@ fn validate(validator: &mut Validator, bytes: &[u8]) {
         Ok(_) => return,
         Err(e) => e,
     };
-    drop(std::fs::write("test.wasm", &bytes));
+    let dir = tempfile::tempdir().unwrap().keep();
+    drop(std::fs::write(dir.join("test.wasm"), bytes));
     if let Ok(text) = wasmprinter::print_bytes(bytes) {
-        drop(std::fs::write("test.wat", &text));
+        drop(std::fs::write(dir.join("test.wat"), &text));
     }
 
-    panic!("Wasm failed to validate: {:?}", err);
+    panic!(
+        "Wasm failed to validate: {:?} (written to {})",
+        err,
+        dir.join("test.wasm").display()
+    );
 }
 
 #[test]
@@ -67,3 +73,433 @@ fn integration_test() {
         elapsed.subsec_millis()
     );
 }
+
+/// Appends a custom section named after the seed it was given.
+struct AppendSeedSection;
+
+impl CustomMutator for AppendSeedSection {
+    fn can_mutate(&self, _wasm: &[u8], config: &WasmMutate) -> bool {
+        !config.reduces()
+    }
+
+    fn mutate(&self, wasm: &[u8], seed: u64, _config: &WasmMutate) -> wasm_mutate::Result<Vec<u8>> {
+        let mut wasm = wasm.to_vec();
+        let name = format!("{:016x}", seed);
+        wasm.extend_from_slice(&[0, 17, 16]);
+        wasm.extend_from_slice(name.as_bytes());
+        Ok(wasm)
+    }
+}
+
+impl AppendSeedSection {
+    /// Is `mutated` the output of this mutator? The section adds an id, a
+    /// size, a name length, and a 16-byte name.
+    fn mutated(original: &[u8], mutated: &[u8]) -> bool {
+        mutated.starts_with(original) && mutated.len() == original.len() + 19
+    }
+}
+
+/// Adds a second memory, which is only valid with multi-memory.
+struct AddMemory;
+
+impl CustomMutator for AddMemory {
+    fn can_mutate(&self, _wasm: &[u8], config: &WasmMutate) -> bool {
+        !config.reduces()
+    }
+
+    fn mutate(
+        &self,
+        _wasm: &[u8],
+        _seed: u64,
+        _config: &WasmMutate,
+    ) -> wasm_mutate::Result<Vec<u8>> {
+        Ok(wat::parse_str("(module (memory 1) (memory 1))").unwrap())
+    }
+}
+
+/// Produces a module that does not validate.
+struct Invalid;
+
+impl CustomMutator for Invalid {
+    fn can_mutate(&self, _wasm: &[u8], _config: &WasmMutate) -> bool {
+        true
+    }
+
+    fn mutate(
+        &self,
+        wasm: &[u8],
+        _seed: u64,
+        _config: &WasmMutate,
+    ) -> wasm_mutate::Result<Vec<u8>> {
+        // A function section without a code section.
+        let mut wasm = wasm.to_vec();
+        wasm.extend_from_slice(&[3, 2, 1, 0]);
+        Ok(wasm)
+    }
+}
+
+/// Replaces the module with one that has two memories.
+struct MultiMemory;
+
+impl CustomMutator for MultiMemory {
+    fn can_mutate(&self, _wasm: &[u8], _config: &WasmMutate) -> bool {
+        true
+    }
+
+    fn mutate(
+        &self,
+        _wasm: &[u8],
+        _seed: u64,
+        _config: &WasmMutate,
+    ) -> wasm_mutate::Result<Vec<u8>> {
+        Ok(wat::parse_str("(module (memory 1) (memory 1))").unwrap())
+    }
+}
+
+#[test]
+fn custom_mutators() {
+    let _ = env_logger::try_init();
+
+    let original = &wat::parse_str(
+        r#"
+        (module
+            (func (export "f") (result i32)
+                i32.const 42
+            )
+        )
+    "#,
+    )
+    .unwrap();
+
+    let mut custom = 0;
+    for seed in 0..100 {
+        let mut mutator = WasmMutate::default();
+        mutator
+            .seed(seed)
+            .add_mutator(Arc::new(AppendSeedSection))
+            .add_mutator(Arc::new(Invalid));
+        let mut it = match mutator.run(original) {
+            Ok(it) => it,
+            Err(e) => match e.kind() {
+                ErrorKind::NoMutationsApplicable => continue,
+                _ => panic!("{}", e),
+            },
+        };
+        let mutated = it.next().unwrap().unwrap();
+        let mut validator = Validator::new();
+        validate(&mut validator, &mutated);
+        if AppendSeedSection::mutated(original, &mutated) {
+            custom += 1;
+        }
+    }
+    assert!(custom > 0);
+
+    // Custom mutators are not applied when they say they can't be.
+    for seed in 0..100 {
+        let mut mutator = WasmMutate::default();
+        mutator
+            .seed(seed)
+            .reduce(true)
+            .add_mutator(Arc::new(AppendSeedSection));
+        let mutated = match mutator.run(original) {
+            Ok(mut it) => it.next(),
+            Err(_) => None,
+        };
+        if let Some(Ok(mutated)) = mutated {
+            assert!(!AppendSeedSection::mutated(original, &mutated));
+        }
+    }
+}
+
+#[test]
+fn features_restrict_mutations() {
+    let _ = env_logger::try_init();
+
+    let original = &wat::parse_str("(module (memory 1))").unwrap();
+    let multi_memory = &wat::parse_str("(module (memory 1) (memory 1))").unwrap();
+
+    // Multiple memories are supported by default, but not once the features
+    // are restricted to the default ones of the validator.
+    let count_multi_memory = |features: Option<WasmFeatures>| {
+        let mut count = 0;
+        for seed in 0..100 {
+            let mut mutator = WasmMutate::default();
+            mutator.seed(seed).add_mutator(Arc::new(MultiMemory));
+            if let Some(features) = features {
+                mutator.features(features);
+            }
+            let mutated = match mutator.run(original) {
+                Ok(mut it) => it.next().and_then(|m| m.ok()),
+                Err(_) => None,
+            };
+            if let Some(mutated) = mutated {
+                let mut validator = Validator::new_with_features(
+                    features.unwrap_or_else(wasm_mutate::supported_features),
+                );
+                validate(&mut validator, &mutated);
+                if mutated == *multi_memory {
+                    count += 1;
+                }
+            }
+        }
+        count
+    };
+    assert!(count_multi_memory(None) > 0);
+    assert_eq!(count_multi_memory(Some(WasmFeatures::default())), 0);
+}
+
+#[test]
+fn feedback_guides_mutator_selection() {
+    let _ = env_logger::try_init();
+
+    let original = &wat::parse_str(
+        r#"
+        (module
+            (func (export "f") (result i32)
+                i32.const 42
+            )
+        )
+    "#,
+    )
+    .unwrap();
+
+    // Count how often the custom mutator is used in the second half of a
+    // session, with and without feedback that only its mutations are
+    // interesting.
+    let count_custom = |feedback: bool| {
+        let mut mutator = WasmMutate::default();
+        mutator.add_mutator(Arc::new(AppendSeedSection));
+        let mut custom = 0;
+        for seed in 0..400 {
+            mutator.seed(seed);
+            let mutated = match mutator.run(original) {
+                Ok(mut it) => it.next().and_then(|m| m.ok()),
+                Err(_) => None,
+            };
+            let interesting = mutated
+                .map(|m| AppendSeedSection::mutated(original, &m))
+                .unwrap_or(false);
+            if feedback {
+                mutator.report_interesting(interesting);
+            }
+            if seed >= 200 && interesting {
+                custom += 1;
+            }
+        }
+        custom
+    };
+
+    let without_feedback = count_custom(false);
+    let with_feedback = count_custom(true);
+    assert!(
+        with_feedback > 2 * without_feedback,
+        "{} vs {}",
+        with_feedback,
+        without_feedback
+    );
+}
+
+#[test]
+fn preserve_debug_info() {
+    let _ = env_logger::try_init();
+
+    let mut original = wat::parse_str(
+        r#"
+        (module
+            (func $a (export "a") (param $x i32) (result i32)
+                (local $y i32)
+                local.get $x
+                i32.const 1
+                i32.add
+                local.tee $y
+                i32.const 2
+                i32.mul
+            )
+            (func $b (export "b") (result i32)
+                i32.const 42
+                call $a
+            )
+        )
+    "#,
+    )
+    .unwrap();
+    // A stand-in for DWARF, which refers to code by its offset.
+    original.extend_from_slice(&[0, 15, 11]);
+    original.extend_from_slice(b".debug_info");
+    original.extend_from_slice(&[1, 2, 3]);
+
+    // The function names, the DWARF section, and the offsets of the function
+    // bodies within the code section.
+    fn debug_info(wasm: &[u8]) -> (Vec<String>, Option<Vec<u8>>, Vec<usize>) {
+        let mut names = Vec::new();
+        let mut dwarf = None;
+        let mut offsets = Vec::new();
+        let mut code_start = 0;
+        for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+            match payload.unwrap() {
+                wasmparser::Payload::CodeSectionStart { range, .. } => code_start = range.start,
+                wasmparser::Payload::CodeSectionEntry(body) => {
+                    offsets.push(body.range().start - code_start)
+                }
+                wasmparser::Payload::CustomSection(reader) if reader.name() == ".debug_info" => {
+                    dwarf = Some(reader.data().to_vec())
+                }
+                wasmparser::Payload::CustomSection(reader) if reader.name() == "name" => {
+                    let mut reader =
+                        wasmparser::NameSectionReader::new(reader.data(), reader.data_offset())
+                            .unwrap();
+                    while !reader.eof() {
+                        if let wasmparser::Name::Function(map) = reader.read().unwrap() {
+                            let mut map = map.get_map().unwrap();
+                            for _ in 0..map.get_count() {
+                                names.push(map.read().unwrap().name.to_string());
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        (names, dwarf, offsets)
+    }
+
+    let (names, dwarf, offsets) = debug_info(&original);
+    let mut mutator = WasmMutate::default();
+    mutator.preserve_debug_info(true);
+    let mut mutated = 0;
+    for seed in 0..200 {
+        mutator.seed(seed);
+        let it = match mutator.run(&original) {
+            Ok(it) => it,
+            Err(e) if matches!(e.kind(), ErrorKind::NoMutationsApplicable) => continue,
+            Err(e) => panic!("{}", e),
+        };
+        for wasm in it.take(5).filter_map(|m| m.ok()) {
+            validate(&mut Validator::new(), &wasm);
+            let (new_names, new_dwarf, new_offsets) = debug_info(&wasm);
+            assert_eq!(new_names, names);
+            assert_eq!(new_dwarf, dwarf);
+            assert_eq!(new_offsets[..offsets.len()], offsets[..]);
+            mutated += 1;
+        }
+    }
+    assert!(mutated > 0);
+}
+
+#[test]
+fn record_and_replay_mutations() {
+    let _ = env_logger::try_init();
+
+    let original = wat::parse_str(
+        r#"
+        (module
+            (global $g (mut i32) (i32.const 1))
+            (func (export "a") (param i32) (result i32)
+                local.get 0
+                global.get $g
+                i32.add
+                i32.const 2
+                i32.mul
+            )
+            (func (export "b") (result i32)
+                i32.const 42
+                call 0
+            )
+        )
+    "#,
+    )
+    .unwrap();
+
+    // Apply a chain of mutations, recording each of them along with the
+    // intermediate modules.
+    let config = || {
+        let mut mutator = WasmMutate::default();
+        mutator.add_mutator(Arc::new(AppendSeedSection));
+        mutator
+    };
+    let mut records = Vec::new();
+    let mut wasms = vec![original.clone()];
+    for seed in 0..100 {
+        if records.len() == 10 {
+            break;
+        }
+        let wasm = wasms.last().unwrap().clone();
+        let mut mutator = config();
+        mutator.seed(seed);
+        let mutated = match mutator.run(&wasm) {
+            Ok(it) => it.enumerate().find_map(|(i, m)| Some((i, m.ok()?))),
+            Err(_) => None,
+        };
+        if let Some((output, mutated)) = mutated {
+            let record = mutator.last_mutation(output).unwrap();
+            let line = record.to_string();
+            assert_eq!(line.parse::<MutationRecord>().unwrap(), record);
+            records.push(record);
+            wasms.push(mutated);
+        }
+    }
+    assert_eq!(records.len(), 10);
+
+    // Replaying any prefix of the records reproduces the corresponding
+    // intermediate module.
+    let mutator = config();
+    for (i, wasm) in wasms.iter().enumerate() {
+        assert_eq!(mutator.replay(&original, &records[..i]).unwrap(), *wasm);
+    }
+
+    // A record that doesn't match what the replay does is an error.
+    let mut record = records[0].clone();
+    record.mutator = "nonexistent".to_string();
+    assert!(mutator.replay(&original, &[record]).is_err());
+}
+
+#[test]
+fn replay_mutations_with_features() {
+    let _ = env_logger::try_init();
+
+    let original = wat::parse_str(
+        r#"
+        (module
+            (memory 1)
+            (func (export "f") (param i32) (result i32)
+                local.get 0
+                i32.const 1
+                i32.add
+            )
+        )
+    "#,
+    )
+    .unwrap();
+
+    // Without multi-memory, `AddMemory` always produces invalid Wasm and
+    // `AppendSeedSection` is used instead, so replaying those mutations only
+    // reproduces them if the replay uses the configured features too.
+    let features = WasmFeatures::default();
+    let config = || {
+        let mut mutator = WasmMutate::default();
+        mutator
+            .features(features)
+            .add_mutator(Arc::new(AddMemory))
+            .add_mutator(Arc::new(AppendSeedSection));
+        mutator
+    };
+    let mut replayed = 0;
+    for seed in 0..300 {
+        let mut mutator = config();
+        mutator.seed(seed);
+        let mutated = match mutator.run(&original) {
+            Ok(mut it) => it.next(),
+            Err(_) => None,
+        };
+        let Some(Ok(mutated)) = mutated else {
+            continue;
+        };
+        let record = mutator.last_mutation(0).unwrap();
+        assert!(!record.mutator.contains("AddMemory"));
+        if AppendSeedSection::mutated(&original, &mutated) {
+            assert_eq!(config().replay(&original, &[record]).unwrap(), mutated);
+            replayed += 1;
+        }
+    }
+    assert!(replayed > 0);
+}
@@ -0,0 +1,126 @@
+//! Keeping the interface of the Wasm being shrunk intact.
+//!
+//! A reproduction harness usually depends on some of the Wasm's exports, and
+//! for components, on the whole WIT world that the component targets. A
+//! candidate that drops or changes the type of any of these won't run in the
+//! harness anymore, so it is rejected before the predicate is even run.
+
+use anyhow::Result;
+use wasmparser::types::{ComponentEntityType, EntityType, Types};
+use wasmparser::{Parser, Payload};
+
+/// The type of an import or export.
+enum Entity {
+    Core(EntityType),
+    Component(ComponentEntityType),
+}
+
+impl Entity {
+    /// Are `a` and `b` equivalent types, i.e. is each a subtype of the other?
+    fn equivalent(a: &Entity, at: &Types, b: &Entity, bt: &Types) -> bool {
+        let (at, bt) = (at.as_ref(), bt.as_ref());
+        match (a, b) {
+            (Entity::Core(a), Entity::Core(b)) => {
+                EntityType::is_subtype_of(a, at, b, bt) && EntityType::is_subtype_of(b, bt, a, at)
+            }
+            (Entity::Component(a), Entity::Component(b)) => {
+                ComponentEntityType::is_subtype_of(a, at, b, bt)
+                    && ComponentEntityType::is_subtype_of(b, bt, a, at)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The imports and exports of a module or component, and their types.
+pub struct Interface {
+    types: Types,
+    imports: Vec<(String, Entity)>,
+    exports: Vec<(String, Entity)>,
+}
+
+impl Interface {
+    /// Get the interface of the given Wasm, validating it with `validator`.
+    pub fn new(wasm: &[u8], mut validator: wasmparser::Validator) -> Result<Interface> {
+        let types = validator.validate_all(wasm)?;
+        let mut imports = Vec::new();
+        let mut exports = Vec::new();
+        let missing = || anyhow::anyhow!("Failed to get the type of an import or export");
+
+        // Only the outermost module or component's imports and exports are
+        // part of its interface.
+        let mut depth = 0;
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload? {
+                Payload::Version { .. } => depth += 1,
+                Payload::End(_) => depth -= 1,
+                _ if depth > 1 => {}
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        let import = import?;
+                        let ty = types.entity_type_from_import(&import).ok_or_else(missing)?;
+                        let name = format!("{}::{}", import.module, import.name);
+                        imports.push((name, Entity::Core(ty)));
+                    }
+                }
+                Payload::ExportSection(reader) => {
+                    for export in reader {
+                        let export = export?;
+                        let ty = types.entity_type_from_export(&export).ok_or_else(missing)?;
+                        exports.push((export.name.to_string(), Entity::Core(ty)));
+                    }
+                }
+                Payload::ComponentImportSection(reader) => {
+                    for import in reader {
+                        let import = import?;
+                        let ty = types
+                            .component_entity_type_from_import(&import)
+                            .ok_or_else(missing)?;
+                        imports.push((import.name.to_string(), Entity::Component(ty)));
+                    }
+                }
+                Payload::ComponentExportSection(reader) => {
+                    for export in reader {
+                        let export = export?;
+                        let ty = types
+                            .component_entity_type_from_export(&export)
+                            .ok_or_else(missing)?;
+                        exports.push((export.name.to_string(), Entity::Component(ty)));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Interface {
+            types,
+            imports,
+            exports,
+        })
+    }
+
+    /// Does `other` have all of the exports of this interface, each with an
+    /// equivalent type?
+    pub fn exports_preserved_by(&self, other: &Interface) -> bool {
+        self.preserved(&self.exports, other, &other.exports)
+    }
+
+    /// Does `other` have all of the imports of this interface, each with an
+    /// equivalent type?
+    pub fn imports_preserved_by(&self, other: &Interface) -> bool {
+        self.preserved(&self.imports, other, &other.imports)
+    }
+
+    fn preserved(
+        &self,
+        items: &[(String, Entity)],
+        other: &Interface,
+        other_items: &[(String, Entity)],
+    ) -> bool {
+        items.iter().all(|(name, ty)| {
+            other_items.iter().any(|(other_name, other_ty)| {
+                name == other_name && Entity::equivalent(ty, &self.types, other_ty, &other.types)
+            })
+        })
+    }
+}
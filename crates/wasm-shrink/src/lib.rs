@@ -1,13 +1,26 @@
 //! Shrink a Wasm file while maintaining a property of interest (such as
 //! triggering a compiler bug).
 //!
+//! Both core modules and components can be shrunk. Components are shrunk by
+//! shrinking their nested modules and components and by removing their
+//! instances, aliases, types, and other items.
+//!
+//! Shrinking starts with structural passes that remove whole sections,
+//! replace function bodies with `unreachable`, and truncate data segments,
+//! before falling back to random, size-reducing mutations.
+//!
 //! See the [`WasmShrink`][WasmShrink] type for details.
 
+mod interface;
+mod passes;
+
+use interface::Interface;
 use std::collections::HashSet;
 
 use anyhow::{Context, Result};
 use rand::{rngs::SmallRng, Rng, SeedableRng};
 use wasm_mutate::WasmMutate;
+use wasmparser::WasmFeatures;
 
 #[rustfmt::skip]
 static EMPTY_WASM: &'static [u8] = &[
@@ -17,6 +30,28 @@ static EMPTY_WASM: &'static [u8] = &[
     0x01, 0x00, 0x00, 0x00,
 ];
 
+#[rustfmt::skip]
+static EMPTY_COMPONENT: &[u8] = &[
+    // Magic.
+    0x00, b'a', b's', b'm',
+    // Version and layer.
+    0x0a, 0x00, 0x01, 0x00,
+];
+
+/// Is the given Wasm a component, rather than a core module?
+fn is_component(wasm: &[u8]) -> bool {
+    matches!(
+        wasmparser::Parser::new(0).parse(wasm, true),
+        Ok(wasmparser::Chunk::Parsed {
+            payload: wasmparser::Payload::Version {
+                encoding: wasmparser::Encoding::Component,
+                ..
+            },
+            ..
+        })
+    )
+}
+
 #[cfg_attr(
     not(feature = "clap"),
     doc = r###"
@@ -80,6 +115,32 @@ pub struct WasmShrink {
     #[cfg_attr(feature = "clap", clap(short, long, default_value = "42"))]
     seed: u64,
 
+    /// The number of candidates to generate and run the predicate on
+    /// concurrently.
+    #[cfg_attr(feature = "clap", clap(short, long, default_value = "1"))]
+    jobs: u32,
+
+    /// Never remove an export, or change its type.
+    ///
+    /// Use this when the harness that reproduces the bug calls into the Wasm
+    /// through its exports.
+    #[cfg_attr(feature = "clap", clap(long))]
+    preserve_exports: bool,
+
+    /// Never remove or change the type of an import or export.
+    ///
+    /// For a component, this keeps the WIT world that it targets intact.
+    #[cfg_attr(feature = "clap", clap(long))]
+    preserve_wit_world: bool,
+
+    /// Comma-separated list of WebAssembly features that candidates are
+    /// validated with, in the syntax of `wasm-tools validate --features`.
+    #[cfg_attr(
+        feature = "clap",
+        clap(long, default_value = "all,-component-model-async")
+    )]
+    features: WasmFeatures,
+
     #[cfg_attr(feature = "clap", clap(skip))]
     on_new_smallest: Option<Box<dyn FnMut(&[u8]) -> Result<()>>>,
 }
@@ -90,6 +151,13 @@ impl Default for WasmShrink {
             attempts: 1000,
             allow_empty: false,
             seed: 42,
+            jobs: 1,
+            preserve_exports: false,
+            preserve_wit_world: false,
+            features: WasmFeatures {
+                component_model_async: false,
+                ..WasmFeatures::all()
+            },
             on_new_smallest: None,
         }
     }
@@ -119,6 +187,35 @@ impl WasmShrink {
         self
     }
 
+    /// Set the number of candidates to generate and run the predicate on
+    /// concurrently in [`run_parallel`][WasmShrink::run_parallel].
+    pub fn jobs(mut self, jobs: u32) -> WasmShrink {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    /// Should the exports of the input Wasm be kept, each with the same type?
+    pub fn preserve_exports(mut self, preserve_exports: bool) -> WasmShrink {
+        self.preserve_exports = preserve_exports;
+        self
+    }
+
+    /// Should the imports and exports of the input Wasm be kept, each with the
+    /// same type? For a component, this is the WIT world that it targets.
+    pub fn preserve_wit_world(mut self, preserve_wit_world: bool) -> WasmShrink {
+        self.preserve_wit_world = preserve_wit_world;
+        self
+    }
+
+    /// Set the WebAssembly features that candidates are validated with.
+    ///
+    /// By default every proposal is enabled except the async extensions to
+    /// the component model.
+    pub fn features(mut self, features: WasmFeatures) -> WasmShrink {
+        self.features = features;
+        self
+    }
+
     /// Set the callback that is called each time we discover a new smallest
     /// test case that is interesting.
     pub fn on_new_smallest(
@@ -145,6 +242,24 @@ impl WasmShrink {
     {
         ShrinkRun::new(self, input).run(predicate)
     }
+
+    /// Run this configured Wasm shrinking task, running the predicate on
+    /// multiple candidates concurrently.
+    ///
+    /// This is like [`run`][WasmShrink::run], but each round generates as
+    /// many independent candidates as the configured number of
+    /// [`jobs`][WasmShrink::jobs], runs the `predicate` on all of them in
+    /// parallel, and takes the first one that is accepted.
+    pub fn run_parallel<P, I>(self, input: Vec<u8>, predicate: P) -> Result<ShrinkInfo>
+    where
+        P: Fn(&[u8]) -> Result<I> + Sync,
+        I: IsInteresting + Send,
+    {
+        if self.jobs <= 1 {
+            return self.run(input, predicate);
+        }
+        ShrinkRun::new(self, input).run_parallel(predicate)
+    }
 }
 
 struct ShrinkRun {
@@ -154,6 +269,12 @@ struct ShrinkRun {
     // The size of the original input Wasm.
     input_size: u64,
 
+    // The empty module or component, whichever the input is.
+    empty: &'static [u8],
+
+    // The interface of the input Wasm, if it must be preserved.
+    interface: Option<Interface>,
+
     // The smallest Wasm that passes the predicate.
     best: Vec<u8>,
 
@@ -171,11 +292,18 @@ impl ShrinkRun {
     pub fn new(shrink: WasmShrink, input: Vec<u8>) -> ShrinkRun {
         let rng = SmallRng::seed_from_u64(shrink.seed);
         let input_size = input.len() as u64;
+        let empty = if is_component(&input) {
+            EMPTY_COMPONENT
+        } else {
+            EMPTY_WASM
+        };
         let best = input;
         ShrinkRun {
             shrink,
             rng,
             input_size,
+            empty,
+            interface: None,
             best,
             already_tested: HashSet::new(),
             attempt: 0,
@@ -184,7 +312,7 @@ impl ShrinkRun {
 
     fn on_new_best(&mut self, new_best: Vec<u8>) -> Result<()> {
         debug_assert!(
-            new_best.len() < self.best.len() || (new_best == EMPTY_WASM && self.best == EMPTY_WASM)
+            new_best.len() < self.best.len() || (new_best == self.empty && self.best == self.empty)
         );
         log::info!("New smallest Wasm found: {} bytes", new_best.len());
         if let Some(f) = self.shrink.on_new_smallest.as_mut() {
@@ -216,32 +344,29 @@ impl ShrinkRun {
     }
 
     fn validate_wasm(&self, wasm: &[u8]) -> Result<()> {
-        let mut validator = wasmparser::Validator::new_with_features(wasmparser::WasmFeatures {
-            // TODO: we should have CLI flags for each Wasm proposal.
-            reference_types: true,
-            multi_value: true,
-            bulk_memory: true,
-            simd: true,
-            threads: true,
-            tail_call: true,
-            multi_memory: true,
-            exceptions: true,
-            memory64: true,
-            relaxed_simd: true,
-            extended_const: true,
-            mutable_global: true,
-            saturating_float_to_int: true,
-            sign_extension: true,
-            component_model: false,
-
-            // We'll never enable this here.
-            deterministic_only: false,
-        });
-
-        validator.validate_all(wasm)?;
+        self.validator().validate_all(wasm)?;
         Ok(())
     }
 
+    /// Is the candidate valid, and does it preserve the interface of the
+    /// input Wasm, if that is configured?
+    fn is_valid_candidate(&self, candidate: &[u8]) -> bool {
+        let original = match &self.interface {
+            Some(original) => original,
+            None => return self.validate_wasm(candidate).is_ok(),
+        };
+        let interface = match Interface::new(candidate, self.validator()) {
+            Ok(interface) => interface,
+            Err(_) => return false,
+        };
+        original.exports_preserved_by(&interface)
+            && (!self.shrink.preserve_wit_world || original.imports_preserved_by(&interface))
+    }
+
+    fn validator(&self) -> wasmparser::Validator {
+        wasmparser::Validator::new_with_features(self.shrink.features)
+    }
+
     fn finish(self) -> ShrinkInfo {
         ShrinkInfo {
             input_size: self.input_size,
@@ -250,27 +375,20 @@ impl ShrinkRun {
         }
     }
 
-    pub fn run<P, I>(mut self, mut predicate: P) -> Result<ShrinkInfo>
+    /// Check that the input Wasm is valid and interesting, and that the empty
+    /// Wasm isn't. Returns `true` if the input was shrunk to the empty Wasm
+    /// right away.
+    fn check_input<P, I>(&mut self, current: &[u8], predicate: &mut P) -> Result<bool>
     where
         P: FnMut(&[u8]) -> Result<I>,
         I: IsInteresting,
     {
-        // The Wasm that we are currently mutating.
-        //
-        // This can differ from `best` in that, with a very small probability,
-        // we will sometimes accept mutations that don't shrink Wasm size. This
-        // behavior is borrowed from MCMC[0] and helps us avoid getting stuck in
-        // local minima. For example, we might replace a `ref.func $f` with a
-        // `ref.null`, which doesn't actually shrink code size itself, but which
-        // might make `$f` dead code such that we can remove `$f` altogether in
-        // a follow up mutation.
-        //
-        // [0]: https://en.wikipedia.org/wiki/Markov_chain_Monte_Carlo
-        let mut current = self.best.clone();
-
         // Check prerequisites for the input Wasm.
-        self.validate_wasm(&current)
+        self.validate_wasm(current)
             .context("The input is not valid Wasm.")?;
+        if self.shrink.preserve_exports || self.shrink.preserve_wit_world {
+            self.interface = Some(Interface::new(current, self.validator())?);
+        }
 
         // First double check that the input Wasm passes the predicate.
         //
@@ -278,7 +396,7 @@ impl ShrinkRun {
         // doesn't consider the input Wasm interesting. Better to surface this
         // user error as quick as possible than to make them wait until we've
         // exhausted all the ways we could shrink it further.
-        let result = predicate(&current)?;
+        let result = predicate(current)?;
         anyhow::ensure!(
             result.is_interesting(),
             "The predicate does not consider the input Wasm interesting: {}",
@@ -291,11 +409,11 @@ impl ShrinkRun {
         // considers the empty module interesting, and we might as well check
         // for it eagerly, rather than make the user wait forever until we
         // finally to reduce the whole Wasm module to nothing.
-        let result = predicate(EMPTY_WASM)?;
+        let result = predicate(self.empty)?;
         if result.is_interesting() {
             if self.shrink.allow_empty {
-                self.on_new_best(EMPTY_WASM.to_vec())?;
-                return Ok(self.finish());
+                self.on_new_best(self.empty.to_vec())?;
+                return Ok(true);
             } else {
                 anyhow::bail!(
                     "The predicate considers the empty Wasm module \
@@ -308,18 +426,120 @@ impl ShrinkRun {
             }
         }
 
-        // Now we perform the main search. Keep trying to find smaller and
-        // interesting variants of the current smallest interesting Wasm file
-        // until we run out of attempts and get stuck.
-        while self.attempt < self.shrink.attempts {
+        Ok(false)
+    }
+
+    /// Run the structural shrink passes over the current Wasm until none of
+    /// them make any more progress.
+    fn run_passes<P, I>(&mut self, current: &mut Vec<u8>, predicate: &mut P) -> Result<()>
+    where
+        P: FnMut(&[u8]) -> Result<I>,
+        I: IsInteresting,
+    {
+        loop {
+            let size = current.len();
+
+            // Drop whole sections.
+            let mut i = 0;
+            while i < passes::num_sections(current)? {
+                let candidate = passes::remove_section(current, i)?;
+                if !self.try_candidate(current, candidate, predicate)? {
+                    i += 1;
+                }
+            }
+
+            if !is_component(current) {
+                // Replace ever smaller subsets of the function bodies with
+                // `unreachable`.
+                let num_bodies = passes::num_function_bodies(current)?;
+                let mut chunk = num_bodies;
+                while chunk > 0 {
+                    for start in (0..num_bodies).step_by(chunk) {
+                        let end = std::cmp::min(start + chunk, num_bodies);
+                        let candidate = passes::stub_function_bodies(current, start..end)?;
+                        self.try_candidate(current, candidate, predicate)?;
+                    }
+                    chunk /= 2;
+                }
+
+                // Binary search for the shortest prefix of each data segment
+                // that is still interesting.
+                for (segment, len) in passes::data_segment_lens(current)?.into_iter().enumerate() {
+                    let (mut lo, mut hi) = (0, len);
+                    while lo < hi {
+                        let mid = (lo + hi) / 2;
+                        let candidate = passes::truncate_data_segment(current, segment, mid)?;
+                        if self.try_candidate(current, candidate, predicate)? {
+                            hi = mid;
+                        } else {
+                            lo = mid + 1;
+                        }
+                    }
+                }
+            }
+
+            if current.len() == size {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Test a candidate from a structural shrink pass, making it the current
+    /// Wasm if it is smaller, valid, and interesting.
+    fn try_candidate<P, I>(
+        &mut self,
+        current: &mut Vec<u8>,
+        candidate: Vec<u8>,
+        predicate: &mut P,
+    ) -> Result<bool>
+    where
+        P: FnMut(&[u8]) -> Result<I>,
+        I: IsInteresting,
+    {
+        if candidate.len() >= current.len()
+            || !self.is_valid_candidate(&candidate)
+            || !self.already_tested.insert(blake3::hash(&candidate))
+        {
+            return Ok(false);
+        }
+        log::trace!("Testing structural candidate ({} bytes)", candidate.len());
+        if !predicate(&candidate)?.is_interesting() {
+            return Ok(false);
+        }
+        self.on_new_interesting(current, candidate)?;
+        Ok(true)
+    }
+
+    /// Generate the next batch of untested candidates from `current`.
+    ///
+    /// Each of the `seeds` attempts runs `wasm-mutate` with a fresh seed and
+    /// takes up to `per_seed` of the mutated Wasms it produces. Every
+    /// generated candidate counts as an attempt. Candidates are returned with
+    /// their hashes, which are only added to `already_tested` once the caller
+    /// actually runs the predicate on them.
+    fn candidates(
+        &mut self,
+        current: &[u8],
+        seeds: u32,
+        per_seed: u32,
+    ) -> Vec<(blake3::Hash, Vec<u8>)> {
+        let mut candidates: Vec<(blake3::Hash, Vec<u8>)> = Vec::new();
+        for _ in 0..seeds {
+            if self.attempt >= self.shrink.attempts {
+                break;
+            }
             self.attempt += 1;
 
             let mut mutate = WasmMutate::default();
             let seed = self.rng.gen();
-            mutate.reduce(true).seed(seed);
+            mutate
+                .reduce(true)
+                .seed(seed)
+                .features(self.shrink.features)
+                .preserve_component_type(self.shrink.preserve_wit_world);
             log::trace!("Attempt #{}: seed: {}", self.attempt, seed);
 
-            let mutations = match mutate.run(&current) {
+            let mutations = match mutate.run(current) {
                 Ok(m) => m,
                 Err(e) => {
                     // This mutation failed, but another randomly chosen
@@ -333,8 +553,8 @@ impl ShrinkRun {
                 // NB: The only mutator that takes advantage of returning an
                 // iterator with more than one item is the peephole mutator, which
                 // doesn't help shrinking too much. Therefore, we only take at most
-                // 10 elements.
-                .take(std::cmp::min(self.shrink.attempts - self.attempt, 10) as usize)
+                // `per_seed` elements.
+                .take(std::cmp::min(self.shrink.attempts - self.attempt + 1, per_seed) as usize)
                 .peekable();
 
             if mutations.peek().is_none() {
@@ -345,7 +565,6 @@ impl ShrinkRun {
                 continue;
             }
 
-            let mut new_current_wasm = None;
             for (i, mutated_wasm) in mutations.enumerate() {
                 if i > 0 {
                     self.attempt += 1;
@@ -362,27 +581,125 @@ impl ShrinkRun {
                 };
 
                 let hash = blake3::hash(&mutated_wasm);
-                if !self.already_tested.insert(hash) {
-                    log::trace!("Attempt #{}: already tested this candidate", self.attempt,);
+                if self.already_tested.contains(&hash) || candidates.iter().any(|(h, _)| *h == hash)
+                {
+                    log::trace!("Attempt #{}: already tested this candidate", self.attempt);
                     continue;
                 }
 
-                log::trace!(
-                    "Attempt #{}: testing candidate ({} bytes)",
-                    self.attempt,
-                    mutated_wasm.len()
-                );
+                if self.interface.is_some() && !self.is_valid_candidate(&mutated_wasm) {
+                    log::trace!(
+                        "Attempt #{}: candidate doesn't preserve the interface",
+                        self.attempt
+                    );
+                    continue;
+                }
+                candidates.push((hash, mutated_wasm));
+            }
+        }
+        candidates
+    }
 
-                let result = predicate(&mutated_wasm)?;
+    pub fn run<P, I>(mut self, mut predicate: P) -> Result<ShrinkInfo>
+    where
+        P: FnMut(&[u8]) -> Result<I>,
+        I: IsInteresting,
+    {
+        // The Wasm that we are currently mutating.
+        //
+        // This can differ from `best` in that, with a very small probability,
+        // we will sometimes accept mutations that don't shrink Wasm size. This
+        // behavior is borrowed from MCMC[0] and helps us avoid getting stuck in
+        // local minima. For example, we might replace a `ref.func $f` with a
+        // `ref.null`, which doesn't actually shrink code size itself, but which
+        // might make `$f` dead code such that we can remove `$f` altogether in
+        // a follow up mutation.
+        //
+        // [0]: https://en.wikipedia.org/wiki/Markov_chain_Monte_Carlo
+        let mut current = self.best.clone();
+
+        if self.check_input(&current, &mut predicate)? {
+            return Ok(self.finish());
+        }
+        self.run_passes(&mut current, &mut predicate)?;
+
+        // Now we perform the main search. Keep trying to find smaller and
+        // interesting variants of the current smallest interesting Wasm file
+        // until we run out of attempts and get stuck.
+        while self.attempt < self.shrink.attempts {
+            let candidates = self.candidates(&current, 1, 10);
+
+            let mut new_current_wasm = None;
+            for (hash, candidate) in candidates {
+                log::trace!("Testing candidate ({} bytes)", candidate.len());
+                self.already_tested.insert(hash);
+
+                let result = predicate(&candidate)?;
                 if result.is_interesting() {
-                    log::trace!("Attempt #{}: candidate is interesting", self.attempt);
-                    if self.should_accept(&current, &mutated_wasm) {
-                        log::trace!("Attempt #{}: accepting candidate", self.attempt);
-                        new_current_wasm = Some(mutated_wasm);
+                    log::trace!("Candidate is interesting");
+                    if self.should_accept(&current, &candidate) {
+                        log::trace!("Accepting candidate");
+                        new_current_wasm = Some(candidate);
                         break;
                     }
                 } else {
-                    log::trace!("Attempt #{}: candidate is not interesting", self.attempt);
+                    log::trace!("Candidate is not interesting");
+                }
+            }
+
+            if let Some(new_current_wasm) = new_current_wasm {
+                self.on_new_interesting(&mut current, new_current_wasm)?;
+            }
+        }
+
+        Ok(self.finish())
+    }
+
+    pub fn run_parallel<P, I>(mut self, predicate: P) -> Result<ShrinkInfo>
+    where
+        P: Fn(&[u8]) -> Result<I> + Sync,
+        I: IsInteresting + Send,
+    {
+        // See `run` for why this can differ from `best`.
+        let mut current = self.best.clone();
+
+        if self.check_input(&current, &mut &predicate)? {
+            return Ok(self.finish());
+        }
+        self.run_passes(&mut current, &mut &predicate)?;
+
+        while self.attempt < self.shrink.attempts {
+            // Generate a batch of candidates, each from an independent
+            // mutation of the current Wasm.
+            let candidates = self.candidates(&current, self.shrink.jobs, 1);
+            for (hash, _) in &candidates {
+                self.already_tested.insert(*hash);
+            }
+
+            log::trace!("Testing {} candidates concurrently", candidates.len());
+            let results = std::thread::scope(|scope| {
+                let handles = candidates
+                    .iter()
+                    .map(|(_, candidate)| scope.spawn(|| predicate(candidate)))
+                    .collect::<Vec<_>>();
+                handles
+                    .into_iter()
+                    .map(|handle| {
+                        handle
+                            .join()
+                            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+            // Take the first accepted candidate, in the order they were
+            // generated, so that the result doesn't depend on scheduling.
+            let mut new_current_wasm = None;
+            for ((_, candidate), result) in candidates.into_iter().zip(results) {
+                if result?.is_interesting() && self.should_accept(&current, &candidate) {
+                    log::trace!("Accepting candidate ({} bytes)", candidate.len());
+                    new_current_wasm = Some(candidate);
+                    break;
                 }
             }
 
@@ -0,0 +1,176 @@
+//! Structural shrink passes.
+//!
+//! These coarse-grained passes are run before falling back to random,
+//! fine-grained mutations with `wasm-mutate`. Each pass produces a series of
+//! smaller candidates by removing large chunks of the Wasm at once, which
+//! converges much faster on large inputs.
+
+use anyhow::Result;
+use wasm_encoder::{CodeSection, DataSection, Encode, SectionId};
+use wasmparser::{BinaryReader, CodeSectionReader, DataKind, DataSectionReader};
+
+/// The encoding of a function body that has no locals and is just an
+/// `unreachable`.
+const STUB_BODY: &[u8] = &[0x00, 0x00, 0x0b];
+
+/// A top-level section of a module or component.
+struct Section<'a> {
+    id: u8,
+    data: &'a [u8],
+    /// The offset of `data` within the whole Wasm.
+    offset: usize,
+}
+
+/// Split the given Wasm into its header and its top-level sections.
+fn sections(wasm: &[u8]) -> Result<(&[u8], Vec<Section<'_>>)> {
+    anyhow::ensure!(wasm.len() >= 8, "Wasm is missing its header");
+    let mut reader = BinaryReader::new_with_offset(&wasm[8..], 8);
+    let mut sections = Vec::new();
+    while !reader.eof() {
+        let id = reader.read_u8()?;
+        let size = reader.read_var_u32()?;
+        let offset = reader.original_position();
+        let data = reader.read_bytes(size as usize)?;
+        sections.push(Section { id, data, offset });
+    }
+    Ok((&wasm[..8], sections))
+}
+
+/// Encode a Wasm from its header and sections, replacing the `index`th section
+/// with `replacement`, if any.
+fn encode(
+    header: &[u8],
+    sections: &[Section],
+    index: usize,
+    replacement: Option<&[u8]>,
+) -> Vec<u8> {
+    let mut wasm = header.to_vec();
+    for (i, section) in sections.iter().enumerate() {
+        let data = match replacement {
+            Some(replacement) if i == index => replacement,
+            None if i == index => continue,
+            _ => section.data,
+        };
+        wasm.push(section.id);
+        data.encode(&mut wasm);
+    }
+    wasm
+}
+
+/// Get the number of top-level sections in the given Wasm.
+pub fn num_sections(wasm: &[u8]) -> Result<usize> {
+    Ok(sections(wasm)?.1.len())
+}
+
+/// Remove the `index`th top-level section of the given Wasm.
+pub fn remove_section(wasm: &[u8], index: usize) -> Result<Vec<u8>> {
+    let (header, sections) = sections(wasm)?;
+    Ok(encode(header, &sections, index, None))
+}
+
+/// Find the section with the given id, and its index.
+fn find<'a, 'b>(sections: &'b [Section<'a>], id: SectionId) -> Option<(usize, &'b Section<'a>)> {
+    sections.iter().enumerate().find(|(_, s)| s.id == id as u8)
+}
+
+/// Get the number of function bodies in the given module.
+pub fn num_function_bodies(wasm: &[u8]) -> Result<usize> {
+    let (_, sections) = sections(wasm)?;
+    match find(&sections, SectionId::Code) {
+        Some((_, code)) => Ok(CodeSectionReader::new(code.data, code.offset)?.get_count() as usize),
+        None => Ok(0),
+    }
+}
+
+/// Replace the bodies of the functions within `range` (indices into the code
+/// section) with `unreachable`.
+pub fn stub_function_bodies(wasm: &[u8], range: std::ops::Range<usize>) -> Result<Vec<u8>> {
+    let (header, sections) = sections(wasm)?;
+    let (index, code) = match find(&sections, SectionId::Code) {
+        Some(code) => code,
+        None => return Ok(wasm.to_vec()),
+    };
+
+    let mut section = CodeSection::new();
+    for (i, body) in CodeSectionReader::new(code.data, code.offset)?
+        .into_iter()
+        .enumerate()
+    {
+        if range.contains(&i) {
+            section.raw(STUB_BODY);
+        } else {
+            let body = body?.range();
+            section.raw(&wasm[body.start..body.end]);
+        }
+    }
+
+    let mut data = Vec::new();
+    section.encode(&mut data);
+    Ok(encode(
+        header,
+        &sections,
+        index,
+        Some(section_contents(&data)?),
+    ))
+}
+
+/// Get the length of each data segment in the given module.
+pub fn data_segment_lens(wasm: &[u8]) -> Result<Vec<usize>> {
+    let (_, sections) = sections(wasm)?;
+    let mut lens = Vec::new();
+    if let Some((_, data)) = find(&sections, SectionId::Data) {
+        for segment in DataSectionReader::new(data.data, data.offset)? {
+            lens.push(segment?.data.len());
+        }
+    }
+    Ok(lens)
+}
+
+/// Truncate the `segment`th data segment of the given module to `len` bytes.
+pub fn truncate_data_segment(wasm: &[u8], segment: usize, len: usize) -> Result<Vec<u8>> {
+    let (header, sections) = sections(wasm)?;
+    let (index, data) = match find(&sections, SectionId::Data) {
+        Some(data) => data,
+        None => return Ok(wasm.to_vec()),
+    };
+
+    let mut section = DataSection::new();
+    for (i, s) in DataSectionReader::new(data.data, data.offset)?
+        .into_iter()
+        .enumerate()
+    {
+        let s = s?;
+        if i != segment {
+            section.raw(&wasm[s.range.start..s.range.end]);
+            continue;
+        }
+        // Keep everything before the segment's data, except its length.
+        let prefix_end = match s.kind {
+            DataKind::Passive => {
+                let mut reader = BinaryReader::new(&wasm[s.range.start..s.range.end]);
+                reader.read_var_u32()?;
+                s.range.start + reader.current_position()
+            }
+            DataKind::Active { offset_expr, .. } => offset_expr.get_binary_reader().range().end,
+        };
+        let mut raw = wasm[s.range.start..prefix_end].to_vec();
+        s.data[..len].encode(&mut raw);
+        section.raw(&raw);
+    }
+
+    let mut encoded = Vec::new();
+    section.encode(&mut encoded);
+    Ok(encode(
+        header,
+        &sections,
+        index,
+        Some(section_contents(&encoded)?),
+    ))
+}
+
+/// Strip the size prefix off of an encoded section's contents.
+fn section_contents(encoded: &[u8]) -> Result<&[u8]> {
+    let mut reader = BinaryReader::new(encoded);
+    let size = reader.read_var_u32()?;
+    Ok(reader.read_bytes(size as usize)?)
+}
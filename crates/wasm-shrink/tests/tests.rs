@@ -44,7 +44,7 @@ fn shrink_to_empty_allowed() -> Result<()> {
 #[test]
 fn smoke_test() -> Result<()> {
     let info = WasmShrink::default().attempts(100).run(wasm(), |wasm| {
-        let wat = wasmprinter::print_bytes(&wasm)?;
+        let wat = wasmprinter::print_bytes(wasm)?;
         Ok(wat.contains("local.get"))
     })?;
 
@@ -56,3 +56,183 @@ fn smoke_test() -> Result<()> {
     wasmparser::validate(&info.output)?;
     Ok(())
 }
+
+#[test]
+fn shrink_component() -> Result<()> {
+    let wasm = wat::parse_str(
+        r#"
+            (component
+                (import "i" (instance $i (export "f" (func)) (export "g" (func))))
+                (alias export $i "f" (func $f))
+                (alias export $i "g" (func $g))
+                (core module $m
+                    (memory 1)
+                    (func $a (param i32 i32) (result i32)
+                        local.get 0
+                        local.get 1
+                        i32.add
+                    )
+                    (func (export "f") (param i32 i32) (result i32)
+                        local.get 0
+                        local.get 1
+                        call $a
+                    )
+                )
+                (core instance (instantiate $m))
+            )
+        "#,
+    )?;
+
+    let info = WasmShrink::default().attempts(200).run(wasm, |wasm| {
+        let wat = wasmprinter::print_bytes(wasm)?;
+        Ok(wat.contains("i32.add"))
+    })?;
+
+    assert!(info.input_size > info.output_size);
+
+    let wat = wasmprinter::print_bytes(&info.output)?;
+    assert!(wat.contains("i32.add"));
+    assert!(!wat.contains("alias"));
+
+    let mut validator = wasmparser::Validator::new_with_features(wasmparser::WasmFeatures {
+        component_model: true,
+        ..Default::default()
+    });
+    validator.validate_all(&info.output)?;
+    Ok(())
+}
+
+#[test]
+fn parallel_smoke_test() -> Result<()> {
+    let info = WasmShrink::default()
+        .attempts(100)
+        .jobs(4)
+        .run_parallel(wasm(), |wasm| {
+            let wat = wasmprinter::print_bytes(wasm)?;
+            Ok(wat.contains("local.get"))
+        })?;
+
+    assert!(info.input_size > info.output_size);
+
+    let wat = wasmprinter::print_bytes(&info.output)?;
+    assert!(wat.contains("local.get"));
+
+    wasmparser::validate(&info.output)?;
+    Ok(())
+}
+
+#[test]
+fn structural_passes() -> Result<()> {
+    let _ = env_logger::try_init();
+    let wasm = wat::parse_str(
+        r#"
+            (module
+                (memory 1)
+                (func (export "a") (result i32)
+                    i32.const 1
+                    i32.const 2
+                    i32.add
+                )
+                (func (export "b") (param i64) (result i64)
+                    local.get 0
+                    i64.popcnt
+                )
+                (func (export "c") (result i32)
+                    i32.const 3
+                    i32.const 4
+                    i32.mul
+                )
+                (data (i32.const 0) "hello, world!")
+            )
+        "#,
+    )?;
+
+    // With no attempts left for `wasm-mutate`, only the structural passes can
+    // shrink the Wasm.
+    let info = WasmShrink::default().attempts(0).run(wasm, |wasm| {
+        let wat = wasmprinter::print_bytes(wasm)?;
+        Ok(wat.contains("i64.popcnt"))
+    })?;
+
+    let wat = wasmprinter::print_bytes(&info.output)?;
+    assert!(wat.contains("i64.popcnt"));
+    assert!(!wat.contains("i32.add"));
+    assert!(!wat.contains("i32.mul"));
+    assert!(!wat.contains("hello"));
+
+    wasmparser::validate(&info.output)?;
+    Ok(())
+}
+
+#[test]
+fn preserve_exports() -> Result<()> {
+    let wasm = wat::parse_str(
+        r#"
+            (module
+                (import "env" "log" (func $log (param i32)))
+                (memory (export "memory") 1)
+                (func (export "run") (param i32) (result i32)
+                    local.get 0
+                    call $log
+                    local.get 0
+                    i32.const 1
+                    i32.add
+                )
+            )
+        "#,
+    )?;
+
+    let info = WasmShrink::default()
+        .attempts(100)
+        .preserve_exports(true)
+        .run(wasm, |wasm| {
+            let wat = wasmprinter::print_bytes(wasm)?;
+            Ok(wat.contains("i32.add"))
+        })?;
+
+    let wat = wasmprinter::print_bytes(&info.output)?;
+    assert!(wat.contains("i32.add"));
+    assert!(wat.contains(r#"(export "memory" (memory 0))"#));
+    assert!(wat.contains(r#"(export "run" (func"#));
+    assert!(wat.contains("(param i32) (result i32)"));
+    Ok(())
+}
+
+#[test]
+fn preserve_wit_world() -> Result<()> {
+    let wasm = wat::parse_str(
+        r#"
+            (component
+                (import "f" (func $f (param "s" string)))
+                (import "g" (func $g))
+                (core module $m
+                    (func (export "h") (param i32 i32) (result i32)
+                        local.get 0
+                        local.get 1
+                        i32.add
+                    )
+                )
+                (core instance $i (instantiate $m))
+                (func (export "h") (param "a" u32) (param "b" u32) (result u32)
+                    (canon lift (core func $i "h")))
+                (export "g2" (func $g))
+            )
+        "#,
+    )?;
+
+    let info = WasmShrink::default()
+        .attempts(100)
+        .preserve_wit_world(true)
+        .run(wasm, |wasm| {
+            let wat = wasmprinter::print_bytes(wasm)?;
+            Ok(wat.contains("i32.add"))
+        })?;
+
+    let wat = wasmprinter::print_bytes(&info.output)?;
+    assert!(wat.contains("i32.add"));
+    assert!(wat.contains(r#"(import "f""#));
+    assert!(wat.contains(r#"(import "g""#));
+    assert!(wat.contains(r#"(export "h""#));
+    assert!(wat.contains(r#"(export "g2""#));
+    Ok(())
+}
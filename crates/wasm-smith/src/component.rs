@@ -175,6 +175,9 @@ struct ComponentContext {
     // This component's instance index space.
     instances: Vec<ComponentOrCoreInstanceType>,
 
+    // Which entries in `instances` are core Wasm instances?
+    core_instances: Vec<u32>,
+
     // This component's value index space.
     values: Vec<ComponentValType>,
 }
@@ -192,6 +195,7 @@ impl ComponentContext {
             components: vec![],
             modules: vec![],
             instances: vec![],
+            core_instances: vec![],
             values: vec![],
         }
     }
@@ -422,12 +426,17 @@ impl ComponentBuilder {
                     choices.push(Self::arbitrary_component_section);
                 }
 
+                if self.total_instances < self.config.max_instances() {
+                    choices.push(Self::arbitrary_core_instance_section);
+                }
+
+                choices.push(Self::arbitrary_alias_section);
+
                 // TODO FITZGEN
                 //
                 // choices.push(Self::arbitrary_instance_section);
                 // choices.push(Self::arbitrary_export_section);
                 // choices.push(Self::arbitrary_start_section);
-                // choices.push(Self::arbitrary_alias_section);
             }
 
             let f = u.choose(&choices)?;
@@ -1676,11 +1685,15 @@ impl ComponentBuilder {
                 });
             }
 
-            if !self.component().core_funcs.is_empty() {
+            if (0..self.component().core_funcs.len())
+                .any(|i| is_scalar_liftable(self.core_function_type(i as u32)))
+            {
                 choices.push(|u, c| {
-                    let core_func_index = u.int_in_range(
-                        0..=u32::try_from(c.component().core_funcs.len() - 1).unwrap(),
-                    )?;
+                    let liftable = (0..c.component().core_funcs.len())
+                        .map(|i| u32::try_from(i).unwrap())
+                        .filter(|i| is_scalar_liftable(c.core_function_type(*i)))
+                        .collect::<Vec<_>>();
+                    let core_func_index = *u.choose(&liftable)?;
                     let core_func_ty = c.core_function_type(core_func_index);
                     let comp_func_ty = inverse_scalar_canonical_abi_for(u, core_func_ty)?;
 
@@ -1737,10 +1750,127 @@ impl ComponentBuilder {
             crate::core::DuplicateImportsBehavior::Disallowed,
         )?;
         self.push_section(Section::CoreModule(module));
+        let section_index = self.component().component.sections.len() - 1;
+        self.component_mut().modules.push((section_index, 0));
         self.total_modules += 1;
         Ok(Step::StillBuilding)
     }
 
+    fn arbitrary_core_instance_section(&mut self, u: &mut Unstructured) -> Result<Step> {
+        self.push_section(Section::CoreInstance(CoreInstanceSection {
+            instances: vec![],
+        }));
+
+        arbitrary_loop(u, 0, self.config.max_instances(), |u| {
+            // NB: we only instantiate modules defined in this component whose
+            // imports are all functions that can be satisfied by core functions
+            // in this component; each imported module name is satisfied by an
+            // instance of exported core functions.
+            let candidates = (0..self.component().modules.len())
+                .filter(|i| match self.defined_module(*i) {
+                    Some(module) => module.imports().iter().all(|imp| match &imp.entity_type {
+                        crate::core::EntityType::Func(_, ty) => {
+                            !self.core_funcs_of_type(ty).is_empty()
+                        }
+                        _ => false,
+                    }),
+                    None => false,
+                })
+                .collect::<Vec<_>>();
+            if candidates.is_empty() {
+                return Ok(false);
+            }
+
+            let module_index = *u.choose(&candidates)?;
+            let module = self.defined_module(module_index).unwrap();
+
+            let mut arg_instances: BTreeMap<String, Vec<(String, u32)>> = BTreeMap::new();
+            for imp in module.imports() {
+                let ty = match &imp.entity_type {
+                    crate::core::EntityType::Func(_, ty) => ty,
+                    _ => unreachable!(),
+                };
+                let core_func_index = *u.choose(&self.core_funcs_of_type(ty))?;
+                arg_instances
+                    .entry(imp.module.clone())
+                    .or_default()
+                    .push((imp.field.clone(), core_func_index));
+            }
+            let exports = module
+                .export_types()
+                .map(|(name, ty)| (name.to_string(), ty))
+                .collect::<BTreeMap<_, _>>();
+
+            if self.total_instances + arg_instances.len() + 1 > self.config.max_instances() {
+                return Ok(false);
+            }
+
+            let mut args = Vec::with_capacity(arg_instances.len());
+            for (name, items) in arg_instances {
+                let ty = items
+                    .iter()
+                    .map(|(field, core_func_index)| {
+                        let func_ty = Rc::clone(self.core_function_type(*core_func_index));
+                        (
+                            field.clone(),
+                            crate::core::EntityType::Func(u32::MAX, func_ty),
+                        )
+                    })
+                    .collect();
+                let instance_index = self.push_core_instance(CoreInstance::FromExports(items), ty);
+                args.push((name, instance_index));
+            }
+
+            let module_index = u32::try_from(module_index).unwrap();
+            self.push_core_instance(CoreInstance::Instantiate { module_index, args }, exports);
+            Ok(true)
+        })?;
+
+        Ok(Step::StillBuilding)
+    }
+
+    /// Get the module defined (not imported) at the given index of this
+    /// component's module index space.
+    fn defined_module(&self, module_index: usize) -> Option<&crate::Module> {
+        let (section_index, _) = self.component().modules[module_index];
+        match &self.component().component.sections[section_index] {
+            Section::CoreModule(module) => Some(module),
+            _ => None,
+        }
+    }
+
+    /// Get the indices of the core functions in this component with the given
+    /// type.
+    fn core_funcs_of_type(&self, ty: &crate::core::FuncType) -> Vec<u32> {
+        (0..self.component().core_funcs.len())
+            .map(|i| u32::try_from(i).unwrap())
+            .filter(|i| **self.core_function_type(*i) == *ty)
+            .collect()
+    }
+
+    fn push_core_instance(
+        &mut self,
+        instance: CoreInstance,
+        ty: BTreeMap<String, crate::core::EntityType>,
+    ) -> u32 {
+        let core_instance_index = u32::try_from(self.component().core_instances.len()).unwrap();
+        let instance_index = u32::try_from(self.component().instances.len()).unwrap();
+        self.component_mut().core_instances.push(instance_index);
+        self.component_mut()
+            .instances
+            .push(ComponentOrCoreInstanceType::Core(ty));
+        self.total_instances += 1;
+
+        match self.component_mut().component.sections.last_mut() {
+            Some(Section::CoreInstance(CoreInstanceSection { instances })) => {
+                instances.push(instance)
+            }
+            _ => unreachable!(),
+        }
+
+        core_instance_index
+    }
+
     fn arbitrary_component_section(&mut self, u: &mut Unstructured) -> Result<Step> {
         self.types.push(TypesScope::default());
         self.components.push(ComponentContext::empty());
@@ -1760,8 +1890,56 @@ impl ComponentBuilder {
         todo!()
     }
 
-    fn arbitrary_alias_section(&mut self, u: &mut Unstructured) -> Result<()> {
-        todo!()
+    fn arbitrary_alias_section(&mut self, u: &mut Unstructured) -> Result<Step> {
+        self.push_section(Section::Alias(AliasSection { aliases: vec![] }));
+
+        // NB: only the core function exports of core instances are aliased,
+        // as those are the only aliased entities the rest of the generator
+        // knows how to use (by lifting them).
+        let mut candidates = vec![];
+        for (i, instance_index) in self.component().core_instances.iter().enumerate() {
+            if let ComponentOrCoreInstanceType::Core(exports) =
+                &self.component().instances[*instance_index as usize]
+            {
+                for (name, ty) in exports {
+                    if let crate::core::EntityType::Func(_, func_ty) = ty {
+                        candidates.push((u32::try_from(i).unwrap(), name.clone(), func_ty.clone()));
+                    }
+                }
+            }
+        }
+
+        let max = self.config.max_aliases().min(
+            self.config
+                .max_funcs()
+                .saturating_sub(self.component().funcs.len()),
+        );
+        arbitrary_loop(u, 0, max, |u| {
+            if candidates.is_empty() {
+                return Ok(false);
+            }
+
+            let (instance, name, func_ty) = u.choose(&candidates)?.clone();
+            match self.component_mut().component.sections.last_mut() {
+                Some(Section::Alias(AliasSection { aliases })) => {
+                    aliases.push(Alias::CoreInstanceExport {
+                        instance,
+                        name,
+                        kind: CoreInstanceExportAliasKind::Func,
+                    });
+                }
+                _ => unreachable!(),
+            }
+
+            let func_index = u32::try_from(self.component().funcs.len()).unwrap();
+            self.component_mut()
+                .funcs
+                .push(ComponentOrCoreFuncType::Core(func_ty));
+            self.component_mut().core_funcs.push(func_index);
+            Ok(true)
+        })?;
+
+        Ok(Step::StillBuilding)
     }
 }
 
@@ -1800,6 +1978,27 @@ fn canonical_abi_for(func_ty: &FuncType) -> Rc<crate::core::FuncType> {
     })
 }
 
+/// Can a core function of the given type be lifted to a component function of
+/// scalar types by `inverse_scalar_canonical_abi_for`?
+fn is_scalar_liftable(core_func_ty: &crate::core::FuncType) -> bool {
+    // Lifting a function with more than 16 flattened parameters or more than
+    // one flattened result requires passing values through linear memory.
+    const MAX_FLAT_PARAMS: usize = 16;
+    const MAX_FLAT_RESULTS: usize = 1;
+
+    let is_scalar = |ty: &ValType| {
+        matches!(
+            ty,
+            ValType::I32 | ValType::I64 | ValType::F32 | ValType::F64
+        )
+    };
+
+    core_func_ty.params.len() <= MAX_FLAT_PARAMS
+        && core_func_ty.results.len() <= MAX_FLAT_RESULTS
+        && core_func_ty.params.iter().all(is_scalar)
+        && core_func_ty.results.iter().all(is_scalar)
+}
+
 fn inverse_scalar_canonical_abi_for(
     u: &mut Unstructured,
     core_func_ty: &crate::core::FuncType,
@@ -2186,10 +2385,23 @@ struct ExportSection {}
 struct StartSection {}
 
 #[derive(Debug)]
-struct AliasSection {}
+struct AliasSection {
+    aliases: Vec<Alias>,
+}
 
 #[derive(Debug)]
-struct CoreInstanceSection {}
+struct CoreInstanceSection {
+    instances: Vec<CoreInstance>,
+}
+
+#[derive(Debug)]
+enum CoreInstance {
+    Instantiate {
+        module_index: u32,
+        args: Vec<(String, u32)>,
+    },
+    FromExports(Vec<(String, u32)>),
+}
 
 #[derive(Debug)]
 struct CoreTypeSection {
@@ -26,7 +26,7 @@ impl Section {
                     data: &bytes,
                 });
             }
-            Self::CoreInstance(_) => todo!(),
+            Self::CoreInstance(sec) => sec.encode(component),
             Self::CoreType(sec) => sec.encode(component),
             Self::Component(comp) => {
                 let bytes = comp.to_bytes();
@@ -36,7 +36,7 @@ impl Section {
                 });
             }
             Self::Instance(_) => todo!(),
-            Self::Alias(_) => todo!(),
+            Self::Alias(sec) => sec.encode(component),
             Self::Type(sec) => sec.encode(component),
             Self::Canonical(sec) => sec.encode(component),
             Self::Start(_) => todo!(),
@@ -101,6 +101,56 @@ impl CanonicalSection {
     }
 }
 
+impl CoreInstanceSection {
+    fn encode(&self, component: &mut wasm_encoder::Component) {
+        let mut sec = wasm_encoder::InstanceSection::new();
+        for instance in &self.instances {
+            match instance {
+                CoreInstance::Instantiate { module_index, args } => {
+                    sec.instantiate(
+                        *module_index,
+                        args.iter().map(|(name, instance)| {
+                            (name.as_str(), wasm_encoder::ModuleArg::Instance(*instance))
+                        }),
+                    );
+                }
+                CoreInstance::FromExports(exports) => {
+                    sec.export_items(exports.iter().map(|(name, func)| {
+                        (name.as_str(), wasm_encoder::ExportKind::Func, *func)
+                    }));
+                }
+            }
+        }
+        component.section(&sec);
+    }
+}
+
+impl AliasSection {
+    fn encode(&self, component: &mut wasm_encoder::Component) {
+        let mut sec = wasm_encoder::ComponentAliasSection::new();
+        for alias in &self.aliases {
+            match alias {
+                Alias::CoreInstanceExport {
+                    instance,
+                    name,
+                    kind,
+                } => {
+                    let kind = match kind {
+                        CoreInstanceExportAliasKind::Func => wasm_encoder::ExportKind::Func,
+                        CoreInstanceExportAliasKind::Table => wasm_encoder::ExportKind::Table,
+                        CoreInstanceExportAliasKind::Memory => wasm_encoder::ExportKind::Memory,
+                        CoreInstanceExportAliasKind::Global => wasm_encoder::ExportKind::Global,
+                        CoreInstanceExportAliasKind::Tag => wasm_encoder::ExportKind::Tag,
+                    };
+                    sec.core_instance_export(*instance, kind, name);
+                }
+                Alias::InstanceExport { .. } | Alias::Outer { .. } => unreachable!(),
+            }
+        }
+        component.section(&sec);
+    }
+}
+
 impl CoreTypeSection {
     fn encode(&self, component: &mut wasm_encoder::Component) {
         let mut sec = wasm_encoder::CoreTypeSection::new();
@@ -1,8 +1,9 @@
 //! Configuring the shape of generated Wasm modules.
 
-use crate::InstructionKinds;
+use crate::{InstructionKind, InstructionKinds};
 use arbitrary::{Arbitrary, Result, Unstructured};
 use std::borrow::Cow;
+use wasmparser::WasmFeatures;
 
 /// Configuration for a generated module.
 ///
@@ -339,6 +340,20 @@ pub trait Config: 'static + std::fmt::Debug {
         false
     }
 
+    /// Determines whether only the relaxed SIMD instructions with fully
+    /// specified results are generated.
+    ///
+    /// When enabled, the relaxed truncation, fused multiply-add, and min/max
+    /// instructions are never generated, and the operands of
+    /// `i8x16.relaxed_swizzle` and the `laneselect` instructions are masked so
+    /// that every engine computes the same result. This has no effect unless
+    /// [`Config::relaxed_simd_enabled`] is also enabled.
+    ///
+    /// Defaults to `false`.
+    fn relaxed_simd_deterministic_only(&self) -> bool {
+        false
+    }
+
     /// Determines whether the exception-handling proposal is enabled for
     /// generating instructions.
     ///
@@ -347,6 +362,19 @@ pub trait Config: 'static + std::fmt::Debug {
         false
     }
 
+    /// Determines whether the tail-call proposal is enabled for generating
+    /// instructions.
+    ///
+    /// The [tail-call proposal] adds the `return_call` and
+    /// `return_call_indirect` instructions.
+    ///
+    /// [tail-call proposal]: https://github.com/WebAssembly/tail-call/blob/main/proposals/tail-call/Overview.md
+    ///
+    /// Defaults to `false`.
+    fn tail_call_enabled(&self) -> bool {
+        false
+    }
+
     /// Determines whether the multi-value results are enabled.
     ///
     /// Defaults to `true`.
@@ -431,13 +459,73 @@ pub trait Config: 'static + std::fmt::Debug {
         InstructionKinds::all()
     }
 
+    /// The relative weight of choosing instructions of the given kind when
+    /// generating function bodies.
+    ///
+    /// Each instruction that is valid at a given point in a function body is
+    /// chosen with a probability proportional to the weight of its kind, so
+    /// raising the weight of a kind (e.g., memory) steers generation toward
+    /// instructions of that kind. A weight of zero prevents instructions of the
+    /// kind from being generated, just like leaving the kind out of
+    /// [`Config::allowed_instructions`]. Weights greater than 1000 are treated
+    /// as 1000.
+    ///
+    /// Defaults to `1` for every kind.
+    fn instruction_kind_weight(&self, _kind: InstructionKind) -> u32 {
+        1
+    }
+
     /// Returns whether we should generate custom sections or not.
     ///
+    /// Core modules then get plausible `name`, `producers`, and `dylink.0`
+    /// sections that describe the rest of the module, along with custom
+    /// sections of arbitrary names and contents anywhere in the module.
+    ///
     /// This is false by default.
     fn generate_custom_sections(&self) -> bool {
         false
     }
 
+    /// Returns whether function bodies may be made of thousands of nested
+    /// `block`, `loop`, and `if` instructions.
+    ///
+    /// This is false by default.
+    fn pathological_deep_nesting(&self) -> bool {
+        false
+    }
+
+    /// Returns whether function bodies may consist of a `br_table` with
+    /// thousands of targets.
+    ///
+    /// This is false by default.
+    fn pathological_br_tables(&self) -> bool {
+        false
+    }
+
+    /// Returns whether functions may declare thousands of locals, up to the
+    /// limit of 50,000 locals per function that engines implement.
+    ///
+    /// This is false by default.
+    fn pathological_locals(&self) -> bool {
+        false
+    }
+
+    /// Returns whether function bodies may be made of tens of thousands of
+    /// instructions in a single straight line.
+    ///
+    /// This is false by default.
+    fn pathological_functions(&self) -> bool {
+        false
+    }
+
+    /// Returns whether modules should define as many functions as
+    /// [`Config::max_funcs`] allows rather than a random number of them.
+    ///
+    /// This is false by default.
+    fn pathological_function_count(&self) -> bool {
+        false
+    }
+
     /// Determines whether the threads proposal is enabled.
     ///
     /// The [threads proposal] involves shared linear memory, new atomic
@@ -449,6 +537,31 @@ pub trait Config: 'static + std::fmt::Debug {
     fn threads_enabled(&self) -> bool {
         false
     }
+
+    /// Returns the WebAssembly features which generated modules may use, so
+    /// that they can be validated or otherwise processed with the same
+    /// configuration that generated them.
+    ///
+    /// This is derived from the other methods, such as
+    /// [`Config::simd_enabled`], and there's usually no need to override it.
+    fn features(&self) -> WasmFeatures {
+        WasmFeatures {
+            mutable_global: true,
+            saturating_float_to_int: self.saturating_float_to_int_enabled(),
+            sign_extension: self.sign_extension_ops_enabled(),
+            reference_types: self.reference_types_enabled(),
+            multi_value: self.multi_value_enabled(),
+            bulk_memory: self.bulk_memory_enabled(),
+            simd: self.simd_enabled(),
+            relaxed_simd: self.relaxed_simd_enabled(),
+            multi_memory: self.max_memories() > 1,
+            exceptions: self.exceptions_enabled(),
+            memory64: self.memory64_enabled(),
+            threads: self.threads_enabled(),
+            tail_call: self.tail_call_enabled(),
+            ..WasmFeatures::mvp()
+        }
+    }
 }
 
 /// The default configuration.
@@ -478,6 +591,7 @@ pub struct SwarmConfig {
     pub canonicalize_nans: bool,
     pub exceptions_enabled: bool,
     pub export_everything: bool,
+    pub generate_custom_sections: bool,
     pub max_aliases: usize,
     pub max_components: usize,
     pub max_data_segments: usize,
@@ -514,11 +628,18 @@ pub struct SwarmConfig {
     pub min_types: usize,
     pub min_uleb_size: u8,
     pub multi_value_enabled: bool,
+    pub pathological_br_tables: bool,
+    pub pathological_deep_nesting: bool,
+    pub pathological_function_count: bool,
+    pub pathological_functions: bool,
+    pub pathological_locals: bool,
     pub reference_types_enabled: bool,
+    pub relaxed_simd_deterministic_only: bool,
     pub relaxed_simd_enabled: bool,
     pub saturating_float_to_int_enabled: bool,
     pub sign_extension_enabled: bool,
     pub simd_enabled: bool,
+    pub tail_call_enabled: bool,
     pub threads_enabled: bool,
     pub allowed_instructions: InstructionKinds,
     pub max_table_elements: u32,
@@ -590,13 +711,21 @@ impl<'a> Arbitrary<'a> for SwarmConfig {
             memory_offset_choices: (75, 24, 1),
             allow_start_export: true,
             relaxed_simd_enabled: false,
+            relaxed_simd_deterministic_only: false,
             exceptions_enabled: false,
+            tail_call_enabled: false,
             memory64_enabled: false,
             max_type_size: 1000,
             canonicalize_nans: false,
             available_imports: None,
             threads_enabled: false,
             export_everything: false,
+            generate_custom_sections: false,
+            pathological_deep_nesting: false,
+            pathological_br_tables: false,
+            pathological_locals: false,
+            pathological_functions: false,
+            pathological_function_count: false,
         })
     }
 }
@@ -740,10 +869,18 @@ impl Config for SwarmConfig {
         self.relaxed_simd_enabled
     }
 
+    fn relaxed_simd_deterministic_only(&self) -> bool {
+        self.relaxed_simd_deterministic_only
+    }
+
     fn exceptions_enabled(&self) -> bool {
         self.exceptions_enabled
     }
 
+    fn tail_call_enabled(&self) -> bool {
+        self.tail_call_enabled
+    }
+
     fn multi_value_enabled(&self) -> bool {
         self.multi_value_enabled
     }
@@ -784,6 +921,30 @@ impl Config for SwarmConfig {
         self.threads_enabled
     }
 
+    fn generate_custom_sections(&self) -> bool {
+        self.generate_custom_sections
+    }
+
+    fn pathological_deep_nesting(&self) -> bool {
+        self.pathological_deep_nesting
+    }
+
+    fn pathological_br_tables(&self) -> bool {
+        self.pathological_br_tables
+    }
+
+    fn pathological_locals(&self) -> bool {
+        self.pathological_locals
+    }
+
+    fn pathological_functions(&self) -> bool {
+        self.pathological_functions
+    }
+
+    fn pathological_function_count(&self) -> bool {
+        self.pathological_function_count
+    }
+
     fn allowed_instructions(&self) -> InstructionKinds {
         self.allowed_instructions
     }
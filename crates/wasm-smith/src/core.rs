@@ -1,14 +1,19 @@
 //! Generating arbitary core Wasm modules.
 
 mod code_builder;
+mod custom;
+mod differential;
 pub(crate) mod encode;
+mod invalid;
 pub(crate) mod no_traps;
+mod pathological;
 mod terminate;
 
 use crate::{arbitrary_loop, limited_string, unique_string, Config, DefaultConfig};
 use arbitrary::{Arbitrary, Result, Unstructured};
 use code_builder::CodeBuilderAllocations;
 use flagset::{flags, FlagSet};
+pub use invalid::InvalidityClass;
 use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::marker;
@@ -117,6 +122,10 @@ pub struct Module {
     code: Vec<Code>,
     data: Vec<DataSegment>,
 
+    /// Custom sections, each paired with the number of known sections that
+    /// are encoded before it.
+    custom_sections: Vec<(usize, custom::CustomSection)>,
+
     /// The predicted size of the effective type of this module, based on this
     /// module's size of the types of imports/exports.
     type_size: u32,
@@ -170,6 +179,18 @@ impl Module {
         Ok(module)
     }
 
+    /// The imports of this module.
+    pub(crate) fn imports(&self) -> &[Import] {
+        &self.imports
+    }
+
+    /// The names and types of this module's exports.
+    pub(crate) fn export_types(&self) -> impl Iterator<Item = (&str, EntityType)> + '_ {
+        self.exports
+            .iter()
+            .map(move |(name, kind, index)| (name.as_str(), self.type_of(*kind, *index)))
+    }
+
     fn empty(config: Rc<dyn Config>, duplicate_imports_behavior: DuplicateImportsBehavior) -> Self {
         Module {
             config,
@@ -196,6 +217,7 @@ impl Module {
             elems: Vec::new(),
             code: Vec::new(),
             data: Vec::new(),
+            custom_sections: Vec::new(),
             type_size: 0,
         }
     }
@@ -220,6 +242,23 @@ pub struct MaybeInvalidModule {
 }
 
 impl MaybeInvalidModule {
+    /// Generate a module that is invalid in exactly one place, in a way that
+    /// belongs to one of the given `classes` of invalidity.
+    ///
+    /// Unlike the `Arbitrary` implementation, which fills function bodies
+    /// with arbitrary bytes, this generates a valid module and then makes a
+    /// single one of its function bodies invalid, so that the validator
+    /// reports an error of the chosen class.
+    pub fn with_invalidity(u: &mut Unstructured, classes: &[InvalidityClass]) -> Result<Self> {
+        let mut module = Module::empty(
+            Rc::new(invalid::InvalidityConfig),
+            DuplicateImportsBehavior::Allowed,
+        );
+        module.build(u, false)?;
+        module.introduce_invalidity(u, classes)?;
+        Ok(MaybeInvalidModule { module })
+    }
+
     /// Encode this Wasm module into bytes.
     pub fn to_bytes(&self) -> Vec<u8> {
         self.module.to_bytes()
@@ -372,6 +411,7 @@ impl Module {
         self.arbitrary_elems(u)?;
         self.arbitrary_data(u)?;
         self.arbitrary_code(u, allow_invalid)?;
+        self.arbitrary_custom_sections(u)?;
         Ok(())
     }
 
@@ -657,6 +697,7 @@ impl Module {
                 wasmparser::TypeRef::Table(table_ty) => {
                     let table_ty = TableType {
                         element_type: convert_type(table_ty.element_type),
+                        table64: table_ty.table64,
                         minimum: table_ty.initial,
                         maximum: table_ty.maximum,
                     };
@@ -810,7 +851,14 @@ impl Module {
             return Ok(());
         }
 
-        arbitrary_loop(u, self.config.min_funcs(), self.config.max_funcs(), |u| {
+        // When asked for as many functions as possible, fill up whatever room
+        // the imports left.
+        let min = if self.config.pathological_function_count() {
+            self.config.max_funcs().saturating_sub(self.funcs.len())
+        } else {
+            self.config.min_funcs()
+        };
+        arbitrary_loop(u, min, self.config.max_funcs(), |u| {
             if !self.can_add_local_or_import_func() {
                 return Ok(false);
             }
@@ -1021,31 +1069,50 @@ impl Module {
         let func_max = self.funcs.len() as u32;
 
         // Create a helper closure to choose an arbitrary offset.
-        let mut offset_global_choices = vec![];
+        let mut offset_global_choices32 = vec![];
+        let mut offset_global_choices64 = vec![];
         for (i, g) in self.globals[..self.globals.len() - self.defined_globals.len()]
             .iter()
             .enumerate()
         {
-            if !g.mutable && g.val_type == ValType::I32 {
-                offset_global_choices.push(i as u32);
+            if g.mutable {
+                continue;
+            }
+            if g.val_type == ValType::I32 {
+                offset_global_choices32.push(i as u32);
+            } else if g.val_type == ValType::I64 {
+                offset_global_choices64.push(i as u32);
             }
         }
-        let arbitrary_active_elem = |u: &mut Unstructured, min: u32, table: Option<u32>| {
-            let (offset, max_size_hint) = if !offset_global_choices.is_empty() && u.arbitrary()? {
-                let g = u.choose(&offset_global_choices)?;
-                (Offset::Global(*g), None)
-            } else {
-                let offset = arbitrary_offset(u, min.into(), u32::MAX.into(), 0)? as u32;
-                let max_size_hint =
-                    if offset <= min && u.int_in_range(0..=CHANCE_OFFSET_INBOUNDS)? != 0 {
-                        Some(min - offset)
+        let arbitrary_active_elem =
+            |u: &mut Unstructured, min: u64, table: Option<u32>, table64: bool| {
+                let offset_global_choices = if table64 {
+                    &offset_global_choices64
+                } else {
+                    &offset_global_choices32
+                };
+                let (offset, max_size_hint) =
+                    if !offset_global_choices.is_empty() && u.arbitrary()? {
+                        let g = u.choose(offset_global_choices)?;
+                        (Offset::Global(*g), None)
                     } else {
-                        None
+                        let max = if table64 { u64::MAX } else { u32::MAX.into() };
+                        let offset = arbitrary_offset(u, min, max, 0)?;
+                        let max_size_hint =
+                            if offset <= min && u.int_in_range(0..=CHANCE_OFFSET_INBOUNDS)? != 0 {
+                                Some(u32::try_from(min - offset).unwrap())
+                            } else {
+                                None
+                            };
+                        let offset = if table64 {
+                            Offset::Const64(offset as i64)
+                        } else {
+                            Offset::Const32(offset as i32)
+                        };
+                        (offset, max_size_hint)
                     };
-                (Offset::Const32(offset as i32), max_size_hint)
+                Ok((ElementKind::Active { table, offset }, max_size_hint))
             };
-            Ok((ElementKind::Active { table, offset }, max_size_hint))
-        };
 
         type GenElemSegment<'a> =
             dyn Fn(&mut Unstructured) -> Result<(ElementKind, Option<u32>)> + 'a;
@@ -1067,14 +1134,19 @@ impl Module {
                 &mut externrefs
             };
             let minimum = ty.minimum;
+            let table64 = ty.table64;
             // If the first table is a funcref table then it's a candidate for
             // the MVP encoding of element segments.
             if i == 0 && ty.element_type == ValType::FuncRef {
-                dst.push(Box::new(move |u| arbitrary_active_elem(u, minimum, None)));
+                dst.push(Box::new(move |u| {
+                    arbitrary_active_elem(u, minimum, None, table64)
+                }));
             }
             if self.config.bulk_memory_enabled() {
                 let idx = Some(i as u32);
-                dst.push(Box::new(move |u| arbitrary_active_elem(u, minimum, idx)));
+                dst.push(Box::new(move |u| {
+                    arbitrary_active_elem(u, minimum, idx, table64)
+                }));
             }
         }
 
@@ -1167,10 +1239,15 @@ impl Module {
         allocs: &mut CodeBuilderAllocations,
         allow_invalid: bool,
     ) -> Result<Code> {
-        let mut locals = self.arbitrary_locals(u)?;
+        let mut locals = match self.arbitrary_pathological_locals(u, ty)? {
+            Some(locals) => locals,
+            None => self.arbitrary_locals(u)?,
+        };
         let builder = allocs.builder(ty, &mut locals);
         let instructions = if allow_invalid && u.arbitrary().unwrap_or(false) {
             Instructions::Arbitrary(arbitrary_vec_u8(u)?)
+        } else if let Some(instrs) = self.arbitrary_pathological_body(u)? {
+            Instructions::Generated(instrs)
         } else {
             Instructions::Generated(builder.arbitrary(u, self)?)
         };
@@ -1301,19 +1378,6 @@ impl Module {
     }
 }
 
-pub(crate) fn arbitrary_limits32(
-    u: &mut Unstructured,
-    max_minimum: u32,
-    max_required: bool,
-    max_inbounds: u32,
-) -> Result<(u32, Option<u32>)> {
-    let (min, max) = arbitrary_limits64(u, max_minimum.into(), max_required, max_inbounds.into())?;
-    Ok((
-        u32::try_from(min).unwrap(),
-        max.map(|i| u32::try_from(i).unwrap()),
-    ))
-}
-
 pub(crate) fn arbitrary_limits64(
     u: &mut Unstructured,
     max_minimum: u64,
@@ -1372,11 +1436,13 @@ pub(crate) fn arbitrary_table_type(u: &mut Unstructured, config: &dyn Config) ->
     // keep the "inbounds" limit here a bit smaller.
     let max_inbounds = 10_000;
     let max_elements = config.max_table_elements();
-    let (minimum, maximum) = arbitrary_limits32(
+    // 64-bit tables are part of the memory64 proposal.
+    let table64 = config.memory64_enabled() && u.arbitrary()?;
+    let (minimum, maximum) = arbitrary_limits64(
         u,
-        max_elements,
+        max_elements.into(),
         config.table_max_size_required(),
-        max_inbounds.min(max_elements),
+        max_inbounds.min(max_elements).into(),
     )?;
     Ok(TableType {
         element_type: if config.reference_types_enabled() {
@@ -1384,6 +1450,7 @@ pub(crate) fn arbitrary_table_type(u: &mut Unstructured, config: &dyn Config) ->
         } else {
             ValType::FuncRef
         },
+        table64,
         minimum,
         maximum,
     })
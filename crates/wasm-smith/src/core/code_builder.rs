@@ -1,11 +1,12 @@
 use super::{
-    Elements, FuncType, GlobalInitExpr, Instruction, InstructionKind::*, InstructionKinds, Module,
-    ValType,
+    Elements, FuncType, GlobalInitExpr, Instruction, InstructionKind, InstructionKind::*,
+    InstructionKinds, Module, ValType,
 };
 use arbitrary::{Result, Unstructured};
+use flagset::{FlagSet, Flags};
 use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryFrom;
-use wasm_encoder::{BlockType, MemArg};
+use wasm_encoder::{BlockType, MemArg, TableType};
 
 macro_rules! instructions {
 	(
@@ -36,10 +37,13 @@ macro_rules! instructions {
             // the `corpus` benchmark.
             $(
                 let predicate: Option<fn(&Module, &mut CodeBuilder) -> bool> = $predicate;
-                if predicate.map_or(true, |f| f(module, builder))
+                let weight = builder.allocs.instruction_kind_weights
+                    [FlagSet::from($instruction_kind).bits().trailing_zeros() as usize];
+                if weight > 0
+                    && predicate.map_or(true, |f| f(module, builder))
                     && allowed_instructions.contains($instruction_kind) {
                     builder.allocs.options.push(($generator_fn, cost));
-                    cost += 1000 $(- $cost)?;
+                    cost += (1000 $(- $cost)?) * weight;
                 }
             )*
 
@@ -104,6 +108,8 @@ instructions! {
     (Some(return_valid), r#return, Control, 900),
     (Some(call_valid), call, Control),
     (Some(call_indirect_valid), call_indirect, Control),
+    (Some(return_call_valid), return_call, Control, 900),
+    (Some(return_call_indirect_valid), return_call_indirect, Control, 900),
     (Some(throw_valid), throw, Control, 850),
     (Some(rethrow_valid), rethrow, Control),
     // Parametric instructions.
@@ -145,6 +151,74 @@ instructions! {
     (Some(data_drop_valid), data_drop, Memory),
     (Some(memory_copy_valid), memory_copy, Memory),
     (Some(memory_fill_valid), memory_fill, Memory),
+    // Atomic instructions (the threads proposal).
+    (Some(atomic_fence_valid), atomic_fence, Memory),
+    (Some(memory_atomic_notify_valid), memory_atomic_notify, Memory),
+    (Some(memory_atomic_wait32_valid), memory_atomic_wait32, Memory),
+    (Some(memory_atomic_wait64_valid), memory_atomic_wait64, Memory),
+    (Some(atomic_load_valid), i32_atomic_load, Memory),
+    (Some(atomic_load_valid), i64_atomic_load, Memory),
+    (Some(atomic_load_valid), i32_atomic_load_8_u, Memory),
+    (Some(atomic_load_valid), i32_atomic_load_16_u, Memory),
+    (Some(atomic_load_valid), i64_atomic_load_8_u, Memory),
+    (Some(atomic_load_valid), i64_atomic_load_16_u, Memory),
+    (Some(atomic_load_valid), i64_atomic_load_32_u, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_store, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_store, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_store_8, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_store_16, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_store_8, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_store_16, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_store_32, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw_add, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw_add, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw8_add_u, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw16_add_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw8_add_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw16_add_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw32_add_u, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw_sub, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw_sub, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw8_sub_u, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw16_sub_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw8_sub_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw16_sub_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw32_sub_u, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw_and, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw_and, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw8_and_u, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw16_and_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw8_and_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw16_and_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw32_and_u, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw_or, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw_or, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw8_or_u, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw16_or_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw8_or_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw16_or_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw32_or_u, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw_xor, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw_xor, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw8_xor_u, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw16_xor_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw8_xor_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw16_xor_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw32_xor_u, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw_xchg, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw_xchg, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw8_xchg_u, Memory),
+    (Some(i32_atomic_store_valid), i32_atomic_rmw16_xchg_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw8_xchg_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw16_xchg_u, Memory),
+    (Some(i64_atomic_store_valid), i64_atomic_rmw32_xchg_u, Memory),
+    (Some(i32_atomic_cmpxchg_valid), i32_atomic_rmw_cmpxchg, Memory),
+    (Some(i64_atomic_cmpxchg_valid), i64_atomic_rmw_cmpxchg, Memory),
+    (Some(i32_atomic_cmpxchg_valid), i32_atomic_rmw8_cmpxchg_u, Memory),
+    (Some(i32_atomic_cmpxchg_valid), i32_atomic_rmw16_cmpxchg_u, Memory),
+    (Some(i64_atomic_cmpxchg_valid), i64_atomic_rmw8_cmpxchg_u, Memory),
+    (Some(i64_atomic_cmpxchg_valid), i64_atomic_rmw16_cmpxchg_u, Memory),
+    (Some(i64_atomic_cmpxchg_valid), i64_atomic_rmw32_cmpxchg_u, Memory),
     // Numeric instructions.
     (None, i32_const, Numeric),
     (None, i64_const, Numeric),
@@ -540,18 +614,18 @@ instructions! {
     (Some(simd_v128_on_stack), f64x2_convert_low_i32x4u, Vector),
     (Some(simd_v128_on_stack), f32x4_demote_f64x2_zero, Vector),
     (Some(simd_v128_on_stack), f64x2_promote_low_f32x4, Vector),
-    (Some(simd_v128_on_stack_relaxed), i32x4_relaxed_trunc_sat_f32x4s, Vector),
-    (Some(simd_v128_on_stack_relaxed), i32x4_relaxed_trunc_sat_f32x4u, Vector),
-    (Some(simd_v128_on_stack_relaxed), i32x4_relaxed_trunc_sat_f64x2s_zero, Vector),
-    (Some(simd_v128_on_stack_relaxed), i32x4_relaxed_trunc_sat_f64x2u_zero, Vector),
-    (Some(simd_v128_v128_v128_on_stack_relaxed), f32x4_fma, Vector),
-    (Some(simd_v128_v128_v128_on_stack_relaxed), f32x4_fms, Vector),
-    (Some(simd_v128_v128_v128_on_stack_relaxed), f64x2_fma, Vector),
-    (Some(simd_v128_v128_v128_on_stack_relaxed), f64x2_fms, Vector),
-    (Some(simd_v128_v128_on_stack_relaxed), f32x4_relaxed_min, Vector),
-    (Some(simd_v128_v128_on_stack_relaxed), f32x4_relaxed_max, Vector),
-    (Some(simd_v128_v128_on_stack_relaxed), f64x2_relaxed_min, Vector),
-    (Some(simd_v128_v128_on_stack_relaxed), f64x2_relaxed_max, Vector),
+    (Some(simd_v128_on_stack_relaxed_nondeterministic), i32x4_relaxed_trunc_sat_f32x4s, Vector),
+    (Some(simd_v128_on_stack_relaxed_nondeterministic), i32x4_relaxed_trunc_sat_f32x4u, Vector),
+    (Some(simd_v128_on_stack_relaxed_nondeterministic), i32x4_relaxed_trunc_sat_f64x2s_zero, Vector),
+    (Some(simd_v128_on_stack_relaxed_nondeterministic), i32x4_relaxed_trunc_sat_f64x2u_zero, Vector),
+    (Some(simd_v128_v128_v128_on_stack_relaxed_nondeterministic), f32x4_fma, Vector),
+    (Some(simd_v128_v128_v128_on_stack_relaxed_nondeterministic), f32x4_fms, Vector),
+    (Some(simd_v128_v128_v128_on_stack_relaxed_nondeterministic), f64x2_fma, Vector),
+    (Some(simd_v128_v128_v128_on_stack_relaxed_nondeterministic), f64x2_fms, Vector),
+    (Some(simd_v128_v128_on_stack_relaxed_nondeterministic), f32x4_relaxed_min, Vector),
+    (Some(simd_v128_v128_on_stack_relaxed_nondeterministic), f32x4_relaxed_max, Vector),
+    (Some(simd_v128_v128_on_stack_relaxed_nondeterministic), f64x2_relaxed_min, Vector),
+    (Some(simd_v128_v128_on_stack_relaxed_nondeterministic), f64x2_relaxed_max, Vector),
 }
 
 pub(crate) struct CodeBuilderAllocations {
@@ -568,6 +642,10 @@ pub(crate) struct CodeBuilderAllocations {
         u32,
     )>,
 
+    // The weight of each kind of instruction, indexed by the kind's position
+    // in `InstructionKind::LIST` (which is also the position of its bit).
+    instruction_kind_weights: Vec<u32>,
+
     // Cached information about the module that we're generating functions for,
     // used to speed up validity checks. The mutable globals map is a map of the
     // type of global to the global indices which have that type (and they're
@@ -725,6 +803,10 @@ impl CodeBuilderAllocations {
             controls: Vec::with_capacity(4),
             operands: Vec::with_capacity(16),
             options: Vec::with_capacity(NUM_OPTIONS),
+            instruction_kind_weights: InstructionKind::LIST
+                .iter()
+                .map(|kind| module.config.instruction_kind_weight(*kind).min(1000))
+                .collect(),
             functions,
             tags,
             mutable_globals,
@@ -846,6 +928,9 @@ impl CodeBuilder<'_> {
             match choose_instruction(u, module, allowed_instructions, &mut self) {
                 Some(f) => {
                     let inst = f(u, module, &mut self)?;
+                    if module.config.relaxed_simd_deterministic_only() {
+                        Self::make_relaxed_deterministic(&inst, &mut instructions);
+                    }
                     instructions.push(inst);
                 }
                 // Choosing an instruction can fail because there is not enough
@@ -924,6 +1009,40 @@ impl CodeBuilder<'_> {
         Ok(instructions)
     }
 
+    /// Constrain the operands of a relaxed SIMD instruction, just before it is
+    /// pushed, to the inputs for which its result is fully specified.
+    fn make_relaxed_deterministic(inst: &Instruction, ins: &mut Vec<Instruction>) {
+        // Returns an `i128` with every `bits`-wide lane set to `lane`.
+        fn splat(lane: i128, bits: u32) -> i128 {
+            (0..128 / bits).fold(0, |acc, i| acc | (lane << (i * bits)))
+        }
+
+        match inst {
+            // Lane indices of 16 or more are implementation-defined, so keep
+            // only the low four bits of each index.
+            Instruction::I8x16RelaxedSwizzle => {
+                ins.push(Instruction::V128Const(splat(0x0f, 8)));
+                ins.push(Instruction::V128And);
+            }
+            // Masks whose lanes are not all ones or all zeros are
+            // implementation-defined, so turn each lane of the mask into its
+            // sign.
+            Instruction::I8x16LaneSelect
+            | Instruction::I16x8LaneSelect
+            | Instruction::I32x4LaneSelect
+            | Instruction::I64x2LaneSelect => {
+                ins.push(Instruction::V128Const(0));
+                ins.push(match inst {
+                    Instruction::I8x16LaneSelect => Instruction::I8x16LtS,
+                    Instruction::I16x8LaneSelect => Instruction::I16x8LtS,
+                    Instruction::I32x4LaneSelect => Instruction::I32x4LtS,
+                    _ => Instruction::I64x2LtS,
+                });
+            }
+            _ => {}
+        }
+    }
+
     fn canonicalize_nan(&mut self, ty: Float, ins: &mut Vec<Instruction>) {
         // We'll need to temporarily save the top of the stack into a local, so
         // figure out that local here. Note that this tries to use the same
@@ -1439,15 +1558,9 @@ fn call(u: &mut Unstructured, module: &Module, builder: &mut CodeBuilder) -> Res
 
 #[inline]
 fn call_indirect_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
-    if builder.allocs.funcref_tables.is_empty() || !builder.type_on_stack(ValType::I32) {
-        return false;
-    }
-    let ty = builder.allocs.operands.pop().unwrap();
-    let is_valid = module
-        .func_types()
-        .any(|(_, ty)| builder.types_on_stack(&ty.params));
-    builder.allocs.operands.push(ty);
-    is_valid
+    [ValType::I32, ValType::I64]
+        .into_iter()
+        .any(|index_ty| call_indirect_index_type_valid(module, builder, index_ty, false))
 }
 
 fn call_indirect(
@@ -1455,7 +1568,12 @@ fn call_indirect(
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    builder.pop_operands(&[ValType::I32]);
+    let index_types = [ValType::I32, ValType::I64]
+        .into_iter()
+        .filter(|index_ty| call_indirect_index_type_valid(module, builder, *index_ty, false))
+        .collect::<Vec<_>>();
+    let index_ty = *u.choose(&index_types)?;
+    builder.pop_operands(&[index_ty]);
 
     let choices = module
         .func_types()
@@ -1464,13 +1582,117 @@ fn call_indirect(
     let (type_idx, ty) = u.choose(&choices)?;
     builder.pop_operands(&ty.params);
     builder.push_operands(&ty.results);
-    let table = *u.choose(&builder.allocs.funcref_tables)?;
+    let table = funcref_table_index(u, module, builder, index_ty)?;
     Ok(Instruction::CallIndirect {
         ty: *type_idx as u32,
         table,
     })
 }
 
+/// Can a `call_indirect`, or a `return_call_indirect` if `return_call` is
+/// set, go through a funcref table indexed by `index_ty` with the operands on
+/// the stack?
+fn call_indirect_index_type_valid(
+    module: &Module,
+    builder: &mut CodeBuilder,
+    index_ty: ValType,
+    return_call: bool,
+) -> bool {
+    let has_table = builder
+        .allocs
+        .funcref_tables
+        .iter()
+        .any(|t| table_index_type(&module.tables[*t as usize]) == index_ty);
+    if !has_table || !builder.type_on_stack(index_ty) {
+        return false;
+    }
+    let ty = builder.allocs.operands.pop().unwrap();
+    let results = &builder.allocs.controls[0].results;
+    let is_valid = module.func_types().any(|(_, ty)| {
+        (!return_call || ty.results == *results) && builder.types_on_stack(&ty.params)
+    });
+    builder.allocs.operands.push(ty);
+    is_valid
+}
+
+fn funcref_table_index(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &CodeBuilder,
+    index_ty: ValType,
+) -> Result<u32> {
+    let tables = builder
+        .allocs
+        .funcref_tables
+        .iter()
+        .copied()
+        .filter(|t| table_index_type(&module.tables[*t as usize]) == index_ty)
+        .collect::<Vec<_>>();
+    Ok(*u.choose(&tables)?)
+}
+
+#[inline]
+fn return_call_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    if !module.config.tail_call_enabled() {
+        return false;
+    }
+    let results = &builder.allocs.controls[0].results;
+    module
+        .funcs()
+        .any(|(_, ty)| ty.results == *results && builder.types_on_stack(&ty.params))
+}
+
+fn return_call(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let results = &builder.allocs.controls[0].results;
+    let candidates = module
+        .funcs()
+        .filter(|(_, ty)| ty.results == *results && builder.types_on_stack(&ty.params))
+        .collect::<Vec<_>>();
+    let (func_idx, ty) = u.choose(&candidates)?;
+    builder.pop_operands(&ty.params);
+    Ok(Instruction::ReturnCall(*func_idx))
+}
+
+#[inline]
+fn return_call_indirect_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.tail_call_enabled()
+        && [ValType::I32, ValType::I64]
+            .into_iter()
+            .any(|index_ty| call_indirect_index_type_valid(module, builder, index_ty, true))
+}
+
+fn return_call_indirect(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let index_types = [ValType::I32, ValType::I64]
+        .into_iter()
+        .filter(|index_ty| call_indirect_index_type_valid(module, builder, *index_ty, true))
+        .collect::<Vec<_>>();
+    let index_ty = *u.choose(&index_types)?;
+    builder.pop_operands(&[index_ty]);
+
+    // Any type index with the caller's results will do, including ones other
+    // than the caller's own type index.
+    let results = &builder.allocs.controls[0].results;
+    let choices = module
+        .func_types()
+        .filter(|(_, ty)| ty.results == *results && builder.types_on_stack(&ty.params))
+        .collect::<Vec<_>>();
+    let (type_idx, ty) = u.choose(&choices)?;
+    builder.pop_operands(&ty.params);
+    let table = funcref_table_index(u, module, builder, index_ty)?;
+    Ok(Instruction::ReturnCallIndirect {
+        ty: *type_idx,
+        table,
+    })
+}
+
 #[inline]
 fn throw_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.exceptions_enabled()
@@ -1760,283 +1982,1058 @@ fn i32_load_16_s(
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    let memarg = mem_arg(u, module, builder, &[0, 1])?;
-    builder.allocs.operands.push(Some(ValType::I32));
-    Ok(Instruction::I32Load16_S(memarg))
+    let memarg = mem_arg(u, module, builder, &[0, 1])?;
+    builder.allocs.operands.push(Some(ValType::I32));
+    Ok(Instruction::I32Load16_S(memarg))
+}
+
+fn i32_load_16_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let memarg = mem_arg(u, module, builder, &[0, 1])?;
+    builder.allocs.operands.push(Some(ValType::I32));
+    Ok(Instruction::I32Load16_U(memarg))
+}
+
+fn i64_load_8_s(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let memarg = mem_arg(u, module, builder, &[0])?;
+    builder.allocs.operands.push(Some(ValType::I64));
+    Ok(Instruction::I64Load8_S(memarg))
+}
+
+fn i64_load_16_s(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let memarg = mem_arg(u, module, builder, &[0, 1])?;
+    builder.allocs.operands.push(Some(ValType::I64));
+    Ok(Instruction::I64Load16_S(memarg))
+}
+
+fn i64_load_32_s(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let memarg = mem_arg(u, module, builder, &[0, 1, 2])?;
+    builder.allocs.operands.push(Some(ValType::I64));
+    Ok(Instruction::I64Load32_S(memarg))
+}
+
+fn i64_load_8_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let memarg = mem_arg(u, module, builder, &[0])?;
+    builder.allocs.operands.push(Some(ValType::I64));
+    Ok(Instruction::I64Load8_U(memarg))
+}
+
+fn i64_load_16_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let memarg = mem_arg(u, module, builder, &[0, 1])?;
+    builder.allocs.operands.push(Some(ValType::I64));
+    Ok(Instruction::I64Load16_U(memarg))
+}
+
+fn i64_load_32_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let memarg = mem_arg(u, module, builder, &[0, 1, 2])?;
+    builder.allocs.operands.push(Some(ValType::I64));
+    Ok(Instruction::I64Load32_U(memarg))
+}
+
+#[inline]
+fn store_valid(_module: &Module, builder: &mut CodeBuilder, f: impl Fn() -> ValType) -> bool {
+    (builder.allocs.memory32.len() > 0 && builder.types_on_stack(&[ValType::I32, f()]))
+        || (builder.allocs.memory64.len() > 0 && builder.types_on_stack(&[ValType::I64, f()]))
+}
+
+#[inline]
+fn i32_store_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    store_valid(module, builder, || ValType::I32)
+}
+
+fn i32_store(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = mem_arg(u, module, builder, &[0, 1, 2])?;
+    Ok(Instruction::I32Store(memarg))
+}
+
+#[inline]
+fn i64_store_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    store_valid(module, builder, || ValType::I64)
+}
+
+fn i64_store(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = mem_arg(u, module, builder, &[0, 1, 2, 3])?;
+    Ok(Instruction::I64Store(memarg))
+}
+
+#[inline]
+fn f32_store_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    store_valid(module, builder, || ValType::F32)
+}
+
+fn f32_store(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::F32]);
+    let memarg = mem_arg(u, module, builder, &[0, 1, 2])?;
+    Ok(Instruction::F32Store(memarg))
+}
+
+#[inline]
+fn f64_store_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    store_valid(module, builder, || ValType::F64)
+}
+
+fn f64_store(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::F64]);
+    let memarg = mem_arg(u, module, builder, &[0, 1, 2, 3])?;
+    Ok(Instruction::F64Store(memarg))
+}
+
+fn i32_store_8(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = mem_arg(u, module, builder, &[0])?;
+    Ok(Instruction::I32Store8(memarg))
+}
+
+fn i32_store_16(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = mem_arg(u, module, builder, &[0, 1])?;
+    Ok(Instruction::I32Store16(memarg))
+}
+
+fn i64_store_8(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = mem_arg(u, module, builder, &[0])?;
+    Ok(Instruction::I64Store8(memarg))
+}
+
+fn i64_store_16(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = mem_arg(u, module, builder, &[0, 1])?;
+    Ok(Instruction::I64Store16(memarg))
+}
+
+fn i64_store_32(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = mem_arg(u, module, builder, &[0, 1, 2])?;
+    Ok(Instruction::I64Store32(memarg))
+}
+
+fn memory_size(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let i = u.int_in_range(0..=module.memories.len() - 1)?;
+    let ty = if module.memories[i].memory64 {
+        ValType::I64
+    } else {
+        ValType::I32
+    };
+    builder.push_operands(&[ty]);
+    Ok(Instruction::MemorySize(i as u32))
+}
+
+#[inline]
+fn memory_grow_valid(_module: &Module, builder: &mut CodeBuilder) -> bool {
+    (builder.allocs.memory32.len() > 0 && builder.type_on_stack(ValType::I32))
+        || (builder.allocs.memory64.len() > 0 && builder.type_on_stack(ValType::I64))
+}
+
+fn memory_grow(
+    u: &mut Unstructured,
+    _module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let ty = if builder.type_on_stack(ValType::I32) {
+        ValType::I32
+    } else {
+        ValType::I64
+    };
+    let index = memory_index(u, builder, ty)?;
+    builder.pop_operands(&[ty]);
+    builder.push_operands(&[ty]);
+    Ok(Instruction::MemoryGrow(index))
+}
+
+#[inline]
+fn memory_init_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.bulk_memory_enabled()
+        && have_data(module, builder)
+        && (builder.allocs.memory32.len() > 0
+            && builder.types_on_stack(&[ValType::I32, ValType::I32, ValType::I32])
+            || (builder.allocs.memory64.len() > 0
+                && builder.types_on_stack(&[ValType::I64, ValType::I32, ValType::I32])))
+}
+
+fn memory_init(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32, ValType::I32]);
+    let ty = if builder.type_on_stack(ValType::I32) {
+        ValType::I32
+    } else {
+        ValType::I64
+    };
+    let mem = memory_index(u, builder, ty)?;
+    let data = data_index(u, module)?;
+    builder.pop_operands(&[ty]);
+    Ok(Instruction::MemoryInit { mem, data })
+}
+
+#[inline]
+fn memory_fill_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.bulk_memory_enabled()
+        && (builder.allocs.memory32.len() > 0
+            && builder.types_on_stack(&[ValType::I32, ValType::I32, ValType::I32])
+            || (builder.allocs.memory64.len() > 0
+                && builder.types_on_stack(&[ValType::I64, ValType::I32, ValType::I64])))
+}
+
+fn memory_fill(
+    u: &mut Unstructured,
+    _module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let ty = if builder.type_on_stack(ValType::I32) {
+        ValType::I32
+    } else {
+        ValType::I64
+    };
+    let mem = memory_index(u, builder, ty)?;
+    builder.pop_operands(&[ty, ValType::I32, ty]);
+    Ok(Instruction::MemoryFill(mem))
+}
+
+#[inline]
+fn atomic_fence_valid(module: &Module, _: &mut CodeBuilder) -> bool {
+    module.config.threads_enabled()
+}
+
+fn atomic_fence(_: &mut Unstructured, _: &Module, _: &mut CodeBuilder) -> Result<Instruction> {
+    Ok(Instruction::AtomicFence)
+}
+
+#[inline]
+fn memory_atomic_notify_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.threads_enabled() && store_valid(module, builder, || ValType::I32)
+}
+
+fn memory_atomic_notify(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::MemoryAtomicNotify { memarg })
+}
+
+#[inline]
+fn memory_atomic_wait32_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.threads_enabled() && atomic_rmw_valid(builder, &[ValType::I32, ValType::I64])
+}
+
+fn memory_atomic_wait32(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32, ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::MemoryAtomicWait32 { memarg })
+}
+
+#[inline]
+fn memory_atomic_wait64_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.threads_enabled() && atomic_rmw_valid(builder, &[ValType::I64, ValType::I64])
+}
+
+fn memory_atomic_wait64(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64, ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 3)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::MemoryAtomicWait64 { memarg })
+}
+
+#[inline]
+fn atomic_load_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.threads_enabled() && have_memory_and_offset(module, builder)
+}
+
+fn i32_atomic_load(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicLoad { memarg })
+}
+
+fn i64_atomic_load(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let memarg = atomic_mem_arg(u, module, builder, 3)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicLoad { memarg })
+}
+
+fn i32_atomic_load_8_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicLoad8U { memarg })
+}
+
+fn i32_atomic_load_16_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicLoad16U { memarg })
+}
+
+fn i64_atomic_load_8_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicLoad8U { memarg })
+}
+
+fn i64_atomic_load_16_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicLoad16U { memarg })
+}
+
+fn i64_atomic_load_32_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicLoad32U { memarg })
+}
+
+#[inline]
+fn i32_atomic_store_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.threads_enabled() && store_valid(module, builder, || ValType::I32)
+}
+
+#[inline]
+fn i64_atomic_store_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.threads_enabled() && store_valid(module, builder, || ValType::I64)
+}
+
+fn i32_atomic_store(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    Ok(Instruction::I32AtomicStore { memarg })
+}
+
+fn i64_atomic_store(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 3)?;
+    Ok(Instruction::I64AtomicStore { memarg })
+}
+
+fn i32_atomic_store_8(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    Ok(Instruction::I32AtomicStore8 { memarg })
+}
+
+fn i32_atomic_store_16(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    Ok(Instruction::I32AtomicStore16 { memarg })
+}
+
+fn i64_atomic_store_8(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    Ok(Instruction::I64AtomicStore8 { memarg })
+}
+
+fn i64_atomic_store_16(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    Ok(Instruction::I64AtomicStore16 { memarg })
+}
+
+fn i64_atomic_store_32(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    Ok(Instruction::I64AtomicStore32 { memarg })
+}
+
+#[inline]
+fn i32_atomic_cmpxchg_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.threads_enabled() && atomic_rmw_valid(builder, &[ValType::I32, ValType::I32])
+}
+
+#[inline]
+fn i64_atomic_cmpxchg_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.threads_enabled() && atomic_rmw_valid(builder, &[ValType::I64, ValType::I64])
+}
+
+fn i32_atomic_rmw_add(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmwAdd { memarg })
+}
+
+fn i64_atomic_rmw_add(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 3)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmwAdd { memarg })
+}
+
+fn i32_atomic_rmw8_add_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmw8AddU { memarg })
+}
+
+fn i32_atomic_rmw16_add_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmw16AddU { memarg })
+}
+
+fn i64_atomic_rmw8_add_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw8AddU { memarg })
+}
+
+fn i64_atomic_rmw16_add_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw16AddU { memarg })
+}
+
+fn i64_atomic_rmw32_add_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw32AddU { memarg })
+}
+
+fn i32_atomic_rmw_sub(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmwSub { memarg })
+}
+
+fn i64_atomic_rmw_sub(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 3)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmwSub { memarg })
+}
+
+fn i32_atomic_rmw8_sub_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmw8SubU { memarg })
+}
+
+fn i32_atomic_rmw16_sub_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmw16SubU { memarg })
+}
+
+fn i64_atomic_rmw8_sub_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw8SubU { memarg })
+}
+
+fn i64_atomic_rmw16_sub_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw16SubU { memarg })
+}
+
+fn i64_atomic_rmw32_sub_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw32SubU { memarg })
+}
+
+fn i32_atomic_rmw_and(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmwAnd { memarg })
+}
+
+fn i64_atomic_rmw_and(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 3)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmwAnd { memarg })
+}
+
+fn i32_atomic_rmw8_and_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmw8AndU { memarg })
+}
+
+fn i32_atomic_rmw16_and_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmw16AndU { memarg })
+}
+
+fn i64_atomic_rmw8_and_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw8AndU { memarg })
+}
+
+fn i64_atomic_rmw16_and_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw16AndU { memarg })
+}
+
+fn i64_atomic_rmw32_and_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw32AndU { memarg })
+}
+
+fn i32_atomic_rmw_or(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmwOr { memarg })
+}
+
+fn i64_atomic_rmw_or(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 3)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmwOr { memarg })
+}
+
+fn i32_atomic_rmw8_or_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmw8OrU { memarg })
+}
+
+fn i32_atomic_rmw16_or_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmw16OrU { memarg })
+}
+
+fn i64_atomic_rmw8_or_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw8OrU { memarg })
 }
 
-fn i32_load_16_u(
+fn i64_atomic_rmw16_or_u(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    let memarg = mem_arg(u, module, builder, &[0, 1])?;
-    builder.allocs.operands.push(Some(ValType::I32));
-    Ok(Instruction::I32Load16_U(memarg))
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw16OrU { memarg })
 }
 
-fn i64_load_8_s(
+fn i64_atomic_rmw32_or_u(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    let memarg = mem_arg(u, module, builder, &[0])?;
-    builder.allocs.operands.push(Some(ValType::I64));
-    Ok(Instruction::I64Load8_S(memarg))
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw32OrU { memarg })
 }
 
-fn i64_load_16_s(
+fn i32_atomic_rmw_xor(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    let memarg = mem_arg(u, module, builder, &[0, 1])?;
-    builder.allocs.operands.push(Some(ValType::I64));
-    Ok(Instruction::I64Load16_S(memarg))
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmwXor { memarg })
 }
 
-fn i64_load_32_s(
+fn i64_atomic_rmw_xor(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    let memarg = mem_arg(u, module, builder, &[0, 1, 2])?;
-    builder.allocs.operands.push(Some(ValType::I64));
-    Ok(Instruction::I64Load32_S(memarg))
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 3)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmwXor { memarg })
 }
 
-fn i64_load_8_u(
+fn i32_atomic_rmw8_xor_u(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    let memarg = mem_arg(u, module, builder, &[0])?;
-    builder.allocs.operands.push(Some(ValType::I64));
-    Ok(Instruction::I64Load8_U(memarg))
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmw8XorU { memarg })
 }
 
-fn i64_load_16_u(
+fn i32_atomic_rmw16_xor_u(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    let memarg = mem_arg(u, module, builder, &[0, 1])?;
-    builder.allocs.operands.push(Some(ValType::I64));
-    Ok(Instruction::I64Load16_U(memarg))
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmw16XorU { memarg })
 }
 
-fn i64_load_32_u(
+fn i64_atomic_rmw8_xor_u(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    let memarg = mem_arg(u, module, builder, &[0, 1, 2])?;
-    builder.allocs.operands.push(Some(ValType::I64));
-    Ok(Instruction::I64Load32_U(memarg))
-}
-
-#[inline]
-fn store_valid(_module: &Module, builder: &mut CodeBuilder, f: impl Fn() -> ValType) -> bool {
-    (builder.allocs.memory32.len() > 0 && builder.types_on_stack(&[ValType::I32, f()]))
-        || (builder.allocs.memory64.len() > 0 && builder.types_on_stack(&[ValType::I64, f()]))
-}
-
-#[inline]
-fn i32_store_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
-    store_valid(module, builder, || ValType::I32)
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw8XorU { memarg })
 }
 
-fn i32_store(
+fn i64_atomic_rmw16_xor_u(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    builder.pop_operands(&[ValType::I32]);
-    let memarg = mem_arg(u, module, builder, &[0, 1, 2])?;
-    Ok(Instruction::I32Store(memarg))
-}
-
-#[inline]
-fn i64_store_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
-    store_valid(module, builder, || ValType::I64)
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw16XorU { memarg })
 }
 
-fn i64_store(
+fn i64_atomic_rmw32_xor_u(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
     builder.pop_operands(&[ValType::I64]);
-    let memarg = mem_arg(u, module, builder, &[0, 1, 2, 3])?;
-    Ok(Instruction::I64Store(memarg))
-}
-
-#[inline]
-fn f32_store_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
-    store_valid(module, builder, || ValType::F32)
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw32XorU { memarg })
 }
 
-fn f32_store(
+fn i32_atomic_rmw_xchg(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    builder.pop_operands(&[ValType::F32]);
-    let memarg = mem_arg(u, module, builder, &[0, 1, 2])?;
-    Ok(Instruction::F32Store(memarg))
-}
-
-#[inline]
-fn f64_store_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
-    store_valid(module, builder, || ValType::F64)
+    builder.pop_operands(&[ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmwXchg { memarg })
 }
 
-fn f64_store(
+fn i64_atomic_rmw_xchg(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    builder.pop_operands(&[ValType::F64]);
-    let memarg = mem_arg(u, module, builder, &[0, 1, 2, 3])?;
-    Ok(Instruction::F64Store(memarg))
+    builder.pop_operands(&[ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 3)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmwXchg { memarg })
 }
 
-fn i32_store_8(
+fn i32_atomic_rmw8_xchg_u(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
     builder.pop_operands(&[ValType::I32]);
-    let memarg = mem_arg(u, module, builder, &[0])?;
-    Ok(Instruction::I32Store8(memarg))
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmw8XchgU { memarg })
 }
 
-fn i32_store_16(
+fn i32_atomic_rmw16_xchg_u(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
     builder.pop_operands(&[ValType::I32]);
-    let memarg = mem_arg(u, module, builder, &[0, 1])?;
-    Ok(Instruction::I32Store16(memarg))
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmw16XchgU { memarg })
 }
 
-fn i64_store_8(
+fn i64_atomic_rmw8_xchg_u(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
     builder.pop_operands(&[ValType::I64]);
-    let memarg = mem_arg(u, module, builder, &[0])?;
-    Ok(Instruction::I64Store8(memarg))
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw8XchgU { memarg })
 }
 
-fn i64_store_16(
+fn i64_atomic_rmw16_xchg_u(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
     builder.pop_operands(&[ValType::I64]);
-    let memarg = mem_arg(u, module, builder, &[0, 1])?;
-    Ok(Instruction::I64Store16(memarg))
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw16XchgU { memarg })
 }
 
-fn i64_store_32(
+fn i64_atomic_rmw32_xchg_u(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
     builder.pop_operands(&[ValType::I64]);
-    let memarg = mem_arg(u, module, builder, &[0, 1, 2])?;
-    Ok(Instruction::I64Store32(memarg))
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw32XchgU { memarg })
 }
 
-fn memory_size(
+fn i32_atomic_rmw_cmpxchg(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    let i = u.int_in_range(0..=module.memories.len() - 1)?;
-    let ty = if module.memories[i].memory64 {
-        ValType::I64
-    } else {
-        ValType::I32
-    };
-    builder.push_operands(&[ty]);
-    Ok(Instruction::MemorySize(i as u32))
+    builder.pop_operands(&[ValType::I32, ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmwCmpxchg { memarg })
 }
 
-#[inline]
-fn memory_grow_valid(_module: &Module, builder: &mut CodeBuilder) -> bool {
-    (builder.allocs.memory32.len() > 0 && builder.type_on_stack(ValType::I32))
-        || (builder.allocs.memory64.len() > 0 && builder.type_on_stack(ValType::I64))
+fn i64_atomic_rmw_cmpxchg(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64, ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 3)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmwCmpxchg { memarg })
 }
 
-fn memory_grow(
+fn i32_atomic_rmw8_cmpxchg_u(
     u: &mut Unstructured,
-    _module: &Module,
+    module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    let ty = if builder.type_on_stack(ValType::I32) {
-        ValType::I32
-    } else {
-        ValType::I64
-    };
-    let index = memory_index(u, builder, ty)?;
-    builder.pop_operands(&[ty]);
-    builder.push_operands(&[ty]);
-    Ok(Instruction::MemoryGrow(index))
+    builder.pop_operands(&[ValType::I32, ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmw8CmpxchgU { memarg })
 }
 
-#[inline]
-fn memory_init_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
-    module.config.bulk_memory_enabled()
-        && have_data(module, builder)
-        && (builder.allocs.memory32.len() > 0
-            && builder.types_on_stack(&[ValType::I32, ValType::I32, ValType::I32])
-            || (builder.allocs.memory64.len() > 0
-                && builder.types_on_stack(&[ValType::I64, ValType::I32, ValType::I32])))
+fn i32_atomic_rmw16_cmpxchg_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I32, ValType::I32]);
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I32]);
+    Ok(Instruction::I32AtomicRmw16CmpxchgU { memarg })
 }
 
-fn memory_init(
+fn i64_atomic_rmw8_cmpxchg_u(
     u: &mut Unstructured,
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    builder.pop_operands(&[ValType::I32, ValType::I32]);
-    let ty = if builder.type_on_stack(ValType::I32) {
-        ValType::I32
-    } else {
-        ValType::I64
-    };
-    let mem = memory_index(u, builder, ty)?;
-    let data = data_index(u, module)?;
-    builder.pop_operands(&[ty]);
-    Ok(Instruction::MemoryInit { mem, data })
+    builder.pop_operands(&[ValType::I64, ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 0)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw8CmpxchgU { memarg })
 }
 
-#[inline]
-fn memory_fill_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
-    module.config.bulk_memory_enabled()
-        && (builder.allocs.memory32.len() > 0
-            && builder.types_on_stack(&[ValType::I32, ValType::I32, ValType::I32])
-            || (builder.allocs.memory64.len() > 0
-                && builder.types_on_stack(&[ValType::I64, ValType::I32, ValType::I64])))
+fn i64_atomic_rmw16_cmpxchg_u(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> Result<Instruction> {
+    builder.pop_operands(&[ValType::I64, ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 1)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw16CmpxchgU { memarg })
 }
 
-fn memory_fill(
+fn i64_atomic_rmw32_cmpxchg_u(
     u: &mut Unstructured,
-    _module: &Module,
+    module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    let ty = if builder.type_on_stack(ValType::I32) {
-        ValType::I32
-    } else {
-        ValType::I64
+    builder.pop_operands(&[ValType::I64, ValType::I64]);
+    let memarg = atomic_mem_arg(u, module, builder, 2)?;
+    builder.push_operands(&[ValType::I64]);
+    Ok(Instruction::I64AtomicRmw32CmpxchgU { memarg })
+}
+
+/// Are the given operands on the stack on top of an address into one of the
+/// available memories?
+#[inline]
+fn atomic_rmw_valid(builder: &mut CodeBuilder, operands: &[ValType]) -> bool {
+    let with_address = |address| {
+        let mut types = vec![address];
+        types.extend_from_slice(operands);
+        types
     };
-    let mem = memory_index(u, builder, ty)?;
-    builder.pop_operands(&[ty, ValType::I32, ty]);
-    Ok(Instruction::MemoryFill(mem))
+    (!builder.allocs.memory32.is_empty() && builder.types_on_stack(&with_address(ValType::I32)))
+        || (!builder.allocs.memory64.is_empty()
+            && builder.types_on_stack(&with_address(ValType::I64)))
 }
 
 #[inline]
@@ -3227,6 +4224,17 @@ fn mem_arg(
     })
 }
 
+/// Like `mem_arg`, but atomic instructions must always use their natural
+/// alignment.
+fn atomic_mem_arg(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    alignment: u32,
+) -> Result<MemArg> {
+    mem_arg(u, module, builder, &[alignment])
+}
+
 fn memory_index(u: &mut Unstructured, builder: &CodeBuilder, ty: ValType) -> Result<u32> {
     if ty == ValType::I32 {
         Ok(*u.choose(&builder.allocs.memory32)?)
@@ -3283,9 +4291,9 @@ fn ref_is_null(_: &mut Unstructured, _: &Module, builder: &mut CodeBuilder) -> R
 fn table_fill_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.reference_types_enabled()
         && module.config.bulk_memory_enabled()
-        && [ValType::ExternRef, ValType::FuncRef].iter().any(|ty| {
-            builder.types_on_stack(&[ValType::I32, *ty, ValType::I32])
-                && module.tables.iter().any(|t| t.element_type == *ty)
+        && module.tables.iter().any(|t| {
+            let index_ty = table_index_type(t);
+            builder.types_on_stack(&[index_ty, t.element_type, index_ty])
         })
 }
 
@@ -3294,20 +4302,23 @@ fn table_fill(
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    builder.pop_operands(&[ValType::I32]);
-    let ty = pop_reference_type(builder);
-    builder.pop_operands(&[ValType::I32]);
-    let table = table_index(ty, u, module)?;
+    let table = table_index(u, module, |t| {
+        let index_ty = table_index_type(t);
+        builder.types_on_stack(&[index_ty, t.element_type, index_ty])
+    })?;
+    let ty = &module.tables[table as usize];
+    let index_ty = table_index_type(ty);
+    builder.pop_operands(&[index_ty, ty.element_type, index_ty]);
     Ok(Instruction::TableFill { table })
 }
 
 #[inline]
 fn table_set_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.reference_types_enabled()
-        && [ValType::ExternRef, ValType::FuncRef].iter().any(|ty| {
-            builder.types_on_stack(&[ValType::I32, *ty])
-                && module.tables.iter().any(|t| t.element_type == *ty)
-        })
+        && module
+            .tables
+            .iter()
+            .any(|t| builder.types_on_stack(&[table_index_type(t), t.element_type]))
 }
 
 fn table_set(
@@ -3315,17 +4326,21 @@ fn table_set(
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    let ty = pop_reference_type(builder);
-    builder.pop_operands(&[ValType::I32]);
-    let table = table_index(ty, u, module)?;
+    let table = table_index(u, module, |t| {
+        builder.types_on_stack(&[table_index_type(t), t.element_type])
+    })?;
+    let ty = &module.tables[table as usize];
+    builder.pop_operands(&[table_index_type(ty), ty.element_type]);
     Ok(Instruction::TableSet { table })
 }
 
 #[inline]
 fn table_get_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.reference_types_enabled()
-        && builder.type_on_stack(ValType::I32)
-        && module.tables.len() > 0
+        && module
+            .tables
+            .iter()
+            .any(|t| builder.type_on_stack(table_index_type(t)))
 }
 
 fn table_get(
@@ -3333,11 +4348,11 @@ fn table_get(
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    builder.pop_operands(&[ValType::I32]);
-    let idx = u.int_in_range(0..=module.tables.len() - 1)?;
-    let ty = module.tables[idx].element_type;
-    builder.push_operands(&[ty]);
-    Ok(Instruction::TableGet { table: idx as u32 })
+    let table = table_index(u, module, |t| builder.type_on_stack(table_index_type(t)))?;
+    let ty = &module.tables[table as usize];
+    builder.pop_operands(&[table_index_type(ty)]);
+    builder.push_operands(&[ty.element_type]);
+    Ok(Instruction::TableGet { table })
 }
 
 #[inline]
@@ -3351,17 +4366,17 @@ fn table_size(
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
     let table = u.int_in_range(0..=module.tables.len() - 1)? as u32;
-    builder.push_operands(&[ValType::I32]);
+    builder.push_operands(&[table_index_type(&module.tables[table as usize])]);
     Ok(Instruction::TableSize { table })
 }
 
 #[inline]
 fn table_grow_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.reference_types_enabled()
-        && [ValType::ExternRef, ValType::FuncRef].iter().any(|ty| {
-            builder.types_on_stack(&[*ty, ValType::I32])
-                && module.tables.iter().any(|t| t.element_type == *ty)
-        })
+        && module
+            .tables
+            .iter()
+            .any(|t| builder.types_on_stack(&[t.element_type, table_index_type(t)]))
 }
 
 fn table_grow(
@@ -3369,18 +4384,25 @@ fn table_grow(
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    builder.pop_operands(&[ValType::I32]);
-    let ty = pop_reference_type(builder);
-    let table = table_index(ty, u, module)?;
-    builder.push_operands(&[ValType::I32]);
+    let table = table_index(u, module, |t| {
+        builder.types_on_stack(&[t.element_type, table_index_type(t)])
+    })?;
+    let ty = &module.tables[table as usize];
+    let index_ty = table_index_type(ty);
+    builder.pop_operands(&[ty.element_type, index_ty]);
+    builder.push_operands(&[index_ty]);
     Ok(Instruction::TableGrow { table })
 }
 
 #[inline]
 fn table_copy_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.reference_types_enabled()
-        && module.tables.len() > 0
-        && builder.types_on_stack(&[ValType::I32, ValType::I32, ValType::I32])
+        && module.tables.iter().any(|src| {
+            module
+                .tables
+                .iter()
+                .any(|dst| table_copy_operands_on_stack(builder, src, dst))
+        })
 }
 
 fn table_copy(
@@ -3388,17 +4410,47 @@ fn table_copy(
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    builder.pop_operands(&[ValType::I32, ValType::I32, ValType::I32]);
-    let src = u.int_in_range(0..=module.tables.len() - 1)? as u32;
-    let dst = table_index(module.tables[src as usize].element_type, u, module)?;
+    let mut choices = Vec::new();
+    for (src, src_ty) in module.tables.iter().enumerate() {
+        for (dst, dst_ty) in module.tables.iter().enumerate() {
+            if table_copy_operands_on_stack(builder, src_ty, dst_ty) {
+                choices.push((src as u32, dst as u32));
+            }
+        }
+    }
+    let (src, dst) = *u.choose(&choices)?;
+    builder.pop_operands(&table_copy_operands(
+        &module.tables[src as usize],
+        &module.tables[dst as usize],
+    ));
     Ok(Instruction::TableCopy { src, dst })
 }
 
+/// The operands of a `table.copy` from `src` to `dst`: the destination and
+/// source indices, and the length, which is an `i32` if either table is
+/// 32-bit.
+fn table_copy_operands(src: &TableType, dst: &TableType) -> [ValType; 3] {
+    let src_index_ty = table_index_type(src);
+    let dst_index_ty = table_index_type(dst);
+    let len_ty = match src_index_ty {
+        ValType::I32 => ValType::I32,
+        _ => dst_index_ty,
+    };
+    [dst_index_ty, src_index_ty, len_ty]
+}
+
+fn table_copy_operands_on_stack(builder: &CodeBuilder, src: &TableType, dst: &TableType) -> bool {
+    src.element_type == dst.element_type && builder.types_on_stack(&table_copy_operands(src, dst))
+}
+
 #[inline]
 fn table_init_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.reference_types_enabled()
         && builder.allocs.table_init_possible
-        && builder.types_on_stack(&[ValType::I32, ValType::I32, ValType::I32])
+        && module.tables.iter().any(|t| {
+            builder.types_on_stack(&[table_index_type(t), ValType::I32, ValType::I32])
+                && module.elems.iter().any(|e| e.ty == t.element_type)
+        })
 }
 
 fn table_init(
@@ -3406,16 +4458,20 @@ fn table_init(
     module: &Module,
     builder: &mut CodeBuilder,
 ) -> Result<Instruction> {
-    builder.pop_operands(&[ValType::I32, ValType::I32, ValType::I32]);
+    let table = table_index(u, module, |t| {
+        builder.types_on_stack(&[table_index_type(t), ValType::I32, ValType::I32])
+            && module.elems.iter().any(|e| e.ty == t.element_type)
+    })?;
+    let ty = &module.tables[table as usize];
+    builder.pop_operands(&[table_index_type(ty), ValType::I32, ValType::I32]);
     let segments = module
         .elems
         .iter()
         .enumerate()
-        .filter(|(_, e)| module.tables.iter().any(|t| t.element_type == e.ty))
+        .filter(|(_, e)| e.ty == ty.element_type)
         .map(|(i, _)| i)
         .collect::<Vec<_>>();
     let segment = *u.choose(&segments)?;
-    let table = table_index(module.elems[segment].ty, u, module)?;
     Ok(Instruction::TableInit {
         segment: segment as u32,
         table,
@@ -3446,17 +4502,31 @@ fn pop_reference_type(builder: &mut CodeBuilder) -> ValType {
     }
 }
 
-fn table_index(ty: ValType, u: &mut Unstructured, module: &Module) -> Result<u32> {
+/// Choose one of the tables for which `f` holds.
+fn table_index(
+    u: &mut Unstructured,
+    module: &Module,
+    f: impl Fn(&TableType) -> bool,
+) -> Result<u32> {
     let tables = module
         .tables
         .iter()
         .enumerate()
-        .filter(|(_, t)| t.element_type == ty)
+        .filter(|(_, t)| f(t))
         .map(|t| t.0 as u32)
         .collect::<Vec<_>>();
     Ok(*u.choose(&tables)?)
 }
 
+/// The type of the indices into `table`.
+fn table_index_type(table: &TableType) -> ValType {
+    if table.table64 {
+        ValType::I64
+    } else {
+        ValType::I32
+    }
+}
+
 fn lane_index(u: &mut Unstructured, number_of_lanes: u8) -> Result<u8> {
     u.int_in_range(0..=(number_of_lanes - 1))
 }
@@ -3471,6 +4541,11 @@ fn simd_v128_on_stack_relaxed(module: &Module, builder: &mut CodeBuilder) -> boo
     module.config.relaxed_simd_enabled() && builder.types_on_stack(&[ValType::V128])
 }
 
+#[inline]
+fn simd_v128_on_stack_relaxed_nondeterministic(module: &Module, builder: &mut CodeBuilder) -> bool {
+    !module.config.relaxed_simd_deterministic_only() && simd_v128_on_stack_relaxed(module, builder)
+}
+
 #[inline]
 fn simd_v128_v128_on_stack(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.simd_enabled() && builder.types_on_stack(&[ValType::V128, ValType::V128])
@@ -3481,6 +4556,15 @@ fn simd_v128_v128_on_stack_relaxed(module: &Module, builder: &mut CodeBuilder) -
     module.config.relaxed_simd_enabled() && builder.types_on_stack(&[ValType::V128, ValType::V128])
 }
 
+#[inline]
+fn simd_v128_v128_on_stack_relaxed_nondeterministic(
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> bool {
+    !module.config.relaxed_simd_deterministic_only()
+        && simd_v128_v128_on_stack_relaxed(module, builder)
+}
+
 #[inline]
 fn simd_v128_v128_v128_on_stack(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.simd_enabled()
@@ -3493,6 +4577,15 @@ fn simd_v128_v128_v128_on_stack_relaxed(module: &Module, builder: &mut CodeBuild
         && builder.types_on_stack(&[ValType::V128, ValType::V128, ValType::V128])
 }
 
+#[inline]
+fn simd_v128_v128_v128_on_stack_relaxed_nondeterministic(
+    module: &Module,
+    builder: &mut CodeBuilder,
+) -> bool {
+    !module.config.relaxed_simd_deterministic_only()
+        && simd_v128_v128_v128_on_stack_relaxed(module, builder)
+}
+
 #[inline]
 fn simd_v128_i32_on_stack(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.simd_enabled() && builder.types_on_stack(&[ValType::V128, ValType::I32])
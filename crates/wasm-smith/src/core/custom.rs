@@ -0,0 +1,229 @@
+//! Generating custom sections for core Wasm modules.
+//!
+//! Besides sections with arbitrary names and contents, this generates
+//! well-formed `name`, `producers`, and `dylink.0` sections whose contents
+//! refer to the rest of the module, so that tools which interpret those
+//! sections get exercised beyond their error paths.
+
+use super::*;
+use wasm_encoder::{Encode, IndirectNameMap, NameMap, NameSection};
+
+/// The number of places between the known sections of a module where custom
+/// sections may be encoded: before the types section, between each pair of
+/// known sections, and after the data section.
+pub(super) const NUM_PLACES: usize = 14;
+
+#[derive(Debug)]
+pub(super) enum CustomSection {
+    Name(NameSection),
+    Raw { name: String, data: Vec<u8> },
+}
+
+impl Module {
+    pub(super) fn arbitrary_custom_sections(&mut self, u: &mut Unstructured) -> Result<()> {
+        if !self.config.generate_custom_sections() {
+            return Ok(());
+        }
+
+        // By convention `dylink.0` is the very first section of a module, and
+        // the `name` and `producers` sections come after the data section.
+        if u.arbitrary()? {
+            let section = self.arbitrary_dylink0_section(u)?;
+            self.custom_sections.push((0, section));
+        }
+        if u.arbitrary()? {
+            let section = CustomSection::Name(self.arbitrary_name_section(u)?);
+            self.custom_sections.push((NUM_PLACES - 1, section));
+        }
+        if u.arbitrary()? {
+            let section = arbitrary_producers_section(u)?;
+            self.custom_sections.push((NUM_PLACES - 1, section));
+        }
+
+        let mut junk = Vec::new();
+        arbitrary_loop(u, 0, 5, |u| {
+            let place = u.int_in_range(0..=NUM_PLACES - 1)?;
+            let name = limited_string(100, u)?;
+            let data = u.arbitrary()?;
+            junk.push((place, CustomSection::Raw { name, data }));
+            Ok(true)
+        })?;
+        self.custom_sections.extend(junk);
+
+        // Keep the sections for each place in the order they were generated.
+        self.custom_sections.sort_by_key(|(place, _)| *place);
+        Ok(())
+    }
+
+    fn arbitrary_name_section(&self, u: &mut Unstructured) -> Result<NameSection> {
+        fn arbitrary_name_map(u: &mut Unstructured, count: usize) -> Result<Option<NameMap>> {
+            if count == 0 || !u.arbitrary()? {
+                return Ok(None);
+            }
+            let mut map = NameMap::new();
+            let mut names = HashSet::new();
+            for i in 0..count {
+                if u.arbitrary()? {
+                    map.append(i as u32, &unique_string(100, &mut names, u)?);
+                }
+            }
+            Ok(Some(map))
+        }
+
+        let mut section = NameSection::new();
+        if u.arbitrary()? {
+            section.module(&limited_string(100, u)?);
+        }
+        if let Some(map) = arbitrary_name_map(u, self.funcs.len())? {
+            section.functions(&map);
+        }
+        if !self.code.is_empty() && u.arbitrary()? {
+            let mut locals = IndirectNameMap::new();
+            let num_imported_funcs = self.funcs.len() - self.num_defined_funcs;
+            for (i, code) in self.code.iter().enumerate() {
+                let func = num_imported_funcs + i;
+                let count = self.funcs[func].1.params.len() + code.locals.len();
+                if let Some(map) = arbitrary_name_map(u, count)? {
+                    locals.append(func as u32, &map);
+                }
+            }
+            section.locals(&locals);
+        }
+        if let Some(map) = arbitrary_name_map(u, self.types.len())? {
+            section.types(&map);
+        }
+        if let Some(map) = arbitrary_name_map(u, self.tables.len())? {
+            section.tables(&map);
+        }
+        if let Some(map) = arbitrary_name_map(u, self.memories.len())? {
+            section.memories(&map);
+        }
+        if let Some(map) = arbitrary_name_map(u, self.globals.len())? {
+            section.globals(&map);
+        }
+        if let Some(map) = arbitrary_name_map(u, self.elems.len())? {
+            section.elements(&map);
+        }
+        if let Some(map) = arbitrary_name_map(u, self.data.len())? {
+            section.data(&map);
+        }
+        Ok(section)
+    }
+
+    fn arbitrary_dylink0_section(&self, u: &mut Unstructured) -> Result<CustomSection> {
+        const WASM_DYLINK_MEM_INFO: u8 = 1;
+        const WASM_DYLINK_NEEDED: u8 = 2;
+        const WASM_DYLINK_EXPORT_INFO: u8 = 3;
+        const WASM_DYLINK_IMPORT_INFO: u8 = 4;
+
+        fn subsection(data: &mut Vec<u8>, id: u8, contents: &[u8]) {
+            data.push(id);
+            contents.len().encode(data);
+            data.extend_from_slice(contents);
+        }
+
+        let mut data = Vec::new();
+        if u.arbitrary()? {
+            let mut contents = Vec::new();
+            u.arbitrary::<u32>()?.encode(&mut contents);
+            u.int_in_range(0..=16u32)?.encode(&mut contents);
+            u.arbitrary::<u32>()?.encode(&mut contents);
+            u.int_in_range(0..=16u32)?.encode(&mut contents);
+            subsection(&mut data, WASM_DYLINK_MEM_INFO, &contents);
+        }
+        if u.arbitrary()? {
+            let mut needed = Vec::new();
+            arbitrary_loop(u, 0, 10, |u| {
+                needed.push(limited_string(100, u)?);
+                Ok(true)
+            })?;
+            let mut contents = Vec::new();
+            needed.len().encode(&mut contents);
+            for name in &needed {
+                name.as_str().encode(&mut contents);
+            }
+            subsection(&mut data, WASM_DYLINK_NEEDED, &contents);
+        }
+        if !self.exports.is_empty() && u.arbitrary()? {
+            let mut contents = Vec::new();
+            self.exports.len().encode(&mut contents);
+            for (name, _, _) in &self.exports {
+                name.as_str().encode(&mut contents);
+                arbitrary_symbol_flags(u)?.encode(&mut contents);
+            }
+            subsection(&mut data, WASM_DYLINK_EXPORT_INFO, &contents);
+        }
+        if !self.imports.is_empty() && u.arbitrary()? {
+            let mut contents = Vec::new();
+            self.imports.len().encode(&mut contents);
+            for import in &self.imports {
+                import.module.as_str().encode(&mut contents);
+                import.field.as_str().encode(&mut contents);
+                arbitrary_symbol_flags(u)?.encode(&mut contents);
+            }
+            subsection(&mut data, WASM_DYLINK_IMPORT_INFO, &contents);
+        }
+
+        Ok(CustomSection::Raw {
+            name: "dylink.0".to_string(),
+            data,
+        })
+    }
+}
+
+/// Generate a combination of the `WASM_SYM_*` flags from the tool conventions
+/// for dynamic linking.
+fn arbitrary_symbol_flags(u: &mut Unstructured) -> Result<u32> {
+    const FLAGS: &[u32] = &[
+        0x01,  // WASM_SYM_BINDING_WEAK
+        0x02,  // WASM_SYM_BINDING_LOCAL
+        0x04,  // WASM_SYM_VISIBILITY_HIDDEN
+        0x10,  // WASM_SYM_UNDEFINED
+        0x20,  // WASM_SYM_EXPORTED
+        0x40,  // WASM_SYM_EXPLICIT_NAME
+        0x80,  // WASM_SYM_NO_STRIP
+        0x100, // WASM_SYM_TLS
+    ];
+    let mut flags = 0;
+    for flag in FLAGS {
+        if u.arbitrary()? {
+            flags |= flag;
+        }
+    }
+    Ok(flags)
+}
+
+fn arbitrary_producers_section(u: &mut Unstructured) -> Result<CustomSection> {
+    const FIELDS: &[&str] = &["language", "processed-by", "sdk"];
+
+    let mut data = Vec::new();
+    let mut fields = Vec::new();
+    for field in FIELDS {
+        if u.arbitrary()? {
+            let mut values = Vec::new();
+            let mut names = HashSet::new();
+            arbitrary_loop(u, 1, 5, |u| {
+                let name = unique_string(100, &mut names, u)?;
+                let version = limited_string(100, u)?;
+                values.push((name, version));
+                Ok(true)
+            })?;
+            fields.push((*field, values));
+        }
+    }
+
+    fields.len().encode(&mut data);
+    for (field, values) in &fields {
+        field.encode(&mut data);
+        values.len().encode(&mut data);
+        for (name, version) in values {
+            name.as_str().encode(&mut data);
+            version.as_str().encode(&mut data);
+        }
+    }
+
+    Ok(CustomSection::Raw {
+        name: "producers".to_string(),
+        data,
+    })
+}
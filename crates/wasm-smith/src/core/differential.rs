@@ -0,0 +1,944 @@
+use super::*;
+use std::fmt::Write;
+
+/// The maximum number of instructions evaluated for a single invocation.
+const MAX_FUEL: u32 = 10_000;
+
+/// The maximum call depth when evaluating an invocation; deeper invocations
+/// are skipped since engines differ in when they exhaust their stack.
+const MAX_CALL_DEPTH: u32 = 32;
+
+const F32_SIGN: u32 = 1 << 31;
+const F64_SIGN: u64 = 1 << 63;
+
+/// A value of the evaluator.
+///
+/// Floats are kept as their bits so that NaN payloads are preserved exactly.
+#[derive(Clone, Copy, Debug)]
+enum Value {
+    I32(i32),
+    I64(i64),
+    F32(u32),
+    F64(u64),
+}
+
+impl Value {
+    fn default_for(ty: ValType) -> Option<Self> {
+        match ty {
+            ValType::I32 => Some(Value::I32(0)),
+            ValType::I64 => Some(Value::I64(0)),
+            ValType::F32 => Some(Value::F32(0)),
+            ValType::F64 => Some(Value::F64(0)),
+            _ => None,
+        }
+    }
+
+    fn arbitrary(u: &mut Unstructured, ty: ValType) -> Result<Self> {
+        Ok(match ty {
+            ValType::I32 => Value::I32(u.arbitrary()?),
+            ValType::I64 => Value::I64(u.arbitrary()?),
+            ValType::F32 => Value::F32(u.arbitrary()?),
+            ValType::F64 => Value::F64(u.arbitrary()?),
+            _ => unreachable!("unsupported value type"),
+        })
+    }
+
+    fn i32(self) -> i32 {
+        match self {
+            Value::I32(x) => x,
+            _ => unreachable!("expected an i32 operand"),
+        }
+    }
+
+    fn i64(self) -> i64 {
+        match self {
+            Value::I64(x) => x,
+            _ => unreachable!("expected an i64 operand"),
+        }
+    }
+
+    fn f32_bits(self) -> u32 {
+        match self {
+            Value::F32(x) => x,
+            _ => unreachable!("expected an f32 operand"),
+        }
+    }
+
+    fn f64_bits(self) -> u64 {
+        match self {
+            Value::F64(x) => x,
+            _ => unreachable!("expected an f64 operand"),
+        }
+    }
+
+    fn f32(self) -> f32 {
+        f32::from_bits(self.f32_bits())
+    }
+
+    fn f64(self) -> f64 {
+        f64::from_bits(self.f64_bits())
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Floats are written as hexadecimal literals (or NaNs with their
+        // payload) so that they are exact.
+        match *self {
+            Value::I32(x) => write!(f, "(i32.const {})", x),
+            Value::I64(x) => write!(f, "(i64.const {})", x),
+            Value::F32(bits) => {
+                let sign = if bits & F32_SIGN != 0 { "-" } else { "" };
+                let exponent = (bits >> 23) & 0xff;
+                let mantissa = bits & 0x7f_ffff;
+                match (exponent, mantissa) {
+                    (0xff, 0) => write!(f, "(f32.const {}inf)", sign),
+                    (0xff, m) => write!(f, "(f32.const {}nan:0x{:x})", sign, m),
+                    (0, 0) => write!(f, "(f32.const {}0x0p+0)", sign),
+                    (0, m) => write!(f, "(f32.const {}0x0.{:06x}p-126)", sign, m << 1),
+                    (e, m) => write!(
+                        f,
+                        "(f32.const {}0x1.{:06x}p{:+})",
+                        sign,
+                        m << 1,
+                        e as i32 - 127
+                    ),
+                }
+            }
+            Value::F64(bits) => {
+                let sign = if bits & F64_SIGN != 0 { "-" } else { "" };
+                let exponent = (bits >> 52) & 0x7ff;
+                let mantissa = bits & 0xf_ffff_ffff_ffff;
+                match (exponent, mantissa) {
+                    (0x7ff, 0) => write!(f, "(f64.const {}inf)", sign),
+                    (0x7ff, m) => write!(f, "(f64.const {}nan:0x{:x})", sign, m),
+                    (0, 0) => write!(f, "(f64.const {}0x0p+0)", sign),
+                    (0, m) => write!(f, "(f64.const {}0x0.{:013x}p-1022)", sign, m),
+                    (e, m) => write!(
+                        f,
+                        "(f64.const {}0x1.{:013x}p{:+})",
+                        sign,
+                        m,
+                        e as i64 - 1023
+                    ),
+                }
+            }
+        }
+    }
+}
+
+/// The outcome of evaluating an invocation: either its results or the
+/// message of the trap it raised.
+type Outcome = std::result::Result<Vec<Value>, &'static str>;
+
+#[derive(Debug)]
+struct Frame {
+    /// Whether branches to this frame jump back to its start.
+    is_loop: bool,
+    /// The index of the instruction that started this frame.
+    start: usize,
+    /// The index of the `end` instruction of this frame.
+    end: usize,
+    /// The height of the operand stack below this frame's parameters.
+    height: usize,
+    /// The number of values a branch to this frame carries.
+    arity: usize,
+}
+
+impl Module {
+    /// Encode this Wasm module as a `.wast` script for differential testing.
+    ///
+    /// The script defines this module and then, for each exported function
+    /// whose behavior can be computed by a built-in reference evaluator,
+    /// invokes it with arbitrary arguments and asserts the results with
+    /// `assert_return` (or the trap with `assert_trap`).
+    ///
+    /// The evaluator only supports functions with numeric parameters and
+    /// results whose execution is limited to control instructions,
+    /// parametric instructions, locals, numeric instructions, and calls to
+    /// other such functions. Since the bits of NaNs produced by float
+    /// arithmetic are nondeterministic, invocations that produce such a NaN
+    /// are skipped as well. Other exported functions are not invoked;
+    /// restricting [`Config::allowed_instructions`] to control, parametric,
+    /// variable, and numeric instructions makes most functions eligible.
+    ///
+    /// The script expects the module to be instantiated without imports and
+    /// without trapping, so it should be generated without imports, active
+    /// segments, or a start function.
+    pub fn to_wast(&self, u: &mut Unstructured) -> Result<String> {
+        let mut wast = String::from("(module binary \"");
+        for byte in self.to_bytes() {
+            write!(wast, "\\{:02x}", byte).unwrap();
+        }
+        wast.push_str("\")\n");
+
+        for (name, kind, index) in &self.exports {
+            if *kind != ExportKind::Func {
+                continue;
+            }
+
+            let ty = &self.funcs[*index as usize].1;
+            if !ty
+                .params
+                .iter()
+                .chain(ty.results.iter())
+                .all(|ty| Value::default_for(*ty).is_some())
+            {
+                continue;
+            }
+
+            let mut args = Vec::with_capacity(ty.params.len());
+            for param in &ty.params {
+                args.push(Value::arbitrary(u, *param)?);
+            }
+
+            let mut fuel = MAX_FUEL;
+            let outcome = match self.evaluate(*index, args.clone(), &mut fuel, 0) {
+                Some(outcome) => outcome,
+                None => continue,
+            };
+
+            let mut invoke = String::from("(invoke \"");
+            for byte in name.bytes() {
+                if byte.is_ascii_graphic() && byte != b'"' && byte != b'\\' {
+                    invoke.push(char::from(byte));
+                } else {
+                    write!(invoke, "\\{:02x}", byte).unwrap();
+                }
+            }
+            invoke.push('"');
+            for arg in &args {
+                write!(invoke, " {}", arg).unwrap();
+            }
+            invoke.push(')');
+
+            match outcome {
+                Ok(results) => {
+                    write!(wast, "(assert_return {}", invoke).unwrap();
+                    for result in results {
+                        write!(wast, " {}", result).unwrap();
+                    }
+                    wast.push_str(")\n");
+                }
+                Err(message) => {
+                    writeln!(wast, "(assert_trap {} \"{}\")", invoke, message).unwrap();
+                }
+            }
+        }
+
+        Ok(wast)
+    }
+
+    /// Evaluate the function with the given index.
+    ///
+    /// Returns `None` if the function cannot be evaluated, either because it
+    /// uses unsupported instructions or because it exceeds the fuel or call
+    /// depth limits.
+    fn evaluate(&self, func: u32, args: Vec<Value>, fuel: &mut u32, depth: u32) -> Option<Outcome> {
+        if depth > MAX_CALL_DEPTH {
+            return None;
+        }
+
+        let num_imported_funcs = self
+            .imports
+            .iter()
+            .filter(|imp| matches!(imp.entity_type, EntityType::Func(..)))
+            .count();
+        let code = self
+            .code
+            .get((func as usize).checked_sub(num_imported_funcs)?)?;
+        let instrs = match &code.instructions {
+            Instructions::Generated(list) => list,
+            Instructions::Arbitrary(_) => return None,
+        };
+        let ty = Rc::clone(&self.funcs[func as usize].1);
+
+        let mut locals = args;
+        for local in &code.locals {
+            locals.push(Value::default_for(*local)?);
+        }
+
+        // Match each block, loop, and if with its `else` and `end`.
+        let mut ends = vec![0; instrs.len()];
+        let mut elses = vec![None; instrs.len()];
+        let mut openers = vec![];
+        for (i, instr) in instrs.iter().enumerate() {
+            match instr {
+                Instruction::Block(_)
+                | Instruction::Loop(_)
+                | Instruction::If(_)
+                | Instruction::Try(_) => openers.push(i),
+                Instruction::Else => elses[*openers.last()?] = Some(i),
+                Instruction::End | Instruction::Delegate(_) => {
+                    if let Some(opener) = openers.pop() {
+                        ends[opener] = i;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // NB: the function's own `end` is not part of its generated
+        // instructions; it is added when encoding.
+        let mut stack: Vec<Value> = vec![];
+        let mut frames = vec![Frame {
+            is_loop: false,
+            start: 0,
+            end: instrs.len(),
+            height: 0,
+            arity: ty.results.len(),
+        }];
+        let mut pc = 0;
+
+        macro_rules! pop {
+            () => {
+                stack.pop()?
+            };
+        }
+        macro_rules! unop {
+            ($get:ident, $wrap:ident, |$a:ident| $e:expr) => {{
+                let $a = pop!().$get();
+                stack.push(Value::$wrap($e));
+            }};
+        }
+        macro_rules! binop {
+            ($get:ident, $wrap:ident, |$a:ident, $b:ident| $e:expr) => {{
+                let $b = pop!().$get();
+                let $a = pop!().$get();
+                stack.push(Value::$wrap($e));
+            }};
+        }
+        macro_rules! float_unop {
+            ($get:ident, $wrap:ident, |$a:ident| $e:expr) => {{
+                let $a = pop!().$get();
+                let x = $e;
+                if x.is_nan() {
+                    return None;
+                }
+                stack.push(Value::$wrap(x.to_bits()));
+            }};
+        }
+        macro_rules! float_binop {
+            ($get:ident, $wrap:ident, |$a:ident, $b:ident| $e:expr) => {{
+                let $b = pop!().$get();
+                let $a = pop!().$get();
+                let x = $e;
+                if x.is_nan() {
+                    return None;
+                }
+                stack.push(Value::$wrap(x.to_bits()));
+            }};
+        }
+        macro_rules! trapping_unop {
+            ($get:ident, $wrap:ident, |$a:ident| $e:expr) => {{
+                let $a = pop!().$get();
+                match $e {
+                    Ok(x) => stack.push(Value::$wrap(x)),
+                    Err(message) => return Some(Err(message)),
+                }
+            }};
+        }
+        macro_rules! trapping_binop {
+            ($get:ident, $wrap:ident, |$a:ident, $b:ident| $e:expr) => {{
+                let $b = pop!().$get();
+                let $a = pop!().$get();
+                match $e {
+                    Ok(x) => stack.push(Value::$wrap(x)),
+                    Err(message) => return Some(Err(message)),
+                }
+            }};
+        }
+
+        while pc < instrs.len() {
+            *fuel = fuel.checked_sub(1)?;
+
+            // The index of the frame to branch to, if any, counting from the
+            // outermost frame.
+            let mut branch = None;
+
+            match &instrs[pc] {
+                Instruction::Unreachable => return Some(Err("unreachable")),
+                Instruction::Nop => {}
+                Instruction::Block(bt) | Instruction::Loop(bt) | Instruction::If(bt) => {
+                    let (params, results) = self.params_results(bt);
+                    let is_loop = matches!(instrs[pc], Instruction::Loop(_));
+                    let cond = match instrs[pc] {
+                        Instruction::If(_) => Some(pop!().i32() != 0),
+                        _ => None,
+                    };
+                    let frame = Frame {
+                        is_loop,
+                        start: pc,
+                        end: ends[pc],
+                        height: stack.len().checked_sub(params.len())?,
+                        arity: if is_loop { params.len() } else { results.len() },
+                    };
+                    match (cond, elses[pc]) {
+                        (Some(false), Some(else_)) => {
+                            frames.push(frame);
+                            pc = else_;
+                        }
+                        (Some(false), None) => pc = frame.end,
+                        _ => frames.push(frame),
+                    }
+                }
+                // Reaching an `else` means the `then` arm finished, so skip
+                // to the `end` of the `if`.
+                Instruction::Else => pc = frames.last()?.end - 1,
+                Instruction::End | Instruction::Delegate(_) => {
+                    frames.pop();
+                }
+                Instruction::Br(depth) => branch = Some(*depth),
+                Instruction::BrIf(depth) => {
+                    if pop!().i32() != 0 {
+                        branch = Some(*depth);
+                    }
+                }
+                Instruction::BrTable(targets, default) => {
+                    let index = pop!().i32() as u32 as usize;
+                    branch = Some(*targets.get(index).unwrap_or(default));
+                }
+                Instruction::Return => branch = Some(u32::try_from(frames.len() - 1).ok()?),
+                Instruction::Call(callee) | Instruction::ReturnCall(callee) => {
+                    let callee_ty = Rc::clone(&self.funcs[*callee as usize].1);
+                    let args = stack.split_off(stack.len().checked_sub(callee_ty.params.len())?);
+                    match self.evaluate(*callee, args, fuel, depth + 1)? {
+                        Ok(results) => stack.extend(results),
+                        Err(message) => return Some(Err(message)),
+                    }
+                    if let Instruction::ReturnCall(_) = instrs[pc] {
+                        branch = Some(u32::try_from(frames.len() - 1).ok()?);
+                    }
+                }
+
+                Instruction::Drop => {
+                    pop!();
+                }
+                Instruction::Select | Instruction::TypedSelect(_) => {
+                    let cond = pop!().i32();
+                    let b = pop!();
+                    let a = pop!();
+                    stack.push(if cond != 0 { a } else { b });
+                }
+
+                Instruction::LocalGet(i) => stack.push(*locals.get(*i as usize)?),
+                Instruction::LocalSet(i) => *locals.get_mut(*i as usize)? = pop!(),
+                Instruction::LocalTee(i) => *locals.get_mut(*i as usize)? = *stack.last()?,
+
+                Instruction::I32Const(x) => stack.push(Value::I32(*x)),
+                Instruction::I64Const(x) => stack.push(Value::I64(*x)),
+                Instruction::F32Const(x) => stack.push(Value::F32(x.to_bits())),
+                Instruction::F64Const(x) => stack.push(Value::F64(x.to_bits())),
+
+                Instruction::I32Eqz => unop!(i32, I32, |a| (a == 0) as i32),
+                Instruction::I32Eq => binop!(i32, I32, |a, b| (a == b) as i32),
+                Instruction::I32Ne => binop!(i32, I32, |a, b| (a != b) as i32),
+                Instruction::I32LtS => binop!(i32, I32, |a, b| (a < b) as i32),
+                Instruction::I32LtU => binop!(i32, I32, |a, b| ((a as u32) < (b as u32)) as i32),
+                Instruction::I32GtS => binop!(i32, I32, |a, b| (a > b) as i32),
+                Instruction::I32GtU => binop!(i32, I32, |a, b| ((a as u32) > (b as u32)) as i32),
+                Instruction::I32LeS => binop!(i32, I32, |a, b| (a <= b) as i32),
+                Instruction::I32LeU => binop!(i32, I32, |a, b| ((a as u32) <= (b as u32)) as i32),
+                Instruction::I32GeS => binop!(i32, I32, |a, b| (a >= b) as i32),
+                Instruction::I32GeU => binop!(i32, I32, |a, b| ((a as u32) >= (b as u32)) as i32),
+                Instruction::I64Eqz => unop!(i64, I32, |a| (a == 0) as i32),
+                Instruction::I64Eq => binop!(i64, I32, |a, b| (a == b) as i32),
+                Instruction::I64Ne => binop!(i64, I32, |a, b| (a != b) as i32),
+                Instruction::I64LtS => binop!(i64, I32, |a, b| (a < b) as i32),
+                Instruction::I64LtU => binop!(i64, I32, |a, b| ((a as u64) < (b as u64)) as i32),
+                Instruction::I64GtS => binop!(i64, I32, |a, b| (a > b) as i32),
+                Instruction::I64GtU => binop!(i64, I32, |a, b| ((a as u64) > (b as u64)) as i32),
+                Instruction::I64LeS => binop!(i64, I32, |a, b| (a <= b) as i32),
+                Instruction::I64LeU => binop!(i64, I32, |a, b| ((a as u64) <= (b as u64)) as i32),
+                Instruction::I64GeS => binop!(i64, I32, |a, b| (a >= b) as i32),
+                Instruction::I64GeU => binop!(i64, I32, |a, b| ((a as u64) >= (b as u64)) as i32),
+                Instruction::F32Eq => binop!(f32, I32, |a, b| (a == b) as i32),
+                Instruction::F32Ne => binop!(f32, I32, |a, b| (a != b) as i32),
+                Instruction::F32Lt => binop!(f32, I32, |a, b| (a < b) as i32),
+                Instruction::F32Gt => binop!(f32, I32, |a, b| (a > b) as i32),
+                Instruction::F32Le => binop!(f32, I32, |a, b| (a <= b) as i32),
+                Instruction::F32Ge => binop!(f32, I32, |a, b| (a >= b) as i32),
+                Instruction::F64Eq => binop!(f64, I32, |a, b| (a == b) as i32),
+                Instruction::F64Ne => binop!(f64, I32, |a, b| (a != b) as i32),
+                Instruction::F64Lt => binop!(f64, I32, |a, b| (a < b) as i32),
+                Instruction::F64Gt => binop!(f64, I32, |a, b| (a > b) as i32),
+                Instruction::F64Le => binop!(f64, I32, |a, b| (a <= b) as i32),
+                Instruction::F64Ge => binop!(f64, I32, |a, b| (a >= b) as i32),
+
+                Instruction::I32Clz => unop!(i32, I32, |a| a.leading_zeros() as i32),
+                Instruction::I32Ctz => unop!(i32, I32, |a| a.trailing_zeros() as i32),
+                Instruction::I32Popcnt => unop!(i32, I32, |a| a.count_ones() as i32),
+                Instruction::I32Add => binop!(i32, I32, |a, b| a.wrapping_add(b)),
+                Instruction::I32Sub => binop!(i32, I32, |a, b| a.wrapping_sub(b)),
+                Instruction::I32Mul => binop!(i32, I32, |a, b| a.wrapping_mul(b)),
+                Instruction::I32DivS => trapping_binop!(i32, I32, |a, b| div_s(a, b, i32::MIN)),
+                Instruction::I32DivU => trapping_binop!(i32, I32, |a, b| {
+                    nonzero(b).map(|b| ((a as u32) / (b as u32)) as i32)
+                }),
+                Instruction::I32RemS => {
+                    trapping_binop!(i32, I32, |a, b| nonzero(b).map(|b| a.wrapping_rem(b)))
+                }
+                Instruction::I32RemU => trapping_binop!(i32, I32, |a, b| {
+                    nonzero(b).map(|b| ((a as u32) % (b as u32)) as i32)
+                }),
+                Instruction::I32And => binop!(i32, I32, |a, b| a & b),
+                Instruction::I32Or => binop!(i32, I32, |a, b| a | b),
+                Instruction::I32Xor => binop!(i32, I32, |a, b| a ^ b),
+                Instruction::I32Shl => binop!(i32, I32, |a, b| a.wrapping_shl(b as u32)),
+                Instruction::I32ShrS => binop!(i32, I32, |a, b| a.wrapping_shr(b as u32)),
+                Instruction::I32ShrU => {
+                    binop!(i32, I32, |a, b| (a as u32).wrapping_shr(b as u32) as i32)
+                }
+                Instruction::I32Rotl => binop!(i32, I32, |a, b| a.rotate_left(b as u32 % 32)),
+                Instruction::I32Rotr => binop!(i32, I32, |a, b| a.rotate_right(b as u32 % 32)),
+                Instruction::I64Clz => unop!(i64, I64, |a| i64::from(a.leading_zeros())),
+                Instruction::I64Ctz => unop!(i64, I64, |a| i64::from(a.trailing_zeros())),
+                Instruction::I64Popcnt => unop!(i64, I64, |a| i64::from(a.count_ones())),
+                Instruction::I64Add => binop!(i64, I64, |a, b| a.wrapping_add(b)),
+                Instruction::I64Sub => binop!(i64, I64, |a, b| a.wrapping_sub(b)),
+                Instruction::I64Mul => binop!(i64, I64, |a, b| a.wrapping_mul(b)),
+                Instruction::I64DivS => trapping_binop!(i64, I64, |a, b| div_s(a, b, i64::MIN)),
+                Instruction::I64DivU => trapping_binop!(i64, I64, |a, b| {
+                    nonzero(b).map(|b| ((a as u64) / (b as u64)) as i64)
+                }),
+                Instruction::I64RemS => {
+                    trapping_binop!(i64, I64, |a, b| nonzero(b).map(|b| a.wrapping_rem(b)))
+                }
+                Instruction::I64RemU => trapping_binop!(i64, I64, |a, b| {
+                    nonzero(b).map(|b| ((a as u64) % (b as u64)) as i64)
+                }),
+                Instruction::I64And => binop!(i64, I64, |a, b| a & b),
+                Instruction::I64Or => binop!(i64, I64, |a, b| a | b),
+                Instruction::I64Xor => binop!(i64, I64, |a, b| a ^ b),
+                Instruction::I64Shl => binop!(i64, I64, |a, b| a.wrapping_shl(b as u32)),
+                Instruction::I64ShrS => binop!(i64, I64, |a, b| a.wrapping_shr(b as u32)),
+                Instruction::I64ShrU => {
+                    binop!(i64, I64, |a, b| (a as u64).wrapping_shr(b as u32) as i64)
+                }
+                Instruction::I64Rotl => binop!(i64, I64, |a, b| a.rotate_left((b % 64) as u32)),
+                Instruction::I64Rotr => binop!(i64, I64, |a, b| a.rotate_right((b % 64) as u32)),
+
+                Instruction::F32Abs => unop!(f32_bits, F32, |a| a & !F32_SIGN),
+                Instruction::F32Neg => unop!(f32_bits, F32, |a| a ^ F32_SIGN),
+                Instruction::F32Copysign => {
+                    binop!(f32_bits, F32, |a, b| (a & !F32_SIGN) | (b & F32_SIGN))
+                }
+                Instruction::F32Ceil => float_unop!(f32, F32, |a| a.ceil()),
+                Instruction::F32Floor => float_unop!(f32, F32, |a| a.floor()),
+                Instruction::F32Trunc => float_unop!(f32, F32, |a| a.trunc()),
+                Instruction::F32Nearest => float_unop!(f32, F32, |a| {
+                    if (a - a.trunc()).abs() == 0.5 {
+                        2.0 * (a / 2.0).round()
+                    } else {
+                        a.round()
+                    }
+                }),
+                Instruction::F32Sqrt => float_unop!(f32, F32, |a| a.sqrt()),
+                Instruction::F32Add => float_binop!(f32, F32, |a, b| a + b),
+                Instruction::F32Sub => float_binop!(f32, F32, |a, b| a - b),
+                Instruction::F32Mul => float_binop!(f32, F32, |a, b| a * b),
+                Instruction::F32Div => float_binop!(f32, F32, |a, b| a / b),
+                Instruction::F32Min => float_binop!(f32, F32, |a, b| {
+                    if a.is_nan() || b.is_nan() {
+                        f32::NAN
+                    } else if a == b {
+                        // Prefer `-0` over `0`.
+                        f32::from_bits(a.to_bits() | b.to_bits())
+                    } else {
+                        a.min(b)
+                    }
+                }),
+                Instruction::F32Max => float_binop!(f32, F32, |a, b| {
+                    if a.is_nan() || b.is_nan() {
+                        f32::NAN
+                    } else if a == b {
+                        // Prefer `0` over `-0`.
+                        f32::from_bits(a.to_bits() & b.to_bits())
+                    } else {
+                        a.max(b)
+                    }
+                }),
+                Instruction::F64Abs => unop!(f64_bits, F64, |a| a & !F64_SIGN),
+                Instruction::F64Neg => unop!(f64_bits, F64, |a| a ^ F64_SIGN),
+                Instruction::F64Copysign => {
+                    binop!(f64_bits, F64, |a, b| (a & !F64_SIGN) | (b & F64_SIGN))
+                }
+                Instruction::F64Ceil => float_unop!(f64, F64, |a| a.ceil()),
+                Instruction::F64Floor => float_unop!(f64, F64, |a| a.floor()),
+                Instruction::F64Trunc => float_unop!(f64, F64, |a| a.trunc()),
+                Instruction::F64Nearest => float_unop!(f64, F64, |a| {
+                    if (a - a.trunc()).abs() == 0.5 {
+                        2.0 * (a / 2.0).round()
+                    } else {
+                        a.round()
+                    }
+                }),
+                Instruction::F64Sqrt => float_unop!(f64, F64, |a| a.sqrt()),
+                Instruction::F64Add => float_binop!(f64, F64, |a, b| a + b),
+                Instruction::F64Sub => float_binop!(f64, F64, |a, b| a - b),
+                Instruction::F64Mul => float_binop!(f64, F64, |a, b| a * b),
+                Instruction::F64Div => float_binop!(f64, F64, |a, b| a / b),
+                Instruction::F64Min => float_binop!(f64, F64, |a, b| {
+                    if a.is_nan() || b.is_nan() {
+                        f64::NAN
+                    } else if a == b {
+                        // Prefer `-0` over `0`.
+                        f64::from_bits(a.to_bits() | b.to_bits())
+                    } else {
+                        a.min(b)
+                    }
+                }),
+                Instruction::F64Max => float_binop!(f64, F64, |a, b| {
+                    if a.is_nan() || b.is_nan() {
+                        f64::NAN
+                    } else if a == b {
+                        // Prefer `0` over `-0`.
+                        f64::from_bits(a.to_bits() & b.to_bits())
+                    } else {
+                        a.max(b)
+                    }
+                }),
+
+                Instruction::I32WrapI64 => unop!(i64, I32, |a| a as i32),
+                Instruction::I32TruncF32S => trapping_unop!(f32, I32, |a| {
+                    trunc(f64::from(a), I32_MIN, I32_MAX).map(|t| t as i32)
+                }),
+                Instruction::I32TruncF32U => trapping_unop!(f32, I32, |a| {
+                    trunc(f64::from(a), 0.0, U32_MAX).map(|t| t as u32 as i32)
+                }),
+                Instruction::I32TruncF64S => {
+                    trapping_unop!(f64, I32, |a| trunc(a, I32_MIN, I32_MAX).map(|t| t as i32))
+                }
+                Instruction::I32TruncF64U => trapping_unop!(f64, I32, |a| {
+                    trunc(a, 0.0, U32_MAX).map(|t| t as u32 as i32)
+                }),
+                Instruction::I64TruncF32S => trapping_unop!(f32, I64, |a| {
+                    trunc(f64::from(a), I64_MIN, I64_MAX).map(|t| t as i64)
+                }),
+                Instruction::I64TruncF32U => trapping_unop!(f32, I64, |a| {
+                    trunc(f64::from(a), 0.0, U64_MAX).map(|t| t as u64 as i64)
+                }),
+                Instruction::I64TruncF64S => {
+                    trapping_unop!(f64, I64, |a| trunc(a, I64_MIN, I64_MAX).map(|t| t as i64))
+                }
+                Instruction::I64TruncF64U => trapping_unop!(f64, I64, |a| {
+                    trunc(a, 0.0, U64_MAX).map(|t| t as u64 as i64)
+                }),
+                // Rust's float to integer casts saturate and map NaN to zero,
+                // just like the saturating truncations.
+                Instruction::I32TruncSatF32S => unop!(f32, I32, |a| a as i32),
+                Instruction::I32TruncSatF32U => unop!(f32, I32, |a| a as u32 as i32),
+                Instruction::I32TruncSatF64S => unop!(f64, I32, |a| a as i32),
+                Instruction::I32TruncSatF64U => unop!(f64, I32, |a| a as u32 as i32),
+                Instruction::I64TruncSatF32S => unop!(f32, I64, |a| a as i64),
+                Instruction::I64TruncSatF32U => unop!(f32, I64, |a| a as u64 as i64),
+                Instruction::I64TruncSatF64S => unop!(f64, I64, |a| a as i64),
+                Instruction::I64TruncSatF64U => unop!(f64, I64, |a| a as u64 as i64),
+                Instruction::F32ConvertI32S => unop!(i32, F32, |a| (a as f32).to_bits()),
+                Instruction::F32ConvertI32U => unop!(i32, F32, |a| (a as u32 as f32).to_bits()),
+                Instruction::F32ConvertI64S => unop!(i64, F32, |a| (a as f32).to_bits()),
+                Instruction::F32ConvertI64U => unop!(i64, F32, |a| (a as u64 as f32).to_bits()),
+                Instruction::F64ConvertI32S => unop!(i32, F64, |a| f64::from(a).to_bits()),
+                Instruction::F64ConvertI32U => unop!(i32, F64, |a| f64::from(a as u32).to_bits()),
+                Instruction::F64ConvertI64S => unop!(i64, F64, |a| (a as f64).to_bits()),
+                Instruction::F64ConvertI64U => unop!(i64, F64, |a| (a as u64 as f64).to_bits()),
+                Instruction::F32DemoteF64 => float_unop!(f64, F32, |a| a as f32),
+                Instruction::F64PromoteF32 => float_unop!(f32, F64, |a| f64::from(a)),
+                Instruction::I32ReinterpretF32 => unop!(f32_bits, I32, |a| a as i32),
+                Instruction::I64ReinterpretF64 => unop!(f64_bits, I64, |a| a as i64),
+                Instruction::F32ReinterpretI32 => unop!(i32, F32, |a| a as u32),
+                Instruction::F64ReinterpretI64 => unop!(i64, F64, |a| a as u64),
+                Instruction::I64ExtendI32S => unop!(i32, I64, |a| i64::from(a)),
+                Instruction::I64ExtendI32U => unop!(i32, I64, |a| i64::from(a as u32)),
+                Instruction::I32Extend8S => unop!(i32, I32, |a| i32::from(a as i8)),
+                Instruction::I32Extend16S => unop!(i32, I32, |a| i32::from(a as i16)),
+                Instruction::I64Extend8S => unop!(i64, I64, |a| i64::from(a as i8)),
+                Instruction::I64Extend16S => unop!(i64, I64, |a| i64::from(a as i16)),
+                Instruction::I64Extend32S => unop!(i64, I64, |a| i64::from(a as i32)),
+
+                _ => return None,
+            }
+
+            match branch {
+                Some(depth) => {
+                    let target = frames.len().checked_sub(depth as usize + 1)?;
+                    let frame = &frames[target];
+                    let values = stack.split_off(stack.len().checked_sub(frame.arity)?);
+                    stack.truncate(frame.height);
+                    stack.extend(values);
+                    if frame.is_loop {
+                        pc = frame.start + 1;
+                        frames.truncate(target + 1);
+                    } else {
+                        pc = frame.end + 1;
+                        frames.truncate(target);
+                    }
+                }
+                None => pc += 1,
+            }
+        }
+
+        let results = stack.split_off(stack.len().checked_sub(ty.results.len())?);
+        Some(Ok(results))
+    }
+}
+
+// The bounds of the integer types as floats, for checking truncations.
+const I32_MIN: f64 = -2147483648.0;
+const I32_MAX: f64 = 2147483648.0;
+const U32_MAX: f64 = 4294967296.0;
+const I64_MIN: f64 = -9223372036854775808.0;
+const I64_MAX: f64 = 9223372036854775808.0;
+const U64_MAX: f64 = 18446744073709551616.0;
+
+/// Truncate the given float, checking that the result is within `min`
+/// (inclusive) and `max` (exclusive).
+fn trunc(x: f64, min: f64, max: f64) -> std::result::Result<f64, &'static str> {
+    if x.is_nan() {
+        return Err("invalid conversion to integer");
+    }
+    let t = x.trunc();
+    if t >= min && t < max {
+        Ok(t)
+    } else {
+        Err("integer overflow")
+    }
+}
+
+fn nonzero<T: Default + PartialEq>(x: T) -> std::result::Result<T, &'static str> {
+    if x == T::default() {
+        Err("integer divide by zero")
+    } else {
+        Ok(x)
+    }
+}
+
+fn div_s<T>(a: T, b: T, min: T) -> std::result::Result<T, &'static str>
+where
+    T: Copy + Default + PartialEq + std::ops::Div<Output = T> + std::ops::Not<Output = T>,
+{
+    let b = nonzero(b)?;
+    // `-1` is the bitwise negation of `0`.
+    if a == min && b == !T::default() {
+        Err("integer overflow")
+    } else {
+        Ok(a / b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_encoder::Instruction::*;
+
+    /// Returns the assertions of the script for a module exporting each of
+    /// the given functions, which take no parameters.
+    fn assertions(funcs: Vec<(&str, Vec<ValType>, Vec<Instruction>)>) -> Vec<String> {
+        let mut module = Module::empty(Rc::new(DefaultConfig), DuplicateImportsBehavior::Allowed);
+        for (name, results, instructions) in funcs {
+            let ty = Rc::new(FuncType {
+                params: vec![],
+                results,
+            });
+            let index = module.funcs.len() as u32;
+            module.types.push(Type::Func(Rc::clone(&ty)));
+            module.func_types.push(index);
+            module.funcs.push((index, ty));
+            module.num_defined_funcs += 1;
+            module.code.push(Code {
+                locals: vec![],
+                instructions: Instructions::Generated(instructions),
+            });
+            module
+                .exports
+                .push((name.to_string(), ExportKind::Func, index));
+        }
+        let script = module.to_wast(&mut Unstructured::new(&[])).unwrap();
+        script.lines().skip(1).map(str::to_string).collect()
+    }
+
+    fn returns(name: &str, result: &str) -> String {
+        format!("(assert_return (invoke \"{}\") {})", name, result)
+    }
+
+    fn traps(name: &str, message: &str) -> String {
+        format!("(assert_trap (invoke \"{}\") \"{}\")", name, message)
+    }
+
+    #[test]
+    fn integer_division() {
+        let i32_div = |op, a, b| vec![I32Const(a), I32Const(b), op];
+        let i64_div = |op, a, b| vec![I64Const(a), I64Const(b), op];
+        let funcs = vec![
+            ("a", vec![ValType::I32], i32_div(I32DivS, i32::MIN, -1)),
+            ("b", vec![ValType::I32], i32_div(I32DivS, 1, 0)),
+            ("c", vec![ValType::I32], i32_div(I32RemS, i32::MIN, -1)),
+            ("d", vec![ValType::I32], i32_div(I32RemS, 1, 0)),
+            ("e", vec![ValType::I32], i32_div(I32DivU, 1, 0)),
+            ("f", vec![ValType::I32], i32_div(I32DivS, -7, 2)),
+            ("g", vec![ValType::I32], i32_div(I32RemS, -7, 2)),
+            ("h", vec![ValType::I64], i64_div(I64DivS, i64::MIN, -1)),
+            ("i", vec![ValType::I64], i64_div(I64RemS, i64::MIN, -1)),
+            ("j", vec![ValType::I64], i64_div(I64RemU, 1, 0)),
+        ];
+        assert_eq!(
+            assertions(funcs),
+            [
+                traps("a", "integer overflow"),
+                traps("b", "integer divide by zero"),
+                returns("c", "(i32.const 0)"),
+                traps("d", "integer divide by zero"),
+                traps("e", "integer divide by zero"),
+                returns("f", "(i32.const -3)"),
+                returns("g", "(i32.const -1)"),
+                traps("h", "integer overflow"),
+                returns("i", "(i64.const 0)"),
+                traps("j", "integer divide by zero"),
+            ]
+        );
+    }
+
+    #[test]
+    fn float_to_int_truncation() {
+        let f32_trunc = |op, x: f32| (vec![ValType::I32], vec![F32Const(x), op]);
+        let f64_trunc = |op, x: f64| (vec![ValType::I64], vec![F64Const(x), op]);
+        let cases = vec![
+            ("a", f32_trunc(I32TruncF32S, f32::NAN)),
+            ("b", f32_trunc(I32TruncF32S, 2147483648.0)),
+            ("c", f32_trunc(I32TruncF32S, -2147483648.0)),
+            ("d", f32_trunc(I32TruncF32U, -1.0)),
+            ("e", f32_trunc(I32TruncF32U, -0.75)),
+            ("f", f64_trunc(I64TruncF64S, 9223372036854775808.0)),
+            ("g", f64_trunc(I64TruncF64U, f64::INFINITY)),
+            ("h", f32_trunc(I32TruncSatF32S, f32::NAN)),
+            ("i", f32_trunc(I32TruncSatF32U, -1.0)),
+            ("j", f64_trunc(I64TruncSatF64S, f64::NEG_INFINITY)),
+        ];
+        let funcs = cases
+            .into_iter()
+            .map(|(name, (results, instrs))| (name, results, instrs))
+            .collect();
+        assert_eq!(
+            assertions(funcs),
+            [
+                traps("a", "invalid conversion to integer"),
+                traps("b", "integer overflow"),
+                returns("c", "(i32.const -2147483648)"),
+                traps("d", "integer overflow"),
+                returns("e", "(i32.const 0)"),
+                traps("f", "integer overflow"),
+                traps("g", "integer overflow"),
+                returns("h", "(i32.const 0)"),
+                returns("i", "(i32.const 0)"),
+                returns("j", "(i64.const -9223372036854775808)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn nearest_rounds_ties_to_even() {
+        let f32_nearest = |x: f32| (vec![ValType::F32], vec![F32Const(x), F32Nearest]);
+        let f64_nearest = |x: f64| (vec![ValType::F64], vec![F64Const(x), F64Nearest]);
+        let cases = vec![
+            ("a", f32_nearest(0.5)),
+            ("b", f32_nearest(1.5)),
+            ("c", f32_nearest(2.5)),
+            ("d", f32_nearest(-0.5)),
+            ("e", f32_nearest(-3.5)),
+            ("f", f64_nearest(2.5)),
+            ("g", f64_nearest(-2.5)),
+            ("h", f64_nearest(2.6)),
+        ];
+        let funcs = cases
+            .into_iter()
+            .map(|(name, (results, instrs))| (name, results, instrs))
+            .collect();
+        assert_eq!(
+            assertions(funcs),
+            [
+                returns("a", "(f32.const 0x0p+0)"),
+                returns("b", "(f32.const 0x1.000000p+1)"),
+                returns("c", "(f32.const 0x1.000000p+1)"),
+                returns("d", "(f32.const -0x0p+0)"),
+                returns("e", "(f32.const -0x1.000000p+2)"),
+                returns("f", "(f64.const 0x1.0000000000000p+1)"),
+                returns("g", "(f64.const -0x1.0000000000000p+1)"),
+                returns("h", "(f64.const 0x1.8000000000000p+1)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn min_and_max_of_zeros_and_nans() {
+        let f32_op = |op, a: f32, b: f32| (vec![ValType::F32], vec![F32Const(a), F32Const(b), op]);
+        let f64_op = |op, a: f64, b: f64| (vec![ValType::F64], vec![F64Const(a), F64Const(b), op]);
+        let cases = vec![
+            ("a", f32_op(F32Min, 0.0, -0.0)),
+            ("b", f32_op(F32Min, -0.0, 0.0)),
+            ("c", f32_op(F32Max, -0.0, 0.0)),
+            ("d", f32_op(F32Max, 0.0, -0.0)),
+            ("e", f64_op(F64Min, 0.0, -0.0)),
+            ("f", f64_op(F64Max, -0.0, 0.0)),
+            ("g", f64_op(F64Max, -0.0, -0.0)),
+            // The bits of the resulting NaNs are nondeterministic, so these
+            // are not invoked at all.
+            ("h", f32_op(F32Min, f32::NAN, 1.0)),
+            ("i", f64_op(F64Max, 1.0, f64::NAN)),
+        ];
+        let funcs = cases
+            .into_iter()
+            .map(|(name, (results, instrs))| (name, results, instrs))
+            .collect();
+        assert_eq!(
+            assertions(funcs),
+            [
+                returns("a", "(f32.const -0x0p+0)"),
+                returns("b", "(f32.const -0x0p+0)"),
+                returns("c", "(f32.const 0x0p+0)"),
+                returns("d", "(f32.const 0x0p+0)"),
+                returns("e", "(f64.const -0x0p+0)"),
+                returns("f", "(f64.const 0x0p+0)"),
+                returns("g", "(f64.const -0x0p+0)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn br_table_index_past_the_end() {
+        // Returns 10, 20, or 30 for the targets `0`, `1`, and the default.
+        let br_table = |index| {
+            vec![
+                Block(BlockType::Empty),
+                Block(BlockType::Empty),
+                Block(BlockType::Empty),
+                I32Const(index),
+                BrTable(vec![0, 1].into(), 2),
+                End,
+                I32Const(10),
+                Return,
+                End,
+                I32Const(20),
+                Return,
+                End,
+                I32Const(30),
+            ]
+        };
+        let funcs = vec![
+            ("a", vec![ValType::I32], br_table(0)),
+            ("b", vec![ValType::I32], br_table(1)),
+            ("c", vec![ValType::I32], br_table(2)),
+            ("d", vec![ValType::I32], br_table(100)),
+            ("e", vec![ValType::I32], br_table(-1)),
+        ];
+        assert_eq!(
+            assertions(funcs),
+            [
+                returns("a", "(i32.const 10)"),
+                returns("b", "(i32.const 20)"),
+                returns("c", "(i32.const 30)"),
+                returns("d", "(i32.const 30)"),
+                returns("e", "(i32.const 30)"),
+            ]
+        );
+    }
+}
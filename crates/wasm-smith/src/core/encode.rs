@@ -10,23 +10,41 @@ impl Module {
     fn encoded(&self) -> wasm_encoder::Module {
         let mut module = wasm_encoder::Module::new();
 
-        self.encode_types(&mut module);
-        self.encode_imports(&mut module);
-        self.encode_funcs(&mut module);
-        self.encode_tables(&mut module);
-        self.encode_memories(&mut module);
-        self.encode_tags(&mut module);
-        self.encode_globals(&mut module);
-        self.encode_exports(&mut module);
-        self.encode_start(&mut module);
-        self.encode_elems(&mut module);
-        self.encode_data_count(&mut module);
-        self.encode_code(&mut module);
-        self.encode_data(&mut module);
+        let sections: [fn(&Self, &mut wasm_encoder::Module); custom::NUM_PLACES - 1] = [
+            Self::encode_types,
+            Self::encode_imports,
+            Self::encode_funcs,
+            Self::encode_tables,
+            Self::encode_memories,
+            Self::encode_tags,
+            Self::encode_globals,
+            Self::encode_exports,
+            Self::encode_start,
+            Self::encode_elems,
+            Self::encode_data_count,
+            Self::encode_code,
+            Self::encode_data,
+        ];
+        for (place, encode_section) in sections.iter().enumerate() {
+            self.encode_custom_sections(&mut module, place);
+            encode_section(self, &mut module);
+        }
+        self.encode_custom_sections(&mut module, sections.len());
 
         module
     }
 
+    fn encode_custom_sections(&self, module: &mut wasm_encoder::Module, place: usize) {
+        for (_, section) in self.custom_sections.iter().filter(|(p, _)| *p == place) {
+            match section {
+                custom::CustomSection::Name(section) => module.section(section),
+                custom::CustomSection::Raw { name, data } => {
+                    module.section(&wasm_encoder::CustomSection { name, data })
+                }
+            };
+        }
+    }
+
     fn encode_types(&self, module: &mut wasm_encoder::Module) {
         if !self.should_encode_types {
             return;
@@ -202,9 +220,7 @@ impl Module {
         }
         let mut code = wasm_encoder::CodeSection::new();
         for c in &self.code {
-            // Skip the run-length encoding because it is a little
-            // annoying to compute; use a length of one for every local.
-            let mut func = wasm_encoder::Function::new(c.locals.iter().map(|l| (1, *l)));
+            let mut func = wasm_encoder::Function::new_with_locals_types(c.locals.iter().copied());
             match &c.instructions {
                 Instructions::Generated(instrs) => {
                     for instr in instrs {
@@ -0,0 +1,145 @@
+//! Introducing a single, chosen kind of invalidity into an otherwise valid
+//! module.
+
+use super::*;
+use wasm_encoder::Encode;
+
+/// A class of invalidity that [`MaybeInvalidModule::with_invalidity`] can
+/// introduce into a module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InvalidityClass {
+    /// An instruction whose operand has the wrong type.
+    TypeError,
+    /// An instruction immediate whose LEB128 encoding is too long, too large,
+    /// or cut off by the end of the function body.
+    MalformedLeb,
+    /// A local, global, function, or label index that is out of range.
+    OutOfRangeIndex,
+}
+
+impl FromStr for InvalidityClass {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "type-error" => Ok(InvalidityClass::TypeError),
+            "malformed-leb" => Ok(InvalidityClass::MalformedLeb),
+            "out-of-range-index" => Ok(InvalidityClass::OutOfRangeIndex),
+            _ => Err(format!("unknown invalidity class: {}", s)),
+        }
+    }
+}
+
+/// The configuration used to generate the valid module that invalidity is
+/// introduced into, which always defines at least one function.
+#[derive(Debug)]
+pub(super) struct InvalidityConfig;
+
+impl Config for InvalidityConfig {
+    fn min_types(&self) -> usize {
+        1
+    }
+
+    fn min_funcs(&self) -> usize {
+        1
+    }
+}
+
+impl Module {
+    /// Make one of this module's function bodies invalid in a way that
+    /// belongs to one of the given `classes`.
+    pub(super) fn introduce_invalidity(
+        &mut self,
+        u: &mut Unstructured,
+        classes: &[InvalidityClass],
+    ) -> Result<()> {
+        let class = *u.choose(classes)?;
+        if self.code.is_empty() {
+            return Err(arbitrary::Error::IncorrectFormat);
+        }
+
+        let index = u.int_in_range(0..=self.code.len() - 1)?;
+        let func_index = self.funcs.len() - self.num_defined_funcs + index;
+        let num_locals =
+            (self.funcs[func_index].1.params.len() + self.code[index].locals.len()) as u32;
+        let num_globals = self.globals.len() as u32;
+        let num_funcs = self.funcs.len() as u32;
+
+        let code = &mut self.code[index];
+        let instructions = match &mut code.instructions {
+            Instructions::Generated(instructions) => instructions,
+            Instructions::Arbitrary(_) => unreachable!(),
+        };
+        let at = u.int_in_range(0..=instructions.len())?;
+
+        match class {
+            // Each of these consumes a constant of the wrong type, which is a
+            // type error even in unreachable code.
+            InvalidityClass::TypeError => {
+                let (value, consumer) = match u.int_in_range(0..=3)? {
+                    0 => (Instruction::I32Const(0), Instruction::F32Neg),
+                    1 => (Instruction::I64Const(0), Instruction::I32Eqz),
+                    2 => (Instruction::F32Const(0.0), Instruction::I64Eqz),
+                    _ => (Instruction::F64Const(0.0), Instruction::I32Clz),
+                };
+                instructions.splice(at..at, [value, consumer]);
+            }
+            InvalidityClass::OutOfRangeIndex => {
+                let instruction = match u.int_in_range(0..=3)? {
+                    0 => Instruction::LocalGet(u.int_in_range(num_locals..=u32::MAX)?),
+                    1 => Instruction::GlobalGet(u.int_in_range(num_globals..=u32::MAX)?),
+                    2 => Instruction::Call(u.int_in_range(num_funcs..=u32::MAX)?),
+                    _ => {
+                        // The function's own label plus one label per nested
+                        // block is more than can be in scope anywhere.
+                        let num_labels = instructions
+                            .iter()
+                            .filter(|i| {
+                                matches!(
+                                    i,
+                                    Instruction::Block(_)
+                                        | Instruction::Loop(_)
+                                        | Instruction::If(_)
+                                        | Instruction::Try(_)
+                                )
+                            })
+                            .count() as u32
+                            + 1;
+                        Instruction::Br(u.int_in_range(num_labels..=u32::MAX)?)
+                    }
+                };
+                instructions.insert(at, instruction);
+            }
+            InvalidityClass::MalformedLeb => {
+                let mut body = Vec::new();
+                for instruction in &instructions[..at] {
+                    instruction.encode(&mut body);
+                }
+                match u.int_in_range(0..=2)? {
+                    // A 32-bit immediate encoded in six bytes.
+                    0 => {
+                        body.push(0x41); // i32.const
+                        body.extend_from_slice(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x00]);
+                    }
+                    // A 32-bit immediate with bits set beyond the 32nd.
+                    1 => {
+                        body.push(0x20); // local.get
+                        body.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 0x7f]);
+                    }
+                    // An immediate that runs into the end of the body.
+                    _ => {
+                        body.push(0x41); // i32.const
+                        body.push(0x80);
+                        code.instructions = Instructions::Arbitrary(body);
+                        return Ok(());
+                    }
+                }
+                for instruction in &instructions[at..] {
+                    instruction.encode(&mut body);
+                }
+                Instruction::End.encode(&mut body);
+                code.instructions = Instructions::Arbitrary(body);
+            }
+        }
+        Ok(())
+    }
+}
@@ -78,8 +78,11 @@ impl Module {
                     // the `i`th slot in a table and dynamically avoid trapping
                     // `call_indirect`s. Therefore, we can't emit *any*
                     // `call_indirect` instructions. Instead, we consume the
-                    // arguments and generate dummy results.
-                    Instruction::CallIndirect { ty, table: _ } => {
+                    // arguments and generate dummy results. The same goes for
+                    // `return_call_indirect`, which then returns the dummy
+                    // results.
+                    Instruction::CallIndirect { ty, table }
+                    | Instruction::ReturnCallIndirect { ty, table } => {
                         // When we can, avoid emitting `drop`s to consume the
                         // arguments when possible, since dead code isn't
                         // usually an interesting thing to give to a Wasm
@@ -108,11 +111,16 @@ impl Module {
                             let val_to_store =
                                 u32::try_from(this_func_ty.params.len() + code.locals.len())
                                     .unwrap();
-                            code.locals.push(ValType::I32);
+                            let (index_ty, store) = if self.tables[table as usize].table64 {
+                                (ValType::I64, Instruction::I64Store(memarg))
+                            } else {
+                                (ValType::I32, Instruction::I32Store(memarg))
+                            };
+                            code.locals.push(index_ty);
                             new_insts.push(Instruction::LocalSet(val_to_store));
                             new_insts.push(address.clone());
                             new_insts.push(Instruction::LocalGet(val_to_store));
-                            new_insts.push(Instruction::I32Store(memarg));
+                            new_insts.push(store);
                         } else {
                             new_insts.push(Instruction::Drop);
                         }
@@ -168,6 +176,9 @@ impl Module {
                         for ty in &callee_func_ty.results {
                             new_insts.push(dummy_value_inst(*ty));
                         }
+                        if let Instruction::ReturnCallIndirect { .. } = inst {
+                            new_insts.push(Instruction::Return);
+                        }
                     }
 
                     // For loads, we dynamically check whether the load will
@@ -528,7 +539,73 @@ impl Module {
                         segment: _,
                         table: _,
                     }
-                    | Instruction::TableCopy { src: _, dst: _ } => {
+                    | Instruction::TableCopy { src: _, dst: _ }
+                    | Instruction::MemoryAtomicNotify { memarg: _ }
+                    | Instruction::MemoryAtomicWait32 { memarg: _ }
+                    | Instruction::MemoryAtomicWait64 { memarg: _ }
+                    | Instruction::I32AtomicLoad { memarg: _ }
+                    | Instruction::I64AtomicLoad { memarg: _ }
+                    | Instruction::I32AtomicLoad8U { memarg: _ }
+                    | Instruction::I32AtomicLoad16U { memarg: _ }
+                    | Instruction::I64AtomicLoad8U { memarg: _ }
+                    | Instruction::I64AtomicLoad16U { memarg: _ }
+                    | Instruction::I64AtomicLoad32U { memarg: _ }
+                    | Instruction::I32AtomicStore { memarg: _ }
+                    | Instruction::I64AtomicStore { memarg: _ }
+                    | Instruction::I32AtomicStore8 { memarg: _ }
+                    | Instruction::I32AtomicStore16 { memarg: _ }
+                    | Instruction::I64AtomicStore8 { memarg: _ }
+                    | Instruction::I64AtomicStore16 { memarg: _ }
+                    | Instruction::I64AtomicStore32 { memarg: _ }
+                    | Instruction::I32AtomicRmwAdd { memarg: _ }
+                    | Instruction::I64AtomicRmwAdd { memarg: _ }
+                    | Instruction::I32AtomicRmw8AddU { memarg: _ }
+                    | Instruction::I32AtomicRmw16AddU { memarg: _ }
+                    | Instruction::I64AtomicRmw8AddU { memarg: _ }
+                    | Instruction::I64AtomicRmw16AddU { memarg: _ }
+                    | Instruction::I64AtomicRmw32AddU { memarg: _ }
+                    | Instruction::I32AtomicRmwSub { memarg: _ }
+                    | Instruction::I64AtomicRmwSub { memarg: _ }
+                    | Instruction::I32AtomicRmw8SubU { memarg: _ }
+                    | Instruction::I32AtomicRmw16SubU { memarg: _ }
+                    | Instruction::I64AtomicRmw8SubU { memarg: _ }
+                    | Instruction::I64AtomicRmw16SubU { memarg: _ }
+                    | Instruction::I64AtomicRmw32SubU { memarg: _ }
+                    | Instruction::I32AtomicRmwAnd { memarg: _ }
+                    | Instruction::I64AtomicRmwAnd { memarg: _ }
+                    | Instruction::I32AtomicRmw8AndU { memarg: _ }
+                    | Instruction::I32AtomicRmw16AndU { memarg: _ }
+                    | Instruction::I64AtomicRmw8AndU { memarg: _ }
+                    | Instruction::I64AtomicRmw16AndU { memarg: _ }
+                    | Instruction::I64AtomicRmw32AndU { memarg: _ }
+                    | Instruction::I32AtomicRmwOr { memarg: _ }
+                    | Instruction::I64AtomicRmwOr { memarg: _ }
+                    | Instruction::I32AtomicRmw8OrU { memarg: _ }
+                    | Instruction::I32AtomicRmw16OrU { memarg: _ }
+                    | Instruction::I64AtomicRmw8OrU { memarg: _ }
+                    | Instruction::I64AtomicRmw16OrU { memarg: _ }
+                    | Instruction::I64AtomicRmw32OrU { memarg: _ }
+                    | Instruction::I32AtomicRmwXor { memarg: _ }
+                    | Instruction::I64AtomicRmwXor { memarg: _ }
+                    | Instruction::I32AtomicRmw8XorU { memarg: _ }
+                    | Instruction::I32AtomicRmw16XorU { memarg: _ }
+                    | Instruction::I64AtomicRmw8XorU { memarg: _ }
+                    | Instruction::I64AtomicRmw16XorU { memarg: _ }
+                    | Instruction::I64AtomicRmw32XorU { memarg: _ }
+                    | Instruction::I32AtomicRmwXchg { memarg: _ }
+                    | Instruction::I64AtomicRmwXchg { memarg: _ }
+                    | Instruction::I32AtomicRmw8XchgU { memarg: _ }
+                    | Instruction::I32AtomicRmw16XchgU { memarg: _ }
+                    | Instruction::I64AtomicRmw8XchgU { memarg: _ }
+                    | Instruction::I64AtomicRmw16XchgU { memarg: _ }
+                    | Instruction::I64AtomicRmw32XchgU { memarg: _ }
+                    | Instruction::I32AtomicRmwCmpxchg { memarg: _ }
+                    | Instruction::I64AtomicRmwCmpxchg { memarg: _ }
+                    | Instruction::I32AtomicRmw8CmpxchgU { memarg: _ }
+                    | Instruction::I32AtomicRmw16CmpxchgU { memarg: _ }
+                    | Instruction::I64AtomicRmw8CmpxchgU { memarg: _ }
+                    | Instruction::I64AtomicRmw16CmpxchgU { memarg: _ }
+                    | Instruction::I64AtomicRmw32CmpxchgU { memarg: _ } => {
                         return Err(NotSupported { opcode: inst })
                     }
 
@@ -606,24 +683,32 @@ impl Module {
                     let elem_len = match &mut elem.items {
                         Elements::Functions(fs) => {
                             fs.truncate(usize::try_from(table.minimum).unwrap());
-                            u32::try_from(fs.len()).unwrap()
+                            u64::try_from(fs.len()).unwrap()
                         }
                         Elements::Expressions(es) => {
                             es.truncate(usize::try_from(table.minimum).unwrap());
-                            u32::try_from(es.len()).unwrap()
+                            u64::try_from(es.len()).unwrap()
                         }
                     };
 
                     match offset {
+                        Offset::Const64(n) => {
+                            let n = *n as u64;
+                            let n = n.checked_rem(table.minimum - elem_len).unwrap_or(0);
+                            *offset = Offset::Const64(n as i64);
+                        }
                         Offset::Const32(n) => {
-                            let n = *n as u32;
+                            let n = u64::from(*n as u32);
                             let n = n.checked_rem(table.minimum - elem_len).unwrap_or(0);
+                            let n = u32::try_from(n).unwrap();
                             *offset = Offset::Const32(n as i32);
                         }
+                        Offset::Global(_) if table.table64 => {
+                            *offset = Offset::Const64(0);
+                        }
                         Offset::Global(_) => {
                             *offset = Offset::Const32(0);
                         }
-                        _ => unreachable!(),
                     }
                 }
             }
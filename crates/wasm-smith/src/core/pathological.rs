@@ -0,0 +1,116 @@
+//! Generating function bodies with shapes that are known to stress engines.
+//!
+//! These bodies are not built by the `CodeBuilder`; they are made of a single
+//! extreme construct (e.g. thousands of nested blocks) that the builder would
+//! practically never generate, and they stay within the limits that
+//! `wasmparser` enforces.
+
+use super::*;
+use wasm_encoder::BlockType;
+
+/// The maximum number of locals in a function, including its parameters.
+const MAX_LOCALS: usize = 50_000;
+
+#[derive(Clone, Copy, Debug)]
+enum Shape {
+    DeepNesting,
+    LargeBrTable,
+    HugeFunction,
+}
+
+impl Module {
+    /// Generate the locals of a function with the given type, which may be a
+    /// very large number of them when pathological locals are enabled.
+    pub(super) fn arbitrary_pathological_locals(
+        &self,
+        u: &mut Unstructured,
+        ty: &FuncType,
+    ) -> Result<Option<Vec<ValType>>> {
+        if !self.config.pathological_locals() || !u.ratio(1, 8)? {
+            return Ok(None);
+        }
+
+        // Generate the locals in runs of the same type to keep the encoded
+        // local declarations well below the function size limit.
+        let count = u.int_in_range(1_000..=MAX_LOCALS - ty.params.len())?;
+        let mut locals = Vec::with_capacity(count);
+        while locals.len() < count {
+            let ty = self.arbitrary_valtype(u)?;
+            let run = u.int_in_range(1..=count - locals.len())?;
+            locals.extend((0..run).map(|_| ty));
+        }
+        Ok(Some(locals))
+    }
+
+    /// Generate a function body with one of the enabled pathological shapes,
+    /// if any.
+    pub(super) fn arbitrary_pathological_body(
+        &self,
+        u: &mut Unstructured,
+    ) -> Result<Option<Vec<Instruction>>> {
+        let mut shapes = Vec::new();
+        if self.config.pathological_deep_nesting() {
+            shapes.push(Shape::DeepNesting);
+        }
+        if self.config.pathological_br_tables() {
+            shapes.push(Shape::LargeBrTable);
+        }
+        if self.config.pathological_functions() {
+            shapes.push(Shape::HugeFunction);
+        }
+        if shapes.is_empty() || !u.ratio(1, 8)? {
+            return Ok(None);
+        }
+
+        let mut instructions = match u.choose(&shapes)? {
+            Shape::DeepNesting => {
+                let depth = u.int_in_range(1_000..=10_000)?;
+                let mut instructions = Vec::with_capacity(depth * 3);
+                for _ in 0..depth {
+                    match u.int_in_range(0..=2)? {
+                        0 => instructions.push(Instruction::Block(BlockType::Empty)),
+                        1 => instructions.push(Instruction::Loop(BlockType::Empty)),
+                        _ => {
+                            instructions.push(Instruction::I32Const(u.arbitrary()?));
+                            instructions.push(Instruction::If(BlockType::Empty));
+                        }
+                    }
+                }
+                instructions.extend((0..depth).map(|_| Instruction::End));
+                instructions
+            }
+            Shape::LargeBrTable => {
+                // NB: the targets are limited to the nested blocks since
+                // branching to the function's label would require its results.
+                let depth: u32 = u.int_in_range(1..=100)?;
+                let len = u.int_in_range(1_000..=20_000)?;
+                let mut targets = Vec::with_capacity(len);
+                for _ in 0..len {
+                    targets.push(u.int_in_range(0..=depth - 1)?);
+                }
+                let default = u.int_in_range(0..=depth - 1)?;
+
+                let mut instructions = Vec::with_capacity(depth as usize * 2 + 2);
+                instructions.extend((0..depth).map(|_| Instruction::Block(BlockType::Empty)));
+                instructions.push(Instruction::I32Const(u.arbitrary()?));
+                instructions.push(Instruction::BrTable(targets.into(), default));
+                instructions.extend((0..depth).map(|_| Instruction::End));
+                instructions
+            }
+            Shape::HugeFunction => {
+                let len = u.int_in_range(10_000..=15_000)?;
+                let mut instructions = Vec::with_capacity(len * 2);
+                for _ in 0..len {
+                    instructions.push(Instruction::I32Const(u.arbitrary()?));
+                    instructions.push(Instruction::Drop);
+                }
+                instructions
+            }
+        };
+
+        // Leave the stack polymorphic so that the body is valid regardless
+        // of the function's results.
+        instructions.push(Instruction::Unreachable);
+        Ok(Some(instructions))
+    }
+}
@@ -55,14 +55,14 @@
 
 #![deny(missing_docs, missing_debug_implementations)]
 // Needed for the `instructions!` macro in `src/code_builder.rs`.
-#![recursion_limit = "512"]
+#![recursion_limit = "1024"]
 
 mod component;
 mod config;
 mod core;
 
 pub use crate::core::{
-    no_traps::NotSupported, ConfiguredModule, InstructionKind, InstructionKinds,
+    no_traps::NotSupported, ConfiguredModule, InstructionKind, InstructionKinds, InvalidityClass,
     MaybeInvalidModule, Module,
 };
 use arbitrary::{Result, Unstructured};
@@ -9,6 +9,8 @@ fn smoke_test_component() {
     let mut rng = SmallRng::seed_from_u64(0);
     let mut buf = vec![0; 1024];
     let mut ok_count = 0;
+    let mut core_instance_count = 0;
+    let mut alias_count = 0;
 
     for _ in 0..NUM_RUNS {
         rng.fill_bytes(&mut buf);
@@ -30,6 +32,18 @@ fn smoke_test_component() {
                     e
                 );
             }
+
+            for payload in wasmparser::Parser::new(0).parse_all(&component) {
+                match payload.unwrap() {
+                    wasmparser::Payload::InstanceSection(s) if s.get_count() > 0 => {
+                        core_instance_count += 1;
+                    }
+                    wasmparser::Payload::ComponentAliasSection(s) if s.get_count() > 0 => {
+                        alias_count += 1;
+                    }
+                    _ => {}
+                }
+            }
         }
     }
 
@@ -39,4 +53,10 @@ fn smoke_test_component() {
         NUM_RUNS,
         ok_count as f64 / NUM_RUNS as f64 * 100.0
     );
+    println!(
+        "Generated {} core instance sections and {} alias sections",
+        core_instance_count, alias_count
+    );
+    assert!(core_instance_count > 0);
+    assert!(alias_count > 0);
 }
@@ -1,7 +1,10 @@
 use arbitrary::{Arbitrary, Unstructured};
 use rand::{rngs::SmallRng, RngCore, SeedableRng};
 use std::collections::HashMap;
-use wasm_smith::{Config, ConfiguredModule, Module, SwarmConfig};
+use wasm_smith::{
+    Config, ConfiguredModule, InstructionKind, InstructionKinds, InvalidityClass,
+    MaybeInvalidModule, Module, SwarmConfig,
+};
 use wasmparser::{Parser, TypeRef, ValType, Validator, WasmFeatures};
 
 #[test]
@@ -73,6 +76,457 @@ fn multi_value_disabled() {
     }
 }
 
+#[test]
+fn smoke_test_threads() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 2048];
+    let mut atomics = 0;
+    for _ in 0..1024 {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let mut cfg = SwarmConfig::arbitrary(&mut u).unwrap();
+        cfg.threads_enabled = true;
+        cfg.min_memories = 1;
+        cfg.max_memories = cfg.max_memories.max(1);
+        let features = cfg.features();
+        if let Ok(module) = Module::new(cfg, &mut u) {
+            let wasm_bytes = module.to_bytes();
+            let mut validator = Validator::new_with_features(features);
+            validate(&mut validator, &wasm_bytes);
+            if wasmprinter::print_bytes(&wasm_bytes)
+                .unwrap()
+                .contains(".atomic.")
+            {
+                atomics += 1;
+            }
+        }
+    }
+    assert!(atomics > 0);
+}
+
+#[test]
+fn invalidity_classes() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 2048];
+    for (class, messages) in [
+        (InvalidityClass::TypeError, &["type mismatch"][..]),
+        (
+            InvalidityClass::MalformedLeb,
+            &["too long", "too large", "unexpected end"][..],
+        ),
+        (
+            InvalidityClass::OutOfRangeIndex,
+            &[
+                "unknown local",
+                "unknown global",
+                "unknown function",
+                "unknown label",
+            ][..],
+        ),
+    ] {
+        let mut seen = vec![false; messages.len()];
+        for _ in 0..256 {
+            rng.fill_bytes(&mut buf);
+            let mut u = Unstructured::new(&buf);
+            let module = match MaybeInvalidModule::with_invalidity(&mut u, &[class]) {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+            let wasm_bytes = module.to_bytes();
+            let err = match Validator::new_with_features(wasm_features()).validate_all(&wasm_bytes)
+            {
+                Ok(_) => panic!("module should be invalid for {:?}", class),
+                Err(e) => e,
+            };
+            let message = err.message();
+            let i = messages
+                .iter()
+                .position(|m| message.contains(m))
+                .unwrap_or_else(|| panic!("unexpected error for {:?}: {}", class, message));
+            seen[i] = true;
+        }
+        assert!(seen.iter().all(|s| *s), "{:?}: {:?}", class, seen);
+    }
+}
+
+#[test]
+fn smoke_test_custom_sections() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 2048];
+    let (mut names, mut producers, mut dylink0, mut other) = (0, 0, 0, 0);
+    for _ in 0..1024 {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let mut cfg = SwarmConfig::arbitrary(&mut u).unwrap();
+        cfg.generate_custom_sections = true;
+        let features = cfg.features();
+        if let Ok(module) = Module::new(cfg, &mut u) {
+            let wasm_bytes = module.to_bytes();
+            let mut validator = Validator::new_with_features(features);
+            validate(&mut validator, &wasm_bytes);
+            wasmprinter::print_bytes(&wasm_bytes).unwrap();
+            for payload in Parser::new(0).parse_all(&wasm_bytes) {
+                let section = match payload.unwrap() {
+                    wasmparser::Payload::CustomSection(section) => section,
+                    _ => continue,
+                };
+                match section.name() {
+                    "name" => {
+                        let reader = wasmparser::NameSectionReader::new(
+                            section.data(),
+                            section.data_offset(),
+                        )
+                        .unwrap();
+                        for name in reader {
+                            let name = name.unwrap();
+                            assert!(!matches!(name, wasmparser::Name::Unknown { .. }));
+                        }
+                        names += 1;
+                    }
+                    "producers" => {
+                        let reader = wasmparser::ProducersSectionReader::new(
+                            section.data(),
+                            section.data_offset(),
+                        )
+                        .unwrap();
+                        for field in reader {
+                            let field = field.unwrap();
+                            for value in field.get_producer_field_values_reader().unwrap() {
+                                value.unwrap();
+                            }
+                        }
+                        producers += 1;
+                    }
+                    "dylink.0" => {
+                        let reader = wasmparser::Dylink0SectionReader::new(
+                            section.data(),
+                            section.data_offset(),
+                        )
+                        .unwrap();
+                        for subsection in reader {
+                            let subsection = subsection.unwrap();
+                            assert!(!matches!(
+                                subsection,
+                                wasmparser::Dylink0Subsection::Unknown { .. }
+                            ));
+                        }
+                        dylink0 += 1;
+                    }
+                    _ => other += 1,
+                }
+            }
+        }
+    }
+    assert!(names > 0);
+    assert!(producers > 0);
+    assert!(dylink0 > 0);
+    assert!(other > 0);
+}
+
+#[test]
+fn smoke_test_tail_calls() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 2048];
+    let (mut direct, mut indirect) = (0, 0);
+    for _ in 0..1024 {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let mut cfg = SwarmConfig::arbitrary(&mut u).unwrap();
+        cfg.tail_call_enabled = true;
+        let features = cfg.features();
+        if let Ok(module) = Module::new(cfg, &mut u) {
+            let wasm_bytes = module.to_bytes();
+            let mut validator = Validator::new_with_features(features);
+            validate(&mut validator, &wasm_bytes);
+            for payload in Parser::new(0).parse_all(&wasm_bytes) {
+                if let wasmparser::Payload::CodeSectionEntry(body) = payload.unwrap() {
+                    let mut reader = body.get_operators_reader().unwrap();
+                    while !reader.eof() {
+                        match reader.read().unwrap() {
+                            wasmparser::Operator::ReturnCall { .. } => direct += 1,
+                            wasmparser::Operator::ReturnCallIndirect { .. } => indirect += 1,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+    }
+    assert!(direct > 0);
+    assert!(indirect > 0);
+}
+
+#[test]
+fn smoke_test_table64() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 2048];
+    let mut table64 = 0;
+    for _ in 0..1024 {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let mut cfg = SwarmConfig::arbitrary(&mut u).unwrap();
+        cfg.memory64_enabled = true;
+        let features = cfg.features();
+        if let Ok(module) = Module::new(cfg, &mut u) {
+            let wasm_bytes = module.to_bytes();
+            let mut validator = Validator::new_with_features(features);
+            validate(&mut validator, &wasm_bytes);
+            for payload in Parser::new(0).parse_all(&wasm_bytes) {
+                if let wasmparser::Payload::TableSection(reader) = payload.unwrap() {
+                    for table in reader {
+                        if table.unwrap().table64 {
+                            table64 += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    assert!(table64 > 0);
+}
+
+#[test]
+fn smoke_test_relaxed_simd_deterministic_only() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 2048];
+    let mut relaxed = 0;
+    for _ in 0..1024 {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let mut cfg = SwarmConfig::arbitrary(&mut u).unwrap();
+        cfg.simd_enabled = true;
+        cfg.relaxed_simd_enabled = true;
+        cfg.relaxed_simd_deterministic_only = true;
+        cfg.allowed_instructions = InstructionKinds::new(&[
+            InstructionKind::Vector,
+            InstructionKind::Parametric,
+            InstructionKind::Variable,
+        ]);
+        let features = cfg.features();
+        if let Ok(module) = Module::new(cfg, &mut u) {
+            let wasm_bytes = module.to_bytes();
+            let mut validator = Validator::new_with_features(features);
+            validate(&mut validator, &wasm_bytes);
+            for payload in Parser::new(0).parse_all(&wasm_bytes) {
+                if let wasmparser::Payload::CodeSectionEntry(body) = payload.unwrap() {
+                    let mut reader = body.get_operators_reader().unwrap();
+                    let mut prev = String::new();
+                    while !reader.eof() {
+                        let op = format!("{:?}", reader.read().unwrap());
+                        assert!(!op.contains("RelaxedTrunc"), "{}", op);
+                        assert!(!op.contains("RelaxedM"), "{}", op);
+                        assert!(!op.contains("Fma") && !op.contains("Fms"), "{}", op);
+                        if op.contains("RelaxedSwizzle") {
+                            assert_eq!(prev, "V128And");
+                            relaxed += 1;
+                        }
+                        if op.contains("LaneSelect") {
+                            assert!(prev.ends_with("LtS"), "{}", prev);
+                            relaxed += 1;
+                        }
+                        prev = op;
+                    }
+                }
+            }
+        }
+    }
+    assert!(relaxed > 0);
+}
+
+#[test]
+fn smoke_test_pathological_shapes() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 2048];
+    let (mut deep, mut br_tables, mut locals, mut huge) = (0, 0, 0, 0);
+    for _ in 0..64 {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let mut cfg = SwarmConfig::arbitrary(&mut u).unwrap();
+        cfg.pathological_deep_nesting = true;
+        cfg.pathological_br_tables = true;
+        cfg.pathological_locals = true;
+        cfg.pathological_functions = true;
+        cfg.pathological_function_count = true;
+        let max_funcs = cfg.max_funcs;
+        let features = cfg.features();
+        let module = match Module::new(cfg, &mut u) {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+        let wasm_bytes = module.to_bytes();
+        let mut validator = Validator::new_with_features(features);
+        validate(&mut validator, &wasm_bytes);
+
+        let mut funcs = 0;
+        for payload in Parser::new(0).parse_all(&wasm_bytes) {
+            match payload.unwrap() {
+                wasmparser::Payload::ImportSection(reader) => {
+                    for import in reader {
+                        if let TypeRef::Func(_) = import.unwrap().ty {
+                            funcs += 1;
+                        }
+                    }
+                }
+                wasmparser::Payload::FunctionSection(reader) => funcs += reader.get_count(),
+                wasmparser::Payload::CodeSectionEntry(body) => {
+                    let mut reader = body.get_locals_reader().unwrap();
+                    let mut num_locals = 0;
+                    for _ in 0..reader.get_count() {
+                        num_locals += reader.read().unwrap().0;
+                    }
+                    if num_locals >= 1_000 {
+                        locals += 1;
+                    }
+
+                    let mut reader = body.get_operators_reader().unwrap();
+                    let (mut depth, mut max_depth, mut drops) = (0, 0, 0);
+                    while !reader.eof() {
+                        match reader.read().unwrap() {
+                            wasmparser::Operator::Block { .. }
+                            | wasmparser::Operator::Loop { .. }
+                            | wasmparser::Operator::If { .. } => {
+                                depth += 1;
+                                max_depth = max_depth.max(depth);
+                            }
+                            wasmparser::Operator::End => depth -= 1,
+                            wasmparser::Operator::BrTable { table } if table.len() >= 1_000 => {
+                                br_tables += 1;
+                            }
+                            wasmparser::Operator::Drop => drops += 1,
+                            _ => {}
+                        }
+                    }
+                    if max_depth >= 1_000 {
+                        deep += 1;
+                    }
+                    if drops >= 10_000 {
+                        huge += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        assert!(funcs == 0 || funcs as usize == max_funcs);
+    }
+    assert!(deep > 0);
+    assert!(br_tables > 0);
+    assert!(locals > 0);
+    assert!(huge > 0);
+}
+
+#[test]
+fn smoke_test_wast_script() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 2048];
+    let (mut returns, mut traps) = (0, 0);
+    for _ in 0..1024 {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let mut cfg = SwarmConfig::arbitrary(&mut u).unwrap();
+        cfg.max_imports = 0;
+        cfg.max_data_segments = 0;
+        cfg.max_element_segments = 0;
+        cfg.allow_start_export = false;
+        cfg.allowed_instructions = InstructionKinds::new(&[
+            InstructionKind::Control,
+            InstructionKind::Parametric,
+            InstructionKind::Variable,
+            InstructionKind::Numeric,
+        ]);
+        if let Ok(module) = Module::new(cfg, &mut u) {
+            let script = module.to_wast(&mut u).unwrap();
+            let buf = wast::parser::ParseBuffer::new(&script).unwrap();
+            let wast = wast::parser::parse::<wast::Wast>(&buf).unwrap();
+            for directive in wast.directives {
+                match directive {
+                    wast::WastDirective::Wat(mut wat) => {
+                        let wasm_bytes = wat.encode().unwrap();
+                        let mut validator = Validator::new_with_features(wasm_features());
+                        validate(&mut validator, &wasm_bytes);
+                    }
+                    wast::WastDirective::AssertReturn { .. } => returns += 1,
+                    wast::WastDirective::AssertTrap { message, .. } => {
+                        // Only traps of the supported instructions are
+                        // expected, with the spec's messages.
+                        assert!(
+                            [
+                                "unreachable",
+                                "integer divide by zero",
+                                "integer overflow",
+                                "invalid conversion to integer",
+                            ]
+                            .contains(&message),
+                            "unexpected trap message {:?}",
+                            message
+                        );
+                        traps += 1;
+                    }
+                    other => panic!("unexpected directive {:?}", other),
+                }
+            }
+        }
+    }
+    assert!(returns > 0);
+    assert!(traps > 0);
+}
+
+#[test]
+fn instruction_kind_weights() {
+    #[derive(Debug)]
+    struct MemoryHeavyConfig(u32);
+
+    impl Config for MemoryHeavyConfig {
+        fn min_memories(&self) -> u32 {
+            1
+        }
+
+        fn instruction_kind_weight(&self, kind: InstructionKind) -> u32 {
+            if kind == InstructionKind::Memory {
+                self.0
+            } else {
+                1
+            }
+        }
+    }
+
+    // Returns the number of memory instructions and the total number of
+    // instructions generated with the given weight for memory instructions.
+    let count = |weight| {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut buf = vec![0; 2048];
+        let (mut memory, mut total) = (0, 0);
+        for _ in 0..256 {
+            rng.fill_bytes(&mut buf);
+            let mut u = Unstructured::new(&buf);
+            if let Ok(module) = Module::new(MemoryHeavyConfig(weight), &mut u) {
+                let wasm_bytes = module.to_bytes();
+                let mut validator = Validator::new_with_features(wasm_features());
+                validate(&mut validator, &wasm_bytes);
+                for payload in Parser::new(0).parse_all(&wasm_bytes) {
+                    if let wasmparser::Payload::CodeSectionEntry(body) = payload.unwrap() {
+                        let mut reader = body.get_operators_reader().unwrap();
+                        while !reader.eof() {
+                            let op = format!("{:?}", reader.read().unwrap());
+                            if op.contains("Load") || op.contains("Store") {
+                                memory += 1;
+                            }
+                            total += 1;
+                        }
+                    }
+                }
+            }
+        }
+        (memory as f64, total as f64)
+    };
+
+    let (memory, total) = count(1);
+    let (heavy_memory, heavy_total) = count(100);
+    assert!(heavy_memory / heavy_total > 2.0 * memory / total);
+
+    let (memory, _) = count(0);
+    assert_eq!(memory, 0.0);
+}
+
 #[test]
 fn smoke_can_smith_valid_webassembly_one_point_oh() {
     let mut rng = SmallRng::seed_from_u64(42);
@@ -92,7 +546,7 @@ fn smoke_can_smith_valid_webassembly_one_point_oh() {
         cfg.memory64_enabled = false;
         cfg.max_memories = 1;
         cfg.max_tables = 1;
-        let features = parser_features_from_config(&cfg);
+        let features = cfg.features();
         if let Ok(module) = Module::new(cfg, &mut u) {
             let wasm_bytes = module.to_bytes();
             // This table should set to `true` only features specified in wasm-core-1 spec.
@@ -113,7 +567,7 @@ fn smoke_test_imports_config() {
 
         let mut u = Unstructured::new(&buf);
         let (config, available) = import_config(&mut u);
-        let features = parser_features_from_config(&config);
+        let features = config.features();
 
         if let Ok(module) = Module::new(config, &mut u) {
             let wasm_bytes = module.to_bytes();
@@ -279,36 +733,19 @@ fn import_config(
     (config, available)
 }
 
-fn parser_features_from_config(config: &impl Config) -> WasmFeatures {
-    WasmFeatures {
-        mutable_global: true,
-        saturating_float_to_int: config.saturating_float_to_int_enabled(),
-        sign_extension: config.sign_extension_ops_enabled(),
-        reference_types: config.reference_types_enabled(),
-        multi_value: config.multi_value_enabled(),
-        bulk_memory: config.bulk_memory_enabled(),
-        simd: config.simd_enabled(),
-        relaxed_simd: config.relaxed_simd_enabled(),
-        multi_memory: config.max_memories() > 1,
-        exceptions: config.exceptions_enabled(),
-        memory64: config.memory64_enabled(),
-
-        threads: false,
-        tail_call: false,
-        deterministic_only: false,
-        extended_const: false,
-        component_model: false,
-    }
-}
-
 fn validate(validator: &mut Validator, bytes: &[u8]) {
     let err = match validator.validate_all(bytes) {
         Ok(_) => return,
         Err(e) => e,
     };
-    drop(std::fs::write("test.wasm", &bytes));
+    let dir = tempfile::tempdir().unwrap().keep();
+    drop(std::fs::write(dir.join("test.wasm"), bytes));
     if let Ok(text) = wasmprinter::print_bytes(bytes) {
-        drop(std::fs::write("test.wat", &text));
+        drop(std::fs::write(dir.join("test.wat"), &text));
     }
-    panic!("wasm failed to validate {:?}", err);
+    panic!(
+        "wasm failed to validate {:?} (written to {})",
+        err,
+        dir.join("test.wasm").display()
+    );
 }
@@ -206,6 +206,11 @@ fn read_all_wasm(wasm: &[u8]) -> Result<()> {
                     item?;
                 }
             }
+            ComponentValueSection(s) => {
+                for item in s {
+                    item?;
+                }
+            }
 
             Version { .. }
             | StartSection { .. }
@@ -231,24 +236,7 @@ fn collect_benchmark_inputs() -> Vec<BenchmarkInput> {
 
 fn define_benchmarks(c: &mut Criterion) {
     fn validator() -> Validator {
-        Validator::new_with_features(WasmFeatures {
-            reference_types: true,
-            multi_value: true,
-            simd: true,
-            relaxed_simd: true,
-            exceptions: true,
-            component_model: true,
-            bulk_memory: true,
-            threads: true,
-            tail_call: true,
-            multi_memory: true,
-            memory64: true,
-            extended_const: true,
-            deterministic_only: false,
-            mutable_global: true,
-            saturating_float_to_int: true,
-            sign_extension: true,
-        })
+        Validator::new_with_features(WasmFeatures::all())
     }
 
     let test_inputs = once_cell::unsync::Lazy::new(collect_benchmark_inputs);
@@ -544,6 +544,17 @@ impl<'a> BinaryReader<'a> {
         })
     }
 
+    pub(crate) fn read_component_value(&mut self) -> Result<ComponentValue<'a>> {
+        let ty = self.read_component_val_type()?;
+        let len = self.read_var_u32()? as usize;
+        let offset = self.original_position();
+        Ok(ComponentValue {
+            ty,
+            data: self.read_bytes(len)?,
+            offset,
+        })
+    }
+
     pub(crate) fn read_import(&mut self) -> Result<Import<'a>> {
         Ok(Import {
             module: self.read_string()?,
@@ -604,10 +615,61 @@ impl<'a> BinaryReader<'a> {
                 },
                 x => return self.invalid_leading_byte(x, "canonical function lower"),
             },
+            0x09 => CanonicalFunction::TaskReturn {
+                type_index: self.read_var_u32()?,
+            },
+            0x0a => CanonicalFunction::TaskWait {
+                async_: self.read_async_flag()?,
+                memory: self.read_var_u32()?,
+            },
+            0x0b => CanonicalFunction::TaskPoll {
+                async_: self.read_async_flag()?,
+                memory: self.read_var_u32()?,
+            },
+            0x0c => CanonicalFunction::TaskYield {
+                async_: self.read_async_flag()?,
+            },
+            0x0e => CanonicalFunction::StreamNew {
+                ty: self.read_var_u32()?,
+            },
+            0x0f => CanonicalFunction::StreamReceive {
+                ty: self.read_var_u32()?,
+                options: self.read_canonical_options()?,
+            },
+            0x10 => CanonicalFunction::StreamSend {
+                ty: self.read_var_u32()?,
+                options: self.read_canonical_options()?,
+            },
+            0x15 => CanonicalFunction::FutureNew {
+                ty: self.read_var_u32()?,
+            },
+            0x16 => CanonicalFunction::FutureReceive {
+                ty: self.read_var_u32()?,
+                options: self.read_canonical_options()?,
+            },
+            0x17 => CanonicalFunction::FutureSend {
+                ty: self.read_var_u32()?,
+                options: self.read_canonical_options()?,
+            },
+            0x1e => CanonicalFunction::ErrorDrop,
             x => return self.invalid_leading_byte(x, "canonical function"),
         })
     }
 
+    fn read_canonical_options(&mut self) -> Result<Box<[CanonicalOption]>> {
+        (0..self.read_size(MAX_WASM_CANONICAL_OPTIONS, "canonical options")?)
+            .map(|_| self.read_canonical_option())
+            .collect()
+    }
+
+    fn read_async_flag(&mut self) -> Result<bool> {
+        match self.read_u8()? {
+            0x00 => Ok(false),
+            0x01 => Ok(true),
+            x => self.invalid_leading_byte(x, "async flag"),
+        }
+    }
+
     pub(crate) fn read_canonical_option(&mut self) -> Result<CanonicalOption> {
         Ok(match self.read_u8()? {
             0x00 => CanonicalOption::UTF8,
@@ -616,6 +678,8 @@ impl<'a> BinaryReader<'a> {
             0x03 => CanonicalOption::Memory(self.read_var_u32()?),
             0x04 => CanonicalOption::Realloc(self.read_var_u32()?),
             0x05 => CanonicalOption::PostReturn(self.read_var_u32()?),
+            0x06 => CanonicalOption::Async,
+            0x07 => CanonicalOption::Callback(self.read_var_u32()?),
             x => return self.invalid_leading_byte(x, "canonical option"),
         })
     }
@@ -758,26 +822,32 @@ impl<'a> BinaryReader<'a> {
 
     pub(crate) fn read_table_type(&mut self) -> Result<TableType> {
         let element_type = self.read_val_type()?;
-        let has_max = match self.read_u8()? {
-            0x00 => false,
-            0x01 => true,
-            _ => {
-                return Err(BinaryReaderError::new(
-                    "invalid table resizable limits flags",
-                    self.original_position() - 1,
-                ))
-            }
-        };
-        let initial = self.read_var_u32()?;
-        let maximum = if has_max {
-            Some(self.read_var_u32()?)
-        } else {
-            None
-        };
+        let pos = self.original_position();
+        let flags = self.read_u8()?;
+        if (flags & !0b101) != 0 {
+            return Err(BinaryReaderError::new(
+                "invalid table resizable limits flags",
+                pos,
+            ));
+        }
+
+        let table64 = flags & 0b100 != 0;
+        let has_max = flags & 0b001 != 0;
         Ok(TableType {
             element_type,
-            initial,
-            maximum,
+            table64,
+            initial: if table64 {
+                self.read_var_u64()?
+            } else {
+                self.read_var_u32()?.into()
+            },
+            maximum: if !has_max {
+                None
+            } else if table64 {
+                Some(self.read_var_u64()?)
+            } else {
+                Some(self.read_var_u32()?.into())
+            },
         })
     }
 
@@ -3,10 +3,10 @@ use crate::{
     limits::MAX_WASM_MODULE_SIZE, BinaryReader, BinaryReaderError, ComponentAliasSectionReader,
     ComponentCanonicalSectionReader, ComponentExportSectionReader, ComponentImportSectionReader,
     ComponentInstanceSectionReader, ComponentStartSectionReader, ComponentTypeSectionReader,
-    CustomSectionReader, DataSectionReader, ElementSectionReader, ExportSectionReader,
-    FunctionBody, FunctionSectionReader, GlobalSectionReader, ImportSectionReader,
-    InstanceSectionReader, MemorySectionReader, Result, TableSectionReader, TagSectionReader,
-    TypeSectionReader,
+    ComponentValueSectionReader, CustomSectionReader, DataSectionReader, ElementSectionReader,
+    ExportSectionReader, FunctionBody, FunctionSectionReader, GlobalSectionReader,
+    ImportSectionReader, InstanceSectionReader, MemorySectionReader, Result, TableSectionReader,
+    TagSectionReader, TypeSectionReader,
 };
 use std::convert::TryInto;
 use std::fmt;
@@ -254,6 +254,9 @@ pub enum Payload<'a> {
     /// A component export section was received, and the provided reader can be
     /// used to parse the contents of the component export section.
     ComponentExportSection(ComponentExportSectionReader<'a>),
+    /// A component value section was received, and the provided reader can be
+    /// used to parse the contents of the component value section.
+    ComponentValueSection(ComponentValueSectionReader<'a>),
 
     /// A module or component custom section was received.
     CustomSection(CustomSectionReader<'a>),
@@ -409,6 +412,7 @@ impl Parser {
     ///             ComponentStartSection { .. } => { /* ... */ }
     ///             ComponentImportSection(_) => { /* ... */ }
     ///             ComponentExportSection(_) => { /* ... */ }
+    ///             ComponentValueSection(_) => { /* ... */ }
     ///
     ///             CustomSection(_) => { /* ... */ }
     ///
@@ -667,6 +671,12 @@ impl Parser {
                         ComponentExportSectionReader::new,
                         ComponentExportSection,
                     ),
+                    (Encoding::Component, 12) => section(
+                        reader,
+                        len,
+                        ComponentValueSectionReader::new,
+                        ComponentValueSection,
+                    ),
                     (_, id) => {
                         let offset = reader.original_position();
                         let contents = reader.read_bytes(len as usize)?;
@@ -1010,6 +1020,10 @@ impl fmt::Debug for Payload<'_> {
                 .debug_tuple("ComponentExportSection")
                 .field(&"...")
                 .finish(),
+            ComponentValueSection(_) => f
+                .debug_tuple("ComponentValueSection")
+                .field(&"...")
+                .finish(),
 
             CustomSection(c) => f.debug_tuple("CustomSection").field(c).finish(),
 
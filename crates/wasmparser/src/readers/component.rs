@@ -5,6 +5,7 @@ mod imports;
 mod instances;
 mod start;
 mod types;
+mod values;
 
 pub use self::aliases::*;
 pub use self::canonicals::*;
@@ -13,3 +14,4 @@ pub use self::imports::*;
 pub use self::instances::*;
 pub use self::start::*;
 pub use self::types::*;
+pub use self::values::*;
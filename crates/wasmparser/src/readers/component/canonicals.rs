@@ -22,6 +22,13 @@ pub enum CanonicalOption {
     /// The post-return function to use if the lifting of a function requires
     /// cleanup after the function returns.
     PostReturn(u32),
+    /// Use the async ABI for lifting or lowering.
+    Async,
+    /// Use the specified function to deliver async events to a lifted
+    /// function which uses the stackless async ABI.
+    ///
+    /// The value is an index to a core function of type `(func (param i32 i32 i32) (result i32))`.
+    Callback(u32),
 }
 
 /// Represents a canonical function in a WebAssembly component.
@@ -43,6 +50,75 @@ pub enum CanonicalFunction {
         /// The canonical options for the function.
         options: Box<[CanonicalOption]>,
     },
+    /// A function which returns a result to the caller of a lifted async
+    /// function.
+    TaskReturn {
+        /// The index of the type of the function whose results are being
+        /// returned.
+        type_index: u32,
+    },
+    /// A function which waits for at least one outstanding async task, stream,
+    /// or future to make progress, returning the first such event.
+    TaskWait {
+        /// Whether the task may be suspended while waiting.
+        async_: bool,
+        /// The index of the core memory the event is written to.
+        memory: u32,
+    },
+    /// A function which checks whether any outstanding async task, stream, or
+    /// future has made progress without blocking.
+    TaskPoll {
+        /// Whether the task may be suspended while polling.
+        async_: bool,
+        /// The index of the core memory the event is written to.
+        memory: u32,
+    },
+    /// A function which yields control to the host so that other tasks are
+    /// able to make progress, if any.
+    TaskYield {
+        /// Whether the task may be suspended while yielding.
+        async_: bool,
+    },
+    /// A function which creates a new `stream` handle.
+    StreamNew {
+        /// The index of the `stream` type.
+        ty: u32,
+    },
+    /// A function which receives values from a `stream`.
+    StreamReceive {
+        /// The index of the `stream` type.
+        ty: u32,
+        /// The canonical options for the values being received.
+        options: Box<[CanonicalOption]>,
+    },
+    /// A function which sends values to a `stream`.
+    StreamSend {
+        /// The index of the `stream` type.
+        ty: u32,
+        /// The canonical options for the values being sent.
+        options: Box<[CanonicalOption]>,
+    },
+    /// A function which creates a new `future` handle.
+    FutureNew {
+        /// The index of the `future` type.
+        ty: u32,
+    },
+    /// A function which receives the value of a `future`.
+    FutureReceive {
+        /// The index of the `future` type.
+        ty: u32,
+        /// The canonical options for the value being received.
+        options: Box<[CanonicalOption]>,
+    },
+    /// A function which sends the value of a `future`.
+    FutureSend {
+        /// The index of the `future` type.
+        ty: u32,
+        /// The canonical options for the value being sent.
+        options: Box<[CanonicalOption]>,
+    },
+    /// A function which drops an `error` handle.
+    ErrorDrop,
 }
 
 /// A reader for the canonical section of a WebAssembly component.
@@ -0,0 +1,175 @@
+use crate::{
+    BinaryReader, BinaryReaderError, ComponentValType, Ieee32, Ieee64, PrimitiveValType, Result,
+    SectionIteratorLimited, SectionReader, SectionWithLimitedItems,
+};
+use std::ops::Range;
+
+/// Represents a value defined in the value section of a WebAssembly component.
+#[derive(Debug, Clone)]
+pub struct ComponentValue<'a> {
+    /// The type of the value.
+    pub ty: ComponentValType,
+    /// The encoded contents of the value.
+    pub data: &'a [u8],
+    /// The original offset of `data` in the binary.
+    pub offset: usize,
+}
+
+/// Represents a decoded value of a primitive component value type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrimitiveValue<'a> {
+    /// A boolean value.
+    Bool(bool),
+    /// A signed 8-bit integer.
+    S8(i8),
+    /// An unsigned 8-bit integer.
+    U8(u8),
+    /// A signed 16-bit integer.
+    S16(i16),
+    /// An unsigned 16-bit integer.
+    U16(u16),
+    /// A signed 32-bit integer.
+    S32(i32),
+    /// An unsigned 32-bit integer.
+    U32(u32),
+    /// A signed 64-bit integer.
+    S64(i64),
+    /// An unsigned 64-bit integer.
+    U64(u64),
+    /// A 32-bit floating point number.
+    Float32(Ieee32),
+    /// A 64-bit floating point number.
+    Float64(Ieee64),
+    /// A Unicode character.
+    Char(char),
+    /// A string.
+    String(&'a str),
+}
+
+impl<'a> ComponentValue<'a> {
+    /// Decodes the contents of this value as a value of the given primitive
+    /// type.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the contents are not a valid encoding of a value of
+    /// type `ty`, or if there is trailing data after the value.
+    pub fn read_primitive(&self, ty: PrimitiveValType) -> Result<PrimitiveValue<'a>> {
+        let mut reader = BinaryReader::new_with_offset(self.data, self.offset);
+        let pos = reader.original_position();
+        let value = match ty {
+            PrimitiveValType::Bool => match reader.read_u8()? {
+                0 => PrimitiveValue::Bool(false),
+                1 => PrimitiveValue::Bool(true),
+                _ => return Err(BinaryReaderError::new("invalid boolean value", pos)),
+            },
+            PrimitiveValType::S8 => PrimitiveValue::S8(reader.read_u8()? as i8),
+            PrimitiveValType::U8 => PrimitiveValue::U8(reader.read_u8()?),
+            PrimitiveValType::S16 => PrimitiveValue::S16(
+                i16::try_from(reader.read_var_i32()?)
+                    .map_err(|_| BinaryReaderError::new("s16 value out of range", pos))?,
+            ),
+            PrimitiveValType::U16 => PrimitiveValue::U16(
+                u16::try_from(reader.read_var_u32()?)
+                    .map_err(|_| BinaryReaderError::new("u16 value out of range", pos))?,
+            ),
+            PrimitiveValType::S32 => PrimitiveValue::S32(reader.read_var_i32()?),
+            PrimitiveValType::U32 => PrimitiveValue::U32(reader.read_var_u32()?),
+            PrimitiveValType::S64 => PrimitiveValue::S64(reader.read_var_i64()?),
+            PrimitiveValType::U64 => PrimitiveValue::U64(reader.read_var_u64()?),
+            PrimitiveValType::Float32 => PrimitiveValue::Float32(reader.read_f32()?),
+            PrimitiveValType::Float64 => PrimitiveValue::Float64(reader.read_f64()?),
+            PrimitiveValType::Char => PrimitiveValue::Char(
+                char::from_u32(reader.read_var_u32()?)
+                    .ok_or_else(|| BinaryReaderError::new("invalid Unicode scalar value", pos))?,
+            ),
+            PrimitiveValType::String => PrimitiveValue::String(reader.read_string()?),
+        };
+        if !reader.eof() {
+            return Err(BinaryReaderError::new(
+                "trailing data at the end of a value",
+                reader.original_position(),
+            ));
+        }
+        Ok(value)
+    }
+}
+
+/// A reader for the value section of a WebAssembly component.
+#[derive(Clone)]
+pub struct ComponentValueSectionReader<'a> {
+    reader: BinaryReader<'a>,
+    count: u32,
+}
+
+impl<'a> ComponentValueSectionReader<'a> {
+    /// Constructs a new `ComponentValueSectionReader` for the given data and offset.
+    pub fn new(data: &'a [u8], offset: usize) -> Result<Self> {
+        let mut reader = BinaryReader::new_with_offset(data, offset);
+        let count = reader.read_var_u32()?;
+        Ok(Self { reader, count })
+    }
+
+    /// Gets the original position of the section reader.
+    pub fn original_position(&self) -> usize {
+        self.reader.original_position()
+    }
+
+    /// Gets the count of items in the section.
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    /// Reads content of the value section.
+    ///
+    /// # Examples
+    /// ```
+    /// use wasmparser::{ComponentValueSectionReader, PrimitiveValType, PrimitiveValue};
+    ///
+    /// # let data: &[u8] = &[0x01, 0x79, 0x01, 0x2a];
+    /// let mut reader = ComponentValueSectionReader::new(data, 0).unwrap();
+    /// for _ in 0..reader.get_count() {
+    ///     let value = reader.read().expect("value");
+    ///     let value = value.read_primitive(PrimitiveValType::U32).expect("u32");
+    ///     assert_eq!(value, PrimitiveValue::U32(42));
+    /// }
+    /// ```
+    pub fn read(&mut self) -> Result<ComponentValue<'a>> {
+        self.reader.read_component_value()
+    }
+}
+
+impl<'a> SectionReader for ComponentValueSectionReader<'a> {
+    type Item = ComponentValue<'a>;
+
+    fn read(&mut self) -> Result<Self::Item> {
+        Self::read(self)
+    }
+
+    fn eof(&self) -> bool {
+        self.reader.eof()
+    }
+
+    fn original_position(&self) -> usize {
+        Self::original_position(self)
+    }
+
+    fn range(&self) -> Range<usize> {
+        self.reader.range()
+    }
+}
+
+impl<'a> SectionWithLimitedItems for ComponentValueSectionReader<'a> {
+    fn get_count(&self) -> u32 {
+        Self::get_count(self)
+    }
+}
+
+impl<'a> IntoIterator for ComponentValueSectionReader<'a> {
+    type Item = Result<ComponentValue<'a>>;
+    type IntoIter = SectionIteratorLimited<Self>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SectionIteratorLimited::new(self)
+    }
+}
@@ -1,6 +1,9 @@
+mod branch_hinting;
 mod code;
+mod coredump;
 mod custom;
 mod data;
+mod dylink0;
 mod elements;
 mod exports;
 mod functions;
@@ -17,9 +20,12 @@ mod tables;
 mod tags;
 mod types;
 
+pub use self::branch_hinting::*;
 pub use self::code::*;
+pub use self::coredump::*;
 pub use self::custom::*;
 pub use self::data::*;
+pub use self::dylink0::*;
 pub use self::elements::*;
 pub use self::exports::*;
 pub use self::functions::*;
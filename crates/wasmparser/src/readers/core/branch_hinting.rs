@@ -0,0 +1,218 @@
+/* Copyright 2022 Mozilla Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    BinaryReader, BinaryReaderError, Result, SectionIteratorLimited, SectionReader,
+    SectionWithLimitedItems,
+};
+use std::ops::Range;
+
+/// A hint for a single branch instruction in the
+/// `metadata.code.branch_hint` custom section.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BranchHint {
+    /// The offset of the hinted instruction, relative to the start of the
+    /// function body (the first byte of its locals declarations).
+    pub func_offset: u32,
+    /// Whether the branch is likely to be taken.
+    pub taken: bool,
+}
+
+/// A reader for the branch hints of a single function.
+pub struct BranchHintFunctionReader<'a> {
+    reader: BinaryReader<'a>,
+    count: u32,
+}
+
+impl<'a> BranchHintFunctionReader<'a> {
+    /// Gets the count of hints in the reader.
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    /// Gets the original position of the reader.
+    pub fn original_position(&self) -> usize {
+        self.reader.original_position()
+    }
+
+    fn skip(reader: &mut BinaryReader, count: u32) -> Result<()> {
+        for _ in 0..count {
+            reader.read_var_u32()?;
+            let size = reader.read_var_u32()?;
+            reader.skip_bytes(size as usize)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a hint from the reader.
+    pub fn read(&mut self) -> Result<BranchHint> {
+        let func_offset = self.reader.read_var_u32()?;
+        let pos = self.reader.original_position();
+        if self.reader.read_var_u32()? != 1 {
+            return Err(BinaryReaderError::new("invalid branch hint size", pos));
+        }
+        let pos = self.reader.original_position();
+        let taken = match self.reader.read_u8()? {
+            0 => false,
+            1 => true,
+            _ => return Err(BinaryReaderError::new("invalid branch hint value", pos)),
+        };
+        Ok(BranchHint { func_offset, taken })
+    }
+}
+
+impl<'a> IntoIterator for BranchHintFunctionReader<'a> {
+    type Item = Result<BranchHint>;
+    type IntoIter = BranchHintIterator<'a>;
+    fn into_iter(self) -> Self::IntoIter {
+        let count = self.count;
+        BranchHintIterator {
+            reader: self,
+            left: count,
+            err: false,
+        }
+    }
+}
+
+/// An iterator over the branch hints of a single function.
+pub struct BranchHintIterator<'a> {
+    reader: BranchHintFunctionReader<'a>,
+    left: u32,
+    err: bool,
+}
+
+impl<'a> Iterator for BranchHintIterator<'a> {
+    type Item = Result<BranchHint>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.err || self.left == 0 {
+            return None;
+        }
+        let result = self.reader.read();
+        self.err = result.is_err();
+        self.left -= 1;
+        Some(result)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.reader.get_count() as usize;
+        (count, Some(count))
+    }
+}
+
+/// The branch hints for a function in the `metadata.code.branch_hint` custom
+/// section.
+#[derive(Debug, Copy, Clone)]
+pub struct BranchHintFunction<'a> {
+    /// The index of the function these hints are for.
+    pub func: u32,
+    hints_count: u32,
+    hints_data: &'a [u8],
+    hints_offset: usize,
+}
+
+impl<'a> BranchHintFunction<'a> {
+    /// Gets a reader of the hints for this function.
+    pub fn get_hints_reader(&self) -> BranchHintFunctionReader<'a> {
+        BranchHintFunctionReader {
+            reader: BinaryReader::new_with_offset(self.hints_data, self.hints_offset),
+            count: self.hints_count,
+        }
+    }
+}
+
+/// A reader for the `metadata.code.branch_hint` custom section of a
+/// WebAssembly module.
+pub struct BranchHintSectionReader<'a> {
+    reader: BinaryReader<'a>,
+    count: u32,
+}
+
+impl<'a> BranchHintSectionReader<'a> {
+    /// Creates a reader for the branch hint section.
+    ///
+    /// # Examples
+    /// ```
+    /// # let data: &[u8] = &[0x01, 0x02, 0x01, 0x05, 0x01, 0x01];
+    /// use wasmparser::{BranchHint, BranchHintSectionReader};
+    /// let mut reader = BranchHintSectionReader::new(data, 0).expect("branch hint reader");
+    /// let func = reader.read().expect("branch hint function");
+    /// assert_eq!(func.func, 2);
+    /// let hints = func.get_hints_reader().into_iter().collect::<Result<Vec<_>, _>>().expect("hints");
+    /// assert_eq!(hints, [BranchHint { func_offset: 5, taken: true }]);
+    /// ```
+    pub fn new(data: &'a [u8], offset: usize) -> Result<BranchHintSectionReader<'a>> {
+        let mut reader = BinaryReader::new_with_offset(data, offset);
+        let count = reader.read_var_u32()?;
+        Ok(BranchHintSectionReader { reader, count })
+    }
+
+    /// Gets the original position of the reader.
+    pub fn original_position(&self) -> usize {
+        self.reader.original_position()
+    }
+
+    /// Gets the count of functions in the reader.
+    pub fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    /// Reads the hints of the next function from the reader.
+    pub fn read<'b>(&mut self) -> Result<BranchHintFunction<'b>>
+    where
+        'a: 'b,
+    {
+        let func = self.reader.read_var_u32()?;
+        let hints_count = self.reader.read_var_u32()?;
+        let hints_start = self.reader.position;
+        BranchHintFunctionReader::skip(&mut self.reader, hints_count)?;
+        let hints_end = self.reader.position;
+        Ok(BranchHintFunction {
+            func,
+            hints_count,
+            hints_data: &self.reader.buffer[hints_start..hints_end],
+            hints_offset: self.reader.original_offset + hints_start,
+        })
+    }
+}
+
+impl<'a> SectionReader for BranchHintSectionReader<'a> {
+    type Item = BranchHintFunction<'a>;
+    fn read(&mut self) -> Result<Self::Item> {
+        BranchHintSectionReader::read(self)
+    }
+    fn eof(&self) -> bool {
+        self.reader.eof()
+    }
+    fn original_position(&self) -> usize {
+        BranchHintSectionReader::original_position(self)
+    }
+    fn range(&self) -> Range<usize> {
+        self.reader.range()
+    }
+}
+
+impl<'a> SectionWithLimitedItems for BranchHintSectionReader<'a> {
+    fn get_count(&self) -> u32 {
+        BranchHintSectionReader::get_count(self)
+    }
+}
+
+impl<'a> IntoIterator for BranchHintSectionReader<'a> {
+    type Item = Result<BranchHintFunction<'a>>;
+    type IntoIter = SectionIteratorLimited<BranchHintSectionReader<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SectionIteratorLimited::new(self)
+    }
+}
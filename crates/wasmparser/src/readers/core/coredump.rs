@@ -0,0 +1,214 @@
+/* Copyright 2022 Mozilla Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Readers for the custom sections of WebAssembly coredumps, as described by
+//! the [tool conventions].
+//!
+//! [tool conventions]: https://github.com/WebAssembly/tool-conventions/blob/main/Coredump.md
+
+use crate::{BinaryReader, BinaryReaderError, Ieee32, Ieee64, Result};
+
+/// The `core` custom section, which describes the process that crashed.
+#[derive(Debug, Clone, Copy)]
+pub struct CoreDumpSection<'a> {
+    /// The name of the executable that crashed.
+    pub name: &'a str,
+}
+
+impl<'a> CoreDumpSection<'a> {
+    /// Parses the contents of a `core` custom section.
+    pub fn new(data: &'a [u8], offset: usize) -> Result<CoreDumpSection<'a>> {
+        let mut reader = BinaryReader::new_with_offset(data, offset);
+        read_tag(&mut reader, "process-info")?;
+        let name = reader.read_string()?;
+        ensure_end(&reader)?;
+        Ok(CoreDumpSection { name })
+    }
+}
+
+/// The `coremodules` custom section, which names the modules that were
+/// instantiated.
+#[derive(Debug, Clone)]
+pub struct CoreDumpModulesSection<'a> {
+    /// The name of each module, in module index order.
+    pub modules: Vec<&'a str>,
+}
+
+impl<'a> CoreDumpModulesSection<'a> {
+    /// Parses the contents of a `coremodules` custom section.
+    pub fn new(data: &'a [u8], offset: usize) -> Result<CoreDumpModulesSection<'a>> {
+        let mut reader = BinaryReader::new_with_offset(data, offset);
+        let mut modules = Vec::new();
+        for _ in 0..reader.read_var_u32()? {
+            read_tag(&mut reader, "module")?;
+            modules.push(reader.read_string()?);
+        }
+        ensure_end(&reader)?;
+        Ok(CoreDumpModulesSection { modules })
+    }
+}
+
+/// The `coreinstances` custom section, which describes the instances of the
+/// modules in the `coremodules` section.
+#[derive(Debug, Clone)]
+pub struct CoreDumpInstancesSection {
+    /// The instances, in instance index order.
+    pub instances: Vec<CoreDumpInstance>,
+}
+
+/// A single instance in the `coreinstances` custom section.
+#[derive(Debug, Clone)]
+pub struct CoreDumpInstance {
+    /// The index of the module that this is an instance of.
+    pub module_index: u32,
+    /// The indices of this instance's memories in the coredump's memory index
+    /// space.
+    pub memories: Vec<u32>,
+    /// The indices of this instance's globals in the coredump's global index
+    /// space.
+    pub globals: Vec<u32>,
+}
+
+impl CoreDumpInstancesSection {
+    /// Parses the contents of a `coreinstances` custom section.
+    pub fn new(data: &[u8], offset: usize) -> Result<CoreDumpInstancesSection> {
+        let mut reader = BinaryReader::new_with_offset(data, offset);
+        let mut instances = Vec::new();
+        for _ in 0..reader.read_var_u32()? {
+            read_tag(&mut reader, "instance")?;
+            let module_index = reader.read_var_u32()?;
+            let memories = read_indices(&mut reader)?;
+            let globals = read_indices(&mut reader)?;
+            instances.push(CoreDumpInstance {
+                module_index,
+                memories,
+                globals,
+            });
+        }
+        ensure_end(&reader)?;
+        Ok(CoreDumpInstancesSection { instances })
+    }
+}
+
+/// The `corestack` custom section, which holds the stack of a single thread.
+#[derive(Debug, Clone)]
+pub struct CoreDumpStackSection<'a> {
+    /// The name of the thread.
+    pub name: &'a str,
+    /// The frames of the stack, innermost first.
+    pub frames: Vec<CoreDumpStackFrame>,
+}
+
+/// A single frame in the `corestack` custom section.
+#[derive(Debug, Clone)]
+pub struct CoreDumpStackFrame {
+    /// The index of the instance that this frame is executing in.
+    pub instance_index: u32,
+    /// The index of the function being executed, within its module.
+    pub func_index: u32,
+    /// The offset of the instruction being executed, relative to the start of
+    /// the function body (the first byte of its locals declarations).
+    pub code_offset: u32,
+    /// The values of the function's locals, including its parameters.
+    pub locals: Vec<CoreDumpValue>,
+    /// The values on the operand stack, from the bottom up.
+    pub stack: Vec<CoreDumpValue>,
+}
+
+/// A value of a local or on the operand stack in a coredump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreDumpValue {
+    /// The value was optimized out, or otherwise couldn't be captured.
+    Missing,
+    /// An `i32` value.
+    I32(i32),
+    /// An `i64` value.
+    I64(i64),
+    /// An `f32` value.
+    F32(Ieee32),
+    /// An `f64` value.
+    F64(Ieee64),
+}
+
+impl<'a> CoreDumpStackSection<'a> {
+    /// Parses the contents of a `corestack` custom section.
+    pub fn new(data: &'a [u8], offset: usize) -> Result<CoreDumpStackSection<'a>> {
+        let mut reader = BinaryReader::new_with_offset(data, offset);
+        read_tag(&mut reader, "thread-info")?;
+        let name = reader.read_string()?;
+        let mut frames = Vec::new();
+        for _ in 0..reader.read_var_u32()? {
+            read_tag(&mut reader, "frame")?;
+            let instance_index = reader.read_var_u32()?;
+            let func_index = reader.read_var_u32()?;
+            let code_offset = reader.read_var_u32()?;
+            let locals = read_values(&mut reader)?;
+            let stack = read_values(&mut reader)?;
+            frames.push(CoreDumpStackFrame {
+                instance_index,
+                func_index,
+                code_offset,
+                locals,
+                stack,
+            });
+        }
+        ensure_end(&reader)?;
+        Ok(CoreDumpStackSection { name, frames })
+    }
+}
+
+/// Reads the `0x00` tag which precedes each kind of coredump record.
+fn read_tag(reader: &mut BinaryReader, what: &str) -> Result<()> {
+    let pos = reader.original_position();
+    if reader.read_u8()? != 0 {
+        return Err(BinaryReaderError::new(
+            format!("invalid coredump {} tag", what),
+            pos,
+        ));
+    }
+    Ok(())
+}
+
+fn read_indices(reader: &mut BinaryReader) -> Result<Vec<u32>> {
+    (0..reader.read_var_u32()?)
+        .map(|_| reader.read_var_u32())
+        .collect()
+}
+
+fn read_values(reader: &mut BinaryReader) -> Result<Vec<CoreDumpValue>> {
+    let mut values = Vec::new();
+    for _ in 0..reader.read_var_u32()? {
+        let pos = reader.original_position();
+        values.push(match reader.read_u8()? {
+            0x01 => CoreDumpValue::Missing,
+            0x7f => CoreDumpValue::I32(reader.read_var_i32()?),
+            0x7e => CoreDumpValue::I64(reader.read_var_i64()?),
+            0x7d => CoreDumpValue::F32(reader.read_f32()?),
+            0x7c => CoreDumpValue::F64(reader.read_f64()?),
+            _ => return Err(BinaryReaderError::new("invalid coredump value type", pos)),
+        });
+    }
+    Ok(values)
+}
+
+fn ensure_end(reader: &BinaryReader) -> Result<()> {
+    if reader.eof() {
+        return Ok(());
+    }
+    Err(BinaryReaderError::new(
+        "unexpected data at the end of the coredump section",
+        reader.original_position(),
+    ))
+}
@@ -0,0 +1,210 @@
+/* Copyright 2022 Mozilla Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{BinaryReader, BinaryReaderError, Result, SectionIterator, SectionReader};
+use std::ops::Range;
+
+const WASM_DYLINK_MEM_INFO: u8 = 1;
+const WASM_DYLINK_NEEDED: u8 = 2;
+const WASM_DYLINK_EXPORT_INFO: u8 = 3;
+const WASM_DYLINK_IMPORT_INFO: u8 = 4;
+
+/// The memory and table requirements of a dynamic library, from the
+/// `WASM_DYLINK_MEM_INFO` subsection of the `dylink.0` custom section.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MemInfo {
+    /// The size, in bytes, of the library's static data.
+    pub memory_size: u32,
+    /// The required alignment of the static data, as a power of two.
+    pub memory_alignment: u32,
+    /// The number of table elements the library needs.
+    pub table_size: u32,
+    /// The required alignment of the table elements, as a power of two.
+    pub table_alignment: u32,
+}
+
+/// Symbol flags for an export of a dynamic library.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExportInfo<'a> {
+    /// The name of the export.
+    pub name: &'a str,
+    /// The `WASM_SYM_*` flags of the export.
+    pub flags: u32,
+}
+
+/// Symbol flags for an import of a dynamic library.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImportInfo<'a> {
+    /// The module of the import.
+    pub module: &'a str,
+    /// The field name of the import.
+    pub field: &'a str,
+    /// The `WASM_SYM_*` flags of the import.
+    pub flags: u32,
+}
+
+/// A subsection of the `dylink.0` custom section.
+#[derive(Debug, Clone)]
+pub enum Dylink0Subsection<'a> {
+    /// The `WASM_DYLINK_MEM_INFO` subsection.
+    MemInfo(MemInfo),
+    /// The `WASM_DYLINK_NEEDED` subsection, listing the libraries this
+    /// library depends on.
+    Needed(Vec<&'a str>),
+    /// The `WASM_DYLINK_EXPORT_INFO` subsection.
+    ExportInfo(Vec<ExportInfo<'a>>),
+    /// The `WASM_DYLINK_IMPORT_INFO` subsection.
+    ImportInfo(Vec<ImportInfo<'a>>),
+    /// An unknown subsection.
+    Unknown {
+        /// The identifier of this subsection.
+        ty: u8,
+        /// The contents of this subsection.
+        data: &'a [u8],
+        /// The range of bytes, relative to the start of the original data
+        /// stream, that the contents of this subsection reside in.
+        range: Range<usize>,
+    },
+}
+
+/// A reader for the `dylink.0` custom section of a WebAssembly module, which
+/// describes the module as a dynamic library.
+///
+/// See the [dynamic linking conventions] for more information.
+///
+/// [dynamic linking conventions]: https://github.com/WebAssembly/tool-conventions/blob/main/DynamicLinking.md
+pub struct Dylink0SectionReader<'a> {
+    reader: BinaryReader<'a>,
+}
+
+impl<'a> Dylink0SectionReader<'a> {
+    /// Creates a reader for the `dylink.0` section.
+    ///
+    /// # Examples
+    /// ```
+    /// # let data: &[u8] = &[0x01, 0x04, 0x10, 0x02, 0x01, 0x00, 0x02, 0x03, 0x01, 0x01, b'a'];
+    /// use wasmparser::{Dylink0SectionReader, Dylink0Subsection, MemInfo};
+    /// let mut reader = Dylink0SectionReader::new(data, 0).expect("dylink.0 reader");
+    /// match reader.read().expect("subsection") {
+    ///     Dylink0Subsection::MemInfo(info) => assert_eq!(info.memory_size, 16),
+    ///     _ => unreachable!(),
+    /// }
+    /// match reader.read().expect("subsection") {
+    ///     Dylink0Subsection::Needed(needed) => assert_eq!(needed, ["a"]),
+    ///     _ => unreachable!(),
+    /// }
+    /// assert!(reader.eof());
+    /// ```
+    pub fn new(data: &'a [u8], offset: usize) -> Result<Dylink0SectionReader<'a>> {
+        Ok(Dylink0SectionReader {
+            reader: BinaryReader::new_with_offset(data, offset),
+        })
+    }
+
+    /// Determines if the reader is at the end of the section.
+    pub fn eof(&self) -> bool {
+        self.reader.eof()
+    }
+
+    /// Gets the original position of the reader.
+    pub fn original_position(&self) -> usize {
+        self.reader.original_position()
+    }
+
+    /// Reads a subsection from the section.
+    pub fn read<'b>(&mut self) -> Result<Dylink0Subsection<'b>>
+    where
+        'a: 'b,
+    {
+        let ty = self.reader.read_u8()?;
+        let size = self.reader.read_var_u32()? as usize;
+        let offset = self.reader.original_position();
+        let data = self.reader.read_bytes(size)?;
+        let mut reader = BinaryReader::new_with_offset(data, offset);
+        let subsection = match ty {
+            WASM_DYLINK_MEM_INFO => Dylink0Subsection::MemInfo(MemInfo {
+                memory_size: reader.read_var_u32()?,
+                memory_alignment: reader.read_var_u32()?,
+                table_size: reader.read_var_u32()?,
+                table_alignment: reader.read_var_u32()?,
+            }),
+            WASM_DYLINK_NEEDED => Dylink0Subsection::Needed(
+                (0..reader.read_var_u32()?)
+                    .map(|_| reader.read_string())
+                    .collect::<Result<_>>()?,
+            ),
+            WASM_DYLINK_EXPORT_INFO => Dylink0Subsection::ExportInfo(
+                (0..reader.read_var_u32()?)
+                    .map(|_| {
+                        Ok(ExportInfo {
+                            name: reader.read_string()?,
+                            flags: reader.read_var_u32()?,
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            WASM_DYLINK_IMPORT_INFO => Dylink0Subsection::ImportInfo(
+                (0..reader.read_var_u32()?)
+                    .map(|_| {
+                        Ok(ImportInfo {
+                            module: reader.read_string()?,
+                            field: reader.read_string()?,
+                            flags: reader.read_var_u32()?,
+                        })
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            ty => {
+                return Ok(Dylink0Subsection::Unknown {
+                    ty,
+                    data,
+                    range: offset..offset + size,
+                })
+            }
+        };
+        if !reader.eof() {
+            return Err(BinaryReaderError::new(
+                "unexpected content in dylink.0 subsection",
+                reader.original_position(),
+            ));
+        }
+        Ok(subsection)
+    }
+}
+
+impl<'a> SectionReader for Dylink0SectionReader<'a> {
+    type Item = Dylink0Subsection<'a>;
+    fn read(&mut self) -> Result<Self::Item> {
+        Dylink0SectionReader::read(self)
+    }
+    fn eof(&self) -> bool {
+        Dylink0SectionReader::eof(self)
+    }
+    fn original_position(&self) -> usize {
+        Dylink0SectionReader::original_position(self)
+    }
+    fn range(&self) -> Range<usize> {
+        self.reader.range()
+    }
+}
+
+impl<'a> IntoIterator for Dylink0SectionReader<'a> {
+    type Item = Result<Dylink0Subsection<'a>>;
+    type IntoIter = SectionIterator<Dylink0SectionReader<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SectionIterator::new(self)
+    }
+}
@@ -196,45 +196,15 @@ impl Default for State {
     }
 }
 
-/// Flags for features that are enabled for validation.
-#[derive(Hash, Debug, Copy, Clone)]
-pub struct WasmFeatures {
-    /// The WebAssembly `mutable-global` proposal (enabled by default)
-    pub mutable_global: bool,
-    /// The WebAssembly `nontrapping-float-to-int-conversions` proposal (enabled by default)
-    pub saturating_float_to_int: bool,
-    /// The WebAssembly `sign-extension-ops` proposal (enabled by default)
-    pub sign_extension: bool,
-    /// The WebAssembly reference types proposal (enabled by default)
-    pub reference_types: bool,
-    /// The WebAssembly multi-value proposal (enabled by default)
-    pub multi_value: bool,
-    /// The WebAssembly bulk memory operations proposal (enabled by default)
-    pub bulk_memory: bool,
-    /// The WebAssembly SIMD proposal
-    pub simd: bool,
-    /// The WebAssembly Relaxed SIMD proposal
-    pub relaxed_simd: bool,
-    /// The WebAssembly threads proposal
-    pub threads: bool,
-    /// The WebAssembly tail-call proposal
-    pub tail_call: bool,
-    /// Whether or not only deterministic instructions are allowed
-    pub deterministic_only: bool,
-    /// The WebAssembly multi memory proposal
-    pub multi_memory: bool,
-    /// The WebAssembly exception handling proposal
-    pub exceptions: bool,
-    /// The WebAssembly memory64 proposal
-    pub memory64: bool,
-    /// The WebAssembly extended_const proposal
-    pub extended_const: bool,
-    /// The WebAssembly component model proposal.
-    pub component_model: bool,
+pub use wasm_features::WasmFeatures;
+
+/// Validation-specific checks of the [`WasmFeatures`] which are enabled.
+pub(crate) trait FeaturesExt {
+    fn check_value_type(&self, ty: ValType) -> Result<(), &'static str>;
 }
 
-impl WasmFeatures {
-    pub(crate) fn check_value_type(&self, ty: ValType) -> Result<(), &'static str> {
+impl FeaturesExt for WasmFeatures {
+    fn check_value_type(&self, ty: ValType) -> Result<(), &'static str> {
         match ty {
             ValType::I32 | ValType::I64 | ValType::F32 | ValType::F64 => Ok(()),
             ValType::FuncRef | ValType::ExternRef => {
@@ -255,32 +225,6 @@ impl WasmFeatures {
     }
 }
 
-impl Default for WasmFeatures {
-    fn default() -> WasmFeatures {
-        WasmFeatures {
-            // off-by-default features
-            relaxed_simd: false,
-            threads: false,
-            tail_call: false,
-            multi_memory: false,
-            exceptions: false,
-            memory64: false,
-            extended_const: false,
-            component_model: false,
-            deterministic_only: cfg!(feature = "deterministic"),
-
-            // on-by-default features
-            mutable_global: true,
-            saturating_float_to_int: true,
-            sign_extension: true,
-            bulk_memory: true,
-            multi_value: true,
-            reference_types: true,
-            simd: true,
-        }
-    }
-}
-
 /// Possible return values from [`Validator::payload`].
 #[allow(clippy::large_enum_variant)]
 pub enum ValidPayload<'a> {
@@ -445,6 +389,7 @@ impl Validator {
             ComponentStartSection(s) => self.component_start_section(s)?,
             ComponentImportSection(s) => self.component_import_section(s)?,
             ComponentExportSection(s) => self.component_export_section(s)?,
+            ComponentValueSection(s) => self.component_value_section(s)?,
 
             End(offset) => return Ok(ValidPayload::End(self.end(*offset)?)),
 
@@ -1068,7 +1013,7 @@ impl Validator {
                 current.funcs.reserve(count as usize);
                 Ok(())
             },
-            |components, types, _, func, offset| {
+            |components, types, features, func, offset| {
                 let current = components.last_mut().unwrap();
                 match func {
                     crate::CanonicalFunction::Lift {
@@ -1080,12 +1025,44 @@ impl Validator {
                         type_index,
                         options.into_vec(),
                         types,
+                        features,
                         offset,
                     ),
                     crate::CanonicalFunction::Lower {
                         func_index,
                         options,
-                    } => current.lower_function(func_index, options.into_vec(), types, offset),
+                    } => current.lower_function(
+                        func_index,
+                        options.into_vec(),
+                        types,
+                        features,
+                        offset,
+                    ),
+                    crate::CanonicalFunction::TaskReturn { type_index } => {
+                        current.task_return(type_index, types, features, offset)
+                    }
+                    crate::CanonicalFunction::TaskWait { memory, .. }
+                    | crate::CanonicalFunction::TaskPoll { memory, .. } => {
+                        current.task_wait(memory, types, features, offset)
+                    }
+                    crate::CanonicalFunction::TaskYield { .. } => {
+                        current.task_yield(types, features, offset)
+                    }
+                    crate::CanonicalFunction::StreamNew { ty }
+                    | crate::CanonicalFunction::FutureNew { ty } => {
+                        current.transmit_new(ty, types, features, offset)
+                    }
+                    crate::CanonicalFunction::StreamReceive { ty, options }
+                    | crate::CanonicalFunction::StreamSend { ty, options } => {
+                        current.transmit(ty, true, options.into_vec(), types, features, offset)
+                    }
+                    crate::CanonicalFunction::FutureReceive { ty, options }
+                    | crate::CanonicalFunction::FutureSend { ty, options } => {
+                        current.transmit(ty, false, options.into_vec(), types, features, offset)
+                    }
+                    crate::CanonicalFunction::ErrorDrop => {
+                        current.error_drop(types, features, offset)
+                    }
                 }
             },
         )
@@ -1170,6 +1147,26 @@ impl Validator {
         )
     }
 
+    /// Validates [`Payload::ComponentValueSection`](crate::Payload).
+    ///
+    /// This method should only be called when parsing a component.
+    pub fn component_value_section(
+        &mut self,
+        section: &crate::ComponentValueSectionReader,
+    ) -> Result<()> {
+        self.process_component_section(
+            section,
+            "value",
+            |_, _, _, _| Ok(()), // add_value will check limits
+            |components, types, _, value, offset| {
+                components
+                    .last_mut()
+                    .unwrap()
+                    .add_value(value, types, offset)
+            },
+        )
+    }
+
     /// Validates [`Payload::UnknownSection`](crate::Payload).
     ///
     /// Currently always returns an error.
@@ -1402,6 +1399,7 @@ mod tests {
                 initial: 10,
                 maximum: None,
                 element_type: ValType::FuncRef,
+                table64: false,
             })
         );
 
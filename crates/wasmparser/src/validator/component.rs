@@ -268,6 +268,7 @@ impl ComponentState {
         type_index: u32,
         options: Vec<CanonicalOption>,
         types: &TypeList,
+        features: &WasmFeatures,
         offset: usize,
     ) -> Result<()> {
         let ty = self.function_type_at(type_index, types, offset)?;
@@ -277,8 +278,20 @@ impl ComponentState {
 
         // Lifting a function is for an export, so match the expected canonical ABI
         // export signature
-        let info = ty.lower(types, false);
-        self.check_options(Some(core_ty), &info, &options, types, offset)?;
+        let info = if is_async(&options) {
+            let mut info = ty.lower_async(types, false);
+            // Stackless async exports return a status code to the callback
+            if options
+                .iter()
+                .any(|o| matches!(o, CanonicalOption::Callback(_)))
+            {
+                assert!(info.results.push(ValType::I32));
+            }
+            info
+        } else {
+            ty.lower(types, false)
+        };
+        self.check_options(Some(core_ty), &info, &options, types, features, offset)?;
 
         if core_ty.params() != info.params.as_slice() {
             bail!(
@@ -310,6 +323,7 @@ impl ComponentState {
         func_index: u32,
         options: Vec<CanonicalOption>,
         types: &mut TypeList,
+        features: &WasmFeatures,
         offset: usize,
     ) -> Result<()> {
         let ty = types[self.function_at(func_index, offset)?]
@@ -318,20 +332,125 @@ impl ComponentState {
 
         // Lowering a function is for an import, so use a function type that matches
         // the expected canonical ABI import signature.
-        let info = ty.lower(types, true);
+        let info = if is_async(&options) {
+            ty.lower_async(types, true)
+        } else {
+            ty.lower(types, true)
+        };
+
+        self.check_options(None, &info, &options, types, features, offset)?;
+
+        self.push_core_func(info.into_func_type(), types);
+
+        Ok(())
+    }
+
+    pub fn task_return(
+        &mut self,
+        type_index: u32,
+        types: &mut TypeList,
+        features: &WasmFeatures,
+        offset: usize,
+    ) -> Result<()> {
+        check_async_enabled(features, offset)?;
+        let ty = self.function_type_at(type_index, types, offset)?;
+        let info = ty.lower_task_return(types);
+        self.push_core_func(info.into_func_type(), types);
+        Ok(())
+    }
+
+    pub fn task_wait(
+        &mut self,
+        memory: u32,
+        types: &mut TypeList,
+        features: &WasmFeatures,
+        offset: usize,
+    ) -> Result<()> {
+        check_async_enabled(features, offset)?;
+        self.memory_at(memory, offset)?;
+        self.push_core_func(FuncType::new([ValType::I32], [ValType::I32]), types);
+        Ok(())
+    }
+
+    pub fn task_yield(
+        &mut self,
+        types: &mut TypeList,
+        features: &WasmFeatures,
+        offset: usize,
+    ) -> Result<()> {
+        check_async_enabled(features, offset)?;
+        self.push_core_func(FuncType::new([], []), types);
+        Ok(())
+    }
+
+    /// Validates a `stream` or `future` built-in which creates a new handle.
+    pub fn transmit_new(
+        &mut self,
+        ty: u32,
+        types: &mut TypeList,
+        features: &WasmFeatures,
+        offset: usize,
+    ) -> Result<()> {
+        check_async_enabled(features, offset)?;
+        self.type_at(ty, false, offset)?;
+        self.push_core_func(FuncType::new([], [ValType::I32]), types);
+        Ok(())
+    }
 
-        self.check_options(None, &info, &options, types, offset)?;
+    /// Validates a `stream` or `future` built-in which sends or receives
+    /// values.
+    ///
+    /// Streams transfer a buffer of values given a handle, pointer, and count
+    /// while futures transfer a single value given a handle and pointer; both
+    /// return a status code.
+    pub fn transmit(
+        &mut self,
+        ty: u32,
+        stream: bool,
+        options: Vec<CanonicalOption>,
+        types: &mut TypeList,
+        features: &WasmFeatures,
+        offset: usize,
+    ) -> Result<()> {
+        check_async_enabled(features, offset)?;
+        self.type_at(ty, false, offset)?;
+
+        // Values are always transferred through linear memory
+        let info = LoweringInfo {
+            requires_memory: true,
+            ..Default::default()
+        };
+        self.check_options(None, &info, &options, types, features, offset)?;
+
+        let params: &[ValType] = if stream {
+            &[ValType::I32; 3]
+        } else {
+            &[ValType::I32; 2]
+        };
+        self.push_core_func(FuncType::new(params.iter().copied(), [ValType::I32]), types);
+        Ok(())
+    }
+
+    pub fn error_drop(
+        &mut self,
+        types: &mut TypeList,
+        features: &WasmFeatures,
+        offset: usize,
+    ) -> Result<()> {
+        check_async_enabled(features, offset)?;
+        self.push_core_func(FuncType::new([ValType::I32], []), types);
+        Ok(())
+    }
 
-        let lowered_ty = Type::Func(info.into_func_type());
+    fn push_core_func(&mut self, ty: FuncType, types: &mut TypeList) {
+        let ty = Type::Func(ty);
 
         self.core_funcs.push(TypeId {
-            type_size: lowered_ty.type_size(),
+            type_size: ty.type_size(),
             index: types.len(),
         });
 
-        types.push(lowered_ty);
-
-        Ok(())
+        types.push(ty);
     }
 
     pub fn add_component(&mut self, component: &mut Self, types: &mut TypeList) {
@@ -414,6 +533,27 @@ impl ComponentState {
         }
     }
 
+    pub fn add_value(
+        &mut self,
+        value: crate::ComponentValue,
+        types: &TypeList,
+        offset: usize,
+    ) -> Result<()> {
+        check_max(self.values.len(), 1, MAX_WASM_VALUES, "values", offset)?;
+        let ty = self.create_component_val_type(value.ty, types, offset)?;
+        match value.ty {
+            crate::ComponentValType::Primitive(pt) => {
+                value.read_primitive(pt)?;
+            }
+            crate::ComponentValType::Type(_) => bail!(
+                offset,
+                "values of defined types are not currently supported"
+            ),
+        }
+        self.values.push((ty, false));
+        Ok(())
+    }
+
     pub fn add_start(
         &mut self,
         func_index: u32,
@@ -481,6 +621,7 @@ impl ComponentState {
         info: &LoweringInfo,
         options: &[CanonicalOption],
         types: &TypeList,
+        features: &WasmFeatures,
         offset: usize,
     ) -> Result<()> {
         fn display(option: CanonicalOption) -> &'static str {
@@ -491,6 +632,8 @@ impl ComponentState {
                 CanonicalOption::Memory(_) => "memory",
                 CanonicalOption::Realloc(_) => "realloc",
                 CanonicalOption::PostReturn(_) => "post-return",
+                CanonicalOption::Async => "async",
+                CanonicalOption::Callback(_) => "callback",
             }
         }
 
@@ -498,6 +641,8 @@ impl ComponentState {
         let mut memory = None;
         let mut realloc = None;
         let mut post_return = None;
+        let mut async_ = false;
+        let mut callback = None;
 
         for option in options {
             match option {
@@ -583,9 +728,66 @@ impl ComponentState {
                         }
                     }
                 }
+                CanonicalOption::Async => {
+                    check_async_enabled(features, offset)?;
+                    if async_ {
+                        return Err(BinaryReaderError::new(
+                            "canonical option `async` is specified more than once",
+                            offset,
+                        ));
+                    }
+                    async_ = true;
+                }
+                CanonicalOption::Callback(idx) => {
+                    check_async_enabled(features, offset)?;
+                    callback = match callback {
+                        None => {
+                            if core_ty.is_none() {
+                                bail!(
+                                    offset,
+                                    "canonical option `{}` cannot be specified for lowerings",
+                                    display(*option)
+                                );
+                            }
+
+                            let ty = types[self.core_function_at(*idx, offset)?]
+                                .as_func_type()
+                                .unwrap();
+                            if ty.params() != [ValType::I32, ValType::I32, ValType::I32]
+                                || ty.results() != [ValType::I32]
+                            {
+                                return Err(BinaryReaderError::new(
+                                    "canonical option `callback` uses a core function with an incorrect signature",
+                                    offset,
+                                ));
+                            }
+                            Some(*idx)
+                        }
+                        Some(_) => {
+                            return Err(BinaryReaderError::new(
+                                "canonical option `callback` is specified more than once",
+                                offset,
+                            ))
+                        }
+                    }
+                }
             }
         }
 
+        if callback.is_some() && !async_ {
+            return Err(BinaryReaderError::new(
+                "canonical option `callback` requires the `async` option",
+                offset,
+            ));
+        }
+
+        if post_return.is_some() && async_ {
+            return Err(BinaryReaderError::new(
+                "canonical option `post-return` cannot be specified for async lifts",
+                offset,
+            ));
+        }
+
         if info.requires_memory && memory.is_none() {
             return Err(BinaryReaderError::new(
                 "canonical option `memory` is required",
@@ -1966,3 +2168,17 @@ impl Default for ComponentState {
         }
     }
 }
+
+fn is_async(options: &[CanonicalOption]) -> bool {
+    options.iter().any(|o| matches!(o, CanonicalOption::Async))
+}
+
+fn check_async_enabled(features: &WasmFeatures, offset: usize) -> Result<()> {
+    if !features.component_model_async {
+        return Err(BinaryReaderError::new(
+            "component model async support is not enabled",
+            offset,
+        ));
+    }
+    Ok(())
+}
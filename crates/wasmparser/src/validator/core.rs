@@ -4,6 +4,7 @@ use super::{
     check_max, combine_type_sizes,
     operators::OperatorValidator,
     types::{EntityType, Type, TypeId, TypeList},
+    FeaturesExt,
 };
 use crate::validator::core::arc::MaybeOwned;
 use crate::{
@@ -190,7 +191,8 @@ impl ModuleState {
                     ));
                 }
 
-                self.check_const_expr(&offset_expr, ValType::I32, features, types)?;
+                let index_ty = table.index_type();
+                self.check_const_expr(&offset_expr, index_ty, features, types)?;
             }
             ElementKind::Passive | ElementKind::Declared => {
                 if !features.bulk_memory {
@@ -671,8 +673,14 @@ impl Module {
                 offset,
             ));
         }
+        if ty.table64 && !features.memory64 {
+            return Err(BinaryReaderError::new(
+                "memory64 must be enabled for 64-bit tables",
+                offset,
+            ));
+        }
         self.check_limits(ty.initial, ty.maximum, offset)?;
-        if ty.initial > MAX_WASM_TABLE_ENTRIES as u32 {
+        if ty.initial > MAX_WASM_TABLE_ENTRIES as u64 {
             return Err(BinaryReaderError::new(
                 "minimum table size is out of bounds",
                 offset,
@@ -22,6 +22,7 @@
 // confusing it's recommended to read over that section to see how it maps to
 // the various methods here.
 
+use crate::validator::FeaturesExt;
 use crate::{
     limits::MAX_WASM_FUNCTION_LOCALS, BinaryReaderError, BlockType, BrTable, Ieee32, Ieee64,
     MemArg, Result, ValType, VisitOperator, WasmFeatures, WasmFuncType, WasmModuleResources, V128,
@@ -638,7 +639,7 @@ impl<'resources, R: WasmModuleResources> OperatorValidatorTemp<'_, 'resources, R
 
     /// Validates a call to an indirect function, very similar to `check_call`.
     fn check_call_indirect(&mut self, offset: usize, index: u32, table_index: u32) -> Result<()> {
-        match self.resources.table_at(table_index) {
+        let index_ty = match self.resources.table_at(table_index) {
             None => {
                 bail!(offset, "unknown table: table index out of bounds");
             }
@@ -646,10 +647,11 @@ impl<'resources, R: WasmModuleResources> OperatorValidatorTemp<'_, 'resources, R
                 if tab.element_type != ValType::FuncRef {
                     bail!(offset, "indirect calls must go through a table of funcref");
                 }
+                tab.index_type()
             }
-        }
+        };
         let ty = self.func_type_at(index, offset)?;
-        self.pop_operand(offset, Some(ValType::I32))?;
+        self.pop_operand(offset, Some(index_ty))?;
         for ty in ty.inputs().rev() {
             self.pop_operand(offset, Some(ty))?;
         }
@@ -3082,7 +3084,7 @@ where
         }
         self.pop_operand(offset, Some(ValType::I32))?;
         self.pop_operand(offset, Some(ValType::I32))?;
-        self.pop_operand(offset, Some(ValType::I32))?;
+        self.pop_operand(offset, Some(table.index_type()))?;
         Ok(())
     }
     fn visit_elem_drop(&mut self, offset: usize, segment: u32) -> Self::Output {
@@ -3111,59 +3113,71 @@ where
         if src.element_type != dst.element_type {
             bail!(offset, "type mismatch");
         }
-        self.pop_operand(offset, Some(ValType::I32))?;
-        self.pop_operand(offset, Some(ValType::I32))?;
-        self.pop_operand(offset, Some(ValType::I32))?;
+        let dst_ty = dst.index_type();
+        let src_ty = src.index_type();
+
+        // As with `memory.copy`, the length operand here is the smaller of
+        // src/dst, which is i32 if one is i32
+        self.pop_operand(
+            offset,
+            Some(match src_ty {
+                ValType::I32 => ValType::I32,
+                _ => dst_ty,
+            }),
+        )?;
+        self.pop_operand(offset, Some(src_ty))?;
+        self.pop_operand(offset, Some(dst_ty))?;
         Ok(())
     }
     fn visit_table_get(&mut self, offset: usize, table: u32) -> Self::Output {
         self.check_reference_types_enabled(offset)?;
-        let ty = match self.resources.table_at(table) {
-            Some(ty) => ty.element_type,
+        let table = match self.resources.table_at(table) {
+            Some(table) => table,
             None => bail!(offset, "table index out of bounds"),
         };
-        self.pop_operand(offset, Some(ValType::I32))?;
-        self.push_operand(ty)?;
+        self.pop_operand(offset, Some(table.index_type()))?;
+        self.push_operand(table.element_type)?;
         Ok(())
     }
     fn visit_table_set(&mut self, offset: usize, table: u32) -> Self::Output {
         self.check_reference_types_enabled(offset)?;
-        let ty = match self.resources.table_at(table) {
-            Some(ty) => ty.element_type,
+        let table = match self.resources.table_at(table) {
+            Some(table) => table,
             None => bail!(offset, "table index out of bounds"),
         };
-        self.pop_operand(offset, Some(ty))?;
-        self.pop_operand(offset, Some(ValType::I32))?;
+        self.pop_operand(offset, Some(table.element_type))?;
+        self.pop_operand(offset, Some(table.index_type()))?;
         Ok(())
     }
     fn visit_table_grow(&mut self, offset: usize, table: u32) -> Self::Output {
         self.check_reference_types_enabled(offset)?;
-        let ty = match self.resources.table_at(table) {
-            Some(ty) => ty.element_type,
+        let table = match self.resources.table_at(table) {
+            Some(table) => table,
             None => bail!(offset, "table index out of bounds"),
         };
-        self.pop_operand(offset, Some(ValType::I32))?;
-        self.pop_operand(offset, Some(ty))?;
-        self.push_operand(ValType::I32)?;
+        self.pop_operand(offset, Some(table.index_type()))?;
+        self.pop_operand(offset, Some(table.element_type))?;
+        self.push_operand(table.index_type())?;
         Ok(())
     }
     fn visit_table_size(&mut self, offset: usize, table: u32) -> Self::Output {
         self.check_reference_types_enabled(offset)?;
-        if self.resources.table_at(table).is_none() {
-            bail!(offset, "table index out of bounds");
-        }
-        self.push_operand(ValType::I32)?;
+        let table = match self.resources.table_at(table) {
+            Some(table) => table,
+            None => bail!(offset, "table index out of bounds"),
+        };
+        self.push_operand(table.index_type())?;
         Ok(())
     }
     fn visit_table_fill(&mut self, offset: usize, table: u32) -> Self::Output {
         self.check_bulk_memory_enabled(offset)?;
-        let ty = match self.resources.table_at(table) {
-            Some(ty) => ty.element_type,
+        let table = match self.resources.table_at(table) {
+            Some(table) => table,
             None => bail!(offset, "table index out of bounds"),
         };
-        self.pop_operand(offset, Some(ValType::I32))?;
-        self.pop_operand(offset, Some(ty))?;
-        self.pop_operand(offset, Some(ValType::I32))?;
+        self.pop_operand(offset, Some(table.index_type()))?;
+        self.pop_operand(offset, Some(table.element_type))?;
+        self.pop_operand(offset, Some(table.index_type()))?;
         Ok(())
     }
 }
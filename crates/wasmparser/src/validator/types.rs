@@ -19,6 +19,9 @@ use std::{
 /// Functions that exceed this limit will instead pass parameters indirectly from
 /// linear memory via a single pointer parameter.
 const MAX_FLAT_FUNC_PARAMS: usize = 16;
+/// The maximum number of parameters of an async import in the canonical ABI that
+/// can be passed by value.
+const MAX_FLAT_ASYNC_PARAMS: usize = 4;
 /// The maximum number of results in the canonical ABI that can be returned by a function.
 ///
 /// Functions that exceed this limit have their results written to linear memory via an
@@ -61,7 +64,7 @@ impl LoweredTypes {
         }
     }
 
-    fn push(&mut self, ty: ValType) -> bool {
+    pub(crate) fn push(&mut self, ty: ValType) -> bool {
         if self.maxed() {
             return false;
         }
@@ -369,7 +372,7 @@ impl EntityType {
                 at[*a].as_func_type().unwrap() == bt[*b].as_func_type().unwrap()
             }
             (EntityType::Table(a), EntityType::Table(b)) => {
-                a.element_type == b.element_type && limits_match!(a, b)
+                a.element_type == b.element_type && a.table64 == b.table64 && limits_match!(a, b)
             }
             (EntityType::Memory(a), EntityType::Memory(b)) => {
                 a.shared == b.shared && a.memory64 == b.memory64 && limits_match!(a, b)
@@ -773,28 +776,12 @@ impl ComponentFuncType {
     pub(crate) fn lower(&self, types: &TypeList, import: bool) -> LoweringInfo {
         let mut info = LoweringInfo::default();
 
-        for (_, ty) in self.params.iter() {
-            // When `import` is false, it means we're lifting a core function,
-            // check if the parameters needs realloc
-            if !import && !info.requires_realloc {
-                info.requires_realloc = ty.requires_realloc(types);
-            }
-
-            if !ty.push_wasm_types(types, &mut info.params) {
-                // Too many parameters to pass directly
-                // Function will have a single pointer parameter to pass the arguments
-                // via linear memory
-                info.params.clear();
-                assert!(info.params.push(ValType::I32));
-                info.requires_memory = true;
-
-                // We need realloc as well when lifting a function
-                if !import {
-                    info.requires_realloc = true;
-                }
-                break;
-            }
-        }
+        lower_params(
+            self.params.iter().map(|(_, ty)| ty),
+            types,
+            import,
+            &mut info,
+        );
 
         for (_, ty) in self.results.iter() {
             // When `import` is true, it means we're lowering a component function,
@@ -823,6 +810,91 @@ impl ComponentFuncType {
 
         info
     }
+
+    /// Lowers the component function type to core parameter and result types for the
+    /// async canonical ABI.
+    ///
+    /// Async exports receive their parameters as usual but have no results, as results
+    /// are instead returned with `task.return`. Async imports receive fewer flat
+    /// parameters, a pointer to write the results to if there are any, and return a
+    /// status code.
+    pub(crate) fn lower_async(&self, types: &TypeList, import: bool) -> LoweringInfo {
+        let mut info = LoweringInfo::default();
+
+        if import {
+            info.params = LoweredTypes::new(MAX_FLAT_ASYNC_PARAMS);
+        }
+
+        lower_params(
+            self.params.iter().map(|(_, ty)| ty),
+            types,
+            import,
+            &mut info,
+        );
+
+        if import {
+            if !self.results.is_empty() {
+                info.params.max = MAX_LOWERED_TYPES;
+                assert!(info.params.push(ValType::I32));
+                info.requires_memory = true;
+                info.requires_realloc |= self
+                    .results
+                    .iter()
+                    .any(|(_, ty)| ty.requires_realloc(types));
+            }
+            assert!(info.results.push(ValType::I32));
+        }
+
+        // Memory is always required when realloc is required
+        info.requires_memory |= info.requires_realloc;
+
+        info
+    }
+
+    /// Lowers the results of the component function type to the core parameter types
+    /// of a `task.return` built-in that returns them.
+    pub(crate) fn lower_task_return(&self, types: &TypeList) -> LoweringInfo {
+        let mut info = LoweringInfo::default();
+        lower_params(
+            self.results.iter().map(|(_, ty)| ty),
+            types,
+            true,
+            &mut info,
+        );
+        info
+    }
+}
+
+/// Lowers the given parameter types into `info`, passing them indirectly
+/// through linear memory if there are too many to pass directly.
+fn lower_params<'a>(
+    params: impl Iterator<Item = &'a ComponentValType>,
+    types: &TypeList,
+    import: bool,
+    info: &mut LoweringInfo,
+) {
+    for ty in params {
+        // When `import` is false, it means we're lifting a core function,
+        // check if the parameters needs realloc
+        if !import && !info.requires_realloc {
+            info.requires_realloc = ty.requires_realloc(types);
+        }
+
+        if !ty.push_wasm_types(types, &mut info.params) {
+            // Too many parameters to pass directly
+            // Function will have a single pointer parameter to pass the arguments
+            // via linear memory
+            info.params.clear();
+            assert!(info.params.push(ValType::I32));
+            info.requires_memory = true;
+
+            // We need realloc as well when lifting a function
+            if !import {
+                info.requires_realloc = true;
+            }
+            break;
+        }
+    }
 }
 
 /// Represents a variant case.
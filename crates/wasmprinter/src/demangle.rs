@@ -0,0 +1,35 @@
+use std::borrow::Cow;
+
+/// Demangles a Rust or Itanium C++ symbol name.
+///
+/// Names that aren't mangled, or that fail to demangle, are returned as they
+/// are. The hash suffix of legacy Rust symbols is omitted.
+pub fn demangle(name: &str) -> Cow<'_, str> {
+    if let Ok(demangled) = rustc_demangle::try_demangle(name) {
+        return Cow::Owned(format!("{:#}", demangled));
+    }
+    if name.starts_with("_Z") {
+        if let Ok(symbol) = cpp_demangle::Symbol::new(name) {
+            if let Ok(demangled) = symbol.demangle(&Default::default()) {
+                return Cow::Owned(demangled);
+            }
+        }
+    }
+    Cow::Borrowed(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::demangle;
+
+    #[test]
+    fn demangle_names() {
+        assert_eq!(
+            demangle("_ZN4core3fmt5write17h2b2e1c4ad3a6b8f1E"),
+            "core::fmt::write"
+        );
+        assert_eq!(demangle("_ZN3foo3barEv"), "foo::bar()");
+        assert_eq!(demangle("main"), "main");
+        assert_eq!(demangle("_Znot mangled"), "_Znot mangled");
+    }
+}
@@ -21,6 +21,11 @@ const MAX_WASM_FUNCTIONS: u32 = 1_000_000;
 
 mod operator;
 
+#[cfg(feature = "demangle")]
+mod demangle;
+#[cfg(feature = "demangle")]
+pub use demangle::demangle;
+
 /// Reads a WebAssembly `file` from the filesystem and then prints it into an
 /// in-memory `String`.
 pub fn print_file(file: impl AsRef<Path>) -> Result<String> {
@@ -42,6 +47,14 @@ pub fn print_bytes(wasm: impl AsRef<[u8]>) -> Result<String> {
 #[derive(Default)]
 pub struct Printer {
     print_offsets: bool,
+    #[cfg(feature = "demangle")]
+    demangle_names: bool,
+    skeleton: bool,
+    sections: Vec<String>,
+    funcs: Vec<String>,
+    /// The sections and functions of the filters which matched something.
+    sections_found: HashSet<String>,
+    funcs_found: HashSet<String>,
     printers: HashMap<String, Box<dyn FnMut(&mut Printer, usize, &[u8]) -> Result<()>>>,
     result: String,
     nesting: u32,
@@ -71,6 +84,7 @@ struct CoreState {
     data_names: HashMap<u32, Naming>,
     module_names: HashMap<u32, Naming>,
     instance_names: HashMap<u32, Naming>,
+    branch_hints: HashMap<u32, HashMap<u32, bool>>,
 }
 
 #[derive(Default)]
@@ -123,6 +137,46 @@ impl Printer {
         self.print_offsets = print;
     }
 
+    /// Whether or not to demangle the Rust and C++ function names found in
+    /// the `name` section when printing them.
+    #[cfg(feature = "demangle")]
+    pub fn demangle_names(&mut self, demangle: bool) {
+        self.demangle_names = demangle;
+    }
+
+    /// Whether or not to print only a "skeleton" of the wasm, which elides
+    /// function bodies and the contents of element and data segments with
+    /// `...`.
+    ///
+    /// Note that the output is then no longer valid text format.
+    pub fn skeleton(&mut self, skeleton: bool) {
+        self.skeleton = skeleton;
+    }
+
+    /// Only print the sections with the given name.
+    ///
+    /// Names are the keywords of the items within the section in the text
+    /// format, such as `type`, `import`, `func`, `elem`, or `data`, and for
+    /// components also `core-type`, `core-instance`, `instance`, `alias`,
+    /// `canon`, and `value`. Custom sections are named by their own name.
+    ///
+    /// This can be called multiple times, and is combined with
+    /// [`Printer::filter_func`]: once any filter is added, only the items
+    /// matching one of them are printed, along with the modules and
+    /// components that enclose them.
+    pub fn filter_section(&mut self, name: impl Into<String>) {
+        self.sections.push(name.into());
+    }
+
+    /// Only print the functions with the given name or index.
+    ///
+    /// The name can be either the name from the `name` section or the
+    /// identifier that is printed for the function, with or without its
+    /// leading `$`. See [`Printer::filter_section`] for how filters combine.
+    pub fn filter_func(&mut self, name_or_index: impl Into<String>) {
+        self.funcs.push(name_or_index.into());
+    }
+
     /// Registers a custom `printer` function to get invoked whenever a custom
     /// section of name `section` is seen.
     ///
@@ -155,9 +209,68 @@ impl Printer {
     ///
     /// This function takes an entire `wasm` binary blob and will print it to
     /// the WebAssembly Text Format and return the result as a `String`.
+    ///
+    /// Returns an error if a filter added with [`Printer::filter_section`] or
+    /// [`Printer::filter_func`] doesn't match anything in `wasm`.
     pub fn print(&mut self, wasm: &[u8]) -> Result<String> {
+        self.sections_found.clear();
+        self.funcs_found.clear();
         self.print_contents(wasm)?;
-        Ok(mem::take(&mut self.result))
+        let result = mem::take(&mut self.result);
+        if let Some(section) = self
+            .sections
+            .iter()
+            .find(|s| !self.sections_found.contains(*s))
+        {
+            bail!("no section named `{}` found", section);
+        }
+        if let Some(func) = self.funcs.iter().find(|f| !self.funcs_found.contains(*f)) {
+            bail!("no function named `{}` found", func);
+        }
+        Ok(result)
+    }
+
+    /// Prints each instruction of a function `body` in the text format,
+    /// returning the offset and text of each one, such as `i32.add` or
+    /// `call 3`.
+    ///
+    /// The instructions are printed without the rest of their module, so
+    /// items are referred to by index rather than by name, and unlike
+    /// [`Printer::print`] the final `end` of the body is included.
+    pub fn print_operators(&mut self, body: &FunctionBody<'_>) -> Result<Vec<(usize, String)>> {
+        let result = mem::take(&mut self.result);
+        let nesting = mem::replace(&mut self.nesting, 0);
+        let ops = self.print_operators_of(body);
+        self.result = result;
+        self.nesting = nesting;
+        ops
+    }
+
+    fn print_operators_of(&mut self, body: &FunctionBody<'_>) -> Result<Vec<(usize, String)>> {
+        let mut state = State::new(Encoding::Module);
+        let mut body = body.get_binary_reader();
+        for _ in 0..body.read_var_u32()? {
+            body.read_var_u32()?;
+            body.read_val_type()?;
+        }
+        body.allow_memarg64(true);
+
+        let mut ops = Vec::new();
+        let mut op_printer = operator::PrintOperator::new(self, &mut state);
+        while !body.eof() {
+            let offset = body.original_position();
+            match body.visit_operator(&mut op_printer)?? {
+                operator::OpKind::BlockStart => op_printer.printer.nesting += 1,
+                operator::OpKind::End | operator::OpKind::Delegate
+                    if op_printer.printer.nesting > 0 =>
+                {
+                    op_printer.printer.nesting -= 1
+                }
+                _ => {}
+            }
+            ops.push((offset, mem::take(&mut op_printer.printer.result)));
+        }
+        Ok(ops)
     }
 
     fn read_names_and_code<'a>(
@@ -203,6 +316,12 @@ impl Printer {
                     // Ignore any error associated with the name section.
                     drop(self.register_names(state, reader));
                 }
+                Payload::CustomSection(c) if c.name() == "metadata.code.branch_hint" => {
+                    let reader = BranchHintSectionReader::new(c.data(), c.data_offset())?;
+
+                    // Like the name section, ignore any errors here.
+                    drop(self.register_branch_hints(state, reader));
+                }
                 Payload::End(_) => break,
                 _ => {}
             }
@@ -211,6 +330,26 @@ impl Printer {
         Ok(())
     }
 
+    fn register_branch_hints(
+        &mut self,
+        state: &mut State,
+        mut reader: BranchHintSectionReader<'_>,
+    ) -> Result<()> {
+        for _ in 0..reader.get_count() {
+            let func = reader.read()?;
+            for hint in func.get_hints_reader() {
+                let hint = hint?;
+                state
+                    .core
+                    .branch_hints
+                    .entry(func.func)
+                    .or_default()
+                    .insert(hint.func_offset, hint.taken);
+            }
+        }
+        Ok(())
+    }
+
     fn ensure_module(states: &[State]) -> Result<()> {
         if !matches!(states.last().unwrap().encoding, Encoding::Module) {
             bail!("a module section was encountered when parsing a component");
@@ -234,6 +373,10 @@ impl Printer {
         let mut parsers = Vec::new();
         let mut code = Vec::new();
         let mut code_printed = false;
+        // For each nested module or component, the position before it was
+        // printed and the length of the result after its header, to remove
+        // it if filters leave it empty.
+        let mut nested: Vec<((usize, usize), usize)> = Vec::new();
 
         loop {
             let payload = match parser.parse(bytes, true)? {
@@ -243,6 +386,10 @@ impl Printer {
                     payload
                 }
             };
+            // Sections that aren't selected by a filter are printed as usual,
+            // to keep track of indices and names, but then discarded.
+            let section = section_name(&payload);
+            let mark = (self.result.len(), self.line);
             match payload {
                 Payload::Version { encoding, .. } => {
                     if let Some(e) = expected {
@@ -297,6 +444,16 @@ impl Printer {
                         self.result.push(' ');
                         name.write(&mut self.result);
                     }
+                    if states.len() > 1 {
+                        nested.last_mut().unwrap().1 = self.result.len();
+                    }
+                }
+                Payload::CustomSection(c)
+                    if c.name() == "dylink.0"
+                        && !self.printers.contains_key("dylink.0")
+                        && matches!(states.last().unwrap().encoding, Encoding::Module) =>
+                {
+                    self.print_dylink0(c.data_offset(), c.data())?;
                 }
                 Payload::CustomSection(c) => {
                     let mut printers = mem::take(&mut self.printers);
@@ -374,6 +531,7 @@ impl Printer {
                 } => {
                     Self::ensure_component(&states)?;
                     expected = Some(Encoding::Module);
+                    nested.push((mark, 0));
                     parsers.push(parser);
                     parser = inner;
                     self.newline(range.start);
@@ -389,6 +547,7 @@ impl Printer {
                 } => {
                     Self::ensure_component(&states)?;
                     expected = Some(Encoding::Component);
+                    nested.push((mark, 0));
                     parsers.push(parser);
                     parser = inner;
                     self.newline(range.start);
@@ -421,12 +580,22 @@ impl Printer {
                     Self::ensure_component(&states)?;
                     self.print_component_exports(states.last_mut().unwrap(), s)?;
                 }
+                Payload::ComponentValueSection(s) => {
+                    Self::ensure_component(&states)?;
+                    self.print_component_values(states.last_mut().unwrap(), s)?;
+                }
 
                 Payload::End(_) => {
+                    let empty = states.len() > 1 && nested.last().unwrap().1 == self.result.len();
                     self.end_group(); // close the `module` or `component` group
 
                     let state = states.pop().unwrap();
                     if let Some(parent) = states.last_mut() {
+                        let (mark, _) = nested.pop().unwrap();
+                        if empty && self.is_filtered() {
+                            self.result.truncate(mark.0);
+                            self.line = mark.1;
+                        }
                         match state.encoding {
                             Encoding::Module => {
                                 parent.core.modules += 1;
@@ -443,11 +612,42 @@ impl Printer {
 
                 Payload::UnknownSection { id, .. } => bail!("found unknown section `{}`", id),
             }
+
+            if let Some(name) = section {
+                self.sections_found.insert(name.to_string());
+            }
+            if section.is_some_and(|name| !self.section_selected(name)) {
+                self.result.truncate(mark.0);
+                self.line = mark.1;
+            }
         }
 
         Ok(())
     }
 
+    fn is_filtered(&self) -> bool {
+        !self.sections.is_empty() || !self.funcs.is_empty()
+    }
+
+    fn section_selected(&self, name: &str) -> bool {
+        !self.is_filtered() || self.sections.iter().any(|s| s == name)
+    }
+
+    fn func_selected(&mut self, state: &State, idx: u32) -> bool {
+        let naming = state.core.func_names.get(&idx);
+        let mut selected = self.section_selected("func");
+        for filter in &self.funcs {
+            let f = filter.strip_prefix('$').unwrap_or(filter);
+            if *f == idx.to_string()
+                || naming.is_some_and(|n| n.name == f || n.identifier.as_deref() == Some(f))
+            {
+                self.funcs_found.insert(filter.clone());
+                selected = true;
+            }
+        }
+        selected
+    }
+
     fn start_group(&mut self, name: &str) {
         self.result.push('(');
         self.result.push_str(name);
@@ -479,6 +679,26 @@ impl Printer {
             Ok(())
         }
 
+        #[cfg(feature = "demangle")]
+        fn demangled_name_map(
+            into: &mut HashMap<u32, Naming>,
+            names: NameMap<'_>,
+            name: &str,
+        ) -> Result<()> {
+            // Demangle all of the names up front, since `used` borrows them.
+            let mut map = names.get_map()?;
+            let mut demangled = Vec::new();
+            for _ in 0..map.get_count() {
+                let naming = map.read()?;
+                demangled.push((naming.index, demangle::demangle(naming.name)));
+            }
+            let mut used = HashSet::new();
+            for (index, demangled) in &demangled {
+                into.insert(*index, Naming::new(demangled, *index, name, &mut used));
+            }
+            Ok(())
+        }
+
         fn indirect_name_map(
             into: &mut HashMap<(u32, u32), Naming>,
             names: IndirectNameMap<'_>,
@@ -506,6 +726,10 @@ impl Printer {
                     let name = Naming::new(n.get_name()?, 0, "module", &mut HashSet::new());
                     state.name = Some(name);
                 }
+                #[cfg(feature = "demangle")]
+                Name::Function(n) if self.demangle_names => {
+                    demangled_name_map(&mut state.core.func_names, n, "func")?
+                }
                 Name::Function(n) => name_map(&mut state.core.func_names, n, "func")?,
                 Name::Local(n) => indirect_name_map(&mut state.core.local_names, n, "local")?,
                 Name::Label(n) => indirect_name_map(&mut state.core.label_names, n, "label")?,
@@ -717,6 +941,9 @@ impl Printer {
             self.print_name(&state.core.table_names, state.core.tables)?;
             self.result.push(' ');
         }
+        if ty.table64 {
+            self.result.push_str("i64 ");
+        }
         self.print_limits(ty.initial, ty.maximum)?;
         self.result.push(' ');
         self.print_valtype(ty.element_type)?;
@@ -830,112 +1057,247 @@ impl Printer {
         if funcs.get_count() != code.len() as u32 {
             bail!("mismatch in function and code section counts");
         }
+        if !code.is_empty() {
+            self.sections_found.insert("func".to_string());
+        }
         for body in code {
-            let mut body = body.get_binary_reader();
-            let offset = body.original_position();
             let ty = funcs.read()?;
-            self.newline(offset);
-            self.start_group("func ");
-            let func_idx = state.core.funcs;
-            self.print_name(&state.core.func_names, func_idx)?;
-            let params = self
-                .print_core_functype_idx(state, ty, true, Some(func_idx))?
-                .unwrap_or(0);
-
-            let mut first = true;
-            let mut local_idx = 0;
-            let mut locals = NamedLocalPrinter::new("local");
-            for _ in 0..body.read_var_u32()? {
-                let offset = body.original_position();
-                let cnt = body.read_var_u32()?;
-                let ty = body.read_val_type()?;
-                if MAX_LOCALS
-                    .checked_sub(local_idx)
-                    .and_then(|s| s.checked_sub(cnt))
-                    .is_none()
-                {
-                    bail!("function exceeds the maximum number of locals that can be printed");
+            // Functions which aren't selected aren't printed at all, so large
+            // modules only pay for the functions which are.
+            if self.func_selected(state, state.core.funcs) {
+                self.print_func(state, body, ty)?;
+            }
+            state.core.funcs += 1;
+        }
+        Ok(())
+    }
+
+    fn print_func(&mut self, state: &mut State, body: &FunctionBody<'_>, ty: u32) -> Result<()> {
+        let mut body = body.get_binary_reader();
+        let offset = body.original_position();
+        let body_start = offset;
+        self.newline(offset);
+        self.start_group("func ");
+        let func_idx = state.core.funcs;
+        self.print_name(&state.core.func_names, func_idx)?;
+        let params = self
+            .print_core_functype_idx(state, ty, true, Some(func_idx))?
+            .unwrap_or(0);
+        if self.skeleton {
+            self.result.push_str(" ...");
+            self.end_group();
+            return Ok(());
+        }
+
+        let mut first = true;
+        let mut local_idx = 0;
+        let mut locals = NamedLocalPrinter::new("local");
+        for _ in 0..body.read_var_u32()? {
+            let offset = body.original_position();
+            let cnt = body.read_var_u32()?;
+            let ty = body.read_val_type()?;
+            if MAX_LOCALS
+                .checked_sub(local_idx)
+                .and_then(|s| s.checked_sub(cnt))
+                .is_none()
+            {
+                bail!("function exceeds the maximum number of locals that can be printed");
+            }
+            for _ in 0..cnt {
+                if first {
+                    self.newline(offset);
+                    first = false;
                 }
-                for _ in 0..cnt {
-                    if first {
-                        self.newline(offset);
-                        first = false;
-                    }
-                    let name = state.core.local_names.get(&(func_idx, params + local_idx));
-                    locals.start_local(name, &mut self.result);
-                    self.print_valtype(ty)?;
-                    locals.end_local(&mut self.result);
-                    local_idx += 1;
-                }
-            }
-            locals.finish(&mut self.result);
-
-            state.core.labels = 0;
-            let nesting_start = self.nesting;
-            body.allow_memarg64(true);
-
-            let mut buf = String::new();
-            let mut op_printer = operator::PrintOperator::new(self, state);
-            while !body.eof() {
-                // TODO
-                let offset = body.original_position();
-                mem::swap(&mut buf, &mut op_printer.printer.result);
-                let op_kind = body.visit_operator(&mut op_printer)??;
-                mem::swap(&mut buf, &mut op_printer.printer.result);
-
-                match op_kind {
-                    // The final `end` in a reader is not printed, it's implied
-                    // in the text format.
-                    operator::OpKind::End if body.eof() => break,
-
-                    // When we start a block we newline to the current
-                    // indentation, then we increase the indentation so further
-                    // instructions are tabbed over.
-                    operator::OpKind::BlockStart => {
-                        op_printer.printer.newline(offset);
-                        op_printer.printer.nesting += 1;
-                    }
+                let name = state.core.local_names.get(&(func_idx, params + local_idx));
+                locals.start_local(name, &mut self.result);
+                self.print_valtype(ty)?;
+                locals.end_local(&mut self.result);
+                local_idx += 1;
+            }
+        }
+        locals.finish(&mut self.result);
 
-                    // `else`/`catch` are special in that it's printed at
-                    // the previous indentation, but it doesn't actually change
-                    // our nesting level.
-                    operator::OpKind::BlockMid => {
-                        op_printer.printer.nesting -= 1;
-                        op_printer.printer.newline(offset);
-                        op_printer.printer.nesting += 1;
-                    }
+        state.core.labels = 0;
+        let nesting_start = self.nesting;
+        body.allow_memarg64(true);
 
-                    // Exiting a block prints `end` at the previous indentation
-                    // level. `delegate` also ends a block like `end` for `try`.
-                    operator::OpKind::End | operator::OpKind::Delegate
-                        if op_printer.printer.nesting > nesting_start =>
-                    {
-                        op_printer.printer.nesting -= 1;
-                        op_printer.printer.newline(offset);
-                    }
+        let branch_hints = state.core.branch_hints.remove(&func_idx);
 
-                    // .. otherwise everything else just has a normal newline
-                    // out in front.
-                    _ => op_printer.printer.newline(offset),
+        let mut buf = String::new();
+        let mut op_printer = operator::PrintOperator::new(self, state);
+        while !body.eof() {
+            // TODO
+            let offset = body.original_position();
+            mem::swap(&mut buf, &mut op_printer.printer.result);
+            let op_kind = body.visit_operator(&mut op_printer)??;
+            mem::swap(&mut buf, &mut op_printer.printer.result);
+
+            match op_kind {
+                // The final `end` in a reader is not printed, it's implied
+                // in the text format.
+                operator::OpKind::End if body.eof() => break,
+
+                // When we start a block we newline to the current
+                // indentation, then we increase the indentation so further
+                // instructions are tabbed over.
+                operator::OpKind::BlockStart => {
+                    op_printer.printer.newline(offset);
+                    op_printer.printer.nesting += 1;
+                }
+
+                // `else`/`catch` are special in that it's printed at
+                // the previous indentation, but it doesn't actually change
+                // our nesting level.
+                operator::OpKind::BlockMid => {
+                    op_printer.printer.nesting -= 1;
+                    op_printer.printer.newline(offset);
+                    op_printer.printer.nesting += 1;
+                }
+
+                // Exiting a block prints `end` at the previous indentation
+                // level. `delegate` also ends a block like `end` for `try`.
+                operator::OpKind::End | operator::OpKind::Delegate
+                    if op_printer.printer.nesting > nesting_start =>
+                {
+                    op_printer.printer.nesting -= 1;
+                    op_printer.printer.newline(offset);
                 }
-                op_printer.printer.result.push_str(&buf);
-                buf.truncate(0);
-            }
 
-            // If this was an invalid function body then the nesting may not
-            // have reset back to normal. Fix that up here and forcibly insert
-            // a newline as well in case the last instruction was something
-            // like an `if` which has a comment after it which could interfere
-            // with the closing paren printed for the func.
-            if self.nesting != nesting_start {
-                self.nesting = nesting_start;
-                self.newline(body.original_position());
+                // .. otherwise everything else just has a normal newline
+                // out in front.
+                _ => op_printer.printer.newline(offset),
+            }
+            let hint = branch_hints
+                .as_ref()
+                .and_then(|h| h.get(&((offset - body_start) as u32)));
+            if let Some(taken) = hint {
+                op_printer.printer.result.push_str(if *taken {
+                    "(@metadata.code.branch_hint \"\\01\") "
+                } else {
+                    "(@metadata.code.branch_hint \"\\00\") "
+                });
             }
+            op_printer.printer.result.push_str(&buf);
+            buf.truncate(0);
+        }
 
-            self.end_group();
+        // If this was an invalid function body then the nesting may not
+        // have reset back to normal. Fix that up here and forcibly insert
+        // a newline as well in case the last instruction was something
+        // like an `if` which has a comment after it which could interfere
+        // with the closing paren printed for the func.
+        if self.nesting != nesting_start {
+            self.nesting = nesting_start;
+            self.newline(body.original_position());
+        }
 
-            state.core.funcs += 1;
+        self.end_group();
+        Ok(())
+    }
+
+    /// Prints a `dylink.0` custom section as a `@dylink.0` annotation.
+    ///
+    /// Like other custom sections nothing is printed if the section can't be
+    /// fully represented in the text format, such as if it's malformed or has
+    /// unknown subsections.
+    fn print_dylink0(&mut self, offset: usize, data: &[u8]) -> Result<()> {
+        const SYM_FLAGS: &[(&str, u32)] = &[
+            ("binding-weak", 1 << 0),
+            ("binding-local", 1 << 1),
+            ("visibility-hidden", 1 << 2),
+            ("undefined", 1 << 4),
+            ("exported", 1 << 5),
+            ("explicit-name", 1 << 6),
+            ("no-strip", 1 << 7),
+            ("tls", 1 << 8),
+            ("absolute", 1 << 9),
+        ];
+        fn print_flags(result: &mut String, mut flags: u32) {
+            for (name, flag) in SYM_FLAGS {
+                if flags & flag != 0 {
+                    write!(result, " {}", name).unwrap();
+                    flags &= !flag;
+                }
+            }
+            if flags != 0 {
+                write!(result, " {:#x}", flags).unwrap();
+            }
+        }
+
+        let subsections = Dylink0SectionReader::new(data, offset)
+            .and_then(|reader| reader.into_iter().collect::<wasmparser::Result<Vec<_>>>());
+        let subsections = match subsections {
+            Ok(s) => s,
+            Err(_) => return Ok(()),
+        };
+        if subsections
+            .iter()
+            .any(|s| matches!(s, Dylink0Subsection::Unknown { .. }))
+        {
+            return Ok(());
+        }
+
+        self.newline(offset);
+        self.start_group("@dylink.0");
+        for subsection in subsections {
+            self.newline_unknown_pos();
+            match subsection {
+                Dylink0Subsection::MemInfo(info) => {
+                    self.start_group("mem-info");
+                    if info.memory_size > 0 || info.memory_alignment > 0 {
+                        write!(
+                            self.result,
+                            " (memory {} {})",
+                            info.memory_size, info.memory_alignment
+                        )
+                        .unwrap();
+                    }
+                    if info.table_size > 0 || info.table_alignment > 0 {
+                        write!(
+                            self.result,
+                            " (table {} {})",
+                            info.table_size, info.table_alignment
+                        )
+                        .unwrap();
+                    }
+                    self.end_group();
+                }
+                Dylink0Subsection::Needed(libs) => {
+                    self.start_group("needed");
+                    for lib in libs {
+                        self.result.push(' ');
+                        self.print_str(lib)?;
+                    }
+                    self.end_group();
+                }
+                Dylink0Subsection::ExportInfo(exports) => {
+                    for (i, export) in exports.iter().enumerate() {
+                        if i > 0 {
+                            self.newline_unknown_pos();
+                        }
+                        self.start_group("export-info ");
+                        self.print_str(export.name)?;
+                        print_flags(&mut self.result, export.flags);
+                        self.end_group();
+                    }
+                }
+                Dylink0Subsection::ImportInfo(imports) => {
+                    for (i, import) in imports.iter().enumerate() {
+                        if i > 0 {
+                            self.newline_unknown_pos();
+                        }
+                        self.start_group("import-info ");
+                        self.print_str(import.module)?;
+                        self.result.push(' ');
+                        self.print_str(import.field)?;
+                        print_flags(&mut self.result, import.flags);
+                        self.end_group();
+                    }
+                }
+                Dylink0Subsection::Unknown { .. } => unreachable!(),
+            }
         }
+        self.end_group();
         Ok(())
     }
 
@@ -1140,6 +1502,11 @@ impl Printer {
             } else {
                 self.print_reftype(elem.ty)?;
             }
+            if self.skeleton {
+                self.result.push_str(" ...");
+                self.end_group();
+                continue;
+            }
             for _ in 0..items_reader.get_count() {
                 self.result.push(' ');
                 match items_reader.read()? {
@@ -1174,7 +1541,11 @@ impl Printer {
                     self.result.push(' ');
                 }
             }
-            self.print_bytes(data.data)?;
+            if self.skeleton {
+                self.result.push_str("...");
+            } else {
+                self.print_bytes(data.data)?;
+            }
             self.end_group();
         }
         Ok(())
@@ -1900,6 +2271,12 @@ impl Printer {
                     self.print_idx(&state.core.func_names, *idx)?;
                     self.end_group();
                 }
+                CanonicalOption::Async => self.result.push_str("async"),
+                CanonicalOption::Callback(idx) => {
+                    self.start_group("callback ");
+                    self.print_idx(&state.core.func_names, *idx)?;
+                    self.end_group();
+                }
             }
         }
         Ok(())
@@ -1951,12 +2328,101 @@ impl Printer {
                     self.end_group();
                     state.core.funcs += 1;
                 }
+                CanonicalFunction::TaskReturn { type_index } => {
+                    self.print_canonical_builtin(state, "task.return", |me, state| {
+                        me.result.push(' ');
+                        me.print_idx(&state.component.type_names, type_index)
+                    })?;
+                }
+                CanonicalFunction::TaskWait { async_, memory } => {
+                    self.print_canonical_builtin(state, "task.wait", |me, state| {
+                        me.print_async_memory(state, async_, memory)
+                    })?;
+                }
+                CanonicalFunction::TaskPoll { async_, memory } => {
+                    self.print_canonical_builtin(state, "task.poll", |me, state| {
+                        me.print_async_memory(state, async_, memory)
+                    })?;
+                }
+                CanonicalFunction::TaskYield { async_ } => {
+                    self.print_canonical_builtin(state, "task.yield", |me, _| {
+                        if async_ {
+                            me.result.push_str(" async");
+                        }
+                        Ok(())
+                    })?;
+                }
+                CanonicalFunction::StreamNew { ty } => {
+                    self.print_canonical_transmit(state, "stream.new", ty, &[])?;
+                }
+                CanonicalFunction::StreamReceive { ty, options } => {
+                    self.print_canonical_transmit(state, "stream.receive", ty, &options)?;
+                }
+                CanonicalFunction::StreamSend { ty, options } => {
+                    self.print_canonical_transmit(state, "stream.send", ty, &options)?;
+                }
+                CanonicalFunction::FutureNew { ty } => {
+                    self.print_canonical_transmit(state, "future.new", ty, &[])?;
+                }
+                CanonicalFunction::FutureReceive { ty, options } => {
+                    self.print_canonical_transmit(state, "future.receive", ty, &options)?;
+                }
+                CanonicalFunction::FutureSend { ty, options } => {
+                    self.print_canonical_transmit(state, "future.send", ty, &options)?;
+                }
+                CanonicalFunction::ErrorDrop => {
+                    self.print_canonical_builtin(state, "error.drop", |_, _| Ok(()))?;
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Prints a canonical built-in which defines a core function.
+    fn print_canonical_builtin(
+        &mut self,
+        state: &mut State,
+        name: &str,
+        print_args: impl FnOnce(&mut Self, &State) -> Result<()>,
+    ) -> Result<()> {
+        self.start_group("core func ");
+        self.print_name(&state.core.func_names, state.core.funcs)?;
+        self.result.push(' ');
+        self.start_group("canon ");
+        self.result.push_str(name);
+        print_args(self, state)?;
+        self.end_group();
+        self.end_group();
+        state.core.funcs += 1;
+        Ok(())
+    }
+
+    fn print_canonical_transmit(
+        &mut self,
+        state: &mut State,
+        name: &str,
+        ty: u32,
+        options: &[CanonicalOption],
+    ) -> Result<()> {
+        self.print_canonical_builtin(state, name, |me, state| {
+            me.result.push(' ');
+            me.print_idx(&state.component.type_names, ty)?;
+            me.print_canonical_options(state, options)
+        })
+    }
+
+    fn print_async_memory(&mut self, state: &State, async_: bool, memory: u32) -> Result<()> {
+        if async_ {
+            self.result.push_str(" async");
+        }
+        self.result.push(' ');
+        self.start_group("memory ");
+        self.print_idx(&state.core.memory_names, memory)?;
+        self.end_group();
+        Ok(())
+    }
+
     fn print_instances(&mut self, state: &mut State, parser: InstanceSectionReader) -> Result<()> {
         for instance in parser.into_iter_with_offsets() {
             let (offset, instance) = instance?;
@@ -2087,6 +2553,46 @@ impl Printer {
         Ok(())
     }
 
+    fn print_component_values(
+        &mut self,
+        state: &mut State,
+        parser: ComponentValueSectionReader,
+    ) -> Result<()> {
+        for value in parser.into_iter_with_offsets() {
+            let (offset, value) = value?;
+            self.newline(offset);
+            self.start_group("value ");
+            self.print_name(&state.component.value_names, state.component.values)?;
+            self.result.push(' ');
+            let ty = match value.ty {
+                ComponentValType::Primitive(ty) => ty,
+                ComponentValType::Type(_) => {
+                    bail!("values of defined types are not currently supported")
+                }
+            };
+            self.print_primitive_val_type(&ty);
+            self.result.push(' ');
+            match value.read_primitive(ty)? {
+                PrimitiveValue::Bool(b) => self.result.push_str(if b { "true" } else { "false" }),
+                PrimitiveValue::S8(i) => write!(self.result, "{}", i)?,
+                PrimitiveValue::U8(i) => write!(self.result, "{}", i)?,
+                PrimitiveValue::S16(i) => write!(self.result, "{}", i)?,
+                PrimitiveValue::U16(i) => write!(self.result, "{}", i)?,
+                PrimitiveValue::S32(i) => write!(self.result, "{}", i)?,
+                PrimitiveValue::U32(i) => write!(self.result, "{}", i)?,
+                PrimitiveValue::S64(i) => write!(self.result, "{}", i)?,
+                PrimitiveValue::U64(i) => write!(self.result, "{}", i)?,
+                PrimitiveValue::Float32(f) => self.print_f32(f.bits())?,
+                PrimitiveValue::Float64(f) => self.print_f64(f.bits())?,
+                PrimitiveValue::Char(c) => self.print_str(c.encode_utf8(&mut [0; 4]))?,
+                PrimitiveValue::String(s) => self.print_str(s)?,
+            }
+            self.end_group();
+            state.component.values += 1;
+        }
+        Ok(())
+    }
+
     fn print_component_aliases(
         &mut self,
         states: &mut [State],
@@ -2251,6 +2757,33 @@ impl Printer {
     }
 }
 
+/// The name of the section that `payload` is, for filtering what's printed.
+///
+/// Functions are filtered individually, and modules and components are never
+/// filtered since they enclose other sections, so these have no name.
+fn section_name<'a>(payload: &Payload<'a>) -> Option<&'a str> {
+    Some(match payload {
+        Payload::TypeSection(_) | Payload::ComponentTypeSection(_) => "type",
+        Payload::ImportSection(_) | Payload::ComponentImportSection(_) => "import",
+        Payload::TableSection(_) => "table",
+        Payload::MemorySection(_) => "memory",
+        Payload::TagSection(_) => "tag",
+        Payload::GlobalSection(_) => "global",
+        Payload::ExportSection(_) | Payload::ComponentExportSection(_) => "export",
+        Payload::StartSection { .. } | Payload::ComponentStartSection(_) => "start",
+        Payload::ElementSection(_) => "elem",
+        Payload::DataSection(_) => "data",
+        Payload::CoreTypeSection(_) => "core-type",
+        Payload::InstanceSection(_) => "core-instance",
+        Payload::ComponentInstanceSection(_) => "instance",
+        Payload::ComponentAliasSection(_) => "alias",
+        Payload::ComponentCanonicalSection(_) => "canon",
+        Payload::ComponentValueSection(_) => "value",
+        Payload::CustomSection(c) => c.name(),
+        _ => return None,
+    })
+}
+
 struct NamedLocalPrinter {
     group_name: &'static str,
     in_group: bool,
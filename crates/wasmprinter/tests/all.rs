@@ -169,3 +169,115 @@ fn dont_reserve_the_world() {
         err
     );
 }
+
+#[test]
+fn print_operators() {
+    let bytes = wat::parse_str(
+        r#"
+            (module
+                (memory 1)
+                (func $f (param i32) (result i32)
+                    (local i64)
+                    block $b
+                        local.get 0
+                        br_if $b
+                        i32.const 0
+                        i32.load offset=4
+                        drop
+                    end
+                    local.get 0
+                    call $f)
+            )
+        "#,
+    )
+    .unwrap();
+    let body = wasmparser::Parser::new(0)
+        .parse_all(&bytes)
+        .find_map(|payload| match payload.unwrap() {
+            wasmparser::Payload::CodeSectionEntry(body) => Some(body),
+            _ => None,
+        })
+        .unwrap();
+
+    let mut printer = wasmprinter::Printer::new();
+    let ops = printer.print_operators(&body).unwrap();
+    let text = ops
+        .iter()
+        .map(|(_, text)| text.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        text,
+        [
+            "block  ;; label = @0",
+            "local.get 0",
+            "br_if 0 (;@1;)",
+            "i32.const 0",
+            "i32.load offset=4",
+            "drop",
+            "end",
+            "local.get 0",
+            "call 0",
+            "end",
+        ]
+    );
+
+    // Each offset is that of the instruction in the module.
+    let mut reader = body.get_operators_reader().unwrap();
+    for (offset, _) in &ops {
+        assert_eq!(reader.read_with_offset().unwrap().1, *offset);
+    }
+    assert!(reader.eof());
+}
+
+#[test]
+fn filters_and_skeleton() {
+    let bytes = wat::parse_str(
+        r#"
+            (module
+                (func $a (result i32) i32.const 1)
+                (func $b (result i32) i32.const 2)
+                (export "a" (func $a))
+                (memory 1)
+                (data (i32.const 0) "hello")
+            )
+        "#,
+    )
+    .unwrap();
+
+    let mut printer = wasmprinter::Printer::new();
+    printer.filter_func("$b");
+    printer.filter_section("export");
+    let wat = printer.print(&bytes).unwrap();
+    assert!(wat.contains("i32.const 2"), "{}", wat);
+    assert!(wat.contains("(export \"a\" (func $a))"), "{}", wat);
+    assert!(!wat.contains("i32.const 1"), "{}", wat);
+    assert!(!wat.contains("memory"), "{}", wat);
+
+    let mut printer = wasmprinter::Printer::new();
+    printer.filter_func("0");
+    let wat = printer.print(&bytes).unwrap();
+    assert!(wat.contains("i32.const 1"), "{}", wat);
+    assert!(!wat.contains("i32.const 2"), "{}", wat);
+
+    let mut printer = wasmprinter::Printer::new();
+    printer.filter_func("$c");
+    let err = printer.print(&bytes).unwrap_err();
+    assert_eq!(err.to_string(), "no function named `$c` found");
+
+    let mut printer = wasmprinter::Printer::new();
+    printer.filter_func("$a");
+    printer.filter_section("elem");
+    let err = printer.print(&bytes).unwrap_err();
+    assert_eq!(err.to_string(), "no section named `elem` found");
+
+    let mut printer = wasmprinter::Printer::new();
+    printer.skeleton(true);
+    let wat = printer.print(&bytes).unwrap();
+    assert!(
+        wat.contains("(func $b (;1;) (type 0) (result i32) ...)"),
+        "{}",
+        wat
+    );
+    assert!(wat.contains("(data (;0;) (i32.const 0) ...)"), "{}", wat);
+    assert!(!wat.contains("hello"), "{}", wat);
+}
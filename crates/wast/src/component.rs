@@ -13,6 +13,7 @@ mod item_ref;
 mod module;
 mod resolve;
 mod types;
+mod value;
 mod wast;
 
 pub use self::alias::*;
@@ -25,4 +26,5 @@ pub use self::instance::*;
 pub use self::item_ref::*;
 pub use self::module::*;
 pub use self::types::*;
+pub use self::value::*;
 pub use self::wast::*;
@@ -5,8 +5,8 @@ use wasm_encoder::{
     CanonicalFunctionSection, ComponentAliasSection, ComponentDefinedTypeEncoder,
     ComponentExportSection, ComponentImportSection, ComponentInstanceSection, ComponentSection,
     ComponentSectionId, ComponentStartSection, ComponentTypeEncoder, ComponentTypeSection,
-    CoreTypeEncoder, CoreTypeSection, InstanceSection, NestedComponentSection, RawSection,
-    SectionId,
+    ComponentValueSection, CoreTypeEncoder, CoreTypeSection, InstanceSection,
+    NestedComponentSection, RawSection, SectionId,
 };
 
 pub fn encode(component: &Component<'_>) -> Vec<u8> {
@@ -40,6 +40,7 @@ fn encode_fields(
                 unreachable!("should be expanded already")
             }
             ComponentField::Start(s) => e.encode_start(s),
+            ComponentField::Value(v) => e.encode_value(v),
             ComponentField::Import(i) => e.encode_import(i),
             ComponentField::Export(ex) => e.encode_export(ex),
             ComponentField::Custom(c) => e.encode_custom(c),
@@ -157,6 +158,7 @@ struct Encoder {
     funcs: CanonicalFunctionSection,
     imports: ComponentImportSection,
     exports: ComponentExportSection,
+    values: ComponentValueSection,
 }
 
 impl Encoder {
@@ -174,7 +176,7 @@ impl Encoder {
             CoreModuleKind::Import { .. } => unreachable!("should be expanded already"),
             CoreModuleKind::Inline { fields } => {
                 // TODO: replace this with a wasm-encoder based encoding (should return `wasm_encoder::Module`)
-                let data = crate::core::binary::encode(&module.id, &module.name, fields);
+                let data = crate::core::binary::encode(&module.id, &module.name, fields).0;
                 self.component.section(&RawSection {
                     id: ComponentSectionId::CoreModule.into(),
                     data: &data,
@@ -303,6 +305,43 @@ impl Encoder {
                 self.funcs
                     .lower(info.func.idx.into(), info.opts.iter().map(Into::into));
             }
+            CanonicalFuncKind::TaskReturn(info) => {
+                self.funcs.task_return(info.ty.into());
+            }
+            CanonicalFuncKind::TaskWait(info) => {
+                self.funcs.task_wait(info.async_, info.memory.idx.into());
+            }
+            CanonicalFuncKind::TaskPoll(info) => {
+                self.funcs.task_poll(info.async_, info.memory.idx.into());
+            }
+            CanonicalFuncKind::TaskYield(info) => {
+                self.funcs.task_yield(info.async_);
+            }
+            CanonicalFuncKind::StreamNew(info) => {
+                self.funcs.stream_new(info.ty.into());
+            }
+            CanonicalFuncKind::StreamReceive(info) => {
+                self.funcs
+                    .stream_receive(info.ty.into(), info.opts.iter().map(Into::into));
+            }
+            CanonicalFuncKind::StreamSend(info) => {
+                self.funcs
+                    .stream_send(info.ty.into(), info.opts.iter().map(Into::into));
+            }
+            CanonicalFuncKind::FutureNew(info) => {
+                self.funcs.future_new(info.ty.into());
+            }
+            CanonicalFuncKind::FutureReceive(info) => {
+                self.funcs
+                    .future_receive(info.ty.into(), info.opts.iter().map(Into::into));
+            }
+            CanonicalFuncKind::FutureSend(info) => {
+                self.funcs
+                    .future_send(info.ty.into(), info.opts.iter().map(Into::into));
+            }
+            CanonicalFuncKind::ErrorDrop(_) => {
+                self.funcs.error_drop();
+            }
         }
 
         self.flush(Some(self.funcs.id()));
@@ -319,6 +358,11 @@ impl Encoder {
         self.flush(Some(self.exports.id()));
     }
 
+    fn encode_value(&mut self, value: &Value) {
+        self.values.primitive((&value.value).into());
+        self.flush(Some(self.values.id()));
+    }
+
     fn flush(&mut self, section_id: Option<u8>) {
         if self.current_section_id == section_id {
             return;
@@ -370,6 +414,11 @@ impl Encoder {
                     self.component.section(&self.exports);
                     self.exports = Default::default();
                 }
+                12 => {
+                    assert_eq!(id, self.values.id());
+                    self.component.section(&self.values);
+                    self.values = Default::default();
+                }
                 _ => unreachable!("unknown incremental component section id: {}", id),
             }
         }
@@ -450,6 +499,7 @@ impl From<core::TableType<'_>> for wasm_encoder::TableType {
     fn from(ty: core::TableType) -> Self {
         Self {
             element_type: ty.elem.into(),
+            table64: ty.is64,
             minimum: ty.limits.min,
             maximum: ty.limits.max,
         }
@@ -803,6 +853,26 @@ impl From<ComponentExportAliasKind> for wasm_encoder::ComponentExportKind {
     }
 }
 
+impl<'a> From<&PrimitiveValue<'a>> for wasm_encoder::PrimitiveValue<'a> {
+    fn from(value: &PrimitiveValue<'a>) -> Self {
+        match *value {
+            PrimitiveValue::Bool(b) => Self::Bool(b),
+            PrimitiveValue::S8(i) => Self::S8(i),
+            PrimitiveValue::U8(i) => Self::U8(i),
+            PrimitiveValue::S16(i) => Self::S16(i),
+            PrimitiveValue::U16(i) => Self::U16(i),
+            PrimitiveValue::S32(i) => Self::S32(i),
+            PrimitiveValue::U32(i) => Self::U32(i),
+            PrimitiveValue::S64(i) => Self::S64(i),
+            PrimitiveValue::U64(i) => Self::U64(i),
+            PrimitiveValue::Float32(f) => Self::Float32(f.bits),
+            PrimitiveValue::Float64(f) => Self::Float64(f.bits),
+            PrimitiveValue::Char(c) => Self::Char(c),
+            PrimitiveValue::String(s) => Self::String(s),
+        }
+    }
+}
+
 impl From<&CanonOpt<'_>> for wasm_encoder::CanonicalOption {
     fn from(opt: &CanonOpt) -> Self {
         match opt {
@@ -812,6 +882,8 @@ impl From<&CanonOpt<'_>> for wasm_encoder::CanonicalOption {
             CanonOpt::Memory(m) => Self::Memory(m.idx.into()),
             CanonOpt::Realloc(f) => Self::Realloc(f.idx.into()),
             CanonOpt::PostReturn(f) => Self::PostReturn(f.idx.into()),
+            CanonOpt::Async => Self::Async,
+            CanonOpt::Callback(f) => Self::Callback(f.idx.into()),
         }
     }
 }
@@ -106,11 +106,15 @@ impl<'a> Component<'a> {
     }
 }
 
-impl<'a> Parse<'a> for Component<'a> {
-    fn parse(parser: Parser<'a>) -> Result<Self> {
+impl<'a> Component<'a> {
+    /// Parses the rest of a component whose leading `component` keyword, at
+    /// `span`, has already been parsed.
+    pub(crate) fn parse_without_component_keyword(
+        span: Span,
+        parser: Parser<'a>,
+    ) -> Result<Self> {
+        parser.check_feature(span, "component_model", |f| f.component_model)?;
         let _r = parser.register_annotation("custom");
-
-        let span = parser.parse::<kw::component>()?.0;
         let id = parser.parse()?;
         let name = parser.parse()?;
 
@@ -133,6 +137,15 @@ impl<'a> Parse<'a> for Component<'a> {
     }
 }
 
+impl<'a> Parse<'a> for Component<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        let _r = parser.register_annotation("custom");
+
+        let span = parser.parse::<kw::component>()?.0;
+        Component::parse_without_component_keyword(span, parser)
+    }
+}
+
 /// A listing of all possible fields that can make up a WebAssembly component.
 #[allow(missing_docs)]
 #[derive(Debug)]
@@ -148,6 +161,7 @@ pub enum ComponentField<'a> {
     CoreFunc(CoreFunc<'a>), // Supports inverted forms of other items
     Func(Func<'a>),         // Supports inverted forms of other items
     Start(Start<'a>),
+    Value(Value<'a>),
     Import(ComponentImport<'a>),
     Export(ComponentExport<'a>),
     Custom(Custom<'a>),
@@ -157,7 +171,9 @@ impl<'a> ComponentField<'a> {
     fn parse_remaining(parser: Parser<'a>) -> Result<Vec<ComponentField>> {
         let mut fields = Vec::new();
         while !parser.is_empty() {
-            fields.push(parser.parens(ComponentField::parse)?);
+            if let Some(field) = parser.parens_recovering(ComponentField::parse)? {
+                fields.push(field);
+            }
         }
         Ok(fields)
     }
@@ -197,12 +213,18 @@ impl<'a> Parse<'a> for ComponentField<'a> {
             if parser.peek::<kw::func>() {
                 return Ok(Self::Func(parser.parse()?));
             }
+            if parser.peek::<kw::canon>() {
+                return Ok(Self::CanonicalFunc(parser.parse()?));
+            }
             if parser.peek::<kw::export>() {
                 return Ok(Self::Export(parser.parse()?));
             }
             if parser.peek::<kw::start>() {
                 return Ok(Self::Start(parser.parse()?));
             }
+            if parser.peek::<kw::value>() {
+                return Ok(Self::Value(parser.parse()?));
+            }
             if parser.peek::<annotation::custom>() {
                 return Ok(Self::Custom(parser.parse()?));
             }
@@ -121,6 +121,10 @@ impl<'a> Expander<'a> {
                 self.expand_item_sig(&mut i.item);
                 None
             }
+            ComponentField::Value(v) => {
+                self.expand_value(v);
+                None
+            }
             ComponentField::Start(_)
             | ComponentField::Alias(_)
             | ComponentField::Export(_)
@@ -240,34 +244,71 @@ impl<'a> Expander<'a> {
         }
     }
 
+    fn expand_value(&mut self, value: &mut Value<'a>) {
+        for name in value.exports.names.drain(..) {
+            let id = gensym::fill(value.span, &mut value.id);
+            self.component_fields_to_append
+                .push(ComponentField::Export(ComponentExport {
+                    span: value.span,
+                    name,
+                    kind: ComponentExportKind::value(value.span, id),
+                }));
+        }
+    }
+
     fn expand_canonical_func(&mut self, func: &mut CanonicalFunc<'a>) {
         match &mut func.kind {
             CanonicalFuncKind::Lift { ty, .. } => {
                 self.expand_component_type_use(ty);
             }
-            CanonicalFuncKind::Lower(_) => {}
+            CanonicalFuncKind::Lower(_)
+            | CanonicalFuncKind::TaskReturn(_)
+            | CanonicalFuncKind::TaskWait(_)
+            | CanonicalFuncKind::TaskPoll(_)
+            | CanonicalFuncKind::TaskYield(_)
+            | CanonicalFuncKind::StreamNew(_)
+            | CanonicalFuncKind::StreamReceive(_)
+            | CanonicalFuncKind::StreamSend(_)
+            | CanonicalFuncKind::FutureNew(_)
+            | CanonicalFuncKind::FutureReceive(_)
+            | CanonicalFuncKind::FutureSend(_)
+            | CanonicalFuncKind::ErrorDrop(_) => {}
         }
     }
 
     fn expand_core_func(&mut self, func: &mut CoreFunc<'a>) -> Option<ComponentField<'a>> {
-        match &mut func.kind {
-            CoreFuncKind::Alias(a) => Some(ComponentField::Alias(Alias {
-                span: func.span,
-                id: func.id,
-                name: func.name,
-                target: AliasTarget::CoreExport {
-                    instance: a.instance,
-                    name: a.name,
-                    kind: core::ExportKind::Func,
-                },
-            })),
-            CoreFuncKind::Lower(info) => Some(ComponentField::CanonicalFunc(CanonicalFunc {
-                span: func.span,
-                id: func.id,
-                name: func.name,
-                kind: CanonicalFuncKind::Lower(mem::take(info)),
-            })),
-        }
+        let kind = match &mut func.kind {
+            CoreFuncKind::Alias(a) => {
+                return Some(ComponentField::Alias(Alias {
+                    span: func.span,
+                    id: func.id,
+                    name: func.name,
+                    target: AliasTarget::CoreExport {
+                        instance: a.instance,
+                        name: a.name,
+                        kind: core::ExportKind::Func,
+                    },
+                }))
+            }
+            CoreFuncKind::Lower(info) => CanonicalFuncKind::Lower(mem::take(info)),
+            CoreFuncKind::TaskReturn(info) => CanonicalFuncKind::TaskReturn(mem::take(info)),
+            CoreFuncKind::TaskWait(info) => CanonicalFuncKind::TaskWait(mem::take(info)),
+            CoreFuncKind::TaskPoll(info) => CanonicalFuncKind::TaskPoll(mem::take(info)),
+            CoreFuncKind::TaskYield(info) => CanonicalFuncKind::TaskYield(mem::take(info)),
+            CoreFuncKind::StreamNew(info) => CanonicalFuncKind::StreamNew(mem::take(info)),
+            CoreFuncKind::StreamReceive(info) => CanonicalFuncKind::StreamReceive(mem::take(info)),
+            CoreFuncKind::StreamSend(info) => CanonicalFuncKind::StreamSend(mem::take(info)),
+            CoreFuncKind::FutureNew(info) => CanonicalFuncKind::FutureNew(mem::take(info)),
+            CoreFuncKind::FutureReceive(info) => CanonicalFuncKind::FutureReceive(mem::take(info)),
+            CoreFuncKind::FutureSend(info) => CanonicalFuncKind::FutureSend(mem::take(info)),
+            CoreFuncKind::ErrorDrop(info) => CanonicalFuncKind::ErrorDrop(mem::take(info)),
+        };
+        Some(ComponentField::CanonicalFunc(CanonicalFunc {
+            span: func.span,
+            id: func.id,
+            name: func.name,
+            kind,
+        }))
     }
 
     fn expand_func(&mut self, func: &mut Func<'a>) -> Option<ComponentField<'a>> {
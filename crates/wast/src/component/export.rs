@@ -70,6 +70,14 @@ impl<'a> ComponentExportKind<'a> {
         })
     }
 
+    pub(crate) fn value(span: Span, id: Id<'a>) -> Self {
+        Self::Value(ItemRef {
+            kind: kw::value(span),
+            idx: Index::Id(id),
+            export_names: Default::default(),
+        })
+    }
+
     pub(crate) fn instance(span: Span, id: Id<'a>) -> Self {
         Self::Instance(ItemRef {
             kind: kw::instance(span),
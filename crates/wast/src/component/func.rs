@@ -48,6 +48,28 @@ pub enum CoreFuncKind<'a> {
     ///
     /// The core function is actually a member of the core alias section.
     Alias(InlineExportAlias<'a>),
+    /// The core function is the `task.return` canonical built-in.
+    TaskReturn(CanonTaskReturn<'a>),
+    /// The core function is the `task.wait` canonical built-in.
+    TaskWait(CanonTaskWait<'a>),
+    /// The core function is the `task.poll` canonical built-in.
+    TaskPoll(CanonTaskPoll<'a>),
+    /// The core function is the `task.yield` canonical built-in.
+    TaskYield(CanonTaskYield),
+    /// The core function is the `stream.new` canonical built-in.
+    StreamNew(CanonStreamNew<'a>),
+    /// The core function is the `stream.receive` canonical built-in.
+    StreamReceive(CanonStreamReceive<'a>),
+    /// The core function is the `stream.send` canonical built-in.
+    StreamSend(CanonStreamSend<'a>),
+    /// The core function is the `future.new` canonical built-in.
+    FutureNew(CanonFutureNew<'a>),
+    /// The core function is the `future.receive` canonical built-in.
+    FutureReceive(CanonFutureReceive<'a>),
+    /// The core function is the `future.send` canonical built-in.
+    FutureSend(CanonFutureSend<'a>),
+    /// The core function is the `error.drop` canonical built-in.
+    ErrorDrop(CanonErrorDrop),
 }
 
 impl<'a> Parse<'a> for CoreFuncKind<'a> {
@@ -56,7 +78,7 @@ impl<'a> Parse<'a> for CoreFuncKind<'a> {
             let mut l = parser.lookahead1();
             if l.peek::<kw::canon>() {
                 parser.parse::<kw::canon>()?;
-                Ok(Self::Lower(parser.parse()?))
+                Self::parse_canon(parser)
             } else if l.peek::<kw::alias>() {
                 Ok(Self::Alias(parser.parse()?))
             } else {
@@ -66,6 +88,52 @@ impl<'a> Parse<'a> for CoreFuncKind<'a> {
     }
 }
 
+/// Parses the body of a `canon` definition which produces a core function,
+/// with the leading `canon` keyword already consumed, as a variant of `$kind`.
+///
+/// Both [`CoreFuncKind`] and [`CanonicalFuncKind`] have a variant of the same
+/// name for each of these.
+macro_rules! parse_core_canon {
+    ($parser:ident, $kind:ident) => {{
+        let mut l = $parser.lookahead1();
+        if l.peek::<kw::lower>() {
+            Ok($kind::Lower($parser.parse()?))
+        } else if l.peek::<kw::task_return>() {
+            Ok($kind::TaskReturn($parser.parse()?))
+        } else if l.peek::<kw::task_wait>() {
+            Ok($kind::TaskWait($parser.parse()?))
+        } else if l.peek::<kw::task_poll>() {
+            Ok($kind::TaskPoll($parser.parse()?))
+        } else if l.peek::<kw::task_yield>() {
+            Ok($kind::TaskYield($parser.parse()?))
+        } else if l.peek::<kw::stream_new>() {
+            Ok($kind::StreamNew($parser.parse()?))
+        } else if l.peek::<kw::stream_receive>() {
+            Ok($kind::StreamReceive($parser.parse()?))
+        } else if l.peek::<kw::stream_send>() {
+            Ok($kind::StreamSend($parser.parse()?))
+        } else if l.peek::<kw::future_new>() {
+            Ok($kind::FutureNew($parser.parse()?))
+        } else if l.peek::<kw::future_receive>() {
+            Ok($kind::FutureReceive($parser.parse()?))
+        } else if l.peek::<kw::future_send>() {
+            Ok($kind::FutureSend($parser.parse()?))
+        } else if l.peek::<kw::error_drop>() {
+            Ok($kind::ErrorDrop($parser.parse()?))
+        } else {
+            Err(l.error())
+        }
+    }};
+}
+
+impl<'a> CoreFuncKind<'a> {
+    /// Parses the body of a `canon` definition which produces a core function,
+    /// with the leading `canon` keyword already consumed.
+    fn parse_canon(parser: Parser<'a>) -> Result<Self> {
+        parse_core_canon!(parser, Self)
+    }
+}
+
 /// A declared component function.
 ///
 /// This may be a member of the import, alias, or canon sections.
@@ -189,8 +257,8 @@ impl<'a> Parse<'a> for CanonicalFunc<'a> {
                 name,
                 kind: CanonicalFuncKind::Lift { info, ty },
             })
-        } else if parser.peek::<kw::lower>() {
-            let info = parser.parse()?;
+        } else {
+            let kind = CanonicalFuncKind::parse_canon(parser)?;
             let (id, name) = parser.parens(|parser| {
                 parser.parse::<kw::core>()?;
                 parser.parse::<kw::func>()?;
@@ -203,10 +271,8 @@ impl<'a> Parse<'a> for CanonicalFunc<'a> {
                 span,
                 id,
                 name,
-                kind: CanonicalFuncKind::Lower(info),
+                kind,
             })
-        } else {
-            Err(parser.error("expected `canon lift` or `canon lower`"))
         }
     }
 }
@@ -223,6 +289,36 @@ pub enum CanonicalFuncKind<'a> {
     },
     /// A canonical function that is defined in terms of lowering a component function.
     Lower(CanonLower<'a>),
+    /// A `task.return` canonical built-in.
+    TaskReturn(CanonTaskReturn<'a>),
+    /// A `task.wait` canonical built-in.
+    TaskWait(CanonTaskWait<'a>),
+    /// A `task.poll` canonical built-in.
+    TaskPoll(CanonTaskPoll<'a>),
+    /// A `task.yield` canonical built-in.
+    TaskYield(CanonTaskYield),
+    /// A `stream.new` canonical built-in.
+    StreamNew(CanonStreamNew<'a>),
+    /// A `stream.receive` canonical built-in.
+    StreamReceive(CanonStreamReceive<'a>),
+    /// A `stream.send` canonical built-in.
+    StreamSend(CanonStreamSend<'a>),
+    /// A `future.new` canonical built-in.
+    FutureNew(CanonFutureNew<'a>),
+    /// A `future.receive` canonical built-in.
+    FutureReceive(CanonFutureReceive<'a>),
+    /// A `future.send` canonical built-in.
+    FutureSend(CanonFutureSend<'a>),
+    /// An `error.drop` canonical built-in.
+    ErrorDrop(CanonErrorDrop),
+}
+
+impl<'a> CanonicalFuncKind<'a> {
+    /// Parses the body of a `canon` definition which produces a core function,
+    /// with the leading `canon` keyword already consumed.
+    fn parse_canon(parser: Parser<'a>) -> Result<Self> {
+        parse_core_canon!(parser, Self)
+    }
 }
 
 /// Information relating to lifting a core function.
@@ -296,6 +392,181 @@ impl Default for CanonLower<'_> {
     }
 }
 
+/// Information relating to the `task.return` built-in.
+#[derive(Debug)]
+pub struct CanonTaskReturn<'a> {
+    /// The type of the function whose results are being returned.
+    pub ty: Index<'a>,
+}
+
+impl<'a> Parse<'a> for CanonTaskReturn<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        parser.parse::<kw::task_return>()?;
+
+        Ok(Self {
+            ty: parser.parse()?,
+        })
+    }
+}
+
+impl Default for CanonTaskReturn<'_> {
+    fn default() -> Self {
+        Self {
+            ty: Index::Num(0, Span::from_offset(0)),
+        }
+    }
+}
+
+/// Information relating to the `task.wait` built-in.
+#[derive(Debug)]
+pub struct CanonTaskWait<'a> {
+    /// Whether the task may be suspended while waiting.
+    pub async_: bool,
+    /// The memory into which the event payload is written.
+    pub memory: CoreItemRef<'a, kw::memory>,
+}
+
+impl<'a> Parse<'a> for CanonTaskWait<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        parser.parse::<kw::task_wait>()?;
+        let (async_, memory) = parse_async_memory(parser)?;
+
+        Ok(Self { async_, memory })
+    }
+}
+
+impl Default for CanonTaskWait<'_> {
+    fn default() -> Self {
+        Self {
+            async_: false,
+            memory: default_memory(),
+        }
+    }
+}
+
+/// Information relating to the `task.poll` built-in.
+#[derive(Debug)]
+pub struct CanonTaskPoll<'a> {
+    /// Whether the task may be suspended while polling.
+    pub async_: bool,
+    /// The memory into which the event payload is written.
+    pub memory: CoreItemRef<'a, kw::memory>,
+}
+
+impl<'a> Parse<'a> for CanonTaskPoll<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        parser.parse::<kw::task_poll>()?;
+        let (async_, memory) = parse_async_memory(parser)?;
+
+        Ok(Self { async_, memory })
+    }
+}
+
+impl Default for CanonTaskPoll<'_> {
+    fn default() -> Self {
+        Self {
+            async_: false,
+            memory: default_memory(),
+        }
+    }
+}
+
+fn parse_async_memory<'a>(parser: Parser<'a>) -> Result<(bool, CoreItemRef<'a, kw::memory>)> {
+    let async_ = parser.parse::<Option<kw::r#async>>()?.is_some();
+    let memory = parser.parens(|parser| {
+        let span = parser.parse::<kw::memory>()?.0;
+        parse_trailing_item_ref(kw::memory(span), parser)
+    })?;
+    Ok((async_, memory))
+}
+
+fn default_memory<'a>() -> CoreItemRef<'a, kw::memory> {
+    let span = Span::from_offset(0);
+    CoreItemRef {
+        kind: kw::memory(span),
+        idx: Index::Num(0, span),
+        export_name: None,
+    }
+}
+
+/// Information relating to the `task.yield` built-in.
+#[derive(Debug, Default)]
+pub struct CanonTaskYield {
+    /// Whether the task may be suspended while yielding.
+    pub async_: bool,
+}
+
+impl<'a> Parse<'a> for CanonTaskYield {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        parser.parse::<kw::task_yield>()?;
+
+        Ok(Self {
+            async_: parser.parse::<Option<kw::r#async>>()?.is_some(),
+        })
+    }
+}
+
+macro_rules! canon_transmit {
+    ($(#[$docs:meta] $name:ident = $kw:ident $(with $opts:ident)?,)*) => {$(
+        #[$docs]
+        #[derive(Debug)]
+        pub struct $name<'a> {
+            /// The `stream` or `future` type being operated on.
+            pub ty: Index<'a>,
+            $(
+                /// The canonical options for the values being transferred.
+                pub $opts: Vec<CanonOpt<'a>>,
+            )?
+        }
+
+        impl<'a> Parse<'a> for $name<'a> {
+            fn parse(parser: Parser<'a>) -> Result<Self> {
+                parser.parse::<kw::$kw>()?;
+
+                Ok(Self {
+                    ty: parser.parse()?,
+                    $($opts: parser.parse()?,)?
+                })
+            }
+        }
+
+        impl Default for $name<'_> {
+            fn default() -> Self {
+                Self {
+                    ty: Index::Num(0, Span::from_offset(0)),
+                    $($opts: Vec::new(),)?
+                }
+            }
+        }
+    )*};
+}
+
+canon_transmit! {
+    /// Information relating to the `stream.new` built-in.
+    CanonStreamNew = stream_new,
+    /// Information relating to the `stream.receive` built-in.
+    CanonStreamReceive = stream_receive with opts,
+    /// Information relating to the `stream.send` built-in.
+    CanonStreamSend = stream_send with opts,
+    /// Information relating to the `future.new` built-in.
+    CanonFutureNew = future_new,
+    /// Information relating to the `future.receive` built-in.
+    CanonFutureReceive = future_receive with opts,
+    /// Information relating to the `future.send` built-in.
+    CanonFutureSend = future_send with opts,
+}
+
+/// Information relating to the `error.drop` built-in.
+#[derive(Debug, Default)]
+pub struct CanonErrorDrop;
+
+impl<'a> Parse<'a> for CanonErrorDrop {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        parser.parse::<kw::error_drop>()?;
+        Ok(Self)
+    }
+}
+
 #[derive(Debug)]
 /// Canonical ABI options.
 pub enum CanonOpt<'a> {
@@ -311,6 +582,11 @@ pub enum CanonOpt<'a> {
     Realloc(CoreItemRef<'a, kw::func>),
     /// Call the specified function after the lifted function has returned.
     PostReturn(CoreItemRef<'a, kw::func>),
+    /// Use the async ABI for lifting or lowering.
+    Async,
+    /// Use the specified function to deliver async events to a lifted
+    /// function using the stackless async ABI.
+    Callback(CoreItemRef<'a, kw::func>),
 }
 
 impl<'a> Parse<'a> for CanonOpt<'a> {
@@ -325,6 +601,9 @@ impl<'a> Parse<'a> for CanonOpt<'a> {
         } else if l.peek::<kw::string_latin1_utf16>() {
             parser.parse::<kw::string_latin1_utf16>()?;
             Ok(Self::StringLatin1Utf16)
+        } else if l.peek::<kw::r#async>() {
+            parser.parse::<kw::r#async>()?;
+            Ok(Self::Async)
         } else if l.peek::<LParen>() {
             parser.parens(|parser| {
                 let mut l = parser.lookahead1();
@@ -344,6 +623,11 @@ impl<'a> Parse<'a> for CanonOpt<'a> {
                     Ok(CanonOpt::PostReturn(
                         parser.parse::<IndexOrCoreRef<'_, _>>()?.0,
                     ))
+                } else if l.peek::<kw::callback>() {
+                    parser.parse::<kw::callback>()?;
+                    Ok(CanonOpt::Callback(
+                        parser.parse::<IndexOrCoreRef<'_, _>>()?.0,
+                    ))
                 } else {
                     Err(l.error())
                 }
@@ -366,6 +650,12 @@ impl<'a> Parse<'a> for Vec<CanonOpt<'a>> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let mut funcs = Vec::new();
         while !parser.is_empty() {
+            // Stop at the trailing `(core func ...)` or `(func ...)` of a
+            // standalone `canon` definition.
+            if parser.peek::<LParen>() && (parser.peek2::<kw::core>() || parser.peek2::<kw::func>())
+            {
+                break;
+            }
             funcs.push(parser.parse()?);
         }
         Ok(funcs)
@@ -152,6 +152,7 @@ impl<'a> Resolver<'a> {
             ComponentField::CoreFunc(_) => unreachable!("should be expanded already"),
             ComponentField::Func(_) => unreachable!("should be expanded already"),
             ComponentField::Start(s) => self.start(s),
+            ComponentField::Value(_) => Ok(()),
             ComponentField::Import(i) => self.item_sig(&mut i.item),
             ComponentField::Export(e) => self.export(&mut e.kind),
             ComponentField::Custom(_) => Ok(()),
@@ -356,13 +357,55 @@ impl<'a> Resolver<'a> {
                 self.component_item_ref(&mut info.func)?;
                 &mut info.opts
             }
+            CanonicalFuncKind::TaskReturn(info) => {
+                self.resolve_ns(&mut info.ty, Ns::Type)?;
+                return Ok(());
+            }
+            CanonicalFuncKind::TaskWait(info) => {
+                self.core_item_ref(&mut info.memory)?;
+                return Ok(());
+            }
+            CanonicalFuncKind::TaskPoll(info) => {
+                self.core_item_ref(&mut info.memory)?;
+                return Ok(());
+            }
+            CanonicalFuncKind::StreamNew(info) => {
+                self.resolve_ns(&mut info.ty, Ns::Type)?;
+                return Ok(());
+            }
+            CanonicalFuncKind::FutureNew(info) => {
+                self.resolve_ns(&mut info.ty, Ns::Type)?;
+                return Ok(());
+            }
+            CanonicalFuncKind::StreamReceive(info) => {
+                self.resolve_ns(&mut info.ty, Ns::Type)?;
+                &mut info.opts
+            }
+            CanonicalFuncKind::StreamSend(info) => {
+                self.resolve_ns(&mut info.ty, Ns::Type)?;
+                &mut info.opts
+            }
+            CanonicalFuncKind::FutureReceive(info) => {
+                self.resolve_ns(&mut info.ty, Ns::Type)?;
+                &mut info.opts
+            }
+            CanonicalFuncKind::FutureSend(info) => {
+                self.resolve_ns(&mut info.ty, Ns::Type)?;
+                &mut info.opts
+            }
+            CanonicalFuncKind::TaskYield(_) | CanonicalFuncKind::ErrorDrop(_) => return Ok(()),
         };
 
         for opt in opts {
             match opt {
-                CanonOpt::StringUtf8 | CanonOpt::StringUtf16 | CanonOpt::StringLatin1Utf16 => {}
+                CanonOpt::StringUtf8
+                | CanonOpt::StringUtf16
+                | CanonOpt::StringLatin1Utf16
+                | CanonOpt::Async => {}
                 CanonOpt::Memory(r) => self.core_item_ref(r)?,
-                CanonOpt::Realloc(r) | CanonOpt::PostReturn(r) => self.core_item_ref(r)?,
+                CanonOpt::Realloc(r) | CanonOpt::PostReturn(r) | CanonOpt::Callback(r) => {
+                    self.core_item_ref(r)?
+                }
             }
         }
 
@@ -767,7 +810,7 @@ impl<'a> ComponentState<'a> {
             ComponentField::Type(t) => self.types.register(t.id, "type")?,
             ComponentField::CanonicalFunc(f) => match &f.kind {
                 CanonicalFuncKind::Lift { .. } => self.funcs.register(f.id, "func")?,
-                CanonicalFuncKind::Lower(_) => self.core_funcs.register(f.id, "core func")?,
+                _ => self.core_funcs.register(f.id, "core func")?,
             },
             ComponentField::CoreFunc(_) | ComponentField::Func(_) => {
                 unreachable!("should be expanded already")
@@ -778,6 +821,7 @@ impl<'a> ComponentState<'a> {
                 }
                 return Ok(());
             }
+            ComponentField::Value(v) => self.values.register(v.id, "value")?,
             ComponentField::Import(i) => match &i.item.kind {
                 ItemSigKind::CoreModule(_) => {
                     self.core_modules.register(i.item.id, "core module")?
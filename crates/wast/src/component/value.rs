@@ -0,0 +1,106 @@
+use crate::component::PrimitiveValType;
+use crate::core;
+use crate::kw;
+use crate::parser::{Parse, Parser, Result};
+use crate::token::{Float32, Float64, Id, NameAnnotation, Span};
+
+/// A value defined in the value section of a component.
+///
+/// Only values of primitive types may currently be defined in the text format:
+///
+/// ```text
+/// (value $v u32 42)
+/// (value $s (export "greeting") string "hello")
+/// ```
+#[derive(Debug)]
+pub struct Value<'a> {
+    /// Where this `value` was defined.
+    pub span: Span,
+    /// An identifier that this value is resolved with (optionally) for name
+    /// resolution.
+    pub id: Option<Id<'a>>,
+    /// An optional name for this value stored in the custom `name` section.
+    pub name: Option<NameAnnotation<'a>>,
+    /// If present, inline export annotations which indicate names this
+    /// definition should be exported under.
+    pub exports: core::InlineExport<'a>,
+    /// The contents of the value.
+    pub value: PrimitiveValue<'a>,
+}
+
+impl<'a> Parse<'a> for Value<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        let span = parser.parse::<kw::value>()?.0;
+        let id = parser.parse()?;
+        let name = parser.parse()?;
+        let exports = parser.parse()?;
+        let value = parser.parse()?;
+
+        Ok(Self {
+            span,
+            id,
+            name,
+            exports,
+            value,
+        })
+    }
+}
+
+/// A value of a primitive component value type, written as the type followed
+/// by its literal contents.
+#[allow(missing_docs)]
+#[derive(Debug)]
+pub enum PrimitiveValue<'a> {
+    Bool(bool),
+    S8(i8),
+    U8(u8),
+    S16(i16),
+    U16(u16),
+    S32(i32),
+    U32(u32),
+    S64(i64),
+    U64(u64),
+    Float32(Float32),
+    Float64(Float64),
+    Char(char),
+    String(&'a str),
+}
+
+impl<'a> Parse<'a> for PrimitiveValue<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        Ok(match parser.parse::<PrimitiveValType>()? {
+            PrimitiveValType::Bool => {
+                let mut l = parser.lookahead1();
+                if l.peek::<kw::true_>() {
+                    parser.parse::<kw::true_>()?;
+                    Self::Bool(true)
+                } else if l.peek::<kw::false_>() {
+                    parser.parse::<kw::false_>()?;
+                    Self::Bool(false)
+                } else {
+                    return Err(l.error());
+                }
+            }
+            PrimitiveValType::S8 => Self::S8(parser.parse()?),
+            PrimitiveValType::U8 => Self::U8(parser.parse()?),
+            PrimitiveValType::S16 => Self::S16(parser.parse()?),
+            PrimitiveValType::U16 => Self::U16(parser.parse()?),
+            PrimitiveValType::S32 => Self::S32(parser.parse()?),
+            PrimitiveValType::U32 => Self::U32(parser.parse()?),
+            PrimitiveValType::S64 => Self::S64(parser.parse()?),
+            PrimitiveValType::U64 => Self::U64(parser.parse()?),
+            PrimitiveValType::Float32 => Self::Float32(parser.parse()?),
+            PrimitiveValType::Float64 => Self::Float64(parser.parse()?),
+            PrimitiveValType::Char => {
+                let s = parser.parse::<&str>()?;
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Self::Char(c),
+                    (None, _) => return Err(parser.error("empty string")),
+                    (Some(_), Some(_)) => return Err(parser.error("more than one character")),
+                }
+            }
+            PrimitiveValType::String => Self::String(parser.parse()?),
+        })
+    }
+}
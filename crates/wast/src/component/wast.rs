@@ -2,8 +2,8 @@ use crate::kw;
 use crate::parser::{Cursor, Parse, Parser, Peek, Result};
 use crate::token::{Float32, Float64};
 
-/// Expression that can be used inside of `invoke` expressions for core wasm
-/// functions.
+/// Expression that can be used inside of `invoke` expressions for component
+/// functions, and as the expected results of those invocations.
 #[derive(Debug)]
 #[allow(missing_docs)]
 pub enum WastVal<'a> {
@@ -81,7 +81,7 @@ static CASES: &[(&str, fn(Parser<'_>) -> Result<WastVal<'_>>)] = {
             while !p.is_empty() {
                 ret.push(p.parens(|p| {
                     p.parse::<kw::field>()?;
-                    Ok((p.parse()?, p.parse()?))
+                    Ok((p.parse()?, p.parens(|p| p.parse())?))
                 })?);
             }
             Ok(Record(ret))
@@ -161,6 +161,6 @@ impl Peek for WastVal<'_> {
     }
 
     fn display() -> &'static str {
-        "core wasm argument"
+        "component value"
     }
 }
@@ -2,11 +2,13 @@ use crate::core::*;
 use crate::encode::Encode;
 use crate::token::*;
 
+/// Encodes a module, returning the binary along with the offset in the binary
+/// of each instruction which had its span tracked during parsing.
 pub fn encode(
     module_id: &Option<Id<'_>>,
     module_name: &Option<NameAnnotation<'_>>,
     fields: &[ModuleField<'_>],
-) -> Vec<u8> {
+) -> (Vec<u8>, InstrOffsets) {
     use CustomPlace::*;
     use CustomPlaceAnchor::*;
 
@@ -22,6 +24,7 @@ pub fn encode(
     let mut data = Vec::new();
     let mut tags = Vec::new();
     let mut customs = Vec::new();
+    let mut dylink0 = Vec::new();
     for field in fields {
         match field {
             ModuleField::Type(i) => types.push(RecOrType::Type(i)),
@@ -37,6 +40,7 @@ pub fn encode(
             ModuleField::Data(i) => data.push(i),
             ModuleField::Tag(i) => tags.push(i),
             ModuleField::Custom(i) => customs.push(i),
+            ModuleField::Dylink0(i) => dylink0.push(i),
         }
     }
 
@@ -48,6 +52,9 @@ pub fn encode(
     e.wasm.extend(b"\0asm");
     e.wasm.extend(b"\x01\0\0\0");
 
+    for section in dylink0 {
+        e.section(0, &("dylink.0", section));
+    }
     e.custom_sections(BeforeFirst);
 
     e.section_list(1, Type, &types);
@@ -69,7 +76,11 @@ pub fn encode(
     if needs_data_count(&funcs) {
         e.section(12, &data.len());
     }
-    e.section_list(10, Code, &funcs);
+    let branch_hints = find_branch_hints(&imports, &funcs);
+    if !branch_hints.is_empty() {
+        e.section(0, &("metadata.code.branch_hint", branch_hints));
+    }
+    let instr_offsets = e.code_section(&funcs);
     e.section_list(11, Data, &data);
 
     let names = find_names(module_id, module_name, fields);
@@ -78,7 +89,7 @@ pub fn encode(
     }
     e.custom_sections(AfterLast);
 
-    return e.wasm;
+    return (e.wasm, instr_offsets);
 
     fn needs_data_count(funcs: &[&crate::core::Func<'_>]) -> bool {
         funcs
@@ -90,6 +101,31 @@ pub fn encode(
             .flat_map(|e| e.instrs.iter())
             .any(|i| i.needs_data_count())
     }
+
+    fn find_branch_hints(
+        imports: &[&crate::core::Import<'_>],
+        funcs: &[&crate::core::Func<'_>],
+    ) -> Vec<FuncBranchHints> {
+        let num_imported = imports
+            .iter()
+            .filter(|i| matches!(i.item.kind, ItemKind::Func(_)))
+            .count();
+        funcs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, f)| {
+                let hints = f.branch_hint_offsets();
+                if hints.is_empty() {
+                    None
+                } else {
+                    Some(FuncBranchHints {
+                        func: (num_imported + i) as u32,
+                        hints,
+                    })
+                }
+            })
+            .collect()
+    }
 }
 
 struct Encoder<'a> {
@@ -114,6 +150,35 @@ impl Encoder<'_> {
         }
     }
 
+    /// Same as `section_list` for the code section, except that this also
+    /// returns the absolute offset of each instruction with a known span.
+    fn code_section(&mut self, funcs: &[&Func<'_>]) -> InstrOffsets {
+        self.custom_sections(CustomPlace::Before(CustomPlaceAnchor::Code));
+        let mut offsets = Vec::new();
+        if !funcs.is_empty() {
+            self.tmp.truncate(0);
+            funcs.len().encode(&mut self.tmp);
+            for func in funcs {
+                let mut body = Vec::new();
+                let start = offsets.len();
+                func.encode_body(&mut body, &mut offsets);
+                body.len().encode(&mut self.tmp);
+                for (offset, _) in offsets[start..].iter_mut() {
+                    *offset += self.tmp.len();
+                }
+                self.tmp.extend_from_slice(&body);
+            }
+            self.wasm.push(10);
+            self.tmp.len().encode(&mut self.wasm);
+            for (offset, _) in offsets.iter_mut() {
+                *offset += self.wasm.len();
+            }
+            self.wasm.extend_from_slice(&self.tmp);
+        }
+        self.custom_sections(CustomPlace::After(CustomPlaceAnchor::Code));
+        offsets
+    }
+
     fn section_list(&mut self, id: u8, anchor: CustomPlaceAnchor, list: &[impl Encode]) {
         self.custom_sections(CustomPlace::Before(anchor));
         if !list.is_empty() {
@@ -365,7 +430,20 @@ impl Encode for Index<'_> {
 impl<'a> Encode for TableType<'a> {
     fn encode(&self, e: &mut Vec<u8>) {
         self.elem.encode(e);
-        self.limits.encode(e);
+        let flag_max = self.limits.max.is_some() as u8;
+        let flag_64 = if self.is64 { 0x04 } else { 0 };
+        e.push(flag_max | flag_64);
+        if self.is64 {
+            self.limits.min.encode(e);
+            if let Some(max) = self.limits.max {
+                max.encode(e);
+            }
+        } else {
+            (self.limits.min as u32).encode(e);
+            if let Some(max) = self.limits.max {
+                (max as u32).encode(e);
+            }
+        }
     }
 }
 
@@ -578,18 +656,70 @@ impl Encode for Data<'_> {
 
 impl Encode for Func<'_> {
     fn encode(&self, e: &mut Vec<u8>) {
-        assert!(self.exports.names.is_empty());
         let mut tmp = Vec::new();
+        self.encode_body(&mut tmp, &mut Vec::new());
+        tmp.len().encode(e);
+        e.extend_from_slice(&tmp);
+    }
+}
+
+/// The entry of a `metadata.code.branch_hint` custom section for a single
+/// function.
+struct FuncBranchHints {
+    func: u32,
+    hints: Vec<(usize, u32)>,
+}
+
+impl Encode for FuncBranchHints {
+    fn encode(&self, e: &mut Vec<u8>) {
+        self.func.encode(e);
+        self.hints.len().encode(e);
+        for (offset, value) in self.hints.iter() {
+            offset.encode(e);
+            // Each hint's payload is a single byte.
+            1u32.encode(e);
+            (*value as u8).encode(e);
+        }
+    }
+}
+
+impl Func<'_> {
+    /// Returns the offset, relative to the start of the function body, and
+    /// value of each branch hint in this function.
+    fn branch_hint_offsets(&self) -> Vec<(usize, u32)> {
         let (expr, locals) = match &self.kind {
             FuncKind::Inline { expression, locals } => (expression, locals),
-            _ => panic!("should only have inline functions in emission"),
+            _ => return Vec::new(),
         };
+        if expr.branch_hints.is_empty() {
+            return Vec::new();
+        }
 
-        locals.encode(&mut tmp);
-        expr.encode(&mut tmp);
+        let mut body = Vec::new();
+        locals.encode(&mut body);
+        let mut hints = expr.branch_hints.iter().peekable();
+        let mut ret = Vec::new();
+        for (i, instr) in expr.instrs.iter().enumerate() {
+            if let Some(hint) = hints.next_if(|h| h.instr_index == i) {
+                ret.push((body.len(), hint.value));
+            }
+            instr.encode(&mut body);
+        }
+        ret
+    }
 
-        tmp.len().encode(e);
-        e.extend_from_slice(&tmp);
+    /// Encodes the body of this function, without its length prefix, into
+    /// `e`, recording the offset within `e` of each instruction with a known
+    /// span into `offsets`.
+    fn encode_body(&self, e: &mut Vec<u8>, offsets: &mut Vec<(usize, Span)>) {
+        assert!(self.exports.names.is_empty());
+        let (expr, locals) = match &self.kind {
+            FuncKind::Inline { expression, locals } => (expression, locals),
+            _ => panic!("should only have inline functions in emission"),
+        };
+
+        locals.encode(e);
+        expr.encode_with_offsets(e, offsets);
     }
 }
 
@@ -611,8 +741,24 @@ impl Encode for Vec<Local<'_>> {
 
 impl Encode for Expression<'_> {
     fn encode(&self, e: &mut Vec<u8>) {
-        for instr in self.instrs.iter() {
-            instr.encode(e);
+        self.encode_with_offsets(e, &mut Vec::new());
+    }
+}
+
+impl Expression<'_> {
+    fn encode_with_offsets(&self, e: &mut Vec<u8>, offsets: &mut Vec<(usize, Span)>) {
+        match &self.instr_spans {
+            Some(spans) => {
+                for (instr, span) in self.instrs.iter().zip(spans.iter()) {
+                    offsets.push((e.len(), *span));
+                    instr.encode(e);
+                }
+            }
+            None => {
+                for instr in self.instrs.iter() {
+                    instr.encode(e);
+                }
+            }
         }
         e.push(0x0b);
     }
@@ -827,7 +973,10 @@ fn find_names<'a>(
             ModuleField::Elem(e) => (Name::Elem, &e.id, &e.name),
             ModuleField::Data(d) => (Name::Data, &d.id, &d.name),
             ModuleField::Func(f) => (Name::Func, &f.id, &f.name),
-            ModuleField::Export(_) | ModuleField::Start(_) | ModuleField::Custom(_) => continue,
+            ModuleField::Export(_)
+            | ModuleField::Start(_)
+            | ModuleField::Custom(_)
+            | ModuleField::Dylink0(_) => continue,
         };
         names.push((kind, id, name, field));
     }
@@ -1017,6 +1166,45 @@ impl Encode for Custom<'_> {
     }
 }
 
+impl Encode for Dylink0<'_> {
+    fn encode(&self, e: &mut Vec<u8>) {
+        for section in self.subsections.iter() {
+            e.push(section.id());
+            let mut tmp = Vec::new();
+            section.encode(&mut tmp);
+            tmp.encode(e);
+        }
+    }
+}
+
+impl Encode for Dylink0Subsection<'_> {
+    fn encode(&self, e: &mut Vec<u8>) {
+        match self {
+            Dylink0Subsection::MemInfo {
+                memory_size,
+                memory_align,
+                table_size,
+                table_align,
+            } => {
+                memory_size.encode(e);
+                memory_align.encode(e);
+                table_size.encode(e);
+                table_align.encode(e);
+            }
+            Dylink0Subsection::Needed(libs) => libs.encode(e),
+            Dylink0Subsection::ExportInfo(list) => list.encode(e),
+            Dylink0Subsection::ImportInfo(list) => {
+                list.len().encode(e);
+                for (module, field, flags) in list {
+                    module.encode(e);
+                    field.encode(e);
+                    flags.encode(e);
+                }
+            }
+        }
+    }
+}
+
 impl Encode for Tag<'_> {
     fn encode(&self, e: &mut Vec<u8>) {
         self.ty.encode(e);
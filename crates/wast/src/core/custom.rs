@@ -149,3 +149,164 @@ impl<'a> Parse<'a> for CustomPlaceAnchor {
         Err(parser.error("expected a valid section name"))
     }
 }
+
+/// The contents of a `dylink.0` custom section, which describes a module as a
+/// dynamic library.
+///
+/// This is written as the `@dylink.0` annotation, and is always encoded as the
+/// first section of the module.
+///
+/// See the [dynamic linking conventions] for more information.
+///
+/// [dynamic linking conventions]: https://github.com/WebAssembly/tool-conventions/blob/main/DynamicLinking.md
+#[derive(Debug)]
+pub struct Dylink0<'a> {
+    /// Where this `@dylink.0` was defined.
+    pub span: Span,
+    /// The subsections of this section, in order.
+    pub subsections: Vec<Dylink0Subsection<'a>>,
+}
+
+/// A subsection of a [`Dylink0`] section.
+#[derive(Debug)]
+pub enum Dylink0Subsection<'a> {
+    /// `(mem-info (memory $size $align)? (table $size $align)?)`, where
+    /// omitted sizes and alignments are 0.
+    MemInfo {
+        /// The size, in bytes, of the library's static data.
+        memory_size: u32,
+        /// The required alignment of the static data, as a power of two.
+        memory_align: u32,
+        /// The number of table elements the library needs.
+        table_size: u32,
+        /// The required alignment of the table elements, as a power of two.
+        table_align: u32,
+    },
+    /// `(needed "lib"*)`, the libraries this library depends on.
+    Needed(Vec<&'a str>),
+    /// `(export-info "name" $flags*)`, the symbol flags of an export.
+    ExportInfo(Vec<(&'a str, u32)>),
+    /// `(import-info "module" "field" $flags*)`, the symbol flags of an
+    /// import.
+    ImportInfo(Vec<(&'a str, &'a str, u32)>),
+}
+
+impl Dylink0Subsection<'_> {
+    /// The identifier of this subsection in the binary format.
+    pub fn id(&self) -> u8 {
+        match self {
+            Dylink0Subsection::MemInfo { .. } => 1,
+            Dylink0Subsection::Needed(_) => 2,
+            Dylink0Subsection::ExportInfo(_) => 3,
+            Dylink0Subsection::ImportInfo(_) => 4,
+        }
+    }
+}
+
+impl<'a> Parse<'a> for Dylink0<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        let span = parser.parse::<annotation::dylink_0>()?.0;
+        let mut subsections: Vec<Dylink0Subsection<'a>> = Vec::new();
+        while !parser.is_empty() {
+            parser.parens(|p| {
+                let mut l = p.lookahead1();
+                if l.peek::<kw::mem_info>() {
+                    p.parse::<kw::mem_info>()?;
+                    let mut memory_size = 0;
+                    let mut memory_align = 0;
+                    let mut table_size = 0;
+                    let mut table_align = 0;
+                    if p.peek2::<kw::memory>() {
+                        p.parens(|p| {
+                            p.parse::<kw::memory>()?;
+                            memory_size = p.parse()?;
+                            memory_align = p.parse()?;
+                            Ok(())
+                        })?;
+                    }
+                    if p.peek2::<kw::table>() {
+                        p.parens(|p| {
+                            p.parse::<kw::table>()?;
+                            table_size = p.parse()?;
+                            table_align = p.parse()?;
+                            Ok(())
+                        })?;
+                    }
+                    subsections.push(Dylink0Subsection::MemInfo {
+                        memory_size,
+                        memory_align,
+                        table_size,
+                        table_align,
+                    });
+                } else if l.peek::<kw::needed>() {
+                    p.parse::<kw::needed>()?;
+                    let mut names = Vec::new();
+                    while !p.is_empty() {
+                        names.push(p.parse()?);
+                    }
+                    subsections.push(Dylink0Subsection::Needed(names));
+                } else if l.peek::<kw::export_info>() {
+                    p.parse::<kw::export_info>()?;
+                    let name = p.parse()?;
+                    let flags = parse_sym_flags(p)?;
+                    // Consecutive entries are merged into one subsection.
+                    match subsections.last_mut() {
+                        Some(Dylink0Subsection::ExportInfo(list)) => list.push((name, flags)),
+                        _ => subsections.push(Dylink0Subsection::ExportInfo(vec![(name, flags)])),
+                    }
+                } else if l.peek::<kw::import_info>() {
+                    p.parse::<kw::import_info>()?;
+                    let module = p.parse()?;
+                    let field = p.parse()?;
+                    let flags = parse_sym_flags(p)?;
+                    match subsections.last_mut() {
+                        Some(Dylink0Subsection::ImportInfo(list)) => {
+                            list.push((module, field, flags))
+                        }
+                        _ => subsections.push(Dylink0Subsection::ImportInfo(vec![(
+                            module, field, flags,
+                        )])),
+                    }
+                } else {
+                    return Err(l.error());
+                }
+                Ok(())
+            })?;
+        }
+        Ok(Dylink0 { span, subsections })
+    }
+}
+
+/// The names of the `WASM_SYM_*` symbol flags, as used in the text format.
+pub const SYM_FLAGS: &[(&str, u32)] = &[
+    ("binding-weak", 1 << 0),
+    ("binding-local", 1 << 1),
+    ("visibility-hidden", 1 << 2),
+    ("undefined", 1 << 4),
+    ("exported", 1 << 5),
+    ("explicit-name", 1 << 6),
+    ("no-strip", 1 << 7),
+    ("tls", 1 << 8),
+    ("absolute", 1 << 9),
+];
+
+/// Parses a list of flag names or integers, which are combined into one set
+/// of flags.
+fn parse_sym_flags(parser: Parser<'_>) -> Result<u32> {
+    let mut flags = 0;
+    while !parser.is_empty() {
+        if parser.peek::<u32>() {
+            flags |= parser.parse::<u32>()?;
+            continue;
+        }
+        flags |= parser.step(|c| {
+            if let Some((kw, rest)) = c.keyword() {
+                if let Some((_, flag)) = SYM_FLAGS.iter().find(|(name, _)| *name == kw) {
+                    return Ok((*flag, rest));
+                }
+            }
+            Err(c.error("expected a symbol flag"))
+        })?;
+    }
+    Ok(flags)
+}
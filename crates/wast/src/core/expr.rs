@@ -1,6 +1,6 @@
 use crate::core::*;
 use crate::encode::Encode;
-use crate::kw;
+use crate::{annotation, kw};
 use crate::parser::{Cursor, Parse, Parser, Result};
 use crate::token::*;
 use std::mem;
@@ -14,14 +14,68 @@ use std::mem;
 #[allow(missing_docs)]
 pub struct Expression<'a> {
     pub instrs: Box<[Instruction<'a>]>,
+
+    /// The location in the source of each instruction in `instrs`.
+    ///
+    /// This is only recorded if the [`ParseBuffer`](crate::parser::ParseBuffer)
+    /// this expression was parsed from was configured to track instruction
+    /// spans, and otherwise it's `None`.
+    pub instr_spans: Option<Box<[Span]>>,
+
+    /// Branch hints attached to instructions in `instrs` with the
+    /// `@metadata.code.branch_hint` annotation.
+    pub branch_hints: Vec<BranchHint>,
+}
+
+/// A `@metadata.code.branch_hint` annotation attached to an `if` or `br_if`
+/// instruction of an [`Expression`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BranchHint {
+    /// The index, within [`Expression::instrs`], of the hinted instruction.
+    pub instr_index: usize,
+    /// The value of the hint: `0` if the branch is likely not taken and `1`
+    /// if it's likely taken.
+    pub value: u32,
+}
+
+impl<'a> Expression<'a> {
+    /// Parses a constant expression, such as the initializer of a global,
+    /// which may only use arithmetic if the extended-const proposal is
+    /// enabled.
+    pub(crate) fn parse_const(parser: Parser<'a>) -> Result<Self> {
+        let span = parser.cur_span();
+        let expr: Expression = parser.parse()?;
+        let extended = expr.instrs.iter().any(|i| {
+            matches!(
+                i,
+                Instruction::I32Add
+                    | Instruction::I32Sub
+                    | Instruction::I32Mul
+                    | Instruction::I64Add
+                    | Instruction::I64Sub
+                    | Instruction::I64Mul
+            )
+        });
+        if extended {
+            parser.check_feature(span, "extended_const", |f| f.extended_const)?;
+        }
+        Ok(expr)
+    }
 }
 
 impl<'a> Parse<'a> for Expression<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let mut exprs = ExpressionParser::default();
         exprs.parse(parser)?;
+        let branch_hints = exprs.resolve_branch_hints(parser)?;
         Ok(Expression {
             instrs: exprs.instrs.into(),
+            branch_hints,
+            instr_spans: if parser.track_instr_spans() {
+                Some(exprs.spans.into())
+            } else {
+                None
+            },
         })
     }
 }
@@ -38,9 +92,20 @@ struct ExpressionParser<'a> {
     /// eventually become the final `Expression`.
     instrs: Vec<Instruction<'a>>,
 
+    /// The span of each instruction in `instrs`.
+    spans: Vec<Span>,
+
     /// Descriptor of all our nested s-expr blocks. This only happens when
     /// instructions themselves are nested.
     stack: Vec<Level<'a>>,
+
+    /// A branch hint which has been parsed but not yet attached to the
+    /// instruction following it, along with the span of the annotation.
+    pending_branch_hint: Option<(u32, Span)>,
+
+    /// Branch hints that have been parsed, recorded as the value of the hint,
+    /// the span of the annotation, and the span of the hinted instruction.
+    branch_hints: Vec<(u32, Span, Span)>,
 }
 
 enum Paren {
@@ -53,7 +118,7 @@ enum Paren {
 enum Level<'a> {
     /// This is a normal `block` or `loop` or similar, where the instruction
     /// payload here is pushed when the block is exited.
-    EndWith(Instruction<'a>),
+    EndWith(Instruction<'a>, Span),
 
     /// This is a pretty special variant which means that we're parsing an `if`
     /// statement, and the state of the `if` parsing is tracked internally in
@@ -77,9 +142,9 @@ enum Level<'a> {
 enum If<'a> {
     /// Only the `if` has been parsed, next thing to parse is the clause, if
     /// any, of the `if` instruction.
-    Clause(Instruction<'a>),
+    Clause(Instruction<'a>, Span),
     /// Next thing to parse is the `then` block
-    Then(Instruction<'a>),
+    Then(Instruction<'a>, Span),
     /// Next thing to parse is the `else` block
     Else,
     /// This `if` statement has finished parsing and if anything remains it's a
@@ -90,7 +155,7 @@ enum If<'a> {
 /// Possible state of "what should be parsed next?" in a `try` expression.
 enum Try<'a> {
     /// Next thing to parse is the `do` block.
-    Do(Instruction<'a>),
+    Do(Instruction<'a>, Span),
     /// Next thing to parse is `catch`/`catch_all`, or `delegate`.
     CatchOrDelegate,
     /// Next thing to parse is a `catch` block or `catch_all`.
@@ -104,6 +169,8 @@ enum Try<'a> {
 
 impl<'a> ExpressionParser<'a> {
     fn parse(&mut self, parser: Parser<'a>) -> Result<()> {
+        let _r = parser.register_annotation("metadata.code.branch_hint");
+
         // Here we parse instructions in a loop, and we do not recursively
         // invoke this parse function to avoid blowing the stack on
         // deeply-recursive parses.
@@ -122,10 +189,14 @@ impl<'a> ExpressionParser<'a> {
                 }
             }
 
+            let span = parser.cur_span();
             match self.paren(parser)? {
                 // No parenthesis seen? Then we just parse the next instruction
                 // and move on.
-                Paren::None => self.instrs.push(parser.parse()?),
+                Paren::None => {
+                    self.attach_branch_hint(span);
+                    self.push(parser.parse()?, span);
+                }
 
                 // If we see a left-parenthesis then things are a little
                 // special. We handle block-like instructions specially
@@ -136,6 +207,13 @@ impl<'a> ExpressionParser<'a> {
                 // In all cases here we push something onto the `stack` to get
                 // popped when the `)` character is seen.
                 Paren::Left => {
+                    // Branch hints are annotations which apply to the
+                    // instruction that follows them, so record the hint and
+                    // move on to the instruction itself.
+                    if parser.peek::<annotation::metadata_code_branch_hint>() {
+                        self.parse_branch_hint(parser)?;
+                        continue;
+                    }
                     // First up is handling `if` parsing, which is funky in a
                     // whole bunch of ways. See the method internally for more
                     // information.
@@ -147,6 +225,8 @@ impl<'a> ExpressionParser<'a> {
                     if self.handle_try_lparen(parser)? {
                         continue;
                     }
+                    let span = parser.cur_span();
+                    self.attach_branch_hint(span);
                     match parser.parse()? {
                         // If block/loop show up then we just need to be sure to
                         // push an `end` instruction whenever the `)` token is
@@ -154,27 +234,28 @@ impl<'a> ExpressionParser<'a> {
                         i @ Instruction::Block(_)
                         | i @ Instruction::Loop(_)
                         | i @ Instruction::Let(_) => {
-                            self.instrs.push(i);
-                            self.stack.push(Level::EndWith(Instruction::End(None)));
+                            self.push(i, span);
+                            self.stack
+                                .push(Level::EndWith(Instruction::End(None), span));
                         }
 
                         // Parsing an `if` instruction is super tricky, so we
                         // push an `If` scope and we let all our scope-based
                         // parsing handle the remaining items.
                         i @ Instruction::If(_) => {
-                            self.stack.push(Level::If(If::Clause(i)));
+                            self.stack.push(Level::If(If::Clause(i, span)));
                         }
 
                         // Parsing a `try` is easier than `if` but we also push
                         // a `Try` scope to handle the required nested blocks.
                         i @ Instruction::Try(_) => {
-                            self.stack.push(Level::Try(Try::Do(i)));
+                            self.stack.push(Level::Try(Try::Do(i, span)));
                         }
 
                         // Anything else means that we're parsing a nested form
                         // such as `(i32.add ...)` which means that the
                         // instruction we parsed will be coming at the end.
-                        other => self.stack.push(Level::EndWith(other)),
+                        other => self.stack.push(Level::EndWith(other, span)),
                     }
                 }
 
@@ -182,7 +263,10 @@ impl<'a> ExpressionParser<'a> {
                 // guaranteed there's an item in the `stack` stack for us to
                 // pop. We peel that off and take a look at what it says to do.
                 Paren::Right => match self.stack.pop().unwrap() {
-                    Level::EndWith(i) => self.instrs.push(i),
+                    Level::EndWith(Instruction::End(None), _) => {
+                        self.push(Instruction::End(None), span)
+                    }
+                    Level::EndWith(i, span) => self.push(i, span),
                     Level::IfArm => {}
                     Level::TryArm => {}
 
@@ -190,33 +274,97 @@ impl<'a> ExpressionParser<'a> {
                     // block, then that's an error because there weren't enough
                     // items in the `if` statement. Otherwise we're just careful
                     // to terminate with an `end` instruction.
-                    Level::If(If::Clause(_)) => {
+                    Level::If(If::Clause(..)) => {
                         return Err(parser.error("previous `if` had no clause"));
                     }
-                    Level::If(If::Then(_)) => {
+                    Level::If(If::Then(..)) => {
                         return Err(parser.error("previous `if` had no `then`"));
                     }
                     Level::If(_) => {
-                        self.instrs.push(Instruction::End(None));
+                        self.push(Instruction::End(None), span);
                     }
 
                     // The `do` clause is required in a `try` statement, so
                     // we will signal that error here. Otherwise, terminate with
                     // an `end` or `delegate` instruction.
-                    Level::Try(Try::Do(_)) => {
+                    Level::Try(Try::Do(..)) => {
                         return Err(parser.error("previous `try` had no `do`"));
                     }
                     Level::Try(Try::Delegate) => {}
                     Level::Try(_) => {
-                        self.instrs.push(Instruction::End(None));
+                        self.push(Instruction::End(None), span);
                     }
                 },
             }
         }
 
+        if let Some((_, span)) = self.pending_branch_hint {
+            return Err(parser.error_at(span, &"branch hint must be followed by an instruction"));
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, instr: Instruction<'a>, span: Span) {
+        self.instrs.push(instr);
+        self.spans.push(span);
+    }
+
+    /// Parses the remainder of a `(@metadata.code.branch_hint "...")`
+    /// annotation, whose leading `(` has already been consumed.
+    fn parse_branch_hint(&mut self, parser: Parser<'a>) -> Result<()> {
+        let span = parser.parse::<annotation::metadata_code_branch_hint>()?.0;
+        if self.pending_branch_hint.is_some() {
+            return Err(parser.error_at(span, &"multiple branch hints for one instruction"));
+        }
+        let value = match parser.parse::<&[u8]>()? {
+            [0] => 0,
+            [1] => 1,
+            _ => return Err(parser.error_at(span, &"invalid branch hint value")),
+        };
+        parser.step(|c| match c.rparen() {
+            Some(rest) => Ok(((), rest)),
+            None => Err(c.error("expected `)`")),
+        })?;
+        self.pending_branch_hint = Some((value, span));
         Ok(())
     }
 
+    /// Attaches the pending branch hint, if any, to the instruction starting
+    /// at `span`.
+    fn attach_branch_hint(&mut self, span: Span) {
+        if let Some((value, hint)) = self.pending_branch_hint.take() {
+            self.branch_hints.push((value, hint, span));
+        }
+    }
+
+    /// Translates the spans of hinted instructions into their final indices
+    /// within `instrs`, which aren't known until parsing has finished due to
+    /// folded instructions.
+    fn resolve_branch_hints(&self, parser: Parser<'a>) -> Result<Vec<BranchHint>> {
+        let mut ret = Vec::with_capacity(self.branch_hints.len());
+        for (value, hint, span) in self.branch_hints.iter() {
+            let instr_index = self
+                .instrs
+                .iter()
+                .zip(&self.spans)
+                .position(|(instr, s)| {
+                    s == span && matches!(instr, Instruction::If(_) | Instruction::BrIf(_))
+                })
+                .ok_or_else(|| {
+                    parser.error_at(
+                        *hint,
+                        &"branch hints may only be placed on `if` and `br_if` instructions",
+                    )
+                })?;
+            ret.push(BranchHint {
+                instr_index,
+                value: *value,
+            });
+        }
+        ret.sort_by_key(|h| h.instr_index);
+        Ok(ret)
+    }
+
     /// Parses either `(`, `)`, or nothing.
     fn paren(&self, parser: Parser<'a>) -> Result<Paren> {
         parser.step(|cursor| {
@@ -262,9 +410,9 @@ impl<'a> ExpressionParser<'a> {
         // The first thing parsed in an `if` statement is the clause. If the
         // clause starts with `then`, however, then we know to skip the clause
         // and fall through to below.
-        if let If::Clause(if_instr) = i {
+        if let If::Clause(if_instr, span) = i {
             let instr = mem::replace(if_instr, Instruction::End(None));
-            *i = If::Then(instr);
+            *i = If::Then(instr, *span);
             if !parser.peek::<kw::then>() {
                 return Ok(false);
             }
@@ -277,10 +425,11 @@ impl<'a> ExpressionParser<'a> {
         //
         // Note that when we see the `then`, that's when we actually add the
         // original `if` instruction to the stream.
-        if let If::Then(if_instr) = i {
+        if let If::Then(if_instr, span) = i {
             let instr = mem::replace(if_instr, Instruction::End(None));
-            self.instrs.push(instr);
+            let span = *span;
             *i = If::Else;
+            self.push(instr, span);
             if parser.parse::<Option<kw::then>>()?.is_some() {
                 self.stack.push(Level::IfArm);
                 return Ok(true);
@@ -290,15 +439,16 @@ impl<'a> ExpressionParser<'a> {
 
         // effectively the same as the `then` parsing above
         if let If::Else = i {
-            self.instrs.push(Instruction::Else(None));
+            let span = parser.cur_span();
             if parser.parse::<Option<kw::r#else>>()?.is_some() {
-                if parser.is_empty() {
-                    self.instrs.pop();
+                if !parser.is_empty() {
+                    self.push(Instruction::Else(None), span);
                 }
                 self.stack.push(Level::IfArm);
                 return Ok(true);
             }
             *i = If::End;
+            self.push(Instruction::Else(None), span);
             return Ok(false);
         }
 
@@ -329,13 +479,16 @@ impl<'a> ExpressionParser<'a> {
         };
 
         // Try statements must start with a `do` block.
-        if let Try::Do(try_instr) = i {
+        if let Try::Do(try_instr, span) = i {
             let instr = mem::replace(try_instr, Instruction::End(None));
-            self.instrs.push(instr);
-            if parser.parse::<Option<kw::r#do>>()?.is_some() {
-                // The state is advanced here only if the parse succeeds in
-                // order to strictly require the keyword.
+            let span = *span;
+            if parser.peek::<kw::r#do>() {
+                // The state is advanced here only if the keyword is present
+                // in order to strictly require it.
                 *i = Try::CatchOrDelegate;
+            }
+            self.push(instr, span);
+            if parser.parse::<Option<kw::r#do>>()?.is_some() {
                 self.stack.push(Level::TryArm);
                 return Ok(true);
             }
@@ -348,25 +501,26 @@ impl<'a> ExpressionParser<'a> {
         // After a try's `do`, there are several possible kinds of handlers.
         if let Try::CatchOrDelegate = i {
             // `catch` may be followed by more `catch`s or `catch_all`.
+            let span = parser.cur_span();
             if parser.parse::<Option<kw::catch>>()?.is_some() {
                 let evt = parser.parse::<Index<'a>>()?;
-                self.instrs.push(Instruction::Catch(evt));
                 *i = Try::Catch;
+                self.push(Instruction::Catch(evt), span);
                 self.stack.push(Level::TryArm);
                 return Ok(true);
             }
             // `catch_all` can only come at the end and has no argument.
             if parser.parse::<Option<kw::catch_all>>()?.is_some() {
-                self.instrs.push(Instruction::CatchAll);
                 *i = Try::End;
+                self.push(Instruction::CatchAll, span);
                 self.stack.push(Level::TryArm);
                 return Ok(true);
             }
             // `delegate` has an index, and also ends the block like `end`.
             if parser.parse::<Option<kw::delegate>>()?.is_some() {
                 let depth = parser.parse::<Index<'a>>()?;
-                self.instrs.push(Instruction::Delegate(depth));
                 *i = Try::Delegate;
+                self.push(Instruction::Delegate(depth), span);
                 match self.paren(parser)? {
                     Paren::Left | Paren::None => return Ok(false),
                     Paren::Right => return Ok(true),
@@ -376,16 +530,17 @@ impl<'a> ExpressionParser<'a> {
         }
 
         if let Try::Catch = i {
+            let span = parser.cur_span();
             if parser.parse::<Option<kw::catch>>()?.is_some() {
                 let evt = parser.parse::<Index<'a>>()?;
-                self.instrs.push(Instruction::Catch(evt));
                 *i = Try::Catch;
+                self.push(Instruction::Catch(evt), span);
                 self.stack.push(Level::TryArm);
                 return Ok(true);
             }
             if parser.parse::<Option<kw::catch_all>>()?.is_some() {
-                self.instrs.push(Instruction::CatchAll);
                 *i = Try::End;
+                self.push(Instruction::CatchAll, span);
                 self.stack.push(Level::TryArm);
                 return Ok(true);
             }
@@ -425,6 +580,7 @@ macro_rules! instructions {
                         ))?)
                     }
                 )*
+                let span = parser.cur_span();
                 let parse_remainder = parser.step(|c| {
                     let (kw, rest) = match c.keyword() {
                         Some(pair) => pair,
@@ -435,7 +591,9 @@ macro_rules! instructions {
                         _ => return Err(c.error("unknown operator or unexpected token")),
                     }
                 })?;
-                parse_remainder(parser)
+                let instr: Instruction<'a> = parse_remainder(parser)?;
+                instr.check_feature(parser, span)?;
+                Ok(instr)
             }
         }
 
@@ -469,6 +627,19 @@ macro_rules! instructions {
                     )*
                 }
             }
+
+            /// Returns the leading byte of this instruction's encoding along
+            /// with the opcode following it for prefixed instructions, or
+            /// `None` if the encoding depends on the instruction's payload.
+            fn opcode(&self) -> Option<(u8, Option<u32>)> {
+                match self {
+                    $(
+                        Instruction::$name $((instructions!(@memarg_binding _x $($arg)*)))? => {
+                            instructions!(@opcode $($binary)*)
+                        }
+                    )*
+                }
+            }
         }
     );
 
@@ -491,6 +662,10 @@ macro_rules! instructions {
     });
     (@encode $dst:ident $($bytes:tt)*) => ($dst.extend_from_slice(&[$($bytes)*]););
 
+    (@opcode) => (None);
+    (@opcode $prefix:tt) => (Some(($prefix, None)));
+    (@opcode $prefix:tt, $op:tt $(, $rest:tt)*) => (Some(($prefix, Some($op))));
+
     (@get_memarg $name:ident MemArg<$amt:tt>) => (Some($name));
     (@get_memarg $name:ident LoadOrStoreLane<$amt:tt>) => (Some(&mut $name.memarg));
     (@get_memarg $($other:tt)*) => (None);
@@ -1160,6 +1335,58 @@ instructions! {
 }
 
 impl<'a> Instruction<'a> {
+    /// Returns an error at `span` if this instruction belongs to a proposal
+    /// which isn't enabled in the [`Features`](crate::parser::Features) of
+    /// `parser`.
+    fn check_feature(&self, parser: Parser<'a>, span: Span) -> Result<()> {
+        use crate::parser::Features;
+
+        let (name, enabled): (_, fn(&Features) -> bool) = match self {
+            Instruction::Select(SelectTypes { tys: Some(_) }) => {
+                ("reference_types", |f| f.reference_types)
+            }
+            Instruction::I8x16RelaxedSwizzle
+            | Instruction::I32x4RelaxedTruncF32x4S
+            | Instruction::I32x4RelaxedTruncF32x4U
+            | Instruction::I32x4RelaxedTruncF64x2SZero
+            | Instruction::I32x4RelaxedTruncF64x2UZero
+            | Instruction::F32x4Fma
+            | Instruction::F32x4Fms
+            | Instruction::F64x4Fma
+            | Instruction::F64x4Fms
+            | Instruction::I8x16LaneSelect
+            | Instruction::I16x8LaneSelect
+            | Instruction::I32x4LaneSelect
+            | Instruction::I64x2LaneSelect
+            | Instruction::F32x4RelaxedMin
+            | Instruction::F32x4RelaxedMax
+            | Instruction::F64x2RelaxedMin
+            | Instruction::F64x2RelaxedMax => ("relaxed_simd", |f| f.relaxed_simd),
+            _ => match self.opcode() {
+                Some((0xfd, _)) => ("simd", |f| f.simd),
+                Some((0xfe, _)) => ("threads", |f| f.threads),
+                Some((0xfb, _)) => ("gc", |f| f.gc),
+                Some((0xfc, Some(0x00..=0x07))) => {
+                    ("saturating_float_to_int", |f| f.saturating_float_to_int)
+                }
+                Some((0xfc, Some(0x08..=0x0e))) => ("bulk_memory", |f| f.bulk_memory),
+                Some((0xfc, Some(0x0f..=0x11))) => ("reference_types", |f| f.reference_types),
+                Some((0x06..=0x09 | 0x18 | 0x19, None)) => ("exceptions", |f| f.exceptions),
+                Some((0x12 | 0x13, None)) => ("tail_call", |f| f.tail_call),
+                Some((0x14 | 0x15 | 0x17 | 0xd3 | 0xd4 | 0xd6, None)) => {
+                    ("function_references", |f| f.function_references)
+                }
+                Some((0xd5, None)) => ("gc", |f| f.gc),
+                Some((0xc0..=0xc4, None)) => ("sign_extension", |f| f.sign_extension),
+                Some((0x25 | 0x26 | 0xd0..=0xd2, None)) => {
+                    ("reference_types", |f| f.reference_types)
+                }
+                _ => return Ok(()),
+            },
+        };
+        parser.check_feature(span, name, enabled)
+    }
+
     pub(crate) fn needs_data_count(&self) -> bool {
         match self {
             Instruction::MemoryInit(_)
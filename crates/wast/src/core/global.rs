@@ -45,7 +45,10 @@ impl<'a> Parse<'a> for Global<'a> {
         let (ty, kind) = if let Some(import) = parser.parse()? {
             (parser.parse()?, GlobalKind::Import(import))
         } else {
-            (parser.parse()?, GlobalKind::Inline(parser.parse()?))
+            (
+                parser.parse()?,
+                GlobalKind::Inline(Expression::parse_const(parser)?),
+            )
         };
         Ok(Global {
             span,
@@ -85,14 +85,20 @@ impl<'a> Parse<'a> for ItemSig<'a> {
             })
         } else if l.peek::<kw::global>() {
             let span = parser.parse::<kw::global>()?.0;
+            let id = parser.parse()?;
+            let ty: GlobalType = parser.parse()?;
+            if ty.mutable {
+                parser.check_feature(span, "mutable_global", |f| f.mutable_global)?;
+            }
             Ok(ItemSig {
                 span,
-                id: parser.parse()?,
+                id,
                 name: None,
-                kind: ItemKind::Global(parser.parse()?),
+                kind: ItemKind::Global(ty),
             })
         } else if l.peek::<kw::tag>() {
             let span = parser.parse::<kw::tag>()?.0;
+            parser.check_feature(span, "exceptions", |f| f.exceptions)?;
             Ok(ItemSig {
                 span,
                 id: parser.parse()?,
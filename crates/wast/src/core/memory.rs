@@ -156,7 +156,7 @@ impl<'a> Parse<'a> for Data<'a> {
             let offset = parser.parens(|parser| {
                 if parser.peek::<kw::offset>() {
                     parser.parse::<kw::offset>()?;
-                    parser.parse()
+                    Expression::parse_const(parser)
                 } else {
                     // This is all that the spec allows, which is that if
                     // `offset` isn't present then this is "sugar" for a
@@ -165,6 +165,8 @@ impl<'a> Parse<'a> for Data<'a> {
                     if parser.is_empty() {
                         return Ok(Expression {
                             instrs: [insn].into(),
+                            instr_spans: None,
+                            branch_hints: Vec::new(),
                         });
                     }
 
@@ -179,11 +181,13 @@ impl<'a> Parse<'a> for Data<'a> {
                     //    (data (offset ...))
                     //
                     // but alas
-                    let expr: Expression = parser.parse()?;
+                    let expr = Expression::parse_const(parser)?;
                     let mut instrs = Vec::from(expr.instrs);
                     instrs.push(insn);
                     Ok(Expression {
                         instrs: instrs.into(),
+                        instr_spans: None,
+                        branch_hints: Vec::new(),
                     })
                 }
             })?;
@@ -5,6 +5,10 @@ use crate::{annotation, kw};
 
 pub use crate::core::resolve::Names;
 
+/// The offset of each instruction in an encoded binary, paired with the span
+/// of the instruction in the original text.
+pub type InstrOffsets = Vec<(usize, Span)>;
+
 /// A parsed WebAssembly core module.
 #[derive(Debug)]
 pub struct Module<'a> {
@@ -85,19 +89,53 @@ impl<'a> Module<'a> {
     /// This function can return an error for name resolution errors and other
     /// expansion-related errors.
     pub fn encode(&mut self) -> std::result::Result<Vec<u8>, crate::Error> {
+        Ok(self.encode_with_instr_offsets()?.0)
+    }
+
+    /// Same as [`Module::encode`], except that this also returns the offset
+    /// in the returned binary of each instruction along with its span in the
+    /// original text.
+    ///
+    /// Instruction offsets are only available if the module was parsed from
+    /// a [`ParseBuffer`](crate::parser::ParseBuffer) configured to
+    /// [track instruction spans](crate::parser::ParseBuffer::track_instr_spans),
+    /// and are otherwise empty. Offsets are sorted in ascending order.
+    pub fn encode_with_instr_offsets(&mut self) -> Result<(Vec<u8>, InstrOffsets)> {
         self.resolve()?;
         Ok(match &self.kind {
             ModuleKind::Text(fields) => crate::core::binary::encode(&self.id, &self.name, fields),
-            ModuleKind::Binary(blobs) => blobs.iter().flat_map(|b| b.iter().cloned()).collect(),
+            ModuleKind::Binary(blobs) => (
+                blobs.iter().flat_map(|b| b.iter().cloned()).collect(),
+                Vec::new(),
+            ),
         })
     }
 
     pub(crate) fn validate(&self, parser: Parser<'_>) -> Result<()> {
         let mut starts = 0;
+        let mut memories = 0;
         if let ModuleKind::Text(fields) = &self.kind {
             for item in fields.iter() {
-                if let ModuleField::Start(_) = item {
-                    starts += 1;
+                let memory_span = match item {
+                    ModuleField::Start(_) => {
+                        starts += 1;
+                        continue;
+                    }
+                    ModuleField::Memory(m) => m.span,
+                    ModuleField::Import(Import {
+                        item:
+                            ItemSig {
+                                span,
+                                kind: ItemKind::Memory(_),
+                                ..
+                            },
+                        ..
+                    }) => *span,
+                    _ => continue,
+                };
+                memories += 1;
+                if memories > 1 {
+                    parser.check_feature(memory_span, "multi_memory", |f| f.multi_memory)?;
                 }
             }
         }
@@ -108,10 +146,12 @@ impl<'a> Module<'a> {
     }
 }
 
-impl<'a> Parse<'a> for Module<'a> {
-    fn parse(parser: Parser<'a>) -> Result<Self> {
+impl<'a> Module<'a> {
+    /// Parses the rest of a module whose leading `module` keyword, at
+    /// `span`, has already been parsed.
+    pub(crate) fn parse_without_module_keyword(span: Span, parser: Parser<'a>) -> Result<Self> {
         let _r = parser.register_annotation("custom");
-        let span = parser.parse::<kw::module>()?.0;
+        let _r = parser.register_annotation("dylink.0");
         let id = parser.parse()?;
         let name = parser.parse()?;
 
@@ -134,6 +174,15 @@ impl<'a> Parse<'a> for Module<'a> {
     }
 }
 
+impl<'a> Parse<'a> for Module<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        let _r = parser.register_annotation("custom");
+        let _r = parser.register_annotation("dylink.0");
+        let span = parser.parse::<kw::module>()?.0;
+        Module::parse_without_module_keyword(span, parser)
+    }
+}
+
 /// A listing of all possible fields that can make up a WebAssembly module.
 #[allow(missing_docs)]
 #[derive(Debug)]
@@ -151,13 +200,16 @@ pub enum ModuleField<'a> {
     Data(Data<'a>),
     Tag(Tag<'a>),
     Custom(Custom<'a>),
+    Dylink0(Dylink0<'a>),
 }
 
 impl<'a> ModuleField<'a> {
     pub(crate) fn parse_remaining(parser: Parser<'a>) -> Result<Vec<ModuleField>> {
         let mut fields = Vec::new();
         while !parser.is_empty() {
-            fields.push(parser.parens(ModuleField::parse)?);
+            if let Some(field) = parser.parens_recovering(ModuleField::parse)? {
+                fields.push(field);
+            }
         }
         Ok(fields)
     }
@@ -205,6 +257,9 @@ impl<'a> Parse<'a> for ModuleField<'a> {
         if parser.peek::<annotation::custom>() {
             return Ok(ModuleField::Custom(parser.parse()?));
         }
+        if parser.peek::<annotation::dylink_0>() {
+            return Ok(ModuleField::Dylink0(parser.parse()?));
+        }
         Err(parser.error("expected valid module field"))
     }
 }
@@ -85,6 +85,8 @@ pub fn run(fields: &mut Vec<ModuleField>) {
                                     } else {
                                         Instruction::I64Const(0)
                                     }]),
+                                    instr_spans: None,
+                                    branch_hints: Vec::new(),
                                 },
                             },
                             data,
@@ -115,15 +117,21 @@ pub fn run(fields: &mut Vec<ModuleField>) {
                     }
                     // If data is defined inline insert an explicit `data` module
                     // field here instead, switching this to a `Normal` memory.
-                    TableKind::Inline { payload, elem } => {
+                    TableKind::Inline {
+                        is64,
+                        payload,
+                        elem,
+                    } => {
+                        let is64 = *is64;
                         let len = match payload {
                             ElemPayload::Indices(v) => v.len(),
                             ElemPayload::Exprs { exprs, .. } => exprs.len(),
                         };
                         let kind = TableKind::Normal(TableType {
-                            limits: Limits {
-                                min: len as u32,
-                                max: Some(len as u32),
+                            is64,
+                            limits: Limits64 {
+                                min: len as u64,
+                                max: Some(len as u64),
                             },
                             elem: *elem,
                         });
@@ -139,7 +147,13 @@ pub fn run(fields: &mut Vec<ModuleField>) {
                             kind: ElemKind::Active {
                                 table: Index::Id(id),
                                 offset: Expression {
-                                    instrs: Box::new([Instruction::I32Const(0)]),
+                                    instrs: Box::new([if is64 {
+                                        Instruction::I64Const(0)
+                                    } else {
+                                        Instruction::I32Const(0)
+                                    }]),
+                                    instr_spans: None,
+                                    branch_hints: Vec::new(),
                                 },
                             },
                             payload,
@@ -201,7 +215,8 @@ pub fn run(fields: &mut Vec<ModuleField>) {
             | ModuleField::Start(_)
             | ModuleField::Elem(_)
             | ModuleField::Data(_)
-            | ModuleField::Custom(_) => {}
+            | ModuleField::Custom(_)
+            | ModuleField::Dylink0(_) => {}
         }
 
         fields.push(item);
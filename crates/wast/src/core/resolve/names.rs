@@ -107,9 +107,10 @@ impl<'a> Resolver<'a> {
             ModuleField::Tag(t) => self.tags.register(t.id, "tag")?,
 
             // These fields don't define any items in any index space.
-            ModuleField::Export(_) | ModuleField::Start(_) | ModuleField::Custom(_) => {
-                return Ok(())
-            }
+            ModuleField::Export(_)
+            | ModuleField::Start(_)
+            | ModuleField::Custom(_)
+            | ModuleField::Dylink0(_) => return Ok(()),
         };
 
         Ok(())
@@ -268,7 +269,7 @@ impl<'a> Resolver<'a> {
                 Ok(())
             }
 
-            ModuleField::Memory(_) | ModuleField::Custom(_) => Ok(()),
+            ModuleField::Memory(_) | ModuleField::Custom(_) | ModuleField::Dylink0(_) => Ok(()),
         }
     }
 
@@ -107,7 +107,8 @@ impl<'a> Expander<'a> {
             | ModuleField::Memory(_)
             | ModuleField::Start(_)
             | ModuleField::Export(_)
-            | ModuleField::Custom(_) => {}
+            | ModuleField::Custom(_)
+            | ModuleField::Dylink0(_) => {}
         }
     }
 
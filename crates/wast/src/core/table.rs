@@ -34,6 +34,8 @@ pub enum TableKind<'a> {
 
     /// The elem segments of this table, starting from 0, explicitly listed
     Inline {
+        /// Whether or not this is a 64-bit table, indexed with `i64`.
+        is64: bool,
         /// The element type of this table.
         elem: RefType<'a>,
         /// The element table entries to have, and the length of this list is
@@ -51,11 +53,14 @@ impl<'a> Parse<'a> for Table<'a> {
 
         // Afterwards figure out which style this is, either:
         //
-        //  *   `elemtype (elem ...)`
-        //  *   `(import "a" "b") limits`
-        //  *   `limits`
+        //  *   `indextype? elemtype (elem ...)`
+        //  *   `(import "a" "b") indextype? limits`
+        //  *   `indextype? limits`
+        let index_type_then_elem = (parser.peek::<kw::i32>() || parser.peek::<kw::i64>())
+            && parser.peek2::<RefType>();
         let mut l = parser.lookahead1();
-        let kind = if l.peek::<RefType>() {
+        let kind = if l.peek::<RefType>() || index_type_then_elem {
+            let is64 = parse_table64(parser)?;
             let elem = parser.parse()?;
             let payload = parser.parens(|p| {
                 p.parse::<kw::elem>()?;
@@ -66,8 +71,12 @@ impl<'a> Parse<'a> for Table<'a> {
                 };
                 ElemPayload::parse_tail(parser, ty)
             })?;
-            TableKind::Inline { elem, payload }
-        } else if l.peek::<u32>() {
+            TableKind::Inline {
+                is64,
+                elem,
+                payload,
+            }
+        } else if l.peek::<u32>() || l.peek::<kw::i32>() || l.peek::<kw::i64>() {
             TableKind::Normal(parser.parse()?)
         } else if let Some(import) = parser.parse()? {
             TableKind::Import {
@@ -166,7 +175,7 @@ impl<'a> Parse<'a> for Elem<'a> {
                 if parser.peek::<kw::offset>() {
                     parser.parse::<kw::offset>()?;
                 }
-                parser.parse()
+                Expression::parse_const(parser)
             })?;
             ElemKind::Active { table, offset }
         } else {
@@ -219,6 +228,8 @@ impl<'a> ElemPayload<'a> {
                     let insn = parser.parse()?;
                     Ok(Expression {
                         instrs: [insn].into(),
+                        instr_spans: None,
+                        branch_hints: Vec::new(),
                     })
                 }
             })?;
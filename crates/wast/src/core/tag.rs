@@ -45,6 +45,7 @@ pub enum TagKind<'a> {
 impl<'a> Parse<'a> for Tag<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let span = parser.parse::<kw::tag>()?.0;
+        parser.check_feature(span, "exceptions", |f| f.exceptions)?;
         let id = parser.parse()?;
         let name = parser.parse()?;
         let exports = parser.parse()?;
@@ -32,9 +32,11 @@ impl<'a> Parse<'a> for ValType<'a> {
             parser.parse::<kw::f64>()?;
             Ok(ValType::F64)
         } else if l.peek::<kw::v128>() {
-            parser.parse::<kw::v128>()?;
+            let span = parser.parse::<kw::v128>()?.0;
+            parser.check_feature(span, "simd", |f| f.simd)?;
             Ok(ValType::V128)
         } else if l.peek::<RefType>() {
+            parser.check_feature(parser.cur_span(), "reference_types", |f| f.reference_types)?;
             Ok(ValType::Ref(parser.parse()?))
         } else {
             Err(l.error())
@@ -367,21 +369,46 @@ impl<'a> Parse<'a> for Limits64 {
 /// Configuration for a table of a wasm mdoule
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct TableType<'a> {
+    /// Whether or not this is a 64-bit table, indexed with `i64`
+    pub is64: bool,
     /// Limits on the element sizes of this table
-    pub limits: Limits,
+    pub limits: Limits64,
     /// The type of element stored in this table
     pub elem: RefType<'a>,
 }
 
 impl<'a> Parse<'a> for TableType<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
+        let is64 = parse_table64(parser)?;
+        let limits = if is64 {
+            parser.parse()?
+        } else {
+            let limits: Limits = parser.parse()?;
+            Limits64 {
+                min: limits.min.into(),
+                max: limits.max.map(Into::into),
+            }
+        };
         Ok(TableType {
-            limits: parser.parse()?,
+            is64,
+            limits,
             elem: parser.parse()?,
         })
     }
 }
 
+/// Parses the optional index type of a table, returning whether it's `i64`.
+pub(crate) fn parse_table64(parser: Parser<'_>) -> Result<bool> {
+    if parser.peek::<kw::i64>() {
+        let span = parser.parse::<kw::i64>()?.0;
+        parser.check_feature(span, "memory64", |f| f.memory64)?;
+        Ok(true)
+    } else {
+        parser.parse::<Option<kw::i32>>()?;
+        Ok(false)
+    }
+}
+
 /// Configuration for a memory of a wasm module
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum MemoryType {
@@ -404,19 +431,30 @@ pub enum MemoryType {
 impl<'a> Parse<'a> for MemoryType {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         if parser.peek::<kw::i64>() {
-            parser.parse::<kw::i64>()?;
+            let span = parser.parse::<kw::i64>()?.0;
+            parser.check_feature(span, "memory64", |f| f.memory64)?;
             let limits = parser.parse()?;
-            let shared = parser.parse::<Option<kw::shared>>()?.is_some();
+            let shared = parse_shared(parser)?;
             Ok(MemoryType::B64 { limits, shared })
         } else {
             parser.parse::<Option<kw::i32>>()?;
             let limits = parser.parse()?;
-            let shared = parser.parse::<Option<kw::shared>>()?.is_some();
+            let shared = parse_shared(parser)?;
             Ok(MemoryType::B32 { limits, shared })
         }
     }
 }
 
+fn parse_shared(parser: Parser<'_>) -> Result<bool> {
+    match parser.parse::<Option<kw::shared>>()? {
+        Some(kw) => {
+            parser.check_feature(kw.0, "threads", |f| f.threads)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
 /// A function type with parameters and results.
 #[derive(Clone, Debug, Default)]
 pub struct FunctionType<'a> {
@@ -459,6 +497,9 @@ impl<'a> FunctionType<'a> {
                 } else if l.peek::<kw::result>() {
                     p.parse::<kw::result>()?;
                     while !p.is_empty() {
+                        if !results.is_empty() {
+                            p.check_feature(p.cur_span(), "multi_value", |f| f.multi_value)?;
+                        }
                         results.push(p.parse()?);
                     }
                 } else {
@@ -76,6 +76,10 @@ pub enum WastRetCore<'a> {
     RefExtern(u32),
     /// A non-null funcref is expected.
     RefFunc(Option<Index<'a>>),
+
+    /// Any one of the listed values is expected, used for results which are
+    /// nondeterministic such as those of racing threads.
+    Either(Vec<WastRetCore<'a>>),
 }
 
 static RETS: &[(&str, fn(Parser<'_>) -> Result<WastRetCore<'_>>)] = {
@@ -89,6 +93,14 @@ static RETS: &[(&str, fn(Parser<'_>) -> Result<WastRetCore<'_>>)] = {
         ("ref.null", |p| Ok(RefNull(p.parse()?))),
         ("ref.extern", |p| Ok(RefExtern(p.parse()?))),
         ("ref.func", |p| Ok(RefFunc(p.parse()?))),
+        ("either", |p| {
+            p.depth_check()?;
+            let mut cases = Vec::new();
+            while !p.is_empty() {
+                cases.push(p.parens(|p| p.parse())?);
+            }
+            Ok(Either(cases))
+        }),
     ]
 };
 
@@ -147,6 +159,83 @@ where
     }
 }
 
+/// How NaN results are compared against the `nan:canonical` and
+/// `nan:arithmetic` patterns of a [`NanPattern`].
+///
+/// Values which aren't patterns, including NaN literals, always match by
+/// their exact bits.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum NanComparison {
+    /// The semantics of the spec test suite, and the default: `nan:canonical`
+    /// matches a canonical NaN of either sign, and `nan:arithmetic` matches
+    /// any NaN with the quiet bit set.
+    #[default]
+    Arithmetic,
+    /// Both patterns only match a canonical NaN of either sign, for engines
+    /// which never produce non-canonical NaNs.
+    Canonical,
+    /// Both patterns only match the positive canonical NaN, as produced by
+    /// engines implementing the deterministic profile.
+    BitExact,
+}
+
+/// The layout of the bits of a floating point type.
+struct FloatBits {
+    sign: u64,
+    exponent: u64,
+    quiet: u64,
+}
+
+const F32_BITS: FloatBits = FloatBits {
+    sign: 0x8000_0000,
+    exponent: 0x7f80_0000,
+    quiet: 0x0040_0000,
+};
+
+const F64_BITS: FloatBits = FloatBits {
+    sign: 0x8000_0000_0000_0000,
+    exponent: 0x7ff0_0000_0000_0000,
+    quiet: 0x0008_0000_0000_0000,
+};
+
+impl<T> NanPattern<T> {
+    fn matches_bits(
+        &self,
+        bits: u64,
+        value: impl FnOnce(&T) -> u64,
+        layout: &FloatBits,
+        cmp: NanComparison,
+    ) -> bool {
+        let canonical = layout.exponent | layout.quiet;
+        match (self, cmp) {
+            (NanPattern::Value(v), _) => value(v) == bits,
+            (_, NanComparison::BitExact) => bits == canonical,
+            (NanPattern::CanonicalNan, _) | (NanPattern::ArithmeticNan, NanComparison::Canonical) => {
+                bits & !layout.sign == canonical
+            }
+            (NanPattern::ArithmeticNan, NanComparison::Arithmetic) => {
+                bits & layout.exponent == layout.exponent && bits & layout.quiet != 0
+            }
+        }
+    }
+}
+
+impl NanPattern<Float32> {
+    /// Returns whether the `f32` result with the given `bits` matches this
+    /// pattern, using `cmp` to compare NaNs.
+    pub fn matches(&self, bits: u32, cmp: NanComparison) -> bool {
+        self.matches_bits(bits.into(), |f| f.bits.into(), &F32_BITS, cmp)
+    }
+}
+
+impl NanPattern<Float64> {
+    /// Returns whether the `f64` result with the given `bits` matches this
+    /// pattern, using `cmp` to compare NaNs.
+    pub fn matches(&self, bits: u64, cmp: NanComparison) -> bool {
+        self.matches_bits(bits, |f| f.bits, &F64_BITS, cmp)
+    }
+}
+
 /// A version of `V128Const` that allows `NanPattern`s.
 ///
 /// This implementation is necessary because only float types can include NaN patterns; otherwise
@@ -162,6 +251,39 @@ pub enum V128Pattern {
     F64x2([NanPattern<Float64>; 2]),
 }
 
+impl V128Pattern {
+    /// Returns whether the `v128` result with the given `bits`, with lane 0 in
+    /// the least significant bits, matches this pattern, using `cmp` to
+    /// compare NaNs in floating point lanes.
+    pub fn matches(&self, bits: u128, cmp: NanComparison) -> bool {
+        let bytes = bits.to_le_bytes();
+        match self {
+            V128Pattern::I8x16(lanes) => lanes
+                .iter()
+                .zip(bytes)
+                .all(|(lane, b)| *lane as u8 == b),
+            V128Pattern::I16x8(lanes) => lanes
+                .iter()
+                .zip(bytes.chunks(2))
+                .all(|(lane, b)| lane.to_le_bytes() == b),
+            V128Pattern::I32x4(lanes) => lanes
+                .iter()
+                .zip(bytes.chunks(4))
+                .all(|(lane, b)| lane.to_le_bytes() == b),
+            V128Pattern::I64x2(lanes) => lanes
+                .iter()
+                .zip(bytes.chunks(8))
+                .all(|(lane, b)| lane.to_le_bytes() == b),
+            V128Pattern::F32x4(lanes) => lanes.iter().zip(bytes.chunks(4)).all(|(lane, b)| {
+                lane.matches(u32::from_le_bytes(b.try_into().unwrap()), cmp)
+            }),
+            V128Pattern::F64x2(lanes) => lanes.iter().zip(bytes.chunks(8)).all(|(lane, b)| {
+                lane.matches(u64::from_le_bytes(b.try_into().unwrap()), cmp)
+            }),
+        }
+    }
+}
+
 impl<'a> Parse<'a> for V128Pattern {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let mut l = parser.lookahead1();
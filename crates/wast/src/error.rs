@@ -120,6 +120,12 @@ impl Error {
         }
     }
 
+    /// Returns the 0-based line and column of this error, as rendered in its
+    /// `Display` implementation, if `set_text` has been called.
+    pub fn line_col(&self) -> Option<(usize, usize)> {
+        self.inner.text.as_ref().map(|t| (t.line, t.col))
+    }
+
     /// Returns the underlying message, if any, that describes this error.
     pub fn message(&self) -> String {
         match &self.inner.kind {
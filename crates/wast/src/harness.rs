@@ -0,0 +1,693 @@
+//! A runner for `*.wast` conformance tests.
+//!
+//! Runtimes which execute the spec test suite all need the same loop around
+//! this crate's parser: read each `*.wast` file, encode its modules, invoke
+//! functions, and compare the results against the expected ones. This module
+//! provides that loop on top of a user-supplied [`Engine`], and reports the
+//! results in the JUnit XML or TAP formats understood by CI systems.
+//!
+//! Each directive of a script is reported as a test case, and each script as
+//! a test suite. Directives which the harness doesn't know how to run, such as
+//! threads and component model values, are reported as skipped.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use wast::harness::{Engine, Harness, Val};
+//!
+//! #[derive(Default)]
+//! struct MyEngine {
+//!     // ...
+//! }
+//!
+//! impl Engine for MyEngine {
+//!     fn instantiate(&mut self, name: Option<&str>, wasm: &[u8]) -> Result<(), String> {
+//!         // ...
+//! #       unimplemented!()
+//!     }
+//!
+//!     fn validate(&mut self, wasm: &[u8]) -> Result<(), String> {
+//!         // ...
+//! #       unimplemented!()
+//!     }
+//!
+//!     fn register(&mut self, as_name: &str, module: Option<&str>) -> Result<(), String> {
+//!         // ...
+//! #       unimplemented!()
+//!     }
+//!
+//!     fn invoke(
+//!         &mut self,
+//!         module: Option<&str>,
+//!         name: &str,
+//!         args: &[Val],
+//!     ) -> Result<Vec<Val>, String> {
+//!         // ...
+//! #       unimplemented!()
+//!     }
+//!
+//!     fn get(&mut self, module: Option<&str>, name: &str) -> Result<Val, String> {
+//!         // ...
+//! #       unimplemented!()
+//!     }
+//! }
+//!
+//! let report = Harness::new().run_dir("tests/spec", MyEngine::default).unwrap();
+//! std::fs::write("results.xml", report.to_junit()).unwrap();
+//! assert_eq!(report.failures(), 0);
+//! ```
+
+use crate::core::{HeapType, NanComparison, WastArgCore, WastRetCore};
+use crate::parser::{self, Features, ParseBuffer};
+use crate::{QuoteWat, Wast, WastArg, WastDirective, WastExecute, WastInvoke, WastRet, Wat};
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A WebAssembly runtime driven by a [`Harness`].
+///
+/// A fresh engine is used for each script, and errors are reported as
+/// strings which are compared against the messages expected by assertions
+/// such as `assert_trap`.
+pub trait Engine {
+    /// Instantiates the binary module or component `wasm`, which becomes the
+    /// current module for directives which don't name one. If `name` is
+    /// given the instance is also available under it to later directives.
+    fn instantiate(&mut self, name: Option<&str>, wasm: &[u8]) -> Result<(), String>;
+
+    /// Validates the binary module or component `wasm` without instantiating
+    /// it.
+    fn validate(&mut self, wasm: &[u8]) -> Result<(), String>;
+
+    /// Makes the exports of the instance named `module`, or of the current
+    /// instance, available to import under the module name `as_name`.
+    fn register(&mut self, as_name: &str, module: Option<&str>) -> Result<(), String>;
+
+    /// Invokes the function exported as `name` from the instance named
+    /// `module`, or from the current instance, returning its results or the
+    /// trap it raised.
+    fn invoke(&mut self, module: Option<&str>, name: &str, args: &[Val])
+        -> Result<Vec<Val>, String>;
+
+    /// Returns the value of the global exported as `name` from the instance
+    /// named `module`, or from the current instance.
+    fn get(&mut self, module: Option<&str>, name: &str) -> Result<Val, String>;
+}
+
+// Forwarding impls so that engines chosen at runtime, such as a
+// `Box<dyn Engine>`, can be passed to the harness.
+macro_rules! forward_engine {
+    ($($ty:ty)*) => ($(
+        impl<E: Engine + ?Sized> Engine for $ty {
+            fn instantiate(&mut self, name: Option<&str>, wasm: &[u8]) -> Result<(), String> {
+                (**self).instantiate(name, wasm)
+            }
+
+            fn validate(&mut self, wasm: &[u8]) -> Result<(), String> {
+                (**self).validate(wasm)
+            }
+
+            fn register(&mut self, as_name: &str, module: Option<&str>) -> Result<(), String> {
+                (**self).register(as_name, module)
+            }
+
+            fn invoke(
+                &mut self,
+                module: Option<&str>,
+                name: &str,
+                args: &[Val],
+            ) -> Result<Vec<Val>, String> {
+                (**self).invoke(module, name, args)
+            }
+
+            fn get(&mut self, module: Option<&str>, name: &str) -> Result<Val, String> {
+                (**self).get(module, name)
+            }
+        }
+    )*)
+}
+
+forward_engine!(Box<E> &mut E);
+
+/// A core wasm value passed to or returned from an [`Engine`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Val {
+    /// An `i32` value.
+    I32(i32),
+    /// An `i64` value.
+    I64(i64),
+    /// An `f32` value, given as its raw bits.
+    F32(u32),
+    /// An `f64` value, given as its raw bits.
+    F64(u64),
+    /// A `v128` value, with lane 0 in the least significant bits.
+    V128(u128),
+    /// A `funcref` value, which is `false` if it's null.
+    FuncRef(bool),
+    /// An `externref` value, which is either null or the host value created
+    /// by `ref.extern N` in a script.
+    ExternRef(Option<u32>),
+}
+
+/// Runs `*.wast` scripts against an [`Engine`].
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Clone)]
+pub struct Harness {
+    nan_comparison: NanComparison,
+    check_messages: bool,
+    features: Features,
+}
+
+impl Default for Harness {
+    fn default() -> Harness {
+        Harness::new()
+    }
+}
+
+impl Harness {
+    /// Creates a new harness with the default configuration.
+    pub fn new() -> Harness {
+        Harness {
+            nan_comparison: NanComparison::default(),
+            check_messages: true,
+            features: Features::all(),
+        }
+    }
+
+    /// Configures how NaN patterns in `assert_return` are compared against
+    /// results.
+    pub fn nan_comparison(&mut self, cmp: NanComparison) -> &mut Self {
+        self.nan_comparison = cmp;
+        self
+    }
+
+    /// Configures whether errors returned by the engine must contain the
+    /// message expected by the script, such as the message of `assert_trap`.
+    ///
+    /// Engines rarely use the exact wording of the reference interpreter, so
+    /// this can be disabled to only check that an error happened. This is
+    /// enabled by default.
+    pub fn check_messages(&mut self, check: bool) -> &mut Self {
+        self.check_messages = check;
+        self
+    }
+
+    /// Configures the WebAssembly proposals that scripts may use, which is
+    /// forwarded to [`ParseBuffer::features`].
+    pub fn features(&mut self, features: Features) -> &mut Self {
+        self.features = features;
+        self
+    }
+
+    /// Runs every `*.wast` file found recursively within `dir`, creating a
+    /// new engine for each with `new_engine`.
+    ///
+    /// Scripts are run in order of their paths and each becomes a suite of
+    /// the returned report, named by its path relative to `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `dir` or any of the scripts can't be read.
+    pub fn run_dir<E: Engine>(
+        &self,
+        dir: impl AsRef<Path>,
+        mut new_engine: impl FnMut() -> E,
+    ) -> io::Result<Report> {
+        let dir = dir.as_ref();
+        let mut paths = Vec::new();
+        find_scripts(dir, &mut paths)?;
+        paths.sort();
+
+        let mut report = Report::default();
+        for path in paths {
+            let contents = fs::read_to_string(&path)?;
+            let name = path.strip_prefix(dir).unwrap_or(&path);
+            let mut engine = new_engine();
+            report
+                .suites
+                .push(self.run_script(&name.display().to_string(), &contents, &mut engine));
+        }
+        Ok(report)
+    }
+
+    /// Runs the script `contents`, named `name` in the returned suite,
+    /// against `engine`.
+    ///
+    /// A script which fails to parse is reported as a single failed test
+    /// case.
+    pub fn run_script(&self, name: &str, contents: &str, engine: &mut impl Engine) -> Suite {
+        let mut suite = Suite {
+            name: name.to_string(),
+            cases: Vec::new(),
+        };
+        let mut buf = match ParseBuffer::new(contents) {
+            Ok(buf) => buf,
+            Err(e) => {
+                suite.cases.push(parse_failure(e, name, contents));
+                return suite;
+            }
+        };
+        buf.features(self.features);
+        let wast = match parser::parse::<Wast>(&buf) {
+            Ok(wast) => wast,
+            Err(e) => {
+                suite.cases.push(parse_failure(e, name, contents));
+                return suite;
+            }
+        };
+        for mut directive in wast.directives {
+            let (line, col) = directive.span().linecol_in(contents);
+            let name = format!("{}:{} {}", line + 1, col + 1, describe(&directive));
+            let outcome = self.run_directive(&mut directive, engine);
+            suite.cases.push(Case { name, outcome });
+        }
+        suite
+    }
+
+    fn run_directive(&self, directive: &mut WastDirective<'_>, engine: &mut impl Engine) -> Outcome {
+        match directive {
+            WastDirective::Wat(module) => {
+                let name = quote_wat_id(module);
+                match module.encode() {
+                    Ok(wasm) => outcome(engine.instantiate(name, &wasm)),
+                    Err(e) => Outcome::Fail(format!("failed to encode: {}", e)),
+                }
+            }
+            WastDirective::Register { name, module, .. } => {
+                outcome(engine.register(name, module.map(|id| id.name())))
+            }
+            WastDirective::Invoke(invoke) => match self.invoke(invoke, engine) {
+                Ok(result) => outcome(result.map(drop)),
+                Err(skip) => skip,
+            },
+            WastDirective::AssertReturn { exec, results, .. } => {
+                let actual = match self.execute(exec, engine) {
+                    Ok(Ok(actual)) => actual,
+                    Ok(Err(e)) => return Outcome::Fail(format!("unexpected error: {}", e)),
+                    Err(skip) => return skip,
+                };
+                self.check_results(results, &actual)
+            }
+            WastDirective::AssertTrap { exec, message, .. } => match self.execute(exec, engine) {
+                Ok(result) => self.expect_error(result, message),
+                Err(skip) => skip,
+            },
+            WastDirective::AssertExhaustion { call, message, .. } => {
+                match self.invoke(call, engine) {
+                    Ok(result) => self.expect_error(result, message),
+                    Err(skip) => skip,
+                }
+            }
+            WastDirective::AssertException { exec, .. } => match self.execute(exec, engine) {
+                Ok(result) => self.expect_error(result, ""),
+                Err(skip) => skip,
+            },
+            WastDirective::AssertInvalid {
+                module, message, ..
+            } => match module.encode() {
+                Ok(wasm) => self.expect_error(engine.validate(&wasm), message),
+                Err(e) => Outcome::Fail(format!("failed to encode: {}", e)),
+            },
+            WastDirective::AssertMalformed {
+                module, message, ..
+            } => match module.encode() {
+                // Text modules which are malformed fail to parse along with
+                // the rest of the script, so only quoted modules can fail to
+                // encode here, and binary ones are left to the engine.
+                Ok(wasm) => self.expect_error(engine.validate(&wasm), message),
+                Err(_) => Outcome::Pass,
+            },
+            WastDirective::AssertUnlinkable {
+                module, message, ..
+            } => match module.encode() {
+                Ok(wasm) => self.expect_error(engine.instantiate(None, &wasm), message),
+                Err(e) => Outcome::Fail(format!("failed to encode: {}", e)),
+            },
+            WastDirective::ModuleDefinition(_)
+            | WastDirective::ModuleInstance { .. }
+            | WastDirective::Thread(_)
+            | WastDirective::Wait { .. } => {
+                Outcome::Skip(format!("`{}` is not supported", describe(directive)))
+            }
+        }
+    }
+
+    /// Runs `exec`, returning `Err` with a skipped outcome if it can't be
+    /// run by this harness.
+    fn execute(
+        &self,
+        exec: &mut WastExecute<'_>,
+        engine: &mut impl Engine,
+    ) -> Result<Result<Vec<Val>, String>, Outcome> {
+        match exec {
+            WastExecute::Invoke(invoke) => self.invoke(invoke, engine),
+            WastExecute::Wat(module) => {
+                let name = match module {
+                    Wat::Module(m) => m.id.map(|id| id.name()),
+                    Wat::Component(c) => c.id.map(|id| id.name()),
+                };
+                Ok(match module.encode() {
+                    Ok(wasm) => engine.instantiate(name, &wasm).map(|()| Vec::new()),
+                    Err(e) => Err(format!("failed to encode: {}", e)),
+                })
+            }
+            WastExecute::Get { module, global } => Ok(engine
+                .get(module.map(|id| id.name()), global)
+                .map(|val| vec![val])),
+        }
+    }
+
+    fn invoke(
+        &self,
+        invoke: &WastInvoke<'_>,
+        engine: &mut impl Engine,
+    ) -> Result<Result<Vec<Val>, String>, Outcome> {
+        let args = invoke
+            .args
+            .iter()
+            .map(|arg| match arg {
+                WastArg::Core(arg) => core_arg(arg),
+                WastArg::Component(_) => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| Outcome::Skip("unsupported argument".to_string()))?;
+        Ok(engine.invoke(invoke.module.map(|id| id.name()), invoke.name, &args))
+    }
+
+    fn check_results(&self, expected: &[WastRet<'_>], actual: &[Val]) -> Outcome {
+        if expected.len() != actual.len() {
+            return Outcome::Fail(format!(
+                "expected {} results, got {}",
+                expected.len(),
+                actual.len()
+            ));
+        }
+        for (i, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+            let expected = match expected {
+                WastRet::Core(ret) => ret,
+                WastRet::Component(_) => {
+                    return Outcome::Skip("unsupported result".to_string());
+                }
+            };
+            if !self.matches(expected, actual) {
+                return Outcome::Fail(format!(
+                    "result {} mismatch: expected {:?}, got {:?}",
+                    i, expected, actual
+                ));
+            }
+        }
+        Outcome::Pass
+    }
+
+    fn matches(&self, expected: &WastRetCore<'_>, actual: &Val) -> bool {
+        let cmp = self.nan_comparison;
+        match (expected, actual) {
+            (WastRetCore::I32(e), Val::I32(a)) => e == a,
+            (WastRetCore::I64(e), Val::I64(a)) => e == a,
+            (WastRetCore::F32(e), Val::F32(a)) => e.matches(*a, cmp),
+            (WastRetCore::F64(e), Val::F64(a)) => e.matches(*a, cmp),
+            (WastRetCore::V128(e), Val::V128(a)) => e.matches(*a, cmp),
+            (WastRetCore::RefNull(ty), Val::FuncRef(false)) => {
+                matches!(ty, None | Some(HeapType::Func))
+            }
+            (WastRetCore::RefNull(ty), Val::ExternRef(None)) => {
+                matches!(ty, None | Some(HeapType::Extern))
+            }
+            (WastRetCore::RefExtern(e), Val::ExternRef(Some(a))) => e == a,
+            (WastRetCore::RefFunc(_), Val::FuncRef(true)) => true,
+            (WastRetCore::Either(cases), _) => cases.iter().any(|e| self.matches(e, actual)),
+            _ => false,
+        }
+    }
+
+    fn expect_error<T>(&self, result: Result<T, String>, message: &str) -> Outcome {
+        match result {
+            Ok(_) => Outcome::Fail(format!("expected an error containing `{}`", message)),
+            Err(e) if !self.check_messages || e.contains(message) => Outcome::Pass,
+            Err(e) => Outcome::Fail(format!(
+                "expected an error containing `{}`, got: {}",
+                message, e
+            )),
+        }
+    }
+}
+
+fn find_scripts(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_scripts(&path, paths)?;
+        } else if path.extension().and_then(|s| s.to_str()) == Some("wast") {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn parse_failure(mut e: crate::Error, name: &str, contents: &str) -> Case {
+    e.set_path(Path::new(name));
+    e.set_text(contents);
+    Case {
+        name: "parse".to_string(),
+        outcome: Outcome::Fail(e.to_string()),
+    }
+}
+
+fn outcome(result: Result<(), String>) -> Outcome {
+    match result {
+        Ok(()) => Outcome::Pass,
+        Err(e) => Outcome::Fail(e),
+    }
+}
+
+fn quote_wat_id<'a>(module: &QuoteWat<'a>) -> Option<&'a str> {
+    match module {
+        QuoteWat::Wat(Wat::Module(m)) => m.id.map(|id| id.name()),
+        QuoteWat::Wat(Wat::Component(c)) => c.id.map(|id| id.name()),
+        QuoteWat::QuoteModule(..) | QuoteWat::QuoteComponent(..) => None,
+    }
+}
+
+fn core_arg(arg: &WastArgCore<'_>) -> Option<Val> {
+    Some(match arg {
+        WastArgCore::I32(i) => Val::I32(*i),
+        WastArgCore::I64(i) => Val::I64(*i),
+        WastArgCore::F32(f) => Val::F32(f.bits),
+        WastArgCore::F64(f) => Val::F64(f.bits),
+        WastArgCore::V128(v) => Val::V128(u128::from_le_bytes(v.to_le_bytes())),
+        WastArgCore::RefNull(HeapType::Func) => Val::FuncRef(false),
+        WastArgCore::RefNull(HeapType::Extern) => Val::ExternRef(None),
+        WastArgCore::RefNull(_) => return None,
+        WastArgCore::RefExtern(i) => Val::ExternRef(Some(*i)),
+    })
+}
+
+/// Returns a short description of `directive` to name its test case.
+fn describe(directive: &WastDirective<'_>) -> String {
+    let invoke = |kind: &str, exec: &WastExecute<'_>| match exec {
+        WastExecute::Invoke(i) => format!("{} \"{}\"", kind, i.name),
+        WastExecute::Get { global, .. } => format!("{} \"{}\"", kind, global),
+        WastExecute::Wat(_) => kind.to_string(),
+    };
+    match directive {
+        WastDirective::Wat(_) => "module".to_string(),
+        WastDirective::ModuleDefinition(_) => "module definition".to_string(),
+        WastDirective::ModuleInstance { .. } => "module instance".to_string(),
+        WastDirective::AssertMalformed { .. } => "assert_malformed".to_string(),
+        WastDirective::AssertInvalid { .. } => "assert_invalid".to_string(),
+        WastDirective::Register { name, .. } => format!("register \"{}\"", name),
+        WastDirective::Invoke(i) => format!("invoke \"{}\"", i.name),
+        WastDirective::AssertTrap { exec, .. } => invoke("assert_trap", exec),
+        WastDirective::AssertReturn { exec, .. } => invoke("assert_return", exec),
+        WastDirective::AssertExhaustion { call, .. } => {
+            format!("assert_exhaustion \"{}\"", call.name)
+        }
+        WastDirective::AssertUnlinkable { .. } => "assert_unlinkable".to_string(),
+        WastDirective::AssertException { exec, .. } => invoke("assert_exception", exec),
+        WastDirective::Thread(_) => "thread".to_string(),
+        WastDirective::Wait { .. } => "wait".to_string(),
+    }
+}
+
+/// The results of running scripts with a [`Harness`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// The results of each script.
+    pub suites: Vec<Suite>,
+}
+
+/// The results of running a single script with a [`Harness`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suite {
+    /// The name of the script, typically its path.
+    pub name: String,
+    /// The result of each directive of the script.
+    pub cases: Vec<Case>,
+}
+
+/// The result of a single directive of a script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    /// The line and column of the directive along with a description of it,
+    /// such as `12:2 assert_return "add"`.
+    pub name: String,
+    /// What happened when the directive was run.
+    pub outcome: Outcome,
+}
+
+/// What happened when a directive was run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The directive succeeded.
+    Pass,
+    /// The directive failed, with a message describing why.
+    Fail(String),
+    /// The directive wasn't run, with a message describing why.
+    Skip(String),
+}
+
+impl Suite {
+    fn count(&self, f: impl Fn(&Outcome) -> bool) -> usize {
+        self.cases.iter().filter(|c| f(&c.outcome)).count()
+    }
+}
+
+impl Report {
+    /// Returns the total number of test cases in this report.
+    pub fn tests(&self) -> usize {
+        self.suites.iter().map(|s| s.cases.len()).sum()
+    }
+
+    /// Returns the number of failed test cases in this report.
+    pub fn failures(&self) -> usize {
+        self.suites
+            .iter()
+            .map(|s| s.count(|o| matches!(o, Outcome::Fail(_))))
+            .sum()
+    }
+
+    /// Returns the number of skipped test cases in this report.
+    pub fn skipped(&self) -> usize {
+        self.suites
+            .iter()
+            .map(|s| s.count(|o| matches!(o, Outcome::Skip(_))))
+            .sum()
+    }
+
+    /// Renders this report as a JUnit XML document.
+    pub fn to_junit(&self) -> String {
+        let mut dst = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        writeln!(
+            dst,
+            "<testsuites tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+            self.tests(),
+            self.failures(),
+            self.skipped()
+        )
+        .unwrap();
+        for suite in self.suites.iter() {
+            writeln!(
+                dst,
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+                xml_escape(&suite.name),
+                suite.cases.len(),
+                suite.count(|o| matches!(o, Outcome::Fail(_))),
+                suite.count(|o| matches!(o, Outcome::Skip(_))),
+            )
+            .unwrap();
+            for case in suite.cases.iter() {
+                write!(
+                    dst,
+                    "    <testcase classname=\"{}\" name=\"{}\"",
+                    xml_escape(&suite.name),
+                    xml_escape(&case.name)
+                )
+                .unwrap();
+                match &case.outcome {
+                    Outcome::Pass => dst.push_str("/>\n"),
+                    Outcome::Fail(msg) => writeln!(
+                        dst,
+                        ">\n      <failure message=\"{}\"/>\n    </testcase>",
+                        xml_escape(msg)
+                    )
+                    .unwrap(),
+                    Outcome::Skip(msg) => writeln!(
+                        dst,
+                        ">\n      <skipped message=\"{}\"/>\n    </testcase>",
+                        xml_escape(msg)
+                    )
+                    .unwrap(),
+                }
+            }
+            dst.push_str("  </testsuite>\n");
+        }
+        dst.push_str("</testsuites>\n");
+        dst
+    }
+
+    /// Renders this report in the [Test Anything Protocol][tap], version 13.
+    ///
+    /// [tap]: https://testanything.org/tap-version-13-specification.html
+    pub fn to_tap(&self) -> String {
+        let mut dst = String::from("TAP version 13\n");
+        writeln!(dst, "1..{}", self.tests()).unwrap();
+        let cases = self
+            .suites
+            .iter()
+            .flat_map(|s| s.cases.iter().map(move |c| (s, c)));
+        for (i, (suite, case)) in cases.enumerate() {
+            // `#` starts a directive in TAP so it can't be in descriptions.
+            let description = format!("{} {}", suite.name, case.name).replace('#', "\\#");
+            match &case.outcome {
+                Outcome::Pass => writeln!(dst, "ok {} - {}", i + 1, description).unwrap(),
+                Outcome::Fail(msg) => {
+                    writeln!(dst, "not ok {} - {}", i + 1, description).unwrap();
+                    dst.push_str("  ---\n  message: ");
+                    yaml_string(&mut dst, msg);
+                    dst.push_str("\n  ...\n");
+                }
+                Outcome::Skip(msg) => writeln!(
+                    dst,
+                    "ok {} - {} # SKIP {}",
+                    i + 1,
+                    description,
+                    msg.replace('\n', " ")
+                )
+                .unwrap(),
+            }
+        }
+        dst
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => ret.push_str("&amp;"),
+            '<' => ret.push_str("&lt;"),
+            '>' => ret.push_str("&gt;"),
+            '"' => ret.push_str("&quot;"),
+            '\n' => ret.push_str("&#10;"),
+            c if (c as u32) < 0x20 && c != '\t' => {}
+            c => ret.push(c),
+        }
+    }
+    ret
+}
+
+fn yaml_string(dst: &mut String, s: &str) {
+    dst.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => dst.push_str("\\\""),
+            '\\' => dst.push_str("\\\\"),
+            '\n' => dst.push_str("\\n"),
+            c if (c as u32) < 0x20 => write!(dst, "\\x{:02x}", c as u32).unwrap(),
+            c => dst.push(c),
+        }
+    }
+    dst.push('"');
+}
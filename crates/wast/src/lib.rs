@@ -200,7 +200,9 @@ macro_rules! custom_reserved {
 /// Note that when you're parsing custom annotations it can be somewhat tricky
 /// due to the nature that most of them are skipped. You'll want to be sure to
 /// consult the documentation of [`Parser::register_annotation`][register] when
-/// using this macro.
+/// using this macro. Alternatively, annotations which are never registered
+/// can be inspected once parsing has finished with
+/// [`ParseBuffer::unknown_annotations`][unknown].
 ///
 /// # Examples
 ///
@@ -302,6 +304,7 @@ macro_rules! custom_reserved {
 /// ```
 ///
 /// [register]: crate::parser::Parser::register_annotation
+/// [unknown]: crate::parser::ParseBuffer::unknown_annotations
 /// [section]: https://github.com/WebAssembly/tool-conventions/blob/master/ProducersSection.md
 #[macro_export]
 macro_rules! annotation {
@@ -369,6 +372,8 @@ id! {
 
     // Support for component model parsing
     pub mod component;
+
+    pub mod harness;
 }
 
 /// Common keyword used to parse WebAssembly text files.
@@ -398,12 +403,14 @@ pub mod kw {
     custom_keyword!(data);
     custom_keyword!(dataref);
     custom_keyword!(declare);
+    custom_keyword!(definition);
     custom_keyword!(delegate);
     custom_keyword!(r#do = "do");
     custom_keyword!(elem);
     custom_keyword!(end);
     custom_keyword!(tag);
     custom_keyword!(export);
+    custom_keyword!(export_info = "export-info");
     custom_keyword!(r#extern = "extern");
     custom_keyword!(externref);
     custom_keyword!(eq);
@@ -429,6 +436,7 @@ pub mod kw {
     custom_keyword!(i8);
     custom_keyword!(i8x16);
     custom_keyword!(import);
+    custom_keyword!(import_info = "import-info");
     custom_keyword!(instance);
     custom_keyword!(instantiate);
     custom_keyword!(invoke);
@@ -436,10 +444,12 @@ pub mod kw {
     custom_keyword!(last);
     custom_keyword!(local);
     custom_keyword!(memory);
+    custom_keyword!(mem_info = "mem-info");
     custom_keyword!(module);
     custom_keyword!(modulecode);
     custom_keyword!(nan_arithmetic = "nan:arithmetic");
     custom_keyword!(nan_canonical = "nan:canonical");
+    custom_keyword!(needed);
     custom_keyword!(null);
     custom_keyword!(nullref);
     custom_keyword!(offset);
@@ -465,9 +475,11 @@ pub mod kw {
     custom_keyword!(sub);
     custom_keyword!(table);
     custom_keyword!(then);
+    custom_keyword!(thread);
     custom_keyword!(r#try = "try");
     custom_keyword!(v128);
     custom_keyword!(value);
+    custom_keyword!(wait);
     custom_keyword!(s8);
     custom_keyword!(s16);
     custom_keyword!(s32);
@@ -500,6 +512,19 @@ pub mod kw {
     custom_keyword!(string_latin1_utf16 = "string-encoding=latin1+utf16");
     custom_keyword!(realloc);
     custom_keyword!(post_return = "post-return");
+    custom_keyword!(r#async = "async");
+    custom_keyword!(callback);
+    custom_keyword!(task_return = "task.return");
+    custom_keyword!(task_wait = "task.wait");
+    custom_keyword!(task_poll = "task.poll");
+    custom_keyword!(task_yield = "task.yield");
+    custom_keyword!(stream_new = "stream.new");
+    custom_keyword!(stream_receive = "stream.receive");
+    custom_keyword!(stream_send = "stream.send");
+    custom_keyword!(future_new = "future.new");
+    custom_keyword!(future_receive = "future.receive");
+    custom_keyword!(future_send = "future.send");
+    custom_keyword!(error_drop = "error.drop");
     custom_keyword!(with);
     custom_keyword!(core);
     custom_keyword!(true_ = "true");
@@ -510,4 +535,6 @@ pub mod kw {
 pub mod annotation {
     annotation!(custom);
     annotation!(name);
+    annotation!(metadata_code_branch_hint = "metadata.code.branch_hint");
+    annotation!(dylink_0 = "dylink.0");
 }
@@ -68,7 +68,7 @@ use crate::lexer::{Float, Integer, Lexer, Token};
 use crate::token::Span;
 use crate::Error;
 use std::cell::{Cell, RefCell};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt;
 use std::usize;
 
@@ -127,6 +127,47 @@ pub fn parse<'a, T: Parse<'a>>(buf: &'a ParseBuffer<'a>) -> Result<T> {
     }
 }
 
+/// Same as [`parse`], except that syntax errors are recovered from where
+/// possible so that all of them can be reported at once.
+///
+/// When an item fails to parse at a point where the parser supports recovery
+/// (such as a module field, a component field, or a `*.wast` directive) the
+/// error is recorded, the remainder of the failing s-expression is skipped,
+/// and parsing resumes at the next s-expression. This is useful for tools,
+/// such as editors, which want to report more than one error per run.
+///
+/// Returns `T` if no errors happened at all, or otherwise all errors in the
+/// order they were encountered. Note that lexing errors are reported when the
+/// [`ParseBuffer`] is created and aren't recoverable.
+///
+/// # Examples
+///
+/// ```
+/// use wast::Wat;
+/// use wast::parser::{self, ParseBuffer};
+///
+/// # fn foo() -> Result<(), wast::Error> {
+/// let wat = "(module (func (foo)) (memory) (global i32 (i32.const x)))";
+/// let buf = ParseBuffer::new(wat)?;
+/// let errors = parser::parse_recovering::<Wat>(&buf).unwrap_err();
+/// assert_eq!(errors.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_recovering<'a, T: Parse<'a>>(buf: &'a ParseBuffer<'a>) -> Result<T, Vec<Error>> {
+    *buf.recovered.borrow_mut() = Some(Vec::new());
+    let result = parse(buf);
+    let mut errors = buf.recovered.borrow_mut().take().unwrap_or_default();
+    match result {
+        Ok(result) if errors.is_empty() => Ok(result),
+        Ok(_) => Err(errors),
+        Err(e) => {
+            errors.push(e);
+            Err(errors)
+        }
+    }
+}
+
 /// A trait for parsing a fragment of syntax in a recursive descent fashion.
 ///
 /// The [`Parse`] trait is main abstraction you'll be working with when defining
@@ -299,8 +340,31 @@ pub struct ParseBuffer<'a> {
     cur: Cell<usize>,
     known_annotations: RefCell<HashMap<String, usize>>,
     depth: Cell<usize>,
+    // Errors recovered from so far, if recovery is enabled. See
+    // `parse_recovering`.
+    recovered: RefCell<Option<Vec<Error>>>,
+    track_instr_spans: bool,
+    features: Features,
+    // Indices of the `(` token of each annotation that has been skipped
+    // because it wasn't registered at the time.
+    unknown_annotations: RefCell<BTreeSet<usize>>,
 }
 
+/// The set of WebAssembly proposals which may be used in parsed text.
+///
+/// This is the same type as `wasmparser::WasmFeatures`, so that test harnesses
+/// can configure both the text parser and the binary validator from the same
+/// settings. Syntax from a disabled proposal fails to parse with a "feature
+/// `name` not enabled" error.
+///
+/// Unlike `wasmparser`, parsing enables every proposal this crate knows how to
+/// parse unless configured otherwise. Gating is done on syntax alone, so some
+/// uses of a proposal which can only be detected by validation, such as block
+/// types with parameters, are left for the validator to reject.
+///
+/// Configured with [`ParseBuffer::features`].
+pub use wasm_features::WasmFeatures as Features;
+
 #[derive(Copy, Clone, Debug)]
 enum NextTokenAt {
     /// Haven't computed where the next token is yet.
@@ -311,6 +375,26 @@ enum NextTokenAt {
     Eof,
 }
 
+/// An annotation which was skipped while parsing because it wasn't registered.
+///
+/// Returned by [`ParseBuffer::unknown_annotations`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownAnnotation<'a> {
+    /// The location of the `(` starting this annotation.
+    pub span: Span,
+    /// The name of this annotation, without the leading `@`.
+    pub name: &'a str,
+    /// The full source text of this annotation, including the surrounding
+    /// parentheses.
+    pub source: &'a str,
+    /// The source text between the name of this annotation and its closing
+    /// `)`, with surrounding whitespace removed.
+    ///
+    /// This can be parsed with a new [`ParseBuffer`] to interpret the
+    /// payload of the annotation.
+    pub contents: &'a str,
+}
+
 /// An in-progress parser for the tokens of a WebAssembly text file.
 ///
 /// A `Parser` is argument to the [`Parse`] trait and is now the input stream is
@@ -371,11 +455,103 @@ impl ParseBuffer<'_> {
             depth: Cell::new(0),
             input,
             known_annotations: Default::default(),
+            recovered: Default::default(),
+            track_instr_spans: false,
+            features: Features::all(),
+            unknown_annotations: Default::default(),
         };
         ret.validate_annotations()?;
         Ok(ret)
     }
 
+    /// Configures whether the span of each instruction is recorded while
+    /// parsing.
+    ///
+    /// When enabled the [`instr_spans`](crate::core::Expression::instr_spans)
+    /// field of each parsed expression is filled in, which can later be used
+    /// to map instructions in the encoded binary back to the original text.
+    /// This is disabled by default.
+    pub fn track_instr_spans(&mut self, track: bool) -> &mut Self {
+        self.track_instr_spans = track;
+        self
+    }
+
+    /// Configures which WebAssembly proposals may be used in the parsed text.
+    ///
+    /// Parsing syntax which belongs to a disabled proposal returns an error.
+    /// By default every proposal is enabled, as with [`Features::all`], see
+    /// [`Features`] for more information.
+    pub fn features(&mut self, features: Features) -> &mut Self {
+        self.features = features;
+        self
+    }
+
+    /// Returns all annotations which were skipped while parsing because they
+    /// weren't registered with [`Parser::register_annotation`], in the order
+    /// they appear in the input.
+    ///
+    /// This is intended to be called after parsing has finished, and allows
+    /// tools to define their own annotations on top of the text format
+    /// without having to write a custom parser. Each annotation has a span
+    /// which can be compared against the spans of parsed items to determine
+    /// what the annotation is attached to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wast::Wat;
+    /// use wast::parser::{self, ParseBuffer};
+    ///
+    /// # fn foo() -> Result<(), wast::Error> {
+    /// let wat = r#"(module (func (@weak) (export "f")))"#;
+    /// let buf = ParseBuffer::new(wat)?;
+    /// let module = parser::parse::<Wat>(&buf)?;
+    ///
+    /// let annotations = buf.unknown_annotations();
+    /// assert_eq!(annotations.len(), 1);
+    /// assert_eq!(annotations[0].name, "weak");
+    /// assert_eq!(annotations[0].span.offset(), 14);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn unknown_annotations(&self) -> Vec<UnknownAnnotation<'_>> {
+        use crate::lexer::Token::*;
+        self.unknown_annotations
+            .borrow()
+            .iter()
+            .filter_map(|&start| {
+                let open = match &self.tokens[start].0 {
+                    LParen(s) => s,
+                    _ => return None,
+                };
+                let name = match &self.tokens.get(start + 1)?.0 {
+                    Reserved(s) => s,
+                    _ => return None,
+                };
+                let mut depth = 1;
+                let mut end = start + 2;
+                let close = loop {
+                    match &self.tokens.get(end)?.0 {
+                        LParen(_) => depth += 1,
+                        RParen(s) if depth == 1 => break s,
+                        RParen(_) => depth -= 1,
+                        _ => {}
+                    }
+                    end += 1;
+                };
+                let open = self.input_pos(open);
+                let name_end = self.input_pos(name) + name.len();
+                let close = self.input_pos(close);
+                Some(UnknownAnnotation {
+                    span: Span { offset: open },
+                    name: &name[1..],
+                    source: &self.input[open..close + 1],
+                    contents: self.input[name_end..close].trim(),
+                })
+            })
+            .collect()
+    }
+
     fn parser(&self) -> Parser<'_> {
         Parser { buf: self }
     }
@@ -717,6 +893,58 @@ impl<'a> Parser<'a> {
         res
     }
 
+    /// Same as [`Parser::parens`], except that errors are recovered from if
+    /// the top-level parse was started with [`parse_recovering`].
+    ///
+    /// When recovering from an error it's recorded and the parser is advanced
+    /// past the s-expression that failed to parse, and `Ok(None)` is returned.
+    /// If recovery isn't enabled, or the rest of the input is too malformed
+    /// to find the end of the s-expression, then the error is returned as-is.
+    pub fn parens_recovering<T>(
+        self,
+        f: impl FnOnce(Parser<'a>) -> Result<T>,
+    ) -> Result<Option<T>> {
+        let err = match self.parens(f) {
+            Ok(result) => return Ok(Some(result)),
+            Err(e) => e,
+        };
+        if self.buf.recovered.borrow().is_none() {
+            return Err(err);
+        }
+        match self.cursor().skip_sexpr() {
+            Some(cursor) => {
+                self.buf.cur.set(cursor.cur);
+                if let Some(errors) = self.buf.recovered.borrow_mut().as_mut() {
+                    errors.push(err);
+                }
+                Ok(None)
+            }
+            None => Err(err),
+        }
+    }
+
+    /// Returns whether instruction spans should be recorded, as configured by
+    /// [`ParseBuffer::track_instr_spans`].
+    pub(crate) fn track_instr_spans(&self) -> bool {
+        self.buf.track_instr_spans
+    }
+
+    /// Returns an error at `span` if the proposal named `name` is disabled,
+    /// as determined by `enabled`, in the [`Features`] configured with
+    /// [`ParseBuffer::features`].
+    pub(crate) fn check_feature(
+        self,
+        span: Span,
+        name: &str,
+        enabled: fn(&Features) -> bool,
+    ) -> Result<()> {
+        if enabled(&self.buf.features) {
+            Ok(())
+        } else {
+            Err(self.error_at(span, &format_args!("feature `{}` not enabled", name)))
+        }
+    }
+
     /// Return the depth of nested parens we've parsed so far.
     ///
     /// This is a low-level method that is only useful for implementing
@@ -766,7 +994,7 @@ impl<'a> Parser<'a> {
         self.error_at(self.cursor().cur_span(), &msg)
     }
 
-    fn error_at(self, span: Span, msg: &dyn fmt::Display) -> Error {
+    pub(crate) fn error_at(self, span: Span, msg: &dyn fmt::Display) -> Error {
         Error::parse(span, self.buf.input, msg.to_string())
     }
 
@@ -1114,7 +1342,16 @@ impl<'a> Cursor<'a> {
             return None;
         }
         match &self.parser.buf.tokens.get(self.cur.wrapping_sub(1))?.0 {
-            Token::LParen(_) => Some((&token[1..], cursor)),
+            Token::LParen(_) => {
+                // This annotation may have been skipped as unknown before it
+                // was registered, but it's known now so don't report it.
+                self.parser
+                    .buf
+                    .unknown_annotations
+                    .borrow_mut()
+                    .remove(&(self.cur - 1));
+                Some((&token[1..], cursor))
+            }
             _ => None,
         }
     }
@@ -1156,7 +1393,13 @@ impl<'a> Cursor<'a> {
             match token {
                 Token::Whitespace(_) | Token::LineComment(_) | Token::BlockComment(_) => {}
                 _ => match self.annotation_start() {
-                    Some(n) if !is_known_annotation(n) => {}
+                    Some(n) if !is_known_annotation(n) => {
+                        self.parser
+                            .buf
+                            .unknown_annotations
+                            .borrow_mut()
+                            .insert(self.cur);
+                    }
                     _ => {
                         self.cur += 1;
                         return Some(token);
@@ -1197,6 +1440,32 @@ impl<'a> Cursor<'a> {
         }
     }
 
+    /// Skips the balanced s-expression at the current position, or if this
+    /// isn't pointing at a `(` then all tokens up to the next `(` or `)`.
+    ///
+    /// Returns `None` if the end of input is reached before the s-expression
+    /// is closed.
+    fn skip_sexpr(mut self) -> Option<Self> {
+        if let Some(mut cursor) = self.lparen() {
+            let mut depth = 1;
+            while depth > 0 {
+                match cursor.advance_token()? {
+                    Token::LParen(_) => depth += 1,
+                    Token::RParen(_) => depth -= 1,
+                    _ => {}
+                }
+            }
+            return Some(cursor);
+        }
+        loop {
+            let mut next = self;
+            match next.advance_token() {
+                Some(Token::LParen(_)) | Some(Token::RParen(_)) | None => return Some(self),
+                Some(_) => self = next,
+            }
+        }
+    }
+
     fn annotation_start(&self) -> Option<&'a str> {
         match self.parser.buf.tokens.get(self.cur).map(|p| &p.0) {
             Some(Token::LParen(_)) => {}
@@ -1,5 +1,5 @@
-use crate::component::WastVal;
-use crate::core::{WastArgCore, WastRetCore};
+use crate::component::{Component, WastVal};
+use crate::core::{Module, WastArgCore, WastRetCore};
 use crate::kw;
 use crate::parser::{self, Cursor, Parse, ParseBuffer, Parser, Peek, Result};
 use crate::token::{Id, Span};
@@ -24,7 +24,9 @@ impl<'a> Parse<'a> for Wast<'a> {
         // bunch of directives, otherwise assume this is an inline module.
         if parser.peek2::<WastDirectiveToken>() {
             while !parser.is_empty() {
-                directives.push(parser.parens(|p| p.parse())?);
+                if let Some(directive) = parser.parens_recovering(|p| p.parse())? {
+                    directives.push(directive);
+                }
             }
         } else {
             let module = parser.parse::<Wat>()?;
@@ -47,6 +49,8 @@ impl Peek for WastDirectiveToken {
             || kw == "component"
             || kw == "register"
             || kw == "invoke"
+            || kw == "thread"
+            || kw == "wait"
     }
 
     fn display() -> &'static str {
@@ -62,6 +66,18 @@ impl Peek for WastDirectiveToken {
 #[derive(Debug)]
 pub enum WastDirective<'a> {
     Wat(QuoteWat<'a>),
+    /// A `(module definition ...)` or `(component definition ...)` which is
+    /// only compiled, to be instantiated by later `ModuleInstance`
+    /// directives.
+    ModuleDefinition(Wat<'a>),
+    /// A `(module instance $instance $definition)` or
+    /// `(component instance $instance $definition)` which instantiates a
+    /// previous `ModuleDefinition`.
+    ModuleInstance {
+        span: Span,
+        instance: Option<Id<'a>>,
+        module: Option<Id<'a>>,
+    },
     AssertMalformed {
         span: Span,
         module: QuoteWat<'a>,
@@ -102,6 +118,11 @@ pub enum WastDirective<'a> {
         span: Span,
         exec: WastExecute<'a>,
     },
+    Thread(WastThread<'a>),
+    Wait {
+        span: Span,
+        thread: Id<'a>,
+    },
 }
 
 impl WastDirective<'_> {
@@ -112,6 +133,8 @@ impl WastDirective<'_> {
             WastDirective::Wat(QuoteWat::Wat(Wat::Component(c))) => c.span,
             WastDirective::Wat(QuoteWat::QuoteModule(span, _)) => *span,
             WastDirective::Wat(QuoteWat::QuoteComponent(span, _)) => *span,
+            WastDirective::ModuleDefinition(Wat::Module(m)) => m.span,
+            WastDirective::ModuleDefinition(Wat::Component(c)) => c.span,
             WastDirective::AssertMalformed { span, .. }
             | WastDirective::Register { span, .. }
             | WastDirective::AssertTrap { span, .. }
@@ -119,8 +142,11 @@ impl WastDirective<'_> {
             | WastDirective::AssertExhaustion { span, .. }
             | WastDirective::AssertUnlinkable { span, .. }
             | WastDirective::AssertInvalid { span, .. }
-            | WastDirective::AssertException { span, .. } => *span,
+            | WastDirective::AssertException { span, .. }
+            | WastDirective::ModuleInstance { span, .. }
+            | WastDirective::Wait { span, .. } => *span,
             WastDirective::Invoke(i) => i.span,
+            WastDirective::Thread(t) => t.span,
         }
     }
 }
@@ -129,7 +155,7 @@ impl<'a> Parse<'a> for WastDirective<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let mut l = parser.lookahead1();
         if l.peek::<kw::module>() || l.peek::<kw::component>() {
-            Ok(WastDirective::Wat(parser.parse()?))
+            parse_wast_module(parser)
         } else if l.peek::<kw::assert_malformed>() {
             let span = parser.parse::<kw::assert_malformed>()?.0;
             Ok(WastDirective::AssertMalformed {
@@ -192,12 +218,50 @@ impl<'a> Parse<'a> for WastDirective<'a> {
                 span,
                 exec: parser.parens(|p| p.parse())?,
             })
+        } else if l.peek::<kw::thread>() {
+            Ok(WastDirective::Thread(parser.parse()?))
+        } else if l.peek::<kw::wait>() {
+            let span = parser.parse::<kw::wait>()?.0;
+            Ok(WastDirective::Wait {
+                span,
+                thread: parser.parse()?,
+            })
         } else {
             Err(l.error())
         }
     }
 }
 
+fn parse_wast_module<'a>(parser: Parser<'a>) -> Result<WastDirective<'a>> {
+    let definition = parser.peek2::<kw::definition>();
+    if !definition && !parser.peek2::<kw::instance>() {
+        return Ok(WastDirective::Wat(parser.parse()?));
+    }
+
+    let (span, component) = if parser.peek::<kw::component>() {
+        (parser.parse::<kw::component>()?.0, true)
+    } else {
+        (parser.parse::<kw::module>()?.0, false)
+    };
+    if definition {
+        parser.parse::<kw::definition>()?;
+        Ok(WastDirective::ModuleDefinition(if component {
+            Wat::Component(Component::parse_without_component_keyword(
+                span, parser,
+            )?)
+        } else {
+            Wat::Module(Module::parse_without_module_keyword(span, parser)?)
+        }))
+    } else {
+        parser.parse::<kw::instance>()?;
+        Ok(WastDirective::ModuleInstance {
+            span,
+            instance: parser.parse()?,
+            module: parser.parse()?,
+        })
+    }
+}
+
 #[allow(missing_docs)]
 #[derive(Debug)]
 pub enum WastExecute<'a> {
@@ -269,6 +333,50 @@ impl<'a> Parse<'a> for WastInvoke<'a> {
     }
 }
 
+/// A `(thread ...)` directive, used by the threads proposal's tests to run a
+/// list of directives concurrently with the rest of the script.
+///
+/// The thread can only see modules declared within it, with the exception
+/// of the module optionally listed in its `(shared (module $id))` clause.
+#[allow(missing_docs)]
+#[derive(Debug)]
+pub struct WastThread<'a> {
+    pub span: Span,
+    pub name: Id<'a>,
+    pub shared_module: Option<Id<'a>>,
+    pub directives: Vec<WastDirective<'a>>,
+}
+
+impl<'a> Parse<'a> for WastThread<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        parser.depth_check()?;
+        let span = parser.parse::<kw::thread>()?.0;
+        let name = parser.parse()?;
+
+        let shared_module = if parser.peek2::<kw::shared>() {
+            parser.parens(|p| {
+                p.parse::<kw::shared>()?;
+                p.parens(|p| {
+                    p.parse::<kw::module>()?;
+                    p.parse()
+                })
+            })?
+        } else {
+            None
+        };
+        let mut directives = Vec::new();
+        while !parser.is_empty() {
+            directives.push(parser.parens(|p| p.parse())?);
+        }
+        Ok(WastThread {
+            span,
+            name,
+            shared_module,
+            directives,
+        })
+    }
+}
+
 #[allow(missing_docs)]
 #[derive(Debug)]
 pub enum QuoteWat<'a> {
@@ -1,5 +1,5 @@
 use crate::component::Component;
-use crate::core::{Module, ModuleField, ModuleKind};
+use crate::core::{InstrOffsets, Module, ModuleField, ModuleKind};
 use crate::kw;
 use crate::parser::{Parse, Parser, Result};
 use crate::token::Span;
@@ -32,6 +32,18 @@ impl Wat<'_> {
             Wat::Component(c) => c.encode(),
         }
     }
+
+    /// Encodes this `Wat` to binary form, also returning the offset of each
+    /// instruction in the binary along with its span in the original text.
+    ///
+    /// See [`Module::encode_with_instr_offsets`] for more information. Note
+    /// that instruction offsets are not currently reported for components.
+    pub fn encode_with_instr_offsets(&mut self) -> Result<(Vec<u8>, InstrOffsets)> {
+        match self {
+            Wat::Module(m) => m.encode_with_instr_offsets(),
+            Wat::Component(c) => Ok((c.encode()?, Vec::new())),
+        }
+    }
 }
 
 impl<'a> Parse<'a> for Wat<'a> {
@@ -41,6 +53,7 @@ impl<'a> Parse<'a> for Wat<'a> {
         }
 
         let _r = parser.register_annotation("custom");
+        let _r = parser.register_annotation("dylink.0");
         let wat = if parser.peek2::<kw::module>() {
             Wat::Module(parser.parens(|parser| parser.parse())?)
         } else if parser.peek2::<kw::component>() {
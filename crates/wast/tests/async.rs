@@ -0,0 +1,69 @@
+use wasm_encoder::{CanonicalFunctionSection, CanonicalOption, Component};
+
+#[test]
+fn async_builtins() -> anyhow::Result<()> {
+    let wasm = wat::parse_str(
+        r#"
+        (component
+          (core module $m (memory (export "m") 1) (func (export "cb") (param i32 i32 i32) (result i32) unreachable))
+          (core instance $i (instantiate $m))
+          (alias core export $i "m" (core memory $mem))
+          (alias core export $i "cb" (core func $cb))
+          (type $s (func))
+          (core func (canon task.return $s))
+          (core func (canon task.wait async (memory $mem)))
+          (core func (canon task.poll (memory $mem)))
+          (core func (canon task.yield async))
+          (canon stream.new $s (core func))
+          (canon stream.send $s async (memory $mem) (core func))
+          (canon stream.receive $s string-encoding=utf8 (core func))
+          (canon future.new $s (core func))
+          (canon future.send $s (core func))
+          (canon future.receive $s async (callback $cb) (core func))
+          (canon error.drop (core func))
+        )
+        "#,
+    )?;
+
+    let mut funcs = CanonicalFunctionSection::new();
+    funcs
+        .task_return(0)
+        .task_wait(true, 0)
+        .task_poll(false, 0)
+        .task_yield(true)
+        .stream_new(0)
+        .stream_send(0, [CanonicalOption::Async, CanonicalOption::Memory(0)])
+        .stream_receive(0, [CanonicalOption::UTF8])
+        .future_new(0)
+        .future_send(0, [])
+        .future_receive(0, [CanonicalOption::Async, CanonicalOption::Callback(0)])
+        .error_drop();
+    let mut expected = Component::new();
+    expected.section(&funcs);
+    let expected = expected.finish();
+
+    // All canonical functions are defined last and contiguously, so they're
+    // encoded as a single trailing section.
+    assert!(wasm.ends_with(&expected[8..]));
+    Ok(())
+}
+
+#[test]
+fn async_lift_options() -> anyhow::Result<()> {
+    wat::parse_str(
+        r#"
+        (component
+          (core module $m (func (export "f")) (func (export "cb") (param i32 i32 i32) (result i32) unreachable))
+          (core instance $i (instantiate $m))
+          (func (canon lift (core func $i "f") async (callback (func $i "cb"))))
+        )
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn unknown_builtin() {
+    let err = wat::parse_str("(component (canon task.frobnicate (core func)))").unwrap_err();
+    assert!(err.to_string().contains("expected"), "{}", err);
+}
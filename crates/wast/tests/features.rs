@@ -0,0 +1,165 @@
+use wast::parser::{self, Features, ParseBuffer};
+use wast::{Wast, Wat};
+
+fn parse(text: &str, features: Features) -> Result<(), String> {
+    let mut buf = ParseBuffer::new(text).map_err(|e| e.to_string())?;
+    buf.features(features);
+    parser::parse::<Wat>(&buf).map_err(|e| {
+        let (line, col) = e.span().linecol_in(text);
+        format!("{}:{}: {}", line + 1, col + 1, e.message())
+    })?;
+    Ok(())
+}
+
+/// Some text, how to disable the proposal it needs, and the name of that
+/// proposal in the expected error.
+type Case = (&'static str, fn(&mut Features), &'static str);
+
+#[test]
+fn disabled_proposals() {
+    let cases: &[Case] = &[
+        (
+            "(module (func i32.const 0 i32.extend8_s drop))",
+            |f| f.sign_extension = false,
+            "sign_extension",
+        ),
+        (
+            "(module (func f32.const 0 i32.trunc_sat_f32_s drop))",
+            |f| f.saturating_float_to_int = false,
+            "saturating_float_to_int",
+        ),
+        (
+            "(module (memory 1) (func i32.const 0 i32.const 0 i32.const 0 memory.fill))",
+            |f| f.bulk_memory = false,
+            "bulk_memory",
+        ),
+        (
+            "(module (func (param externref)))",
+            |f| f.reference_types = false,
+            "reference_types",
+        ),
+        (
+            "(module (func ref.null func drop))",
+            |f| f.reference_types = false,
+            "reference_types",
+        ),
+        (
+            "(module (func (result i32 i64) unreachable))",
+            |f| f.multi_value = false,
+            "multi_value",
+        ),
+        ("(module (func (param v128)))", |f| f.simd = false, "simd"),
+        (
+            "(module (func v128.const i64x2 0 0 drop))",
+            |f| f.simd = false,
+            "simd",
+        ),
+        (
+            "(module (func v128.const i64x2 0 0 i8x16.relaxed_swizzle drop))",
+            |f| f.relaxed_simd = false,
+            "relaxed_simd",
+        ),
+        (
+            "(module (memory 1 1 shared))",
+            |f| f.threads = false,
+            "threads",
+        ),
+        (
+            "(module (memory 1) (func atomic.fence))",
+            |f| f.threads = false,
+            "threads",
+        ),
+        (
+            "(module (func return_call 0))",
+            |f| f.tail_call = false,
+            "tail_call",
+        ),
+        (
+            "(module (memory 1) (import \"\" \"\" (memory 1)))",
+            |f| f.multi_memory = false,
+            "multi_memory",
+        ),
+        ("(module (tag))", |f| f.exceptions = false, "exceptions"),
+        (
+            "(module (func try end))",
+            |f| f.exceptions = false,
+            "exceptions",
+        ),
+        (
+            "(module (memory i64 1))",
+            |f| f.memory64 = false,
+            "memory64",
+        ),
+        (
+            "(module (global i32 (i32.add (i32.const 1) (i32.const 2))))",
+            |f| f.extended_const = false,
+            "extended_const",
+        ),
+        (
+            "(module (import \"\" \"\" (global (mut i32))))",
+            |f| f.mutable_global = false,
+            "mutable_global",
+        ),
+        (
+            "(component)",
+            |f| f.component_model = false,
+            "component_model",
+        ),
+    ];
+
+    for (text, disable, name) in cases {
+        parse(text, Features::all()).unwrap();
+        let mut features = Features::all();
+        disable(&mut features);
+        let err = parse(text, features).unwrap_err();
+        assert!(
+            err.contains(&format!("feature `{}` not enabled", name)),
+            "{}: {}",
+            text,
+            err
+        );
+    }
+}
+
+#[test]
+fn error_location() {
+    let text = "(module\n  (func\n    i32.const 0\n    i32.extend16_s\n    drop))";
+    let features = Features {
+        sign_extension: false,
+        ..Features::all()
+    };
+    let err = parse(text, features).unwrap_err();
+    assert_eq!(err, "4:5: feature `sign_extension` not enabled");
+}
+
+#[test]
+fn mvp() {
+    let text = r#"
+        (module
+          (import "" "g" (global i32))
+          (memory 1)
+          (table 1 funcref)
+          (global (mut i32) (i32.const 0))
+          (func (param i32) (result i32)
+            local.get 0
+            i32.const 1
+            i32.add)
+          (data (i32.const 0) "x")
+          (elem (i32.const 0) 0))
+    "#;
+    parse(text, Features::mvp()).unwrap();
+}
+
+#[test]
+fn wast_directives() {
+    let text = "(module (func i32.const 0 i32.extend8_s drop))\n(assert_return (invoke \"f\"))";
+    let mut buf = ParseBuffer::new(text).unwrap();
+    buf.features(Features::mvp());
+    let err = parser::parse::<Wast>(&buf).unwrap_err();
+    assert!(
+        err.message()
+            .contains("feature `sign_extension` not enabled"),
+        "{}",
+        err
+    );
+}
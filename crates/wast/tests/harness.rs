@@ -0,0 +1,191 @@
+use wast::core::NanComparison;
+use wast::harness::{Engine, Harness, Outcome, Val};
+
+/// An engine which doesn't run any wasm, and instead returns canned results
+/// for each export name.
+#[derive(Default)]
+struct FakeEngine {
+    instances: Vec<Option<String>>,
+    registered: Vec<String>,
+}
+
+impl Engine for FakeEngine {
+    fn instantiate(&mut self, name: Option<&str>, wasm: &[u8]) -> Result<(), String> {
+        wasmparser::validate(wasm).map_err(|e| e.to_string())?;
+        self.instances.push(name.map(|s| s.to_string()));
+        Ok(())
+    }
+
+    fn validate(&mut self, wasm: &[u8]) -> Result<(), String> {
+        wasmparser::validate(wasm)
+            .map(drop)
+            .map_err(|e| e.to_string())
+    }
+
+    fn register(&mut self, as_name: &str, _module: Option<&str>) -> Result<(), String> {
+        self.registered.push(as_name.to_string());
+        Ok(())
+    }
+
+    fn invoke(
+        &mut self,
+        module: Option<&str>,
+        name: &str,
+        args: &[Val],
+    ) -> Result<Vec<Val>, String> {
+        if let Some(module) = module {
+            if !self.instances.iter().any(|i| i.as_deref() == Some(module)) {
+                return Err(format!("unknown module {}", module));
+            }
+        }
+        match name {
+            "one" => Ok(vec![Val::I32(1)]),
+            "id" => Ok(args.to_vec()),
+            "nan" => Ok(vec![Val::F32(0x7fc0_0001)]),
+            "boom" => Err("wasm trap: unreachable executed".to_string()),
+            _ => Err(format!("unknown export {}", name)),
+        }
+    }
+
+    fn get(&mut self, _module: Option<&str>, name: &str) -> Result<Val, String> {
+        match name {
+            "g" => Ok(Val::I64(7)),
+            _ => Err(format!("unknown global {}", name)),
+        }
+    }
+}
+
+const SCRIPT: &str = r#"
+(module $m (func (export "one") (result i32) i32.const 1))
+(register "m" $m)
+(assert_return (invoke $m "one") (i32.const 1))
+(assert_return (invoke "one") (i32.const 2))
+(assert_return (invoke "id" (i64.const 3) (ref.extern 4)) (i64.const 3) (ref.extern 4))
+(assert_return (invoke "nan") (f32.const nan:arithmetic))
+(assert_return (get "g") (i64.const 7))
+(assert_trap (invoke "boom") "unreachable")
+(assert_trap (invoke "one") "unreachable")
+(assert_invalid (module (func (result i32))) "type mismatch")
+(assert_malformed (module quote "(func") "unexpected token")
+(thread $t (module))
+"#;
+
+#[test]
+fn outcomes() {
+    let mut engine = FakeEngine::default();
+    let suite = Harness::new().run_script("test.wast", SCRIPT, &mut engine);
+    let outcomes = suite
+        .cases
+        .iter()
+        .map(|c| (c.name.as_str(), &c.outcome))
+        .collect::<Vec<_>>();
+    assert_eq!(outcomes.len(), 12, "{:#?}", outcomes);
+    assert_eq!(outcomes[0], ("2:2 module", &Outcome::Pass));
+    assert_eq!(outcomes[1], ("3:2 register \"m\"", &Outcome::Pass));
+    assert_eq!(outcomes[2], ("4:2 assert_return \"one\"", &Outcome::Pass));
+    match outcomes[3].1 {
+        Outcome::Fail(msg) => assert!(msg.contains("result 0 mismatch"), "{}", msg),
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(outcomes[4].1, &Outcome::Pass);
+    assert_eq!(outcomes[5].1, &Outcome::Pass);
+    assert_eq!(outcomes[6].1, &Outcome::Pass);
+    assert_eq!(outcomes[7].1, &Outcome::Pass);
+    match outcomes[8].1 {
+        Outcome::Fail(msg) => assert!(msg.contains("expected an error"), "{}", msg),
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(outcomes[9].1, &Outcome::Pass);
+    assert_eq!(outcomes[10].1, &Outcome::Pass);
+    assert!(matches!(outcomes[11].1, Outcome::Skip(_)));
+    assert_eq!(engine.registered, ["m"]);
+}
+
+#[test]
+fn nan_comparison() {
+    let script = "(assert_return (invoke \"nan\") (f32.const nan:arithmetic))";
+    let suite = Harness::new()
+        .nan_comparison(NanComparison::Canonical)
+        .run_script("nan.wast", script, &mut FakeEngine::default());
+    assert!(matches!(suite.cases[0].outcome, Outcome::Fail(_)));
+}
+
+#[test]
+fn messages() {
+    let script = "(assert_trap (invoke \"boom\") \"integer overflow\")";
+    let suite = Harness::new().run_script("trap.wast", script, &mut FakeEngine::default());
+    assert!(matches!(suite.cases[0].outcome, Outcome::Fail(_)));
+    let suite = Harness::new().check_messages(false).run_script(
+        "trap.wast",
+        script,
+        &mut FakeEngine::default(),
+    );
+    assert_eq!(suite.cases[0].outcome, Outcome::Pass);
+}
+
+#[test]
+fn parse_errors() {
+    let suite = Harness::new().run_script("bad.wast", "(assert_return", &mut FakeEngine::default());
+    assert_eq!(suite.cases.len(), 1);
+    match &suite.cases[0].outcome {
+        Outcome::Fail(msg) => assert!(msg.contains("bad.wast:1:"), "{}", msg),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn run_dir_and_reports() {
+    let dir = std::env::temp_dir().join(format!("wast-harness-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("nested")).unwrap();
+    std::fs::write(dir.join("b.wast"), SCRIPT).unwrap();
+    std::fs::write(
+        dir.join("nested/a.wast"),
+        "(module)\n(assert_return (invoke \"one\") (i32.const 1))",
+    )
+    .unwrap();
+    std::fs::write(dir.join("ignored.wat"), "(module)").unwrap();
+
+    let report = Harness::new().run_dir(&dir, FakeEngine::default).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let names = report
+        .suites
+        .iter()
+        .map(|s| s.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names.len(), 2);
+    assert_eq!(names[0], "b.wast");
+    assert!(names[1].ends_with("a.wast"), "{}", names[1]);
+    assert_eq!(report.tests(), 14);
+    assert_eq!(report.failures(), 2);
+    assert_eq!(report.skipped(), 1);
+
+    let junit = report.to_junit();
+    assert!(junit.starts_with("<?xml"), "{}", junit);
+    assert!(
+        junit.contains("<testsuites tests=\"14\" failures=\"2\" skipped=\"1\">"),
+        "{}",
+        junit
+    );
+    assert!(
+        junit.contains("<testcase classname=\"b.wast\" name=\"3:2 register &quot;m&quot;\"/>"),
+        "{}",
+        junit
+    );
+    assert_eq!(junit.matches("<failure ").count(), 2);
+
+    let tap = report.to_tap();
+    assert!(tap.starts_with("TAP version 13\n1..14\n"), "{}", tap);
+    assert!(tap.contains("ok 1 - b.wast 2:2 module\n"), "{}", tap);
+    assert!(tap.contains("not ok 4 - b.wast 5:2 assert_return \"one\"\n  ---\n  message: \""));
+    assert!(tap.contains("ok 12 - b.wast 13:2 thread # SKIP"), "{}", tap);
+}
+
+#[test]
+fn trait_objects() {
+    let mut engine: Box<dyn Engine> = Box::new(FakeEngine::default());
+    let script = "(assert_return (invoke \"one\") (i32.const 1))";
+    let suite = Harness::new().run_script("dyn.wast", script, &mut engine);
+    assert_eq!(suite.cases[0].outcome, Outcome::Pass);
+}
@@ -0,0 +1,83 @@
+use wast::core::{NanComparison, NanPattern, V128Pattern};
+use wast::parser::{self, ParseBuffer};
+use wast::token::{Float32, Float64};
+
+fn f32_pattern(s: &str) -> NanPattern<Float32> {
+    let buf = ParseBuffer::new(s).unwrap();
+    parser::parse(&buf).unwrap()
+}
+
+fn f64_pattern(s: &str) -> NanPattern<Float64> {
+    let buf = ParseBuffer::new(s).unwrap();
+    parser::parse(&buf).unwrap()
+}
+
+fn v128_pattern(s: &str) -> V128Pattern {
+    let buf = ParseBuffer::new(s).unwrap();
+    parser::parse(&buf).unwrap()
+}
+
+const CANONICAL: u32 = 0x7fc0_0000;
+const NEG_CANONICAL: u32 = 0xffc0_0000;
+const ARITHMETIC: u32 = 0x7fc0_0001;
+const SIGNALING: u32 = 0x7f80_0001;
+
+#[test]
+fn f32_semantics() {
+    use NanComparison::*;
+
+    let canonical = f32_pattern("nan:canonical");
+    for cmp in [Arithmetic, Canonical] {
+        assert!(canonical.matches(CANONICAL, cmp));
+        assert!(canonical.matches(NEG_CANONICAL, cmp));
+        assert!(!canonical.matches(ARITHMETIC, cmp));
+    }
+    assert!(canonical.matches(CANONICAL, BitExact));
+    assert!(!canonical.matches(NEG_CANONICAL, BitExact));
+
+    let arithmetic = f32_pattern("nan:arithmetic");
+    assert!(arithmetic.matches(ARITHMETIC, Arithmetic));
+    assert!(arithmetic.matches(NEG_CANONICAL, Arithmetic));
+    assert!(!arithmetic.matches(SIGNALING, Arithmetic));
+    assert!(!arithmetic.matches(0x3f80_0000, Arithmetic));
+    assert!(!arithmetic.matches(ARITHMETIC, Canonical));
+    assert!(arithmetic.matches(NEG_CANONICAL, Canonical));
+    assert!(!arithmetic.matches(NEG_CANONICAL, BitExact));
+    assert!(arithmetic.matches(CANONICAL, BitExact));
+
+    // Literal values, including NaNs, always compare by their bits.
+    let literal = f32_pattern("nan:0x1");
+    for cmp in [Arithmetic, Canonical, BitExact] {
+        assert!(literal.matches(SIGNALING, cmp));
+        assert!(!literal.matches(CANONICAL, cmp));
+    }
+    assert!(f32_pattern("1.0").matches(0x3f80_0000, BitExact));
+}
+
+#[test]
+fn f64_semantics() {
+    let arithmetic = f64_pattern("nan:arithmetic");
+    assert!(arithmetic.matches(0xfff8_0000_0000_0001, NanComparison::Arithmetic));
+    assert!(!arithmetic.matches(0xfff8_0000_0000_0001, NanComparison::Canonical));
+    assert!(arithmetic.matches(0x7ff8_0000_0000_0000, NanComparison::BitExact));
+}
+
+#[test]
+fn v128_lanes() {
+    let lanes = |l: [u32; 4]| {
+        l.iter()
+            .rev()
+            .fold(0u128, |acc, lane| (acc << 32) | u128::from(*lane))
+    };
+
+    let pattern = v128_pattern("f32x4 nan:canonical nan:arithmetic 1.0 -0.0");
+    let bits = lanes([NEG_CANONICAL, ARITHMETIC, 0x3f80_0000, 0x8000_0000]);
+    assert!(pattern.matches(bits, NanComparison::Arithmetic));
+    assert!(!pattern.matches(bits, NanComparison::Canonical));
+    let bits = lanes([CANONICAL, CANONICAL, 0x3f80_0000, 0x8000_0000]);
+    assert!(pattern.matches(bits, NanComparison::BitExact));
+
+    let pattern = v128_pattern("i32x4 1 -1 2 3");
+    assert!(pattern.matches(lanes([1, u32::MAX, 2, 3]), NanComparison::BitExact));
+    assert!(!pattern.matches(lanes([1, u32::MAX, 3, 2]), NanComparison::BitExact));
+}
@@ -0,0 +1,83 @@
+use wast::parser::{self, ParseBuffer};
+use wast::{Wast, Wat};
+
+fn errors<'a, T: wast::parser::Parse<'a>>(buf: &'a ParseBuffer<'a>, text: &str) -> Vec<String> {
+    match parser::parse_recovering::<T>(buf) {
+        Ok(_) => Vec::new(),
+        Err(errors) => errors
+            .into_iter()
+            .map(|e| {
+                let (line, col) = e.span().linecol_in(text);
+                format!("{}:{}: {}", line + 1, col + 1, e.message())
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn module_fields() -> anyhow::Result<()> {
+    let text = "\
+(module
+  (func (foo))
+  (memory 1)
+  (global i32 (i32.const x))
+  oops
+  (func (export \"f\"))
+)";
+    let buf = ParseBuffer::new(text)?;
+    let errors = errors::<Wat>(&buf, text);
+    assert_eq!(errors.len(), 3, "{:#?}", errors);
+    assert!(errors[0].starts_with("2:10:"), "{}", errors[0]);
+    assert!(errors[1].starts_with("4:26:"), "{}", errors[1]);
+    assert!(errors[2].starts_with("5:3:"), "{}", errors[2]);
+    Ok(())
+}
+
+#[test]
+fn component_fields() -> anyhow::Result<()> {
+    let text = "(component (type (func (param \"a\" nope))) (core module) (import))";
+    let buf = ParseBuffer::new(text)?;
+    assert_eq!(errors::<Wat>(&buf, text).len(), 2);
+    Ok(())
+}
+
+#[test]
+fn wast_directives() -> anyhow::Result<()> {
+    let text = "\
+(module (func (bad)))
+(assert_return (invoke \"f\") (i32.const 1))
+(assert_bogus)
+(module (func (worse)))
+";
+    let buf = ParseBuffer::new(text)?;
+    let errors = errors::<Wast>(&buf, text);
+    assert_eq!(errors.len(), 3, "{:#?}", errors);
+    assert!(errors[0].starts_with("1:16:"), "{}", errors[0]);
+    assert!(errors[1].starts_with("3:2:"), "{}", errors[1]);
+    assert!(errors[2].starts_with("4:16:"), "{}", errors[2]);
+    Ok(())
+}
+
+#[test]
+fn valid_input() -> anyhow::Result<()> {
+    let text = "(module (func))";
+    let buf = ParseBuffer::new(text)?;
+    assert!(parser::parse_recovering::<Wat>(&buf).is_ok());
+    Ok(())
+}
+
+#[test]
+fn unbalanced() -> anyhow::Result<()> {
+    let text = "(module (func (foo)) (func";
+    let buf = ParseBuffer::new(text)?;
+    assert_eq!(errors::<Wat>(&buf, text).len(), 2);
+    Ok(())
+}
+
+#[test]
+fn recovery_is_opt_in() -> anyhow::Result<()> {
+    let text = "(module (func (foo)) (memory x))";
+    let buf = ParseBuffer::new(text)?;
+    assert!(parser::parse::<Wat>(&buf).is_err());
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use wast::parser::{self, Parse, ParseBuffer, Parser, Result};
+use wast::{Wast, Wat};
+
+#[test]
+fn known_annotations_are_not_reported() -> anyhow::Result<()> {
+    let wat = r#"
+        (module (@name "m")
+          (@custom "a" "b")
+          (func $f (@name "f") (param $p (@name "p") i32))
+          (@custom "c" (after func) "d"))
+    "#;
+    let buf = ParseBuffer::new(wat)?;
+    parser::parse::<Wat>(&buf)?;
+    assert_eq!(buf.unknown_annotations(), []);
+    Ok(())
+}
+
+#[test]
+fn unknown_annotations() -> anyhow::Result<()> {
+    let wat = r#"(module
+  (@section ".text")
+  (func (@weak) (export "f") (@inner (@nested x)) nop)
+  (global i32 (@foo) (i32.const 0)))"#;
+    let buf = ParseBuffer::new(wat)?;
+    parser::parse::<Wat>(&buf)?;
+    let annotations = buf
+        .unknown_annotations()
+        .into_iter()
+        .map(|a| (a.span.linecol_in(wat), a.name, a.source, a.contents))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        annotations,
+        [
+            ((1, 2), "section", r#"(@section ".text")"#, r#"".text""#),
+            ((2, 8), "weak", "(@weak)", ""),
+            ((2, 29), "inner", "(@inner (@nested x))", "(@nested x)"),
+            ((3, 14), "foo", "(@foo)", ""),
+        ]
+    );
+    Ok(())
+}
+
+struct Section<'a> {
+    name: &'a str,
+    align: u32,
+}
+
+impl<'a> Parse<'a> for Section<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        Ok(Section {
+            name: parser.parse()?,
+            align: parser.parse()?,
+        })
+    }
+}
+
+#[test]
+fn annotation_contents_can_be_parsed() -> anyhow::Result<()> {
+    let wat = r#"(module (@section ".data" 4))"#;
+    let buf = ParseBuffer::new(wat)?;
+    parser::parse::<Wat>(&buf)?;
+    let annotation = buf.unknown_annotations()[0];
+
+    let contents = ParseBuffer::new(annotation.contents)?;
+    let section = parser::parse::<Section>(&contents)?;
+    assert_eq!(section.name, ".data");
+    assert_eq!(section.align, 4);
+    Ok(())
+}
+
+#[test]
+fn wast_directives() -> anyhow::Result<()> {
+    let wast = r#"
+        (module (@a))
+        (assert_return (invoke "f" (@b)))
+    "#;
+    let buf = ParseBuffer::new(wast)?;
+    parser::parse::<Wast>(&buf)?;
+    let names = buf
+        .unknown_annotations()
+        .iter()
+        .map(|a| a.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["a", "b"]);
+    Ok(())
+}
@@ -0,0 +1,439 @@
+//! A concrete syntax tree for the WebAssembly text format.
+//!
+//! The AST in the [`wast`] crate is tailored for translating text to binary,
+//! so it discards comments and whitespace and flattens folded instructions.
+//! The [`Cst`] here is instead a tree of the s-expressions in the source
+//! which retains every byte of its input, which makes it suitable for tools
+//! like formatters and source-level refactorings that need to preserve what
+//! was written by hand.
+//!
+//! A [`Cst`] is printed back to text with its `Display` implementation, which
+//! reproduces the original source exactly unless the tree has been modified,
+//! or with [`Cst::format`] which normalizes its whitespace.
+//!
+//! # Examples
+//!
+//! ```
+//! use wat::cst::{Cst, Node};
+//!
+//! # fn foo() -> wat::Result<()> {
+//! let src = "(module\n        ;; a comment\n  (func $f   (nop)))\n";
+//! let cst = Cst::parse(src)?;
+//! assert_eq!(cst.to_string(), src);
+//!
+//! let module = match &cst.nodes[0] {
+//!     Node::List(list) => list,
+//!     _ => unreachable!(),
+//! };
+//! assert_eq!(module.head(), Some("module"));
+//!
+//! assert_eq!(cst.format(), "(module\n  ;; a comment\n  (func $f (nop)))\n");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Error, Result};
+use std::borrow::Cow;
+use std::fmt;
+use wast::lexer::{Lexer, Token as LexToken};
+use wast::token::Span;
+
+/// The maximum nesting of lists in a [`Cst`], which bounds the recursion
+/// used to print and drop the tree.
+const MAX_DEPTH: usize = 1000;
+
+/// A concrete syntax tree of a `*.wat` or `*.wast` file.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cst<'a> {
+    /// The top-level nodes of the file, including any leading and trailing
+    /// whitespace and comments.
+    pub nodes: Vec<Node<'a>>,
+}
+
+/// A node of a [`Cst`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node<'a> {
+    /// A parenthesized list of nodes, such as `(func ...)`.
+    List(List<'a>),
+    /// Any other token, including whitespace and comments.
+    Token(Token<'a>),
+}
+
+/// A parenthesized list of nodes in a [`Cst`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct List<'a> {
+    /// The offset of the opening `(` in the source, or where the list would
+    /// have been for lists that were created after parsing.
+    pub offset: usize,
+    /// The contents of the list, excluding the parentheses.
+    pub nodes: Vec<Node<'a>>,
+}
+
+/// A token of a [`Cst`] other than a parenthesis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a> {
+    /// The offset of this token in the source.
+    pub offset: usize,
+    /// What kind of token this is.
+    pub kind: TokenKind,
+    /// The text of this token.
+    ///
+    /// This may be replaced to rename or otherwise rewrite the token, and the
+    /// new text will be used when printing the tree.
+    pub src: Cow<'a, str>,
+}
+
+/// The different kinds of [`Token`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum TokenKind {
+    Whitespace,
+    LineComment,
+    BlockComment,
+    String,
+    Id,
+    Keyword,
+    Reserved,
+    Integer,
+    Float,
+}
+
+impl TokenKind {
+    /// Returns whether this is whitespace or a comment, which are otherwise
+    /// insignificant.
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment
+        )
+    }
+}
+
+impl<'a> Node<'a> {
+    /// Returns whether this is whitespace or a comment.
+    pub fn is_trivia(&self) -> bool {
+        match self {
+            Node::List(_) => false,
+            Node::Token(t) => t.kind.is_trivia(),
+        }
+    }
+
+    /// Converts this node into one which doesn't borrow from the source.
+    pub fn into_owned(self) -> Node<'static> {
+        match self {
+            Node::List(list) => Node::List(list.into_owned()),
+            Node::Token(token) => Node::Token(token.into_owned()),
+        }
+    }
+
+    /// Returns the offset just past the end of this node in the source.
+    pub(crate) fn end(&self) -> usize {
+        match self {
+            Node::List(list) => list.end(),
+            Node::Token(token) => token.offset + token.src.len(),
+        }
+    }
+
+    /// Moves this node, and everything within it, by `delta` bytes.
+    pub(crate) fn shift(&mut self, delta: isize) {
+        match self {
+            Node::List(list) => {
+                list.offset = list.offset.wrapping_add(delta as usize);
+                for node in list.nodes.iter_mut() {
+                    node.shift(delta);
+                }
+            }
+            Node::Token(token) => token.offset = token.offset.wrapping_add(delta as usize),
+        }
+    }
+}
+
+impl<'a> List<'a> {
+    /// Returns the nodes of this list which aren't whitespace or comments.
+    pub fn items(&self) -> impl Iterator<Item = &Node<'a>> {
+        self.nodes.iter().filter(|n| !n.is_trivia())
+    }
+
+    /// Returns the keyword this list starts with, such as `func` for
+    /// `(func ...)`, if any.
+    pub fn head(&self) -> Option<&str> {
+        match self.items().next()? {
+            Node::Token(t) if t.kind == TokenKind::Keyword => Some(&t.src),
+            _ => None,
+        }
+    }
+
+    /// Converts this list into one which doesn't borrow from the source.
+    pub fn into_owned(self) -> List<'static> {
+        List {
+            offset: self.offset,
+            nodes: self.nodes.into_iter().map(Node::into_owned).collect(),
+        }
+    }
+
+    /// Returns the offset just past the closing `)` of this list.
+    pub(crate) fn end(&self) -> usize {
+        match self.nodes.last() {
+            Some(node) => node.end() + 1,
+            None => self.offset + 2,
+        }
+    }
+}
+
+impl Token<'_> {
+    /// Converts this token into one which doesn't borrow from the source.
+    pub fn into_owned(self) -> Token<'static> {
+        Token {
+            offset: self.offset,
+            kind: self.kind,
+            src: Cow::Owned(self.src.into_owned()),
+        }
+    }
+}
+
+impl<'a> Cst<'a> {
+    /// Parses the concrete syntax tree of `wat`.
+    ///
+    /// This only checks that `wat` can be lexed and that its parentheses are
+    /// balanced, so a successful parse doesn't mean that `wat` is a valid
+    /// module.
+    pub fn parse(wat: &'a str) -> Result<Cst<'a>> {
+        Ok(Cst {
+            nodes: parse_nodes(wat, 0, 0)?,
+        })
+    }
+
+    /// Converts this tree into one which doesn't borrow from the source.
+    pub fn into_owned(self) -> Cst<'static> {
+        Cst {
+            nodes: self.nodes.into_iter().map(Node::into_owned).collect(),
+        }
+    }
+
+    /// Prints this tree with normalized whitespace.
+    ///
+    /// Comments, line breaks, and the choice between folded and flat
+    /// instructions are kept as written, while the rest of the whitespace is
+    /// rewritten such that:
+    ///
+    /// * each line is indented by two spaces per enclosing list,
+    /// * tokens on the same line are separated by a single space,
+    /// * there's no whitespace just inside of parentheses on the same line,
+    /// * there's no trailing whitespace and at most one blank line in a row,
+    /// * and the output ends with a single newline.
+    pub fn format(&self) -> String {
+        let mut formatter = Formatter {
+            dst: String::new(),
+            pending: Pending::Start,
+        };
+        formatter.nodes(&self.nodes, 0);
+        formatter.dst.push('\n');
+        formatter.dst
+    }
+}
+
+impl fmt::Display for Cst<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.nodes.iter().try_for_each(|node| node.fmt(f))
+    }
+}
+
+impl fmt::Display for Node<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::List(list) => list.fmt(f),
+            Node::Token(token) => f.write_str(&token.src),
+        }
+    }
+}
+
+impl fmt::Display for List<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(")?;
+        self.nodes.iter().try_for_each(|node| node.fmt(f))?;
+        f.write_str(")")
+    }
+}
+
+/// Parses the nodes of `wat`, which is located at `base` in the source and
+/// nested within `depth` lists.
+///
+/// Errors are reported relative to `wat`.
+pub(crate) fn parse_nodes(wat: &str, base: usize, depth: usize) -> Result<Vec<Node<'_>>> {
+    let error = |offset: usize, msg: &str| {
+        Error::cvt(
+            wast::Error::new(Span::from_offset(offset - base), msg.to_string()),
+            wat,
+        )
+    };
+
+    let mut nodes = Vec::new();
+    let mut stack: Vec<List<'_>> = Vec::new();
+    let mut offset = base;
+    for token in Lexer::new(wat) {
+        let token = token.map_err(|e| Error::cvt(e, wat))?;
+        let src = token.src();
+        let kind = match token {
+            LexToken::LParen(_) => {
+                if depth + stack.len() >= MAX_DEPTH {
+                    return Err(error(offset, "item nesting too deep"));
+                }
+                stack.push(List {
+                    offset,
+                    nodes: Vec::new(),
+                });
+                offset += src.len();
+                continue;
+            }
+            LexToken::RParen(_) => {
+                let list = stack.pop().ok_or_else(|| error(offset, "unexpected `)`"))?;
+                match stack.last_mut() {
+                    Some(parent) => parent.nodes.push(Node::List(list)),
+                    None => nodes.push(Node::List(list)),
+                }
+                offset += src.len();
+                continue;
+            }
+            LexToken::Whitespace(_) => TokenKind::Whitespace,
+            LexToken::LineComment(_) => TokenKind::LineComment,
+            LexToken::BlockComment(_) => TokenKind::BlockComment,
+            LexToken::String(_) => TokenKind::String,
+            LexToken::Id(_) => TokenKind::Id,
+            LexToken::Keyword(_) => TokenKind::Keyword,
+            LexToken::Reserved(_) => TokenKind::Reserved,
+            LexToken::Integer(_) => TokenKind::Integer,
+            LexToken::Float(_) => TokenKind::Float,
+        };
+        let token = Node::Token(Token {
+            offset,
+            kind,
+            src: src.into(),
+        });
+        match stack.last_mut() {
+            Some(parent) => parent.nodes.push(token),
+            None => nodes.push(token),
+        }
+        offset += src.len();
+    }
+    if let Some(list) = stack.last() {
+        return Err(error(list.offset, "unclosed `(`"));
+    }
+    Ok(nodes)
+}
+
+/// Whitespace which has been seen by the `Formatter` but not yet printed,
+/// since how it's printed depends on what follows it.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Pending {
+    /// Nothing has been printed yet, so whitespace is dropped.
+    Start,
+    None,
+    Space,
+    Newlines(usize),
+}
+
+struct Formatter {
+    dst: String,
+    pending: Pending,
+}
+
+impl Formatter {
+    fn nodes(&mut self, nodes: &[Node<'_>], depth: usize) {
+        for node in nodes {
+            match node {
+                Node::Token(t) if t.kind == TokenKind::Whitespace => {
+                    let pending = match t.src.matches('\n').count() {
+                        0 => Pending::Space,
+                        n => Pending::Newlines(n.min(2)),
+                    };
+                    if self.pending != Pending::Start {
+                        self.pending = self.pending.max(pending);
+                    }
+                }
+                Node::Token(t) => {
+                    self.flush(depth, false);
+                    self.dst.push_str(&t.src);
+                }
+                Node::List(list) => {
+                    self.flush(depth, false);
+                    self.dst.push('(');
+                    self.nodes(&list.nodes, depth + 1);
+                    self.flush(depth, true);
+                    self.dst.push(')');
+                }
+            }
+        }
+    }
+
+    /// Prints any pending whitespace before a token at `depth`, where
+    /// `closing` indicates whether the token is a `)`.
+    fn flush(&mut self, depth: usize, closing: bool) {
+        match std::mem::replace(&mut self.pending, Pending::None) {
+            Pending::Start | Pending::None => {}
+            Pending::Space => {
+                if !closing && !self.dst.ends_with('(') {
+                    self.dst.push(' ');
+                }
+            }
+            Pending::Newlines(n) => {
+                for _ in 0..n {
+                    self.dst.push('\n');
+                }
+                for _ in 0..depth {
+                    self.dst.push_str("  ");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let src = "  ;; leading\n(module $m (; block (; nested ;) ;)\n\t(func (export \"f\")\n   i32.const 1 (drop)  )   )\n\n";
+        let cst = Cst::parse(src).unwrap();
+        assert_eq!(cst.to_string(), src);
+    }
+
+    #[test]
+    fn format() {
+        let src = "\n\n(module\n        (func $f (param i32)\n    ( local.get 0 )\n\n\n\n    drop   )  \n    ;; trailing\n  )";
+        let expected =
+            "(module\n  (func $f (param i32)\n    (local.get 0)\n\n    drop)\n  ;; trailing\n)\n";
+        assert_eq!(Cst::parse(src).unwrap().format(), expected);
+    }
+
+    #[test]
+    fn edit() {
+        let mut cst = Cst::parse("(module (func $f) (func $g call $f))").unwrap();
+        fn rename(nodes: &mut [Node<'_>]) {
+            for node in nodes {
+                match node {
+                    Node::List(list) => rename(&mut list.nodes),
+                    Node::Token(t) if t.src == "$f" => t.src = "$renamed".into(),
+                    Node::Token(_) => {}
+                }
+            }
+        }
+        rename(&mut cst.nodes);
+        assert_eq!(
+            cst.to_string(),
+            "(module (func $renamed) (func $g call $renamed))"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = Cst::parse("(module))").unwrap_err();
+        assert!(err.to_string().contains("unexpected `)`"), "{}", err);
+        let err = Cst::parse("(module (func)").unwrap_err();
+        assert!(err.to_string().contains("unclosed `(`"), "{}", err);
+        let err = Cst::parse(&"(".repeat(MAX_DEPTH + 1)).unwrap_err();
+        assert!(err.to_string().contains("nesting too deep"), "{}", err);
+    }
+}
@@ -0,0 +1,228 @@
+//! Incremental parsing of WebAssembly text documents.
+//!
+//! Editors and language servers see a document change one keystroke at a
+//! time, and reparsing a large file after every keystroke is wasteful. A
+//! [`Document`] instead keeps the [`Cst`] of its text up to date as edits are
+//! applied, only relexing the innermost list which encloses each edit.
+//!
+//! # Examples
+//!
+//! ```
+//! use wat::incremental::Document;
+//!
+//! let mut doc = Document::new("(module (func $a (nop)) (func $b (nop)))");
+//!
+//! // Replace the second `nop` with `unreachable`. Only `(nop)` needs to be
+//! // reparsed.
+//! let reparsed = doc.edit(34..37, "unreachable");
+//! assert_eq!(&doc.text()[reparsed], "(unreachable)");
+//! assert_eq!(
+//!     doc.text(),
+//!     "(module (func $a (nop)) (func $b (unreachable)))",
+//! );
+//! assert!(doc.cst().is_ok());
+//! ```
+
+use crate::cst::{self, Cst, Node};
+use crate::Error;
+use std::ops::Range;
+
+/// A WebAssembly text document whose [`Cst`] is updated incrementally as it's
+/// edited.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug)]
+pub struct Document {
+    text: String,
+    tree: Result<Cst<'static>, Error>,
+}
+
+impl Document {
+    /// Creates a new document with the initial contents `text`, which is
+    /// parsed in full.
+    pub fn new(text: impl Into<String>) -> Document {
+        let text = text.into();
+        let tree = Cst::parse(&text).map(Cst::into_owned);
+        Document { text, tree }
+    }
+
+    /// Returns the current text of this document.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the concrete syntax tree of the current text, or the error
+    /// from parsing it if it's not well-formed.
+    pub fn cst(&self) -> Result<&Cst<'static>, &Error> {
+        self.tree.as_ref()
+    }
+
+    /// Replaces `range` of the text with `replacement` and updates the
+    /// concrete syntax tree.
+    ///
+    /// Returns the range of the new text which was reparsed, which is the
+    /// innermost list enclosing the edit if possible and otherwise the whole
+    /// document. Anything derived from the tree within this range, such as
+    /// diagnostics, should be recomputed, while nodes outside of it are only
+    /// moved to account for the change in length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds of the text or doesn't lie on
+    /// `char` boundaries, like [`String::replace_range`].
+    pub fn edit(&mut self, range: Range<usize>, replacement: &str) -> Range<usize> {
+        self.text.replace_range(range.clone(), replacement);
+        let delta = replacement.len() as isize - range.len() as isize;
+        if let Ok(tree) = &mut self.tree {
+            if let Some(reparsed) = reparse(&mut tree.nodes, &self.text, &range, delta, 0) {
+                return reparsed;
+            }
+        }
+        self.tree = Cst::parse(&self.text).map(Cst::into_owned);
+        0..self.text.len()
+    }
+}
+
+/// Attempts to reparse the innermost list within `nodes` which encloses the
+/// edit of `range` in the old text, where `text` is the new text and the
+/// lists in `nodes` are nested within `depth` others.
+///
+/// Returns the range of `text` that was reparsed, or `None` if the edit
+/// isn't contained within one of `nodes` or if it changed the structure of
+/// the list it's contained in.
+fn reparse(
+    nodes: &mut [Node<'static>],
+    text: &str,
+    range: &Range<usize>,
+    delta: isize,
+    depth: usize,
+) -> Option<Range<usize>> {
+    // Edits which touch either parenthesis of a list may merge or split it
+    // with its neighbors, so only edits strictly within a list qualify.
+    let i = nodes.iter().position(|node| match node {
+        Node::List(list) => list.offset < range.start && range.end < list.end(),
+        Node::Token(_) => false,
+    })?;
+    let list = match &mut nodes[i] {
+        Node::List(list) => list,
+        Node::Token(_) => unreachable!(),
+    };
+
+    let reparsed = match reparse(&mut list.nodes, text, range, delta, depth + 1) {
+        Some(reparsed) => reparsed,
+        None => {
+            // Relex this list in isolation. Its `(` starts a token in the
+            // new text just as it did in the old text, so this is the same
+            // as relexing the whole document as long as the list is still
+            // balanced and its `)` hasn't become part of a string or comment.
+            let new = list.offset..(list.end() as isize + delta) as usize;
+            let mut parsed = cst::parse_nodes(&text[new.clone()], new.start, depth).ok()?;
+            match parsed.pop() {
+                Some(Node::List(replacement)) if parsed.is_empty() => {
+                    *list = replacement.into_owned();
+                }
+                _ => return None,
+            }
+            return Some(new);
+        }
+    };
+    for node in nodes[i + 1..].iter_mut() {
+        node.shift(delta);
+    }
+    Some(reparsed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_consistent(doc: &Document) {
+        match (doc.cst(), Cst::parse(doc.text())) {
+            (Ok(a), Ok(b)) => assert_eq!(*a, b.into_owned(), "{}", doc.text()),
+            (Err(_), Err(_)) => {}
+            (a, b) => panic!("{:?} != {:?} for {}", a, b, doc.text()),
+        }
+    }
+
+    #[test]
+    fn edits() {
+        let mut doc = Document::new("(module\n  (func $f (param i32)\n    (nop))\n  (memory 1))\n");
+        assert_consistent(&doc);
+
+        // Within a nested list, only that list is reparsed.
+        let start = doc.text().find("nop").unwrap();
+        let reparsed = doc.edit(start..start + 3, "drop (local.get 0)");
+        assert_eq!(&doc.text()[reparsed], "(drop (local.get 0))");
+        assert_consistent(&doc);
+
+        // Which means the nodes after it were moved.
+        let start = doc.text().find("1))").unwrap();
+        let reparsed = doc.edit(start..start + 1, "2");
+        assert_eq!(&doc.text()[reparsed], "(memory 2)");
+        assert_consistent(&doc);
+
+        // Edits which touch the parentheses of a list reparse its parent.
+        let start = doc.text().find("(local.get").unwrap();
+        let reparsed = doc.edit(start..start + 13, "(i32.const 1)");
+        assert_eq!(&doc.text()[reparsed], "(drop (i32.const 1))");
+        assert_consistent(&doc);
+
+        // Unbalancing a list reparses everything.
+        let start = doc.text().find("(i32.const").unwrap();
+        let reparsed = doc.edit(start..start + 1, "");
+        assert_eq!(reparsed, 0..doc.text().len());
+        assert!(doc.cst().is_err());
+        let reparsed = doc.edit(start..start, "(");
+        assert_eq!(reparsed, 0..doc.text().len());
+        assert_consistent(&doc);
+
+        // Edits outside of any list reparse everything.
+        let len = doc.text().len();
+        let reparsed = doc.edit(len..len, ";; done\n");
+        assert_eq!(reparsed, 0..doc.text().len());
+        assert_consistent(&doc);
+    }
+
+    #[test]
+    fn errors() {
+        let mut doc = Document::new("(module (func))");
+        // Commenting out a `)` leaves the document unbalanced...
+        let reparsed = doc.edit(13..13, ";;");
+        assert_eq!(reparsed, 0..doc.text().len());
+        assert!(doc.cst().is_err());
+        assert_consistent(&doc);
+
+        // ... and removing the comment fixes it.
+        doc.edit(13..15, "");
+        assert_eq!(doc.text(), "(module (func))");
+        assert_consistent(&doc);
+
+        // Opening a string or block comment captures the closing paren.
+        let mut doc = Document::new("(module (func) (func))");
+        doc.edit(13..13, "\"");
+        assert!(doc.cst().is_err());
+        let mut doc = Document::new("(module (func) (func))");
+        doc.edit(9..9, "(;");
+        assert!(doc.cst().is_err());
+        assert_consistent(&doc);
+    }
+
+    #[test]
+    fn many_edits() {
+        let mut doc = Document::new("(module)");
+        let mut pos = 7;
+        for piece in [
+            "\n  (func $f",
+            " (result i32)",
+            "\n    (i32.const 1))",
+            "\n",
+        ] {
+            for c in piece.chars() {
+                doc.edit(pos..pos, c.encode_utf8(&mut [0; 4]));
+                pos += c.len_utf8();
+                assert_consistent(&doc);
+            }
+        }
+        assert!(crate::parse_str(doc.text()).is_ok());
+    }
+}
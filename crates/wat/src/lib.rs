@@ -36,6 +36,10 @@
 //! # }
 //! ```
 //!
+//! To work with the text of a file itself, for example to format it, see the
+//! concrete syntax tree in the [`cst`] module, which can be kept up to date
+//! as a file is edited with the [`incremental`] module.
+//!
 //! ## Evolution of the WAT Format
 //!
 //! WebAssembly, and the WAT format, are an evolving specification. Features are
@@ -45,7 +49,9 @@
 //!
 //! Future WebAssembly features will be accepted to this parser **and they will
 //! not require a feature gate to opt-in**. All implemented WebAssembly features
-//! will be enabled at all times. Using a future WebAssembly feature in the WAT
+//! will be enabled by default. Tools which need to reject text using some
+//! proposals, for example to match the configuration of a validator, can use
+//! [`parse_str_with_features`]. Using a future WebAssembly feature in the WAT
 //! format may cause breakage because while specifications are in development
 //! the WAT syntax (and/or binary encoding) will often change. This crate will
 //! do its best to keep up with these proposals, but breaking textual changes
@@ -73,6 +79,12 @@ use std::path::{Path, PathBuf};
 use std::str;
 use wast::parser::{self, ParseBuffer};
 
+pub mod cst;
+pub mod incremental;
+mod source_map;
+pub use source_map::{SourceMap, SourceMapping};
+pub use wast::parser::Features;
+
 /// Parses a file on disk as a [WebAssembly Text format][wat] file, or a binary
 /// WebAssembly file
 ///
@@ -221,6 +233,128 @@ fn _parse_str(wat: &str) -> Result<Vec<u8>> {
     Ok(ast.encode().map_err(|e| Error::cvt(e, wat))?)
 }
 
+/// Same as [`parse_str`], except that text using a WebAssembly proposal which
+/// isn't enabled in `features` fails to parse.
+///
+/// [`Features`] is the same type as `wasmparser::WasmFeatures`, so the same
+/// configuration can be used for both parsing and validation.
+///
+/// # Examples
+///
+/// ```
+/// use wat::Features;
+///
+/// let wat = "(module (func (result i32 i32) i32.const 0 i32.const 1))";
+/// assert!(wat::parse_str_with_features(wat, Features::all()).is_ok());
+///
+/// let features = Features {
+///     multi_value: false,
+///     ..Features::all()
+/// };
+/// let err = wat::parse_str_with_features(wat, features).unwrap_err();
+/// assert!(err.to_string().contains("feature `multi_value` not enabled"));
+/// ```
+pub fn parse_str_with_features(wat: impl AsRef<str>, features: Features) -> Result<Vec<u8>> {
+    _parse_str_with_features(wat.as_ref(), features)
+}
+
+fn _parse_str_with_features(wat: &str, features: Features) -> Result<Vec<u8>> {
+    let mut buf = ParseBuffer::new(wat).map_err(|e| Error::cvt(e, wat))?;
+    buf.features(features);
+    let mut ast = parser::parse::<wast::Wat>(&buf).map_err(|e| Error::cvt(e, wat))?;
+    ast.encode().map_err(|e| Error::cvt(e, wat))
+}
+
+/// Same as [`parse_file`], except that a [`SourceMap`] from the instructions
+/// of the returned binary back to the text of `file` is also returned.
+///
+/// The source map is additionally embedded in the returned binary as a
+/// `sourceMappingURL` custom section holding a `data:` URL, which debuggers
+/// can use to show the original text when stepping through the module.
+///
+/// If `file` is already a WebAssembly binary then it's returned verbatim
+/// with an empty source map. Instruction mappings are currently only produced
+/// for core wasm modules, not components.
+///
+/// # Examples
+///
+/// ```
+/// # fn foo() -> wat::Result<()> {
+/// let (binary, source_map) = wat::parse_file_with_source_map("./foo.wat")?;
+/// std::fs::write("./foo.wasm.map", source_map.to_json()).unwrap();
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_file_with_source_map(file: impl AsRef<Path>) -> Result<(Vec<u8>, SourceMap)> {
+    _parse_file_with_source_map(file.as_ref())
+}
+
+fn _parse_file_with_source_map(file: &Path) -> Result<(Vec<u8>, SourceMap)> {
+    let source = file.display().to_string();
+    let contents = std::fs::read(file).map_err(|err| Error {
+        kind: Box::new(ErrorKind::Io {
+            err,
+            file: Some(file.to_owned()),
+        }),
+    })?;
+    if contents.starts_with(b"\0asm") {
+        return Ok((contents, SourceMap::new(&source, "", &[])));
+    }
+    let result = match str::from_utf8(&contents) {
+        Ok(s) => _parse_str_with_source_map(&source, s),
+        Err(_) => Err(Error {
+            kind: Box::new(ErrorKind::Custom {
+                msg: "input bytes aren't valid utf-8".to_string(),
+                file: None,
+            }),
+        }),
+    };
+    result.map_err(|mut e| {
+        e.set_path(file);
+        e
+    })
+}
+
+/// Same as [`parse_str`], except that a [`SourceMap`] from the instructions
+/// of the returned binary back to `wat` is also returned.
+///
+/// The `source` is the name of the text file recorded in the source map. See
+/// [`parse_file_with_source_map`] for more information.
+///
+/// # Examples
+///
+/// ```
+/// # fn foo() -> wat::Result<()> {
+/// let wat = "(module (func (drop (i32.const 1))))";
+/// let (binary, source_map) = wat::parse_str_with_source_map("foo.wat", wat)?;
+///
+/// // `i32.const` is the first instruction, and is in column 20.
+/// assert_eq!(source_map.mappings[0].column, 20);
+/// assert_eq!(binary[source_map.mappings[0].binary_offset], 0x41);
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_str_with_source_map(
+    source: &str,
+    wat: impl AsRef<str>,
+) -> Result<(Vec<u8>, SourceMap)> {
+    _parse_str_with_source_map(source, wat.as_ref())
+}
+
+fn _parse_str_with_source_map(source: &str, wat: &str) -> Result<(Vec<u8>, SourceMap)> {
+    let mut buf = ParseBuffer::new(wat).map_err(|e| Error::cvt(e, wat))?;
+    buf.track_instr_spans(true);
+    let mut ast = parser::parse::<wast::Wat>(&buf).map_err(|e| Error::cvt(e, wat))?;
+    let (mut binary, offsets) = ast
+        .encode_with_instr_offsets()
+        .map_err(|e| Error::cvt(e, wat))?;
+    let source_map = SourceMap::new(source, wat, &offsets);
+    if !source_map.mappings.is_empty() {
+        binary.extend_from_slice(&source_map.custom_section());
+    }
+    Ok((binary, source_map))
+}
+
 /// A convenience type definition for `Result` where the error is [`Error`]
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -273,6 +407,25 @@ impl Error {
             ErrorKind::Io { file: f, .. } => *f = Some(file.to_owned()),
         }
     }
+
+    /// Returns the 0-based line and column within the text that this error
+    /// points at, if any.
+    pub fn line_col(&self) -> Option<(usize, usize)> {
+        match &*self.kind {
+            ErrorKind::Wast(e) => e.line_col(),
+            ErrorKind::Custom { .. } | ErrorKind::Io { .. } => None,
+        }
+    }
+
+    /// Returns the message describing this error, without the file name or
+    /// the snippet of text that it points at.
+    pub fn message(&self) -> String {
+        match &*self.kind {
+            ErrorKind::Wast(e) => e.message(),
+            ErrorKind::Custom { msg, .. } => msg.clone(),
+            ErrorKind::Io { err, .. } => err.to_string(),
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -329,5 +482,45 @@ mod test {
             e.to_string(),
             "expected valid module field\n     --> foo:1:2\n      |\n    1 | ()\n      |  ^"
         );
+        assert_eq!(e.message(), "expected valid module field");
+        assert_eq!(e.line_col(), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_source_map() {
+        let wat = r#"(module
+  (func (param i32) (result i32)
+    (if (result i32) (local.get 0)
+      (then (i32.const 1))
+      (else (i32.add (i32.const 2) (i32.const 3))))))"#;
+        let (binary, source_map) = parse_str_with_source_map("foo.wat", wat).unwrap();
+
+        let expected = [
+            // opcode, line, column
+            (0x20, 2, 22), // local.get
+            (0x04, 2, 5),  // if
+            (0x41, 3, 13), // i32.const 1
+            (0x05, 4, 7),  // else
+            (0x41, 4, 22), // i32.const 2
+            (0x41, 4, 36), // i32.const 3
+            (0x6a, 4, 13), // i32.add
+            (0x0b, 4, 50), // end of `if`
+        ];
+        let actual = source_map
+            .mappings
+            .iter()
+            .map(|m| (binary[m.binary_offset], m.line, m.column))
+            .collect::<Vec<_>>();
+        assert_eq!(actual, expected);
+
+        let section = source_map.custom_section();
+        assert!(binary.ends_with(&section));
+        assert_eq!(
+            parse_str(wat).unwrap(),
+            &binary[..binary.len() - section.len()]
+        );
+        assert!(source_map
+            .to_json()
+            .starts_with(r#"{"version":3,"sources":["foo.wat"],"names":[],"mappings":""#));
     }
 }
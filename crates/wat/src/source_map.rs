@@ -0,0 +1,210 @@
+use wast::token::Span;
+
+/// A mapping from the instructions of a WebAssembly binary back to the text
+/// they were parsed from.
+///
+/// This is returned by [`parse_str_with_source_map`](crate::parse_str_with_source_map)
+/// and [`parse_file_with_source_map`](crate::parse_file_with_source_map), and
+/// can be serialized as a [Source Map v3][spec] document with
+/// [`SourceMap::to_json`].
+///
+/// [spec]: https://sourcemaps.info/spec.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceMap {
+    /// The name of the text file that the binary was parsed from.
+    pub source: String,
+    /// The location in the text of each instruction in the binary, sorted by
+    /// offset in the binary.
+    pub mappings: Vec<SourceMapping>,
+}
+
+/// A single entry of a [`SourceMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceMapping {
+    /// The offset of the instruction in the binary.
+    pub binary_offset: usize,
+    /// The byte offset of the instruction in the text.
+    pub text_offset: usize,
+    /// The 0-based line of the instruction in the text.
+    pub line: usize,
+    /// The 0-based column, in bytes, of the instruction in the text.
+    pub column: usize,
+}
+
+impl SourceMap {
+    pub(crate) fn new(source: &str, text: &str, offsets: &[(usize, Span)]) -> SourceMap {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect::<Vec<_>>();
+        let mappings = offsets
+            .iter()
+            .map(|(binary_offset, span)| {
+                let text_offset = span.offset();
+                let line = match line_starts.binary_search(&text_offset) {
+                    Ok(line) => line,
+                    Err(next) => next - 1,
+                };
+                SourceMapping {
+                    binary_offset: *binary_offset,
+                    text_offset,
+                    line,
+                    column: text_offset - line_starts[line],
+                }
+            })
+            .collect();
+        SourceMap {
+            source: source.to_string(),
+            mappings,
+        }
+    }
+
+    /// Serializes this source map as a [Source Map v3][spec] JSON document.
+    ///
+    /// Following the convention for WebAssembly source maps the binary is
+    /// treated as a single line where the column of each instruction is its
+    /// offset in the binary.
+    ///
+    /// [spec]: https://sourcemaps.info/spec.html
+    pub fn to_json(&self) -> String {
+        let mut mappings = String::new();
+        let mut prev = SourceMapping {
+            binary_offset: 0,
+            text_offset: 0,
+            line: 0,
+            column: 0,
+        };
+        for (i, mapping) in self.mappings.iter().enumerate() {
+            if i > 0 {
+                mappings.push(',');
+            }
+            vlq(
+                &mut mappings,
+                delta(mapping.binary_offset, prev.binary_offset),
+            );
+            vlq(&mut mappings, 0);
+            vlq(&mut mappings, delta(mapping.line, prev.line));
+            vlq(&mut mappings, delta(mapping.column, prev.column));
+            prev = *mapping;
+        }
+
+        let mut json = String::from("{\"version\":3,\"sources\":[");
+        json_string(&mut json, &self.source);
+        json.push_str("],\"names\":[],\"mappings\":\"");
+        json.push_str(&mappings);
+        json.push_str("\"}");
+        json
+    }
+
+    /// Returns the contents of a `sourceMappingURL` custom section which
+    /// embeds this source map as a `data:` URL.
+    pub(crate) fn custom_section(&self) -> Vec<u8> {
+        let url = format!(
+            "data:application/json;base64,{}",
+            base64(self.to_json().as_bytes())
+        );
+        let mut section = Vec::new();
+        leb128(&mut section, "sourceMappingURL".len());
+        section.extend_from_slice(b"sourceMappingURL");
+        leb128(&mut section, url.len());
+        section.extend_from_slice(url.as_bytes());
+
+        let mut ret = vec![0];
+        leb128(&mut ret, section.len());
+        ret.extend_from_slice(&section);
+        ret
+    }
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn delta(cur: usize, prev: usize) -> i64 {
+    cur as i64 - prev as i64
+}
+
+fn vlq(dst: &mut String, value: i64) {
+    let mut value = if value < 0 {
+        ((-value) << 1) | 1
+    } else {
+        value << 1
+    };
+    loop {
+        let mut digit = value & 0x1f;
+        value >>= 5;
+        if value > 0 {
+            digit |= 0x20;
+        }
+        dst.push(BASE64[digit as usize] as char);
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut ret = String::new();
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                ret.push(BASE64[(n >> (18 - 6 * i)) as usize & 0x3f] as char);
+            } else {
+                ret.push('=');
+            }
+        }
+    }
+    ret
+}
+
+fn json_string(dst: &mut String, s: &str) {
+    dst.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => dst.push_str("\\\""),
+            '\\' => dst.push_str("\\\\"),
+            c if (c as u32) < 0x20 => dst.push_str(&format!("\\u{:04x}", c as u32)),
+            c => dst.push(c),
+        }
+    }
+    dst.push('"');
+}
+
+fn leb128(dst: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            dst.push(byte);
+            break;
+        }
+        dst.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn vlq_encoding() {
+        let mut s = String::new();
+        for v in [0, 1, -1, 15, 16, -16, 1000] {
+            vlq(&mut s, v);
+            s.push(' ');
+        }
+        assert_eq!(s, "A C D e gB hB w+B ");
+    }
+
+    #[test]
+    fn base64_encoding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+    }
+}
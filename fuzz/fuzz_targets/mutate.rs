@@ -44,8 +44,26 @@ fuzz_target!(|bytes: &[u8]| {
     // Mutate the Wasm with `wasm-mutate`. Assert that each mutation is still
     // valid Wasm.
 
+    // Note that on-by-default features in wasmparser are not disabled here if
+    // the feature was disabled in `config` when the module was generated. For
+    // example if the input module doesn't have simd then wasm-mutate may
+    // produce a module that uses simd, which is ok and expected.
+    //
+    // Otherwise only forward some off-by-default features which are affected by
+    // wasm-smith's generation of modules and wasm-mutate otherwise won't add
+    // itself if it doesn't already exist.
+    let features = WasmFeatures {
+        relaxed_simd: config.relaxed_simd_enabled,
+        multi_memory: config.max_memories > 1,
+        memory64: config.memory64_enabled,
+        threads: config.threads_enabled,
+        tail_call: config.tail_call_enabled,
+        ..WasmFeatures::default()
+    };
+
     let mut wasm_mutate = wasm_mutate::WasmMutate::default();
     wasm_mutate.seed(seed);
+    wasm_mutate.features(features);
     wasm_mutate.fuel(300);
     wasm_mutate.preserve_semantics(
         // If we are going to check that we get the same evaluated results
@@ -61,20 +79,6 @@ fuzz_target!(|bytes: &[u8]| {
         }
     };
 
-    // Note that on-by-default features in wasmparser are not disabled here if
-    // the feature was disabled in `config` when the module was generated. For
-    // example if the input module doesn't have simd then wasm-mutate may
-    // produce a module that uses simd, which is ok and expected.
-    //
-    // Otherwise only forward some off-by-default features which are affected by
-    // wasm-smith's generation of modules and wasm-mutate otherwise won't add
-    // itself if it doesn't already exist.
-    let mut features = WasmFeatures::default();
-    features.relaxed_simd = config.relaxed_simd_enabled;
-    features.multi_memory = config.max_memories > 1;
-    features.memory64 = config.memory64_enabled;
-    features.threads = config.threads_enabled;
-
     for (i, mutated_wasm) in iterator.take(10).enumerate() {
         let mutated_wasm = match mutated_wasm {
             Ok(w) => w,
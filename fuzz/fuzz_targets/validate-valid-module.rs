@@ -2,6 +2,7 @@
 
 use arbitrary::Unstructured;
 use libfuzzer_sys::fuzz_target;
+use wasm_smith::Config;
 
 // Define a fuzz target that accepts arbitrary
 // `Module`s or `Component`s as input.
@@ -36,19 +37,11 @@ fuzz_target!(|data: &[u8]| {
     };
 
     // Validate the module or component and assert that it passes validation.
-    let mut validator = wasmparser::Validator::new_with_features(wasmparser::WasmFeatures {
+    let features = wasmparser::WasmFeatures {
         component_model: generate_component,
-        multi_value: config.multi_value_enabled,
-        multi_memory: config.max_memories > 1,
-        bulk_memory: true,
-        reference_types: true,
-        simd: config.simd_enabled,
-        relaxed_simd: config.relaxed_simd_enabled,
-        memory64: config.memory64_enabled,
-        threads: config.threads_enabled,
-        exceptions: config.exceptions_enabled,
-        ..wasmparser::WasmFeatures::default()
-    });
+        ..config.features()
+    };
+    let mut validator = wasmparser::Validator::new_with_features(features);
     if let Err(e) = validator.validate_all(&wasm_bytes) {
         let component_or_module = if generate_component {
             "component"
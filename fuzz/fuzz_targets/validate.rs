@@ -2,7 +2,7 @@
 
 use arbitrary::{Arbitrary, Unstructured};
 use libfuzzer_sys::*;
-use wasm_smith::MaybeInvalidModule;
+use wasm_smith::{InvalidityClass, MaybeInvalidModule};
 use wasmparser::{Validator, WasmFeatures};
 
 fuzz_target!(|data: &[u8]| {
@@ -25,6 +25,7 @@ fuzz_target!(|data: &[u8]| {
         threads: (byte1 & 0b0000_0100) != 0,
         simd: (byte1 & 0b0000_1000) != 0,
         component_model: (byte1 & 0b0001_0000) != 0,
+        component_model_async: (byte3 & 0b0000_0010) != 0,
         tail_call: (byte1 & 0b0010_0000) != 0,
         bulk_memory: (byte1 & 0b0100_0000) != 0,
         deterministic_only: (byte1 & 0b1000_0000) != 0,
@@ -36,8 +37,12 @@ fuzz_target!(|data: &[u8]| {
         mutable_global: (byte2 & 0b0010_0000) != 0,
         saturating_float_to_int: (byte2 & 0b0100_0000) != 0,
         sign_extension: (byte2 & 0b1000_0000) != 0,
+        // The validator doesn't support these yet.
+        function_references: false,
+        gc: false,
     });
     let use_maybe_invalid = byte3 & 0b0000_0001 != 0;
+    let use_invalidity_class = byte3 & 0b0000_0100 != 0;
 
     let wasm = &data[3..];
     if log::log_enabled!(log::Level::Debug) {
@@ -46,7 +51,19 @@ fuzz_target!(|data: &[u8]| {
     }
     if use_maybe_invalid {
         let mut u = Unstructured::new(wasm);
-        if let Ok(module) = MaybeInvalidModule::arbitrary(&mut u) {
+        let module = if use_invalidity_class {
+            MaybeInvalidModule::with_invalidity(
+                &mut u,
+                &[
+                    InvalidityClass::TypeError,
+                    InvalidityClass::MalformedLeb,
+                    InvalidityClass::OutOfRangeIndex,
+                ],
+            )
+        } else {
+            MaybeInvalidModule::arbitrary(&mut u)
+        };
+        if let Ok(module) = module {
             drop(validator.validate_all(&module.to_bytes()));
         }
     } else {
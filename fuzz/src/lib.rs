@@ -15,6 +15,7 @@ pub fn generate_valid_module(
     config.memory64_enabled = u.arbitrary()?;
     config.threads_enabled = u.arbitrary()?;
     config.exceptions_enabled = u.arbitrary()?;
+    config.tail_call_enabled = u.arbitrary()?;
     config.canonicalize_nans = u.arbitrary()?;
 
     configure(&mut config, u)?;
@@ -26,6 +26,7 @@ use std::time::Duration;
 
 // Crates we care about publishing sorted topologically.
 const CRATES_TO_PUBLISH: &[&str] = &[
+    "wasm-features",
     "wasmparser",
     "wasm-encoder",
     "wasmprinter",
@@ -35,6 +36,8 @@ const CRATES_TO_PUBLISH: &[&str] = &[
     "wasm-smith",
     "wasm-mutate",
     "wasm-shrink",
+    "wasm-metadata",
+    "json-from-wast",
     "wasm-tools",
     "wasm-compose",
 ];
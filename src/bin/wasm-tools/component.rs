@@ -0,0 +1,468 @@
+use anyhow::{bail, Context, Result};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use wasmparser::types::{ComponentEntityType, Types};
+use wasmparser::{
+    ComponentAlias, ComponentExternalKind, ComponentOuterAliasKind, ComponentTypeRef, Encoding,
+    ExternalKind, Instance, Parser, Payload::*, Validator, WasmFeatures,
+};
+
+/// WebAssembly component model tools.
+#[derive(clap::Parser)]
+pub struct Opts {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    SemverCheck(SemverCheckOpts),
+    Unbundle(UnbundleOpts),
+}
+
+impl Opts {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            Command::SemverCheck(opts) => opts.run(),
+            Command::Unbundle(opts) => opts.run(),
+        }
+    }
+}
+
+/// Checks whether the changes between two versions of a component are
+/// semver-major, minor, or patch changes.
+///
+/// The imports and exports of the two components are compared by name and
+/// type. Removing or incompatibly changing an export, or adding or
+/// incompatibly changing an import, is a major change. Adding exports and
+/// removing imports, or changing their types compatibly, is a minor change.
+/// Anything else is a patch change.
+///
+/// The process exits with a failure if there are major changes, so that this
+/// can be used to block incompatible releases.
+#[derive(clap::Parser)]
+pub struct SemverCheckOpts {
+    /// The old version of the component, or `-` to read it from stdin.
+    old: PathBuf,
+
+    /// The new version of the component, or `-` to read it from stdin.
+    new: PathBuf,
+
+    /// Don't fail if there are major changes, just report them.
+    #[clap(long)]
+    allow_major: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Semver {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for Semver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Semver::Patch => "patch",
+            Semver::Minor => "minor",
+            Semver::Major => "major",
+        })
+    }
+}
+
+/// The imports and exports of a component, and their types.
+struct Interface {
+    types: Types,
+    imports: Vec<(String, ComponentEntityType)>,
+    exports: Vec<(String, ComponentEntityType)>,
+}
+
+impl Interface {
+    fn new(path: &Path) -> Result<Interface> {
+        let wasm = wasm_tools::parse_wasm_path(path)?;
+        Interface::parse(&wasm).with_context(|| format!("failed to parse `{}`", path.display()))
+    }
+
+    fn parse(wasm: &[u8]) -> Result<Interface> {
+        let mut validator = Validator::new_with_features(WasmFeatures {
+            component_model: true,
+            ..Default::default()
+        });
+        let types = validator.validate_all(wasm)?;
+        let mut imports = Vec::new();
+        let mut exports = Vec::new();
+        let missing = || anyhow::anyhow!("failed to get the type of an import or export");
+
+        // Only the outermost component's imports and exports are part of its
+        // interface.
+        let mut depth = 0;
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload? {
+                Version { encoding, .. } => {
+                    if depth == 0 && encoding != Encoding::Component {
+                        bail!("input is a core module, not a component");
+                    }
+                    depth += 1;
+                }
+                End(_) => depth -= 1,
+                _ if depth > 1 => {}
+                ComponentImportSection(reader) => {
+                    for import in reader {
+                        let import = import?;
+                        let ty = types
+                            .component_entity_type_from_import(&import)
+                            .ok_or_else(missing)?;
+                        imports.push((import.name.to_string(), ty));
+                    }
+                }
+                ComponentExportSection(reader) => {
+                    for export in reader {
+                        let export = export?;
+                        let ty = types
+                            .component_entity_type_from_export(&export)
+                            .ok_or_else(missing)?;
+                        exports.push((export.name.to_string(), ty));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Interface {
+            types,
+            imports,
+            exports,
+        })
+    }
+}
+
+/// Compare the given items of the old and new components, returning each
+/// change and how significant it is.
+///
+/// For exports, the new types must be subtypes of the old ones, since users
+/// of the component expect the old exports. For imports it's the other way
+/// around, since whoever instantiates the component provides the old
+/// imports.
+fn compare(
+    what: &str,
+    old: &Interface,
+    old_items: &[(String, ComponentEntityType)],
+    new: &Interface,
+    new_items: &[(String, ComponentEntityType)],
+    exports: bool,
+) -> Vec<(Semver, String)> {
+    let (removed, added) = if exports {
+        (Semver::Major, Semver::Minor)
+    } else {
+        (Semver::Minor, Semver::Major)
+    };
+    let (old_types, new_types) = (old.types.as_ref(), new.types.as_ref());
+    let mut changes = Vec::new();
+    for (name, old_ty) in old_items {
+        let new_ty = match new_items.iter().find(|(n, _)| n == name) {
+            Some((_, ty)) => ty,
+            None => {
+                changes.push((removed, format!("{} `{}` was removed", what, name)));
+                continue;
+            }
+        };
+        let old_sub_new = ComponentEntityType::is_subtype_of(old_ty, old_types, new_ty, new_types);
+        let new_sub_old = ComponentEntityType::is_subtype_of(new_ty, new_types, old_ty, old_types);
+        let compatible = if exports { new_sub_old } else { old_sub_new };
+        if !compatible {
+            let change = format!("{} `{}` changed type incompatibly", what, name);
+            changes.push((Semver::Major, change));
+        } else if !(old_sub_new && new_sub_old) {
+            let change = format!("{} `{}` changed type compatibly", what, name);
+            changes.push((Semver::Minor, change));
+        }
+    }
+    for (name, _) in new_items {
+        if !old_items.iter().any(|(n, _)| n == name) {
+            changes.push((added, format!("{} `{}` was added", what, name)));
+        }
+    }
+    changes
+}
+
+impl SemverCheckOpts {
+    fn run(&self) -> Result<()> {
+        wasm_tools::check_stdin_once([self.old.as_path(), &self.new])?;
+        let old = Interface::new(&self.old)?;
+        let new = Interface::new(&self.new)?;
+
+        let mut changes = compare("export", &old, &old.exports, &new, &new.exports, true);
+        changes.extend(compare(
+            "import",
+            &old,
+            &old.imports,
+            &new,
+            &new.imports,
+            false,
+        ));
+        changes.sort_by_key(|(semver, _)| std::cmp::Reverse(*semver));
+
+        for (semver, change) in &changes {
+            println!("{}: {}", semver, change);
+        }
+        let semver = changes
+            .iter()
+            .map(|(semver, _)| *semver)
+            .max()
+            .unwrap_or(Semver::Patch);
+        println!("semver: {}", semver);
+
+        if semver == Semver::Major && !self.allow_major {
+            bail!("the new component has semver-major changes");
+        }
+        Ok(())
+    }
+}
+
+/// Extracts the core modules and nested components of a component into a
+/// directory.
+///
+/// Each core module defined at the top level of the component, including
+/// adapter modules, is written to `module{N}.wasm`, and each nested component
+/// to `component{N}.wasm`, where `N` is its index within its index space. A
+/// `manifest.json` describes every module, including imported and aliased
+/// ones, and how the component instantiates them.
+#[derive(clap::Parser)]
+pub struct UnbundleOpts {
+    #[clap(flatten)]
+    input: wasm_tools::InputArg,
+
+    /// The directory to extract the component into, which is created if it
+    /// doesn't exist.
+    #[clap(long, value_name = "DIR")]
+    out_dir: PathBuf,
+}
+
+#[derive(Default, serde::Serialize)]
+struct Manifest {
+    /// The core module index space of the component.
+    modules: Vec<ManifestItem>,
+    /// The component index space of the component.
+    components: Vec<ManifestItem>,
+    /// The core instance index space of the component.
+    core_instances: Vec<CoreInstance>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "kebab-case", tag = "kind")]
+enum ManifestItem {
+    /// Defined by the component, and extracted to `file`.
+    Defined {
+        file: String,
+        size: usize,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        imports: Vec<String>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        exports: Vec<String>,
+    },
+    /// Imported by the component.
+    Import { name: String },
+    /// Aliased from the export of a component instance.
+    InstanceExport { instance: u32, name: String },
+    /// Aliased from an enclosing component.
+    Outer { count: u32, index: u32 },
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "kebab-case", tag = "kind")]
+enum CoreInstance {
+    /// Instantiates a module with the given instances as its imports.
+    Instantiate {
+        module: u32,
+        args: Vec<CoreInstanceArg>,
+    },
+    /// Bundles up core items defined in the component.
+    FromExports { exports: Vec<CoreInstanceExport> },
+}
+
+#[derive(serde::Serialize)]
+struct CoreInstanceArg {
+    name: String,
+    instance: u32,
+}
+
+#[derive(serde::Serialize)]
+struct CoreInstanceExport {
+    name: String,
+    kind: &'static str,
+    index: u32,
+}
+
+impl UnbundleOpts {
+    fn run(&self) -> Result<()> {
+        let wasm = self.input.parse_wasm()?;
+        std::fs::create_dir_all(&self.out_dir)
+            .with_context(|| format!("failed to create `{}`", self.out_dir.display()))?;
+
+        let mut manifest = Manifest::default();
+        let mut depth = 0;
+        for payload in Parser::new(0).parse_all(&wasm) {
+            match payload? {
+                Version { encoding, .. } => {
+                    if depth == 0 && encoding != Encoding::Component {
+                        bail!("input is a core module, not a component");
+                    }
+                    depth += 1;
+                }
+                End(_) => depth -= 1,
+                _ if depth > 1 => {}
+
+                ModuleSection { range, .. } => {
+                    let file = format!("module{}.wasm", manifest.modules.len());
+                    let (imports, exports) = module_interface(&wasm[range.clone()])?;
+                    self.write(&file, &wasm[range.clone()])?;
+                    manifest.modules.push(ManifestItem::Defined {
+                        file,
+                        size: range.len(),
+                        imports,
+                        exports,
+                    });
+                }
+                ComponentSection { range, .. } => {
+                    let file = format!("component{}.wasm", manifest.components.len());
+                    self.write(&file, &wasm[range.clone()])?;
+                    manifest.components.push(ManifestItem::Defined {
+                        file,
+                        size: range.len(),
+                        imports: Vec::new(),
+                        exports: Vec::new(),
+                    });
+                }
+                ComponentImportSection(s) => {
+                    for import in s {
+                        let import = import?;
+                        let name = import.name.to_string();
+                        match import.ty {
+                            ComponentTypeRef::Module(_) => {
+                                manifest.modules.push(ManifestItem::Import { name })
+                            }
+                            ComponentTypeRef::Component(_) => {
+                                manifest.components.push(ManifestItem::Import { name })
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                ComponentAliasSection(s) => {
+                    for alias in s {
+                        let (space, item) = match alias? {
+                            ComponentAlias::InstanceExport {
+                                kind: ComponentExternalKind::Module,
+                                instance_index,
+                                name,
+                            } => (
+                                &mut manifest.modules,
+                                ManifestItem::InstanceExport {
+                                    instance: instance_index,
+                                    name: name.to_string(),
+                                },
+                            ),
+                            ComponentAlias::InstanceExport {
+                                kind: ComponentExternalKind::Component,
+                                instance_index,
+                                name,
+                            } => (
+                                &mut manifest.components,
+                                ManifestItem::InstanceExport {
+                                    instance: instance_index,
+                                    name: name.to_string(),
+                                },
+                            ),
+                            ComponentAlias::Outer {
+                                kind: ComponentOuterAliasKind::CoreModule,
+                                count,
+                                index,
+                            } => (&mut manifest.modules, ManifestItem::Outer { count, index }),
+                            ComponentAlias::Outer {
+                                kind: ComponentOuterAliasKind::Component,
+                                count,
+                                index,
+                            } => (
+                                &mut manifest.components,
+                                ManifestItem::Outer { count, index },
+                            ),
+                            _ => continue,
+                        };
+                        space.push(item);
+                    }
+                }
+                InstanceSection(s) => {
+                    for instance in s {
+                        manifest.core_instances.push(match instance? {
+                            Instance::Instantiate { module_index, args } => {
+                                CoreInstance::Instantiate {
+                                    module: module_index,
+                                    args: args
+                                        .iter()
+                                        .map(|arg| CoreInstanceArg {
+                                            name: arg.name.to_string(),
+                                            instance: arg.index,
+                                        })
+                                        .collect(),
+                                }
+                            }
+                            Instance::FromExports(exports) => CoreInstance::FromExports {
+                                exports: exports
+                                    .iter()
+                                    .map(|export| CoreInstanceExport {
+                                        name: export.name.to_string(),
+                                        kind: external_kind(export.kind),
+                                        index: export.index,
+                                    })
+                                    .collect(),
+                            },
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let manifest = serde_json::to_string_pretty(&manifest)?;
+        self.write("manifest.json", manifest.as_bytes())
+    }
+
+    fn write(&self, file: &str, contents: &[u8]) -> Result<()> {
+        let path = self.out_dir.join(file);
+        std::fs::write(&path, contents)
+            .with_context(|| format!("failed to write `{}`", path.display()))
+    }
+}
+
+/// Get the names of the imports and exports of a core module.
+fn module_interface(wasm: &[u8]) -> Result<(Vec<String>, Vec<String>)> {
+    let mut imports = Vec::new();
+    let mut exports = Vec::new();
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload? {
+            ImportSection(s) => {
+                for import in s {
+                    let import = import?;
+                    imports.push(format!("{}::{}", import.module, import.name));
+                }
+            }
+            ExportSection(s) => {
+                for export in s {
+                    exports.push(export?.name.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((imports, exports))
+}
+
+fn external_kind(kind: ExternalKind) -> &'static str {
+    match kind {
+        ExternalKind::Func => "func",
+        ExternalKind::Table => "table",
+        ExternalKind::Memory => "memory",
+        ExternalKind::Global => "global",
+        ExternalKind::Tag => "tag",
+    }
+}
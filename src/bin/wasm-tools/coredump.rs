@@ -0,0 +1,412 @@
+use addr2line::gimli::{self, EndianSlice, LittleEndian};
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use wasmparser::{
+    CoreDumpInstance, CoreDumpInstancesSection, CoreDumpModulesSection, CoreDumpSection,
+    CoreDumpStackSection, CoreDumpValue, DataKind, Encoding, GlobalType, MemoryType, Name,
+    NameSectionReader, Operator, Parser, Payload::*, TypeRef, ValType,
+};
+
+/// WebAssembly coredump tools.
+#[derive(clap::Parser)]
+pub struct Opts {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    Inspect(InspectOpts),
+}
+
+impl Opts {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            Command::Inspect(opts) => opts.run(),
+        }
+    }
+}
+
+/// Prints the contents of a WebAssembly coredump.
+///
+/// Coredumps are described by the WebAssembly tool conventions: the stack of
+/// each thread, the modules and instances that were running, and the state of
+/// memories and globals at the time of the crash.
+///
+/// Frames are printed with the names of their functions and locals if the
+/// modules that were running are given with `--module`, and with source
+/// locations if those modules also contain DWARF debugging information.
+#[derive(clap::Parser)]
+pub struct InspectOpts {
+    /// The coredump to inspect, or `-` to read it from stdin.
+    coredump: PathBuf,
+
+    /// A module that was running when the coredump was taken.
+    ///
+    /// This may be passed multiple times, in the order in which the modules
+    /// are listed in the coredump.
+    #[clap(long = "module", value_name = "PATH")]
+    modules: Vec<PathBuf>,
+}
+
+impl InspectOpts {
+    pub fn run(&self) -> Result<()> {
+        let paths = std::iter::once(&self.coredump).chain(&self.modules);
+        wasm_tools::check_stdin_once(paths.map(|p| p.as_path()))?;
+        let wasm = wasm_tools::parse_wasm_path(&self.coredump)?;
+        let dump = CoreDump::new(&wasm).context("failed to parse coredump")?;
+
+        let inputs = self
+            .modules
+            .iter()
+            .map(|path| wasm_tools::parse_wasm_path(path))
+            .collect::<Result<Vec<_>>>()?;
+        let modules = inputs
+            .iter()
+            .zip(&self.modules)
+            .map(|(wasm, path)| {
+                Module::new(wasm).with_context(|| format!("failed to parse `{}`", path.display()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if !dump.modules.is_empty() && modules.len() > dump.modules.len() {
+            bail!(
+                "{} modules were given but the coredump only lists {}",
+                modules.len(),
+                dump.modules.len()
+            );
+        }
+
+        let stdout = std::io::stdout();
+        let mut out = stdout.lock();
+        dump.print(&mut out, &modules)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// The contents of a coredump.
+struct CoreDump<'a> {
+    process: Option<&'a str>,
+    modules: Vec<&'a str>,
+    instances: Vec<CoreDumpInstance>,
+    threads: Vec<CoreDumpStackSection<'a>>,
+    memories: Vec<MemoryType>,
+    /// The number of bytes, and of non-zero bytes, in the data segments of
+    /// each memory.
+    data: HashMap<u32, (usize, usize)>,
+    globals: Vec<(GlobalType, Option<CoreDumpValue>)>,
+}
+
+impl<'a> CoreDump<'a> {
+    fn new(wasm: &'a [u8]) -> Result<CoreDump<'a>> {
+        let mut dump = CoreDump {
+            process: None,
+            modules: Vec::new(),
+            instances: Vec::new(),
+            threads: Vec::new(),
+            memories: Vec::new(),
+            data: HashMap::new(),
+            globals: Vec::new(),
+        };
+        let mut found = false;
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload? {
+                Version {
+                    encoding: Encoding::Component,
+                    ..
+                } => bail!("coredumps must be core modules, not components"),
+                ImportSection(s) => {
+                    for import in s {
+                        match import?.ty {
+                            TypeRef::Memory(ty) => dump.memories.push(ty),
+                            TypeRef::Global(ty) => dump.globals.push((ty, None)),
+                            _ => {}
+                        }
+                    }
+                }
+                MemorySection(s) => {
+                    for memory in s {
+                        dump.memories.push(memory?);
+                    }
+                }
+                GlobalSection(s) => {
+                    for global in s {
+                        let global = global?;
+                        let value = const_value(global.init_expr.get_operators_reader().read()?);
+                        dump.globals.push((global.ty, value));
+                    }
+                }
+                DataSection(s) => {
+                    for data in s {
+                        let data = data?;
+                        if let DataKind::Active { memory_index, .. } = data.kind {
+                            let (total, non_zero) = dump.data.entry(memory_index).or_default();
+                            *total += data.data.len();
+                            *non_zero += data.data.iter().filter(|b| **b != 0).count();
+                        }
+                    }
+                }
+                CustomSection(c) => {
+                    let (data, offset) = (c.data(), c.data_offset());
+                    match c.name() {
+                        "core" => {
+                            found = true;
+                            dump.process = Some(CoreDumpSection::new(data, offset)?.name);
+                        }
+                        "coremodules" => {
+                            dump.modules = CoreDumpModulesSection::new(data, offset)?.modules;
+                        }
+                        "coreinstances" => {
+                            dump.instances = CoreDumpInstancesSection::new(data, offset)?.instances;
+                        }
+                        "corestack" => dump.threads.push(CoreDumpStackSection::new(data, offset)?),
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !found {
+            bail!("input has no `core` custom section");
+        }
+        Ok(dump)
+    }
+
+    fn print(&self, out: &mut dyn Write, modules: &[Module]) -> Result<()> {
+        writeln!(out, "process: {}", self.process.unwrap_or_default())?;
+
+        for thread in &self.threads {
+            writeln!(out)?;
+            writeln!(out, "thread `{}`:", thread.name)?;
+            for (i, frame) in thread.frames.iter().enumerate() {
+                let module_index = self
+                    .instances
+                    .get(frame.instance_index as usize)
+                    .map(|i| i.module_index);
+                let module = module_index.and_then(|i| modules.get(i as usize));
+                let func = match module.and_then(|m| m.func_names.get(&frame.func_index)) {
+                    Some(name) => format!("`{}` (function {})", name, frame.func_index),
+                    None => format!("function {}", frame.func_index),
+                };
+                writeln!(
+                    out,
+                    "  #{:<3} instance {}, {} at offset {:#x}",
+                    i, frame.instance_index, func, frame.code_offset
+                )?;
+                if let Some(location) = module.and_then(|m| m.location(frame.func_index, frame.code_offset)) {
+                    writeln!(out, "        at {}", location)?;
+                }
+                for (j, value) in frame.locals.iter().enumerate() {
+                    let name = module.and_then(|m| m.local_names.get(&(frame.func_index, j as u32)));
+                    match name {
+                        Some(name) => writeln!(out, "        local {} `{}`: {}", j, name, Value(*value))?,
+                        None => writeln!(out, "        local {}: {}", j, Value(*value))?,
+                    }
+                }
+                if !frame.stack.is_empty() {
+                    let stack: Vec<_> = frame.stack.iter().map(|v| Value(*v).to_string()).collect();
+                    writeln!(out, "        stack: [{}]", stack.join(", "))?;
+                }
+            }
+        }
+
+        if !self.modules.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "modules:")?;
+            for (i, name) in self.modules.iter().enumerate() {
+                writeln!(out, "  {}: {}", i, name)?;
+            }
+        }
+
+        if !self.instances.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "instances:")?;
+            for (i, instance) in self.instances.iter().enumerate() {
+                writeln!(
+                    out,
+                    "  {}: module {}, memories {:?}, globals {:?}",
+                    i, instance.module_index, instance.memories, instance.globals
+                )?;
+            }
+        }
+
+        if !self.memories.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "memories:")?;
+            for (i, memory) in self.memories.iter().enumerate() {
+                let (total, non_zero) = self.data.get(&(i as u32)).copied().unwrap_or_default();
+                writeln!(
+                    out,
+                    "  {}: {} page(s), {} byte(s) of data of which {} are non-zero",
+                    i, memory.initial, total, non_zero
+                )?;
+            }
+        }
+
+        if !self.globals.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "globals:")?;
+            for (i, (ty, value)) in self.globals.iter().enumerate() {
+                let mutability = if ty.mutable { "mut " } else { "" };
+                match value {
+                    Some(value) => writeln!(out, "  {}: {}{}", i, mutability, Value(*value))?,
+                    None => writeln!(out, "  {}: {}{}", i, mutability, val_type(ty.content_type))?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The value of a global's initializer, if it's a constant.
+fn const_value(op: Operator) -> Option<CoreDumpValue> {
+    Some(match op {
+        Operator::I32Const { value } => CoreDumpValue::I32(value),
+        Operator::I64Const { value } => CoreDumpValue::I64(value),
+        Operator::F32Const { value } => CoreDumpValue::F32(value),
+        Operator::F64Const { value } => CoreDumpValue::F64(value),
+        _ => return None,
+    })
+}
+
+fn val_type(ty: ValType) -> &'static str {
+    match ty {
+        ValType::I32 => "i32",
+        ValType::I64 => "i64",
+        ValType::F32 => "f32",
+        ValType::F64 => "f64",
+        ValType::V128 => "v128",
+        ValType::FuncRef => "funcref",
+        ValType::ExternRef => "externref",
+    }
+}
+
+/// A module that was running when the coredump was taken, used to symbolicate
+/// frames.
+struct Module<'a> {
+    func_names: HashMap<u32, String>,
+    /// Names of locals, keyed by function and local index.
+    local_names: HashMap<(u32, u32), String>,
+    /// The offset of each function body, keyed by function index, relative to
+    /// the start of the code section's contents, which is what DWARF addresses
+    /// are relative to.
+    bodies: HashMap<u32, u64>,
+    dwarf: Option<addr2line::Context<EndianSlice<'a, LittleEndian>>>,
+}
+
+impl<'a> Module<'a> {
+    fn new(wasm: &'a [u8]) -> Result<Module<'a>> {
+        let mut module = Module {
+            func_names: HashMap::new(),
+            local_names: HashMap::new(),
+            bodies: HashMap::new(),
+            dwarf: None,
+        };
+        let mut func_index = 0;
+        let mut code_start = 0;
+        let mut debug_sections = HashMap::new();
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload? {
+                Version {
+                    encoding: Encoding::Component,
+                    ..
+                } => bail!("coredumps can only be symbolicated with core modules"),
+                ImportSection(s) => {
+                    for import in s {
+                        if let TypeRef::Func(_) = import?.ty {
+                            func_index += 1;
+                        }
+                    }
+                }
+                CodeSectionStart { range, .. } => code_start = range.start,
+                CodeSectionEntry(body) => {
+                    let offset = body.range().start - code_start;
+                    module.bodies.insert(func_index, offset as u64);
+                    func_index += 1;
+                }
+                CustomSection(c) => match c.name() {
+                    "name" => {
+                        // Names are best effort; a malformed name section just
+                        // means that functions are referred to by index.
+                        let _ = module.read_names(NameSectionReader::new(c.data(), c.data_offset()));
+                    }
+                    name if name.starts_with(".debug_") => {
+                        debug_sections.insert(name, c.data());
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        if !debug_sections.is_empty() {
+            let dwarf = gimli::Dwarf::load(|id| -> Result<_, gimli::Error> {
+                let data = debug_sections.get(id.name()).copied().unwrap_or_default();
+                Ok(EndianSlice::new(data, LittleEndian))
+            })?;
+            module.dwarf = Some(addr2line::Context::from_dwarf(dwarf)?);
+        }
+        Ok(module)
+    }
+
+    fn read_names(&mut self, reader: wasmparser::Result<NameSectionReader>) -> wasmparser::Result<()> {
+        let mut reader = reader?;
+        while !reader.eof() {
+            match reader.read()? {
+                Name::Function(map) => {
+                    let mut map = map.get_map()?;
+                    for _ in 0..map.get_count() {
+                        let naming = map.read()?;
+                        self.func_names.insert(naming.index, naming.name.to_string());
+                    }
+                }
+                Name::Local(map) => {
+                    let mut map = map.get_indirect_map()?;
+                    for _ in 0..map.get_indirect_count() {
+                        let func = map.read()?;
+                        let mut names = func.get_map()?;
+                        for _ in 0..names.get_count() {
+                            let naming = names.read()?;
+                            let key = (func.indirect_index, naming.index);
+                            self.local_names.insert(key, naming.name.to_string());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// The source location of the instruction at `code_offset` within the
+    /// function `func`, if there's DWARF for it.
+    fn location(&self, func: u32, code_offset: u32) -> Option<String> {
+        let dwarf = self.dwarf.as_ref()?;
+        let address = self.bodies.get(&func)? + u64::from(code_offset);
+        let location = dwarf.find_location(address).ok()??;
+        let mut result = location.file?.to_string();
+        if let Some(line) = location.line {
+            result.push_str(&format!(":{}", line));
+            if let Some(column) = location.column {
+                result.push_str(&format!(":{}", column));
+            }
+        }
+        Some(result)
+    }
+}
+
+struct Value(CoreDumpValue);
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            CoreDumpValue::Missing => f.write_str("<missing>"),
+            CoreDumpValue::I32(v) => write!(f, "i32 {}", v),
+            CoreDumpValue::I64(v) => write!(f, "i64 {}", v),
+            CoreDumpValue::F32(v) => write!(f, "f32 {}", f32::from_bits(v.bits())),
+            CoreDumpValue::F64(v) => write!(f, "f64 {}", f64::from_bits(v.bits())),
+        }
+    }
+}
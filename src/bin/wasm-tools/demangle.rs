@@ -0,0 +1,112 @@
+use anyhow::Result;
+use wasm_encoder::{Encode, NameMap};
+use wasmparser::{BinaryReader, CustomSectionReader};
+
+/// Demangles Rust and C++ symbol names in the `name` section of a WebAssembly
+/// file.
+///
+/// The function names of every module, including modules nested within a
+/// component, are rewritten in place. Everything else, including other
+/// subsections of the `name` section, is left untouched. To only demangle
+/// names when printing the text format, use `wasm-tools print --demangle`
+/// instead.
+#[derive(clap::Parser)]
+pub struct Opts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// Output the text format of WebAssembly instead of the binary format.
+    #[clap(short = 't', long)]
+    wat: bool,
+}
+
+impl Opts {
+    pub fn run(&self) -> Result<()> {
+        let input = self.io.parse_input_wasm()?;
+        let output = demangle(&input, 0)?;
+        self.io.output(wasm_tools::Output::Wasm {
+            bytes: &output,
+            wat: self.wat,
+        })?;
+        Ok(())
+    }
+}
+
+/// The id of the custom section within both modules and components.
+const CUSTOM_SECTION: u8 = 0;
+/// The ids of the sections of a component that contain whole modules or
+/// components.
+const CORE_MODULE_SECTION: u8 = 1;
+const COMPONENT_SECTION: u8 = 4;
+/// The id of the function names subsection of the `name` section.
+const FUNCTION_NAMES: u8 = 1;
+
+/// Demangle the function names of the given module or component, which is
+/// located at `offset` within the original input.
+fn demangle(wasm: &[u8], offset: usize) -> Result<Vec<u8>> {
+    anyhow::ensure!(wasm.len() >= 8, "input is missing its header");
+    // The fourth byte of the version is the layer: 0 for modules and 1 for
+    // components.
+    let is_component = wasm[6] == 1;
+    let mut output = wasm[..8].to_vec();
+    let mut reader = BinaryReader::new_with_offset(&wasm[8..], offset + 8);
+    while !reader.eof() {
+        let id = reader.read_u8()?;
+        let size = reader.read_var_u32()?;
+        let data_offset = reader.original_position();
+        let data = reader.read_bytes(size as usize)?;
+        let data = match id {
+            CUSTOM_SECTION => {
+                let custom = CustomSectionReader::new(data, data_offset)?;
+                if custom.name() == "name" && !is_component {
+                    let mut data = Vec::new();
+                    custom.name().encode(&mut data);
+                    data.extend(demangle_name_section(
+                        custom.data(),
+                        custom.data_offset(),
+                    )?);
+                    data
+                } else {
+                    data.to_vec()
+                }
+            }
+            CORE_MODULE_SECTION | COMPONENT_SECTION if is_component => {
+                demangle(data, data_offset)?
+            }
+            _ => data.to_vec(),
+        };
+        output.push(id);
+        data.encode(&mut output);
+    }
+    Ok(output)
+}
+
+/// Demangle the function names subsection of a `name` section's contents,
+/// copying all other subsections as-is.
+fn demangle_name_section(data: &[u8], offset: usize) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut reader = BinaryReader::new_with_offset(data, offset);
+    while !reader.eof() {
+        let id = reader.read_u8()?;
+        let size = reader.read_var_u32()?;
+        let subsection_offset = reader.original_position();
+        let subsection = reader.read_bytes(size as usize)?;
+        output.push(id);
+        if id != FUNCTION_NAMES {
+            subsection.encode(&mut output);
+            continue;
+        }
+
+        let mut names = NameMap::new();
+        let mut subsection = BinaryReader::new_with_offset(subsection, subsection_offset);
+        for _ in 0..subsection.read_var_u32()? {
+            let index = subsection.read_var_u32()?;
+            let name = subsection.read_string()?;
+            names.append(index, &wasmprinter::demangle(name));
+        }
+        let mut encoded = Vec::new();
+        names.encode(&mut encoded);
+        encoded.encode(&mut output);
+    }
+    Ok(output)
+}
@@ -0,0 +1,683 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::io::Write;
+use std::ops::Range;
+use std::path::PathBuf;
+use wasmparser::{
+    ComponentExternalKind, ComponentTypeRef, Encoding, ExternalKind, FuncType, FunctionBody, Name,
+    NameSectionReader, Parser, Payload::*, SectionReader, Type, TypeRef, ValType,
+};
+
+/// Compares two WebAssembly modules or components.
+///
+/// This reports the functions that were added, removed, or changed, matching
+/// functions by their name in the `name` section where possible, as well as
+/// changes to the size of each section and to the imports and exports.
+///
+/// Functions whose instructions only differ in the indices they refer to,
+/// such as after a function was inserted before the functions they call, are
+/// reported as renumbered rather than changed.
+#[derive(clap::Parser)]
+pub struct Opts {
+    /// The old module or component, or `-` to read it from stdin.
+    old: PathBuf,
+
+    /// The new module or component, or `-` to read it from stdin.
+    new: PathBuf,
+
+    /// Also show the instructions that differ within each changed or
+    /// renumbered function, in the text format.
+    #[clap(long)]
+    instructions: bool,
+
+    /// Output the differences as JSON instead of text.
+    #[clap(long)]
+    json: bool,
+
+    #[clap(flatten)]
+    output: wasm_tools::OutputArg,
+}
+
+impl Opts {
+    pub fn run(&self) -> Result<()> {
+        wasm_tools::check_stdin_once([self.old.as_path(), &self.new])?;
+        let old = wasm_tools::parse_wasm_path(&self.old)?;
+        let new = wasm_tools::parse_wasm_path(&self.new)?;
+        let old = Summary::new(&old)
+            .with_context(|| format!("failed to parse `{}`", self.old.display()))?;
+        let new = Summary::new(&new)
+            .with_context(|| format!("failed to parse `{}`", self.new.display()))?;
+        let diff = Diff::new(&old, &new, self.instructions)?;
+
+        let mut output = self.output.output_writer()?;
+        if self.json {
+            serde_json::to_writer_pretty(&mut output, &diff)?;
+            writeln!(output)?;
+        } else {
+            diff.print(&mut output)?;
+        }
+        Ok(())
+    }
+}
+
+/// What's in a module or component, as far as diffing is concerned.
+#[derive(Default)]
+struct Summary<'a> {
+    /// The name and size of each top-level section.
+    sections: Vec<(String, usize)>,
+    /// The name and description of each top-level import.
+    imports: Vec<(String, String)>,
+    /// The name and description of each top-level export.
+    exports: Vec<(String, String)>,
+    /// The functions defined by all core modules, by name.
+    functions: Vec<Function<'a>>,
+}
+
+struct Function<'a> {
+    name: String,
+    body: FunctionBody<'a>,
+}
+
+/// The state of a core module being summarized.
+#[derive(Default)]
+struct Module<'a> {
+    /// The function types, which are the only types in core modules so far.
+    types: Vec<FuncType>,
+    /// The type of each function, including imported ones.
+    funcs: Vec<u32>,
+    num_imported_funcs: u32,
+    bodies: Vec<FunctionBody<'a>>,
+    names: HashMap<u32, String>,
+}
+
+impl Module<'_> {
+    fn describe_func(&self, func: u32) -> String {
+        let ty = self
+            .funcs
+            .get(func as usize)
+            .and_then(|ty| self.types.get(*ty as usize));
+        match ty {
+            Some(ty) => describe_func_type(ty),
+            None => "func".to_string(),
+        }
+    }
+}
+
+impl<'a> Summary<'a> {
+    fn new(wasm: &'a [u8]) -> Result<Summary<'a>> {
+        let mut summary = Summary::default();
+        // The module being summarized at each level of nesting, if any.
+        let mut stack: Vec<Option<Module>> = Vec::new();
+        let mut num_modules = 0;
+        let mut component = false;
+
+        for payload in Parser::new(0).parse_all(wasm) {
+            let payload = payload?;
+            let top_level = stack.len() == 1;
+            if top_level {
+                if let Some((name, range)) = section(&payload) {
+                    let name = unique_name(&summary.sections, name);
+                    summary.sections.push((name, range.len()));
+                }
+            }
+
+            let module = stack.last_mut().and_then(|m| m.as_mut());
+            match (payload, module) {
+                (Version { encoding, .. }, _) => {
+                    if stack.is_empty() {
+                        component = encoding == Encoding::Component;
+                    }
+                    stack.push(match encoding {
+                        Encoding::Module => Some(Module::default()),
+                        Encoding::Component => None,
+                    });
+                }
+                (End(_), _) => {
+                    if let Some(Some(module)) = stack.pop() {
+                        // Functions of nested modules are named after the
+                        // module that they are in, to tell them apart.
+                        let prefix = if component {
+                            format!("module[{}]::", num_modules)
+                        } else {
+                            String::new()
+                        };
+                        num_modules += 1;
+                        for (i, body) in module.bodies.into_iter().enumerate() {
+                            let index = module.num_imported_funcs + i as u32;
+                            let name = match module.names.get(&index) {
+                                Some(name) => name.clone(),
+                                None => format!("func[{}]", index),
+                            };
+                            summary.functions.push(Function {
+                                name: format!("{}{}", prefix, name),
+                                body,
+                            });
+                        }
+                    }
+                }
+
+                (TypeSection(s), Some(module)) => {
+                    for ty in s {
+                        match ty? {
+                            Type::Func(ty) => module.types.push(ty),
+                        }
+                    }
+                }
+                (ImportSection(s), Some(module)) => {
+                    for import in s {
+                        let import = import?;
+                        let description = match import.ty {
+                            TypeRef::Func(ty) => {
+                                module.funcs.push(ty);
+                                module.num_imported_funcs += 1;
+                                module.describe_func(module.funcs.len() as u32 - 1)
+                            }
+                            TypeRef::Table(_) => "table".to_string(),
+                            TypeRef::Memory(_) => "memory".to_string(),
+                            TypeRef::Global(_) => "global".to_string(),
+                            TypeRef::Tag(_) => "tag".to_string(),
+                        };
+                        if top_level {
+                            let name = format!("{}::{}", import.module, import.name);
+                            summary.imports.push((name, description));
+                        }
+                    }
+                }
+                (FunctionSection(s), Some(module)) => {
+                    for ty in s {
+                        module.funcs.push(ty?);
+                    }
+                }
+                (ExportSection(s), Some(module)) if top_level => {
+                    for export in s {
+                        let export = export?;
+                        let description = match export.kind {
+                            ExternalKind::Func => module.describe_func(export.index),
+                            ExternalKind::Table => "table".to_string(),
+                            ExternalKind::Memory => "memory".to_string(),
+                            ExternalKind::Global => "global".to_string(),
+                            ExternalKind::Tag => "tag".to_string(),
+                        };
+                        summary
+                            .exports
+                            .push((export.name.to_string(), description));
+                    }
+                }
+                (CodeSectionEntry(body), Some(module)) => module.bodies.push(body),
+                (CustomSection(c), Some(module)) if c.name() == "name" => {
+                    // Names are best effort; a malformed name section just
+                    // means that functions are referred to by index.
+                    let _ = read_function_names(
+                        NameSectionReader::new(c.data(), c.data_offset()),
+                        &mut module.names,
+                    );
+                }
+
+                (ComponentImportSection(s), None) if top_level => {
+                    for import in s {
+                        let import = import?;
+                        let description = match import.ty {
+                            ComponentTypeRef::Module(_) => "module",
+                            ComponentTypeRef::Func(_) => "func",
+                            ComponentTypeRef::Value(_) => "value",
+                            ComponentTypeRef::Type(..) => "type",
+                            ComponentTypeRef::Instance(_) => "instance",
+                            ComponentTypeRef::Component(_) => "component",
+                        };
+                        summary
+                            .imports
+                            .push((import.name.to_string(), description.to_string()));
+                    }
+                }
+                (ComponentExportSection(s), None) if top_level => {
+                    for export in s {
+                        let export = export?;
+                        let description = match export.kind {
+                            ComponentExternalKind::Module => "module",
+                            ComponentExternalKind::Func => "func",
+                            ComponentExternalKind::Value => "value",
+                            ComponentExternalKind::Type => "type",
+                            ComponentExternalKind::Instance => "instance",
+                            ComponentExternalKind::Component => "component",
+                        };
+                        summary
+                            .exports
+                            .push((export.name.to_string(), description.to_string()));
+                    }
+                }
+
+                _ => {}
+            }
+        }
+
+        Ok(summary)
+    }
+}
+
+fn read_function_names(
+    reader: wasmparser::Result<NameSectionReader>,
+    names: &mut HashMap<u32, String>,
+) -> wasmparser::Result<()> {
+    let mut reader = reader?;
+    while !reader.eof() {
+        if let Name::Function(map) = reader.read()? {
+            let mut map = map.get_map()?;
+            for _ in 0..map.get_count() {
+                let naming = map.read()?;
+                names.insert(naming.index, naming.name.to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Get the name and range of a section.
+fn section(payload: &wasmparser::Payload) -> Option<(String, Range<usize>)> {
+    let (name, range) = match payload {
+        TypeSection(s) => ("type", s.range()),
+        ImportSection(s) => ("import", s.range()),
+        FunctionSection(s) => ("function", s.range()),
+        TableSection(s) => ("table", s.range()),
+        MemorySection(s) => ("memory", s.range()),
+        TagSection(s) => ("tag", s.range()),
+        GlobalSection(s) => ("global", s.range()),
+        ExportSection(s) => ("export", s.range()),
+        StartSection { range, .. } => ("start", range.clone()),
+        ElementSection(s) => ("element", s.range()),
+        DataCountSection { range, .. } => ("data count", range.clone()),
+        DataSection(s) => ("data", s.range()),
+        CodeSectionStart { range, .. } => ("code", range.clone()),
+        ModuleSection { range, .. } => ("module", range.clone()),
+        InstanceSection(s) => ("core instance", s.range()),
+        CoreTypeSection(s) => ("core type", s.range()),
+        ComponentSection { range, .. } => ("component", range.clone()),
+        ComponentInstanceSection(s) => ("instance", s.range()),
+        ComponentAliasSection(s) => ("alias", s.range()),
+        ComponentTypeSection(s) => ("type", s.range()),
+        ComponentCanonicalSection(s) => ("canonical function", s.range()),
+        ComponentStartSection(s) => ("start", s.range()),
+        ComponentImportSection(s) => ("import", s.range()),
+        ComponentExportSection(s) => ("export", s.range()),
+        ComponentValueSection(s) => ("value", s.range()),
+        CustomSection(c) => return Some((format!("custom {:?}", c.name()), c.range())),
+        UnknownSection { id, range, .. } => return Some((format!("unknown {}", id), range.clone())),
+        _ => return None,
+    };
+    Some((name.to_string(), range))
+}
+
+/// Components can have many sections of the same kind, so number the repeats
+/// to tell them apart.
+fn unique_name(sections: &[(String, usize)], name: String) -> String {
+    let repeats = sections
+        .iter()
+        .filter(|(n, _)| *n == name || n.starts_with(&format!("{} #", name)))
+        .count();
+    if repeats == 0 {
+        name
+    } else {
+        format!("{} #{}", name, repeats + 1)
+    }
+}
+
+fn describe_func_type(ty: &FuncType) -> String {
+    let mut s = "func".to_string();
+    if !ty.params().is_empty() {
+        s.push_str(" (param");
+        for param in ty.params().iter() {
+            s.push(' ');
+            s.push_str(val_type(*param));
+        }
+        s.push(')');
+    }
+    if !ty.results().is_empty() {
+        s.push_str(" (result");
+        for result in ty.results().iter() {
+            s.push(' ');
+            s.push_str(val_type(*result));
+        }
+        s.push(')');
+    }
+    s
+}
+
+fn val_type(ty: ValType) -> &'static str {
+    match ty {
+        ValType::I32 => "i32",
+        ValType::I64 => "i64",
+        ValType::F32 => "f32",
+        ValType::F64 => "f64",
+        ValType::V128 => "v128",
+        ValType::FuncRef => "funcref",
+        ValType::ExternRef => "externref",
+    }
+}
+
+/// The differences between two modules or components.
+#[derive(serde::Serialize)]
+struct Diff {
+    sections: Vec<SectionDiff>,
+    imports: ItemsDiff,
+    exports: ItemsDiff,
+    functions: FunctionsDiff,
+}
+
+#[derive(serde::Serialize)]
+struct SectionDiff {
+    name: String,
+    old_size: Option<usize>,
+    new_size: Option<usize>,
+}
+
+#[derive(Default, serde::Serialize)]
+struct ItemsDiff {
+    added: Vec<Item>,
+    removed: Vec<Item>,
+    changed: Vec<ChangedItem>,
+}
+
+#[derive(serde::Serialize)]
+struct Item {
+    name: String,
+    ty: String,
+}
+
+#[derive(serde::Serialize)]
+struct ChangedItem {
+    name: String,
+    old_ty: String,
+    new_ty: String,
+}
+
+#[derive(Default, serde::Serialize)]
+struct FunctionsDiff {
+    added: Vec<FunctionSize>,
+    removed: Vec<FunctionSize>,
+    changed: Vec<ChangedFunction>,
+    /// Functions whose instructions only differ in the indices they use.
+    renumbered: Vec<ChangedFunction>,
+}
+
+#[derive(serde::Serialize)]
+struct FunctionSize {
+    name: String,
+    size: usize,
+}
+
+#[derive(serde::Serialize)]
+struct ChangedFunction {
+    name: String,
+    old_size: usize,
+    new_size: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instructions: Option<Vec<String>>,
+}
+
+impl Diff {
+    fn new(old: &Summary, new: &Summary, instructions: bool) -> Result<Diff> {
+        let mut sections = Vec::new();
+        for (name, size) in &old.sections {
+            let new_size = new.sections.iter().find(|(n, _)| n == name).map(|(_, s)| *s);
+            if new_size != Some(*size) {
+                sections.push(SectionDiff {
+                    name: name.clone(),
+                    old_size: Some(*size),
+                    new_size,
+                });
+            }
+        }
+        for (name, size) in &new.sections {
+            if !old.sections.iter().any(|(n, _)| n == name) {
+                sections.push(SectionDiff {
+                    name: name.clone(),
+                    old_size: None,
+                    new_size: Some(*size),
+                });
+            }
+        }
+
+        let mut functions = FunctionsDiff::default();
+        let mut printer = wasmprinter::Printer::new();
+        for old_func in &old.functions {
+            let old_size = old_func.body.range().len();
+            match new.functions.iter().find(|f| f.name == old_func.name) {
+                None => functions.removed.push(FunctionSize {
+                    name: old_func.name.clone(),
+                    size: old_size,
+                }),
+                Some(new_func) => {
+                    if body_bytes(&old_func.body)? == body_bytes(&new_func.body)? {
+                        continue;
+                    }
+                    let old_ops = operators(&mut printer, &old_func.body)?;
+                    let new_ops = operators(&mut printer, &new_func.body)?;
+                    let renumbered = old_ops.len() == new_ops.len()
+                        && old_ops
+                            .iter()
+                            .zip(&new_ops)
+                            .all(|(a, b)| without_indices(a) == without_indices(b));
+                    let changed = ChangedFunction {
+                        name: old_func.name.clone(),
+                        old_size,
+                        new_size: new_func.body.range().len(),
+                        instructions: if instructions {
+                            Some(diff_lines(&old_ops, &new_ops))
+                        } else {
+                            None
+                        },
+                    };
+                    if renumbered {
+                        functions.renumbered.push(changed);
+                    } else {
+                        functions.changed.push(changed);
+                    }
+                }
+            }
+        }
+        for new_func in &new.functions {
+            if !old.functions.iter().any(|f| f.name == new_func.name) {
+                functions.added.push(FunctionSize {
+                    name: new_func.name.clone(),
+                    size: new_func.body.range().len(),
+                });
+            }
+        }
+
+        Ok(Diff {
+            sections,
+            imports: diff_items(&old.imports, &new.imports),
+            exports: diff_items(&old.exports, &new.exports),
+            functions,
+        })
+    }
+
+    fn print(&self, out: &mut dyn Write) -> Result<()> {
+        if !self.sections.is_empty() {
+            writeln!(out, "sections:")?;
+            for s in &self.sections {
+                match (s.old_size, s.new_size) {
+                    (Some(old), Some(new)) => writeln!(
+                        out,
+                        "  ~ {}: {} -> {} bytes ({:+})",
+                        s.name,
+                        old,
+                        new,
+                        new as i64 - old as i64
+                    )?,
+                    (Some(old), None) => writeln!(out, "  - {}: {} bytes", s.name, old)?,
+                    (None, Some(new)) => writeln!(out, "  + {}: {} bytes", s.name, new)?,
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        self.imports.print(out, "imports")?;
+        self.exports.print(out, "exports")?;
+
+        let functions = &self.functions;
+        if functions.added.is_empty()
+            && functions.removed.is_empty()
+            && functions.changed.is_empty()
+            && functions.renumbered.is_empty()
+        {
+            return Ok(());
+        }
+        writeln!(out, "functions:")?;
+        for f in &functions.removed {
+            writeln!(out, "  - {}: {} bytes", f.name, f.size)?;
+        }
+        for f in &functions.added {
+            writeln!(out, "  + {}: {} bytes", f.name, f.size)?;
+        }
+        for f in &functions.changed {
+            writeln!(
+                out,
+                "  ~ {}: {} -> {} bytes ({:+})",
+                f.name,
+                f.old_size,
+                f.new_size,
+                f.new_size as i64 - f.old_size as i64
+            )?;
+            for line in f.instructions.iter().flatten() {
+                writeln!(out, "      {}", line)?;
+            }
+        }
+        for f in &functions.renumbered {
+            writeln!(
+                out,
+                "  = {}: renumbered, {} -> {} bytes ({:+})",
+                f.name,
+                f.old_size,
+                f.new_size,
+                f.new_size as i64 - f.old_size as i64
+            )?;
+            for line in f.instructions.iter().flatten() {
+                writeln!(out, "      {}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ItemsDiff {
+    fn print(&self, out: &mut dyn Write, what: &str) -> Result<()> {
+        if self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty() {
+            return Ok(());
+        }
+        writeln!(out, "{}:", what)?;
+        for item in &self.removed {
+            writeln!(out, "  - {}: {}", item.name, item.ty)?;
+        }
+        for item in &self.added {
+            writeln!(out, "  + {}: {}", item.name, item.ty)?;
+        }
+        for item in &self.changed {
+            writeln!(out, "  ~ {}: {} -> {}", item.name, item.old_ty, item.new_ty)?;
+        }
+        Ok(())
+    }
+}
+
+fn diff_items(old: &[(String, String)], new: &[(String, String)]) -> ItemsDiff {
+    let mut diff = ItemsDiff::default();
+    for (name, ty) in old {
+        match new.iter().find(|(n, _)| n == name) {
+            None => diff.removed.push(Item {
+                name: name.clone(),
+                ty: ty.clone(),
+            }),
+            Some((_, new_ty)) if new_ty != ty => diff.changed.push(ChangedItem {
+                name: name.clone(),
+                old_ty: ty.clone(),
+                new_ty: new_ty.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (name, ty) in new {
+        if !old.iter().any(|(n, _)| n == name) {
+            diff.added.push(Item {
+                name: name.clone(),
+                ty: ty.clone(),
+            });
+        }
+    }
+    diff
+}
+
+fn body_bytes<'a>(body: &FunctionBody<'a>) -> Result<&'a [u8]> {
+    let mut reader = body.get_binary_reader();
+    Ok(reader.read_bytes(reader.bytes_remaining())?)
+}
+
+/// The instructions of `body` in the text format, without the final `end`
+/// which is implied in the text format.
+fn operators(printer: &mut wasmprinter::Printer, body: &FunctionBody) -> Result<Vec<String>> {
+    let mut ops = printer
+        .print_operators(body)?
+        .into_iter()
+        .map(|(_, text)| text)
+        .collect::<Vec<_>>();
+    ops.pop();
+    Ok(ops)
+}
+
+/// Replace the indices in the text of an instruction, leaving constants
+/// alone, to compare instructions regardless of how the items they refer to
+/// are numbered.
+fn without_indices(instruction: &str) -> String {
+    let mut tokens = instruction.split_whitespace();
+    let name = tokens.next().unwrap_or_default();
+    if name.ends_with(".const") {
+        return instruction.to_string();
+    }
+    let mut s = name.to_string();
+    for token in tokens {
+        s.push(' ');
+        if token.trim_end_matches(')').parse::<u32>().is_ok() {
+            s.push('_');
+        } else {
+            s.push_str(token);
+        }
+    }
+    s
+}
+
+/// The most instructions that are diffed line by line; beyond this, the
+/// quadratic diff gets too slow.
+const MAX_DIFF_CELLS: usize = 10_000_000;
+
+/// Diff two lists of lines, returning the removed lines prefixed with `-` and
+/// the added lines prefixed with `+`.
+fn diff_lines(old: &[String], new: &[String]) -> Vec<String> {
+    if old.len().saturating_mul(new.len()) > MAX_DIFF_CELLS {
+        return vec!["(too many instructions to diff)".to_string()];
+    }
+
+    // The length of the longest common subsequence of each pair of suffixes.
+    let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+
+/// Removes unreachable items from a WebAssembly module.
+///
+/// Functions, globals, types, and passive data segments which can't be reached
+/// from the module's exports are removed, and the remaining items are
+/// renumbered. The start function, element segments, tags, and active data
+/// segments are always kept, along with everything that they refer to. With
+/// `--root`, only the named exports are kept and used as roots instead of all
+/// of them.
+///
+/// The `name` section is updated to match, but other custom sections, such as
+/// DWARF debugging information, are copied as-is and may be left referring to
+/// the wrong items. The result is validated before it's written out.
+#[derive(clap::Parser)]
+pub struct Opts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// Only keep the export with this name, and what it refers to.
+    ///
+    /// This may be passed multiple times. If it's not passed then all exports
+    /// are kept.
+    #[clap(long = "root", value_name = "EXPORT")]
+    roots: Vec<String>,
+
+    /// Output the text format of WebAssembly instead of the binary format.
+    #[clap(short = 't', long)]
+    wat: bool,
+}
+
+impl Opts {
+    pub fn run(&self) -> Result<()> {
+        let input = self.io.parse_input_wasm()?;
+        let (output, removed) = wasm_tools::gc::gc(&input, &self.roots)?;
+        log::info!(
+            "removing {} functions, {} globals, {} types, and {} data segments",
+            removed.funcs,
+            removed.globals,
+            removed.types,
+            removed.data,
+        );
+        self.io.output(wasm_tools::Output::Wasm {
+            bytes: &output,
+            wat: self.wat,
+        })?;
+        Ok(())
+    }
+}
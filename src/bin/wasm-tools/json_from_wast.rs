@@ -0,0 +1,45 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+/// Compile a `*.wast` script into wasm binaries and a JSON manifest.
+///
+/// This subcommand mirrors wabt's `wast2json`: each module in the script is
+/// written to its own file and the commands of the script are described by a
+/// JSON manifest which references those files by name.
+#[derive(clap::Parser)]
+pub struct Opts {
+    #[clap(flatten)]
+    input: wasm_tools::InputArg,
+
+    #[clap(flatten)]
+    output: wasm_tools::OutputArg,
+
+    /// Directory to write the generated module files to.
+    ///
+    /// Defaults to the directory of the `--output` file, or the current
+    /// directory if the manifest is printed to stdout.
+    #[clap(long)]
+    wasm_dir: Option<PathBuf>,
+}
+
+impl Opts {
+    pub fn run(&self) -> Result<()> {
+        let contents = self.input.read_text()?;
+        let output = json_from_wast::compile(&self.input.name(), &contents)?;
+
+        let wasm_dir = match (&self.wasm_dir, self.output.path()) {
+            (Some(dir), _) => dir.clone(),
+            (None, Some(path)) => path.parent().map(PathBuf::from).unwrap_or_default(),
+            (None, None) => PathBuf::new(),
+        };
+        for (name, contents) in output.files.iter() {
+            let path = wasm_dir.join(name);
+            std::fs::write(&path, contents)
+                .with_context(|| format!("failed to write `{}`", path.display()))?;
+        }
+
+        let mut json = serde_json::to_string_pretty(&output.manifest)?;
+        json.push('\n');
+        self.output.output(wasm_tools::Output::Wat(&json))
+    }
+}
@@ -0,0 +1,406 @@
+use anyhow::{bail, Result};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use wasm_tools::sarif::{self, DiagnosticsArg, DiagnosticsFormat, Level, Region};
+use wasmparser::{
+    ConstExpr, DataKind, ElementItem, Encoding, ExternalKind, Name, NameSectionReader, Operator,
+    Parser, Payload::*, SectionWithLimitedItems, TypeRef,
+};
+
+/// Checks a WebAssembly module or component for likely mistakes.
+///
+/// Every core module, including those nested within components, is checked
+/// against a set of heuristic rules. The process exits with a failure if any
+/// warnings are found.
+///
+/// Rules:
+///
+/// * unused-import: an imported function is never called or referenced
+///
+/// * dead-function: a function is never called, exported, or referenced
+///
+/// * zero-data: a large active data segment contains only zeros, which
+///   memory already is initially
+///
+/// * missing-producers: there's no `producers` section describing the
+///   toolchain that produced the module
+///
+/// * mutable-global-export: a mutable global is exported
+#[derive(clap::Parser)]
+pub struct Opts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// Disable the given rule, or "all" rules.
+    ///
+    /// This may be passed multiple times.
+    #[clap(long, value_name = "RULE")]
+    disable: Vec<String>,
+
+    /// Enable the given rule, even if it was disabled with `--disable all`.
+    ///
+    /// This may be passed multiple times.
+    #[clap(long, value_name = "RULE")]
+    enable: Vec<String>,
+
+    /// Output the warnings as JSON instead of text.
+    #[clap(long)]
+    json: bool,
+
+    #[clap(flatten)]
+    diagnostics: DiagnosticsArg,
+}
+
+const RULES: &[sarif::Rule] = &[
+    sarif::Rule {
+        id: "unused-import",
+        description: "An imported function is never called or referenced",
+    },
+    sarif::Rule {
+        id: "dead-function",
+        description: "A function is never called, exported, or referenced",
+    },
+    sarif::Rule {
+        id: "zero-data",
+        description: "A large active data segment contains only zeros",
+    },
+    sarif::Rule {
+        id: "missing-producers",
+        description: "There's no `producers` section describing the toolchain",
+    },
+    sarif::Rule {
+        id: "mutable-global-export",
+        description: "A mutable global is exported",
+    },
+];
+
+/// Data segments of zeros smaller than this aren't worth warning about.
+const ZERO_DATA_THRESHOLD: usize = 1024;
+
+#[derive(serde::Serialize)]
+struct Warning {
+    rule: &'static str,
+    message: String,
+    offset: usize,
+    /// The nested module that the warning is about, if the input is a
+    /// component.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    module: Option<String>,
+}
+
+impl Warning {
+    fn diagnostic(&self, is_text: bool) -> sarif::Diagnostic {
+        let message = match &self.module {
+            Some(module) => format!("{}: {}", module, self.message),
+            None => self.message.clone(),
+        };
+        sarif::Diagnostic {
+            rule: self.rule,
+            level: Level::Warning,
+            message,
+            // Offsets within the binary encoding of a text input don't point
+            // at anything within the input itself.
+            region: if is_text {
+                None
+            } else {
+                Some(Region::Binary {
+                    offset: self.offset,
+                })
+            },
+        }
+    }
+}
+
+impl Opts {
+    pub fn run(&self) -> Result<()> {
+        for rule in self.disable.iter().chain(&self.enable) {
+            if rule != "all" && !RULES.iter().any(|r| r.id == rule) {
+                let names: Vec<_> = RULES.iter().map(|r| r.id).collect();
+                bail!(
+                    "unknown lint rule `{}`, expected one of: {}",
+                    rule,
+                    names.join(", ")
+                );
+            }
+        }
+
+        let wasm = self.io.parse_input_wasm()?;
+        let warnings: Vec<_> = lint(&wasm)?
+            .into_iter()
+            .filter(|w| self.enabled(w.rule))
+            .collect();
+
+        let mut output = self.io.output_writer()?;
+        if self.diagnostics.format() == Some(DiagnosticsFormat::Sarif) {
+            let diagnostics: Vec<_> = warnings
+                .iter()
+                .map(|w| w.diagnostic(wasm.is_text()))
+                .collect();
+            sarif::write(&mut output, self.io.input_path(), RULES, &diagnostics)?;
+        } else if self.json {
+            serde_json::to_writer_pretty(&mut output, &warnings)?;
+            writeln!(output)?;
+        } else {
+            for w in &warnings {
+                write!(output, "warning[{}]: ", w.rule)?;
+                if let Some(module) = &w.module {
+                    write!(output, "{}: ", module)?;
+                }
+                writeln!(output, "{} (at offset {:#x})", w.message, w.offset)?;
+            }
+        }
+        output.flush()?;
+
+        if !warnings.is_empty() {
+            bail!("found {} lint warning(s)", warnings.len());
+        }
+        Ok(())
+    }
+
+    fn enabled(&self, rule: &str) -> bool {
+        if self.enable.iter().any(|r| r == rule) {
+            return true;
+        }
+        !self.disable.iter().any(|r| r == rule || r == "all")
+    }
+}
+
+/// The state of a core module being linted.
+#[derive(Default)]
+struct Module {
+    /// The name and offset of each imported function.
+    imported_funcs: Vec<(String, usize)>,
+    /// The offset of each defined function's body.
+    defined_funcs: Vec<usize>,
+    /// Functions that are called or referenced from somewhere other than
+    /// their own body.
+    used_funcs: HashSet<u32>,
+    /// Whether each global is mutable.
+    globals: Vec<bool>,
+    names: HashMap<u32, String>,
+    has_producers: bool,
+    warnings: Vec<Warning>,
+}
+
+impl Module {
+    fn warn(&mut self, rule: &'static str, message: String, offset: usize) {
+        self.warnings.push(Warning {
+            rule,
+            message,
+            offset,
+            module: None,
+        });
+    }
+
+    fn use_const_expr(&mut self, expr: &ConstExpr) -> Result<()> {
+        let mut reader = expr.get_operators_reader();
+        while !reader.eof() {
+            if let Operator::RefFunc { function_index } = reader.read()? {
+                self.used_funcs.insert(function_index);
+            }
+        }
+        Ok(())
+    }
+
+    fn func_name(&self, index: u32) -> String {
+        match self.names.get(&index) {
+            Some(name) => format!("function `{}` (index {})", name, index),
+            None => format!("function {}", index),
+        }
+    }
+
+    /// Check the rules that need the whole module, returning all warnings.
+    fn finish(mut self, end: usize) -> Vec<Warning> {
+        let num_imported = self.imported_funcs.len() as u32;
+        for (i, (name, offset)) in self.imported_funcs.iter().enumerate() {
+            if !self.used_funcs.contains(&(i as u32)) {
+                let message = format!("imported function `{}` is never used", name);
+                self.warnings.push(Warning {
+                    rule: "unused-import",
+                    message,
+                    offset: *offset,
+                    module: None,
+                });
+            }
+        }
+        for (i, offset) in self.defined_funcs.iter().enumerate() {
+            let index = num_imported + i as u32;
+            if !self.used_funcs.contains(&index) {
+                let message = format!("{} is never called or exported", self.func_name(index));
+                self.warnings.push(Warning {
+                    rule: "dead-function",
+                    message,
+                    offset: *offset,
+                    module: None,
+                });
+            }
+        }
+        if !self.has_producers {
+            let message = "module has no `producers` section".to_string();
+            self.warn("missing-producers", message, end);
+        }
+        self.warnings.sort_by_key(|w| w.offset);
+        self.warnings
+    }
+}
+
+fn lint(wasm: &[u8]) -> Result<Vec<Warning>> {
+    let mut warnings = Vec::new();
+    // The module being linted at each level of nesting, if any.
+    let mut stack: Vec<Option<Module>> = Vec::new();
+    let mut num_modules = 0;
+    let mut component = false;
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        let payload = payload?;
+        let module = stack.last_mut().and_then(|m| m.as_mut());
+        match (payload, module) {
+            (Version { encoding, .. }, _) => {
+                if stack.is_empty() {
+                    component = encoding == Encoding::Component;
+                }
+                stack.push(match encoding {
+                    Encoding::Module => Some(Module::default()),
+                    Encoding::Component => None,
+                });
+            }
+            (End(end), _) => {
+                if let Some(Some(module)) = stack.pop() {
+                    // Warnings about nested modules say which module they're
+                    // about, to tell them apart.
+                    let name = if component {
+                        Some(format!("module[{}]", num_modules))
+                    } else {
+                        None
+                    };
+                    num_modules += 1;
+                    for mut warning in module.finish(end) {
+                        warning.module = name.clone();
+                        warnings.push(warning);
+                    }
+                }
+            }
+
+            (ImportSection(s), Some(module)) => {
+                for import in s.into_iter_with_offsets() {
+                    let (offset, import) = import?;
+                    match import.ty {
+                        TypeRef::Func(_) => {
+                            let name = format!("{}::{}", import.module, import.name);
+                            module.imported_funcs.push((name, offset));
+                        }
+                        TypeRef::Global(ty) => module.globals.push(ty.mutable),
+                        _ => {}
+                    }
+                }
+            }
+            (GlobalSection(s), Some(module)) => {
+                for global in s {
+                    let global = global?;
+                    module.globals.push(global.ty.mutable);
+                    module.use_const_expr(&global.init_expr)?;
+                }
+            }
+            (ExportSection(s), Some(module)) => {
+                for export in s.into_iter_with_offsets() {
+                    let (offset, export) = export?;
+                    match export.kind {
+                        ExternalKind::Func => {
+                            module.used_funcs.insert(export.index);
+                        }
+                        ExternalKind::Global
+                            if module.globals.get(export.index as usize) == Some(&true) =>
+                        {
+                            let message =
+                                format!("mutable global is exported as `{}`", export.name);
+                            module.warn("mutable-global-export", message, offset);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            (StartSection { func, .. }, Some(module)) => {
+                module.used_funcs.insert(func);
+            }
+            (ElementSection(s), Some(module)) => {
+                for elem in s {
+                    let mut items = elem?.items.get_items_reader()?;
+                    for _ in 0..items.get_count() {
+                        match items.read()? {
+                            ElementItem::Func(index) => {
+                                module.used_funcs.insert(index);
+                            }
+                            ElementItem::Expr(expr) => module.use_const_expr(&expr)?,
+                        }
+                    }
+                }
+            }
+            (CodeSectionEntry(body), Some(module)) => {
+                let index = (module.imported_funcs.len() + module.defined_funcs.len()) as u32;
+                module.defined_funcs.push(body.range().start);
+                let mut reader = body.get_operators_reader()?;
+                while !reader.eof() {
+                    match reader.read()? {
+                        Operator::Call { function_index }
+                        | Operator::ReturnCall { function_index }
+                        | Operator::RefFunc { function_index }
+                            if function_index != index =>
+                        {
+                            module.used_funcs.insert(function_index);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            (DataSection(s), Some(module)) => {
+                for (i, data) in s.into_iter().enumerate() {
+                    let data = data?;
+                    if matches!(data.kind, DataKind::Active { .. })
+                        && data.data.len() >= ZERO_DATA_THRESHOLD
+                        && data.data.iter().all(|b| *b == 0)
+                    {
+                        let message = format!(
+                            "data segment {} is {} bytes of zeros",
+                            i,
+                            data.data.len()
+                        );
+                        module.warn("zero-data", message, data.range.start);
+                    }
+                }
+            }
+            (CustomSection(c), Some(module)) => match c.name() {
+                "producers" => module.has_producers = true,
+                "name" => {
+                    // Names are best effort; a malformed name section just
+                    // means that functions are referred to by index.
+                    let _ = read_function_names(
+                        NameSectionReader::new(c.data(), c.data_offset()),
+                        &mut module.names,
+                    );
+                }
+                _ => {}
+            },
+
+            _ => {}
+        }
+    }
+
+    Ok(warnings)
+}
+
+fn read_function_names(
+    reader: wasmparser::Result<NameSectionReader>,
+    names: &mut HashMap<u32, String>,
+) -> wasmparser::Result<()> {
+    let mut reader = reader?;
+    while !reader.eof() {
+        if let Name::Function(map) = reader.read()? {
+            let mut map = map.get_map()?;
+            for _ in 0..map.get_count() {
+                let naming = map.read()?;
+                names.insert(naming.index, naming.name.to_string());
+            }
+        }
+    }
+    Ok(())
+}
@@ -54,6 +54,18 @@ subcommands! {
     (objdump, "objdump")
     (strip, "strip")
     (compose, "compose")
+    (json_from_wast, "json-from-wast")
+    (metadata, "metadata")
+    (diff, "diff")
+    (demangle, "demangle")
+    (stats, "stats")
+    (component, "component")
+    (lint, "lint")
+    (gc, "gc")
+    (sign, "sign")
+    (verify, "verify")
+    (coredump, "coredump")
+    (wast, "wast")
 }
 
 fn main() -> ExitCode {
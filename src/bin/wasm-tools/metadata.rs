@@ -0,0 +1,529 @@
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+use std::path::PathBuf;
+use wasm_metadata::{Annotation, Annotations, ProducersReport, Value};
+use wasm_tools::Output;
+
+/// Read and manipulate the metadata of WebAssembly modules and components.
+#[derive(clap::Parser)]
+pub struct Opts {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    Producers(ProducersOpts),
+    Set(SetOpts),
+    Get(GetOpts),
+    Remove(RemoveOpts),
+    List(ListOpts),
+    Annotate(AnnotateOpts),
+    Annotations(AnnotationsOpts),
+    AddSbom(AddSbomOpts),
+    Sbom(SbomOpts),
+    Diff(DiffOpts),
+    Dump(DumpOpts),
+}
+
+impl Opts {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            Command::Producers(opts) => opts.run(),
+            Command::Set(opts) => opts.run(),
+            Command::Get(opts) => opts.run(),
+            Command::Remove(opts) => opts.run(),
+            Command::List(opts) => opts.run(),
+            Command::Annotate(opts) => opts.run(),
+            Command::Annotations(opts) => opts.run(),
+            Command::AddSbom(opts) => opts.run(),
+            Command::Sbom(opts) => opts.run(),
+            Command::Diff(opts) => opts.run(),
+            Command::Dump(opts) => opts.run(),
+        }
+    }
+}
+
+/// Reports the `producers` sections of a module or component and of every
+/// module and component nested within it.
+///
+/// Each section is listed with the path of the binary it belongs to, such as
+/// `/component[0]/module[1]` for the second core module of the first nested
+/// component, followed by the number of sections naming each tool and
+/// version.
+#[derive(clap::Parser)]
+pub struct ProducersOpts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// Output the report as JSON instead of text.
+    #[clap(long)]
+    json: bool,
+}
+
+impl ProducersOpts {
+    fn run(&self) -> Result<()> {
+        let input = self.io.parse_input_wasm()?;
+        let report = ProducersReport::from_wasm(&input)?;
+
+        let mut output = self.io.output_writer()?;
+        if self.json {
+            #[derive(serde::Serialize)]
+            struct Json<'a> {
+                entries: &'a [wasm_metadata::ProducersEntry],
+                tools: Vec<wasm_metadata::ToolCount>,
+            }
+            let json = Json {
+                entries: &report.entries,
+                tools: report.tools(),
+            };
+            serde_json::to_writer_pretty(&mut output, &json)?;
+            writeln!(output)?;
+            return Ok(());
+        }
+
+        for entry in &report.entries {
+            writeln!(output, "{} ({})", entry.path, entry.kind)?;
+            for (field, values) in entry.producers.fields() {
+                for (name, version) in values {
+                    writeln!(output, "  {field}: {}", tool(name, version))?;
+                }
+            }
+        }
+        let tools = report.tools();
+        if !tools.is_empty() {
+            writeln!(output, "sections naming each tool:")?;
+            for t in tools {
+                let name = tool(&t.name, &t.version);
+                writeln!(output, "  {:>5} {}: {name}", t.count, t.field)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sets a named metadata entry of a module or component.
+///
+/// Entries are stored in `metadata:<key>` custom sections at the top level of
+/// the input, so build systems can stamp build ids, git commits, and CI
+/// details into an artifact. An existing entry with the same key is
+/// replaced.
+#[derive(clap::Parser)]
+pub struct SetOpts {
+    /// The key of the entry.
+    key: String,
+
+    /// The value of the entry.
+    value: String,
+
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// Store the value as JSON, which must be well-formed, instead of as a
+    /// string.
+    #[clap(long)]
+    json: bool,
+
+    /// Output the text format of WebAssembly instead of the binary format.
+    #[clap(short = 't', long)]
+    wat: bool,
+}
+
+impl SetOpts {
+    fn run(&self) -> Result<()> {
+        let input = self.io.parse_input_wasm()?;
+        let value = if self.json {
+            Value::Json(serde_json::from_str(&self.value).context("value is not valid JSON")?)
+        } else {
+            Value::String(self.value.clone())
+        };
+        let output = wasm_metadata::set_entry(&input, &self.key, &value)?;
+        self.io.output(Output::Wasm {
+            bytes: &output,
+            wat: self.wat,
+        })
+    }
+}
+
+/// Prints the value of a named metadata entry of a module or component.
+///
+/// Fails if there's no entry with the given key.
+#[derive(clap::Parser)]
+pub struct GetOpts {
+    /// The key of the entry.
+    key: String,
+
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+}
+
+impl GetOpts {
+    fn run(&self) -> Result<()> {
+        let input = self.io.parse_input_wasm()?;
+        let value = match wasm_metadata::get_entry(&input, &self.key)? {
+            Some(value) => value,
+            None => bail!("no metadata entry named `{}`", self.key),
+        };
+        let mut output = self.io.output_writer()?;
+        writeln!(output, "{value}")?;
+        Ok(())
+    }
+}
+
+/// Removes a named metadata entry from a module or component.
+///
+/// Fails if there's no entry with the given key.
+#[derive(clap::Parser)]
+pub struct RemoveOpts {
+    /// The key of the entry.
+    key: String,
+
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// Output the text format of WebAssembly instead of the binary format.
+    #[clap(short = 't', long)]
+    wat: bool,
+}
+
+impl RemoveOpts {
+    fn run(&self) -> Result<()> {
+        let input = self.io.parse_input_wasm()?;
+        let (output, removed) = wasm_metadata::remove_entry(&input, &self.key)?;
+        if !removed {
+            bail!("no metadata entry named `{}`", self.key);
+        }
+        self.io.output(Output::Wasm {
+            bytes: &output,
+            wat: self.wat,
+        })
+    }
+}
+
+/// Lists the named metadata entries of a module or component.
+#[derive(clap::Parser)]
+pub struct ListOpts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// Output the entries as a JSON object instead of text.
+    #[clap(long)]
+    json: bool,
+}
+
+impl ListOpts {
+    fn run(&self) -> Result<()> {
+        let input = self.io.parse_input_wasm()?;
+        let entries = wasm_metadata::entries(&input)?;
+        let mut output = self.io.output_writer()?;
+        if self.json {
+            let json = entries
+                .into_iter()
+                .map(|(key, value)| Ok((key, serde_json::to_value(value)?)))
+                .collect::<Result<serde_json::Map<_, _>>>()?;
+            serde_json::to_writer_pretty(&mut output, &json)?;
+            writeln!(output)?;
+            return Ok(());
+        }
+        for (key, value) in entries {
+            writeln!(output, "{key}: {value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Adds, updates, or removes the annotations of a module or component.
+///
+/// Annotations mirror the OCI image annotation keys and are each stored in a
+/// custom section named after them at the top level of the input. Existing
+/// annotations which aren't mentioned are kept.
+#[derive(clap::Parser)]
+pub struct AnnotateOpts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// The SPDX license expression of the code.
+    #[clap(long, value_name = "EXPR")]
+    license: Option<String>,
+
+    /// A URL of the source code.
+    #[clap(long, value_name = "URL")]
+    source: Option<String>,
+
+    /// A human-readable description.
+    #[clap(long, value_name = "TEXT")]
+    description: Option<String>,
+
+    /// A URL with more information.
+    #[clap(long, value_name = "URL")]
+    homepage: Option<String>,
+
+    /// The version control revision that the input was built from.
+    #[clap(long, value_name = "REV")]
+    revision: Option<String>,
+
+    /// Remove the given annotation, which is one of "license", "source",
+    /// "description", "homepage", or "revision".
+    ///
+    /// This may be passed multiple times.
+    #[clap(long, value_name = "ANNOTATION")]
+    remove: Vec<Annotation>,
+
+    /// Output the text format of WebAssembly instead of the binary format.
+    #[clap(short = 't', long)]
+    wat: bool,
+}
+
+impl AnnotateOpts {
+    fn run(&self) -> Result<()> {
+        let annotations = Annotations {
+            license: self.license.clone(),
+            source: self.source.clone(),
+            description: self.description.clone(),
+            homepage: self.homepage.clone(),
+            revision: self.revision.clone(),
+        };
+        if let Some(a) = self.remove.iter().find(|a| annotations.get(**a).is_some()) {
+            bail!("cannot both set and remove the `{a}` annotation");
+        }
+        let mut output = self.io.parse_input_wasm()?.to_vec();
+        for annotation in &self.remove {
+            output = wasm_metadata::remove_annotation(&output, *annotation)?.0;
+        }
+        let output = annotations.add_to(&output)?;
+        self.io.output(Output::Wasm {
+            bytes: &output,
+            wat: self.wat,
+        })
+    }
+}
+
+/// Lists the annotations of a module or component and of every module and
+/// component nested within it.
+///
+/// Each binary with annotations is listed with its nesting path, such as
+/// `/component[0]/module[1]` for the second core module of the first nested
+/// component.
+#[derive(clap::Parser)]
+pub struct AnnotationsOpts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// Output the annotations as JSON instead of text.
+    #[clap(long)]
+    json: bool,
+
+    /// Output only the annotations of the outermost binary, as a JSON object
+    /// keyed by OCI annotation keys such as
+    /// `org.opencontainers.image.licenses`.
+    #[clap(long, conflicts_with = "json")]
+    oci: bool,
+}
+
+impl AnnotationsOpts {
+    fn run(&self) -> Result<()> {
+        let input = self.io.parse_input_wasm()?;
+        let mut output = self.io.output_writer()?;
+        if self.oci {
+            let annotations = Annotations::from_wasm(&input)?;
+            serde_json::to_writer_pretty(&mut output, &annotations.to_oci())?;
+            writeln!(output)?;
+            return Ok(());
+        }
+        let entries = wasm_metadata::extract_annotations(&input)?;
+        if self.json {
+            serde_json::to_writer_pretty(&mut output, &entries)?;
+            writeln!(output)?;
+            return Ok(());
+        }
+        for entry in &entries {
+            writeln!(output, "{} ({})", entry.path, entry.kind)?;
+            for (annotation, value) in entry.annotations.iter() {
+                writeln!(output, "  {annotation}: {value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Embeds an SPDX or CycloneDX JSON SBOM in a module or component.
+///
+/// The document is stored in an `sbom` custom section at the top level of
+/// the input, replacing any SBOM already there.
+#[derive(clap::Parser)]
+pub struct AddSbomOpts {
+    /// The SBOM document to embed.
+    sbom: PathBuf,
+
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// Output the text format of WebAssembly instead of the binary format.
+    #[clap(short = 't', long)]
+    wat: bool,
+}
+
+impl AddSbomOpts {
+    fn run(&self) -> Result<()> {
+        let json = std::fs::read(&self.sbom)
+            .with_context(|| format!("failed to read `{}`", self.sbom.display()))?;
+        let sbom = wasm_metadata::Sbom::parse(&json)
+            .with_context(|| format!("invalid SBOM in `{}`", self.sbom.display()))?;
+        let input = self.io.parse_input_wasm()?;
+        let output = sbom.add_to(&input)?;
+        self.io.output(Output::Wasm {
+            bytes: &output,
+            wat: self.wat,
+        })
+    }
+}
+
+/// Extracts the SBOMs embedded in a module or component and in every module
+/// and component nested within it.
+///
+/// By default the SBOMs are printed as a JSON array of their nesting paths,
+/// formats, and documents. With `--merge` they're merged into a single
+/// document instead, which requires them all to have the same format.
+#[derive(clap::Parser)]
+pub struct SbomOpts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// Merge the SBOMs into a single document.
+    #[clap(long)]
+    merge: bool,
+}
+
+impl SbomOpts {
+    fn run(&self) -> Result<()> {
+        let input = self.io.parse_input_wasm()?;
+        let entries = wasm_metadata::extract_sboms(&input)?;
+        let mut output = self.io.output_writer()?;
+        if self.merge {
+            let sboms = entries.into_iter().map(|e| e.sbom).collect::<Vec<_>>();
+            let merged = wasm_metadata::merge_sboms(&sboms)?;
+            serde_json::to_writer_pretty(&mut output, &merged.document)?;
+        } else {
+            serde_json::to_writer_pretty(&mut output, &entries)?;
+        }
+        writeln!(output)?;
+        Ok(())
+    }
+}
+
+/// Compares the metadata of two modules or components.
+///
+/// The producers, names, annotations, custom sections, and named metadata
+/// entries of the two inputs and of every module and component nested within
+/// them are compared, which is handy for checking that a rebuild changed only what it
+/// should have. Each difference is printed as a removed (`-`), added (`+`),
+/// or changed (`~`) item along with the path of the binary it belongs to.
+#[derive(clap::Parser)]
+pub struct DiffOpts {
+    /// The old module or component, or `-` to read it from stdin.
+    old: PathBuf,
+
+    /// The new module or component, or `-` to read it from stdin.
+    new: PathBuf,
+
+    #[clap(flatten)]
+    output: wasm_tools::OutputArg,
+
+    /// Output the differences as JSON instead of text.
+    #[clap(long)]
+    json: bool,
+}
+
+impl DiffOpts {
+    fn run(&self) -> Result<()> {
+        wasm_tools::check_stdin_once([self.old.as_path(), &self.new])?;
+        let old = wasm_tools::parse_wasm_path(&self.old)?;
+        let new = wasm_tools::parse_wasm_path(&self.new)?;
+        let diff = wasm_metadata::MetadataDiff::new(&old, &new)?;
+
+        let mut output = self.output.output_writer()?;
+        if self.json {
+            serde_json::to_writer_pretty(&mut output, &diff)?;
+            writeln!(output)?;
+            return Ok(());
+        }
+        if diff.is_empty() {
+            writeln!(output, "no metadata differences")?;
+            return Ok(());
+        }
+        for (kind, changes) in [
+            ("producers", &diff.producers),
+            ("names", &diff.names),
+            ("annotations", &diff.annotations),
+            ("custom sections", &diff.custom_sections),
+            ("entries", &diff.entries),
+        ] {
+            if changes.is_empty() {
+                continue;
+            }
+            writeln!(output, "{kind}:")?;
+            for c in changes {
+                match (&c.old, &c.new) {
+                    (Some(old), None) => writeln!(output, "  - {} {}: {old}", c.path, c.item)?,
+                    (None, Some(new)) => writeln!(output, "  + {} {}: {new}", c.path, c.item)?,
+                    (Some(old), Some(new)) => {
+                        writeln!(output, "  ~ {} {}: {old} -> {new}", c.path, c.item)?
+                    }
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lists every custom section of a module or component and of every module
+/// and component nested within it, along with its byte range.
+///
+/// Each section is printed with the path of the binary it belongs to, its
+/// name, and the range of the whole section within the input. With `--json`,
+/// the range of its data and its parsed contents, such as the tools of a
+/// `producers` section or the value of a named entry, are included too.
+#[derive(clap::Parser)]
+pub struct DumpOpts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// Output the sections as JSON instead of text.
+    #[clap(long)]
+    json: bool,
+}
+
+impl DumpOpts {
+    fn run(&self) -> Result<()> {
+        let input = self.io.parse_input_wasm()?;
+        let items = wasm_metadata::dump(&input)?;
+        let mut output = self.io.output_writer()?;
+        if self.json {
+            serde_json::to_writer_pretty(&mut output, &items)?;
+            writeln!(output)?;
+            return Ok(());
+        }
+        for item in &items {
+            writeln!(
+                output,
+                "{} {}: {:#x}..{:#x} ({} bytes)",
+                item.path,
+                item.section,
+                item.range.start,
+                item.range.end,
+                item.range.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a tool and its version, which may be empty.
+fn tool(name: &str, version: &str) -> String {
+    if version.is_empty() {
+        name.to_string()
+    } else {
+        format!("{name} {version}")
+    }
+}
@@ -1,6 +1,8 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use clap::Parser;
-use wasm_mutate::ErrorKind;
+use std::io::Write;
+use std::path::PathBuf;
+use wasm_mutate::{ErrorKind, MutationRecord};
 
 /// A WebAssembly test case mutator.
 ///
@@ -17,6 +19,15 @@ use wasm_mutate::ErrorKind;
 ///
 /// $ wasm-mutate ./input.wasm --seed 1234 -o output.wasm
 ///
+/// Record the mutations applied to a module, one per line, and later replay
+/// some or all of them onto the original module:
+///
+/// $ wasm-mutate ./input.wasm --seed 1 --record log.txt -o a.wasm
+///
+/// $ wasm-mutate ./a.wasm --seed 2 --record log.txt -o b.wasm
+///
+/// $ wasm-mutate ./input.wasm --replay log.txt -o b.wasm
+///
 /// ## Exit Codes
 ///
 /// * 0: Success
@@ -44,6 +55,17 @@ pub struct Opts {
     #[clap(short = 't', long)]
     wat: bool,
 
+    /// Append a record of the applied mutation to the given file, so that it
+    /// can be replayed with `--replay`. Only mutations of core modules can be
+    /// recorded.
+    #[clap(long)]
+    record: Option<PathBuf>,
+
+    /// Instead of applying a new mutation, replay the mutations recorded in
+    /// the given file onto the input Wasm module.
+    #[clap(long, conflicts_with = "record")]
+    replay: Option<PathBuf>,
+
     #[clap(flatten)]
     wasm_mutate: wasm_mutate::WasmMutate<'static>,
 }
@@ -57,12 +79,28 @@ impl Opts {
         // for that long we simply leak it, and this shouldn't matter too much
         // in the grand scheme of things since this is a short-lived process
         // anyway.
-        let input_wasm = Box::leak(input_wasm.into_boxed_slice());
+        let input_wasm: &'static [u8] = Box::leak(Box::new(input_wasm));
 
-        let mut output_wasms =
-            unwrap_wasm_mutate_result(self.wasm_mutate.run(input_wasm)).take(100);
-        let wasm = loop {
-            let res = match output_wasms.next() {
+        if let Some(replay) = &self.replay {
+            let log = std::fs::read_to_string(replay)
+                .with_context(|| format!("failed to read {}", replay.display()))?;
+            let records = log
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.parse())
+                .collect::<wasm_mutate::Result<Vec<MutationRecord>>>()?;
+            let wasm = unwrap_wasm_mutate_result(self.wasm_mutate.replay(input_wasm, &records));
+            return self.io.output(wasm_tools::Output::Wasm {
+                bytes: &wasm,
+                wat: self.wat,
+            });
+        }
+
+        let mut output_wasms = unwrap_wasm_mutate_result(self.wasm_mutate.run(input_wasm))
+            .take(100)
+            .enumerate();
+        let (output, wasm) = loop {
+            let (output, res) = match output_wasms.next() {
                 Some(res) => res,
                 None => {
                     eprintln!("no mutations found");
@@ -74,9 +112,23 @@ impl Opts {
                     // Try the next mutation.
                     continue;
                 }
-                _ => break unwrap_wasm_mutate_result(res),
+                _ => break (output, unwrap_wasm_mutate_result(res)),
             }
         };
+        drop(output_wasms);
+
+        if let Some(record) = &self.record {
+            let mutation = match self.wasm_mutate.last_mutation(output) {
+                Some(mutation) => mutation,
+                None => bail!("`--record` only supports mutations of core modules, not components"),
+            };
+            let mut log = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(record)
+                .with_context(|| format!("failed to open {}", record.display()))?;
+            writeln!(log, "{}", mutation)?;
+        }
 
         self.io.output(wasm_tools::Output::Wasm {
             bytes: &wasm,
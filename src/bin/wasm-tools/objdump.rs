@@ -1,7 +1,13 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::ops::Range;
-use wasmparser::{Encoding, Parser, Payload::*, SectionReader};
+use wasmparser::{
+    BinaryReader, BranchHintSectionReader, CustomSectionReader, DataKind, Dylink0SectionReader,
+    Dylink0Subsection, ElementKind, Encoding, IndirectNameMap, Name, NameMap, NameSectionReader,
+    NamingReader, Parser, Payload::*, ProducersSectionReader, RelocSectionReader, SectionReader,
+    SectionWithLimitedItems, Type, TypeRef, ValType,
+};
 
 /// Dumps information about sections in a WebAssembly file.
 ///
@@ -11,6 +17,18 @@ use wasmparser::{Encoding, Parser, Payload::*, SectionReader};
 pub struct Opts {
     #[clap(flatten)]
     io: wasm_tools::InputOutput,
+
+    /// Show the byte range of each item within each section, and decode the
+    /// contents of known custom sections, such as the name section.
+    #[clap(short, long)]
+    details: bool,
+
+    /// Print a hexdump of the sections with the given name, such as `code` or
+    /// the name of a custom section.
+    ///
+    /// It's an error if there's no section with that name.
+    #[clap(long, value_name = "SECTION")]
+    hexdump: Vec<String>,
 }
 
 impl Opts {
@@ -20,29 +38,99 @@ impl Opts {
         let mut printer = Printer {
             indices: Vec::new(),
             output: self.io.output_writer()?,
+            input: &input,
+            details: self.details,
+            hexdump: self.hexdump.clone(),
+            hexdumped: HashSet::new(),
+            names: function_names(&input)?,
+            modules: Vec::new(),
+            num_modules: 0,
         };
         printer.indices.push(IndexSpace::default());
 
         for payload in Parser::new(0).parse_all(&input) {
             match payload? {
-                Version { .. } => {}
-
-                TypeSection(s) => printer.section(s, "types")?,
-                ImportSection(s) => printer.section(s, "imports")?,
-                FunctionSection(s) => printer.section(s, "functions")?,
-                TableSection(s) => printer.section(s, "tables")?,
-                MemorySection(s) => printer.section(s, "memories")?,
-                TagSection(s) => printer.section(s, "tags")?,
-                GlobalSection(s) => printer.section(s, "globals")?,
-                ExportSection(s) => printer.section(s, "exports")?,
+                Version { encoding, .. } => printer.version(encoding),
+
+                TypeSection(s) => printer.section_items(s, "types", |_, ty| {
+                    let Type::Func(ty) = ty;
+                    Ok(format!(
+                        "func ({}) -> ({})",
+                        val_types(ty.params()),
+                        val_types(ty.results())
+                    ))
+                })?,
+                ImportSection(s) => printer.section_items(s, "imports", |printer, import| {
+                    if let TypeRef::Func(_) = import.ty {
+                        if let Some(module) = printer.module() {
+                            module.num_imported_funcs += 1;
+                        }
+                    }
+                    Ok(format!("{}::{}", import.module, import.name))
+                })?,
+                FunctionSection(s) => {
+                    let mut i = 0;
+                    printer.section_items(s, "functions", |printer, ty| {
+                        let func = printer.module().map_or(0, |m| m.num_imported_funcs) + i;
+                        i += 1;
+                        Ok(format!("{}: type {}", printer.function_label(func), ty))
+                    })?
+                }
+                TableSection(s) => printer.section_items(s, "tables", |_, table| {
+                    Ok(format!(
+                        "{} {}",
+                        val_type(table.element_type),
+                        limits(table.initial, table.maximum),
+                    ))
+                })?,
+                MemorySection(s) => printer.section_items(s, "memories", |_, memory| {
+                    let mut label = format!(
+                        "{} {}",
+                        val_type(memory.index_type()),
+                        limits(memory.initial, memory.maximum)
+                    );
+                    if memory.shared {
+                        label.push_str(" shared");
+                    }
+                    Ok(label)
+                })?,
+                TagSection(s) => printer.section_items(s, "tags", |_, tag| {
+                    Ok(format!("type {}", tag.func_type_idx))
+                })?,
+                GlobalSection(s) => printer.section_items(s, "globals", |_, global| {
+                    let ty = global.ty;
+                    let mutable = if ty.mutable { "mut " } else { "" };
+                    Ok(format!("{}{}", mutable, val_type(ty.content_type)))
+                })?,
+                ExportSection(s) => {
+                    printer.section_items(s, "exports", |_, export| Ok(export.name.to_string()))?
+                }
                 StartSection { range, .. } => printer.section_raw(range, 1, "start")?,
-                ElementSection(s) => printer.section(s, "elements")?,
+                ElementSection(s) => printer.section_items(s, "elements", |_, element| {
+                    let kind = match element.kind {
+                        ElementKind::Passive => "passive".to_string(),
+                        ElementKind::Active { table_index, .. } => {
+                            format!("active table {}", table_index)
+                        }
+                        ElementKind::Declared => "declared".to_string(),
+                    };
+                    let count = element.items.get_items_reader()?.get_count();
+                    Ok(format!("{}, {} {}s", kind, count, val_type(element.ty)))
+                })?,
                 DataCountSection { range, .. } => printer.section_raw(range, 1, "data count")?,
-                DataSection(s) => printer.section(s, "data")?,
+                DataSection(s) => printer.section_items(s, "data", |_, data| {
+                    let kind = match data.kind {
+                        DataKind::Passive => "passive".to_string(),
+                        DataKind::Active { memory_index, .. } => {
+                            format!("active memory {}", memory_index)
+                        }
+                    };
+                    Ok(format!("{}, {} bytes", kind, data.data.len()))
+                })?,
                 CodeSectionStart { range, count, .. } => {
                     printer.section_raw(range, count, "code")?
                 }
-                CodeSectionEntry(_) => {}
+                CodeSectionEntry(body) => printer.function_body(body.range())?,
 
                 ModuleSection { range, .. } => {
                     printer.section_raw(range, 1, "module")?;
@@ -62,19 +150,38 @@ impl Opts {
                 ComponentStartSection(s) => printer.section_raw(s.range(), 1, "component start")?,
                 ComponentImportSection(s) => printer.section(s, "component imports")?,
                 ComponentExportSection(s) => printer.section(s, "component exports")?,
-                
-                CustomSection(c) => printer.section_raw(
-                    c.data_offset()..c.data_offset() + c.data().len(),
-                    1,
-                    &format!("custom {:?}", c.name()),
-                )?,
+                ComponentValueSection(s) => printer.section(s, "component values")?,
+
+                CustomSection(c) => {
+                    printer.section_raw(
+                        c.data_offset()..c.data_offset() + c.data().len(),
+                        1,
+                        &format!("custom {:?}", c.name()),
+                    )?;
+                    if printer.details {
+                        printer.custom_section(&c)?;
+                    }
+                }
 
                 UnknownSection { .. } => {}
 
-                End(_) => printer.end()?,
+                End(_) => {
+                    printer.modules.pop();
+                    printer.end()?
+                }
             }
         }
 
+        let missing = self
+            .hexdump
+            .iter()
+            .filter(|name| !printer.hexdumped.contains(*name))
+            .map(|name| format!("`{}`", name))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            bail!("no section named {} to hexdump", missing.join(" or "));
+        }
+
         Ok(())
     }
 }
@@ -86,12 +193,287 @@ struct IndexSpace {
     processing: Vec<Encoding>,
 }
 
-struct Printer {
+struct Printer<'a> {
     indices: Vec<IndexSpace>,
     output: Box<dyn Write>,
+    input: &'a [u8],
+    details: bool,
+    hexdump: Vec<String>,
+    /// The names in `hexdump` of the sections which were found.
+    hexdumped: HashSet<String>,
+    /// The function names of each core module, in the order that the modules
+    /// appear in the input.
+    names: Vec<HashMap<u32, String>>,
+    /// The core module being printed at each level of nesting, if any.
+    modules: Vec<Option<Module>>,
+    num_modules: usize,
 }
 
-impl Printer {
+/// The state of a core module being printed.
+struct Module {
+    /// The index of the module in `Printer::names`.
+    index: usize,
+    num_imported_funcs: u32,
+    num_bodies: u32,
+}
+
+impl<'a> Printer<'a> {
+    fn version(&mut self, encoding: Encoding) {
+        let module = match encoding {
+            Encoding::Module => {
+                self.num_modules += 1;
+                Some(Module {
+                    index: self.num_modules - 1,
+                    num_imported_funcs: 0,
+                    num_bodies: 0,
+                })
+            }
+            Encoding::Component => None,
+        };
+        self.modules.push(module);
+    }
+
+    fn module(&mut self) -> Option<&mut Module> {
+        self.modules.last_mut().and_then(|m| m.as_mut())
+    }
+
+    fn function_name(&self, func: u32) -> Option<&str> {
+        let module = self.modules.last()?.as_ref()?;
+        self.names.get(module.index)?.get(&func).map(|s| s.as_str())
+    }
+
+    /// The label of function `func`, with its name if it has one.
+    fn function_label(&self, func: u32) -> String {
+        match self.function_name(func) {
+            Some(name) => format!("func {} ({})", func, name),
+            None => format!("func {}", func),
+        }
+    }
+
+    /// Print a section, along with the byte range of each of its items when
+    /// showing details, labelled by `label`.
+    fn section_items<T>(
+        &mut self,
+        mut section: T,
+        name: &str,
+        mut label: impl FnMut(&mut Self, T::Item) -> Result<String>,
+    ) -> Result<()>
+    where
+        T: SectionWithLimitedItems + SectionReader,
+    {
+        self.section_raw(section.range(), section.get_count(), name)?;
+        if !self.details {
+            return Ok(());
+        }
+        let mut i = 0;
+        while !section.eof() {
+            let start = section.original_position();
+            let item = section.read()?;
+            let end = section.original_position();
+            let label = label(self, item)?;
+            self.item(i, start..end, &label)?;
+            i += 1;
+        }
+        Ok(())
+    }
+
+    fn item(&mut self, index: u32, range: Range<usize>, label: &str) -> Result<()> {
+        writeln!(
+            self.output,
+            "{:40} | {:#10x} - {:#10x} | {:9} bytes | {}",
+            format!("{}  [{}]", self.header(), index),
+            range.start,
+            range.end,
+            range.end - range.start,
+            label,
+        )?;
+        Ok(())
+    }
+
+    fn function_body(&mut self, range: Range<usize>) -> Result<()> {
+        if !self.details {
+            return Ok(());
+        }
+        let (func, index) = match self.module() {
+            Some(module) => {
+                module.num_bodies += 1;
+                (
+                    module.num_imported_funcs + module.num_bodies - 1,
+                    module.num_bodies - 1,
+                )
+            }
+            None => return Ok(()),
+        };
+        let label = self.function_label(func);
+        self.item(index, range, &label)
+    }
+
+    fn custom_section(&mut self, section: &CustomSectionReader) -> Result<()> {
+        let data = section.data();
+        let offset = section.data_offset();
+        let header = self.header();
+        match section.name() {
+            "dylink.0" => {
+                let mut reader = Dylink0SectionReader::new(data, offset)?;
+                while !reader.eof() {
+                    let line = match reader.read()? {
+                        Dylink0Subsection::MemInfo(info) => format!(
+                            "mem info: memory size {} (align {}), table size {} (align {})",
+                            info.memory_size,
+                            info.memory_alignment,
+                            info.table_size,
+                            info.table_alignment
+                        ),
+                        Dylink0Subsection::Needed(needed) => {
+                            format!("needed: {}", needed.join(", "))
+                        }
+                        Dylink0Subsection::ExportInfo(exports) => {
+                            let exports = exports
+                                .iter()
+                                .map(|e| format!("{} (flags {:#x})", e.name, e.flags))
+                                .collect::<Vec<_>>();
+                            format!("export info: {}", exports.join(", "))
+                        }
+                        Dylink0Subsection::ImportInfo(imports) => {
+                            let imports = imports
+                                .iter()
+                                .map(|i| {
+                                    format!("{}::{} (flags {:#x})", i.module, i.field, i.flags)
+                                })
+                                .collect::<Vec<_>>();
+                            format!("import info: {}", imports.join(", "))
+                        }
+                        Dylink0Subsection::Unknown { ty, data, .. } => {
+                            format!("unknown subsection {}: {} bytes", ty, data.len())
+                        }
+                    };
+                    writeln!(self.output, "{}  {}", header, line)?;
+                }
+            }
+            "name" => {
+                let mut reader = NameSectionReader::new(data, offset)?;
+                while !reader.eof() {
+                    let (kind, map) = match reader.read()? {
+                        Name::Module(name) => {
+                            writeln!(self.output, "{}  module: {}", header, name.get_name()?)?;
+                            continue;
+                        }
+                        Name::Function(map) => ("func", map),
+                        Name::Local(map) => {
+                            self.indirect_names(&header, "local", map)?;
+                            continue;
+                        }
+                        Name::Label(map) => {
+                            self.indirect_names(&header, "label", map)?;
+                            continue;
+                        }
+                        Name::Type(map) => ("type", map),
+                        Name::Table(map) => ("table", map),
+                        Name::Memory(map) => ("memory", map),
+                        Name::Global(map) => ("global", map),
+                        Name::Element(map) => ("elem", map),
+                        Name::Data(map) => ("data", map),
+                        Name::Unknown { ty, data, .. } => {
+                            writeln!(
+                                self.output,
+                                "{}  unknown subsection {}: {} bytes",
+                                header,
+                                ty,
+                                data.len()
+                            )?;
+                            continue;
+                        }
+                    };
+                    writeln!(
+                        self.output,
+                        "{}  {} names: {}",
+                        header,
+                        kind,
+                        name_map(&map)?
+                    )?;
+                }
+            }
+            "producers" => {
+                let mut reader = ProducersSectionReader::new(data, offset)?;
+                for _ in 0..reader.get_count() {
+                    let field = reader.read()?;
+                    let mut values = Vec::new();
+                    let mut values_reader = field.get_producer_field_values_reader()?;
+                    for _ in 0..values_reader.get_count() {
+                        let value = values_reader.read()?;
+                        values.push(format!("{} {}", value.name, value.version));
+                    }
+                    writeln!(
+                        self.output,
+                        "{}  {}: {}",
+                        header,
+                        field.name,
+                        values.join(", ")
+                    )?;
+                }
+            }
+            "metadata.code.branch_hint" => {
+                let mut reader = BranchHintSectionReader::new(data, offset)?;
+                for _ in 0..reader.get_count() {
+                    let func = reader.read()?;
+                    let mut hints = Vec::new();
+                    let mut hints_reader = func.get_hints_reader();
+                    for _ in 0..hints_reader.get_count() {
+                        let hint = hints_reader.read()?;
+                        let likely = if hint.taken { "likely" } else { "unlikely" };
+                        hints.push(format!("{:#x} {}", hint.func_offset, likely));
+                    }
+                    let name = self.function_label(func.func);
+                    writeln!(self.output, "{}  {}: {}", header, name, hints.join(", "))?;
+                }
+            }
+            "linking" => {
+                for symbol in linking_symbols(data)? {
+                    writeln!(self.output, "{}  {}", header, symbol)?;
+                }
+            }
+            name if name.starts_with("reloc.") => {
+                let mut reader = RelocSectionReader::new(data, offset)?;
+                writeln!(
+                    self.output,
+                    "{}  relocations for {:?}",
+                    header,
+                    reader.get_section_code()
+                )?;
+                for _ in 0..reader.get_count() {
+                    let reloc = reader.read()?;
+                    let addend = match reloc.addend {
+                        Some(addend) => format!(" + {}", addend),
+                        None => String::new(),
+                    };
+                    writeln!(
+                        self.output,
+                        "{}  {:#x}: {:?} {}{}",
+                        header, reloc.offset, reloc.ty, reloc.index, addend
+                    )?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Print the names of the locals or labels of each function.
+    fn indirect_names(&mut self, header: &str, kind: &str, map: IndirectNameMap<'_>) -> Result<()> {
+        let mut map = map.get_indirect_map()?;
+        for _ in 0..map.get_indirect_count() {
+            let names = map.read()?;
+            let func = self.function_label(names.indirect_index);
+            let names = naming_list(names.get_map()?)?;
+            writeln!(
+                self.output,
+                "{}  {} names of {}: {}",
+                header, kind, func, names
+            )?;
+        }
+        Ok(())
+    }
+
     fn start(&mut self, encoding: Encoding) -> Result<()> {
         if let Some(space) = self.indices.last_mut() {
             space.processing.push(encoding);
@@ -169,6 +551,45 @@ impl Printer {
             range.end - range.start,
             count,
         )?;
+
+        // Custom sections are displayed as `custom "name"`, but are matched by
+        // just their name.
+        let plain = name
+            .strip_prefix("custom ")
+            .and_then(|n| n.strip_prefix('"')?.strip_suffix('"'))
+            .unwrap_or(name);
+        if self.hexdump.iter().any(|h| h == plain) {
+            self.hexdumped.insert(plain.to_string());
+            self.hexdump(range)?;
+        }
+        Ok(())
+    }
+
+    fn hexdump(&mut self, range: Range<usize>) -> Result<()> {
+        let header = self.header();
+        let bytes = &self.input[range.clone()];
+        for (i, chunk) in bytes.chunks(16).enumerate() {
+            let hex = chunk
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let ascii = chunk
+                .iter()
+                .map(|b| match *b {
+                    0x20..=0x7e => *b as char,
+                    _ => '.',
+                })
+                .collect::<String>();
+            writeln!(
+                self.output,
+                "{}  {:#010x}: {:47} |{}|",
+                header,
+                range.start + i * 16,
+                hex,
+                ascii
+            )?;
+        }
         Ok(())
     }
 
@@ -188,3 +609,214 @@ impl Printer {
         return s;
     }
 }
+
+/// Collect the function names of each core module in the input, from its
+/// `name` section and, failing that, from the symbol table of its `linking`
+/// section.
+fn function_names(input: &[u8]) -> Result<Vec<HashMap<u32, String>>> {
+    let mut modules = Vec::new();
+    // The indices into `modules` of the core modules being parsed, along with
+    // their linking symbol names.
+    let mut stack: Vec<Option<(usize, HashMap<u32, String>)>> = Vec::new();
+    for payload in Parser::new(0).parse_all(input) {
+        match payload? {
+            Version { encoding, .. } => stack.push(match encoding {
+                Encoding::Module => {
+                    modules.push(HashMap::new());
+                    Some((modules.len() - 1, HashMap::new()))
+                }
+                Encoding::Component => None,
+            }),
+            End(_) => {
+                if let Some(Some((index, symbols))) = stack.pop() {
+                    for (func, name) in symbols {
+                        modules[index].entry(func).or_insert(name);
+                    }
+                }
+            }
+            CustomSection(c) => {
+                let (index, symbols) = match stack.last_mut() {
+                    Some(Some((index, symbols))) => (*index, symbols),
+                    _ => continue,
+                };
+                // Names are best effort, so malformed sections are ignored
+                // here; they are reported when decoding them with `--details`.
+                match c.name() {
+                    "name" => {
+                        let _ = read_names(c.data(), c.data_offset(), &mut modules[index]);
+                    }
+                    "linking" => {
+                        let _ = read_symbol_names(c.data(), symbols);
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(modules)
+}
+
+fn read_names(data: &[u8], offset: usize, names: &mut HashMap<u32, String>) -> Result<()> {
+    let mut reader = NameSectionReader::new(data, offset)?;
+    while !reader.eof() {
+        if let Name::Function(map) = reader.read()? {
+            let mut map = map.get_map()?;
+            for _ in 0..map.get_count() {
+                let naming = map.read()?;
+                names.insert(naming.index, naming.name.to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+const WASM_SYMBOL_TABLE: u8 = 8;
+const WASM_SYM_UNDEFINED: u32 = 0x10;
+const WASM_SYM_EXPLICIT_NAME: u32 = 0x40;
+
+/// A symbol from the symbol table of a `linking` section.
+struct Symbol<'a> {
+    kind: u8,
+    flags: u32,
+    index: Option<u32>,
+    name: Option<&'a str>,
+}
+
+impl std::fmt::Display for Symbol<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            0 => "function",
+            1 => "data",
+            2 => "global",
+            3 => "section",
+            4 => "tag",
+            5 => "table",
+            _ => "unknown",
+        };
+        write!(f, "symbol {}", kind)?;
+        if let Some(index) = self.index {
+            write!(f, " {}", index)?;
+        }
+        if let Some(name) = self.name {
+            write!(f, " {}", name)?;
+        }
+        write!(f, " (flags {:#x})", self.flags)
+    }
+}
+
+/// Read the symbol table of a `linking` section, as described by the [linking
+/// conventions].
+///
+/// [linking conventions]: https://github.com/WebAssembly/tool-conventions/blob/main/Linking.md
+fn read_symbols(data: &[u8]) -> Result<Vec<Symbol<'_>>> {
+    let mut reader = BinaryReader::new(data);
+    let version = reader.read_var_u32()?;
+    anyhow::ensure!(
+        version == 2,
+        "unsupported linking section version {}",
+        version
+    );
+    let mut symbols = Vec::new();
+    while !reader.eof() {
+        let ty = reader.read_u8()?;
+        let size = reader.read_var_u32()?;
+        let payload = reader.read_bytes(size as usize)?;
+        if ty != WASM_SYMBOL_TABLE {
+            continue;
+        }
+        let mut reader = BinaryReader::new(payload);
+        for _ in 0..reader.read_var_u32()? {
+            let kind = reader.read_u8()?;
+            let flags = reader.read_var_u32()?;
+            let defined = flags & WASM_SYM_UNDEFINED == 0;
+            let (index, name) = match kind {
+                // Functions, globals, tags, and tables.
+                0 | 2 | 4 | 5 => {
+                    let index = reader.read_var_u32()?;
+                    let name = if defined || flags & WASM_SYM_EXPLICIT_NAME != 0 {
+                        Some(reader.read_string()?)
+                    } else {
+                        None
+                    };
+                    (Some(index), name)
+                }
+                // Data.
+                1 => {
+                    let name = reader.read_string()?;
+                    if defined {
+                        // The segment index, offset, and size.
+                        reader.read_var_u32()?;
+                        reader.read_var_u32()?;
+                        reader.read_var_u32()?;
+                    }
+                    (None, Some(name))
+                }
+                // Sections.
+                3 => (Some(reader.read_var_u32()?), None),
+                _ => anyhow::bail!("unknown symbol kind {}", kind),
+            };
+            symbols.push(Symbol {
+                kind,
+                flags,
+                index,
+                name,
+            });
+        }
+    }
+    Ok(symbols)
+}
+
+fn read_symbol_names(data: &[u8], names: &mut HashMap<u32, String>) -> Result<()> {
+    for symbol in read_symbols(data)? {
+        if let (0, Some(index), Some(name)) = (symbol.kind, symbol.index, symbol.name) {
+            names.insert(index, name.to_string());
+        }
+    }
+    Ok(())
+}
+
+fn linking_symbols(data: &[u8]) -> Result<Vec<String>> {
+    Ok(read_symbols(data)?.iter().map(|s| s.to_string()).collect())
+}
+
+/// The text format name of `ty`.
+fn val_type(ty: ValType) -> &'static str {
+    match ty {
+        ValType::I32 => "i32",
+        ValType::I64 => "i64",
+        ValType::F32 => "f32",
+        ValType::F64 => "f64",
+        ValType::V128 => "v128",
+        ValType::FuncRef => "funcref",
+        ValType::ExternRef => "externref",
+    }
+}
+
+fn val_types(tys: &[ValType]) -> String {
+    tys.iter()
+        .map(|ty| val_type(*ty))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn limits(initial: u64, maximum: Option<u64>) -> String {
+    match maximum {
+        Some(maximum) => format!("{}..{}", initial, maximum),
+        None => format!("{}..", initial),
+    }
+}
+
+/// The names of a name map, as a list of `index: name` pairs.
+fn name_map(map: &NameMap<'_>) -> Result<String> {
+    naming_list(map.get_map()?)
+}
+
+fn naming_list(mut names: NamingReader<'_>) -> Result<String> {
+    let mut list = Vec::new();
+    for _ in 0..names.get_count() {
+        let naming = names.read()?;
+        list.push(format!("{}: {}", naming.index, naming.name));
+    }
+    Ok(list.join(", "))
+}
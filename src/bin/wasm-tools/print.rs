@@ -11,6 +11,30 @@ pub struct Opts {
     /// as comments for debugging.
     #[clap(short, long)]
     print_offsets: bool,
+
+    /// Demangle Rust and C++ symbol names found in the `name` section when
+    /// printing function names.
+    #[clap(long)]
+    demangle: bool,
+
+    /// Only print the function with this name or index.
+    ///
+    /// This may be passed multiple times, and is combined with `--section`
+    /// so that only the selected items are printed.
+    #[clap(long, value_name = "NAME_OR_INDEX")]
+    func: Vec<String>,
+
+    /// Only print the sections with this name, such as `type`, `import`,
+    /// `func`, `data`, or the name of a custom section.
+    ///
+    /// This may be passed multiple times.
+    #[clap(long, value_name = "NAME")]
+    section: Vec<String>,
+
+    /// Elide function bodies and the contents of element and data segments,
+    /// to get an overview of a large module.
+    #[clap(long)]
+    skeleton: bool,
 }
 
 impl Opts {
@@ -18,6 +42,14 @@ impl Opts {
         let wasm = self.io.parse_input_wasm()?;
         let mut printer = wasmprinter::Printer::new();
         printer.print_offsets(self.print_offsets);
+        printer.demangle_names(self.demangle);
+        printer.skeleton(self.skeleton);
+        for func in &self.func {
+            printer.filter_func(func);
+        }
+        for section in &self.section {
+            printer.filter_section(section);
+        }
         let wat = printer.print(&wasm)?;
         self.io.output(wasm_tools::Output::Wat(&wat))?;
         Ok(())
@@ -1,7 +1,10 @@
 use anyhow::{Context, Result};
 use clap::Parser;
 use is_executable::IsExecutable;
+use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
 use tempfile::NamedTempFile;
 use wasm_shrink::{IsInteresting, WasmShrink};
 
@@ -27,18 +30,30 @@ use wasm_shrink::{IsInteresting, WasmShrink};
 pub struct Opts {
     /// The output file path to write the shrunken Wasm file to.
     ///
-    /// By default, a file path based on the input will be generated.
+    /// By default, a file path based on the input will be generated, so this
+    /// is required when the input is read from stdin.
     #[clap(short, long)]
     output: Option<PathBuf>,
 
+    /// Run the predicate as a single, long-lived process instead of once per
+    /// candidate.
+    ///
+    /// The predicate is spawned without arguments and is sent each candidate
+    /// Wasm on its stdin, as a 32-bit little-endian length followed by that
+    /// many bytes. For each candidate, it must reply on its stdout with a line
+    /// containing `1` if the Wasm is interesting and `0` otherwise. With
+    /// `--jobs`, a predicate process is spawned for each concurrent job.
+    #[clap(long)]
+    persistent: bool,
+
     #[clap(flatten)]
     shrink: WasmShrink,
 
     /// The interestingness predicate script.
     predicate: PathBuf,
 
-    /// The input Wasm.
-    input: PathBuf,
+    #[clap(flatten)]
+    input: wasm_tools::InputArg,
 }
 
 impl Opts {
@@ -55,75 +70,91 @@ impl Opts {
             self.predicate.display()
         );
 
-        let input = wat::parse_file(&self.input)
-            .with_context(|| format!("Failed to read input Wasm file: {}", self.input.display()))?;
+        let input = Vec::from(
+            self.input
+                .parse_wasm()
+                .with_context(|| format!("Failed to read input Wasm: {}", self.input.name()))?,
+        );
         let initial_size = input.len();
 
-        let output = self
-            .output
-            .clone()
-            .unwrap_or_else(|| self.input.with_extension("shrunken.wasm"));
+        let output = match (&self.output, self.input.path()) {
+            (Some(output), _) => output.clone(),
+            (None, Some(input)) => input.with_extension("shrunken.wasm"),
+            (None, None) => anyhow::bail!("`--output` is required when reading from stdin"),
+        };
         log::info!("Will write shrunken Wasm file to: {}", output.display());
 
-        let predicate = make_predicate(&self.predicate);
-
-        let shrunken = self
-            .shrink
-            .on_new_smallest(Some(Box::new({
-                let output = output.clone();
-                move |new_smallest: &[u8]| {
-                    // Write the Wasm to a temp file and then move that to the output
-                    // path as a second, atomic step. This ensures that the output is
-                    // always a valid, interesting, shrunken Wasm file, even in the
-                    // presence of the user doing `Ctrl-C`.
-                    //
-                    // Note that to have the highest likelihood of the rename to succeed
-                    // the temporary file is placed in the same directory as the
-                    // destination. This attempts to avoid possibilities where the
-                    // system tmp directory is not on the same filesystem as the
-                    // destination, which would prevent a rename.
-                    let tmp = match output.parent() {
-                        Some(parent) => NamedTempFile::new_in(parent),
-                        None => NamedTempFile::new(),
-                    };
-                    let tmp = tmp.context("Failed to create a temporary file")?;
-                    std::fs::write(tmp.path(), new_smallest).with_context(|| {
-                        format!("Failed to write to file: {}", tmp.path().display())
-                    })?;
-                    std::fs::rename(tmp.path(), &output).with_context(|| {
-                        format!(
-                            "Failed to rename {} to {}",
-                            tmp.path().display(),
-                            output.display()
-                        )
-                    })?;
-
-                    println!(
-                        "{} bytes ({:.02}% smaller)",
-                        new_smallest.len(),
-                        (100.0 - (new_smallest.len() as f64 / initial_size as f64 * 100.0))
-                    );
-
-                    // Now write the WAT disassembly as well.
-                    match wasmprinter::print_bytes(new_smallest) {
-                        Err(e) => {
-                            // Ignore disassembly errors, since this isn't critical for
-                            // shrinking.
-                            log::warn!("Error disassembling the shrunken Wasm into WAT: {}", e);
-                        }
-                        Ok(wat) => {
-                            let wat_path = output.with_extension("wat");
-                            log::info!("Writing WAT disassembly to {}", wat_path.display());
-                            std::fs::write(&wat_path, wat).with_context(|| {
-                                format!("Failed to write WAT disassembly to {}", wat_path.display())
-                            })?;
-                        }
-                    }
+        let shrink = self.shrink.on_new_smallest(Some(Box::new({
+            let output = output.clone();
+            move |new_smallest: &[u8]| {
+                // Write the Wasm to a temp file and then move that to the output
+                // path as a second, atomic step. This ensures that the output is
+                // always a valid, interesting, shrunken Wasm file, even in the
+                // presence of the user doing `Ctrl-C`.
+                //
+                // Note that to have the highest likelihood of the rename to succeed
+                // the temporary file is placed in the same directory as the
+                // destination. This attempts to avoid possibilities where the
+                // system tmp directory is not on the same filesystem as the
+                // destination, which would prevent a rename.
+                let tmp = match output.parent() {
+                    Some(parent) => NamedTempFile::new_in(parent),
+                    None => NamedTempFile::new(),
+                };
+                let tmp = tmp.context("Failed to create a temporary file")?;
+                std::fs::write(tmp.path(), new_smallest).with_context(|| {
+                    format!("Failed to write to file: {}", tmp.path().display())
+                })?;
+                std::fs::rename(tmp.path(), &output).with_context(|| {
+                    format!(
+                        "Failed to rename {} to {}",
+                        tmp.path().display(),
+                        output.display()
+                    )
+                })?;
 
-                    Ok(())
+                println!(
+                    "{} bytes ({:.02}% smaller)",
+                    new_smallest.len(),
+                    (100.0 - (new_smallest.len() as f64 / initial_size as f64 * 100.0))
+                );
+
+                // Now write the WAT disassembly as well.
+                match wasmprinter::print_bytes(new_smallest) {
+                    Err(e) => {
+                        // Ignore disassembly errors, since this isn't critical for
+                        // shrinking.
+                        log::warn!("Error disassembling the shrunken Wasm into WAT: {}", e);
+                    }
+                    Ok(wat) => {
+                        let wat_path = output.with_extension("wat");
+                        log::info!("Writing WAT disassembly to {}", wat_path.display());
+                        std::fs::write(&wat_path, wat).with_context(|| {
+                            format!("Failed to write WAT disassembly to {}", wat_path.display())
+                        })?;
+                    }
                 }
-            })))
-            .run(input, predicate)?;
+
+                Ok(())
+            }
+        })));
+
+        let shrunken = if self.persistent {
+            // Each concurrently running job gets its own predicate process.
+            let idle = Mutex::new(Vec::new());
+            shrink.run_parallel(input, |wasm| {
+                let predicate = idle.lock().unwrap().pop();
+                let mut predicate = match predicate {
+                    Some(predicate) => predicate,
+                    None => PersistentPredicate::spawn(&self.predicate)?,
+                };
+                let result = predicate.test(wasm)?;
+                idle.lock().unwrap().push(predicate);
+                Ok(result)
+            })?
+        } else {
+            shrink.run_parallel(input, make_predicate(&self.predicate))?
+        };
 
         let wat = wasmprinter::print_bytes(&shrunken.output)
             .unwrap_or_else(|e| format!("<error disassembling WAT: {}>", e));
@@ -177,7 +208,7 @@ impl IsInteresting for OutputIsInteresting {
 
 fn make_predicate<'a>(
     predicate_script: &'a Path,
-) -> impl FnMut(&[u8]) -> Result<OutputIsInteresting> + 'a {
+) -> impl Fn(&[u8]) -> Result<OutputIsInteresting> + Sync + 'a {
     move |wasm| {
         let tmp = NamedTempFile::new().context("Failed to create a temporary file.")?;
         std::fs::write(tmp.path(), wasm).with_context(|| {
@@ -213,3 +244,85 @@ fn make_predicate<'a>(
         Ok(OutputIsInteresting(output))
     }
 }
+
+/// A predicate script that runs as a single process for the whole shrink
+/// task. See the `--persistent` flag for the protocol.
+struct PersistentPredicate<'a> {
+    script: &'a Path,
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+struct ReplyIsInteresting(String);
+
+impl std::fmt::Display for ReplyIsInteresting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "persistent predicate replied: {}", self.0)
+    }
+}
+
+impl IsInteresting for ReplyIsInteresting {
+    fn is_interesting(&self) -> bool {
+        self.0 == "1"
+    }
+}
+
+impl<'a> PersistentPredicate<'a> {
+    fn spawn(script: &'a Path) -> Result<Self> {
+        let mut child = Command::new(script)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .with_context(|| format!("Failed to run predicate script '{}'", script.display()))?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Ok(PersistentPredicate {
+            script,
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    fn test(&mut self, wasm: &[u8]) -> Result<ReplyIsInteresting> {
+        let len = u32::try_from(wasm.len()).context("Candidate Wasm is too large")?;
+        self.stdin
+            .write_all(&len.to_le_bytes())
+            .and_then(|()| self.stdin.write_all(wasm))
+            .and_then(|()| self.stdin.flush())
+            .with_context(|| {
+                format!(
+                    "Failed to send candidate to predicate script '{}'",
+                    self.script.display()
+                )
+            })?;
+
+        let mut reply = String::new();
+        self.stdout.read_line(&mut reply).with_context(|| {
+            format!(
+                "Failed to read reply from predicate script '{}'",
+                self.script.display()
+            )
+        })?;
+        let reply = reply.trim();
+        anyhow::ensure!(
+            reply == "0" || reply == "1",
+            "Predicate script '{}' sent an invalid reply: {:?}",
+            self.script.display(),
+            reply
+        );
+        log::trace!("persistent predicate script reply: {}", reply);
+        Ok(ReplyIsInteresting(reply.to_string()))
+    }
+}
+
+impl Drop for PersistentPredicate<'_> {
+    fn drop(&mut self) {
+        // The predicate may loop forever waiting for candidates, so don't
+        // wait for it to exit on its own.
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
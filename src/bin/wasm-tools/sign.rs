@@ -0,0 +1,165 @@
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use wasm_metadata::{Digest, Ed25519Signer, Signer};
+use wasm_tools::Output;
+
+/// Signs a WebAssembly module or component.
+///
+/// The signature is made over the SHA-256 digest of the input's header and
+/// every top-level section except `signature` custom sections, and is stored
+/// with the digest in a `signature` custom section. Signing an already
+/// signed input adds another signature, as long as nothing else has changed
+/// since the first one was made.
+///
+/// The key is either an Ed25519 key file holding the hex encoding of a
+/// 32-byte seed, or an external signer program. The program is passed the
+/// raw digest on stdin and must write the raw signature to stdout.
+///
+/// The identity of the signer and the byte ranges covered by the signature
+/// are printed to stderr.
+///
+/// The layout of the `signature` section is specific to `wasm-tools` and is
+/// documented on `wasm_metadata::SignatureSection`.
+#[derive(clap::Parser)]
+pub struct Opts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// The Ed25519 key file to sign with.
+    #[clap(long, value_name = "FILE", required_unless_present_any = &["signer", "digest-only"])]
+    key: Option<PathBuf>,
+
+    /// An external program to sign with instead of a key file.
+    #[clap(
+        long,
+        value_name = "PROGRAM",
+        conflicts_with = "key",
+        requires_all = &["key-id", "algorithm"]
+    )]
+    signer: Option<PathBuf>,
+
+    /// An argument to pass to the external signer, which may be passed
+    /// multiple times.
+    #[clap(long, value_name = "ARG", requires = "signer", allow_hyphen_values = true)]
+    signer_arg: Vec<String>,
+
+    /// The key id recorded for signatures made by the external signer.
+    #[clap(long, requires = "signer")]
+    key_id: Option<String>,
+
+    /// The algorithm recorded for signatures made by the external signer.
+    #[clap(long, requires = "signer")]
+    algorithm: Option<String>,
+
+    /// Only embed the digest, without signing it.
+    #[clap(long, conflicts_with_all = &["key", "signer"])]
+    digest_only: bool,
+
+    /// Print the public key of the `--key` file in hex and exit.
+    #[clap(long, requires = "key")]
+    print_public_key: bool,
+
+    /// Output the text format of WebAssembly instead of the binary format.
+    #[clap(short = 't', long)]
+    wat: bool,
+}
+
+/// Signs by running an external program.
+struct ExternalSigner<'a> {
+    program: &'a PathBuf,
+    args: &'a [String],
+    key_id: String,
+    algorithm: String,
+}
+
+impl Signer for ExternalSigner<'_> {
+    fn algorithm(&self) -> String {
+        self.algorithm.clone()
+    }
+
+    fn key_id(&self) -> String {
+        self.key_id.clone()
+    }
+
+    fn sign(&self, digest: &[u8]) -> Result<Vec<u8>> {
+        let program = self.program.display();
+        let mut child = Command::new(self.program)
+            .args(self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to run signer `{program}`"))?;
+        // A signer which exits without reading the digest is reported by its
+        // exit status rather than by the failed write.
+        let written = child.stdin.take().unwrap().write_all(digest);
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!("signer `{program}` failed: {}", output.status);
+        }
+        written.with_context(|| format!("failed to write the digest to signer `{program}`"))?;
+        if output.stdout.is_empty() {
+            bail!("signer `{program}` produced an empty signature");
+        }
+        Ok(output.stdout)
+    }
+}
+
+impl Opts {
+    pub fn run(&self) -> Result<()> {
+        let key = match &self.key {
+            Some(path) => {
+                let text = std::fs::read_to_string(path)
+                    .with_context(|| format!("failed to read `{}`", path.display()))?;
+                Some(
+                    Ed25519Signer::from_hex(&text)
+                        .with_context(|| format!("invalid key file `{}`", path.display()))?,
+                )
+            }
+            None => None,
+        };
+        if self.print_public_key {
+            println!("{}", key.unwrap().public_key_hex());
+            return Ok(());
+        }
+
+        let input = self.io.parse_input_wasm()?;
+        let external;
+        let signer: Option<&dyn Signer> = match (&key, &self.signer) {
+            (Some(key), _) => Some(key),
+            (None, Some(program)) => {
+                external = ExternalSigner {
+                    program,
+                    args: &self.signer_arg,
+                    key_id: self.key_id.clone().unwrap(),
+                    algorithm: self.algorithm.clone().unwrap(),
+                };
+                Some(&external)
+            }
+            (None, None) => None,
+        };
+        let output = match signer {
+            Some(signer) => wasm_metadata::sign(&input, signer)?,
+            None => wasm_metadata::embed_digest(&input)?,
+        };
+
+        let digest = Digest::of(&output)?;
+        match signer {
+            Some(signer) => eprintln!(
+                "signed by {} key `{}`",
+                signer.algorithm(),
+                signer.key_id()
+            ),
+            None => eprintln!("embedded digest without signing"),
+        }
+        for range in &digest.covered {
+            eprintln!("covers bytes {:#x}..{:#x}", range.start, range.end);
+        }
+
+        self.io.output(Output::Wasm {
+            bytes: &output,
+            wat: self.wat,
+        })
+    }
+}
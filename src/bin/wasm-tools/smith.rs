@@ -2,10 +2,12 @@ use anyhow::{Context, Result};
 use arbitrary::Arbitrary;
 use clap::Parser;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::io::{stdin, Read};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
-use wasm_smith::{InstructionKind, InstructionKinds, MaybeInvalidModule, Module};
+use wasm_smith::{InstructionKind, InstructionKinds, InvalidityClass, MaybeInvalidModule, Module};
+use wasmparser::WasmFeatures;
 
 /// A WebAssembly test case generator.
 ///
@@ -37,7 +39,7 @@ use wasm_smith::{InstructionKind, InstructionKinds, MaybeInvalidModule, Module};
 pub struct Opts {
     /// The arbitrary input seed.
     ///
-    /// `stdin` is used if this argument is not supplied.
+    /// `stdin` is used if this argument is not supplied or is `-`.
     #[clap(parse(from_os_str))]
     input: Option<PathBuf>,
 
@@ -62,6 +64,15 @@ pub struct Opts {
     #[clap(long = "maybe-invalid")]
     maybe_invalid: bool,
 
+    /// Generate a module that is invalid in exactly one place, in one of the
+    /// given classes of invalidity.
+    ///
+    /// Available classes: type-error, malformed-leb, out-of-range-index.
+    /// Specify multiple classes with a comma-separated list: e.g.,
+    /// `--invalidity type-error,malformed-leb`
+    #[clap(long = "invalidity", use_value_delimiter = true)]
+    invalidity: Option<Vec<InvalidityClass>>,
+
     /// The default amount of fuel used with `--ensure-termination`.
     ///
     /// This is roughly the number of loop iterations and function calls that
@@ -69,7 +80,16 @@ pub struct Opts {
     #[clap(short = 'f', long = "fuel")]
     fuel: Option<u32>,
 
-    /// JSON configuration file with settings to control the wasm output.
+    /// JSON or TOML configuration file with settings to control the wasm
+    /// output.
+    ///
+    /// The file is parsed as TOML if its extension is `.toml`, and as JSON
+    /// otherwise. Its keys are the same as the names of the flags below,
+    /// without the leading `--`, and flags take precedence over the file.
+    /// The file can additionally contain an `instruction-kind-weights` table
+    /// mapping instruction kinds to their relative weights, and a
+    /// `memory-offset-choices` array of three weights for in-bounds, possibly
+    /// in-bounds, and out-of-bounds memory offsets.
     #[clap(short = 'c', long = "config", parse(from_os_str))]
     config: Option<PathBuf>,
 
@@ -114,6 +134,10 @@ struct Config {
     min_data_segments: Option<usize>,
     #[clap(long = "max-data-segments")]
     max_data_segments: Option<usize>,
+    #[clap(long = "min-elements")]
+    min_elements: Option<usize>,
+    #[clap(long = "max-elements")]
+    max_elements: Option<usize>,
     #[clap(long = "max-instructions")]
     max_instructions: Option<usize>,
     #[clap(long = "min-memories")]
@@ -136,8 +160,18 @@ struct Config {
     max_instances: Option<usize>,
     #[clap(long = "max-modules")]
     max_modules: Option<usize>,
+    #[clap(long = "max-components")]
+    max_components: Option<usize>,
+    #[clap(long = "max-values")]
+    max_values: Option<usize>,
     #[clap(long = "min-uleb-size")]
     min_uleb_size: Option<u8>,
+    /// Comma-separated list of WebAssembly features which generated modules
+    /// may use, as with `wasm-tools validate --features`.
+    ///
+    /// Flags for individual proposals, such as `--simd`, take precedence.
+    #[clap(long = "features")]
+    features: Option<WasmFeatures>,
     #[clap(long = "bulk-memory")]
     #[serde(rename = "bulk-memory")]
     bulk_memory_enabled: Option<bool>,
@@ -150,6 +184,9 @@ struct Config {
     #[clap(long = "relaxed-simd")]
     #[serde(rename = "relaxed-simd")]
     relaxed_simd_enabled: Option<bool>,
+    /// Only generate relaxed SIMD instructions with fully specified results.
+    #[clap(long = "relaxed-simd-deterministic-only")]
+    relaxed_simd_deterministic_only: Option<bool>,
     #[clap(long = "exception-handling")]
     #[serde(rename = "exception-handling")]
     exceptions_enabled: Option<bool>,
@@ -187,11 +224,53 @@ struct Config {
     #[clap(long = "threads")]
     #[serde(rename = "threads")]
     threads_enabled: Option<bool>,
+    #[clap(long = "tail-call")]
+    #[serde(rename = "tail-call")]
+    tail_call_enabled: Option<bool>,
+    /// Allow function bodies made of thousands of nested blocks.
+    #[clap(long = "pathological-deep-nesting")]
+    pathological_deep_nesting: Option<bool>,
+    /// Allow function bodies made of a `br_table` with thousands of targets.
+    #[clap(long = "pathological-br-tables")]
+    pathological_br_tables: Option<bool>,
+    /// Allow functions with thousands of locals.
+    #[clap(long = "pathological-locals")]
+    pathological_locals: Option<bool>,
+    /// Allow function bodies made of tens of thousands of instructions.
+    #[clap(long = "pathological-functions")]
+    pathological_functions: Option<bool>,
+    /// Define as many functions as `--max-funcs` allows.
+    #[clap(long = "pathological-function-count")]
+    pathological_function_count: Option<bool>,
+    #[clap(skip)]
+    memory_offset_choices: Option<(u32, u32, u32)>,
+    #[clap(skip)]
+    instruction_kind_weights: Option<HashMap<String, u32>>,
+}
+
+impl Config {
+    fn from_file(path: &Path) -> Result<Config> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config: {}", path.display()))?;
+        let config: Config = if path.extension().is_some_and(|e| e == "toml") {
+            toml::from_str(&contents)
+                .with_context(|| format!("failed to decode toml config: {}", path.display()))?
+        } else {
+            serde_json::from_str(&contents)
+                .with_context(|| format!("failed to decode json config: {}", path.display()))?
+        };
+        for kind in config.instruction_kind_weights.iter().flat_map(|w| w.keys()) {
+            kind.parse::<InstructionKind>()
+                .map_err(anyhow::Error::msg)
+                .with_context(|| format!("invalid config: {}", path.display()))?;
+        }
+        Ok(config)
+    }
 }
 
 impl Opts {
     pub fn run(&self) -> Result<()> {
-        let seed = match &self.input {
+        let seed = match self.input.as_deref().filter(|f| *f != Path::new("-")) {
             Some(f) => {
                 std::fs::read(f).with_context(|| format!("failed to read '{}'", f.display()))?
             }
@@ -205,7 +284,14 @@ impl Opts {
         };
 
         let mut u = arbitrary::Unstructured::new(&seed);
-        let wasm_bytes = if self.maybe_invalid {
+        let wasm_bytes = if let Some(classes) = &self.invalidity {
+            MaybeInvalidModule::with_invalidity(&mut u, classes)
+                .unwrap_or_else(|e| {
+                    eprintln!("error: failed to generate module: {}", e);
+                    process::exit(2);
+                })
+                .to_bytes()
+        } else if self.maybe_invalid {
             MaybeInvalidModule::arbitrary(&mut u)
                 .unwrap_or_else(|e| {
                     eprintln!("error: failed to generate module: {}", e);
@@ -213,19 +299,12 @@ impl Opts {
                 })
                 .to_bytes()
         } else {
-            let json = match &self.config {
-                Some(path) => {
-                    let json = std::fs::read_to_string(&path).with_context(|| {
-                        format!("failed to read json config: {}", path.display())
-                    })?;
-                    serde_json::from_str(&json).with_context(|| {
-                        format!("failed to decode json config: {}", path.display())
-                    })?
-                }
+            let file = match &self.config {
+                Some(path) => Config::from_file(path)?,
                 None => Config::default(),
             };
-            let config = CliAndJsonConfig {
-                json,
+            let config = CliAndFileConfig {
+                file,
                 cli: self.module_config.clone(),
             };
             let mut module = Module::new(config, &mut u).unwrap_or_else(|e| {
@@ -251,18 +330,40 @@ macro_rules! fields {
         ($field:ident, $ty:ty, $default:expr),
     )*) => ($(
         fn $field(&self) -> $ty {
-            self.cli.$field.or(self.json.$field).unwrap_or($default)
+            self.cli.$field.or(self.file.$field).unwrap_or($default)
+        }
+    )*)
+}
+
+/// Like `fields!`, for the flags which enable proposals, falling back to
+/// `--features` before the default.
+macro_rules! proposals {
+    ($(
+        ($field:ident, $feature:ident, $default:expr),
+    )*) => ($(
+        fn $field(&self) -> bool {
+            self.cli
+                .$field
+                .or(self.file.$field)
+                .or(self.proposals().map(|f| f.$feature))
+                .unwrap_or($default)
         }
     )*)
 }
 
 #[derive(Clone, Debug)]
-struct CliAndJsonConfig {
-    json: Config,
+struct CliAndFileConfig {
+    file: Config,
     cli: Config,
 }
 
-impl wasm_smith::Config for CliAndJsonConfig {
+impl CliAndFileConfig {
+    fn proposals(&self) -> Option<WasmFeatures> {
+        self.cli.features.or(self.file.features)
+    }
+}
+
+impl wasm_smith::Config for CliAndFileConfig {
     fields! {
         (min_types, usize, 0),
         (max_types, usize, 100),
@@ -281,6 +382,8 @@ impl wasm_smith::Config for CliAndJsonConfig {
         (max_element_segments, usize, 100),
         (min_data_segments, usize, 0),
         (max_data_segments, usize, 100),
+        (min_elements, usize, 0),
+        (max_elements, usize, 100),
         (max_instructions, usize, 100),
         (min_memories, u32, 0),
         (max_memories, usize, 1),
@@ -291,29 +394,42 @@ impl wasm_smith::Config for CliAndJsonConfig {
         (table_max_size_required, bool, false),
         (max_instances, usize, 10),
         (max_modules, usize, 10),
+        (max_components, usize, 10),
+        (max_values, usize, 10),
+        (memory_offset_choices, (u32, u32, u32), (75, 24, 1)),
         (min_uleb_size, u8, 1),
-        (bulk_memory_enabled, bool, true),
-        (reference_types_enabled, bool, true),
-        (simd_enabled, bool, true),
-        (relaxed_simd_enabled, bool, false),
-        (exceptions_enabled, bool, false),
-        (multi_value_enabled, bool, true),
-        (saturating_float_to_int_enabled, bool, true),
-        (sign_extension_ops_enabled, bool, true),
-        (memory64_enabled, bool, false),
+        (relaxed_simd_deterministic_only, bool, false),
         (allow_start_export, bool, true),
         (max_aliases, usize, 1000),
         (max_nesting_depth, usize, 1000),
         (max_type_size, u32, 1000),
         (canonicalize_nans, bool, false),
         (generate_custom_sections, bool, false),
-        (threads_enabled, bool, false),
+        (pathological_deep_nesting, bool, false),
+        (pathological_br_tables, bool, false),
+        (pathological_locals, bool, false),
+        (pathological_functions, bool, false),
+        (pathological_function_count, bool, false),
+    }
+
+    proposals! {
+        (bulk_memory_enabled, bulk_memory, true),
+        (reference_types_enabled, reference_types, true),
+        (simd_enabled, simd, true),
+        (relaxed_simd_enabled, relaxed_simd, false),
+        (exceptions_enabled, exceptions, false),
+        (multi_value_enabled, multi_value, true),
+        (saturating_float_to_int_enabled, saturating_float_to_int, true),
+        (sign_extension_ops_enabled, sign_extension, true),
+        (memory64_enabled, memory64, false),
+        (threads_enabled, threads, false),
+        (tail_call_enabled, tail_call, false),
     }
 
     fn max_memory_pages(&self, _is_64: bool) -> u64 {
         self.cli
             .max_memory_pages
-            .or(self.json.max_memory_pages)
+            .or(self.file.max_memory_pages)
             .unwrap_or(65536)
     }
 
@@ -322,19 +438,29 @@ impl wasm_smith::Config for CliAndJsonConfig {
             .cli
             .allowed_instructions
             .as_ref()
-            .or(self.json.allowed_instructions.as_ref())
+            .or(self.file.allowed_instructions.as_ref())
         {
             Some(ks) => InstructionKinds::new(ks),
             None => InstructionKinds::all(),
         }
     }
 
+    fn instruction_kind_weight(&self, kind: InstructionKind) -> u32 {
+        // The names of kinds were already checked when loading the config.
+        self.file
+            .instruction_kind_weights
+            .iter()
+            .flatten()
+            .find(|(name, _)| name.parse() == Ok(kind))
+            .map_or(1, |(_, weight)| *weight)
+    }
+
     fn available_imports(&self) -> Option<Cow<'static, [u8]>> {
         let file = self
             .cli
             .available_imports
             .as_ref()
-            .or(self.json.available_imports.as_ref())?;
+            .or(self.file.available_imports.as_ref())?;
         Some(wat::parse_file(file).unwrap().into())
     }
 }
@@ -0,0 +1,363 @@
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::ops::Range;
+use wasmparser::{
+    ComponentExternalKind, ComponentTypeRef, Encoding, ExternalKind, FunctionBody, Name,
+    NameSectionReader, Parser, Payload::*, SectionReader, TypeRef,
+};
+
+/// Reports statistics about the size and structure of a WebAssembly file.
+///
+/// This includes the size of each section, the largest functions, the most
+/// frequently used instructions, and the number of imports and exports of
+/// each kind. The `--json` output is intended to be consumed by tools, for
+/// example to catch size regressions in CI.
+#[derive(clap::Parser)]
+pub struct Opts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// The number of largest functions and most frequent instructions to
+    /// report, or 0 to report all of them.
+    #[clap(short = 'n', long, default_value = "20")]
+    top: usize,
+
+    /// Output the statistics as JSON instead of text.
+    #[clap(long)]
+    json: bool,
+}
+
+impl Opts {
+    pub fn run(&self) -> Result<()> {
+        let input = self.io.parse_input_wasm()?;
+        let mut stats = Stats::new(&input)?;
+        if self.top > 0 {
+            stats.functions.truncate(self.top);
+            stats.instructions.truncate(self.top);
+        }
+
+        let mut output = self.io.output_writer()?;
+        if self.json {
+            serde_json::to_writer_pretty(&mut output, &stats)?;
+            writeln!(output)?;
+        } else {
+            stats.print(&mut output)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(serde::Serialize)]
+struct Stats {
+    /// The size of the whole file.
+    size: usize,
+    /// The top-level sections, in order.
+    sections: Vec<Size>,
+    /// The custom sections at any level of nesting, in order.
+    custom_sections: Vec<Size>,
+    /// The number of top-level imports of each kind.
+    imports: BTreeMap<&'static str, usize>,
+    /// The number of top-level exports of each kind.
+    exports: BTreeMap<&'static str, usize>,
+    /// The functions defined by all core modules, largest first.
+    functions: Vec<Size>,
+    /// The number of uses of each instruction, most frequent first.
+    instructions: Vec<Count>,
+}
+
+#[derive(serde::Serialize)]
+struct Size {
+    name: String,
+    size: usize,
+}
+
+#[derive(serde::Serialize)]
+struct Count {
+    name: String,
+    count: usize,
+}
+
+/// The state of a core module being analyzed.
+#[derive(Default)]
+struct Module<'a> {
+    num_imported_funcs: u32,
+    bodies: Vec<FunctionBody<'a>>,
+    names: HashMap<u32, String>,
+}
+
+impl Stats {
+    fn new(wasm: &[u8]) -> Result<Stats> {
+        let mut stats = Stats {
+            size: wasm.len(),
+            sections: Vec::new(),
+            custom_sections: Vec::new(),
+            imports: BTreeMap::new(),
+            exports: BTreeMap::new(),
+            functions: Vec::new(),
+            instructions: Vec::new(),
+        };
+        let mut instructions = HashMap::new();
+        let mut printer = wasmprinter::Printer::new();
+        // The module being analyzed at each level of nesting, if any.
+        let mut stack: Vec<Option<Module>> = Vec::new();
+        let mut num_modules = 0;
+        let mut component = false;
+
+        for payload in Parser::new(0).parse_all(wasm) {
+            let payload = payload?;
+            let top_level = stack.len() == 1;
+            if top_level {
+                if let Some((name, range)) = section(&payload) {
+                    stats.sections.push(Size {
+                        name,
+                        size: range.len(),
+                    });
+                }
+            }
+
+            let module = stack.last_mut().and_then(|m| m.as_mut());
+            match (payload, module) {
+                (Version { encoding, .. }, _) => {
+                    if stack.is_empty() {
+                        component = encoding == Encoding::Component;
+                    }
+                    stack.push(match encoding {
+                        Encoding::Module => Some(Module::default()),
+                        Encoding::Component => None,
+                    });
+                }
+                (End(_), _) => {
+                    if let Some(Some(module)) = stack.pop() {
+                        // Functions of nested modules are named after the
+                        // module that they are in, to tell them apart.
+                        let prefix = if component {
+                            format!("module[{}]::", num_modules)
+                        } else {
+                            String::new()
+                        };
+                        num_modules += 1;
+                        for (i, body) in module.bodies.iter().enumerate() {
+                            let index = module.num_imported_funcs + i as u32;
+                            let name = match module.names.get(&index) {
+                                Some(name) => name.clone(),
+                                None => format!("func[{}]", index),
+                            };
+                            stats.functions.push(Size {
+                                name: format!("{}{}", prefix, name),
+                                size: body.range().len(),
+                            });
+
+                            for (_, text) in printer.print_operators(body)? {
+                                *instructions.entry(instruction_name(&text)).or_insert(0) += 1;
+                            }
+                        }
+                    }
+                }
+
+                (CustomSection(c), module) => {
+                    stats.custom_sections.push(Size {
+                        name: c.name().to_string(),
+                        size: c.range().len(),
+                    });
+                    if let (Some(module), "name") = (module, c.name()) {
+                        // Names are best effort; a malformed name section
+                        // just means that functions are referred to by index.
+                        let _ = read_function_names(
+                            NameSectionReader::new(c.data(), c.data_offset()),
+                            &mut module.names,
+                        );
+                    }
+                }
+                (ImportSection(s), Some(module)) => {
+                    for import in s {
+                        let kind = match import?.ty {
+                            TypeRef::Func(_) => {
+                                module.num_imported_funcs += 1;
+                                "func"
+                            }
+                            TypeRef::Table(_) => "table",
+                            TypeRef::Memory(_) => "memory",
+                            TypeRef::Global(_) => "global",
+                            TypeRef::Tag(_) => "tag",
+                        };
+                        if top_level {
+                            *stats.imports.entry(kind).or_insert(0) += 1;
+                        }
+                    }
+                }
+                (ExportSection(s), Some(_)) if top_level => {
+                    for export in s {
+                        let kind = match export?.kind {
+                            ExternalKind::Func => "func",
+                            ExternalKind::Table => "table",
+                            ExternalKind::Memory => "memory",
+                            ExternalKind::Global => "global",
+                            ExternalKind::Tag => "tag",
+                        };
+                        *stats.exports.entry(kind).or_insert(0) += 1;
+                    }
+                }
+                (CodeSectionEntry(body), Some(module)) => module.bodies.push(body),
+
+                (ComponentImportSection(s), None) if top_level => {
+                    for import in s {
+                        let kind = match import?.ty {
+                            ComponentTypeRef::Module(_) => "module",
+                            ComponentTypeRef::Func(_) => "func",
+                            ComponentTypeRef::Value(_) => "value",
+                            ComponentTypeRef::Type(..) => "type",
+                            ComponentTypeRef::Instance(_) => "instance",
+                            ComponentTypeRef::Component(_) => "component",
+                        };
+                        *stats.imports.entry(kind).or_insert(0) += 1;
+                    }
+                }
+                (ComponentExportSection(s), None) if top_level => {
+                    for export in s {
+                        let kind = match export?.kind {
+                            ComponentExternalKind::Module => "module",
+                            ComponentExternalKind::Func => "func",
+                            ComponentExternalKind::Value => "value",
+                            ComponentExternalKind::Type => "type",
+                            ComponentExternalKind::Instance => "instance",
+                            ComponentExternalKind::Component => "component",
+                        };
+                        *stats.exports.entry(kind).or_insert(0) += 1;
+                    }
+                }
+
+                _ => {}
+            }
+        }
+
+        // Sort by size and frequency, breaking ties by name so that the
+        // output is deterministic.
+        stats
+            .functions
+            .sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        stats.instructions = instructions
+            .into_iter()
+            .map(|(name, count)| Count { name, count })
+            .collect();
+        stats
+            .instructions
+            .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+        Ok(stats)
+    }
+
+    fn print(&self, out: &mut dyn Write) -> Result<()> {
+        writeln!(out, "total size: {} bytes", self.size)?;
+
+        let percent = |size: usize| 100.0 * size as f64 / self.size.max(1) as f64;
+        writeln!(out, "sections:")?;
+        for s in &self.sections {
+            writeln!(
+                out,
+                "  {:>10} bytes {:>5.1}%  {}",
+                s.size,
+                percent(s.size),
+                s.name
+            )?;
+        }
+        if !self.custom_sections.is_empty() {
+            writeln!(out, "custom sections:")?;
+            for s in &self.custom_sections {
+                writeln!(
+                    out,
+                    "  {:>10} bytes {:>5.1}%  {}",
+                    s.size,
+                    percent(s.size),
+                    s.name
+                )?;
+            }
+        }
+
+        for (what, counts) in [("imports", &self.imports), ("exports", &self.exports)] {
+            if counts.is_empty() {
+                continue;
+            }
+            writeln!(out, "{}:", what)?;
+            for (kind, count) in counts {
+                writeln!(out, "  {:>10} {}", count, kind)?;
+            }
+        }
+
+        if !self.functions.is_empty() {
+            writeln!(out, "largest functions:")?;
+            for f in &self.functions {
+                writeln!(
+                    out,
+                    "  {:>10} bytes {:>5.1}%  {}",
+                    f.size,
+                    percent(f.size),
+                    f.name
+                )?;
+            }
+        }
+        if !self.instructions.is_empty() {
+            writeln!(out, "most frequent instructions:")?;
+            for i in &self.instructions {
+                writeln!(out, "  {:>10} {}", i.count, i.name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read_function_names(
+    reader: wasmparser::Result<NameSectionReader>,
+    names: &mut HashMap<u32, String>,
+) -> wasmparser::Result<()> {
+    let mut reader = reader?;
+    while !reader.eof() {
+        if let Name::Function(map) = reader.read()? {
+            let mut map = map.get_map()?;
+            for _ in 0..map.get_count() {
+                let naming = map.read()?;
+                names.insert(naming.index, naming.name.to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The name of an instruction in the text format, without its immediates.
+fn instruction_name(text: &str) -> String {
+    text.split_whitespace().next().unwrap_or_default().to_string()
+}
+
+/// Get the name and range of a section.
+fn section(payload: &wasmparser::Payload) -> Option<(String, Range<usize>)> {
+    let (name, range) = match payload {
+        TypeSection(s) => ("type", s.range()),
+        ImportSection(s) => ("import", s.range()),
+        FunctionSection(s) => ("function", s.range()),
+        TableSection(s) => ("table", s.range()),
+        MemorySection(s) => ("memory", s.range()),
+        TagSection(s) => ("tag", s.range()),
+        GlobalSection(s) => ("global", s.range()),
+        ExportSection(s) => ("export", s.range()),
+        StartSection { range, .. } => ("start", range.clone()),
+        ElementSection(s) => ("element", s.range()),
+        DataCountSection { range, .. } => ("data count", range.clone()),
+        DataSection(s) => ("data", s.range()),
+        CodeSectionStart { range, .. } => ("code", range.clone()),
+        ModuleSection { range, .. } => ("module", range.clone()),
+        InstanceSection(s) => ("core instance", s.range()),
+        CoreTypeSection(s) => ("core type", s.range()),
+        ComponentSection { range, .. } => ("component", range.clone()),
+        ComponentInstanceSection(s) => ("instance", s.range()),
+        ComponentAliasSection(s) => ("alias", s.range()),
+        ComponentTypeSection(s) => ("type", s.range()),
+        ComponentCanonicalSection(s) => ("canonical function", s.range()),
+        ComponentStartSection(s) => ("start", s.range()),
+        ComponentImportSection(s) => ("import", s.range()),
+        ComponentExportSection(s) => ("export", s.range()),
+        ComponentValueSection(s) => ("value", s.range()),
+        CustomSection(c) => return Some((format!("custom {:?}", c.name()), c.range())),
+        UnknownSection { id, range, .. } => return Some((format!("unknown {}", id), range.clone())),
+        _ => return None,
+    };
+    Some((name.to_string(), range))
+}
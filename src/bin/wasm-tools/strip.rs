@@ -1,105 +1,118 @@
-use anyhow::{bail, Result};
-use std::ops::Range;
-use wasm_encoder::{RawSection, SectionId};
-use wasmparser::{Encoding, Parser, Payload::*, SectionReader};
+use anyhow::Result;
+use regex::Regex;
+use wasm_encoder::Encode;
+use wasmparser::{BinaryReader, CustomSectionReader};
 
 /// Removes custom sections from an input WebAssembly file.
 ///
 /// This command will by default strip all custom sections such as DWARF
 /// debugging information from a wasm file. It will not strip the `name` section
 /// by default unless the `--all` flag is passed.
+///
+/// Which custom sections are removed can be narrowed down with `--delete`,
+/// `--debug-only`, and `--names-only`, in which case only the sections selected
+/// by any of these are removed. Sections matching a `--keep` pattern are never
+/// removed. Components are stripped recursively, including all of the modules
+/// and components nested within them.
 #[derive(clap::Parser)]
 pub struct Opts {
     #[clap(flatten)]
     io: wasm_tools::InputOutput,
 
     /// Strip all custom sections, including the `name` section
-    #[clap(long, short)]
+    #[clap(long, short, conflicts_with_all = &["delete", "debug-only", "names-only"])]
     all: bool,
 
+    /// Only strip the custom sections whose name matches this regex.
+    ///
+    /// This may be passed multiple times, and patterns match anywhere within
+    /// the name unless anchored with `^` and `$`.
+    #[clap(long, short, value_name = "REGEX")]
+    delete: Vec<Regex>,
+
+    /// Never strip the custom sections whose name matches this regex.
+    ///
+    /// This may be passed multiple times, and takes precedence over all other
+    /// options.
+    #[clap(long, short, value_name = "REGEX")]
+    keep: Vec<Regex>,
+
+    /// Only strip DWARF debugging information, i.e. the `.debug_*` sections.
+    #[clap(long)]
+    debug_only: bool,
+
+    /// Only strip the `name` and `component-name` sections.
+    #[clap(long)]
+    names_only: bool,
+
     /// Output the text format of WebAssembly instead of the binary format.
     #[clap(short = 't', long)]
     wat: bool,
 }
 
+/// The id of the custom section within both modules and components.
+const CUSTOM_SECTION: u8 = 0;
+/// The ids of the sections of a component that contain whole modules or
+/// components.
+const CORE_MODULE_SECTION: u8 = 1;
+const COMPONENT_SECTION: u8 = 4;
+
 impl Opts {
     pub fn run(&self) -> Result<()> {
         let input = self.io.parse_input_wasm()?;
+        let output = self.strip(&input, 0)?;
+        self.io.output(wasm_tools::Output::Wasm {
+            bytes: &output,
+            wat: self.wat,
+        })?;
+        Ok(())
+    }
 
-        let mut module = wasm_encoder::Module::new();
-
-        for payload in Parser::new(0).parse_all(&input) {
-            let payload = payload?;
-            let mut section = |id: SectionId, range: Range<usize>| {
-                module.section(&RawSection {
-                    id: id as u8,
-                    data: &input[range],
-                });
-            };
-            match payload {
-                Version {
-                    encoding: Encoding::Module,
-                    ..
-                } => {}
-                Version {
-                    encoding: Encoding::Component,
-                    ..
-                } => {
-                    bail!("components are not supported yet with the `strip` command");
-                }
-
-                TypeSection(s) => section(SectionId::Type, s.range()),
-                ImportSection(s) => section(SectionId::Import, s.range()),
-                FunctionSection(s) => section(SectionId::Function, s.range()),
-                TableSection(s) => section(SectionId::Table, s.range()),
-                MemorySection(s) => section(SectionId::Memory, s.range()),
-                TagSection(s) => section(SectionId::Tag, s.range()),
-                GlobalSection(s) => section(SectionId::Global, s.range()),
-                ExportSection(s) => section(SectionId::Export, s.range()),
-                ElementSection(s) => section(SectionId::Element, s.range()),
-                DataSection(s) => section(SectionId::Data, s.range()),
-                StartSection { range, .. } => section(SectionId::Start, range),
-                DataCountSection { range, .. } => section(SectionId::DataCount, range),
-                CodeSectionStart { range, .. } => section(SectionId::Code, range),
-                CodeSectionEntry(_) => {}
-
-                ModuleSection { .. }
-                | InstanceSection(_)
-                | CoreTypeSection(_)
-                | ComponentSection { .. }
-                | ComponentInstanceSection(_)
-                | ComponentAliasSection(_)
-                | ComponentTypeSection(_)
-                | ComponentCanonicalSection(_)
-                | ComponentStartSection(_)
-                | ComponentImportSection(_)
-                | ComponentExportSection(_) => unimplemented!("component model"),
-
-                CustomSection(c) if c.name() == "name" && !self.all => {
-                    module.section(&RawSection {
-                        id: SectionId::Custom as u8,
-                        data: &input[c.range()],
-                    });
+    /// Strip the custom sections of the given module or component, which is
+    /// located at `offset` within the original input.
+    fn strip(&self, wasm: &[u8], offset: usize) -> Result<Vec<u8>> {
+        anyhow::ensure!(wasm.len() >= 8, "input is missing its header");
+        // The fourth byte of the version is the layer: 0 for modules and 1 for
+        // components.
+        let is_component = wasm[6] == 1;
+        let mut output = wasm[..8].to_vec();
+        let mut reader = BinaryReader::new_with_offset(&wasm[8..], offset + 8);
+        while !reader.eof() {
+            let id = reader.read_u8()?;
+            let size = reader.read_var_u32()?;
+            let data_offset = reader.original_position();
+            let data = reader.read_bytes(size as usize)?;
+            let stripped;
+            let data = match id {
+                CUSTOM_SECTION => {
+                    let custom = CustomSectionReader::new(data, data_offset)?;
+                    if self.should_strip(custom.name()) {
+                        continue;
+                    }
+                    data
                 }
-
-                CustomSection(_) => {}
-
-                UnknownSection {
-                    id,
-                    contents,
-                    range: _,
-                } => {
-                    module.section(&RawSection { id, data: contents });
+                CORE_MODULE_SECTION | COMPONENT_SECTION if is_component => {
+                    stripped = self.strip(data, data_offset)?;
+                    &stripped
                 }
-
-                End(_) => {}
-            }
+                _ => data,
+            };
+            output.push(id);
+            data.encode(&mut output);
         }
+        Ok(output)
+    }
 
-        self.io.output(wasm_tools::Output::Wasm {
-            bytes: module.as_slice(),
-            wat: self.wat,
-        })?;
-        Ok(())
+    fn should_strip(&self, name: &str) -> bool {
+        if self.keep.iter().any(|k| k.is_match(name)) {
+            return false;
+        }
+        let is_name_section = name == "name" || name == "component-name";
+        if self.debug_only || self.names_only || !self.delete.is_empty() {
+            return (self.debug_only && name.starts_with(".debug_"))
+                || (self.names_only && is_name_section)
+                || self.delete.iter().any(|d| d.is_match(name));
+        }
+        self.all || !is_name_section
     }
 }
@@ -1,7 +1,10 @@
-use anyhow::{anyhow, Context, Result};
+use anyhow::{Context, Result};
 use rayon::prelude::*;
+use std::fmt;
+use std::io::Write;
 use std::time::Instant;
-use wasmparser::{Parser, ValidPayload, Validator, WasmFeatures};
+use wasm_tools::sarif::{self, DiagnosticsArg, DiagnosticsFormat, Level, Region};
+use wasmparser::{BinaryReaderError, Parser, ValidPayload, Validator, WasmFeatures};
 
 /// Validate a WebAssembly binary
 ///
@@ -20,7 +23,13 @@ use wasmparser::{Parser, ValidPayload, Validator, WasmFeatures};
 /// $ wasm-tools validate --features all fancy.wasm
 ///
 /// # Validate `mvp.wasm` without any Wasm feature proposals enabled.
-/// $ wasm-tools validate --features=-all mvp.wasm
+/// $ wasm-tools validate --features=mvp mvp.wasm
+///
+/// # Validate a component, reporting any error as JSON on stdout.
+/// $ wasm-tools validate --features=wasi-preview2 --output json foo.wasm
+///
+/// # Validate `foo.wasm`, reporting any error as SARIF for code scanning.
+/// $ wasm-tools validate --diagnostics sarif foo.wasm > results.sarif
 /// ```
 #[derive(clap::Parser)]
 pub struct Opts {
@@ -29,15 +38,87 @@ pub struct Opts {
     /// The placeholder "all" can be used to enable all wasm features. If a "-"
     /// character is present in front of a feature it will disable that feature.
     /// For example "all,-simd" would enable everything but simd.
-    #[clap(long, short = 'f', parse(try_from_str = parse_features))]
+    ///
+    /// The following presets are also available, and can be combined with
+    /// individual features, as in "mvp,simd":
+    ///
+    /// * "mvp": no features beyond the original WebAssembly specification
+    ///
+    /// * "default": the features that are enabled by default
+    ///
+    /// * "wasi-preview2": the default features plus the component model
+    ///
+    /// * "all-proposals": the same as "all"
+    #[clap(long, short = 'f')]
     features: Option<WasmFeatures>,
 
+    /// The format to report validation errors in.
+    ///
+    /// With "text", errors are printed to stderr. With "json", a report is
+    /// printed to stdout whether or not the input is valid, including the
+    /// offset and kind of any error, and the features which might make the
+    /// input valid if enabled.
+    #[clap(long, arg_enum, default_value = "text", value_name = "FORMAT")]
+    output: OutputFormat,
+
+    #[clap(flatten)]
+    diagnostics: DiagnosticsArg,
+
     #[clap(flatten)]
-    io: wasm_tools::InputOutput,
+    input: wasm_tools::InputArg,
+}
+
+#[derive(Clone, Copy, clap::ArgEnum)]
+enum OutputFormat {
+    Text,
+    Json,
 }
 
 impl Opts {
     pub fn run(&self) -> Result<()> {
+        let mut is_text = false;
+        let result = self.input.parse_wasm().and_then(|wasm| {
+            is_text = wasm.is_text();
+            self.validate(&wasm)
+        });
+
+        if self.diagnostics.format() == Some(DiagnosticsFormat::Sarif) {
+            let diagnostics: Vec<_> = result
+                .as_ref()
+                .err()
+                .map(|e| ErrorReport::new(e).diagnostic(e, is_text))
+                .into_iter()
+                .collect();
+            let mut stdout = std::io::stdout();
+            sarif::write(&mut stdout, self.input.path(), RULES, &diagnostics)?;
+            // As with JSON, the log has already described the error.
+            if result.is_err() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+
+        match self.output {
+            OutputFormat::Text => result,
+            OutputFormat::Json => {
+                let report = Report {
+                    valid: result.is_ok(),
+                    error: result.as_ref().err().map(ErrorReport::new),
+                };
+                let mut stdout = std::io::stdout();
+                serde_json::to_writer_pretty(&mut stdout, &report)?;
+                writeln!(stdout)?;
+                // The report has already described the error, so just exit
+                // with a failure code instead of printing it again.
+                if !report.valid {
+                    std::process::exit(1);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn validate(&self, wasm: &[u8]) -> Result<()> {
         // Note that here we're copying the contents of
         // `Validator::validate_all`, but the end is followed up with a parallel
         // iteration over the functions to validate instead of a synchronous
@@ -50,10 +131,9 @@ impl Opts {
         // validated later.
         let mut validator = Validator::new_with_features(self.features.unwrap_or_default());
         let mut functions_to_validate = Vec::new();
-        let wasm = self.io.parse_input_wasm()?;
 
         let start = Instant::now();
-        for payload in Parser::new(0).parse_all(&wasm) {
+        for payload in Parser::new(0).parse_all(wasm) {
             match validator.payload(&payload?)? {
                 ValidPayload::Ok | ValidPayload::Parser(_) | ValidPayload::End(_) => {}
                 ValidPayload::Func(validator, body) => {
@@ -72,64 +152,172 @@ impl Opts {
             .try_for_each(|(mut validator, body)| {
                 validator
                     .validate(&body)
-                    .with_context(|| format!("func {} failed to validate", validator.index()))
+                    .with_context(|| FuncContext(validator.index()))
             })?;
         log::info!("functions validated in {:?}", start.elapsed());
         Ok(())
     }
 }
 
-fn parse_features(arg: &str) -> Result<WasmFeatures> {
-    let mut ret = WasmFeatures::default();
-
-    const FEATURES: &[(&str, fn(&mut WasmFeatures) -> &mut bool)] = &[
-        ("reference-types", |f| &mut f.reference_types),
-        ("simd", |f| &mut f.simd),
-        ("threads", |f| &mut f.threads),
-        ("bulk-memory", |f| &mut f.bulk_memory),
-        ("multi-value", |f| &mut f.multi_value),
-        ("tail-call", |f| &mut f.tail_call),
-        ("component-model", |f| &mut f.component_model),
-        ("multi-memory", |f| &mut f.multi_memory),
-        ("exception-handling", |f| &mut f.exceptions),
-        ("memory64", |f| &mut f.memory64),
-        ("extended-const", |f| &mut f.extended_const),
-        ("deterministic", |f| &mut f.deterministic_only),
-        ("saturating-float-to-int", |f| {
-            &mut f.saturating_float_to_int
-        }),
-        ("sign-extension", |f| &mut f.sign_extension),
-        ("mutable-global", |f| &mut f.mutable_global),
-        ("relaxed-simd", |f| &mut f.relaxed_simd),
-    ];
-
-    for part in arg.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
-        let (enable, part) = if let Some(part) = part.strip_prefix("-") {
-            (false, part)
-        } else {
-            (true, part)
-        };
-        match part {
-            "all" => {
-                for (name, accessor) in FEATURES {
-                    // don't count this under "all" for now.
-                    if *name == "deterministic" {
-                        continue;
-                    }
-
-                    *accessor(&mut ret) = enable;
+/// The context of an error within a function, which is kept as a type so that
+/// the function's index can be included in JSON reports.
+#[derive(Debug)]
+struct FuncContext(u32);
+
+impl fmt::Display for FuncContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "func {} failed to validate", self.0)
+    }
+}
+
+#[derive(serde::Serialize)]
+struct Report {
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorReport>,
+}
+
+#[derive(serde::Serialize)]
+struct ErrorReport {
+    /// One of "text" for errors parsing the text format, "feature" for uses
+    /// of features which aren't enabled, "invalid" for other errors in the
+    /// binary, or "other" for anything else, such as I/O errors.
+    kind: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<usize>,
+    /// The index of the function that failed to validate, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    func: Option<u32>,
+    /// Features which, if enabled, might make the input valid.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    feature_hints: Vec<&'static str>,
+}
+
+/// The kinds of errors reported in SARIF logs, matching those of JSON reports.
+const RULES: &[sarif::Rule] = &[
+    sarif::Rule {
+        id: "text",
+        description: "The text format input failed to parse",
+    },
+    sarif::Rule {
+        id: "feature",
+        description: "The input uses a WebAssembly feature which isn't enabled",
+    },
+    sarif::Rule {
+        id: "invalid",
+        description: "The input is not valid WebAssembly",
+    },
+    sarif::Rule {
+        id: "other",
+        description: "The input could not be read",
+    },
+];
+
+/// Substrings of validation error messages for disabled features, and the
+/// feature to enable for each. More specific messages come first.
+const FEATURE_HINTS: &[(&str, &str)] = &[
+    ("component model async", "component-model-async"),
+    ("component model", "component-model"),
+    ("relaxed simd", "relaxed-simd"),
+    ("simd", "simd"),
+    ("reference types", "reference-types"),
+    ("reference-types", "reference-types"),
+    ("multiple tables", "reference-types"),
+    ("multi-memory", "multi-memory"),
+    ("multiple memories", "multi-memory"),
+    ("multi-value", "multi-value"),
+    ("mutable global", "mutable-global"),
+    ("saturating float", "saturating-float-to-int"),
+    ("sign extension", "sign-extension"),
+    ("bulk memory", "bulk-memory"),
+    ("tail calls", "tail-call"),
+    ("threads", "threads"),
+    ("exceptions", "exception-handling"),
+    ("memory64", "memory64"),
+    ("non-constant operator", "extended-const"),
+];
+
+impl ErrorReport {
+    fn new(error: &anyhow::Error) -> ErrorReport {
+        let func = error.downcast_ref::<FuncContext>().map(|c| c.0);
+        if let Some(e) = error.downcast_ref::<wat::Error>() {
+            // Only I/O errors have an underlying source.
+            let is_io = std::error::Error::source(e).is_some();
+            return ErrorReport {
+                kind: if is_io { "other" } else { "text" },
+                message: e.to_string(),
+                offset: None,
+                func,
+                feature_hints: Vec::new(),
+            };
+        }
+        let e = match error.downcast_ref::<BinaryReaderError>() {
+            Some(e) => e,
+            None => {
+                return ErrorReport {
+                    kind: "other",
+                    message: format!("{:?}", error),
+                    offset: None,
+                    func,
+                    feature_hints: Vec::new(),
                 }
             }
+        };
 
-            name => {
-                let (_, accessor) = FEATURES
-                    .iter()
-                    .find(|(n, _)| *n == name)
-                    .ok_or_else(|| anyhow!("unknown feature `{}`", name))?;
-                *accessor(&mut ret) = enable;
-            }
+        let message = e.message().to_lowercase();
+        let feature_hints: Vec<_> = FEATURE_HINTS
+            .iter()
+            .find(|(needle, _)| message.contains(needle))
+            .map(|(_, feature)| *feature)
+            .into_iter()
+            .collect();
+        let is_feature_error = message.contains("not enabled")
+            || message.contains("must be enabled")
+            || message.starts_with("multiple ");
+        ErrorReport {
+            kind: if is_feature_error && !feature_hints.is_empty() {
+                "feature"
+            } else {
+                "invalid"
+            },
+            message: e.message().to_string(),
+            offset: Some(e.offset()),
+            func,
+            feature_hints,
         }
     }
 
-    Ok(ret)
+    /// Convert this report of `error` into a SARIF diagnostic.
+    fn diagnostic(self, error: &anyhow::Error, is_text: bool) -> sarif::Diagnostic {
+        let mut message = self.message;
+        let mut region = None;
+        if let Some(e) = error.downcast_ref::<wat::Error>() {
+            // The location is reported separately from the message.
+            if let Some((line, column)) = e.line_col() {
+                message = e.message();
+                region = Some(Region::Text { line, column });
+            }
+        } else if let (Some(offset), false) = (self.offset, is_text) {
+            // Offsets within the binary encoding of a text input don't point
+            // at anything within the input itself.
+            region = Some(Region::Binary { offset });
+        }
+        if let Some(func) = self.func {
+            message = format!("func {} failed to validate: {}", func, message);
+        }
+        if !self.feature_hints.is_empty() {
+            message = format!(
+                "{} (try enabling: {})",
+                message,
+                self.feature_hints.join(", ")
+            );
+        }
+        sarif::Diagnostic {
+            rule: self.kind,
+            level: Level::Error,
+            message,
+            region,
+        }
+    }
 }
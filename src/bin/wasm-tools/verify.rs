@@ -0,0 +1,97 @@
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+use std::path::PathBuf;
+use wasm_metadata::{Ed25519Keys, SignatureStatus};
+
+/// Verifies the embedded signatures of a WebAssembly module or component.
+///
+/// The digest stored in the `signature` custom section must match the
+/// input, at least one signature must have been made by one of the trusted
+/// `--public-key` files, and no signature made by a trusted key may be
+/// invalid. Signatures by unknown keys are reported but otherwise ignored.
+///
+/// The identity of each signer and the byte ranges covered by the digest are
+/// printed, and the process exits with a failure if verification fails.
+///
+/// See `wasm-tools sign --help` for how the `signature` section is made.
+#[derive(clap::Parser)]
+pub struct Opts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// A trusted Ed25519 public key file, holding the hex encoding of the
+    /// key, which may be passed multiple times.
+    #[clap(long, value_name = "FILE", required_unless_present = "digest-only")]
+    public_key: Vec<PathBuf>,
+
+    /// Only check that the embedded digest matches, without requiring any
+    /// signatures.
+    #[clap(long)]
+    digest_only: bool,
+
+    /// Output the verification report as JSON instead of text.
+    #[clap(long)]
+    json: bool,
+}
+
+impl Opts {
+    pub fn run(&self) -> Result<()> {
+        let mut keys = Ed25519Keys::new();
+        for path in &self.public_key {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read `{}`", path.display()))?;
+            keys.add_hex(&text)
+                .with_context(|| format!("invalid public key file `{}`", path.display()))?;
+        }
+
+        let input = self.io.parse_input_wasm()?;
+        let verification = wasm_metadata::verify(&input, &keys)?;
+        let verified = if self.digest_only {
+            verification.digest_matches
+        } else {
+            verification.is_verified()
+        };
+
+        let mut output = self.io.output_writer()?;
+        if self.json {
+            serde_json::to_writer_pretty(&mut output, &verification)?;
+            writeln!(output)?;
+        } else {
+            let digest = &verification.digest;
+            writeln!(
+                output,
+                "digest: {} {}",
+                digest.algorithm,
+                hex(&digest.bytes)
+            )?;
+            for range in &digest.covered {
+                writeln!(output, "covers bytes {:#x}..{:#x}", range.start, range.end)?;
+            }
+            if !verification.digest_matches {
+                writeln!(output, "digest does not match the embedded digest")?;
+            }
+            for signature in &verification.signatures {
+                let status = match signature.status {
+                    SignatureStatus::Valid => "valid",
+                    SignatureStatus::Invalid => "INVALID",
+                    SignatureStatus::UnknownKey => "unknown key",
+                };
+                writeln!(
+                    output,
+                    "signed by {} key `{}`: {status}",
+                    signature.algorithm, signature.key_id
+                )?;
+            }
+        }
+        output.flush()?;
+
+        if !verified {
+            bail!("verification failed");
+        }
+        Ok(())
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
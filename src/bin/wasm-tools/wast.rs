@@ -0,0 +1,411 @@
+use ::wast::core::NanComparison;
+use ::wast::harness::{Engine, Harness, Outcome, Report, Val};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command as Process, Stdio};
+use tempfile::TempDir;
+
+/// Tools for `*.wast` test scripts.
+#[derive(clap::Parser)]
+pub struct Opts {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    Run(RunOpts),
+}
+
+impl Opts {
+    pub fn run(&self) -> Result<()> {
+        match &self.command {
+            Command::Run(opts) => opts.run(),
+        }
+    }
+}
+
+/// Runs `*.wast` scripts against a WebAssembly engine, reporting whether each
+/// directive passed or failed.
+///
+/// The engine is a program given after `--`, which is started afresh for each
+/// script and driven over its stdin and stdout. Each request is written to it
+/// as a single line of JSON, to which it must reply with a single line of
+/// JSON. Requests are objects whose `type` is one of:
+///
+/// * `instantiate`: instantiate the binary in `filename`, naming the instance
+///   `name` if that's present
+///
+/// * `validate`: validate the binary in `filename`
+///
+/// * `register`: make the exports of the instance named `module`, or the
+///   latest instance, importable under the name `as`
+///
+/// * `invoke`: call the export `field` of the instance named `module`, or the
+///   latest instance, with `args`
+///
+/// * `get`: read the global exported as `field` from the instance named
+///   `module`, or the latest instance
+///
+/// Replies are objects with either the `results` of the request, or an
+/// `error` message such as the trap raised by an invocation. Values are
+/// encoded as in the output of `json-from-wast`, as objects with a `type` of
+/// `i32`, `i64`, `f32`, `f64`, `v128`, `funcref`, or `externref` and a
+/// `value` which is the unsigned decimal representation of their bits, or
+/// `"null"` for null references.
+///
+/// The process exits with a failure if any directive fails.
+#[derive(clap::Parser)]
+pub struct RunOpts {
+    /// The `*.wast` scripts to run, or directories to search recursively for
+    /// them, where `-` reads a script from stdin.
+    #[clap(required = true)]
+    scripts: Vec<PathBuf>,
+
+    /// The format in which to report the results.
+    #[clap(long, arg_enum, default_value = "text")]
+    format: Format,
+
+    #[clap(flatten)]
+    output: wasm_tools::OutputArg,
+
+    /// Only require that an error happened for assertions such as
+    /// `assert_trap`, rather than that the engine's message contains the
+    /// expected one.
+    #[clap(long)]
+    ignore_messages: bool,
+
+    /// Only accept canonical NaNs where scripts expect `nan:arithmetic`.
+    #[clap(long)]
+    canonical_nans: bool,
+
+    /// The engine program to run, followed by its arguments.
+    #[clap(last = true, required = true)]
+    engine: Vec<String>,
+}
+
+#[derive(Clone, Copy, clap::ArgEnum)]
+enum Format {
+    Text,
+    Junit,
+    Tap,
+}
+
+impl RunOpts {
+    pub fn run(&self) -> Result<()> {
+        let mut harness = Harness::new();
+        harness.check_messages(!self.ignore_messages);
+        if self.canonical_nans {
+            harness.nan_comparison(NanComparison::Canonical);
+        }
+
+        wasm_tools::check_stdin_once(self.scripts.iter().map(|p| p.as_path()))?;
+        let new_engine = || -> Box<dyn Engine> { Box::new(ProcessEngine::new(&self.engine)) };
+        let mut report = Report::default();
+        for path in &self.scripts {
+            if path.is_dir() {
+                let dir = harness
+                    .run_dir(path, new_engine)
+                    .with_context(|| format!("failed to run scripts in `{}`", path.display()))?;
+                for mut suite in dir.suites {
+                    suite.name = path.join(&suite.name).display().to_string();
+                    report.suites.push(suite);
+                }
+            } else {
+                let contents = wasm_tools::read_text_path(path)?;
+                let name = match path.to_str() {
+                    Some("-") => "<stdin>".to_string(),
+                    _ => path.display().to_string(),
+                };
+                report
+                    .suites
+                    .push(harness.run_script(&name, &contents, &mut new_engine()));
+            }
+        }
+
+        let mut output = self.output.output_writer()?;
+        match self.format {
+            Format::Text => print_text(&mut output, &report)?,
+            Format::Junit => output.write_all(report.to_junit().as_bytes())?,
+            Format::Tap => output.write_all(report.to_tap().as_bytes())?,
+        }
+        output.flush()?;
+
+        if report.failures() > 0 {
+            bail!(
+                "{} of {} directives failed",
+                report.failures(),
+                report.tests()
+            );
+        }
+        Ok(())
+    }
+}
+
+fn print_text(output: &mut dyn Write, report: &Report) -> Result<()> {
+    for suite in &report.suites {
+        for case in &suite.cases {
+            match &case.outcome {
+                Outcome::Pass => writeln!(output, "ok   {} {}", suite.name, case.name)?,
+                Outcome::Fail(msg) => {
+                    writeln!(output, "FAIL {} {}: {}", suite.name, case.name, msg)?
+                }
+                Outcome::Skip(msg) => {
+                    writeln!(output, "skip {} {}: {}", suite.name, case.name, msg)?
+                }
+            }
+        }
+    }
+    let failed = report.failures();
+    let skipped = report.skipped();
+    writeln!(
+        output,
+        "\n{} passed, {} failed, {} skipped",
+        report.tests() - failed - skipped,
+        failed,
+        skipped
+    )?;
+    Ok(())
+}
+
+/// An engine which is a subprocess speaking the protocol described in
+/// [`RunOpts`].
+struct ProcessEngine {
+    /// The running engine, or why it couldn't be started, which is reported
+    /// as the error of every request.
+    process: Result<Running, String>,
+}
+
+struct Running {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    /// Where binaries are written for the engine to read.
+    tmp: TempDir,
+    num_files: usize,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request<'a> {
+    Instantiate {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<&'a str>,
+        filename: &'a Path,
+    },
+    Validate {
+        filename: &'a Path,
+    },
+    Register {
+        #[serde(rename = "as")]
+        as_name: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        module: Option<&'a str>,
+    },
+    Invoke {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        module: Option<&'a str>,
+        field: &'a str,
+        args: Vec<Value>,
+    },
+    Get {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        module: Option<&'a str>,
+        field: &'a str,
+    },
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    results: Vec<Value>,
+    error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Value {
+    I32 { value: String },
+    I64 { value: String },
+    F32 { value: String },
+    F64 { value: String },
+    V128 { value: String },
+    Funcref { value: Option<String> },
+    Externref { value: Option<String> },
+}
+
+impl ProcessEngine {
+    fn new(command: &[String]) -> ProcessEngine {
+        ProcessEngine {
+            process: Running::spawn(command).map_err(|e| format!("{:?}", e)),
+        }
+    }
+
+    fn request(&mut self, request: &Request<'_>) -> Result<Vec<Val>, String> {
+        let process = self.process.as_mut().map_err(|e| e.clone())?;
+        let response = process
+            .request(request)
+            .map_err(|e| format!("engine failed: {:?}", e))?;
+        match response.error {
+            Some(error) => Err(error),
+            None => response.results.iter().map(Value::to_val).collect(),
+        }
+    }
+
+    /// Writes `wasm` to a file for the engine to read, returning its path.
+    fn write(&mut self, wasm: &[u8]) -> Result<PathBuf, String> {
+        let process = self.process.as_mut().map_err(|e| e.clone())?;
+        let path = process
+            .tmp
+            .path()
+            .join(format!("{}.wasm", process.num_files));
+        process.num_files += 1;
+        std::fs::write(&path, wasm).map_err(|e| format!("failed to write module: {}", e))?;
+        Ok(path)
+    }
+}
+
+impl Running {
+    fn spawn(command: &[String]) -> Result<Running> {
+        let mut child = Process::new(&command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to start engine `{}`", command[0]))?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Ok(Running {
+            child,
+            stdin,
+            stdout,
+            tmp: tempfile::tempdir()?,
+            num_files: 0,
+        })
+    }
+
+    fn request(&mut self, request: &Request<'_>) -> Result<Response> {
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        self.stdin.write_all(line.as_bytes())?;
+        self.stdin.flush()?;
+
+        line.clear();
+        if self.stdout.read_line(&mut line)? == 0 {
+            bail!("engine exited without replying");
+        }
+        serde_json::from_str(&line).with_context(|| format!("invalid reply `{}`", line.trim()))
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Engine for ProcessEngine {
+    fn instantiate(&mut self, name: Option<&str>, wasm: &[u8]) -> Result<(), String> {
+        let filename = self.write(wasm)?;
+        self.request(&Request::Instantiate {
+            name,
+            filename: &filename,
+        })
+        .map(drop)
+    }
+
+    fn validate(&mut self, wasm: &[u8]) -> Result<(), String> {
+        let filename = self.write(wasm)?;
+        self.request(&Request::Validate {
+            filename: &filename,
+        })
+        .map(drop)
+    }
+
+    fn register(&mut self, as_name: &str, module: Option<&str>) -> Result<(), String> {
+        self.request(&Request::Register { as_name, module })
+            .map(drop)
+    }
+
+    fn invoke(
+        &mut self,
+        module: Option<&str>,
+        name: &str,
+        args: &[Val],
+    ) -> Result<Vec<Val>, String> {
+        let args = args.iter().map(Value::from_val).collect();
+        self.request(&Request::Invoke {
+            module,
+            field: name,
+            args,
+        })
+    }
+
+    fn get(&mut self, module: Option<&str>, name: &str) -> Result<Val, String> {
+        let mut results = self.request(&Request::Get {
+            module,
+            field: name,
+        })?;
+        if results.len() != 1 {
+            return Err(format!("expected 1 result, got {}", results.len()));
+        }
+        Ok(results.remove(0))
+    }
+}
+
+impl Value {
+    fn from_val(val: &Val) -> Value {
+        match *val {
+            Val::I32(v) => Value::I32 {
+                value: (v as u32).to_string(),
+            },
+            Val::I64(v) => Value::I64 {
+                value: (v as u64).to_string(),
+            },
+            Val::F32(bits) => Value::F32 {
+                value: bits.to_string(),
+            },
+            Val::F64(bits) => Value::F64 {
+                value: bits.to_string(),
+            },
+            Val::V128(bits) => Value::V128 {
+                value: bits.to_string(),
+            },
+            // Non-null function references have no value, as there's no way
+            // to identify them.
+            Val::FuncRef(non_null) => Value::Funcref {
+                value: if non_null {
+                    None
+                } else {
+                    Some("null".to_string())
+                },
+            },
+            Val::ExternRef(r) => Value::Externref {
+                value: Some(r.map_or_else(|| "null".to_string(), |r| r.to_string())),
+            },
+        }
+    }
+
+    fn to_val(&self) -> Result<Val, String> {
+        fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+            value
+                .parse()
+                .map_err(|_| format!("invalid value `{}` in reply", value))
+        }
+        Ok(match self {
+            Value::I32 { value } => Val::I32(parse::<u32>(value)? as i32),
+            Value::I64 { value } => Val::I64(parse::<u64>(value)? as i64),
+            Value::F32 { value } => Val::F32(parse(value)?),
+            Value::F64 { value } => Val::F64(parse(value)?),
+            Value::V128 { value } => Val::V128(parse(value)?),
+            Value::Funcref { value } => Val::FuncRef(value.as_deref() != Some("null")),
+            Value::Externref { value } => match value.as_deref() {
+                None | Some("null") => Val::ExternRef(None),
+                Some(value) => Val::ExternRef(Some(parse(value)?)),
+            },
+        })
+    }
+}
@@ -0,0 +1,848 @@
+//! Removal of unreachable items from a core WebAssembly module.
+//!
+//! Functions, globals, types, and passive data segments which can't be
+//! reached from the module's exports are removed, and the remaining items are
+//! renumbered. The start function, element segments, tags, and active data
+//! segments are always kept, along with everything that they refer to.
+//!
+//! The `name` section is updated to match, but other custom sections, such as
+//! DWARF debugging information, are copied as-is and may be left referring to
+//! the wrong items.
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashSet;
+use wasm_encoder::{BlockType, Encode, IndirectNameMap, Instruction, NameMap};
+use wasmparser::{
+    BinaryReader, CodeSectionReader, ConstExpr, CustomSectionReader, DataKind, DataSectionReader,
+    ElementItem, ElementKind, ElementSectionReader, Encoding, ExportSectionReader, ExternalKind,
+    FunctionBody, FunctionSectionReader, GlobalSectionReader, ImportSectionReader, Operator,
+    OperatorsReader, Parser, Payload, SectionWithLimitedItems, TagSectionReader, TypeRef,
+    TypeSectionReader, Validator, WasmFeatures,
+};
+
+/// The number of items of each kind which were removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Removed {
+    pub funcs: usize,
+    pub globals: usize,
+    pub types: usize,
+    pub data: usize,
+}
+
+/// Removes the items of `wasm` which are unreachable from its exports.
+///
+/// If `roots` isn't empty then only the exports with those names are kept
+/// and used as roots instead of all of them. Both the input and the result
+/// are validated.
+pub fn gc(wasm: &[u8], roots: &[String]) -> Result<(Vec<u8>, Removed)> {
+    validator()
+        .validate_all(wasm)
+        .context("input is not a valid module")?;
+
+    let module = Module::new(wasm, roots)?;
+    let live = module.live()?;
+    let removed = Removed {
+        funcs: live.funcs.removed(),
+        globals: live.globals.removed(),
+        types: live.types.removed(),
+        data: live.data.removed(),
+    };
+    let output = live.encode(wasm, &module)?;
+    validator()
+        .validate_all(&output)
+        .context("failed to validate the module after removing unreachable items")?;
+    Ok((output, removed))
+}
+
+/// A validator for any module, with all features enabled.
+fn validator() -> Validator {
+    Validator::new_with_features(WasmFeatures {
+        component_model: false,
+        component_model_async: false,
+        ..WasmFeatures::all()
+    })
+}
+
+/// A reference to an item that may be removed.
+#[derive(Clone, Copy)]
+enum Ref {
+    Func(u32),
+    Global(u32),
+    Type(u32),
+    Data(u32),
+}
+
+/// The parts of a module which determine what's reachable.
+struct Module<'a> {
+    /// The exports to keep, or `None` to keep all of them.
+    roots: Option<HashSet<&'a str>>,
+    num_types: u32,
+    /// The type of each function, imported functions first.
+    func_types: Vec<u32>,
+    num_imported_funcs: u32,
+    /// The initializer of each global, or `None` for imported globals.
+    globals: Vec<Option<ConstExpr<'a>>>,
+    num_data: u32,
+    bodies: Vec<FunctionBody<'a>>,
+    /// The references made by the items which are always kept.
+    kept_refs: Vec<Ref>,
+    /// The functions which may be used by `ref.func` because the items which
+    /// are always kept refer to them.
+    declared_funcs: HashSet<u32>,
+}
+
+impl<'a> Module<'a> {
+    fn new(wasm: &'a [u8], roots: &'a [String]) -> Result<Module<'a>> {
+        let mut module = Module {
+            roots: if roots.is_empty() {
+                None
+            } else {
+                Some(roots.iter().map(|r| r.as_str()).collect())
+            },
+            num_types: 0,
+            func_types: Vec::new(),
+            num_imported_funcs: 0,
+            globals: Vec::new(),
+            num_data: 0,
+            bodies: Vec::new(),
+            kept_refs: Vec::new(),
+            declared_funcs: HashSet::new(),
+        };
+        let mut exports = HashSet::new();
+
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload? {
+                Payload::Version {
+                    encoding: Encoding::Component,
+                    ..
+                } => bail!("components are not supported, only core modules"),
+                Payload::TypeSection(s) => module.num_types = s.get_count(),
+                Payload::ImportSection(s) => {
+                    for import in s {
+                        match import?.ty {
+                            TypeRef::Func(ty) => {
+                                module.func_types.push(ty);
+                                module.num_imported_funcs += 1;
+                            }
+                            TypeRef::Global(_) => module.globals.push(None),
+                            TypeRef::Tag(ty) => module.kept_refs.push(Ref::Type(ty.func_type_idx)),
+                            TypeRef::Table(_) | TypeRef::Memory(_) => {}
+                        }
+                    }
+                }
+                Payload::FunctionSection(s) => {
+                    for ty in s {
+                        module.func_types.push(ty?);
+                    }
+                }
+                Payload::TagSection(s) => {
+                    for tag in s {
+                        module.kept_refs.push(Ref::Type(tag?.func_type_idx));
+                    }
+                }
+                Payload::GlobalSection(s) => {
+                    for global in s {
+                        module.globals.push(Some(global?.init_expr));
+                    }
+                }
+                Payload::ExportSection(s) => {
+                    for export in s {
+                        let export = export?;
+                        exports.insert(export.name);
+                        if !module.keeps_export(export.name) {
+                            continue;
+                        }
+                        module.kept_refs.push(match export.kind {
+                            ExternalKind::Func => {
+                                module.declared_funcs.insert(export.index);
+                                Ref::Func(export.index)
+                            }
+                            ExternalKind::Global => Ref::Global(export.index),
+                            ExternalKind::Table | ExternalKind::Memory | ExternalKind::Tag => {
+                                continue
+                            }
+                        });
+                    }
+                }
+                Payload::StartSection { func, .. } => module.kept_refs.push(Ref::Func(func)),
+                Payload::ElementSection(s) => {
+                    for elem in s {
+                        let elem = elem?;
+                        if let ElementKind::Active { offset_expr, .. } = &elem.kind {
+                            refs(
+                                operators(offset_expr),
+                                &mut module.kept_refs,
+                                &mut module.declared_funcs,
+                            )?;
+                        }
+                        for item in elem.items.get_items_reader()? {
+                            match item? {
+                                ElementItem::Func(idx) => {
+                                    module.declared_funcs.insert(idx);
+                                    module.kept_refs.push(Ref::Func(idx));
+                                }
+                                ElementItem::Expr(expr) => refs(
+                                    operators(&expr),
+                                    &mut module.kept_refs,
+                                    &mut module.declared_funcs,
+                                )?,
+                            }
+                        }
+                    }
+                }
+                Payload::DataSection(s) => {
+                    for data in s {
+                        if let DataKind::Active { offset_expr, .. } = data?.kind {
+                            module.kept_refs.push(Ref::Data(module.num_data));
+                            refs(
+                                operators(&offset_expr),
+                                &mut module.kept_refs,
+                                &mut module.declared_funcs,
+                            )?;
+                        }
+                        module.num_data += 1;
+                    }
+                }
+                Payload::CodeSectionEntry(body) => module.bodies.push(body),
+                _ => {}
+            }
+        }
+
+        for root in module.roots.iter().flatten() {
+            if !exports.contains(root) {
+                bail!("module has no export named `{}`", root);
+            }
+        }
+        Ok(module)
+    }
+
+    fn keeps_export(&self, name: &str) -> bool {
+        self.roots.as_ref().is_none_or(|r| r.contains(name))
+    }
+
+    /// Find everything that's reachable from the items which are always kept.
+    fn live(&self) -> Result<Live> {
+        let mut live = Live {
+            funcs: Map::new(self.func_types.len() as u32),
+            globals: Map::new(self.globals.len() as u32),
+            types: Map::new(self.num_types),
+            data: Map::new(self.num_data),
+            undeclared_funcs: Vec::new(),
+        };
+        let mut worklist = self.kept_refs.clone();
+        let mut ref_funcs = HashSet::new();
+        let mut declared_funcs = self.declared_funcs.clone();
+        while let Some(r) = worklist.pop() {
+            match r {
+                Ref::Func(idx) => {
+                    if !live.funcs.insert(idx) {
+                        continue;
+                    }
+                    worklist.push(Ref::Type(self.func_types[idx as usize]));
+                    let body = match idx.checked_sub(self.num_imported_funcs) {
+                        Some(defined) => &self.bodies[defined as usize],
+                        None => continue,
+                    };
+                    refs(body_operators(body)?, &mut worklist, &mut ref_funcs)?;
+                }
+                Ref::Global(idx) => {
+                    if !live.globals.insert(idx) {
+                        continue;
+                    }
+                    // Functions referenced by live globals may be used by
+                    // `ref.func` in function bodies.
+                    if let Some(init) = &self.globals[idx as usize] {
+                        refs(operators(init), &mut worklist, &mut declared_funcs)?;
+                    }
+                }
+                Ref::Type(idx) => {
+                    live.types.insert(idx);
+                }
+                Ref::Data(idx) => {
+                    live.data.insert(idx);
+                }
+            }
+        }
+        live.funcs.finish();
+        live.globals.finish();
+        live.types.finish();
+        live.data.finish();
+
+        // Functions used by `ref.func` might only have been declared by
+        // something which was removed, like an export which isn't a root, in
+        // which case they're declared again with a new element segment.
+        live.undeclared_funcs = ref_funcs
+            .into_iter()
+            .filter(|f| !declared_funcs.contains(f))
+            .map(|f| live.funcs.get(f))
+            .collect();
+        live.undeclared_funcs.sort_unstable();
+        Ok(live)
+    }
+}
+
+fn operators<'a>(expr: &ConstExpr<'a>) -> OperatorsReader<'a> {
+    let mut reader = expr.get_operators_reader();
+    reader.allow_memarg64(true);
+    reader
+}
+
+fn body_operators<'a>(body: &FunctionBody<'a>) -> Result<OperatorsReader<'a>> {
+    let mut reader = body.get_operators_reader()?;
+    reader.allow_memarg64(true);
+    Ok(reader)
+}
+
+/// Push the items referenced by `ops` onto `refs`, additionally recording the
+/// functions used by `ref.func` in `ref_funcs`.
+fn refs(ops: OperatorsReader, refs: &mut Vec<Ref>, ref_funcs: &mut HashSet<u32>) -> Result<()> {
+    for op in ops {
+        refs.push(match op? {
+            Operator::Call { function_index } | Operator::ReturnCall { function_index } => {
+                Ref::Func(function_index)
+            }
+            Operator::RefFunc { function_index } => {
+                ref_funcs.insert(function_index);
+                Ref::Func(function_index)
+            }
+            Operator::GlobalGet { global_index } | Operator::GlobalSet { global_index } => {
+                Ref::Global(global_index)
+            }
+            Operator::CallIndirect { index, .. } | Operator::ReturnCallIndirect { index, .. } => {
+                Ref::Type(index)
+            }
+            Operator::Block { ty }
+            | Operator::Loop { ty }
+            | Operator::If { ty }
+            | Operator::Try { ty } => match ty {
+                wasmparser::BlockType::FuncType(idx) => Ref::Type(idx),
+                _ => continue,
+            },
+            Operator::MemoryInit { segment, .. } | Operator::DataDrop { segment } => {
+                Ref::Data(segment)
+            }
+            _ => continue,
+        });
+    }
+    Ok(())
+}
+
+/// Which items of an index space are live, and their new indices.
+struct Map {
+    live: Vec<bool>,
+    new_indices: Vec<u32>,
+}
+
+impl Map {
+    fn new(len: u32) -> Map {
+        Map {
+            live: vec![false; len as usize],
+            new_indices: Vec::new(),
+        }
+    }
+
+    /// Mark an item as live, returning whether it wasn't already.
+    fn insert(&mut self, idx: u32) -> bool {
+        !std::mem::replace(&mut self.live[idx as usize], true)
+    }
+
+    /// Assign new indices to the live items.
+    fn finish(&mut self) {
+        let mut next = 0;
+        self.new_indices = self
+            .live
+            .iter()
+            .map(|live| {
+                let idx = next;
+                next += *live as u32;
+                idx
+            })
+            .collect();
+    }
+
+    fn contains(&self, idx: u32) -> bool {
+        self.live[idx as usize]
+    }
+
+    /// The new index of a live item.
+    fn get(&self, idx: u32) -> u32 {
+        debug_assert!(self.contains(idx));
+        self.new_indices[idx as usize]
+    }
+
+    fn len(&self) -> u32 {
+        self.live.iter().filter(|l| **l).count() as u32
+    }
+
+    fn removed(&self) -> usize {
+        self.live.iter().filter(|l| !**l).count()
+    }
+}
+
+/// The id of each section of a module.
+const CUSTOM: u8 = 0;
+const TYPE: u8 = 1;
+const IMPORT: u8 = 2;
+const FUNCTION: u8 = 3;
+const GLOBAL: u8 = 6;
+const EXPORT: u8 = 7;
+const START: u8 = 8;
+const ELEMENT: u8 = 9;
+const CODE: u8 = 10;
+const DATA: u8 = 11;
+const DATA_COUNT: u8 = 12;
+const TAG: u8 = 13;
+
+/// The ids of the subsections of the `name` section which need renumbering.
+const FUNCTION_NAMES: u8 = 1;
+const LOCAL_NAMES: u8 = 2;
+const LABEL_NAMES: u8 = 3;
+const TYPE_NAMES: u8 = 4;
+const GLOBAL_NAMES: u8 = 7;
+const DATA_NAMES: u8 = 9;
+
+struct Live {
+    funcs: Map,
+    globals: Map,
+    types: Map,
+    data: Map,
+    /// The new indices of functions which need to be declared with a new
+    /// element segment to be used by `ref.func`.
+    undeclared_funcs: Vec<u32>,
+}
+
+impl Live {
+    /// Encode `wasm` with only the live items.
+    fn encode(&self, wasm: &[u8], module: &Module) -> Result<Vec<u8>> {
+        let mut output = wasm[..8].to_vec();
+        let mut reader = BinaryReader::new_with_offset(&wasm[8..], 8);
+        let mut need_declarations = !self.undeclared_funcs.is_empty();
+        let mut func = 0;
+        while !reader.eof() {
+            let id = reader.read_u8()?;
+            let size = reader.read_var_u32()?;
+            let offset = reader.original_position();
+            let data = reader.read_bytes(size as usize)?;
+
+            // Sections which come after the element section are preceded by
+            // a new one, if it's needed but there's no existing section.
+            if need_declarations && matches!(id, DATA_COUNT | CODE | DATA) {
+                let mut items = Vec::new();
+                self.encode_declarations(&mut items);
+                push_section(&mut output, ELEMENT, 1, &items);
+                need_declarations = false;
+            }
+
+            let mut count = 0;
+            let mut items = Vec::new();
+            match id {
+                CUSTOM => {
+                    let custom = CustomSectionReader::new(data, offset)?;
+                    if custom.name() != "name" {
+                        output.push(id);
+                        data.encode(&mut output);
+                        continue;
+                    }
+                    // The name section isn't counted, so it's written out
+                    // directly.
+                    let mut data = Vec::new();
+                    custom.name().encode(&mut data);
+                    data.extend(self.encode_names(custom.data(), custom.data_offset())?);
+                    output.push(id);
+                    data.encode(&mut output);
+                    continue;
+                }
+                TYPE => {
+                    let reader = TypeSectionReader::new(data, offset)?;
+                    for (i, (range, _)) in items_with_ranges(reader, offset + size as usize)?
+                        .into_iter()
+                        .enumerate()
+                    {
+                        if self.types.contains(i as u32) {
+                            items.extend_from_slice(&wasm[range]);
+                            count += 1;
+                        }
+                    }
+                }
+                IMPORT => {
+                    let reader = ImportSectionReader::new(data, offset)?;
+                    let mut global = 0;
+                    for (range, import) in items_with_ranges(reader, offset + size as usize)? {
+                        match import.ty {
+                            TypeRef::Func(ty) => {
+                                func += 1;
+                                if !self.funcs.contains(func - 1) {
+                                    continue;
+                                }
+                                import.module.encode(&mut items);
+                                import.name.encode(&mut items);
+                                items.push(0x00);
+                                self.types.get(ty).encode(&mut items);
+                            }
+                            TypeRef::Global(_) => {
+                                global += 1;
+                                if !self.globals.contains(global - 1) {
+                                    continue;
+                                }
+                                items.extend_from_slice(&wasm[range]);
+                            }
+                            TypeRef::Tag(ty) => {
+                                import.module.encode(&mut items);
+                                import.name.encode(&mut items);
+                                items.extend([0x04, 0x00]);
+                                self.types.get(ty.func_type_idx).encode(&mut items);
+                            }
+                            TypeRef::Table(_) | TypeRef::Memory(_) => {
+                                items.extend_from_slice(&wasm[range]);
+                            }
+                        }
+                        count += 1;
+                    }
+                }
+                FUNCTION => {
+                    for ty in FunctionSectionReader::new(data, offset)? {
+                        let ty = ty?;
+                        func += 1;
+                        if self.funcs.contains(func - 1) {
+                            self.types.get(ty).encode(&mut items);
+                            count += 1;
+                        }
+                    }
+                }
+                TAG => {
+                    for tag in TagSectionReader::new(data, offset)? {
+                        items.push(0x00);
+                        self.types.get(tag?.func_type_idx).encode(&mut items);
+                        count += 1;
+                    }
+                }
+                GLOBAL => {
+                    let reader = GlobalSectionReader::new(data, offset)?;
+                    // Defined globals are numbered after imported ones.
+                    let mut global = module.globals.len() - reader.get_count() as usize;
+                    for (range, g) in items_with_ranges(reader, offset + size as usize)? {
+                        global += 1;
+                        if !self.globals.contains(global as u32 - 1) {
+                            continue;
+                        }
+                        let expr_start = g.init_expr.get_binary_reader().original_position();
+                        items.extend_from_slice(&wasm[range.start..expr_start]);
+                        self.encode_ops(wasm, &mut operators(&g.init_expr), &mut items)?;
+                        count += 1;
+                    }
+                }
+                EXPORT => {
+                    for export in ExportSectionReader::new(data, offset)? {
+                        let export = export?;
+                        if !module.keeps_export(export.name) {
+                            continue;
+                        }
+                        export.name.encode(&mut items);
+                        let (kind, idx) = match export.kind {
+                            ExternalKind::Func => (0x00, self.funcs.get(export.index)),
+                            ExternalKind::Table => (0x01, export.index),
+                            ExternalKind::Memory => (0x02, export.index),
+                            ExternalKind::Global => (0x03, self.globals.get(export.index)),
+                            ExternalKind::Tag => (0x04, export.index),
+                        };
+                        items.push(kind);
+                        idx.encode(&mut items);
+                        count += 1;
+                    }
+                }
+                START => {
+                    let func = BinaryReader::new_with_offset(data, offset).read_var_u32()?;
+                    let mut data = Vec::new();
+                    self.funcs.get(func).encode(&mut data);
+                    output.push(id);
+                    data.encode(&mut output);
+                    continue;
+                }
+                ELEMENT => {
+                    let reader = ElementSectionReader::new(data, offset)?;
+                    for elem in reader {
+                        self.encode_element(wasm, elem?, &mut items)?;
+                        count += 1;
+                    }
+                    if need_declarations {
+                        self.encode_declarations(&mut items);
+                        count += 1;
+                        need_declarations = false;
+                    }
+                }
+                DATA_COUNT => {
+                    let mut data = Vec::new();
+                    self.data.len().encode(&mut data);
+                    output.push(id);
+                    data.encode(&mut output);
+                    continue;
+                }
+                CODE => {
+                    let reader = CodeSectionReader::new(data, offset)?;
+                    for (i, body) in reader.into_iter().enumerate() {
+                        let body = body?;
+                        if !self.funcs.contains(module.num_imported_funcs + i as u32) {
+                            continue;
+                        }
+                        let mut ops = body_operators(&body)?;
+                        let mut encoded =
+                            wasm[body.range().start..ops.original_position()].to_vec();
+                        self.encode_ops(wasm, &mut ops, &mut encoded)?;
+                        encoded.encode(&mut items);
+                        count += 1;
+                    }
+                }
+                DATA => {
+                    let reader = DataSectionReader::new(data, offset)?;
+                    for (i, (range, d)) in items_with_ranges(reader, offset + size as usize)?
+                        .into_iter()
+                        .enumerate()
+                    {
+                        if !self.data.contains(i as u32) {
+                            continue;
+                        }
+                        match d.kind {
+                            DataKind::Passive => items.extend_from_slice(&wasm[range]),
+                            DataKind::Active { offset_expr, .. } => {
+                                let mut ops = operators(&offset_expr);
+                                items
+                                    .extend_from_slice(&wasm[range.start..ops.original_position()]);
+                                self.encode_ops(wasm, &mut ops, &mut items)?;
+                                items.extend_from_slice(&wasm[ops.original_position()..range.end]);
+                            }
+                        }
+                        count += 1;
+                    }
+                }
+                // Tables and memories are always kept, and don't refer to
+                // anything which might be removed.
+                _ => {
+                    output.push(id);
+                    data.encode(&mut output);
+                    continue;
+                }
+            }
+            // Sections whose items were all removed are removed as well.
+            if count > 0 {
+                push_section(&mut output, id, count, &items);
+            }
+        }
+        Ok(output)
+    }
+
+    /// Encode `ops`, renumbering the items that they refer to.
+    fn encode_ops(&self, wasm: &[u8], ops: &mut OperatorsReader, out: &mut Vec<u8>) -> Result<()> {
+        while !ops.eof() {
+            let start = ops.original_position();
+            let op = ops.read()?;
+            let block_type = |ty| match ty {
+                wasmparser::BlockType::FuncType(idx) => {
+                    Some(BlockType::FunctionType(self.types.get(idx)))
+                }
+                _ => None,
+            };
+            let new = match op {
+                Operator::Call { function_index } => {
+                    Instruction::Call(self.funcs.get(function_index))
+                }
+                Operator::ReturnCall { function_index } => {
+                    Instruction::ReturnCall(self.funcs.get(function_index))
+                }
+                Operator::RefFunc { function_index } => {
+                    Instruction::RefFunc(self.funcs.get(function_index))
+                }
+                Operator::GlobalGet { global_index } => {
+                    Instruction::GlobalGet(self.globals.get(global_index))
+                }
+                Operator::GlobalSet { global_index } => {
+                    Instruction::GlobalSet(self.globals.get(global_index))
+                }
+                Operator::CallIndirect {
+                    index, table_index, ..
+                } => Instruction::CallIndirect {
+                    ty: self.types.get(index),
+                    table: table_index,
+                },
+                Operator::ReturnCallIndirect { index, table_index } => {
+                    Instruction::ReturnCallIndirect {
+                        ty: self.types.get(index),
+                        table: table_index,
+                    }
+                }
+                Operator::Block { ty } => match block_type(ty) {
+                    Some(ty) => Instruction::Block(ty),
+                    None => Instruction::Nop,
+                },
+                Operator::Loop { ty } => match block_type(ty) {
+                    Some(ty) => Instruction::Loop(ty),
+                    None => Instruction::Nop,
+                },
+                Operator::If { ty } => match block_type(ty) {
+                    Some(ty) => Instruction::If(ty),
+                    None => Instruction::Nop,
+                },
+                Operator::Try { ty } => match block_type(ty) {
+                    Some(ty) => Instruction::Try(ty),
+                    None => Instruction::Nop,
+                },
+                Operator::MemoryInit { segment, mem } => Instruction::MemoryInit {
+                    mem,
+                    data: self.data.get(segment),
+                },
+                Operator::DataDrop { segment } => Instruction::DataDrop(self.data.get(segment)),
+                _ => Instruction::Nop,
+            };
+            // Everything else is copied as-is, which `nop` stands in for.
+            match new {
+                Instruction::Nop => out.extend_from_slice(&wasm[start..ops.original_position()]),
+                new => new.encode(out),
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_element(
+        &self,
+        wasm: &[u8],
+        elem: wasmparser::Element,
+        out: &mut Vec<u8>,
+    ) -> Result<()> {
+        // Segments keep their original encoding, whose flags say whether
+        // there's an explicit table index and an element type.
+        let flags = BinaryReader::new_with_offset(&wasm[elem.range.start..], elem.range.start)
+            .read_var_u32()?;
+        flags.encode(out);
+        if let ElementKind::Active {
+            table_index,
+            offset_expr,
+        } = &elem.kind
+        {
+            if flags & 0b010 != 0 {
+                table_index.encode(out);
+            }
+            self.encode_ops(wasm, &mut operators(offset_expr), out)?;
+        }
+        let mut items = elem.items.get_items_reader()?;
+        if flags & 0b011 != 0 {
+            if items.uses_exprs() {
+                out.push(match elem.ty {
+                    wasmparser::ValType::FuncRef => 0x70,
+                    wasmparser::ValType::ExternRef => 0x6f,
+                    _ => bail!("invalid element type"),
+                });
+            } else {
+                // The element kind, which is always a function.
+                out.push(0x00);
+            }
+        }
+        items.get_count().encode(out);
+        for _ in 0..items.get_count() {
+            match items.read()? {
+                ElementItem::Func(idx) => self.funcs.get(idx).encode(out),
+                ElementItem::Expr(expr) => self.encode_ops(wasm, &mut operators(&expr), out)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Encode a declared element segment for the functions which otherwise
+    /// wouldn't be declared.
+    fn encode_declarations(&self, out: &mut Vec<u8>) {
+        out.extend([0x03, 0x00]);
+        (self.undeclared_funcs.len() as u32).encode(out);
+        for f in &self.undeclared_funcs {
+            f.encode(out);
+        }
+    }
+
+    /// Renumber the subsections of a `name` section's contents which refer
+    /// to items that may have been removed, copying all others as-is.
+    fn encode_names(&self, data: &[u8], offset: usize) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        let mut reader = BinaryReader::new_with_offset(data, offset);
+        while !reader.eof() {
+            let id = reader.read_u8()?;
+            let size = reader.read_var_u32()?;
+            let subsection_offset = reader.original_position();
+            let subsection = reader.read_bytes(size as usize)?;
+            let mut subsection = BinaryReader::new_with_offset(subsection, subsection_offset);
+            let map = match id {
+                FUNCTION_NAMES => &self.funcs,
+                TYPE_NAMES => &self.types,
+                GLOBAL_NAMES => &self.globals,
+                DATA_NAMES => &self.data,
+                LOCAL_NAMES | LABEL_NAMES => {
+                    let mut names = IndirectNameMap::new();
+                    for _ in 0..subsection.read_var_u32()? {
+                        let func = subsection.read_var_u32()?;
+                        let inner = read_name_map(&mut subsection, None)?;
+                        if self.funcs.contains(func) {
+                            names.append(self.funcs.get(func), &inner);
+                        }
+                    }
+                    output.push(id);
+                    let mut encoded = Vec::new();
+                    names.encode(&mut encoded);
+                    encoded.encode(&mut output);
+                    continue;
+                }
+                _ => {
+                    output.push(id);
+                    let size = subsection.bytes_remaining();
+                    subsection.read_bytes(size)?.encode(&mut output);
+                    continue;
+                }
+            };
+            let names = read_name_map(&mut subsection, Some(map))?;
+            output.push(id);
+            let mut encoded = Vec::new();
+            names.encode(&mut encoded);
+            encoded.encode(&mut output);
+        }
+        Ok(output)
+    }
+}
+
+/// Read a name map, renumbering it with `map` if given and dropping the
+/// names of removed items.
+fn read_name_map(reader: &mut BinaryReader, map: Option<&Map>) -> Result<NameMap> {
+    let mut names = NameMap::new();
+    for _ in 0..reader.read_var_u32()? {
+        let idx = reader.read_var_u32()?;
+        let name = reader.read_string()?;
+        match map {
+            // Names of items which don't exist are dropped as well.
+            Some(map) if (idx as usize) < map.live.len() && map.contains(idx) => {
+                names.append(map.get(idx), name)
+            }
+            Some(_) => {}
+            None => names.append(idx, name),
+        }
+    }
+    Ok(names)
+}
+
+/// Read all of the items of a section along with their byte ranges within the
+/// original input, where the section ends at `end`.
+fn items_with_ranges<S>(reader: S, end: usize) -> Result<Vec<(std::ops::Range<usize>, S::Item)>>
+where
+    S: SectionWithLimitedItems,
+{
+    let mut items: Vec<(std::ops::Range<usize>, S::Item)> = Vec::new();
+    for item in reader.into_iter_with_offsets() {
+        let (start, item) = item?;
+        if let Some(prev) = items.last_mut() {
+            prev.0.end = start;
+        }
+        items.push((start..end, item));
+    }
+    Ok(items)
+}
+
+fn push_section(output: &mut Vec<u8>, id: u8, count: u32, items: &[u8]) {
+    let mut data = Vec::new();
+    count.encode(&mut data);
+    data.extend_from_slice(items);
+    output.push(id);
+    data.encode(output);
+}
@@ -1,10 +1,17 @@
 //! Shared input/output routines amongst most `wasm-tools` subcommands
 
 use anyhow::{bail, Context, Result};
+use memmap2::Mmap;
 use std::fs::File;
 use std::io::{BufWriter, Read, Write};
+use std::ops::Deref;
 use std::path::{Path, PathBuf};
 
+#[cfg(feature = "gc")]
+pub mod gc;
+#[cfg(any(feature = "validate", feature = "lint"))]
+pub mod sarif;
+
 /// Implements the verbosity flag for the CLI commands.
 #[derive(clap::Parser)]
 pub struct Verbosity {
@@ -36,48 +43,210 @@ impl Verbosity {
 // and then the methods are used to read the arguments,
 #[derive(clap::Parser)]
 pub struct InputOutput {
+    #[clap(flatten)]
+    input: InputArg,
+
+    #[clap(flatten)]
+    output: OutputArg,
+}
+
+#[derive(clap::Parser)]
+pub struct InputArg {
     /// Input file to process.
     ///
     /// If not provided or if this is `-` then stdin is read entirely and
     /// processed. Note that for most subcommands this input can either be a
-    /// binary `*.wasm` file or a textual format `*.wat` file.
+    /// binary `*.wasm` file or a textual format `*.wat` file. Files larger
+    /// than 64 MiB are memory-mapped rather than read into memory, so they
+    /// must not be truncated while they're being processed. This includes a
+    /// file redirected to stdin on Unix, but stdin from a pipe can't be
+    /// mapped and is buffered in memory in full before it's processed.
     input: Option<PathBuf>,
-
-    #[clap(flatten)]
-    output: OutputArg,
 }
 
 #[derive(clap::Parser)]
 pub struct OutputArg {
     /// Where to place output.
     ///
-    /// If not provided then stdout is used.
+    /// If not provided or if this is `-` then stdout is used.
     #[clap(short, long)]
     output: Option<PathBuf>,
 }
 
+/// The bytes of a binary WebAssembly input.
+///
+/// Large files are memory-mapped, while other inputs are held in memory.
+pub enum Input {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+    /// The binary encoding of a text-format input.
+    Text(Vec<u8>),
+}
+
+impl Input {
+    /// Whether the input was in the text format, in which case offsets in
+    /// these bytes don't correspond to anything in the original input.
+    pub fn is_text(&self) -> bool {
+        matches!(self, Input::Text(_))
+    }
+}
+
+impl Deref for Input {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Input::Mapped(mmap) => mmap,
+            Input::Owned(bytes) | Input::Text(bytes) => bytes,
+        }
+    }
+}
+
+impl From<Input> for Vec<u8> {
+    fn from(input: Input) -> Vec<u8> {
+        match input {
+            Input::Mapped(mmap) => mmap.to_vec(),
+            Input::Owned(bytes) | Input::Text(bytes) => bytes,
+        }
+    }
+}
+
+/// The size in bytes above which input files are memory-mapped instead of
+/// read into memory.
+pub const MMAP_THRESHOLD: u64 = 64 << 20;
+
+/// Returns an error if more than one of `paths` is `-`, since stdin can only
+/// be read once.
+pub fn check_stdin_once<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Result<()> {
+    if paths.into_iter().filter(|p| *p == Path::new("-")).count() > 1 {
+        bail!("only one input can be read from stdin");
+    }
+    Ok(())
+}
+
+/// Reads the UTF-8 text at `path`, or from stdin if `path` is `-`, without
+/// parsing it.
+pub fn read_text_path(path: &Path) -> Result<String> {
+    if path == Path::new("-") {
+        return read_text_stdin();
+    }
+    std::fs::read_to_string(path).context(format!("failed to read `{}`", path.display()))
+}
+
+fn read_text_stdin() -> Result<String> {
+    let mut stdin = String::new();
+    std::io::stdin()
+        .lock()
+        .read_to_string(&mut stdin)
+        .context("failed to read <stdin>")?;
+    Ok(stdin)
+}
+
+/// Reads the binary or text format WebAssembly at `path`, or from stdin if
+/// `path` is `-`, and returns its binary encoding.
+pub fn parse_wasm_path(path: &Path) -> Result<Input> {
+    if path == Path::new("-") {
+        return parse_wasm_stdin();
+    }
+    let file = File::open(path).context(format!("failed to read `{}`", path.display()))?;
+    let contents =
+        match map_large_file(&file).context(format!("failed to map `{}`", path.display()))? {
+            Some(mmap) => Input::Mapped(mmap),
+            None => {
+                let mut bytes = Vec::new();
+                (&file)
+                    .read_to_end(&mut bytes)
+                    .context(format!("failed to read `{}`", path.display()))?;
+                Input::Owned(bytes)
+            }
+        };
+    if contents.starts_with(b"\0asm") {
+        return Ok(contents);
+    }
+    let bytes = wat::parse_bytes(&contents).map_err(|mut e| {
+        e.set_path(path);
+        e
+    })?;
+    Ok(Input::Text(bytes.into_owned()))
+}
+
+fn parse_wasm_stdin() -> Result<Input> {
+    let stdin = std::io::stdin();
+    let contents = match map_stdin(&stdin).context("failed to map <stdin>")? {
+        Some(mmap) => Input::Mapped(mmap),
+        None => {
+            // A pipe can't be mapped, nor processed before all of it has
+            // arrived, so it's buffered in memory.
+            let mut bytes = Vec::new();
+            stdin
+                .lock()
+                .read_to_end(&mut bytes)
+                .context("failed to read <stdin>")?;
+            Input::Owned(bytes)
+        }
+    };
+    // Binary input is returned as-is rather than copied by `wat`.
+    if contents.starts_with(b"\0asm") {
+        return Ok(contents);
+    }
+    let bytes = wat::parse_bytes(&contents).map_err(|mut e| {
+        e.set_path("<stdin>");
+        e
+    })?;
+    Ok(Input::Text(bytes.into_owned()))
+}
+
+/// Maps stdin if a large file is redirected to it, as with
+/// `wasm-tools print < big.wasm`.
+#[cfg(unix)]
+fn map_stdin(stdin: &std::io::Stdin) -> Result<Option<Mmap>> {
+    use std::io::Seek;
+    use std::os::fd::AsFd;
+
+    let mut file = File::from(stdin.as_fd().try_clone_to_owned()?);
+    // A mapping always starts at the beginning of the file, so a file which
+    // has already been partially read from is read like a pipe would be.
+    if !file.metadata()?.is_file() || file.stream_position()? != 0 {
+        return Ok(None);
+    }
+    map_large_file(&file)
+}
+
+#[cfg(not(unix))]
+fn map_stdin(_stdin: &std::io::Stdin) -> Result<Option<Mmap>> {
+    Ok(None)
+}
+
+/// Maps `file` if it's a regular file larger than [`MMAP_THRESHOLD`].
+///
+/// Anything else, such as a named pipe, should be read like stdin would be.
+fn map_large_file(file: &File) -> Result<Option<Mmap>> {
+    let metadata = file.metadata()?;
+    if !metadata.is_file() || metadata.len() <= MMAP_THRESHOLD {
+        return Ok(None);
+    }
+    // SAFETY: the mapping is only ever read, but it is only sound as long as
+    // no other process modifies the file while it's mapped. That can't be
+    // prevented: if the file is changed, the input may change while it's
+    // being processed, and if it is truncated, reading the pages past its new
+    // end raises `SIGBUS` and kills this process. Files are only mapped when
+    // they're large enough that reading them into memory is a problem, and
+    // this risk is documented on `InputArg`.
+    Ok(Some(unsafe { Mmap::map(file) }?))
+}
+
 pub enum Output<'a> {
     Wat(&'a str),
     Wasm { bytes: &'a [u8], wat: bool },
 }
 
 impl InputOutput {
-    pub fn parse_input_wasm(&self) -> Result<Vec<u8>> {
-        if let Some(path) = &self.input {
-            if path != Path::new("-") {
-                let bytes = wat::parse_file(path)?;
-                return Ok(bytes);
-            }
-        }
-        let mut stdin = Vec::new();
-        std::io::stdin()
-            .read_to_end(&mut stdin)
-            .context("failed to read <stdin>")?;
-        let bytes = wat::parse_bytes(&stdin).map_err(|mut e| {
-            e.set_path("<stdin>");
-            e
-        })?;
-        Ok(bytes.into_owned())
+    pub fn parse_input_wasm(&self) -> Result<Input> {
+        self.input.parse_wasm()
+    }
+
+    pub fn input_path(&self) -> Option<&Path> {
+        self.input.path()
     }
 
     pub fn output(&self, bytes: Output<'_>) -> Result<()> {
@@ -89,7 +258,44 @@ impl InputOutput {
     }
 }
 
+impl InputArg {
+    /// The input file, or `None` if input comes from stdin.
+    pub fn path(&self) -> Option<&Path> {
+        self.input.as_deref().filter(|p| *p != Path::new("-"))
+    }
+
+    /// The name of the input for messages, which is `<stdin>` if input
+    /// comes from stdin.
+    pub fn name(&self) -> String {
+        match self.path() {
+            Some(path) => path.display().to_string(),
+            None => "<stdin>".to_string(),
+        }
+    }
+
+    pub fn parse_wasm(&self) -> Result<Input> {
+        match &self.input {
+            Some(path) => parse_wasm_path(path),
+            None => parse_wasm_stdin(),
+        }
+    }
+
+    /// Reads the input as UTF-8 text, such as a `*.wast` script, without
+    /// parsing it.
+    pub fn read_text(&self) -> Result<String> {
+        match &self.input {
+            Some(path) => read_text_path(path),
+            None => read_text_stdin(),
+        }
+    }
+}
+
 impl OutputArg {
+    /// The output file, or `None` if output goes to stdout.
+    pub fn path(&self) -> Option<&Path> {
+        self.output.as_deref().filter(|p| *p != Path::new("-"))
+    }
+
     pub fn output(&self, output: Output<'_>) -> Result<()> {
         match output {
             Output::Wat(s) => self.output_str(s),
@@ -97,7 +303,7 @@ impl OutputArg {
                 self.output_str(&wasmprinter::print_bytes(&bytes)?)
             }
             Output::Wasm { bytes, wat: false } => {
-                match &self.output {
+                match self.path() {
                     Some(path) => {
                         std::fs::write(path, bytes)
                             .context(format!("failed to write `{}`", path.display()))?;
@@ -117,7 +323,7 @@ impl OutputArg {
     }
 
     fn output_str(&self, output: &str) -> Result<()> {
-        match &self.output {
+        match self.path() {
             Some(path) => {
                 std::fs::write(path, output)
                     .context(format!("failed to write `{}`", path.display()))?;
@@ -130,9 +336,11 @@ impl OutputArg {
     }
 
     pub fn output_writer(&self) -> Result<Box<dyn Write>> {
-        match &self.output {
-            Some(output) => Ok(Box::new(BufWriter::new(File::create(&output)?))),
-            None => Ok(Box::new(std::io::stdout())),
+        match self.path() {
+            Some(output) => Ok(Box::new(BufWriter::new(
+                File::create(output).context(format!("failed to create `{}`", output.display()))?,
+            ))),
+            None => Ok(Box::new(BufWriter::new(std::io::stdout().lock()))),
         }
     }
 }
@@ -0,0 +1,147 @@
+//! Reporting of diagnostics in the [SARIF] format, which code-scanning UIs
+//! such as GitHub's and GitLab's can use to annotate problems inline.
+//!
+//! [SARIF]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::Path;
+
+// This is intended to be included in the options of subcommands which report
+// problems as:
+//
+//      #[clap(flatten)]
+//      diagnostics: wasm_tools::sarif::DiagnosticsArg,
+//
+// and then `format` is used to determine how to report them.
+#[derive(clap::Parser)]
+pub struct DiagnosticsArg {
+    /// Report problems in the given machine-readable format.
+    ///
+    /// With "sarif", a SARIF 2.1.0 log describing every problem found is
+    /// printed instead of the usual output, for consumption by code-scanning
+    /// tools in CI.
+    #[clap(long, arg_enum, value_name = "FORMAT")]
+    diagnostics: Option<DiagnosticsFormat>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ArgEnum)]
+pub enum DiagnosticsFormat {
+    Sarif,
+}
+
+impl DiagnosticsArg {
+    pub fn format(&self) -> Option<DiagnosticsFormat> {
+        self.diagnostics
+    }
+}
+
+/// A kind of problem that a subcommand can report.
+pub struct Rule {
+    pub id: &'static str,
+    pub description: &'static str,
+}
+
+#[derive(Clone, Copy)]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+/// Where a problem is within its input.
+pub enum Region {
+    /// A byte offset within a binary input.
+    Binary { offset: usize },
+    /// A 0-based line and column within a text input.
+    Text { line: usize, column: usize },
+}
+
+/// A single problem found within an input.
+pub struct Diagnostic {
+    /// The id of the [`Rule`] which this is an instance of.
+    pub rule: &'static str,
+    pub level: Level,
+    pub message: String,
+    pub region: Option<Region>,
+}
+
+/// Writes a SARIF log of `diagnostics` found in the input at `artifact`, or
+/// stdin if that's `None`.
+pub fn write(
+    out: &mut dyn Write,
+    artifact: Option<&Path>,
+    rules: &[Rule],
+    diagnostics: &[Diagnostic],
+) -> Result<()> {
+    let rules: Vec<_> = rules
+        .iter()
+        .map(|r| {
+            json!({
+                "id": r.id,
+                "shortDescription": { "text": r.description },
+            })
+        })
+        .collect();
+    let results: Vec<_> = diagnostics.iter().map(|d| result(d, artifact)).collect();
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "wasm-tools",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_writer_pretty(&mut *out, &log)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn result(diagnostic: &Diagnostic, artifact: Option<&Path>) -> Value {
+    let mut result = json!({
+        "ruleId": diagnostic.rule,
+        "level": match diagnostic.level {
+            Level::Error => "error",
+            Level::Warning => "warning",
+        },
+        "message": { "text": diagnostic.message },
+    });
+    // Problems can only be located within files, not stdin.
+    if let Some(artifact) = artifact {
+        let mut location = json!({
+            "artifactLocation": { "uri": uri(artifact) },
+        });
+        // SARIF lines and columns are 1-based.
+        match diagnostic.region {
+            Some(Region::Binary { offset }) => {
+                location["region"] = json!({ "byteOffset": offset, "byteLength": 1 });
+            }
+            Some(Region::Text { line, column }) => {
+                location["region"] = json!({
+                    "startLine": line + 1,
+                    "startColumn": column + 1,
+                });
+            }
+            None => {}
+        }
+        result["locations"] = json!([{ "physicalLocation": location }]);
+    }
+    result
+}
+
+/// The URI of a file, which is relative unless the path is absolute.
+fn uri(path: &Path) -> String {
+    let path = path.display().to_string().replace('\\', "/");
+    if path.starts_with('/') {
+        format!("file://{}", path)
+    } else {
+        path
+    }
+}
@@ -0,0 +1,64 @@
+//! Helpers for tests which run the `wasm-tools` CLI itself.
+
+// Each test crate uses a different subset of these helpers.
+#![allow(dead_code)]
+
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+pub fn wasm_tools(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_wasm-tools"))
+        .current_dir(dir)
+        .args(args)
+        .env("RUST_BACKTRACE", "0")
+        .output()
+        .unwrap()
+}
+
+/// Runs `wasm-tools` with `stdin` as its standard input.
+pub fn wasm_tools_stdin(dir: &Path, args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_wasm-tools"))
+        .current_dir(dir)
+        .args(args)
+        .env("RUST_BACKTRACE", "0")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Commands which fail before reading all of stdin close the pipe early.
+    match child.stdin.take().unwrap().write_all(stdin) {
+        Err(e) if e.kind() != ErrorKind::BrokenPipe => panic!("{e}"),
+        _ => {}
+    }
+    child.wait_with_output().unwrap()
+}
+
+/// Runs `wasm-tools` in `dir`, asserting that it succeeds, and returns its
+/// stdout and stderr.
+pub fn success(dir: &Path, args: &[&str]) -> (String, String) {
+    let output = wasm_tools(dir, args);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        output.status.success(),
+        "`wasm-tools {}` failed\nstdout: {stdout}\nstderr: {stderr}",
+        args.join(" ")
+    );
+    (stdout, stderr)
+}
+
+/// Runs `wasm-tools` in `dir`, asserting that it fails, and returns its
+/// stdout and stderr.
+pub fn failure(dir: &Path, args: &[&str]) -> (String, String) {
+    let output = wasm_tools(dir, args);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        !output.status.success(),
+        "`wasm-tools {}` succeeded\nstdout: {stdout}\nstderr: {stderr}",
+        args.join(" ")
+    );
+    (stdout, stderr)
+}
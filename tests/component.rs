@@ -0,0 +1,186 @@
+//! Tests of `wasm-tools component`.
+
+use common::{failure, success};
+use tempfile::TempDir;
+
+mod common;
+
+#[test]
+fn semver_check() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    let component = |name: &str, imports: &str, exports: &str| {
+        let wat = format!(
+            r#"(component
+                (import "a" (func $a (param "x" u32)))
+                (import "i" (instance $i {imports}))
+                {exports}
+            )"#
+        );
+        std::fs::write(dir.join(name), wat).unwrap();
+    };
+    let both = r#"(export "f" (func)) (export "g" (func))"#;
+    component("v1.wat", both, r#"(export "a" (func $a))"#);
+    component(
+        "v1.1.wat",
+        both,
+        r#"(export "a" (func $a)) (export "b" (func $a))"#,
+    );
+    component(
+        "v1.2.wat",
+        r#"(export "f" (func))"#,
+        r#"(export "a" (func $a))"#,
+    );
+    component("v2.wat", both, "");
+    std::fs::write(
+        dir.join("v3.wat"),
+        r#"(component (import "a" (func $a (param "x" string))) (export "a" (func $a)))"#,
+    )
+    .unwrap();
+    std::fs::write(dir.join("module.wat"), "(module)").unwrap();
+
+    let (stdout, _) = success(dir, &["component", "semver-check", "v1.wat", "v1.wat"]);
+    assert_eq!(stdout, "semver: patch\n");
+
+    let (stdout, _) = success(dir, &["component", "semver-check", "v1.wat", "v1.1.wat"]);
+    assert_eq!(stdout, "minor: export `b` was added\nsemver: minor\n");
+
+    // Imports may require less of their instantiator.
+    let (stdout, _) = success(dir, &["component", "semver-check", "v1.wat", "v1.2.wat"]);
+    assert_eq!(
+        stdout,
+        "minor: import `i` changed type compatibly\nsemver: minor\n"
+    );
+    let (stdout, stderr) = failure(dir, &["component", "semver-check", "v1.2.wat", "v1.wat"]);
+    assert_eq!(
+        stdout,
+        "major: import `i` changed type incompatibly\nsemver: major\n"
+    );
+    assert!(stderr.contains("semver-major changes"), "{stderr}");
+
+    let (stdout, _) = failure(dir, &["component", "semver-check", "v1.1.wat", "v2.wat"]);
+    assert_eq!(
+        stdout,
+        "major: export `a` was removed\nmajor: export `b` was removed\nsemver: major\n"
+    );
+    let args = [
+        "component",
+        "semver-check",
+        "v1.wat",
+        "v3.wat",
+        "--allow-major",
+    ];
+    let (stdout, _) = success(dir, &args);
+    assert_eq!(
+        stdout,
+        "major: export `a` changed type incompatibly\n\
+         major: import `a` changed type incompatibly\n\
+         minor: import `i` was removed\n\
+         semver: major\n"
+    );
+
+    let (_, stderr) = failure(dir, &["component", "semver-check", "module.wat", "v1.wat"]);
+    assert!(stderr.contains("not a component"), "{stderr}");
+}
+
+#[test]
+fn unbundle() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    std::fs::write(
+        dir.join("component.wat"),
+        r#"(component
+            (import "m" (core module))
+            (core module $a
+                (import "b" "f" (func))
+                (func (export "g"))
+            )
+            (core module $b (func (export "f")))
+            (component (core module))
+            (core instance $bi (instantiate $b))
+            (core instance $ai (instantiate $a (with "b" (instance $bi))))
+            (core instance (export "g2" (func $ai "g")))
+        )"#,
+    )
+    .unwrap();
+    std::fs::write(dir.join("module.wat"), "(module)").unwrap();
+
+    success(
+        dir,
+        &["component", "unbundle", "component.wat", "--out-dir", "out"],
+    );
+    let mut files: Vec<_> = std::fs::read_dir(dir.join("out"))
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        [
+            "component0.wasm",
+            "manifest.json",
+            "module1.wasm",
+            "module2.wasm"
+        ]
+    );
+
+    // The extracted modules and components are valid on their own.
+    success(dir, &["validate", "out/module1.wasm"]);
+    success(dir, &["validate", "out/module2.wasm"]);
+    success(
+        dir,
+        &["validate", "-f", "wasi-preview2", "out/component0.wasm"],
+    );
+
+    let manifest = std::fs::read(dir.join("out/manifest.json")).unwrap();
+    let manifest: serde_json::Value = serde_json::from_slice(&manifest).unwrap();
+    let module1 = std::fs::metadata(dir.join("out/module1.wasm"))
+        .unwrap()
+        .len();
+    let module2 = std::fs::metadata(dir.join("out/module2.wasm"))
+        .unwrap()
+        .len();
+    let component0 = std::fs::metadata(dir.join("out/component0.wasm"))
+        .unwrap()
+        .len();
+    assert_eq!(
+        manifest,
+        serde_json::json!({
+            "modules": [
+                { "kind": "import", "name": "m" },
+                {
+                    "kind": "defined",
+                    "file": "module1.wasm",
+                    "size": module1,
+                    "imports": ["b::f"],
+                    "exports": ["g"],
+                },
+                {
+                    "kind": "defined",
+                    "file": "module2.wasm",
+                    "size": module2,
+                    "exports": ["f"],
+                },
+            ],
+            "components": [
+                { "kind": "defined", "file": "component0.wasm", "size": component0 },
+            ],
+            "core_instances": [
+                { "kind": "instantiate", "module": 2, "args": [] },
+                {
+                    "kind": "instantiate",
+                    "module": 1,
+                    "args": [{ "name": "b", "instance": 0 }],
+                },
+                {
+                    "kind": "from-exports",
+                    "exports": [{ "name": "g2", "kind": "func", "index": 0 }],
+                },
+            ],
+        })
+    );
+
+    let args = ["component", "unbundle", "module.wat", "--out-dir", "out2"];
+    let (_, stderr) = failure(dir, &args);
+    assert!(stderr.contains("not a component"), "{stderr}");
+}
@@ -0,0 +1,94 @@
+//! Tests of `wasm-tools coredump`.
+
+use common::{failure, success};
+use tempfile::TempDir;
+
+mod common;
+
+#[test]
+fn inspect() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    // A crash in `$crash`, called from `$start`, with its first local set
+    // and an `i64` on its operand stack.
+    std::fs::write(
+        dir.join("dump.wat"),
+        r#"(module
+            (memory 1)
+            (global (mut i32) (i32.const 42))
+            (data (i32.const 0) "\01\00\02")
+            (@custom "core" "\00\04proc")
+            (@custom "coremodules" "\01\00\09main.wasm")
+            (@custom "coreinstances" "\01\00\00\01\00\01\00")
+            (@custom "corestack" "\00\04main\02\00\00\01\05\02\7f\07\01\01\7e\2a\00\00\00\03\00\00")
+        )"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("main.wat"),
+        r#"(module
+            (func $start i32.const 7 call $crash)
+            (func $crash (param $x i32) (local $y i64) unreachable)
+        )"#,
+    )
+    .unwrap();
+    std::fs::write(dir.join("empty.wat"), "(module)").unwrap();
+
+    let summary = "
+modules:
+  0: main.wasm
+
+instances:
+  0: module 0, memories [0], globals [0]
+
+memories:
+  0: 1 page(s), 3 byte(s) of data of which 2 are non-zero
+
+globals:
+  0: mut i32 42
+";
+    let (stdout, _) = success(dir, &["coredump", "inspect", "dump.wat"]);
+    assert_eq!(
+        stdout,
+        format!(
+            "process: proc
+
+thread `main`:
+  #0   instance 0, function 1 at offset 0x5
+        local 0: i32 7
+        local 1: <missing>
+        stack: [i64 42]
+  #1   instance 0, function 0 at offset 0x3
+{summary}"
+        )
+    );
+
+    // The module's name section symbolicates the frames.
+    let args = ["coredump", "inspect", "dump.wat", "--module", "main.wat"];
+    let (stdout, _) = success(dir, &args);
+    assert_eq!(
+        stdout,
+        format!(
+            "process: proc
+
+thread `main`:
+  #0   instance 0, `crash` (function 1) at offset 0x5
+        local 0 `x`: i32 7
+        local 1 `y`: <missing>
+        stack: [i64 42]
+  #1   instance 0, `start` (function 0) at offset 0x3
+{summary}"
+        )
+    );
+
+    let args = [
+        "coredump", "inspect", "dump.wat", "--module", "main.wat", "--module", "main.wat",
+    ];
+    let (_, stderr) = failure(dir, &args);
+    assert!(
+        stderr.contains("2 modules were given but the coredump only lists 1"),
+        "{stderr}"
+    );
+    let (_, stderr) = failure(dir, &["coredump", "inspect", "empty.wat"]);
+    assert!(stderr.contains("no `core` custom section"), "{stderr}");
+}
@@ -0,0 +1,71 @@
+//! Tests of `wasm-tools diff`.
+
+use common::success;
+use tempfile::TempDir;
+
+mod common;
+
+#[test]
+fn instructions() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    std::fs::write(
+        dir.join("old.wat"),
+        r#"(module
+            (global $x (mut i32) (i32.const 0))
+            (func $f (result i32) i32.const 1)
+            (func $g (result i32) call $f global.get $x i32.add)
+            (func $h (param i32) (result i32)
+                local.get 0
+                if (result i32) i32.const 1 else i32.const 2 end))"#,
+    )
+    .unwrap();
+    // Inserting a global and a function renumbers the ones after them.
+    std::fs::write(
+        dir.join("new.wat"),
+        r#"(module
+            (global $y (mut i32) (i32.const 0))
+            (global $x (mut i32) (i32.const 0))
+            (func $new)
+            (func $f (result i32) i32.const 1)
+            (func $g (result i32) call $f global.get $x i32.add)
+            (func $h (param i32) (result i32)
+                local.get 0
+                if (result i32) i32.const 1 else i32.const 3 end))"#,
+    )
+    .unwrap();
+
+    let (stdout, _) = success(dir, &["diff", "old.wat", "new.wat", "--instructions"]);
+    assert!(stdout.contains("  + new: 2 bytes\n"), "{stdout}");
+    assert!(
+        stdout.contains("  = g: renumbered, 7 -> 7 bytes (+0)\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("  ~ h: 12 -> 12 bytes (+0)\n      - i32.const 2\n      + i32.const 3\n"),
+        "{stdout}"
+    );
+
+    // Without names, the changed indices are shown.
+    std::fs::write(
+        dir.join("old.wat"),
+        "(module (func (result i32) i32.const 1) (func (result i32) call 0))",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("new.wat"),
+        "(module (func (result i32) i32.const 1) (func (result i32) call 1))",
+    )
+    .unwrap();
+    let (stdout, _) = success(dir, &["diff", "old.wat", "new.wat", "--instructions"]);
+    assert!(
+        stdout.contains(
+            "  = func[1]: renumbered, 4 -> 4 bytes (+0)\n      - call 0\n      + call 1\n"
+        ),
+        "{stdout}"
+    );
+    let (stdout, _) = success(dir, &["diff", "old.wat", "new.wat", "--json"]);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["functions"]["renumbered"][0]["name"], "func[1]");
+    assert_eq!(json["functions"]["changed"], serde_json::json!([]));
+}
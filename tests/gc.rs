@@ -0,0 +1,210 @@
+#![cfg(feature = "gc")]
+
+use anyhow::Result;
+use wasm_tools::gc::{gc, Removed};
+
+/// Collects `input` with `roots` and checks that it prints the same as
+/// `expected`, returning what was removed.
+fn assert_gc(input: &str, roots: &[&str], expected: &str) -> Result<Removed> {
+    let input = wat::parse_str(input)?;
+    let roots = roots.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+    let (output, removed) = gc(&input, &roots)?;
+    let actual = wasmprinter::print_bytes(&output)?;
+    let expected = wasmprinter::print_bytes(wat::parse_str(expected)?)?;
+    assert_eq!(actual, expected);
+    Ok(removed)
+}
+
+#[test]
+fn element_segments() -> Result<()> {
+    let removed = assert_gc(
+        r#"
+        (module
+          (type $unused (func (param i64)))
+          (type $t (func))
+          (import "m" "dead" (func $dead (type $unused)))
+          (import "m" "used" (func $used (type $t)))
+          (global $dead_g i32 (i32.const 0))
+          (global $g (mut i32) (i32.const 1))
+          (table 2 funcref)
+          (func $dead2 (type $unused))
+          (func $f (type $t) global.get $g drop call $used)
+          (func $h (type $t))
+          (elem (i32.const 0) func $f)
+          (elem funcref (ref.func $h)))
+        "#,
+        &[],
+        r#"
+        (module
+          (type $t (func))
+          (import "m" "used" (func $used (type $t)))
+          (global $g (mut i32) (i32.const 1))
+          (table 2 funcref)
+          (func $f (type $t) global.get $g drop call $used)
+          (func $h (type $t))
+          (elem (i32.const 0) func $f)
+          (elem funcref (ref.func $h)))
+        "#,
+    )?;
+    assert_eq!(
+        removed,
+        Removed {
+            funcs: 2,
+            globals: 1,
+            types: 1,
+            data: 0,
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn start() -> Result<()> {
+    assert_gc(
+        r#"
+        (module
+          (type $unused (func (param i32)))
+          (type $t (func))
+          (import "m" "dead" (func $dead (type $unused)))
+          (global $dead_g i32 (i32.const 0))
+          (global $g (mut i32) (i32.const 0))
+          (func $dead2 (type $t))
+          (func $s (type $t) i32.const 1 global.set $g call $helper)
+          (func $helper (type $t))
+          (start $s))
+        "#,
+        &[],
+        r#"
+        (module
+          (type $t (func))
+          (global $g (mut i32) (i32.const 0))
+          (func $s (type $t) i32.const 1 global.set $g call $helper)
+          (func $helper (type $t))
+          (start $s))
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn exports() -> Result<()> {
+    let input = r#"
+        (module
+          (type $a_ty (func (param i32)))
+          (type $b_ty (func (result i32)))
+          (import "m" "a" (func $imported_a (type $a_ty)))
+          (import "m" "b" (func $imported_b (type $b_ty)))
+          (import "m" "g" (global $imported_g i32))
+          (global $ga i32 (i32.const 1))
+          (global $gb i32 (global.get $imported_g))
+          (func $a (type $a_ty) global.get $ga call $imported_a)
+          (func $b (type $b_ty) call $imported_b)
+          (export "a" (func $a))
+          (export "b" (func $b))
+          (export "gb" (global $gb))
+          (export "mem" (memory 0))
+          (memory 1))
+    "#;
+    let removed = assert_gc(
+        input,
+        &["b", "gb"],
+        r#"
+        (module
+          (type $b_ty (func (result i32)))
+          (import "m" "b" (func $imported_b (type $b_ty)))
+          (import "m" "g" (global $imported_g i32))
+          (global $gb i32 (global.get $imported_g))
+          (func $b (type $b_ty) call $imported_b)
+          (export "b" (func $b))
+          (export "gb" (global $gb))
+          (memory 1))
+        "#,
+    )?;
+    assert_eq!(
+        removed,
+        Removed {
+            funcs: 2,
+            globals: 1,
+            types: 1,
+            data: 0,
+        }
+    );
+
+    // Without roots everything that's exported is kept.
+    let removed = assert_gc(input, &[], input)?;
+    assert_eq!(removed, Removed::default());
+
+    let err = gc(&wat::parse_str(input)?, &["c".to_string()]).unwrap_err();
+    assert_eq!(err.to_string(), "module has no export named `c`");
+    Ok(())
+}
+
+#[test]
+fn names() -> Result<()> {
+    let input = wat::parse_str(
+        r#"
+        (module
+          (func $dead (param $unused i64))
+          (func $f (export "f") (param $x i32) (local $y i32)
+            block $outer
+              local.get $x
+              local.set $y
+            end
+            call $g)
+          (func $g))
+        "#,
+    )?;
+    let (output, _) = gc(&input, &[])?;
+    let text = wasmprinter::print_bytes(&output)?;
+    assert!(
+        text.contains("(func $f (;0;) (type 0) (param $x i32)"),
+        "{text}"
+    );
+    assert!(text.contains("(local $y i32)"), "{text}");
+    assert!(text.contains("block $outer"), "{text}");
+    assert!(text.contains("call $g"), "{text}");
+    assert!(text.contains("(func $g (;1;)"), "{text}");
+    assert!(
+        !text.contains("$dead") && !text.contains("$unused"),
+        "{text}"
+    );
+    Ok(())
+}
+
+#[test]
+fn passive_data() -> Result<()> {
+    let removed = assert_gc(
+        r#"
+        (module
+          (memory 1)
+          (data $dead "dead")
+          (data $p "hello")
+          (data (i32.const 0) "active")
+          (func $dead_f
+            (memory.init $dead (i32.const 0) (i32.const 0) (i32.const 4)))
+          (func (export "init")
+            (memory.init $p (i32.const 0) (i32.const 0) (i32.const 5))
+            data.drop $p))
+        "#,
+        &[],
+        r#"
+        (module
+          (memory 1)
+          (data $p "hello")
+          (data (i32.const 0) "active")
+          (func (export "init")
+            (memory.init $p (i32.const 0) (i32.const 0) (i32.const 5))
+            data.drop $p))
+        "#,
+    )?;
+    assert_eq!(
+        removed,
+        Removed {
+            funcs: 1,
+            globals: 0,
+            types: 0,
+            data: 1,
+        }
+    );
+    Ok(())
+}
@@ -0,0 +1,128 @@
+//! Tests of how `wasm-tools` reads its inputs.
+
+use common::wasm_tools_stdin;
+use std::process::Output;
+use tempfile::TempDir;
+use wasm_tools::{parse_wasm_path, Input, MMAP_THRESHOLD};
+
+mod common;
+
+/// A module of exactly `len` bytes, padded with a custom section.
+fn module_of_len(len: u64) -> Vec<u8> {
+    let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+    // The custom section's size is encoded with a fixed-width LEB128, so the
+    // rest of the module has a known length.
+    let payload = len as usize - wasm.len() - 1 - 5;
+    wasm.push(0);
+    let mut size = payload as u32;
+    for i in 0..5 {
+        let byte = (size & 0x7f) as u8;
+        size >>= 7;
+        wasm.push(if i < 4 { byte | 0x80 } else { byte });
+    }
+    wasm.extend_from_slice(&[3, b'p', b'a', b'd']);
+    wasm.resize(len as usize, 0);
+    wasm
+}
+
+#[test]
+fn mmap_threshold() {
+    let dir = TempDir::new().unwrap();
+    for (len, mapped) in [(MMAP_THRESHOLD, false), (MMAP_THRESHOLD + 1, true)] {
+        let path = dir.path().join("module.wasm");
+        let wasm = module_of_len(len);
+        std::fs::write(&path, &wasm).unwrap();
+
+        let input = parse_wasm_path(&path).unwrap();
+        assert_eq!(matches!(input, Input::Mapped(_)), mapped, "{len} bytes");
+        assert!(!input.is_text());
+        assert!(*input == *wasm, "{len} bytes");
+        wasmparser::validate(&input).unwrap();
+    }
+}
+
+#[test]
+fn small_text_input() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("module.wat");
+    std::fs::write(&path, "(module)").unwrap();
+    let input = parse_wasm_path(&path).unwrap();
+    assert!(input.is_text());
+    assert_eq!(&*input, b"\0asm\x01\0\0\0");
+}
+
+#[test]
+fn large_redirected_stdin() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("module.wasm");
+    std::fs::write(&path, module_of_len(MMAP_THRESHOLD + 1)).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_wasm-tools"))
+        .args(["strip", "-a"])
+        .stdin(std::fs::File::open(&path).unwrap())
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert_eq!(output.stdout, b"\0asm\x01\0\0\0");
+}
+
+#[test]
+fn inputs_from_stdin() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    let module = "(module (func (export \"f\") (result i32) i32.const 1))";
+    std::fs::write(dir.join("module.wat"), module).unwrap();
+
+    let stdout = |output: Output| {
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        assert!(output.status.success(), "{stderr}");
+        String::from_utf8(output.stdout).unwrap()
+    };
+    for args in [&["print"][..], &["print", "-"]] {
+        let text = stdout(wasm_tools_stdin(dir, args, module.as_bytes()));
+        assert!(text.contains("(export \"f\" (func 0))"), "{text}");
+    }
+
+    let binary = wat::parse_str(module).unwrap();
+    let text = stdout(wasm_tools_stdin(
+        dir,
+        &["diff", "-", "module.wat", "--json"],
+        &binary,
+    ));
+    let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(json["functions"]["changed"], serde_json::json!([]));
+
+    let wast = format!("{module}\n(assert_return (invoke \"f\") (i32.const 1))\n");
+    let text = stdout(wasm_tools_stdin(
+        dir,
+        &["json-from-wast", "-o", "out.json"],
+        wast.as_bytes(),
+    ));
+    assert_eq!(text, "");
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("out.json")).unwrap()).unwrap();
+    assert_eq!(json["source_filename"], "<stdin>");
+    assert_eq!(json["commands"][1]["type"], "assert_return");
+
+    // A shrunken module has nowhere to go by default.
+    let output = wasm_tools_stdin(dir, &["shrink", "predicate.sh"], &binary);
+    assert!(!output.status.success());
+
+    // Stdin can't be read for more than one input.
+    for args in [
+        &["diff", "-", "-"][..],
+        &["metadata", "diff", "-", "-"],
+        &["component", "semver-check", "-", "-"],
+        &["coredump", "inspect", "-", "--module", "-"],
+        &["wast", "run", "-", "-", "--", "engine"],
+    ] {
+        let output = wasm_tools_stdin(dir, args, &binary);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(!output.status.success(), "{args:?}");
+        assert!(
+            stderr.contains("only one input can be read from stdin"),
+            "{args:?}: {stderr}"
+        );
+    }
+}
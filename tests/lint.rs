@@ -0,0 +1,113 @@
+//! Tests of `wasm-tools lint`.
+
+use common::{failure, success};
+use tempfile::TempDir;
+
+mod common;
+
+#[test]
+fn rules() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    std::fs::write(
+        dir.join("module.wat"),
+        format!(
+            r#"(module
+                (import "env" "used" (func $used))
+                (import "env" "unused" (func $unused))
+                (memory 1)
+                (global (export "g") (mut i32) (i32.const 0))
+                (func $dead call $used)
+                (func (export "live"))
+                (data (i32.const 0) "{}")
+            )"#,
+            "\\00".repeat(2048)
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("clean.wat"),
+        r#"(module (@custom "producers" "\00") (func (export "f")))"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("component.wat"),
+        r#"(component
+            (core module (@custom "producers" "\00") (func))
+            (core module)
+        )"#,
+    )
+    .unwrap();
+
+    let (stdout, stderr) = failure(dir, &["lint", "module.wat"]);
+    assert_eq!(
+        stdout,
+        "warning[unused-import]: imported function `env::unused` is never used (at offset 0x1c)\n\
+         warning[mutable-global-export]: mutable global is exported as `g` (at offset 0x3e)\n\
+         warning[dead-function]: function `dead` (index 2) is never called or exported (at offset 0x4d)\n\
+         warning[zero-data]: data segment 0 is 2048 bytes of zeros (at offset 0x58)\n\
+         warning[missing-producers]: module has no `producers` section (at offset 0x87c)\n"
+    );
+    assert!(stderr.contains("found 5 lint warning(s)"), "{stderr}");
+
+    let (stdout, _) = failure(dir, &["lint", "module.wat", "--json"]);
+    let warnings: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let rules: Vec<_> = warnings
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|w| w["rule"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        rules,
+        [
+            "unused-import",
+            "mutable-global-export",
+            "dead-function",
+            "zero-data",
+            "missing-producers"
+        ]
+    );
+    assert_eq!(warnings[3]["offset"], 0x58);
+    assert_eq!(
+        warnings[3]["message"],
+        "data segment 0 is 2048 bytes of zeros"
+    );
+
+    let args = [
+        "lint",
+        "module.wat",
+        "--disable=zero-data",
+        "--disable=dead-function",
+        "--disable=missing-producers",
+    ];
+    let (stdout, _) = failure(dir, &args);
+    assert_eq!(stdout.lines().count(), 2, "{stdout}");
+    let args = ["lint", "module.wat", "--disable=all", "--enable=zero-data"];
+    let (stdout, stderr) = failure(dir, &args);
+    assert!(stdout.starts_with("warning[zero-data]"), "{stdout}");
+    assert!(stderr.contains("found 1 lint warning(s)"), "{stderr}");
+    let (stdout, _) = success(dir, &["lint", "module.wat", "--disable=all"]);
+    assert_eq!(stdout, "");
+
+    let (stdout, _) = success(dir, &["lint", "clean.wat"]);
+    assert_eq!(stdout, "");
+    let (stdout, _) = success(dir, &["lint", "clean.wat", "--json"]);
+    assert_eq!(stdout, "[]\n");
+
+    // Each module within a component is checked.
+    let (stdout, _) = failure(dir, &["lint", "component.wat"]);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2, "{stdout}");
+    assert!(
+        lines[0].starts_with("warning[dead-function]: module[0]: function 0 "),
+        "{stdout}"
+    );
+    assert!(
+        lines[1].starts_with("warning[missing-producers]: module[1]: "),
+        "{stdout}"
+    );
+
+    let (_, stderr) = failure(dir, &["lint", "clean.wat", "--disable=nope"]);
+    assert!(stderr.contains("unknown lint rule `nope`"), "{stderr}");
+}
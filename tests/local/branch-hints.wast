@@ -0,0 +1,39 @@
+(module
+  (func $plain (param i32)
+    (local i64)
+    local.get 0
+    (@metadata.code.branch_hint "\00")
+    if
+      nop
+    end
+    block
+      local.get 0
+      (@metadata.code.branch_hint "\01")
+      br_if 0
+    end
+  )
+
+  (func $folded (param i32) (result i32)
+    (@metadata.code.branch_hint "\01")
+    (if (result i32) (local.get 0)
+      (then (i32.const 1))
+      (else
+        (block (result i32)
+          (@metadata.code.branch_hint "\00")
+          (br_if 0 (i32.const 2) (local.get 0))
+          drop
+          (i32.const 3)))))
+)
+
+(assert_malformed
+  (module quote
+    "(func (@metadata.code.branch_hint \"\\02\") (if (i32.const 0) (then)))")
+  "invalid branch hint value")
+
+(assert_malformed
+  (module quote "(func (@metadata.code.branch_hint \"\\01\") nop)")
+  "branch hints may only be placed on `if` and `br_if` instructions")
+
+(assert_malformed
+  (module quote "(func (@metadata.code.branch_hint \"\\01\"))")
+  "branch hint must be followed by an instruction")
@@ -0,0 +1,172 @@
+;; async lifts with and without a callback
+(component
+  (core module $m
+    (memory (export "memory") 1)
+    (func (export "f") (param i32 i64))
+    (func (export "g") (param i32 i64) (result i32) unreachable)
+    (func (export "cb") (param i32 i32 i32) (result i32) unreachable)
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32) unreachable)
+  )
+  (core instance $i (instantiate $m))
+
+  (func (export "f") (param "x" u32) (param "y" u64) (result string)
+    (canon lift (core func $i "f") async (memory $i "memory") (realloc (func $i "realloc")))
+  )
+  (func (export "g") (param "x" u32) (param "y" u64) (result string)
+    (canon lift (core func $i "g") async (callback (func $i "cb")) (memory $i "memory") (realloc (func $i "realloc")))
+  )
+)
+
+;; async lowers pass few parameters directly and results through a pointer
+(component
+  (import "f" (func $f (param "a" u32) (param "b" u32) (param "c" u32) (param "d" u32) (result u8)))
+  (import "g" (func $g (param "a" u32) (param "b" u32) (param "c" u32) (param "d" u32) (param "e" u32)))
+  (core module $libc (memory (export "memory") 1))
+  (core instance $libc (instantiate $libc))
+  (core func $f (canon lower (func $f) async (memory $libc "memory")))
+  (core func $g (canon lower (func $g) async (memory $libc "memory")))
+  (core module $m
+    (import "" "f" (func (param i32 i32 i32 i32 i32) (result i32)))
+    (import "" "g" (func (param i32) (result i32)))
+  )
+  (core instance (instantiate $m
+    (with "" (instance (export "f" (func $f)) (export "g" (func $g))))
+  ))
+)
+
+;; task, stream, future, and error built-ins
+(component
+  (type $t (func (result "a" u32) (result "b" float64)))
+  (type $s (list u8))
+  (core module $libc (memory (export "memory") 1))
+  (core instance $libc (instantiate $libc))
+  (core func $return (canon task.return $t))
+  (core func $wait (canon task.wait async (memory $libc "memory")))
+  (core func $poll (canon task.poll (memory $libc "memory")))
+  (core func $yield (canon task.yield))
+  (core func $stream-new (canon stream.new $s))
+  (core func $stream-receive (canon stream.receive $s (memory $libc "memory")))
+  (core func $stream-send (canon stream.send $s async (memory $libc "memory")))
+  (core func $future-new (canon future.new $s))
+  (core func $future-receive (canon future.receive $s (memory $libc "memory")))
+  (core func $future-send (canon future.send $s (memory $libc "memory")))
+  (core func $error-drop (canon error.drop))
+  (core module $m
+    (import "" "return" (func (param i32 f64)))
+    (import "" "wait" (func (param i32) (result i32)))
+    (import "" "poll" (func (param i32) (result i32)))
+    (import "" "yield" (func))
+    (import "" "stream-new" (func (result i32)))
+    (import "" "stream-receive" (func (param i32 i32 i32) (result i32)))
+    (import "" "stream-send" (func (param i32 i32 i32) (result i32)))
+    (import "" "future-new" (func (result i32)))
+    (import "" "future-receive" (func (param i32 i32) (result i32)))
+    (import "" "future-send" (func (param i32 i32) (result i32)))
+    (import "" "error-drop" (func (param i32)))
+  )
+  (core instance (instantiate $m
+    (with "" (instance
+      (export "return" (func $return))
+      (export "wait" (func $wait))
+      (export "poll" (func $poll))
+      (export "yield" (func $yield))
+      (export "stream-new" (func $stream-new))
+      (export "stream-receive" (func $stream-receive))
+      (export "stream-send" (func $stream-send))
+      (export "future-new" (func $future-new))
+      (export "future-receive" (func $future-receive))
+      (export "future-send" (func $future-send))
+      (export "error-drop" (func $error-drop))
+    ))
+  ))
+)
+
+(assert_invalid
+  (component
+    (core module $m
+      (func (export "f") (result i32) unreachable)
+    )
+    (core instance $i (instantiate $m))
+    (func (export "f") (result u32)
+      (canon lift (core func $i "f") async)
+    )
+  )
+  "lowered result types `[]` do not match result types `[I32]`"
+)
+
+(assert_invalid
+  (component
+    (core module $m
+      (func (export "f"))
+      (func (export "cb") (param i32 i32) (result i32) unreachable)
+    )
+    (core instance $i (instantiate $m))
+    (func (export "f")
+      (canon lift (core func $i "f") async (callback (func $i "cb")))
+    )
+  )
+  "canonical option `callback` uses a core function with an incorrect signature"
+)
+
+(assert_invalid
+  (component
+    (core module $m
+      (func (export "f"))
+      (func (export "cb") (param i32 i32 i32) (result i32) unreachable)
+    )
+    (core instance $i (instantiate $m))
+    (func (export "f")
+      (canon lift (core func $i "f") (callback (func $i "cb")))
+    )
+  )
+  "canonical option `callback` requires the `async` option"
+)
+
+(assert_invalid
+  (component
+    (import "f" (func $f))
+    (core module $m
+      (func (export "cb") (param i32 i32 i32) (result i32) unreachable)
+    )
+    (core instance $i (instantiate $m))
+    (core func (canon lower (func $f) async (callback (func $i "cb"))))
+  )
+  "canonical option `callback` cannot be specified for lowerings"
+)
+
+(assert_invalid
+  (component
+    (core module $m
+      (func (export "f"))
+      (func (export "post") unreachable)
+    )
+    (core instance $i (instantiate $m))
+    (func (export "f")
+      (canon lift (core func $i "f") async (post-return (func $i "post")))
+    )
+  )
+  "canonical option `post-return` cannot be specified for async lifts"
+)
+
+(assert_invalid
+  (component
+    (type $s (list u8))
+    (core func (canon stream.send $s))
+  )
+  "canonical option `memory` is required"
+)
+
+(assert_invalid
+  (component
+    (core func (canon task.wait (memory 0)))
+  )
+  "memory index out of bounds"
+)
+
+(assert_invalid
+  (component
+    (type $s (list u8))
+    (core func (canon task.return $s))
+  )
+  "not a function type"
+)
@@ -0,0 +1,39 @@
+(component definition $C
+  (core module $m
+    (func (export "add") (param i32 i32) (result i32)
+      (i32.add (local.get 0) (local.get 1)))
+  )
+  (core instance $i (instantiate $m))
+  (func (export "add") (param "a" u32) (param "b" u32) (result u32)
+    (canon lift (core func $i "add"))
+  )
+)
+
+(component instance $c1 $C)
+(component instance $c2 $C)
+
+(assert_return (invoke $c1 "add" (u32.const 1) (u32.const 2)) (u32.const 3))
+(assert_return (invoke $c2 "add" (u32.const 4) (u32.const 5)) (u32.const 9))
+
+(module definition $M
+  (func (export "f") (result i32) i32.const 1)
+)
+(module instance $m $M)
+(assert_return (invoke $m "f") (i32.const 1))
+
+(component
+  (type $point (record (field "x" s32) (field "y" s32)))
+  (type $shape (variant (case "circle" u32) (case "point")))
+  (import "describe" (func $describe (param "shape" $shape) (result string)))
+  (import "translate" (func $translate (param "p" $point) (result $point)))
+  (export "describe" (func $describe))
+  (export "translate" (func $translate))
+)
+
+(assert_return
+  (invoke "describe" (variant.const "circle" (u32.const 3)))
+  (str.const "circle of radius 3"))
+(assert_return
+  (invoke "translate" (record.const (field "x" (s32.const 1)) (field "y" (s32.const -1))))
+  (record.const (field "x" (s32.const 2)) (field "y" (s32.const 0))))
+(assert_trap (invoke $c1 "add" (u32.const 1)) "wrong number of arguments")
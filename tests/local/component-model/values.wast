@@ -0,0 +1,116 @@
+(component
+  (value $b bool true)
+  (value $f bool false)
+  (value $s8 s8 -128)
+  (value $u8 u8 255)
+  (value $s16 s16 -32768)
+  (value $u16 u16 65535)
+  (value $s32 s32 -2147483648)
+  (value $u32 u32 4294967295)
+  (value $s64 s64 -9223372036854775808)
+  (value $u64 u64 18446744073709551615)
+  (value $f32 float32 1.5)
+  (value $f64 float64 -0x1p-1022)
+  (value $nan float32 nan:0x200000)
+  (value $inf float64 inf)
+  (value $c1 char "x")
+  (value $c2 char "☃")
+  (value $s string "hello")
+  (value $e string "")
+  (export "b" (value $b))
+  (export "f" (value $f))
+  (export "s8" (value $s8))
+  (export "u8" (value $u8))
+  (export "s16" (value $s16))
+  (export "u16" (value $u16))
+  (export "s32" (value $s32))
+  (export "u32" (value $u32))
+  (export "s64" (value $s64))
+  (export "u64" (value $u64))
+  (export "f32" (value $f32))
+  (export "f64" (value $f64))
+  (export "nan" (value $nan))
+  (export "inf" (value $inf))
+  (export "c1" (value $c1))
+  (export "c2" (value $c2))
+  (export "s" (value $s))
+  (export "e" (value $e))
+)
+
+(component
+  (value (export "answer") u32 42)
+  (value $greeting (export "greeting") string "hi")
+)
+
+(assert_invalid
+  (component
+    (value (export "a") (export "b") u32 42)
+  )
+  "cannot be used more than once")
+
+(assert_invalid
+  (component
+    (value u32 42)
+  )
+  "value index 0 was not used")
+
+(component
+  (import "" (func $f (param "a" u32) (param "b" string) (result "c" u32)))
+  (value $a u32 1)
+  (value $b string "two")
+  (start $f (value $a) (value $b) (result (value $c)))
+  (export "c" (value $c))
+)
+
+(assert_invalid
+  (component
+    (import "" (func $f (param u32)))
+    (value $v string "not a u32")
+    (start $f (value $v))
+  )
+  "type mismatch for component start function argument 0")
+
+(assert_invalid
+  (component binary
+    "\00asm" "\0a\00\01\00"   ;; component header
+
+    "\0c\04"          ;; value section, 4 bytes large
+    "\01"             ;; 1 count
+    "\7f"             ;; bool
+    "\01\02"          ;; 1 byte: 2
+  )
+  "invalid boolean value")
+
+(assert_invalid
+  (component binary
+    "\00asm" "\0a\00\01\00"   ;; component header
+
+    "\0c\05"          ;; value section, 5 bytes large
+    "\01"             ;; 1 count
+    "\7d"             ;; u8
+    "\02\00\00"       ;; 2 bytes: 0, 0
+  )
+  "trailing data at the end of a value")
+
+(assert_invalid
+  (component binary
+    "\00asm" "\0a\00\01\00"   ;; component header
+
+    "\0c\06"          ;; value section, 6 bytes large
+    "\01"             ;; 1 count
+    "\74"             ;; char
+    "\03\80\b0\03"    ;; 3 bytes: 0xd800 (a surrogate)
+  )
+  "invalid Unicode scalar value")
+
+(assert_malformed
+  (component quote
+    "(value char \"ab\")"
+  )
+  "more than one character")
+
+(assert_malformed
+  (component quote
+    "(value u8 256)"
+  )
+  "constant out of range")
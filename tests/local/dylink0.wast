@@ -0,0 +1,27 @@
+(module
+  (@dylink.0
+    (mem-info (memory 16 2) (table 1 0))
+    (needed "libc.so" "libm.so")
+    (export-info "f" binding-weak visibility-hidden)
+    (export-info "g" 0x400)
+    (import-info "env" "h" binding-weak undefined)
+  )
+  (import "env" "h" (func))
+  (func (export "f"))
+  (func (export "g"))
+)
+
+(module
+  (@dylink.0
+    (mem-info)
+    (needed)
+  )
+)
+
+(module
+  (@dylink.0 (mem-info (table 3 1)))
+)
+
+(assert_malformed
+  (module quote "(@dylink.0 (export-info \"f\" not-a-flag))")
+  "expected a symbol flag")
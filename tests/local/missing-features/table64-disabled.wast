@@ -0,0 +1,3 @@
+(assert_invalid
+  (module (table i64 1 funcref))
+  "memory64 must be enabled for 64-bit tables")
@@ -0,0 +1,46 @@
+(module
+  (type $t (func))
+  (table $t64 i64 2 funcref)
+  (table $t32 1 funcref)
+  (table $inline i64 funcref (elem $f))
+  (func $f)
+
+  (elem (table $t64) (i64.const 0) func $f)
+  (elem $seg func $f)
+
+  (func
+    i64.const 0 call_indirect $t64 (type $t)
+    i64.const 0 table.get $t64 drop
+    i64.const 0 ref.null func table.set $t64
+    ref.null func i64.const 1 table.grow $t64 drop
+    table.size $t64 drop
+    i64.const 0 ref.null func i64.const 1 table.fill $t64
+    i64.const 0 i32.const 0 i32.const 1 table.init $t64 $seg
+
+    ;; copies between tables of the same index type use that type
+    i64.const 0 i64.const 0 i64.const 1 table.copy $t64 $t64
+    ;; copies between mixed index types use i32 for the length
+    i64.const 0 i32.const 0 i32.const 1 table.copy $t64 $t32
+    i32.const 0 i64.const 0 i32.const 1 table.copy $t32 $t64
+  )
+
+  (func (param i64)
+    local.get 0 return_call_indirect $t64 (type $t))
+)
+
+(assert_invalid
+  (module (table i64 1 funcref) (elem (i32.const 0) func))
+  "type mismatch")
+(assert_invalid
+  (module (table 1 funcref) (elem (i64.const 0) func))
+  "type mismatch")
+(assert_invalid
+  (module (table i64 1 funcref) (func i32.const 0 table.get 0 drop))
+  "type mismatch")
+(assert_invalid
+  (module (table i64 1 funcref) (func (result i32) table.size 0))
+  "type mismatch")
+(assert_invalid
+  (module (table i64 1 funcref) (type (func))
+    (func i32.const 0 call_indirect (type 0)))
+  "type mismatch")
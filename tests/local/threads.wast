@@ -0,0 +1,39 @@
+(module $Mem
+  (memory (export "shared") 1 1 shared)
+)
+
+(thread $T1 (shared (module $Mem))
+  (register "mem" $Mem)
+  (module
+    (memory (import "mem" "shared") 1 1 shared)
+    (func (export "run")
+      (i32.atomic.store (i32.const 0) (i32.const 1))
+      (drop (memory.atomic.notify (i32.const 4) (i32.const 1)))
+    )
+  )
+  (invoke "run")
+)
+
+(thread $T2 (shared (module $Mem))
+  (register "mem" $Mem)
+  (module
+    (memory (import "mem" "shared") 1 1 shared)
+    (func (export "run") (result i32)
+      (memory.atomic.wait32 (i32.const 4) (i32.const 0) (i64.const 100))
+    )
+  )
+  (assert_return (invoke "run") (either (i32.const 0) (i32.const 1) (i32.const 2)))
+)
+
+(wait $T1)
+(wait $T2)
+
+(register "mem" $Mem)
+(module $Check
+  (memory (import "mem" "shared") 1 1 shared)
+  (func (export "check") (result i32)
+    (i32.atomic.load (i32.const 0))
+  )
+)
+
+(assert_return (invoke $Check "check") (either (i32.const 0) (i32.const 1)))
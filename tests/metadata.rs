@@ -0,0 +1,131 @@
+//! Tests of the `wasm-tools metadata` subcommands.
+
+use common::{failure, success};
+use tempfile::TempDir;
+
+mod common;
+
+#[test]
+fn annotations() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    std::fs::write(
+        dir.join("component.wat"),
+        r#"(component (core module (@custom "license" "MIT")))"#,
+    )
+    .unwrap();
+
+    let args = [
+        "metadata",
+        "annotate",
+        "component.wat",
+        "--license",
+        "Apache-2.0",
+        "--revision",
+        "3a10b20",
+        "-o",
+        "annotated.wasm",
+    ];
+    success(dir, &args);
+    let (stdout, _) = success(dir, &["metadata", "annotations", "annotated.wasm"]);
+    assert_eq!(
+        stdout,
+        "/ (component)\n  license: Apache-2.0\n  revision: 3a10b20\n\
+         /module[0] (module)\n  license: MIT\n"
+    );
+
+    // Annotations which aren't mentioned are kept.
+    let args = [
+        "metadata",
+        "annotate",
+        "annotated.wasm",
+        "--remove",
+        "revision",
+        "--homepage",
+        "https://example.com",
+        "-o",
+        "updated.wasm",
+    ];
+    success(dir, &args);
+    let (stdout, _) = success(dir, &["metadata", "annotations", "updated.wasm", "--oci"]);
+    let oci: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(
+        oci,
+        serde_json::json!({
+            "org.opencontainers.image.licenses": "Apache-2.0",
+            "org.opencontainers.image.url": "https://example.com",
+        })
+    );
+    let (stdout, _) = success(dir, &["metadata", "annotations", "updated.wasm", "--json"]);
+    let entries: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(entries[1]["path"], "/module[0]");
+    assert_eq!(
+        entries[1]["annotations"],
+        serde_json::json!({ "license": "MIT" })
+    );
+
+    let args = [
+        "metadata",
+        "annotate",
+        "updated.wasm",
+        "--remove",
+        "license",
+        "--license",
+        "MIT",
+    ];
+    let (_, stderr) = failure(dir, &args);
+    assert!(stderr.contains("cannot both set and remove"), "{stderr}");
+    let args = [
+        "metadata",
+        "annotate",
+        "updated.wasm",
+        "--remove",
+        "licenses",
+    ];
+    let (_, stderr) = failure(dir, &args);
+    assert!(stderr.contains("unknown annotation `licenses`"), "{stderr}");
+}
+
+#[test]
+fn dump() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    std::fs::write(
+        dir.join("component.wat"),
+        r#"(component (core module $m (@custom "license" "MIT") (func)))"#,
+    )
+    .unwrap();
+    let args = [
+        "metadata",
+        "set",
+        "build-id",
+        "1234",
+        "component.wat",
+        "-o",
+        "component.wasm",
+    ];
+    success(dir, &args);
+
+    let (stdout, _) = success(dir, &["metadata", "dump", "component.wasm"]);
+    assert_eq!(
+        stdout,
+        "/ metadata:build-id: 0x3a..0x53 (25 bytes)\n\
+         /module[0] name: 0x22..0x2d (11 bytes)\n\
+         /module[0] license: 0x2d..0x3a (13 bytes)\n"
+    );
+
+    let (stdout, _) = success(dir, &["metadata", "dump", "component.wasm", "--json"]);
+    let items: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let wasm = std::fs::read(dir.join("component.wasm")).unwrap();
+    let range = &items[2]["data_range"];
+    let range = range["start"].as_u64().unwrap() as usize..range["end"].as_u64().unwrap() as usize;
+    assert_eq!(&wasm[range], b"MIT");
+    assert_eq!(
+        items[2]["value"],
+        serde_json::json!({ "kind": "annotation", "annotation": "license", "value": "MIT" })
+    );
+    assert_eq!(
+        items[0]["value"],
+        serde_json::json!({ "kind": "entry", "key": "build-id", "value": "1234" })
+    );
+}
@@ -0,0 +1,71 @@
+//! Tests of `wasm-tools mutate`.
+
+use common::{failure, success};
+use tempfile::TempDir;
+
+mod common;
+
+#[test]
+fn record_and_replay() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    std::fs::write(
+        dir.join("module.wat"),
+        "(module (func (export \"f\") (result i32) i32.const 1 i32.const 2 i32.add))",
+    )
+    .unwrap();
+    success(
+        dir,
+        &[
+            "mutate",
+            "module.wat",
+            "--seed",
+            "1",
+            "--record",
+            "log.txt",
+            "-o",
+            "a.wasm",
+        ],
+    );
+    success(
+        dir,
+        &[
+            "mutate", "a.wasm", "--seed", "2", "--record", "log.txt", "-o", "b.wasm",
+        ],
+    );
+    let log = std::fs::read_to_string(dir.join("log.txt")).unwrap();
+    assert_eq!(log.lines().count(), 2, "{log}");
+
+    success(
+        dir,
+        &[
+            "mutate",
+            "module.wat",
+            "--replay",
+            "log.txt",
+            "-o",
+            "c.wasm",
+        ],
+    );
+    assert_eq!(
+        std::fs::read(dir.join("b.wasm")).unwrap(),
+        std::fs::read(dir.join("c.wasm")).unwrap()
+    );
+}
+
+#[test]
+fn record_rejects_components() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    std::fs::write(
+        dir.join("component.wat"),
+        "(component (core module (func (export \"f\") (result i32) i32.const 1)))",
+    )
+    .unwrap();
+    let (_, stderr) = failure(dir, &["mutate", "component.wat", "--record", "log.txt"]);
+    assert!(
+        stderr.contains("`--record` only supports mutations of core modules"),
+        "{stderr}"
+    );
+    assert!(!dir.join("log.txt").exists());
+}
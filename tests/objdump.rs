@@ -0,0 +1,98 @@
+//! Tests of `wasm-tools objdump`.
+
+use common::{failure, success};
+use tempfile::TempDir;
+
+mod common;
+
+const MODULE: &str = r#"(module $m
+    (type (func (param i32) (result i32)))
+    (import "env" "f" (func $imp (param i32)))
+    (func $add (param $x i32) (result i32) local.get $x)
+    (table 1 10 funcref)
+    (memory 1)
+    (global $g (mut i32) (i32.const 0))
+    (export "add" (func $add))
+    (elem (i32.const 0) func $add)
+    (data (i32.const 0) "hello")
+)"#;
+
+#[test]
+fn details() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    std::fs::write(dir.join("module.wat"), MODULE).unwrap();
+    let (stdout, _) = success(dir, &["objdump", "--details", "module.wat"]);
+
+    // Each item of each section has its own byte range.
+    let items = stdout
+        .lines()
+        .filter(|line| line.trim_start().starts_with('['))
+        .map(|line| line.rsplit(" | ").next().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        items,
+        [
+            "func (i32) -> (i32)",
+            "func (i32) -> ()",
+            "env::f",
+            "func 1 (add): type 0",
+            "funcref 1..10",
+            "i32 1..",
+            "mut i32",
+            "add",
+            "active table 0, 1 funcrefs",
+            "func 1 (add)",
+            "active memory 0, 5 bytes",
+        ],
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("    [0]                                  |       0x54 -       0x5e |"),
+        "{stdout}"
+    );
+
+    // The name section is decoded.
+    for line in [
+        "module: m",
+        "func names: 0: imp, 1: add",
+        "local names of func 1 (add): 0: x",
+        "global names: 0: g",
+    ] {
+        assert!(stdout.contains(&format!("    {line}\n")), "{stdout}");
+    }
+
+    // Without details, only the sections are listed.
+    let (stdout, _) = success(dir, &["objdump", "module.wat"]);
+    assert!(!stdout.contains("[0]"), "{stdout}");
+    assert!(!stdout.contains("module: m"), "{stdout}");
+}
+
+#[test]
+fn hexdump() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    std::fs::write(dir.join("module.wat"), MODULE).unwrap();
+    let (stdout, _) = success(dir, &["objdump", "--hexdump", "data", "module.wat"]);
+    assert!(
+        stdout.contains("0x00000053: 01 00 41 00 0b 05 68 65 6c 6c 6f"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("|..A...hello|"), "{stdout}");
+
+    let (_, stderr) = failure(
+        dir,
+        &[
+            "objdump",
+            "--hexdump",
+            "data",
+            "--hexdump",
+            "nope",
+            "module.wat",
+        ],
+    );
+    assert!(
+        stderr.contains("no section named `nope` to hexdump"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,27 @@
+//! Tests of `wasm-tools print`.
+
+use common::{failure, success};
+use tempfile::TempDir;
+
+mod common;
+
+#[test]
+fn unknown_filters() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    std::fs::write(
+        dir.join("module.wat"),
+        "(module (func $f) (export \"f\" (func $f)))",
+    )
+    .unwrap();
+    let (stdout, _) = success(dir, &["print", "module.wat", "--func", "f"]);
+    assert!(stdout.contains("(func $f"), "{stdout}");
+
+    let (_, stderr) = failure(dir, &["print", "module.wat", "--func", "g"]);
+    assert!(stderr.contains("no function named `g` found"), "{stderr}");
+    let (_, stderr) = failure(dir, &["print", "module.wat", "--section", "datas"]);
+    assert!(
+        stderr.contains("no section named `datas` found"),
+        "{stderr}"
+    );
+}
@@ -328,6 +328,19 @@ impl TestState {
                 }
             }
 
+            // Definitions are tested the same as any other module.
+            WastDirective::ModuleDefinition(module) => {
+                self.test_wast_directive(test, WastDirective::Wat(QuoteWat::Wat(module)))?;
+            }
+
+            // Modules defined within threads are tested like any other, but
+            // the threads themselves are never run.
+            WastDirective::Thread(thread) => {
+                for directive in thread.directives {
+                    self.test_wast_directive(test, directive)?;
+                }
+            }
+
             // This test suite doesn't actually execute any wasm code, so ignore
             // all of these assertions.
             WastDirective::Register { .. }
@@ -336,7 +349,9 @@ impl TestState {
             | WastDirective::AssertReturn { .. }
             | WastDirective::AssertExhaustion { .. }
             | WastDirective::AssertUnlinkable { .. }
-            | WastDirective::AssertException { .. } => {}
+            | WastDirective::AssertException { .. }
+            | WastDirective::ModuleInstance { .. }
+            | WastDirective::Wait { .. } => {}
         }
         Ok(())
     }
@@ -415,36 +430,14 @@ impl TestState {
 
     fn wasmparser_validator_for(&self, test: &Path) -> Validator {
         let mut features = WasmFeatures {
-            threads: true,
-            reference_types: true,
-            simd: true,
-            relaxed_simd: true,
-            exceptions: true,
-            bulk_memory: true,
-            tail_call: true,
             component_model: false,
-            deterministic_only: false,
-            multi_value: true,
-            multi_memory: true,
-            memory64: true,
-            extended_const: true,
-            saturating_float_to_int: true,
-            sign_extension: true,
-            mutable_global: true,
+            component_model_async: false,
+            ..WasmFeatures::all()
         };
         for part in test.iter().filter_map(|t| t.to_str()) {
             match part {
                 "testsuite" => features = WasmFeatures::default(),
-                "missing-features" => {
-                    features = WasmFeatures::default();
-                    features.simd = false;
-                    features.reference_types = false;
-                    features.multi_value = false;
-                    features.sign_extension = false;
-                    features.saturating_float_to_int = false;
-                    features.mutable_global = false;
-                    features.bulk_memory = false;
-                }
+                "missing-features" => features = WasmFeatures::mvp(),
                 "threads" => {
                     features.threads = true;
                     features.bulk_memory = false;
@@ -457,7 +450,10 @@ impl TestState {
                     features.memory64 = true;
                     features.reference_types = false;
                 }
-                "component-model" => features.component_model = true,
+                "component-model" => {
+                    features.component_model = true;
+                    features.component_model_async = true;
+                }
                 "multi-memory" => features.multi_memory = true,
                 "extended-const" => features.extended_const = true,
                 _ => {}
@@ -0,0 +1,93 @@
+//! Tests of the SARIF diagnostics of `wasm-tools validate` and `lint`.
+
+use common::{failure, success, wasm_tools_stdin};
+use tempfile::TempDir;
+
+mod common;
+
+/// Parses a SARIF log, checking that it's from `wasm-tools` and lists `rules`,
+/// and returns its results.
+fn sarif_results(log: &str, rules: &[&str]) -> Vec<serde_json::Value> {
+    let log: serde_json::Value = serde_json::from_str(log).unwrap();
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "wasm-tools");
+    let ids: Vec<_> = run["tool"]["driver"]["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, rules);
+    run["results"].as_array().unwrap().clone()
+}
+
+#[test]
+fn validate_and_lint() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    std::fs::write(dir.join("syntax.wat"), "(module\n  (func i32.const))").unwrap();
+    std::fs::write(dir.join("ok.wat"), "(module)").unwrap();
+    std::fs::write(dir.join("invalid.wat"), "(module (func (result i32)))").unwrap();
+    success(dir, &["parse", "invalid.wat", "-o", "invalid.wasm"]);
+    let validate_rules = ["text", "feature", "invalid", "other"];
+
+    let args = ["validate", "--diagnostics", "sarif", "syntax.wat"];
+    let (stdout, _) = failure(dir, &args);
+    let results = sarif_results(&stdout, &validate_rules);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["ruleId"], "text");
+    assert_eq!(results[0]["level"], "error");
+    let location = &results[0]["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "syntax.wat");
+    assert_eq!(
+        location["region"],
+        serde_json::json!({ "startLine": 2, "startColumn": 18 })
+    );
+
+    let args = ["validate", "--diagnostics", "sarif", "invalid.wasm"];
+    let (stdout, _) = failure(dir, &args);
+    let results = sarif_results(&stdout, &validate_rules);
+    assert_eq!(results[0]["ruleId"], "invalid");
+    let message = results[0]["message"]["text"].as_str().unwrap();
+    assert!(
+        message.starts_with("func 0 failed to validate: "),
+        "{message}"
+    );
+    let location = &results[0]["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "invalid.wasm");
+    assert_eq!(
+        location["region"],
+        serde_json::json!({ "byteOffset": 24, "byteLength": 1 })
+    );
+
+    // Offsets aren't reported for text inputs which failed to validate.
+    let args = ["validate", "--diagnostics", "sarif", "invalid.wat"];
+    let (stdout, _) = failure(dir, &args);
+    let results = sarif_results(&stdout, &validate_rules);
+    let location = &results[0]["locations"][0]["physicalLocation"];
+    assert!(location.get("region").is_none(), "{location}");
+
+    let args = ["validate", "--diagnostics", "sarif", "ok.wat"];
+    let (stdout, _) = success(dir, &args);
+    assert!(sarif_results(&stdout, &validate_rules).is_empty());
+
+    // Lint warnings are reported as such, without locations for stdin.
+    let wasm = std::fs::read(dir.join("invalid.wasm")).unwrap();
+    let output = wasm_tools_stdin(dir, &["lint", "--diagnostics", "sarif"], &wasm);
+    assert!(!output.status.success());
+    let results = sarif_results(
+        std::str::from_utf8(&output.stdout).unwrap(),
+        &[
+            "unused-import",
+            "dead-function",
+            "zero-data",
+            "missing-producers",
+            "mutable-global-export",
+        ],
+    );
+    let rules: Vec<_> = results.iter().map(|r| &r["ruleId"]).collect();
+    assert_eq!(rules, ["dead-function", "missing-producers"]);
+    assert!(results.iter().all(|r| r["level"] == "warning"));
+    assert!(results.iter().all(|r| r.get("locations").is_none()));
+}
@@ -0,0 +1,81 @@
+//! Tests of `wasm-tools shrink`.
+
+use common::{failure, success};
+use tempfile::TempDir;
+
+mod common;
+
+#[cfg(unix)]
+#[test]
+fn persistent_predicate() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    std::fs::write(
+        dir.join("module.wat"),
+        r#"(module
+            (func (export "keep") (result i32) i32.const 1)
+            (func (export "other") (param i32) (result i32) local.get 0)
+            (memory (export "memory") 1)
+        )"#,
+    )
+    .unwrap();
+
+    // A predicate which reads each length-prefixed candidate with `dd`, one
+    // byte at a time so as to not read past it, and finds it interesting
+    // while the `keep` export remains. Every spawn is logged.
+    let write_predicate = |name: &str, reply: &str| {
+        let path = dir.join(name);
+        std::fs::write(
+            &path,
+            format!(
+                "#!/bin/sh\n\
+                 echo spawned >> spawns.txt\n\
+                 while len=$(dd bs=1 count=4 status=none | od -An -tu4) && [ -n \"$len\" ]; do\n\
+                 \x20   dd bs=1 count=$((len)) status=none > candidate.wasm\n\
+                 \x20   {reply}\n\
+                 done\n"
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    };
+    write_predicate(
+        "keep.sh",
+        "if grep -q keep candidate.wasm; then echo 1; else echo 0; fi",
+    );
+    write_predicate("invalid.sh", "echo maybe");
+
+    let (stdout, _) = success(
+        dir,
+        &[
+            "shrink",
+            "--persistent",
+            "--attempts",
+            "200",
+            "./keep.sh",
+            "module.wat",
+            "-o",
+            "shrunken.wasm",
+        ],
+    );
+    assert!(stdout.contains("% smaller"), "{stdout}");
+    let shrunken = std::fs::read(dir.join("shrunken.wasm")).unwrap();
+    let original = wat::parse_file(dir.join("module.wat")).unwrap();
+    assert!(shrunken.len() < original.len());
+    wasmparser::validate(&shrunken).unwrap();
+    let text = wasmprinter::print_bytes(&shrunken).unwrap();
+    assert!(text.contains("(export \"keep\""), "{text}");
+    let spawns = std::fs::read_to_string(dir.join("spawns.txt")).unwrap();
+    assert_eq!(spawns.lines().count(), 1);
+
+    let (_, stderr) = failure(
+        dir,
+        &["shrink", "--persistent", "./invalid.sh", "module.wat"],
+    );
+    assert!(
+        stderr.contains("sent an invalid reply: \"maybe\""),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,257 @@
+//! Tests of `wasm-tools sign` and `wasm-tools verify`.
+
+use common::{failure, success};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+mod common;
+
+/// Creates a directory holding a module and two Ed25519 key files.
+fn signing_dir() -> (TempDir, PathBuf) {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().to_path_buf();
+    std::fs::write(
+        path.join("module.wat"),
+        "(module (func (export \"f\")) (@custom \"note\" \"hi\"))",
+    )
+    .unwrap();
+    std::fs::write(path.join("alice.key"), format!("{}\n", "01".repeat(32))).unwrap();
+    std::fs::write(path.join("bob.key"), "02".repeat(32)).unwrap();
+    for name in ["alice", "bob"] {
+        let key = format!("{name}.key");
+        let (public, _) = success(&path, &["sign", "--key", &key, "--print-public-key"]);
+        std::fs::write(path.join(format!("{name}.pub")), public).unwrap();
+    }
+    (dir, path)
+}
+
+fn public_key(dir: &Path, name: &str) -> String {
+    std::fs::read_to_string(dir.join(format!("{name}.pub")))
+        .unwrap()
+        .trim()
+        .to_string()
+}
+
+#[test]
+fn sign_and_verify() {
+    let (_tmp, dir) = signing_dir();
+    let alice = public_key(&dir, "alice");
+
+    let (_, stderr) = success(
+        &dir,
+        &[
+            "sign",
+            "--key",
+            "alice.key",
+            "module.wat",
+            "-o",
+            "signed.wasm",
+        ],
+    );
+    let size = wat::parse_file(dir.join("module.wat")).unwrap().len();
+    assert!(
+        stderr.contains(&format!("signed by ed25519 key `{alice}`")),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains(&format!("covers bytes 0x0..{size:#x}")),
+        "{stderr}"
+    );
+
+    let (stdout, _) = success(
+        &dir,
+        &["verify", "signed.wasm", "--public-key", "alice.pub"],
+    );
+    assert!(
+        stdout.contains(&format!("signed by ed25519 key `{alice}`: valid")),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains(&format!("covers bytes 0x0..{size:#x}")),
+        "{stdout}"
+    );
+
+    // A second signature can be added, and either key verifies.
+    success(
+        &dir,
+        &[
+            "sign",
+            "--key",
+            "bob.key",
+            "signed.wasm",
+            "-o",
+            "signed.wasm",
+        ],
+    );
+    success(&dir, &["verify", "signed.wasm", "--public-key", "bob.pub"]);
+    let (stdout, _) = success(
+        &dir,
+        &[
+            "verify",
+            "signed.wasm",
+            "--public-key",
+            "alice.pub",
+            "--json",
+        ],
+    );
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["digest_matches"], true);
+    assert_eq!(json["signatures"][0]["status"], "valid");
+    assert_eq!(json["signatures"][1]["status"], "unknown-key");
+    assert_eq!(json["digest"]["covered"][0]["start"], 0);
+}
+
+#[test]
+fn verify_failures() {
+    let (_tmp, dir) = signing_dir();
+    success(
+        &dir,
+        &[
+            "sign",
+            "--key",
+            "alice.key",
+            "module.wat",
+            "-o",
+            "signed.wasm",
+        ],
+    );
+
+    // Untrusted signer.
+    let (stdout, stderr) = failure(&dir, &["verify", "signed.wasm", "--public-key", "bob.pub"]);
+    assert!(stdout.contains("unknown key"), "{stdout}");
+    assert!(stderr.contains("verification failed"), "{stderr}");
+
+    // Modified after signing.
+    success(
+        &dir,
+        &[
+            "metadata",
+            "set",
+            "build-id",
+            "1",
+            "signed.wasm",
+            "-o",
+            "modified.wasm",
+        ],
+    );
+    let (stdout, _) = failure(
+        &dir,
+        &["verify", "modified.wasm", "--public-key", "alice.pub"],
+    );
+    assert!(stdout.contains("does not match"), "{stdout}");
+
+    // Never signed.
+    let (_, stderr) = failure(&dir, &["verify", "module.wat", "--public-key", "alice.pub"]);
+    assert!(stderr.contains("no `signature` section"), "{stderr}");
+
+    // Malformed key files.
+    std::fs::write(dir.join("bad.key"), "not hex").unwrap();
+    let (_, stderr) = failure(&dir, &["sign", "--key", "bad.key", "module.wat"]);
+    assert!(stderr.contains("invalid key file `bad.key`"), "{stderr}");
+    let (_, stderr) = failure(&dir, &["verify", "signed.wasm", "--public-key", "bad.key"]);
+    assert!(
+        stderr.contains("invalid public key file `bad.key`"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn digest_only() {
+    let (_tmp, dir) = signing_dir();
+    let (_, stderr) = success(
+        &dir,
+        &["sign", "--digest-only", "module.wat", "-o", "digest.wasm"],
+    );
+    assert!(stderr.contains("without signing"), "{stderr}");
+    success(&dir, &["verify", "--digest-only", "digest.wasm"]);
+    // Without `--digest-only` a signature is required.
+    failure(
+        &dir,
+        &["verify", "digest.wasm", "--public-key", "alice.pub"],
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn external_signer() {
+    let (_tmp, dir) = signing_dir();
+    // A "signer" which signs with the digest itself.
+    let (_, stderr) = success(
+        &dir,
+        &[
+            "sign",
+            "--signer",
+            "sh",
+            "--signer-arg",
+            "-c",
+            "--signer-arg",
+            "cat",
+            "--key-id",
+            "ci@example.com",
+            "--algorithm",
+            "identity",
+            "module.wat",
+            "-o",
+            "signed.wasm",
+        ],
+    );
+    assert!(
+        stderr.contains("signed by identity key `ci@example.com`"),
+        "{stderr}"
+    );
+
+    let (stdout, _) = failure(
+        &dir,
+        &[
+            "verify",
+            "signed.wasm",
+            "--public-key",
+            "alice.pub",
+            "--json",
+        ],
+    );
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["signatures"][0]["key_id"], "ci@example.com");
+    assert_eq!(json["signatures"][0]["status"], "unknown-key");
+
+    // The signature section holds the digest as the signature.
+    let signed = std::fs::read(dir.join("signed.wasm")).unwrap();
+    let section = last_custom_section(&signed);
+    let digest = &json["digest"]["bytes"];
+    assert!(section.ends_with(&hex_decode(digest.as_str().unwrap())));
+
+    let (_, stderr) = failure(
+        &dir,
+        &[
+            "sign",
+            "--signer",
+            "false",
+            "--key-id",
+            "x",
+            "--algorithm",
+            "x",
+            "module.wat",
+        ],
+    );
+    assert!(stderr.contains("signer `false` failed"), "{stderr}");
+}
+
+/// Returns the data of the last custom section of `wasm`.
+#[cfg(unix)]
+fn last_custom_section(wasm: &[u8]) -> Vec<u8> {
+    let mut last = Vec::new();
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        if let wasmparser::Payload::CustomSection(s) = payload.unwrap() {
+            last = s.data().to_vec();
+        }
+    }
+    last
+}
+
+#[cfg(unix)]
+fn hex_decode(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
@@ -0,0 +1,89 @@
+//! Tests of `wasm-tools smith`.
+
+use common::{failure, success};
+use tempfile::TempDir;
+
+mod common;
+
+#[test]
+fn config_file() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    let mut state = 3u32;
+    let seed: Vec<u8> = (0..4096)
+        .map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 16) as u8
+        })
+        .collect();
+    std::fs::write(dir.join("seed"), seed).unwrap();
+    std::fs::write(
+        dir.join("smith.toml"),
+        r#"
+            min-funcs = 3
+            max-funcs = 3
+            max-imports = 0
+            export-everything = true
+            simd = false
+
+            [instruction-kind-weights]
+            numeric = 10
+        "#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("smith.json"),
+        r#"{ "min-funcs": 2, "max-funcs": 2, "max-imports": 0 }"#,
+    )
+    .unwrap();
+    std::fs::write(dir.join("bad.toml"), "max-funcs = \"many\"").unwrap();
+    std::fs::write(
+        dir.join("bad-kind.toml"),
+        "[instruction-kind-weights]\nbogus = 1",
+    )
+    .unwrap();
+
+    let funcs = |args: &[&str]| {
+        let mut all = vec!["smith", "seed", "-o", "out.wasm"];
+        all.extend_from_slice(args);
+        success(dir, &all);
+        let wasm = std::fs::read(dir.join("out.wasm")).unwrap();
+        let mut funcs = 0;
+        for payload in wasmparser::Parser::new(0).parse_all(&wasm) {
+            if let wasmparser::Payload::FunctionSection(s) = payload.unwrap() {
+                funcs += s.get_count();
+            }
+        }
+        funcs
+    };
+
+    assert_eq!(funcs(&["-c", "smith.toml"]), 3);
+    success(dir, &["validate", "out.wasm", "-f", "all,-simd"]);
+    let wasm = std::fs::read(dir.join("out.wasm")).unwrap();
+    let mut exported_funcs = 0;
+    for payload in wasmparser::Parser::new(0).parse_all(&wasm) {
+        if let wasmparser::Payload::ExportSection(s) = payload.unwrap() {
+            for export in s {
+                if export.unwrap().kind == wasmparser::ExternalKind::Func {
+                    exported_funcs += 1;
+                }
+            }
+        }
+    }
+    assert_eq!(exported_funcs, 3);
+
+    // Flags take precedence over the file.
+    assert_eq!(
+        funcs(&["-c", "smith.toml", "--min-funcs=1", "--max-funcs=1"]),
+        1
+    );
+    assert_eq!(funcs(&["-c", "smith.json"]), 2);
+
+    let (_, stderr) = failure(dir, &["smith", "seed", "-c", "bad.toml"]);
+    assert!(stderr.contains("failed to decode toml config"), "{stderr}");
+    let (_, stderr) = failure(dir, &["smith", "seed", "-c", "bad-kind.toml"]);
+    assert!(
+        stderr.contains("unknown instruction kind: bogus"),
+        "{stderr}"
+    );
+}
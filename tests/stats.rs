@@ -0,0 +1,45 @@
+//! Tests of `wasm-tools stats`.
+
+use common::success;
+use tempfile::TempDir;
+
+mod common;
+
+#[test]
+fn instruction_names() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    std::fs::write(
+        dir.join("component.wat"),
+        r#"(component
+            (core module
+                (memory 1)
+                (func (param v128) (result i32)
+                    local.get 0
+                    i32x4.extract_lane 1
+                    i32.const 0
+                    i32.load offset=4
+                    i32.add)))"#,
+    )
+    .unwrap();
+    let (stdout, _) = success(dir, &["stats", "component.wat", "--json"]);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let mut names = json["instructions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| (i["name"].as_str().unwrap(), i["count"].as_u64().unwrap()))
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            ("end", 1),
+            ("i32.add", 1),
+            ("i32.const", 1),
+            ("i32.load", 1),
+            ("i32x4.extract_lane", 1),
+            ("local.get", 1),
+        ]
+    );
+}
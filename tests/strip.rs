@@ -0,0 +1,78 @@
+//! Tests of `wasm-tools strip`.
+
+use common::{failure, success};
+use tempfile::TempDir;
+
+mod common;
+
+/// Returns the sorted names of the custom sections of `wasm`, including those
+/// of nested modules and components.
+fn custom_sections(wasm: &[u8]) -> Vec<String> {
+    let mut names = Vec::new();
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        if let wasmparser::Payload::CustomSection(c) = payload.unwrap() {
+            names.push(c.name().to_string());
+        }
+    }
+    names.sort();
+    names
+}
+
+#[test]
+fn custom_section_selection() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    let module = r#"(module $m
+        (@custom ".debug_info" "a")
+        (@custom ".debug_line" "b")
+        (@custom "producers" "\00")
+        (@custom "foo" "c")
+        (@custom "foobar" "d")
+    )"#;
+    std::fs::write(dir.join("module.wat"), module).unwrap();
+    std::fs::write(
+        dir.join("component.wat"),
+        format!("(component (core {})", &module[1..]),
+    )
+    .unwrap();
+
+    let strip = |input: &str, args: &[&str]| {
+        let mut all = vec!["strip", input, "-o", "out.wasm"];
+        all.extend_from_slice(args);
+        success(dir, &all);
+        custom_sections(&std::fs::read(dir.join("out.wasm")).unwrap())
+    };
+
+    assert_eq!(strip("module.wat", &[]), ["name"]);
+    assert!(strip("module.wat", &["--all"]).is_empty());
+    assert_eq!(
+        strip("module.wat", &["--debug-only"]),
+        ["foo", "foobar", "name", "producers"]
+    );
+    assert_eq!(
+        strip("module.wat", &["--names-only"]),
+        [".debug_info", ".debug_line", "foo", "foobar", "producers"]
+    );
+    assert_eq!(
+        strip("module.wat", &["--delete", "^foo", "--delete", "line"]),
+        [".debug_info", "name", "producers"]
+    );
+    assert_eq!(
+        strip("module.wat", &["--delete", "foo", "--keep", "bar$"]),
+        [".debug_info", ".debug_line", "foobar", "name", "producers"]
+    );
+    assert_eq!(
+        strip("module.wat", &["--debug-only", "--keep", "info"]),
+        [".debug_info", "foo", "foobar", "name", "producers"]
+    );
+
+    // Nested modules are stripped too.
+    assert_eq!(strip("component.wat", &["--all"]), Vec::<String>::new());
+    assert_eq!(
+        strip("component.wat", &["--delete", "^foo$", "--debug-only"]),
+        ["foobar", "name", "producers"]
+    );
+
+    let (_, stderr) = failure(dir, &["strip", "module.wat", "--all", "--debug-only"]);
+    assert!(stderr.contains("cannot be used with"), "{stderr}");
+}
@@ -0,0 +1,65 @@
+//! Tests of `wasm-tools validate`.
+
+use common::{failure, success, wasm_tools};
+use tempfile::TempDir;
+
+mod common;
+
+#[test]
+fn json_and_presets() {
+    let dir = TempDir::new().unwrap();
+    let dir = dir.path();
+    std::fs::write(
+        dir.join("simd.wat"),
+        "(module (func (result v128) v128.const i64x2 0 0))",
+    )
+    .unwrap();
+    std::fs::write(dir.join("component.wat"), "(component)").unwrap();
+    std::fs::write(dir.join("bad.wat"), "(module (func (result i32)))").unwrap();
+
+    let json = |args: &[&str]| {
+        let output = wasm_tools(dir, args);
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        (output.status.success(), report)
+    };
+
+    let (ok, report) = json(&["validate", "simd.wat", "--output", "json"]);
+    assert!(ok);
+    assert_eq!(report, serde_json::json!({ "valid": true }));
+
+    let (ok, report) = json(&["validate", "simd.wat", "--output", "json", "-f", "mvp"]);
+    assert!(!ok);
+    assert_eq!(report["valid"], false);
+    assert_eq!(report["error"]["kind"], "feature");
+    assert_eq!(
+        report["error"]["feature_hints"],
+        serde_json::json!(["simd"])
+    );
+    assert!(report["error"]["offset"].is_u64(), "{report}");
+
+    let (ok, report) = json(&["validate", "bad.wat", "--output", "json"]);
+    assert!(!ok);
+    assert_eq!(report["error"]["kind"], "invalid");
+    assert_eq!(report["error"]["func"], 0);
+    assert!(report["error"].get("feature_hints").is_none(), "{report}");
+
+    let (ok, report) = json(&["validate", "missing.wat", "--output", "json"]);
+    assert!(!ok);
+    assert_eq!(report["error"]["kind"], "other");
+
+    // Presets can be combined with individual features.
+    success(dir, &["validate", "simd.wat", "--features", "mvp,simd"]);
+    failure(dir, &["validate", "simd.wat", "--features", "all,-simd"]);
+    success(
+        dir,
+        &["validate", "simd.wat", "--features", "all-proposals"],
+    );
+    let (_, stderr) = failure(dir, &["validate", "component.wat"]);
+    assert!(stderr.contains("component model"), "{stderr}");
+    success(
+        dir,
+        &["validate", "component.wat", "--features", "wasi-preview2"],
+    );
+    let (_, stderr) = failure(dir, &["validate", "simd.wat", "--features", "nope"]);
+    assert!(stderr.contains("nope"), "{stderr}");
+}